@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE users ADD COLUMN first_seen INTEGER;
+ALTER TABLE users ADD COLUMN violation_count INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- Add up migration script here
+ALTER TABLE reports ADD COLUMN voice_capture BLOB;
@@ -0,0 +1,36 @@
+//! Generates an Argon2 hash that can be used in place of a plaintext password in the central server configuration
+//! (`admin_key`, `game_server_password`) or as the admin password of a user.
+//! Usage: `globed-hash-password [password]`, if the password is not passed as an argument, it is read from stdin.
+//! `globed-hash-password --totp-secret` instead generates a random secret for two-factor authentication.
+
+use std::io::BufRead;
+
+use globed_shared::{generate_totp_secret, hash_password};
+
+fn main() {
+    let arg = std::env::args().nth(1);
+
+    if arg.as_deref() == Some("--totp-secret") {
+        println!("{}", generate_totp_secret());
+        return;
+    }
+
+    let password = arg.unwrap_or_else(|| {
+        eprint!("Password: ");
+
+        let mut line = String::new();
+        if let Err(e) = std::io::stdin().lock().read_line(&mut line) {
+            eprintln!("failed to read the password: {e}");
+            std::process::exit(1);
+        }
+
+        line.trim_end_matches(['\r', '\n']).to_owned()
+    });
+
+    if password.is_empty() {
+        eprintln!("the password must not be empty");
+        std::process::exit(1);
+    }
+
+    println!("{}", hash_password(&password));
+}
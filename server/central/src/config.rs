@@ -5,8 +5,11 @@ use std::{
 
 use globed_shared::{
     anyhow::{self, anyhow},
+    bytes32_from_hex, cosmetic_hash_from_hex,
     esp::{self, Decodable, Encodable},
-    generate_alphanum_string, Decodable, Encodable, ServerRole, ADMIN_KEY_LENGTH, DEFAULT_GAME_SERVER_PORT,
+    generate_alphanum_string, is_password_hash, ChatFilterPolicy, ChatLinkPolicy, ChatWordFilter, Decodable, DuplicateLoginPolicy, Encodable,
+    NamePolicy, PlatformFeaturePolicy, PlayerStatePolicy, RoomQuotaPolicy, Secret, ServerRole, TrustTier, ADMIN_KEY_LENGTH, DEFAULT_AFK_TIMEOUT,
+    DEFAULT_GAME_SERVER_PORT,
 };
 use json_comments::StripComments;
 use serde::{Deserialize, Serialize};
@@ -60,6 +63,14 @@ const fn default_status_print_interval() -> u64 {
     7200 // 2 hours
 }
 
+const fn default_player_history_interval() -> u64 {
+    60 // 1 minute
+}
+
+const fn default_player_history_size() -> u32 {
+    1440 // 24 hours with the default interval
+}
+
 const fn default_userlist_mode() -> UserlistMode {
     UserlistMode::None
 }
@@ -101,10 +112,18 @@ fn default_roles() -> Vec<ServerRole> {
     ]
 }
 
-fn default_secret_key() -> String {
+fn default_secret() -> Secret {
+    Secret::default()
+}
+
+fn default_secret_key() -> Secret {
     let rand_string = generate_alphanum_string(32);
 
-    format!("Insecure-{rand_string}")
+    Secret::new(format!("Insecure-{rand_string}"))
+}
+
+const fn default_previous_secret_keys() -> Vec<Secret> {
+    Vec::new()
 }
 
 const fn default_challenge_expiry() -> u32 {
@@ -115,6 +134,74 @@ const fn default_token_expiry() -> u64 {
     60 * 60 * 24
 }
 
+const fn default_trust_account_age() -> u32 {
+    7
+}
+
+const fn default_min_trust() -> TrustTier {
+    TrustTier::Flagged
+}
+
+fn default_chat_link_policy() -> ChatLinkPolicy {
+    ChatLinkPolicy::default()
+}
+
+fn default_duplicate_login_policy() -> DuplicateLoginPolicy {
+    DuplicateLoginPolicy::default()
+}
+
+fn default_chat_filter_policy() -> ChatFilterPolicy {
+    ChatFilterPolicy::default()
+}
+
+fn default_chat_word_filter() -> ChatWordFilter {
+    ChatWordFilter::default()
+}
+
+fn default_name_policy() -> NamePolicy {
+    NamePolicy::default()
+}
+
+const fn default_blocked_levels() -> Vec<i64> {
+    Vec::new()
+}
+
+const fn default_banned_ips() -> Vec<String> {
+    Vec::new()
+}
+
+const fn default_game_server_credentials() -> Vec<GameServerCredential> {
+    Vec::new()
+}
+
+const fn default_approved_cosmetics() -> Vec<String> {
+    Vec::new()
+}
+
+const fn default_afk_timeout() -> u32 {
+    DEFAULT_AFK_TIMEOUT
+}
+
+const fn default_afk_room_grace() -> u32 {
+    0
+}
+
+fn default_platform_features() -> PlatformFeaturePolicy {
+    PlatformFeaturePolicy::default()
+}
+
+fn default_player_state_policy() -> PlayerStatePolicy {
+    PlayerStatePolicy::default()
+}
+
+fn default_room_quota() -> RoomQuotaPolicy {
+    RoomQuotaPolicy::default()
+}
+
+const fn default_voice_capture_seconds() -> u32 {
+    0
+}
+
 /* end stinky serde defaults */
 
 #[derive(PartialEq, Eq, Debug, Default, Clone, Serialize, Deserialize)]
@@ -128,6 +215,35 @@ pub enum UserlistMode {
     None, // same as blacklist
 }
 
+/// What a game server is allowed to do with its credential.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum GameServerScope {
+    /// getting the boot data and role definitions
+    #[serde(rename = "boot_data")]
+    BootData,
+    /// getting users, reports, transfer tickets and sessions
+    #[serde(rename = "user_read")]
+    UserRead,
+    /// updating users, creating reports and transfer tickets, changing the state of reports, and reporting sessions
+    #[serde(rename = "user_write")]
+    UserWrite,
+    /// getting the admin webhook URL along with the boot data
+    #[serde(rename = "webhook")]
+    Webhook,
+}
+
+impl GameServerScope {
+    pub const ALL: [Self; 4] = [Self::BootData, Self::UserRead, Self::UserWrite, Self::Webhook];
+}
+
+/// A password for one or more game servers, limited to the given scopes. Removing it from the config revokes it.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GameServerCredential {
+    pub name: String,
+    pub password: Secret,
+    pub scopes: Vec<GameServerScope>,
+}
+
 #[derive(Serialize, Deserialize, Encodable, Decodable, Default, Clone)]
 pub struct GameServerEntry {
     pub id: String,
@@ -147,6 +263,10 @@ pub struct ServerConfig {
     pub maintenance: bool,
     #[serde(default = "default_status_print_interval")]
     pub status_print_interval: u64,
+    #[serde(default = "default_player_history_interval")]
+    pub player_history_interval: u64,
+    #[serde(default = "default_player_history_size")]
+    pub player_history_size: u32,
 
     // special users and "special" users
     #[serde(default = "default_userlist_mode")]
@@ -156,23 +276,69 @@ pub struct ServerConfig {
     #[serde(default = "default_tps")]
     pub tps: u32,
 
-    #[serde(default = "default_string")]
-    pub admin_webhook_url: String,
+    #[serde(default = "default_secret")]
+    pub admin_webhook_url: Secret,
 
     // chat limits
     #[serde(default = "default_chat_burst_limit")]
     pub chat_burst_limit: u32,
     #[serde(default = "default_chat_burst_interval")]
     pub chat_burst_interval: u32,
+    #[serde(default = "default_chat_filter_policy")]
+    pub chat_filter_policy: ChatFilterPolicy,
+    #[serde(default = "default_chat_word_filter")]
+    pub chat_word_filter: ChatWordFilter,
 
     // roles
     #[serde(default = "default_roles")]
     pub roles: Vec<ServerRole>,
 
+    // trust tiers
+    #[serde(default = "default_trust_account_age")]
+    pub trust_account_age: u32,
+    #[serde(default = "default_min_trust")]
+    pub min_trust_create_room: TrustTier,
+    #[serde(default = "default_min_trust")]
+    pub min_trust_invite: TrustTier,
+    #[serde(default = "default_min_trust")]
+    pub min_trust_voice: TrustTier,
+    #[serde(default = "default_chat_link_policy")]
+    pub chat_link_policy: ChatLinkPolicy,
+
+    // moderation
+    #[serde(default = "default_blocked_levels")]
+    pub blocked_levels: Vec<i64>,
+    #[serde(default = "default_banned_ips")]
+    pub banned_ips: Vec<String>,
+    #[serde(default = "default_name_policy")]
+    pub name_policy: NamePolicy,
+    #[serde(default = "default_approved_cosmetics")]
+    pub approved_cosmetics: Vec<String>,
+
+    // sessions
+    #[serde(default = "default_duplicate_login_policy")]
+    pub duplicate_login_policy: DuplicateLoginPolicy,
+    #[serde(default = "default_afk_timeout")]
+    pub afk_timeout: u32,
+    #[serde(default = "default_afk_room_grace")]
+    pub afk_room_grace: u32,
+    #[serde(default = "default_platform_features")]
+    pub platform_features: PlatformFeaturePolicy,
+    #[serde(default = "default_player_state_policy")]
+    pub player_state_policy: PlayerStatePolicy,
+    #[serde(default = "default_room_quota")]
+    pub room_quota: RoomQuotaPolicy,
+    #[serde(default = "default_voice_capture_seconds")]
+    pub voice_capture_seconds: u32,
+    #[serde(default = "default_string")]
+    pub voice_capture_key: String,
+
     // security
     #[serde(default = "default_admin_key")]
     pub admin_key: String,
     #[serde(default = "default_false")]
+    pub require_totp: bool,
+    #[serde(default = "default_false")]
     pub use_gd_api: bool,
     #[serde(default = "default_gd_api_account")]
     pub gd_api_account: i32,
@@ -185,17 +351,23 @@ pub struct ServerConfig {
     #[serde(default = "default_refresh_interval")]
     pub refresh_interval: u64,
     #[serde(default = "default_secret_key")]
-    pub secret_key: String,
+    pub secret_key: Secret,
     #[serde(default = "default_secret_key")]
-    pub secret_key2: String,
+    pub secret_key2: Secret,
+    #[serde(default = "default_previous_secret_keys")]
+    pub previous_secret_keys2: Vec<Secret>,
     #[serde(default = "default_secret_key")]
-    pub game_server_password: String,
+    pub game_server_password: Secret,
+    #[serde(default = "default_game_server_credentials")]
+    pub game_server_credentials: Vec<GameServerCredential>,
     #[serde(default = "default_false")]
     pub cloudflare_protection: bool,
     #[serde(default = "default_challenge_expiry")]
     pub challenge_expiry: u32,
     #[serde(default = "default_token_expiry")]
     pub token_expiry: u64,
+    #[serde(default = "default_string")]
+    pub geoip_database: String,
 }
 
 impl ServerConfig {
@@ -221,10 +393,18 @@ impl ServerConfig {
         let conf = Self::load(source)?;
 
         // Do validation
-        if conf.admin_key.len() > ADMIN_KEY_LENGTH {
+        if !is_password_hash(&conf.admin_key) && conf.admin_key.len() > ADMIN_KEY_LENGTH {
             return Err(anyhow!("Invalid admin key size, must be {ADMIN_KEY_LENGTH} characters or less"));
         }
 
+        if let Some(hash) = conf.approved_cosmetics.iter().find(|hash| cosmetic_hash_from_hex(hash).is_none()) {
+            return Err(anyhow!("Invalid approved cosmetic hash '{hash}', must be 64 hex characters"));
+        }
+
+        if !conf.voice_capture_key.is_empty() && bytes32_from_hex(&conf.voice_capture_key).is_none() {
+            return Err(anyhow!("Invalid voice capture key, must be 64 hex characters"));
+        }
+
         self.clone_from(&conf);
         Ok(())
     }
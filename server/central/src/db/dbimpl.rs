@@ -1,6 +1,6 @@
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use globed_shared::UserEntry;
+use globed_shared::{PlayerReport, ReportState, ReportStateChange, UserEntry, UserPreferences};
 use rocket_db_pools::sqlx::{query_as, Result};
 use serde::Serialize;
 use sqlx::{prelude::*, query, sqlite::SqliteRow};
@@ -23,8 +23,22 @@ impl<'r> FromRow<'r, SqliteRow> for UserEntryWrapper {
         let is_muted = row.try_get("is_muted")?;
         let is_whitelisted = row.try_get("is_whitelisted")?;
         let admin_password = row.try_get("admin_password")?;
+        let totp_secret = row.try_get("totp_secret")?;
         let violation_reason = row.try_get("violation_reason")?;
         let violation_expiry = row.try_get("violation_expiry")?;
+        let first_seen = row.try_get("first_seen")?;
+        let violation_count = row.try_get("violation_count")?;
+
+        // stored as json, as there can be any amount of them
+        let timed_roles: Option<String> = row.try_get("timed_roles")?;
+        let timed_roles = timed_roles
+            .map_or(Ok(Vec::new()), |s| serde_json::from_str(&s))
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+        let preferences: Option<String> = row.try_get("preferences")?;
+        let preferences = preferences
+            .map_or(Ok(UserPreferences::default()), |s| serde_json::from_str(&s))
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
 
         Ok(UserEntryWrapper(UserEntry {
             account_id,
@@ -35,8 +49,32 @@ impl<'r> FromRow<'r, SqliteRow> for UserEntryWrapper {
             is_muted,
             is_whitelisted,
             admin_password,
+            totp_secret,
             violation_reason,
             violation_expiry,
+            first_seen,
+            violation_count,
+            timed_roles,
+            preferences,
+        }))
+    }
+}
+
+struct PlayerReportWrapper(PlayerReport);
+
+impl<'r> FromRow<'r, SqliteRow> for PlayerReportWrapper {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let state: u8 = row.try_get("state")?;
+        let state = ReportState::from_u8(state).ok_or_else(|| sqlx::Error::Decode(format!("invalid report state {state}").into()))?;
+
+        Ok(PlayerReportWrapper(PlayerReport {
+            id: row.try_get("id")?,
+            reporter_id: row.try_get("reporter_id")?,
+            reported_id: row.try_get("reported_id")?,
+            reason: row.try_get("reason")?,
+            state,
+            handled_by: row.try_get("handled_by")?,
+            created_at: row.try_get("created_at")?,
         }))
     }
 }
@@ -73,8 +111,8 @@ impl GlobedDb {
 
     pub async fn update_user(&self, account_id: i32, user: &UserEntry) -> Result<()> {
         query(
-            "INSERT OR REPLACE INTO users (account_id, user_name, name_color, user_roles, is_banned, is_muted, is_whitelisted, admin_password, violation_reason, violation_expiry)
-                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+            "INSERT OR REPLACE INTO users (account_id, user_name, name_color, user_roles, is_banned, is_muted, is_whitelisted, admin_password, totp_secret, violation_reason, violation_expiry, first_seen, violation_count, timed_roles, preferences)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
             .bind(account_id)
             .bind(&user.user_name)
             .bind(&user.name_color)
@@ -83,8 +121,26 @@ impl GlobedDb {
             .bind(user.is_muted)
             .bind(user.is_whitelisted)
             .bind(&user.admin_password)
+            .bind(&user.totp_secret)
             .bind(&user.violation_reason)
             .bind(user.violation_expiry)
+            .bind(user.first_seen)
+            .bind(user.violation_count)
+            .bind(serde_json::to_string(&user.timed_roles).unwrap_or_default())
+            .bind(serde_json::to_string(&user.preferences).unwrap_or_default())
+            .execute(&self.0)
+            .await
+            .map(|_| ())
+    }
+
+    /// Records the time the user first logged in, if it wasn't recorded yet. Used for computing the trust tier of the user.
+    #[allow(clippy::cast_possible_wrap)]
+    pub async fn record_first_seen(&self, account_id: i32) -> Result<()> {
+        let current_time = SystemTime::now().duration_since(UNIX_EPOCH).expect("clock went backwards").as_secs() as i64;
+
+        query("INSERT INTO users (account_id, first_seen) VALUES (?, ?) ON CONFLICT(account_id) DO UPDATE SET first_seen = excluded.first_seen WHERE first_seen IS NULL")
+            .bind(account_id)
+            .bind(current_time)
             .execute(&self.0)
             .await
             .map(|_| ())
@@ -153,4 +209,84 @@ impl GlobedDb {
             .fetch_all(&self.0)
             .await
     }
+
+    /// Saves a new open report and returns its ID.
+    #[allow(clippy::cast_possible_wrap)]
+    pub async fn create_report(&self, reporter_id: i32, reported_id: i32, reason: &str) -> Result<i64> {
+        let current_time = SystemTime::now().duration_since(UNIX_EPOCH).expect("clock went backwards").as_secs() as i64;
+
+        let result = query("INSERT INTO reports (reporter_id, reported_id, reason, created_at) VALUES (?, ?, ?, ?)")
+            .bind(reporter_id)
+            .bind(reported_id)
+            .bind(reason)
+            .bind(current_time)
+            .execute(&self.0)
+            .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Stores the sealed voice capture of the reported player, returns `false` if the report doesn't exist.
+    pub async fn set_report_voice_capture(&self, id: i64, data: &[u8]) -> Result<bool> {
+        let result = query("UPDATE reports SET voice_capture = ? WHERE id = ?")
+            .bind(data)
+            .bind(id)
+            .execute(&self.0)
+            .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    pub async fn get_report_voice_capture(&self, id: i64) -> Result<Option<Vec<u8>>> {
+        let res: Option<(Option<Vec<u8>>,)> = query_as("SELECT voice_capture FROM reports WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.0)
+            .await?;
+
+        Ok(res.and_then(|x| x.0))
+    }
+
+    pub async fn get_report(&self, id: i64) -> Result<Option<PlayerReport>> {
+        let res: Option<PlayerReportWrapper> = query_as("SELECT * FROM reports WHERE id = ?").bind(id).fetch_optional(&self.0).await?;
+
+        Ok(res.map(|x| x.0))
+    }
+
+    /// Returns reports that are open or claimed, newest first.
+    pub async fn get_pending_reports(&self, limit: u32) -> Result<Vec<PlayerReport>> {
+        let res: Vec<PlayerReportWrapper> = query_as("SELECT * FROM reports WHERE state IN (?, ?) ORDER BY id DESC LIMIT ?")
+            .bind(ReportState::Open as u8)
+            .bind(ReportState::Claimed as u8)
+            .bind(limit)
+            .fetch_all(&self.0)
+            .await?;
+
+        Ok(res.into_iter().map(|x| x.0).collect())
+    }
+
+    /// Changes the state of a report if the moderator is allowed to, see `ReportState::can_change_to`.
+    /// Returns the report as it is afterwards, so the game server can tell whether it changed, or `None` if it doesn't exist.
+    pub async fn update_report_state(&self, change: &ReportStateChange) -> Result<Option<PlayerReport>> {
+        let Some(report) = self.get_report(change.id).await? else {
+            return Ok(None);
+        };
+
+        if !report.state.can_change_to(change.state, report.handled_by, change.moderator_id) {
+            return Ok(Some(report));
+        }
+
+        let handled_by = if change.state == ReportState::Open { 0 } else { change.moderator_id };
+
+        // two game servers can try to claim the same report at once, so only update if nobody else changed it in the meantime
+        query("UPDATE reports SET state = ?, handled_by = ? WHERE id = ? AND state = ? AND handled_by = ?")
+            .bind(change.state as u8)
+            .bind(handled_by)
+            .bind(change.id)
+            .bind(report.state as u8)
+            .bind(report.handled_by)
+            .execute(&self.0)
+            .await?;
+
+        self.get_report(change.id).await
+    }
 }
@@ -20,6 +20,7 @@ use config::ServerConfig;
 use db::GlobedDb;
 use game_pinger::GameServerPinger;
 use globed_shared::{
+    geoip::GeoIpDatabase,
     get_log_level,
     logger::{error, info, log, warn, Logger},
     LogLevelFilter,
@@ -122,7 +123,16 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let state_skey2 = config.secret_key2.clone();
 
     let pinger = GameServerPinger::new(&config.game_servers).await;
-    let ssd = ServerStateData::new(config_path.clone(), config, &state_skey, &state_skey2);
+    let geoip_database = config.geoip_database.clone();
+    let mut ssd = ServerStateData::new(config_path.clone(), config, state_skey.expose(), state_skey2.expose());
+
+    if !geoip_database.is_empty() {
+        match GeoIpDatabase::open(&geoip_database) {
+            Ok(db) => ssd.geoip = Some(db),
+            Err(err) => warn!("failed to open the GeoIP database at {geoip_database}: {err}"),
+        }
+    }
+
     let state = ServerState::new(ssd, pinger);
 
     // config file watcher
@@ -136,15 +146,32 @@ async fn main() -> Result<(), Box<dyn Error>> {
         while let Some(_event) = file_events.recv().await {
             let mut state = watcher_state.state_write().await;
             let cpath = state.config_path.clone();
+            let old_roles = state.config.roles.clone();
             match state.config.reload_in_place(&cpath) {
                 Ok(()) => {
                     info!("Successfully reloaded the configuration");
+                    // pick up rotated token keys, without invalidating tokens signed with the previous ones
+                    let skey2 = state.config.secret_key2.clone();
+                    let previous_skeys2 = state.config.previous_secret_keys2.clone();
+                    let token_expiry = Duration::from_secs(state.config.token_expiry);
+                    state.token_issuer.set_secret_keys(skey2.expose(), &previous_skeys2);
+                    state.token_issuer.set_expiration_period(token_expiry);
                     // set the maintenance flag appropriately
                     watcher_state.set_maintenance(state.config.maintenance);
                     watcher_state.inner.verifier.set_enabled(state.config.use_gd_api);
+                    // the hashes might have changed
+                    watcher_state
+                        .inner
+                        .verified_passwords
+                        .lock()
+                        .reset(state.config.game_server_credentials.len() + 1);
+                    // let game servers know about changed roles, so they can push them to players
+                    if state.config.roles != old_roles {
+                        watcher_state.inner.roles_revision.send_modify(|rev| *rev = rev.wrapping_add(1));
+                    }
                 }
                 Err(err) => {
-                    warn!("Failed to reload configuration: {}", err.to_string());
+                    warn!("Failed to reload configuration: {err}");
                 }
             }
         }
@@ -167,6 +194,11 @@ async fn main() -> Result<(), Box<dyn Error>> {
         pinger_state.pinger.run_pinger().await;
     });
 
+    let resolver_state = state.inner.clone();
+    tokio::spawn(async move {
+        resolver_state.run_server_resolver().await;
+    });
+
     // start up rocket
 
     let rocket = rocket::build()
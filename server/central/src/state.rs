@@ -12,14 +12,15 @@ use std::{
 use globed_shared::{
     anyhow,
     base64::{engine::general_purpose::STANDARD as b64e, Engine},
-    crypto_box::aead::{generic_array::GenericArray, AeadMutInPlace},
-    crypto_secretbox::{KeyInit, XSalsa20Poly1305},
+    crypto_box::aead::AeadMutInPlace,
+    crypto_secretbox::{Key, KeyInit, XSalsa20Poly1305},
+    geoip::GeoIpDatabase,
     hmac::Hmac,
     rand::{self, distributions::Alphanumeric, rngs::OsRng, Rng, RngCore},
     sha2::Sha256,
-    TokenIssuer,
+    AccountSession, SyncMutex, TokenIssuer, TransferTicket,
 };
-use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tokio::sync::{watch, RwLock, RwLockReadGuard, RwLockWriteGuard, Semaphore};
 
 use crate::{
     config::{ServerConfig, UserlistMode},
@@ -30,6 +31,21 @@ use crate::{
 use blake2::{Blake2b, Digest};
 use digest::consts::U32;
 
+/// How long a game server has to redeem a transfer ticket.
+const TRANSFER_TICKET_LIFETIME: Duration = Duration::from_secs(60);
+/// How many sessions that already ended are kept per account.
+const MAX_ENDED_SESSIONS: usize = 5;
+/// How long sessions that already ended are kept.
+const ENDED_SESSION_LIFETIME: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+/// Upper bound on active sessions per account, in case a game server never reports that its sessions ended (for example after a crash).
+const MAX_ACTIVE_SESSIONS: usize = 16;
+/// Active sessions that started longer ago than this are assumed to be left over from a game server that went down without ending them.
+const MAX_ACTIVE_SESSION_AGE: Duration = Duration::from_secs(60 * 60 * 24);
+/// How often the addresses of game servers are resolved again, for ordering the server list.
+const SERVER_RESOLVE_INTERVAL: Duration = Duration::from_secs(60 * 10);
+/// How many game server passwords can be checked against argon2 hashes at the same time.
+const MAX_CONCURRENT_PASSWORD_HASHES: usize = 2;
+
 #[derive(Clone)]
 pub struct ActiveChallenge {
     pub account_id: i32,
@@ -46,8 +62,17 @@ pub struct ServerStateData {
     pub hmac: Hmac<Sha256>,
     pub token_issuer: TokenIssuer,
     pub active_challenges: HashMap<IpAddr, ActiveChallenge>,
-    pub challenge_pubkey: GenericArray<u8, U32>,
+    pub challenge_pubkey: Key,
     pub challenge_box: XSalsa20Poly1305,
+    /// player transfers between game servers that have not been redeemed yet, with the time they were created at
+    pub transfer_tickets: HashMap<String, (TransferTicket, Duration)>,
+    /// active and recent logins of every account, reported by game servers. Kept in memory only, so a restart forgets them
+    pub account_sessions: HashMap<i32, Vec<AccountSession>>,
+    /// used for ordering the server list by proximity to the user
+    pub geoip: Option<GeoIpDatabase>,
+    /// IP addresses of the game servers, keyed by the address in the config. Resolved in the background, so that
+    /// listing servers never has to wait for DNS
+    pub server_ips: HashMap<String, IpAddr>,
 }
 
 impl ServerStateData {
@@ -55,20 +80,27 @@ impl ServerStateData {
         let skey_bytes = secret_key.as_bytes();
 
         let hmac = Hmac::<Sha256>::new_from_slice(skey_bytes).unwrap();
-        let mut challenge_pubkey = GenericArray::<u8, U32>::default();
+        let mut challenge_pubkey = Key::default();
         OsRng.fill_bytes(&mut challenge_pubkey);
         let challenge_box = XSalsa20Poly1305::new(&challenge_pubkey);
 
         let token_expiry = Duration::from_secs(config.token_expiry);
 
+        let mut token_issuer = TokenIssuer::new(token_secret_key, token_expiry);
+        token_issuer.set_secret_keys(token_secret_key, &config.previous_secret_keys2);
+
         Self {
             config_path,
             config,
             hmac,
-            token_issuer: TokenIssuer::new(token_secret_key, token_expiry),
+            token_issuer,
             active_challenges: HashMap::new(),
             challenge_pubkey,
             challenge_box,
+            transfer_tickets: HashMap::new(),
+            account_sessions: HashMap::new(),
+            geoip: None,
+            server_ips: HashMap::new(),
         }
     }
 
@@ -141,6 +173,92 @@ impl ServerStateData {
         output.into()
     }
 
+    /// store a transfer ticket, return the one-time token the receiving game server must use to redeem it
+    pub fn create_transfer_ticket(&mut self, ticket: TransferTicket, current_time: Duration) -> String {
+        self.transfer_tickets
+            .retain(|_, (_, created)| current_time.saturating_sub(*created) < TRANSFER_TICKET_LIFETIME);
+
+        let token: String = OsRng.sample_iter(&Alphanumeric).take(32).map(char::from).collect();
+        self.transfer_tickets.insert(token.clone(), (ticket, current_time));
+
+        token
+    }
+
+    /// remove the ticket with the given token and return it, if it exists and has not expired yet
+    pub fn redeem_transfer_ticket(&mut self, token: &str, current_time: Duration) -> Option<TransferTicket> {
+        self.transfer_tickets
+            .remove(token)
+            .filter(|(_, created)| current_time.saturating_sub(*created) < TRANSFER_TICKET_LIFETIME)
+            .map(|(ticket, _)| ticket)
+    }
+
+    /// store a new session of an account, replacing the session with the same ID if the game server reports it again
+    pub fn start_session(&mut self, session: AccountSession, current_time: Duration) {
+        let sessions = self.account_sessions.entry(session.account_id).or_default();
+        sessions.retain(|x| x.session_id != session.session_id);
+        sessions.push(session);
+
+        Self::trim_sessions(sessions, current_time);
+    }
+
+    /// mark the session as ended, returns `false` if there is no such active session
+    pub fn end_session(&mut self, account_id: i32, session_id: u64, current_time: Duration) -> bool {
+        let Some(sessions) = self.account_sessions.get_mut(&account_id) else {
+            return false;
+        };
+
+        let Some(session) = sessions.iter_mut().find(|x| x.session_id == session_id && x.is_active()) else {
+            return false;
+        };
+
+        session.logout_time = current_time.as_secs() as i64;
+        Self::trim_sessions(sessions, current_time);
+
+        true
+    }
+
+    /// get the active sessions of the account and the ones that ended recently, newest first
+    pub fn get_sessions(&mut self, account_id: i32, current_time: Duration) -> Vec<AccountSession> {
+        let Some(sessions) = self.account_sessions.get_mut(&account_id) else {
+            return Vec::new();
+        };
+
+        Self::trim_sessions(sessions, current_time);
+
+        let mut sessions = sessions.clone();
+        sessions.sort_by_key(|x| std::cmp::Reverse(x.login_time));
+        sessions
+    }
+
+    fn trim_sessions(sessions: &mut Vec<AccountSession>, current_time: Duration) {
+        let expiry = current_time.saturating_sub(ENDED_SESSION_LIFETIME).as_secs() as i64;
+        let active_expiry = current_time.saturating_sub(MAX_ACTIVE_SESSION_AGE).as_secs() as i64;
+        sessions.retain(|x| !x.is_active() || x.login_time >= active_expiry);
+
+        // keep the newest of each kind, oldest ones are at the front
+        sessions.sort_by_key(|x| x.login_time);
+
+        let ended = sessions.iter().filter(|x| !x.is_active()).count();
+        let active = sessions.len() - ended;
+
+        let mut ended_to_remove = ended.saturating_sub(MAX_ENDED_SESSIONS);
+        let mut active_to_remove = active.saturating_sub(MAX_ACTIVE_SESSIONS);
+
+        sessions.retain(|x| {
+            if x.is_active() {
+                if active_to_remove > 0 {
+                    active_to_remove -= 1;
+                    return false;
+                }
+            } else if ended_to_remove > 0 || x.logout_time < expiry {
+                ended_to_remove = ended_to_remove.saturating_sub(1);
+                return false;
+            }
+
+            true
+        });
+    }
+
     pub fn verify_challenge(&self, orig_value: &ActiveChallenge, answer: &str) -> bool {
         orig_value.answer == answer
     }
@@ -163,6 +281,37 @@ impl ServerStateData {
     }
 }
 
+/// Digests of game server passwords that matched a configured argon2 hash, keyed by the hash.
+/// argon2 is slow by design and game servers make a request for every login, so a password is only hashed the first time it's used.
+/// Every hash holds at most one digest, so this never grows past the amount of configured credentials.
+#[derive(Default)]
+pub struct VerifiedPasswords {
+    digests: HashMap<String, [u8; 32]>,
+}
+
+impl VerifiedPasswords {
+    /// Returns whether a password with this digest already matched the hash.
+    pub fn contains(&self, hash: &str, digest: &[u8; 32]) -> bool {
+        self.digests.get(hash).is_some_and(|known| {
+            let mut result = 0u8;
+            for (b1, b2) in digest.iter().zip(known.iter()) {
+                result |= b1 ^ b2;
+            }
+
+            result == 0
+        })
+    }
+
+    pub fn insert(&mut self, hash: &str, digest: [u8; 32]) {
+        self.digests.insert(hash.to_owned(), digest);
+    }
+
+    /// Forgets every password, called when the configuration (and so possibly the hashes) changes.
+    pub fn reset(&mut self, credentials: usize) {
+        self.digests = HashMap::with_capacity(credentials);
+    }
+}
+
 // both roa::Context and RwLock have the methods read() and write()
 // so doing Context<RwLock<..>> will break some things, hence we make a wrapper
 
@@ -171,6 +320,12 @@ pub struct InnerServerState {
     pub maintenance: AtomicBool,
     pub verifier: AccountVerifier,
     pub pinger: GameServerPinger,
+    /// revision of the role definitions, game servers waiting in `/gs/roles` are woken up when it changes.
+    /// Starts at a random value, so that game servers don't miss changes made after a restart of the central server
+    pub roles_revision: watch::Sender<u64>,
+    pub verified_passwords: SyncMutex<VerifiedPasswords>,
+    /// limits how many argon2 hashes are computed at once, so that wrong passwords can't keep every thread busy
+    pub password_hash_permits: Semaphore,
 }
 
 impl InnerServerState {
@@ -184,11 +339,17 @@ impl InnerServerState {
 
         let verifier = AccountVerifier::new(gd_api_account, gd_api_gjp, base_api_url, use_gd_api, ignore_name_mismatch, flush_period);
 
+        let mut verified_passwords = VerifiedPasswords::default();
+        verified_passwords.reset(ssd.config.game_server_credentials.len() + 1);
+
         Self {
             data: RwLock::new(ssd),
             maintenance: AtomicBool::new(maintenance),
             verifier,
             pinger,
+            roles_revision: watch::Sender::new(rand::thread_rng().gen()),
+            verified_passwords: SyncMutex::new(verified_passwords),
+            password_hash_permits: Semaphore::new(MAX_CONCURRENT_PASSWORD_HASHES),
         }
     }
 
@@ -199,6 +360,47 @@ impl InnerServerState {
     pub async fn state_write(&self) -> RwLockWriteGuard<'_, ServerStateData> {
         self.data.write().await
     }
+
+    /// Periodically resolves the addresses of all game servers into `ServerStateData::server_ips`.
+    pub async fn run_server_resolver(&self) -> ! {
+        let mut interval = tokio::time::interval(SERVER_RESOLVE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let addresses = {
+                let state = self.state_read().await;
+                // only needed for ordering by distance
+                if state.geoip.is_none() {
+                    continue;
+                }
+
+                state.config.game_servers.iter().map(|server| server.address.clone()).collect::<Vec<_>>()
+            };
+
+            let mut resolved = HashMap::with_capacity(addresses.len());
+            for address in addresses {
+                let ip = match address.parse::<IpAddr>() {
+                    Ok(ip) => Some(ip),
+                    Err(_) => tokio::net::lookup_host(&address)
+                        .await
+                        .ok()
+                        .and_then(|mut x| x.next())
+                        .map(|addr| addr.ip()),
+                };
+
+                if let Some(ip) = ip {
+                    resolved.insert(address, ip);
+                }
+            }
+
+            let mut state = self.state_write().await;
+            // keep the last known address of servers that failed to resolve this time
+            for (address, ip) in resolved {
+                state.server_ips.insert(address, ip);
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
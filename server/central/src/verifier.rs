@@ -260,7 +260,7 @@ impl AccountVerifier {
 
         let mut response = match result {
             Err(err) => {
-                warn!("Failed to make a request to GD servers: {}", err.to_string());
+                warn!("Failed to make a request to GD servers: {err}");
                 bail!("server error: {err}");
             }
             Ok(x) => x,
@@ -1,6 +1,19 @@
-use std::{error::Error, io::Cursor, net::IpAddr};
+use std::{
+    collections::HashMap,
+    error::Error,
+    io::Cursor,
+    net::IpAddr,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
 
-use globed_shared::esp::{ByteBuffer, ByteBufferExtRead, ByteBufferExtWrite, Decodable, DecodeError, Encodable};
+use globed_shared::{
+    esp::{ByteBuffer, ByteBufferExtRead, ByteBufferExtWrite, Decodable, DecodeError, Encodable},
+    is_password_hash,
+    logger::warn,
+    sha2::{Digest, Sha256},
+    verify_password, SyncMutex,
+};
 use rocket::{
     data::{self, FromData, ToByteUnit},
     http::{ContentType, Status},
@@ -10,6 +23,12 @@ use rocket::{
 };
 use tokio::io::AsyncReadExt;
 
+use crate::{
+    config::{GameServerCredential, GameServerScope},
+    ip_blocker::IpBlocker,
+    state::ServerState,
+};
+
 #[derive(Responder)]
 #[response(status = 503, content_type = "text")]
 pub struct MaintenanceResponder {
@@ -126,6 +145,24 @@ impl<'r> FromRequest<'r> for GameServerUserAgentGuard<'r> {
     }
 }
 
+// response format the game server asked for
+
+/// Whether the requester prefers JSON over the binary format, by its `Accept` header. Our own game servers prefer the binary format,
+/// but accept both, so this mostly shows what other central server implementations have to send. Without the header it's binary.
+pub struct ResponseFormatGuard {
+    pub json: bool,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ResponseFormatGuard {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let json = request.accept().is_some_and(|accept| accept.preferred().is_json());
+        Outcome::Success(Self { json })
+    }
+}
+
 // client user agent
 pub struct ClientUserAgentGuard<'r>(pub &'r str);
 
@@ -269,35 +306,207 @@ impl<'r, T: Decodable> FromData<'r> for CheckedDecodableGuard<T> {
     }
 }
 
-// game server password guar
-pub struct GameServerPasswordGuard(pub String);
+// game server password guard
+pub struct GameServerPasswordGuard {
+    password: String,
+    /// address the lockout is keyed on, never taken from a header that the sender could just change
+    address: IpAddr,
+}
 
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for GameServerPasswordGuard {
     type Error = &'static str;
 
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        match request.headers().get_one("Authorization") {
-            Some(x) => Outcome::Success(GameServerPasswordGuard(x.to_owned())),
-            None => Outcome::Error((Status::Unauthorized, "no password provided")),
-        }
+        let Some(password) = request.headers().get_one("Authorization") else {
+            return Outcome::Error((Status::Unauthorized, "no password provided"));
+        };
+
+        let Some(peer) = request.remote().map(|x| x.ip()) else {
+            return Outcome::Error((Status::BadRequest, "unknown peer address"));
+        };
+
+        let cloudflare_protection = match request.rocket().state::<ServerState>() {
+            Some(state) => state.state_read().await.config.cloudflare_protection,
+            None => false,
+        };
+
+        // same as `get_user_ip!`, the header can only be trusted if the request actually came from cloudflare
+        let address = if cloudflare_protection && !cfg!(debug_assertions) {
+            if !IpBlocker::instance().is_allowed(&peer) {
+                warn!("blocking unknown non-cloudflare address: {peer}");
+                return Outcome::Error((Status::Unauthorized, "access is denied from this IP address"));
+            }
+
+            match request.guard::<CloudflareIPGuard>().await {
+                Outcome::Success(CloudflareIPGuard(Some(x))) => x,
+                _ => return Outcome::Error((Status::BadRequest, "failed to parse the IP header from Cloudflare")),
+            }
+        } else {
+            peer
+        };
+
+        Outcome::Success(GameServerPasswordGuard {
+            password: password.to_owned(),
+            address,
+        })
+    }
+}
+
+/// How many wrong game server passwords an address can send before it gets locked out.
+const FREE_PASSWORD_ATTEMPTS: u32 = 5;
+/// Length of the first lockout, every next failure doubles it.
+const BASE_PASSWORD_LOCKOUT: Duration = Duration::from_secs(5);
+const MAX_PASSWORD_LOCKOUT: Duration = Duration::from_secs(10 * 60);
+/// Failures are forgotten after there were none for this long.
+const PASSWORD_FAILURE_MEMORY: Duration = Duration::from_secs(30 * 60);
+
+struct PasswordFailures {
+    count: u32,
+    last_failure: Instant,
+    locked_until: Instant,
+}
+
+impl PasswordFailures {
+    fn is_expired(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_failure) > PASSWORD_FAILURE_MEMORY && now >= self.locked_until
+    }
+}
+
+// every wrong password is checked against every hashed credential, so without a lockout
+// anyone could keep the server busy hashing by sending wrong passwords
+static PASSWORD_FAILURES: LazyLock<SyncMutex<HashMap<IpAddr, PasswordFailures>>> = LazyLock::new(|| SyncMutex::new(HashMap::new()));
+
+fn password_lockout(address: IpAddr) -> Option<Duration> {
+    let now = Instant::now();
+
+    PASSWORD_FAILURES
+        .lock()
+        .get(&address)
+        .map(|x| x.locked_until.saturating_duration_since(now))
+        .filter(|x| !x.is_zero())
+}
+
+fn record_password_failure(address: IpAddr) {
+    let now = Instant::now();
+    let mut failures = PASSWORD_FAILURES.lock();
+
+    if failures.len() >= 1024 {
+        failures.retain(|_, x| !x.is_expired(now));
+    }
+
+    let record = failures.entry(address).or_insert(PasswordFailures {
+        count: 0,
+        last_failure: now,
+        locked_until: now,
+    });
+
+    if record.is_expired(now) {
+        record.count = 0;
+    }
+
+    record.count += 1;
+    record.last_failure = now;
+
+    if record.count > FREE_PASSWORD_ATTEMPTS {
+        let exponent = (record.count - FREE_PASSWORD_ATTEMPTS - 1).min(16);
+        record.locked_until = now + BASE_PASSWORD_LOCKOUT.saturating_mul(1 << exponent).min(MAX_PASSWORD_LOCKOUT);
     }
 }
 
 impl GameServerPasswordGuard {
-    pub fn verify(&self, correct: &str) -> bool {
-        if self.0.len() != correct.len() {
-            return false;
+    fn digest(&self) -> [u8; 32] {
+        Sha256::digest(self.password.as_bytes()).into()
+    }
+
+    /// Checks the password against every hashed credential off the runtime, returns the index of the one that matched.
+    /// At most `MAX_CONCURRENT_PASSWORD_HASHES` of these run at once, across all requests.
+    async fn find_hashed(&self, state: &ServerState, credentials: &[GameServerCredential]) -> Option<usize> {
+        let hashes: Vec<(usize, String)> = credentials
+            .iter()
+            .enumerate()
+            .filter(|(_, cred)| is_password_hash(cred.password.expose()))
+            .map(|(idx, cred)| (idx, cred.password.expose().to_owned()))
+            .collect();
+
+        if hashes.is_empty() {
+            return None;
         }
 
-        let bytes1 = self.0.as_bytes();
-        let bytes2 = correct.as_bytes();
+        let _permit = state.inner.password_hash_permits.acquire().await.ok()?;
+
+        let password = self.password.clone();
+        let position = tokio::task::spawn_blocking(move || hashes.into_iter().find(|(_, hash)| verify_password(&password, hash)).map(|(idx, _)| idx))
+            .await
+            .ok()
+            .flatten()?;
+
+        state
+            .inner
+            .verified_passwords
+            .lock()
+            .insert(credentials[position].password.expose(), self.digest());
+
+        Some(position)
+    }
+
+    /// Finds the credential this password belongs to, `game_server_password` counts as one with every scope.
+    /// Returns the credential if it has the given scope, otherwise the error to respond with.
+    pub async fn authorize(&self, state: &ServerState, scope: GameServerScope) -> Result<GameServerCredential, &'static str> {
+        if password_lockout(self.address).is_some() {
+            return Err("too many invalid gameserver credentials, try again later");
+        }
 
-        let mut result = 0u8;
-        for (b1, b2) in bytes1.iter().zip(bytes2.iter()) {
-            result |= b1 ^ b2;
+        let mut credentials = {
+            let state = state.state_read().await;
+
+            let mut credentials = vec![GameServerCredential {
+                name: "game_server_password".to_owned(),
+                password: state.config.game_server_password.clone(),
+                scopes: GameServerScope::ALL.to_vec(),
+            }];
+
+            credentials.extend(state.config.game_server_credentials.iter().cloned());
+            credentials
+        };
+
+        // try everything that doesn't need argon2 first, so that a known password doesn't get checked against every other hash
+        let position = {
+            let digest = self.digest();
+            let verified = state.inner.verified_passwords.lock();
+
+            credentials.iter().position(|cred| {
+                let correct = cred.password.expose();
+                if is_password_hash(correct) {
+                    verified.contains(correct, &digest)
+                } else {
+                    verify_password(&self.password, correct)
+                }
+            })
+        };
+
+        let position = match position {
+            Some(x) => Some(x),
+            None => self.find_hashed(state, &credentials).await,
+        };
+
+        let Some(position) = position else {
+            record_password_failure(self.address);
+            return Err("invalid gameserver credentials");
+        };
+
+        PASSWORD_FAILURES.lock().remove(&self.address);
+
+        let credential = credentials.swap_remove(position);
+
+        if !credential.scopes.contains(&scope) {
+            warn!(
+                "game server using the credential \"{}\" tried to do something outside of its scopes ({scope:?})",
+                credential.name
+            );
+            return Err("this game server is not allowed to do this");
         }
 
-        result == 0
+        Ok(credential)
     }
 }
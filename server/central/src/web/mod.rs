@@ -31,6 +31,18 @@ pub mod routes {
             game_server::boot,
             game_server::get_user,
             game_server::update_user,
+            game_server::create_transfer,
+            game_server::redeem_transfer,
+            game_server::start_session,
+            game_server::end_session,
+            game_server::get_sessions,
+            game_server::shutdown,
+            game_server::create_report,
+            game_server::get_reports,
+            game_server::update_report_state,
+            game_server::set_report_voice,
+            game_server::get_report_voice,
+            game_server::wait_roles,
             game_server::p_get_user,
             game_server::p_update_user,
             auth::totp_login,
@@ -64,7 +64,7 @@ pub async fn totp_login(
     get_user_ip!(state_, ip, cfip, _ip);
 
     if state_.config.userlist_mode == UserlistMode::Whitelist {
-        if !db.get_user(aid).await?.map_or(false, |x| x.is_whitelisted) {
+        if !db.get_user(aid).await?.is_some_and(|x| x.is_whitelisted) {
             unauthorized!("This server has whitelist enabled and your account has not been approved.");
         }
     } else {
@@ -89,6 +89,10 @@ pub async fn totp_login(
 
     let token = state_.token_issuer.generate(aid, uid, aname);
 
+    if let Err(err) = db.record_first_seen(aid).await {
+        warn!("failed to record the first login of {aid}: {err}");
+    }
+
     debug!("totp login from {} ({}) successful", aname, aid);
 
     Ok(token)
@@ -116,7 +120,7 @@ pub async fn challenge_start(
     get_user_ip!(state, ip, cfip, user_ip);
 
     if state.config.userlist_mode == UserlistMode::Whitelist {
-        if !db.get_user(aid).await?.map_or(false, |x| x.is_whitelisted) {
+        if !db.get_user(aid).await?.is_some_and(|x| x.is_whitelisted) {
             unauthorized!("This server has whitelist enabled and your account has not been approved.");
         }
     } else {
@@ -1,14 +1,29 @@
-use std::net::IpAddr;
+use std::{
+    net::IpAddr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use globed_shared::{
+    bytes32_from_hex, cosmetic_hash_from_hex,
     esp::{types::FastString, ByteBuffer, ByteBufferExtWrite},
-    logger::debug,
-    GameServerBootData, UserEntry, PROTOCOL_VERSION, SERVER_MAGIC,
+    logger::{debug, info},
+    AccountSession, GameServerBootData, PlayerReport, ReportStateChange, RoleDefinitions, Secret, TransferTicket, UserEntry, PROTOCOL_VERSION,
+    SERVER_MAGIC,
 };
 
-use rocket::{get, post, serde::json::Json, State};
+use rocket::{
+    data::{Data, ToByteUnit},
+    get, post,
+    serde::json::Json,
+    Either, State,
+};
 
-use crate::{config::UserlistMode, db::GlobedDb, state::ServerState, web::*};
+use crate::{
+    config::{GameServerScope, UserlistMode},
+    db::GlobedDb,
+    state::ServerState,
+    web::*,
+};
 
 #[post("/gs/boot")]
 pub async fn boot(
@@ -16,12 +31,14 @@ pub async fn boot(
     password: GameServerPasswordGuard,
     ip_address: IpAddr,
     user_agent: GameServerUserAgentGuard<'_>,
-) -> WebResult<Vec<u8>> {
-    let correct = state.state_read().await.config.game_server_password.clone();
+    format: ResponseFormatGuard,
+) -> WebResult<Either<Vec<u8>, Json<GameServerBootData>>> {
+    let credential = match password.authorize(state, GameServerScope::BootData).await {
+        Ok(credential) => credential,
+        Err(err) => unauthorized!(err),
+    };
 
-    if !password.verify(&correct) {
-        unauthorized!("invalid gameserver credentials");
-    }
+    let roles_revision = *state.inner.roles_revision.borrow();
 
     let state = state.state_read().await;
     let config = &state.config;
@@ -31,24 +48,61 @@ pub async fn boot(
         tps: config.tps,
         maintenance: config.maintenance,
         secret_key2: config.secret_key2.clone(),
+        previous_secret_keys2: config.previous_secret_keys2.clone(),
         token_expiry: config.token_expiry,
         status_print_interval: config.status_print_interval,
+        player_history_interval: config.player_history_interval,
+        player_history_size: config.player_history_size,
         admin_key: FastString::new(&config.admin_key),
+        require_totp: config.require_totp,
         whitelist: config.userlist_mode == UserlistMode::Whitelist,
-        admin_webhook_url: config.admin_webhook_url.clone(),
+        // game servers without the webhook scope don't get the url, so they don't send anything
+        admin_webhook_url: if credential.scopes.contains(&GameServerScope::Webhook) {
+            config.admin_webhook_url.clone()
+        } else {
+            Secret::default()
+        },
         chat_burst_limit: config.chat_burst_limit,
         chat_burst_interval: config.chat_burst_interval,
         roles: config.roles.clone(),
+        roles_revision,
+        trust_account_age: config.trust_account_age,
+        min_trust_create_room: config.min_trust_create_room,
+        min_trust_invite: config.min_trust_invite,
+        min_trust_voice: config.min_trust_voice,
+        chat_link_policy: config.chat_link_policy,
+        duplicate_login_policy: config.duplicate_login_policy.clone(),
+        blocked_levels: config.blocked_levels.clone(),
+        banned_ips: config.banned_ips.clone(),
+        chat_filter_policy: config.chat_filter_policy,
+        chat_word_filter: config.chat_word_filter.clone(),
+        name_policy: config.name_policy.clone(),
+        approved_cosmetics: config.approved_cosmetics.iter().filter_map(|hash| cosmetic_hash_from_hex(hash)).collect(),
+        afk_timeout: config.afk_timeout,
+        afk_room_grace: config.afk_room_grace,
+        platform_features: config.platform_features,
+        player_state_policy: config.player_state_policy,
+        room_quota: config.room_quota.clone(),
+        voice_capture_seconds: config.voice_capture_seconds,
+        voice_capture_key: bytes32_from_hex(&config.voice_capture_key),
     };
 
-    debug!("boot data request from game server {} at {}", user_agent.0, ip_address);
+    debug!(
+        "boot data request from game server {} at {} (credential: {})",
+        user_agent.0, ip_address, credential.name
+    );
+
+    drop(state);
+
+    if format.json {
+        return Ok(Either::Right(Json(bdata)));
+    }
 
     let mut bb = ByteBuffer::new();
     bb.write_bytes(SERVER_MAGIC);
     bb.write_value(&bdata);
 
-    drop(state);
-    Ok(bb.into_vec())
+    Ok(Either::Left(bb.into_vec()))
 }
 
 async fn _get_user(database: &GlobedDb, user: &str) -> WebResult<UserEntry> {
@@ -73,10 +127,8 @@ pub async fn get_user(
     user: &str,
     _user_agent: GameServerUserAgentGuard<'_>,
 ) -> WebResult<CheckedEncodableResponder> {
-    let correct = state.state_read().await.config.game_server_password.clone();
-
-    if !password.verify(&correct) {
-        unauthorized!("invalid gameserver credentials");
+    if let Err(err) = password.authorize(state, GameServerScope::UserRead).await {
+        unauthorized!(err);
     }
 
     Ok(CheckedEncodableResponder::new(_get_user(database, user).await?))
@@ -89,10 +141,8 @@ pub async fn update_user(
     database: &GlobedDb,
     userdata: CheckedDecodableGuard<UserEntry>,
 ) -> WebResult<()> {
-    let correct = state.state_read().await.config.game_server_password.clone();
-
-    if !password.verify(&correct) {
-        unauthorized!("invalid gameserver credentials");
+    if let Err(err) = password.authorize(state, GameServerScope::UserWrite).await {
+        unauthorized!(err);
     }
 
     database.update_user(userdata.0.account_id, &userdata.0).await?;
@@ -100,6 +150,218 @@ pub async fn update_user(
     Ok(())
 }
 
+#[post("/gs/transfer", data = "<ticket>")]
+pub async fn create_transfer(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    ticket: CheckedDecodableGuard<TransferTicket>,
+) -> WebResult<String> {
+    if let Err(err) = password.authorize(state, GameServerScope::UserWrite).await {
+        unauthorized!(err);
+    }
+
+    let current_time = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let token = state.state_write().await.create_transfer_ticket(ticket.0, current_time);
+
+    Ok(token)
+}
+
+#[post("/gs/transfer/redeem/<token>")]
+pub async fn redeem_transfer(state: &State<ServerState>, password: GameServerPasswordGuard, token: &str) -> WebResult<CheckedEncodableResponder> {
+    if let Err(err) = password.authorize(state, GameServerScope::UserRead).await {
+        unauthorized!(err);
+    }
+
+    let current_time = SystemTime::now().duration_since(UNIX_EPOCH)?;
+
+    match state.state_write().await.redeem_transfer_ticket(token, current_time) {
+        Some(ticket) => Ok(CheckedEncodableResponder::new(ticket)),
+        None => bad_request!("invalid or expired transfer token"),
+    }
+}
+
+#[post("/gs/sessions/start", data = "<session>")]
+pub async fn start_session(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    session: CheckedDecodableGuard<AccountSession>,
+) -> WebResult<()> {
+    if let Err(err) = password.authorize(state, GameServerScope::UserWrite).await {
+        unauthorized!(err);
+    }
+
+    let current_time = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    state.state_write().await.start_session(session.0, current_time);
+
+    Ok(())
+}
+
+#[post("/gs/sessions/end/<account_id>/<session_id>")]
+pub async fn end_session(state: &State<ServerState>, password: GameServerPasswordGuard, account_id: i32, session_id: u64) -> WebResult<()> {
+    if let Err(err) = password.authorize(state, GameServerScope::UserWrite).await {
+        unauthorized!(err);
+    }
+
+    let current_time = SystemTime::now().duration_since(UNIX_EPOCH)?;
+
+    if state.state_write().await.end_session(account_id, session_id, current_time) {
+        Ok(())
+    } else {
+        bad_request!("this session does not exist or already ended")
+    }
+}
+
+/// Called by a game server that is going down, ends the sessions of the players that were still on it.
+#[post("/gs/shutdown", data = "<sessions>")]
+pub async fn shutdown(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    sessions: CheckedDecodableGuard<Vec<AccountSession>>,
+) -> WebResult<()> {
+    let credential = match password.authorize(state, GameServerScope::UserWrite).await {
+        Ok(credential) => credential,
+        Err(err) => unauthorized!(err),
+    };
+
+    let current_time = SystemTime::now().duration_since(UNIX_EPOCH)?;
+
+    let mut state = state.state_write().await;
+    let mut ended = 0;
+    for session in &sessions.0 {
+        if state.end_session(session.account_id, session.session_id, current_time) {
+            ended += 1;
+        }
+    }
+
+    info!("game server '{}' is shutting down, ended {ended} sessions", credential.name);
+
+    Ok(())
+}
+
+#[get("/gs/sessions/<account_id>")]
+pub async fn get_sessions(state: &State<ServerState>, password: GameServerPasswordGuard, account_id: i32) -> WebResult<CheckedEncodableResponder> {
+    if let Err(err) = password.authorize(state, GameServerScope::UserRead).await {
+        unauthorized!(err);
+    }
+
+    let current_time = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let sessions = state.state_write().await.get_sessions(account_id, current_time);
+
+    Ok(CheckedEncodableResponder::new(sessions))
+}
+
+/// How many pending reports are sent to the game server at once.
+const MAX_PENDING_REPORTS: u32 = 100;
+
+/// Only the reporter, the reported player and the reason are used, the rest is filled in by us.
+#[post("/gs/reports/create", data = "<report>")]
+pub async fn create_report(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    report: CheckedDecodableGuard<PlayerReport>,
+) -> WebResult<String> {
+    if let Err(err) = password.authorize(state, GameServerScope::UserWrite).await {
+        unauthorized!(err);
+    }
+
+    let report = report.0;
+    let id = database.create_report(report.reporter_id, report.reported_id, &report.reason).await?;
+
+    Ok(id.to_string())
+}
+
+#[get("/gs/reports")]
+pub async fn get_reports(state: &State<ServerState>, password: GameServerPasswordGuard, database: &GlobedDb) -> WebResult<CheckedEncodableResponder> {
+    if let Err(err) = password.authorize(state, GameServerScope::UserRead).await {
+        unauthorized!(err);
+    }
+
+    Ok(CheckedEncodableResponder::new(database.get_pending_reports(MAX_PENDING_REPORTS).await?))
+}
+
+/// Largest voice capture a game server can attach to a report, game servers keep at most 512 KiB of voice per player.
+const MAX_VOICE_CAPTURE_SIZE: usize = 1024 * 1024;
+
+/// The body is a sealed voice capture of the reported player as raw bytes, which only staff can open.
+#[post("/gs/reports/<id>/voice", data = "<capture>")]
+pub async fn set_report_voice(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    id: i64,
+    capture: Data<'_>,
+) -> WebResult<()> {
+    if let Err(err) = password.authorize(state, GameServerScope::UserWrite).await {
+        unauthorized!(err);
+    }
+
+    let capture = capture.open(MAX_VOICE_CAPTURE_SIZE.bytes()).into_bytes().await?;
+    if !capture.is_complete() {
+        bad_request!("voice capture is too large");
+    }
+
+    if database.set_report_voice_capture(id, &capture).await? {
+        Ok(())
+    } else {
+        bad_request!("this report does not exist")
+    }
+}
+
+/// Responds with the sealed voice capture as-is, see the readme for how to open it.
+#[get("/gs/reports/<id>/voice")]
+pub async fn get_report_voice(state: &State<ServerState>, password: GameServerPasswordGuard, database: &GlobedDb, id: i64) -> WebResult<Vec<u8>> {
+    if let Err(err) = password.authorize(state, GameServerScope::UserRead).await {
+        unauthorized!(err);
+    }
+
+    match database.get_report_voice_capture(id).await? {
+        Some(capture) => Ok(capture),
+        None => bad_request!("this report does not exist or has no voice capture"),
+    }
+}
+
+/// Responds with the report as it is after the change, which is unchanged if the moderator wasn't allowed to change it.
+#[post("/gs/reports/state", data = "<change>")]
+pub async fn update_report_state(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    change: CheckedDecodableGuard<ReportStateChange>,
+) -> WebResult<CheckedEncodableResponder> {
+    if let Err(err) = password.authorize(state, GameServerScope::UserWrite).await {
+        unauthorized!(err);
+    }
+
+    match database.update_report_state(&change.0).await? {
+        Some(report) => Ok(CheckedEncodableResponder::new(report)),
+        None => bad_request!("this report does not exist"),
+    }
+}
+
+/// How long a game server waiting for new role definitions is kept waiting before we respond with the current ones.
+const ROLES_WAIT_TIMEOUT: Duration = Duration::from_secs(50);
+
+/// Responds right away if the roles changed since `revision`, otherwise waits for them to change,
+/// so that game servers get new role definitions as soon as the config is reloaded.
+#[get("/gs/roles?<revision>")]
+pub async fn wait_roles(state: &State<ServerState>, password: GameServerPasswordGuard, revision: u64) -> WebResult<CheckedEncodableResponder> {
+    if let Err(err) = password.authorize(state, GameServerScope::BootData).await {
+        unauthorized!(err);
+    }
+
+    let mut receiver = state.inner.roles_revision.subscribe();
+    if *receiver.borrow_and_update() == revision {
+        // on timeout we just respond with the same revision, and the game server asks again
+        let _ = tokio::time::timeout(ROLES_WAIT_TIMEOUT, receiver.changed()).await;
+    }
+
+    let revision = *receiver.borrow();
+    let roles = state.state_read().await.config.roles.clone();
+
+    Ok(CheckedEncodableResponder::new(RoleDefinitions { revision, roles }))
+}
+
 /* /gsp/ apis are the same except they use JSON instead of binary encoding */
 
 #[get("/gsp/user/<user>")]
@@ -110,10 +372,8 @@ pub async fn p_get_user(
     user: &str,
     _user_agent: GameServerUserAgentGuard<'_>,
 ) -> WebResult<Json<UserEntry>> {
-    let correct = state.state_read().await.config.game_server_password.clone();
-
-    if !password.verify(&correct) {
-        unauthorized!("invalid gameserver credentials");
+    if let Err(err) = password.authorize(state, GameServerScope::UserRead).await {
+        unauthorized!(err);
     }
 
     Ok(Json(_get_user(database, user).await?))
@@ -126,10 +386,8 @@ pub async fn p_update_user(
     database: &GlobedDb,
     userdata: Json<UserEntry>,
 ) -> WebResult<()> {
-    let correct = state.state_read().await.config.game_server_password.clone();
-
-    if !password.verify(&correct) {
-        unauthorized!("invalid gameserver credentials");
+    if let Err(err) = password.authorize(state, GameServerScope::UserWrite).await {
+        unauthorized!(err);
     }
 
     database.update_user(userdata.0.account_id, &userdata).await?;
@@ -1,8 +1,9 @@
-use std::sync::OnceLock;
+use std::{net::IpAddr, sync::OnceLock};
 
 use globed_shared::{
     base64::{engine::general_purpose as b64e, Engine as _},
     esp::{ByteBuffer, ByteBufferExt, ByteBufferExtWrite},
+    geoip::DistanceClass,
     rand::{self, Rng},
     MIN_CLIENT_VERSION, PROTOCOL_VERSION, SERVER_MAGIC,
 };
@@ -13,7 +14,7 @@ use rocket::{
     State,
 };
 
-use crate::state::ServerState;
+use crate::{config::GameServerEntry, state::ServerState};
 
 use super::*;
 
@@ -37,24 +38,49 @@ pub fn robots() -> String {
 }
 
 #[get("/servers?<protocol>")]
-pub async fn servers(state: &State<ServerState>, protocol: u16) -> WebResult<String> {
+pub async fn servers(state: &State<ServerState>, protocol: u16, ip: IpAddr, cfip: CloudflareIPGuard) -> WebResult<String> {
     check_maintenance!(state);
     check_protocol!(protocol);
 
     let state = state.state_read().await;
-    let servers = &state.config.game_servers;
+    let mut servers = state.config.game_servers.clone();
+
+    if let Some(geoip) = state.geoip.as_ref() {
+        // only used for ordering, so no need to verify that the request actually came from cloudflare
+        let user_ip = if state.config.cloudflare_protection { cfip.0 } else { Some(ip) };
+
+        if let Some(user_location) = user_ip.and_then(|ip| geoip.lookup(ip)) {
+            let classes = servers
+                .iter()
+                .map(|server| match state.server_ips.get(&server.address) {
+                    Some(server_ip) => geoip.distance_class(&user_location, *server_ip),
+                    None => DistanceClass::Unknown,
+                })
+                .collect::<Vec<_>>();
+
+            sort_by_distance(&mut servers, &classes);
+        }
+    }
+
+    drop(state);
 
     let mut buf = ByteBuffer::with_capacity(servers.len() * 128);
     buf.write_bytes(SERVER_MAGIC);
-    buf.write_value(servers);
-
-    drop(state);
+    buf.write_value(&servers);
 
     let encoded = b64e::STANDARD.encode(buf.as_bytes());
 
     Ok(encoded)
 }
 
+/// Puts the closest servers first, servers with an unknown distance go last and otherwise keep the order from the config.
+fn sort_by_distance(servers: &mut Vec<GameServerEntry>, classes: &[DistanceClass]) {
+    let mut sorted = servers.drain(..).zip(classes.iter().copied()).collect::<Vec<_>>();
+    sorted.sort_by_key(|(_, class)| (*class == DistanceClass::Unknown, *class));
+
+    servers.extend(sorted.into_iter().map(|(server, _)| server));
+}
+
 fn _check() -> (Status, (ContentType, String)) {
     static VALS: OnceLock<(String, String, String, String)> = OnceLock::new();
 
@@ -1,4 +1,4 @@
-#![feature(proc_macro_diagnostic, if_let_guard)]
+#![feature(proc_macro_diagnostic)]
 #![allow(clippy::missing_panics_doc)]
 
 mod attrs;
@@ -127,7 +127,7 @@ pub fn derive_static_size(input: TokenStream) -> TokenStream {
 /// For enums, all the same limitations apply as in `Encodable`.
 ///
 /// If your struct also implements `StaticSize`, for efficiency purposes you can add the attribute `#[dynamic_size(as_static = true)]`, like so:
-/// ```rust
+/// ```rust,ignore
 /// #[derive(StaticSize, DynamicSize)]
 /// #[dynamic_size(as_static = true)]
 /// pub struct MyStruct { val: u32 }
@@ -206,7 +206,7 @@ pub fn derive_dynamic_size(input: TokenStream) -> TokenStream {
 /// Implements `Packet`, `PacketMetadata` and the function `const fn header() -> PacketHeader` for the given struct.
 /// You must also pass additional attributes with `#[packet]`, specifically packet ID and optionally, encryption and whether to use TCP or UDP (only applicable when sending).
 /// Example:
-/// ```rust
+/// ```rust,ignore
 /// #[derive(Packet, Encodable, Decodable)]
 /// #[packet(id = 10000, encrypted = false, tcp = false)] // 'encrypted' and 'tcp' are optional and off by deafult
 /// pub struct MyPacket { /* fields */ }
@@ -242,8 +242,8 @@ pub fn packet(input: TokenStream) -> TokenStream {
 
                 impl #impl_generics #ident #ty_generics #where_clause {
                     #[inline]
-                    pub const fn header() -> crate::data::packets::PacketHeader {
-                        crate::data::packets::PacketHeader::from_packet::<Self>()
+                    pub const fn header() -> globed_protocol::PacketHeader {
+                        globed_protocol::PacketHeader::from_packet::<Self>()
                     }
                 }
             }
@@ -303,5 +303,5 @@ pub(crate) fn get_bitfield_struct_size(input: &DataStruct) -> usize {
         "get_bitfield_struct_size called on a struct with fields that aren't bool"
     );
 
-    (input.fields.len() + 7) / 8
+    input.fields.len().div_ceil(8)
 }
@@ -1,8 +1,19 @@
 use crate::*;
 
-#[derive(Copy, Clone, Default, Debug)]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 pub struct FiniteF32(f32);
 
+impl FiniteF32 {
+    /// Returns `None` if the value is NaN or infinite.
+    pub fn new(value: f32) -> Option<Self> {
+        value.is_finite().then_some(Self(value))
+    }
+
+    pub const fn get(self) -> f32 {
+        self.0
+    }
+}
+
 impl Encodable for FiniteF32 {
     fn encode(&self, buf: &mut ByteBuffer) {
         buf.write_f32(self.0);
@@ -174,86 +174,69 @@ fn strings(c: &mut Criterion) {
         buf_long.write_value(&generate_alphanum_string(rand::thread_rng().gen_range(164..512)));
     }
 
-    let mut output = ByteBuffer::with_capacity(buf_long.len() + 1024);
-
     c.bench_function("read-string-short", |b| {
         b.iter(|| {
-            // output.clear();
             let mut reader = ByteReader::from_bytes(buf_short.as_bytes());
             for _ in 0..1024 {
-                let str = black_box(reader.read::<String>()).unwrap();
-                // output.write_value(&str);
+                black_box(reader.read::<String>()).unwrap();
             }
         });
     });
 
     c.bench_function("read-fast-string-short", |b| {
         b.iter(|| {
-            // output.clear();
             let mut reader = ByteReader::from_bytes(buf_short.as_bytes());
             for _ in 0..1024 {
-                let str = black_box(reader.read::<FastString>()).unwrap();
-                // output.write_value(&str);
+                black_box(reader.read::<FastString>()).unwrap();
             }
         });
     });
 
     c.bench_function("read-inline-string-short", |b| {
         b.iter(|| {
-            // output.clear();
             let mut reader = ByteReader::from_bytes(buf_short.as_bytes());
             for _ in 0..1024 {
-                let str = black_box(reader.read::<InlineString<32>>()).unwrap();
-                // output.write_value(&str);
+                black_box(reader.read::<InlineString<32>>()).unwrap();
             }
         });
     });
 
     c.bench_function("read-string-medium", |b| {
         b.iter(|| {
-            // output.clear();
             let mut reader = ByteReader::from_bytes(buf_med.as_bytes());
             for _ in 0..1024 {
-                let str = black_box(reader.read::<String>()).unwrap();
-                // output.write_value(&str);
+                black_box(reader.read::<String>()).unwrap();
             }
         });
     });
 
     c.bench_function("read-fast-string-medium", |b| {
         b.iter(|| {
-            // output.clear();
             let mut reader = ByteReader::from_bytes(buf_med.as_bytes());
             for _ in 0..1024 {
-                let str = black_box(reader.read::<FastString>()).unwrap();
-                // output.write_value(&str);
+                black_box(reader.read::<FastString>()).unwrap();
             }
         });
     });
 
     c.bench_function("read-string-long", |b| {
         b.iter(|| {
-            // output.clear();
             let mut reader = ByteReader::from_bytes(buf_long.as_bytes());
             for _ in 0..1024 {
-                let str = black_box(reader.read::<String>()).unwrap();
-                // output.write_value(&str);
+                black_box(reader.read::<String>()).unwrap();
             }
         });
     });
 
     c.bench_function("read-fast-string-long", |b| {
         b.iter(|| {
-            // output.clear();
             let mut reader = ByteReader::from_bytes(buf_long.as_bytes());
             for _ in 0..1024 {
-                let str = black_box(reader.read::<FastString>()).unwrap();
-                // output.write_value(&str);
+                black_box(reader.read::<FastString>()).unwrap();
             }
         });
     });
 }
 
-// criterion_group!(benches, buffers, structs, managers, read_value_array, strings);
-criterion_group!(benches, strings);
+criterion_group!(benches, buffers, structs, managers, read_value_array, strings);
 criterion_main!(benches);
@@ -0,0 +1,5 @@
+fn main() {
+    // only generate the gRPC code when it's needed, so that regular builds don't require protoc
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/admin.proto").expect("failed to compile the protobuf definitions");
+}
@@ -0,0 +1,5 @@
+DROP TABLE daily_stats;
+DROP TABLE player_counts;
+DROP INDEX chat_log_time;
+DROP TABLE chat_log;
+DROP TABLE users;
@@ -0,0 +1,41 @@
+-- Local accounts, only used on standalone servers, mirrors the users table of the central server
+CREATE TABLE users (
+    account_id INTEGER PRIMARY KEY,
+    user_name TEXT,
+    name_color TEXT,
+    user_roles TEXT,
+    is_banned BOOLEAN NOT NULL DEFAULT FALSE,
+    is_muted BOOLEAN NOT NULL DEFAULT FALSE,
+    is_whitelisted BOOLEAN NOT NULL DEFAULT FALSE,
+    violation_reason TEXT,
+    violation_expiry INTEGER,
+    first_seen INTEGER,
+    last_seen INTEGER,
+    violation_count INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE chat_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    log_time INTEGER NOT NULL,
+    account_id INTEGER NOT NULL,
+    room_id INTEGER NOT NULL,
+    level_id INTEGER NOT NULL,
+    message TEXT NOT NULL
+);
+
+CREATE INDEX chat_log_time ON chat_log (log_time);
+
+CREATE TABLE player_counts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    log_time INTEGER NOT NULL,
+    count INTEGER NOT NULL
+);
+
+-- aggregated stats, one row per day (days since the unix epoch)
+CREATE TABLE daily_stats (
+    day INTEGER PRIMARY KEY,
+    logins INTEGER NOT NULL DEFAULT 0,
+    chat_messages INTEGER NOT NULL DEFAULT 0,
+    rooms_created INTEGER NOT NULL DEFAULT 0,
+    peak_players INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,12 @@
+-- Player reports, only used on standalone servers, mirrors the reports table of the central server
+CREATE TABLE reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    reporter_id INTEGER NOT NULL,
+    reported_id INTEGER NOT NULL,
+    reason TEXT NOT NULL,
+    state INTEGER NOT NULL DEFAULT 0,
+    handled_by INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX reports_state ON reports (state);
@@ -0,0 +1,86 @@
+#![allow(clippy::cast_possible_truncation)]
+
+//! Replays a packet capture recorded by the game server (see `GLOBED_GS_CAPTURE_DIR`) against a standalone server.
+//! Usage: `globed-replay <server-address> <capture-file> [speed]`
+
+#[cfg(feature = "use_tokio_tracing")]
+use tokio_tracing as tokio;
+
+#[cfg(not(feature = "use_tokio_tracing"))]
+#[allow(clippy::single_component_path_imports)]
+use tokio;
+
+use std::{error::Error, net::SocketAddr, path::PathBuf, time::Duration};
+
+use globed_game_server::{testing::TestClient, util::CaptureReader};
+use globed_shared::{error, info, log, warn, LogLevelFilter, Logger, PROTOCOL_VERSION};
+use tokio::time::Instant;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    log::set_logger(Logger::instance("globed_replay", false)).unwrap();
+    log::set_max_level(LogLevelFilter::Trace);
+
+    let mut args = std::env::args();
+    let exe_name = args.next().unwrap();
+
+    let (Some(address), Some(capture_path)) = (args.next(), args.next()) else {
+        error!("correct usage: \"{exe_name} <server-address> <capture-file> [speed]\"");
+        std::process::exit(1);
+    };
+
+    let address = address.parse::<SocketAddr>()?;
+    let capture_path = PathBuf::from(capture_path);
+    let speed = args.next().map_or(Ok(1.0), |x| x.parse::<f64>())?;
+
+    if !speed.is_finite() || speed <= 0.0 {
+        error!("invalid replay speed: {speed}");
+        std::process::exit(1);
+    }
+
+    let mut reader = CaptureReader::open(&capture_path)?;
+    if reader.protocol != PROTOCOL_VERSION {
+        warn!(
+            "capture was recorded with protocol v{}, while this build uses v{PROTOCOL_VERSION}, packets may fail to decode",
+            reader.protocol
+        );
+    }
+
+    // account id can be overriden, otherwise pick something that is not a real account
+    let account_id = std::env::var("GLOBED_REPLAY_ACCOUNT_ID")
+        .ok()
+        .and_then(|x| x.parse::<i32>().ok())
+        .unwrap_or(2_000_000_000);
+
+    info!("connecting to {address} (note: the server must be running in standalone mode)");
+
+    let mut client = TestClient::connect(address).await?;
+    client.login(account_id, account_id, "Replay").await?;
+
+    info!("logged in as {account_id}, replaying {capture_path:?} at {speed}x speed");
+
+    let started = Instant::now();
+    let mut count = 0usize;
+
+    while let Some(packet) = reader.next_packet()? {
+        let delay = Duration::from_micros((packet.timestamp as f64 / speed) as u64);
+        tokio::time::sleep_until(started + delay).await;
+
+        // discard whatever the server sent us in the meantime
+        while let Ok(Some(_)) = client.try_recv_packet() {}
+
+        if let Err(e) = client.send_raw(packet.packet_id, packet.encrypted, &packet.payload).await {
+            error!("failed to send packet {} (#{count}): {e}", packet.packet_id);
+            error!("the server likely closed the connection, check the server logs");
+            std::process::exit(1);
+        }
+
+        count += 1;
+    }
+
+    info!("replayed {count} packets in {:.3}s", started.elapsed().as_secs_f64());
+
+    client.disconnect().await?;
+
+    Ok(())
+}
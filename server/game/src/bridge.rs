@@ -8,10 +8,17 @@ use std::{
 use esp::{size_of_types, ByteBuffer, ByteBufferExt, ByteBufferExtRead, ByteBufferExtWrite, ByteReader, DecodeError, DynamicSize, StaticSize};
 use globed_shared::{
     reqwest::{self, StatusCode},
-    GameServerBootData, SyncMutex, TokenIssuer, UserEntry, PROTOCOL_VERSION, SERVER_MAGIC, SERVER_MAGIC_LEN,
+    AccountSession, GameServerBootData, PlayerReport, ReportStateChange, RoleDefinitions, Secret, SyncMutex, TokenIssuer, TransferTicket, UserEntry,
+    BOOT_DATA_FORMATS, PROTOCOL_VERSION, SERVER_MAGIC, SERVER_MAGIC_LEN,
 };
 
-use crate::webhook::{self, *};
+use crate::{
+    chaos,
+    data::{ConfigOverride, ConfigOverrideKey},
+    managers::ConfigOverrideManager,
+    safe_mode,
+    webhook::{self, *},
+};
 
 #[derive(Debug)]
 pub enum CentralBridgeError {
@@ -20,6 +27,7 @@ pub enum CentralBridgeError {
     WebhookError((StatusCode, String)), // non 2xx status code
     InvalidMagic(String),               // invalid magic
     MalformedData(DecodeError),         // failed to decode data
+    MalformedJson(serde_json::Error),   // failed to decode or encode json
     ProtocolMismatch(u16),              // protocol version mismatch
     Other(String),
 }
@@ -32,6 +40,7 @@ impl Display for CentralBridgeError {
             Self::WebhookError((err, response)) => write!(f, "webhook error {err}: {response}"),
             Self::InvalidMagic(_) => write!(f, "central server sent invalid magic"),
             Self::MalformedData(err) => write!(f, "failed to decode data sent by the central server: {err}"),
+            Self::MalformedJson(err) => write!(f, "invalid JSON data from the central server: {err}"),
             Self::ProtocolMismatch(proto) => write!(f, "protocol mismatch, we are on v{PROTOCOL_VERSION} while central server is on v{proto}"),
             Self::Other(err) => f.write_str(err),
         }
@@ -50,27 +59,48 @@ impl From<DecodeError> for CentralBridgeError {
     }
 }
 
+impl From<serde_json::Error> for CentralBridgeError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::MalformedJson(value)
+    }
+}
+
 impl Error for CentralBridgeError {}
 
 pub type Result<T> = std::result::Result<T, CentralBridgeError>;
 
+/// The central server holds role requests for up to 50 seconds, so they need a longer timeout than other requests.
+const ROLES_WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// `CentralBridge` stores the configuration of the game server,
 /// and is used for making requests to the central server.
 pub struct CentralBridge {
     pub http_client: reqwest::Client,
     pub central_url: String,
-    pub central_pw: String,
+    pub central_pw: Secret,
     pub token_issuer: SyncMutex<TokenIssuer>,
     pub central_conf: SyncMutex<GameServerBootData>,
+    /// values that admins overrode on this server, applied on top of `central_conf`
+    pub config_overrides: ConfigOverrideManager,
 
     // for performance reasons /shrug
     pub maintenance: AtomicBool,
     pub whitelist: AtomicBool,
     pub webhook_present: AtomicBool,
+    /// whether the central server sent the boot data as JSON, in which case user entries are exchanged as JSON too
+    pub json_format: AtomicBool,
+}
+
+fn is_json_response(response: &reqwest::Response) -> bool {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|x| x.to_str().ok())
+        .is_some_and(|x| x.starts_with("application/json"))
 }
 
 impl CentralBridge {
-    pub fn new(central_url: &str, central_pw: &str) -> Self {
+    pub fn new(central_url: &str, central_pw: Secret) -> Self {
         let http_client = reqwest::Client::builder()
             .timeout(Duration::from_secs(5))
             .user_agent(format!("globed-game-server/{}", env!("CARGO_PKG_VERSION")))
@@ -81,11 +111,13 @@ impl CentralBridge {
             http_client,
             token_issuer: SyncMutex::new(TokenIssuer::new("", Duration::from_secs(0))),
             central_url: central_url.to_owned(),
-            central_pw: central_pw.to_owned(),
+            central_pw,
             central_conf: SyncMutex::new(GameServerBootData::default()),
+            config_overrides: ConfigOverrideManager::default(),
             maintenance: AtomicBool::new(false),
             whitelist: AtomicBool::new(false),
             webhook_present: AtomicBool::new(false),
+            json_format: AtomicBool::new(false),
         }
     }
 
@@ -98,14 +130,18 @@ impl CentralBridge {
     }
 
     pub fn has_webhook(&self) -> bool {
-        self.webhook_present.load(Ordering::Relaxed)
+        // webhooks are disabled in safe mode
+        self.webhook_present.load(Ordering::Relaxed) && !safe_mode::is_active()
     }
 
     pub async fn request_boot_data(&self) -> Result<GameServerBootData> {
+        chaos::delay_bridge().await;
+
         let response = self
             .http_client
             .post(format!("{}gs/boot", self.central_url))
-            .header("Authorization", self.central_pw.clone())
+            .header("Authorization", self.central_pw.expose())
+            .header("Accept", BOOT_DATA_FORMATS)
             .send()
             .await?;
 
@@ -116,25 +152,34 @@ impl CentralBridge {
             return Err(CentralBridgeError::CentralError((status, message)));
         }
 
+        // other central server implementations can send JSON instead of the binary format
+        let is_json = is_json_response(&response);
         let config = response.bytes().await?;
-        let mut reader = ByteReader::from_bytes(&config);
 
-        // verify that the magic bytes match
-        let valid_magic = reader
-            .read_value_array::<u8, SERVER_MAGIC_LEN>()
-            .map_or(false, |magic| magic.iter().eq(SERVER_MAGIC.iter()));
+        let boot_data = if is_json {
+            serde_json::from_slice::<GameServerBootData>(&config)?
+        } else {
+            let mut reader = ByteReader::from_bytes(&config);
 
-        if !valid_magic {
-            let txt = String::from_utf8(reader.as_bytes().to_vec()).unwrap_or_else(|_| "<invalid UTF-8 string>".to_owned());
-            return Err(CentralBridgeError::InvalidMagic(txt));
-        }
+            // verify that the magic bytes match
+            let valid_magic = reader
+                .read_value_array::<u8, SERVER_MAGIC_LEN>()
+                .is_ok_and(|magic| magic.iter().eq(SERVER_MAGIC.iter()));
+
+            if !valid_magic {
+                let txt = String::from_utf8(reader.as_bytes().to_vec()).unwrap_or_else(|_| "<invalid UTF-8 string>".to_owned());
+                return Err(CentralBridgeError::InvalidMagic(txt));
+            }
 
-        let boot_data = reader.read_value::<GameServerBootData>()?;
+            reader.read_value::<GameServerBootData>()?
+        };
 
         if boot_data.protocol != PROTOCOL_VERSION {
             return Err(CentralBridgeError::ProtocolMismatch(boot_data.protocol));
         }
 
+        self.json_format.store(is_json, Ordering::Relaxed);
+
         Ok(boot_data)
     }
 
@@ -152,24 +197,66 @@ impl CentralBridge {
 
     #[inline]
     pub fn set_boot_data(&self, data: GameServerBootData) {
+        {
+            let mut issuer = self.token_issuer.lock();
+
+            issuer.set_expiration_period(Duration::from_secs(data.token_expiry));
+            issuer.set_secret_keys(data.secret_key2.expose(), &data.previous_secret_keys2);
+        }
+
+        self.set_local_config(data);
+    }
+
+    /// Like `set_boot_data`, but leaves the token issuer alone. Used on standalone servers, which sign tokens with their own key.
+    pub fn set_local_config(&self, mut data: GameServerBootData) {
+        self.config_overrides.apply(&mut data);
+
         self.maintenance.store(data.maintenance, Ordering::Relaxed);
         self.whitelist.store(data.whitelist, Ordering::Relaxed);
         self.webhook_present.store(!data.admin_webhook_url.is_empty(), Ordering::Relaxed);
 
-        let mut issuer = self.token_issuer.lock();
+        *self.central_conf.lock() = data;
+    }
+
+    /// Overrides a config value until `expires_at` (seconds since unix epoch), see `ConfigOverrideManager`.
+    pub fn set_config_override(&self, key: ConfigOverrideKey, value: u32, expires_at: i64, author: String) {
+        let mut conf = self.central_conf.lock();
+        self.config_overrides.set(&mut conf, key, value, expires_at, author);
+        self.maintenance.store(conf.maintenance, Ordering::Relaxed);
+    }
 
-        issuer.set_expiration_period(Duration::from_secs(data.token_expiry));
-        issuer.set_secret_key(&data.secret_key2);
+    /// Removes the override of a config value, returns the removed override if there was one.
+    pub fn remove_config_override(&self, key: ConfigOverrideKey) -> Option<ConfigOverride> {
+        let mut conf = self.central_conf.lock();
+        let removed = self.config_overrides.remove(&mut conf, key);
+        self.maintenance.store(conf.maintenance, Ordering::Relaxed);
 
-        *self.central_conf.lock() = data;
+        removed
+    }
+
+    /// Removes overrides that expired by `now`, returns the removed overrides.
+    pub fn expire_config_overrides(&self, now: i64) -> Vec<ConfigOverride> {
+        if self.config_overrides.is_empty() {
+            return Vec::new();
+        }
+
+        let mut conf = self.central_conf.lock();
+        let expired = self.config_overrides.expire(&mut conf, now);
+        self.maintenance.store(conf.maintenance, Ordering::Relaxed);
+
+        expired
     }
 
     // other web requests
     pub async fn get_user_data(&self, player: &str) -> Result<UserEntry> {
+        chaos::delay_bridge().await;
+
+        let json = self.json_format.load(Ordering::Relaxed);
+
         let response = self
             .http_client
-            .get(format!("{}gs/user/{}", self.central_url, player))
-            .header("Authorization", self.central_pw.clone())
+            .get(format!("{}{}/user/{}", self.central_url, if json { "gsp" } else { "gs" }, player))
+            .header("Authorization", self.central_pw.expose())
             .send()
             .await?;
 
@@ -181,6 +268,11 @@ impl CentralBridge {
         }
 
         let config = response.bytes().await?;
+
+        if json {
+            return Ok(serde_json::from_slice(&config)?);
+        }
+
         let mut reader = ByteReader::from_bytes(&config);
         reader.validate_self_checksum()?;
 
@@ -188,6 +280,12 @@ impl CentralBridge {
     }
 
     pub async fn update_user_data(&self, user: &UserEntry) -> Result<()> {
+        chaos::delay_bridge().await;
+
+        if self.json_format.load(Ordering::Relaxed) {
+            return self.update_user_data_json(user).await;
+        }
+
         let mut buffer = ByteBuffer::with_capacity(user.encoded_size() + size_of_types!(u32));
 
         buffer.write_value(user);
@@ -198,7 +296,7 @@ impl CentralBridge {
         let response = self
             .http_client
             .post(format!("{}gs/user/update", self.central_url))
-            .header("Authorization", self.central_pw.clone())
+            .header("Authorization", self.central_pw.expose())
             .body(body)
             .send()
             .await?;
@@ -213,6 +311,316 @@ impl CentralBridge {
         Ok(())
     }
 
+    /// Same as `update_user_data`, for central servers that use JSON.
+    async fn update_user_data_json(&self, user: &UserEntry) -> Result<()> {
+        let response = self
+            .http_client
+            .post(format!("{}gsp/user/update", self.central_url))
+            .header("Authorization", self.central_pw.expose())
+            .header("Content-Type", "application/json")
+            .body(serde_json::to_vec(user)?)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        Ok(())
+    }
+
+    /// Stores the ticket on the central server and returns the one-time token that another game server can redeem it with.
+    pub async fn create_transfer_token(&self, ticket: &TransferTicket) -> Result<String> {
+        chaos::delay_bridge().await;
+
+        let mut buffer = ByteBuffer::with_capacity(ticket.encoded_size() + size_of_types!(u32));
+
+        buffer.write_value(ticket);
+        buffer.append_self_checksum();
+
+        let response = self
+            .http_client
+            .post(format!("{}gs/transfer", self.central_url))
+            .header("Authorization", self.central_pw.expose())
+            .body(buffer.into_vec())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        Ok(response.text().await?)
+    }
+
+    /// Redeems a transfer token created by another game server. Fails if the token was already used or has expired.
+    pub async fn redeem_transfer_token(&self, token: &str) -> Result<TransferTicket> {
+        chaos::delay_bridge().await;
+
+        let response = self
+            .http_client
+            .post(format!("{}gs/transfer/redeem/{}", self.central_url, token))
+            .header("Authorization", self.central_pw.expose())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        let data = response.bytes().await?;
+        let mut reader = ByteReader::from_bytes(&data);
+        reader.validate_self_checksum()?;
+
+        Ok(reader.read_value::<TransferTicket>()?)
+    }
+
+    /// Tells the central server that a player logged in, so that the session shows up in their session list.
+    pub async fn start_session(&self, session: &AccountSession) -> Result<()> {
+        chaos::delay_bridge().await;
+
+        let mut buffer = ByteBuffer::with_capacity(session.encoded_size() + size_of_types!(u32));
+
+        buffer.write_value(session);
+        buffer.append_self_checksum();
+
+        let response = self
+            .http_client
+            .post(format!("{}gs/sessions/start", self.central_url))
+            .header("Authorization", self.central_pw.expose())
+            .body(buffer.into_vec())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        Ok(())
+    }
+
+    /// Tells the central server that the session ended.
+    pub async fn end_session(&self, account_id: i32, session_id: u64) -> Result<()> {
+        chaos::delay_bridge().await;
+
+        let response = self
+            .http_client
+            .post(format!("{}gs/sessions/end/{}/{}", self.central_url, account_id, session_id))
+            .header("Authorization", self.central_pw.expose())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        Ok(())
+    }
+
+    /// Tells the central server that this server is going down, ending the sessions of the players that were still on it.
+    pub async fn notify_shutdown(&self, sessions: &[AccountSession]) -> Result<()> {
+        chaos::delay_bridge().await;
+
+        let mut buffer = ByteBuffer::with_capacity(sessions.encoded_size() + size_of_types!(u32));
+
+        buffer.write_value(sessions);
+        buffer.append_self_checksum();
+
+        let response = self
+            .http_client
+            .post(format!("{}gs/shutdown", self.central_url))
+            .header("Authorization", self.central_pw.expose())
+            .body(buffer.into_vec())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        Ok(())
+    }
+
+    /// Fetches the active and recent sessions of the account, across all game servers.
+    pub async fn get_sessions(&self, account_id: i32) -> Result<Vec<AccountSession>> {
+        chaos::delay_bridge().await;
+
+        let response = self
+            .http_client
+            .get(format!("{}gs/sessions/{}", self.central_url, account_id))
+            .header("Authorization", self.central_pw.expose())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        let data = response.bytes().await?;
+        let mut reader = ByteReader::from_bytes(&data);
+        reader.validate_self_checksum()?;
+
+        Ok(reader.read_value::<Vec<AccountSession>>()?)
+    }
+
+    /// Stores a new report on the central server and returns its ID.
+    pub async fn create_report(&self, report: &PlayerReport) -> Result<i64> {
+        chaos::delay_bridge().await;
+
+        let mut buffer = ByteBuffer::with_capacity(report.encoded_size() + size_of_types!(u32));
+
+        buffer.write_value(report);
+        buffer.append_self_checksum();
+
+        let response = self
+            .http_client
+            .post(format!("{}gs/reports/create", self.central_url))
+            .header("Authorization", self.central_pw.expose())
+            .body(buffer.into_vec())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        let text = response.text().await?;
+        text.trim()
+            .parse()
+            .map_err(|_| CentralBridgeError::Other(format!("central server sent an invalid report ID: {text}")))
+    }
+
+    /// Attaches a sealed voice capture to a report that was just created.
+    /// The capture is sent as the raw body, it can be far larger than an encoded `Vec<u8>` allows.
+    pub async fn set_report_voice_capture(&self, report_id: i64, capture: Vec<u8>) -> Result<()> {
+        chaos::delay_bridge().await;
+
+        let response = self
+            .http_client
+            .post(format!("{}gs/reports/{report_id}/voice", self.central_url))
+            .header("Authorization", self.central_pw.expose())
+            .body(capture)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        Ok(())
+    }
+
+    /// Waits until the role definitions on the central server are different from `revision`, and returns the new ones.
+    /// If they don't change for a while, the central server responds with the current ones anyway.
+    pub async fn wait_for_roles(&self, revision: u64) -> Result<RoleDefinitions> {
+        chaos::delay_bridge().await;
+
+        let response = self
+            .http_client
+            .get(format!("{}gs/roles?revision={revision}", self.central_url))
+            .header("Authorization", self.central_pw.expose())
+            .timeout(ROLES_WAIT_TIMEOUT)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        let data = response.bytes().await?;
+        let mut reader = ByteReader::from_bytes(&data);
+        reader.validate_self_checksum()?;
+
+        Ok(reader.read_value::<RoleDefinitions>()?)
+    }
+
+    /// Fetches the reports that are open or claimed.
+    pub async fn get_pending_reports(&self) -> Result<Vec<PlayerReport>> {
+        chaos::delay_bridge().await;
+
+        let response = self
+            .http_client
+            .get(format!("{}gs/reports", self.central_url))
+            .header("Authorization", self.central_pw.expose())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        let data = response.bytes().await?;
+        let mut reader = ByteReader::from_bytes(&data);
+        reader.validate_self_checksum()?;
+
+        Ok(reader.read_value::<Vec<PlayerReport>>()?)
+    }
+
+    /// Asks the central server to change the state of a report, returns the report as it is afterwards.
+    /// If the moderator wasn't allowed to make the change, the report is returned unchanged.
+    pub async fn update_report_state(&self, change: &ReportStateChange) -> Result<PlayerReport> {
+        chaos::delay_bridge().await;
+
+        let mut buffer = ByteBuffer::with_capacity(change.encoded_size() + size_of_types!(u32));
+
+        buffer.write_value(change);
+        buffer.append_self_checksum();
+
+        let response = self
+            .http_client
+            .post(format!("{}gs/reports/state", self.central_url))
+            .header("Authorization", self.central_pw.expose())
+            .body(buffer.into_vec())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        let data = response.bytes().await?;
+        let mut reader = ByteReader::from_bytes(&data);
+        reader.validate_self_checksum()?;
+
+        Ok(reader.read_value::<PlayerReport>()?)
+    }
+
     #[inline]
     pub async fn send_webhook_message(&self, message: WebhookMessage) -> Result<()> {
         let messages = [message];
@@ -222,6 +630,12 @@ impl CentralBridge {
     // not really bridge but it was making web requests which is sorta related i guess
     pub async fn send_webhook_messages(&self, messages: &[WebhookMessage]) -> Result<()> {
         let url = self.central_conf.lock().admin_webhook_url.clone();
+        self.send_webhook_messages_to(url.expose(), messages).await
+    }
+
+    /// Sends the messages to any webhook, rather than the admin webhook. Used for the webhooks that room owners set up.
+    pub async fn send_webhook_messages_to(&self, url: &str, messages: &[WebhookMessage]) -> Result<()> {
+        chaos::delay_bridge().await;
 
         let mut embeds = Vec::new();
 
@@ -237,13 +651,31 @@ impl CentralBridge {
             embeds,
         };
 
+        // reqwest errors include the url, which contains the webhook token
         let response = self
             .http_client
             .post(url)
             .header("Content-Type", "application/json")
             .body(serde_json::to_string(&opts).map_err(|e| CentralBridgeError::Other(e.to_string()))?)
             .send()
-            .await?;
+            .await
+            .map_err(reqwest::Error::without_url)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::WebhookError((status, message)));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the webhook exists, before anything gets sent to it.
+    pub async fn verify_webhook(&self, url: &str) -> Result<()> {
+        chaos::delay_bridge().await;
+
+        let response = self.http_client.get(url).send().await.map_err(reqwest::Error::without_url)?;
 
         let status = response.status();
         if !status.is_success() {
@@ -0,0 +1,140 @@
+//! Chaos switches for resilience testing. Only functional when the server is built with the `chaos` feature,
+//! otherwise every check is a no-op that never triggers.
+
+#[cfg(feature = "chaos")]
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use std::time::Duration;
+
+#[cfg(feature = "chaos")]
+use globed_shared::rand::{self, Rng};
+#[cfg(feature = "chaos")]
+use globed_shared::warn;
+
+#[cfg(feature = "chaos")]
+use crate::tokio;
+
+/// How often a client thread rolls for a spurious disconnect.
+pub const DISCONNECT_ROLL_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Default)]
+pub struct ChaosSettings {
+    /// maximum random delay added before every request to the central server, in milliseconds
+    pub bridge_delay: u32,
+    /// chance of a broadcast to a single player being dropped, in percent
+    pub broadcast_drop_chance: u8,
+    /// chance of a client thread being disconnected every `DISCONNECT_ROLL_INTERVAL`, in percent
+    pub disconnect_chance: u8,
+}
+
+#[cfg(feature = "chaos")]
+static BRIDGE_DELAY: AtomicU32 = AtomicU32::new(0);
+#[cfg(feature = "chaos")]
+static BROADCAST_DROP_CHANCE: AtomicU8 = AtomicU8::new(0);
+#[cfg(feature = "chaos")]
+static DISCONNECT_CHANCE: AtomicU8 = AtomicU8::new(0);
+
+/// Whether the server was built with chaos support.
+pub const fn is_supported() -> bool {
+    cfg!(feature = "chaos")
+}
+
+#[cfg(feature = "chaos")]
+pub fn set(settings: ChaosSettings) {
+    BRIDGE_DELAY.store(settings.bridge_delay, Ordering::Relaxed);
+    BROADCAST_DROP_CHANCE.store(settings.broadcast_drop_chance.min(100), Ordering::Relaxed);
+    DISCONNECT_CHANCE.store(settings.disconnect_chance.min(100), Ordering::Relaxed);
+
+    if settings.bridge_delay != 0 || settings.broadcast_drop_chance != 0 || settings.disconnect_chance != 0 {
+        warn!(
+            "Chaos enabled: bridge delay up to {}ms, {}% broadcast drop chance, {}% disconnect chance",
+            settings.bridge_delay,
+            settings.broadcast_drop_chance.min(100),
+            settings.disconnect_chance.min(100)
+        );
+    } else {
+        warn!("Chaos disabled");
+    }
+}
+
+#[cfg(not(feature = "chaos"))]
+pub fn set(_settings: ChaosSettings) {}
+
+#[cfg(feature = "chaos")]
+pub fn get() -> ChaosSettings {
+    ChaosSettings {
+        bridge_delay: BRIDGE_DELAY.load(Ordering::Relaxed),
+        broadcast_drop_chance: BROADCAST_DROP_CHANCE.load(Ordering::Relaxed),
+        disconnect_chance: DISCONNECT_CHANCE.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(not(feature = "chaos"))]
+pub fn get() -> ChaosSettings {
+    ChaosSettings::default()
+}
+
+/// Reads the initial settings from `GLOBED_GS_CHAOS_BRIDGE_DELAY`, `GLOBED_GS_CHAOS_BROADCAST_DROP` and `GLOBED_GS_CHAOS_DISCONNECT`.
+pub fn init_from_env() {
+    fn var<T: std::str::FromStr + Default>(name: &str) -> T {
+        std::env::var(name).ok().and_then(|x| x.parse().ok()).unwrap_or_default()
+    }
+
+    let settings = ChaosSettings {
+        bridge_delay: var("GLOBED_GS_CHAOS_BRIDGE_DELAY"),
+        broadcast_drop_chance: var("GLOBED_GS_CHAOS_BROADCAST_DROP"),
+        disconnect_chance: var("GLOBED_GS_CHAOS_DISCONNECT"),
+    };
+
+    if settings.bridge_delay != 0 || settings.broadcast_drop_chance != 0 || settings.disconnect_chance != 0 {
+        if is_supported() {
+            set(settings);
+        } else {
+            globed_shared::warn!("chaos environment variables are set, but the server was built without the `chaos` feature, ignoring");
+        }
+    }
+}
+
+#[cfg(feature = "chaos")]
+fn roll(percent: u8) -> bool {
+    percent != 0 && rand::thread_rng().gen_range(0..100u8) < percent
+}
+
+/// Sleeps for a random amount of time before a request to the central server.
+#[cfg(feature = "chaos")]
+pub async fn delay_bridge() {
+    let max = BRIDGE_DELAY.load(Ordering::Relaxed);
+    if max == 0 {
+        return;
+    }
+
+    let delay = rand::thread_rng().gen_range(0..=max);
+    tokio::time::sleep(Duration::from_millis(u64::from(delay))).await;
+}
+
+#[cfg(not(feature = "chaos"))]
+#[inline]
+pub async fn delay_bridge() {}
+
+/// Returns `true` if a broadcast to a single player should be dropped.
+#[cfg(feature = "chaos")]
+pub fn should_drop_broadcast() -> bool {
+    roll(BROADCAST_DROP_CHANCE.load(Ordering::Relaxed))
+}
+
+#[cfg(not(feature = "chaos"))]
+#[inline]
+pub const fn should_drop_broadcast() -> bool {
+    false
+}
+
+/// Returns `true` if a client thread should be spuriously disconnected, called every `DISCONNECT_ROLL_INTERVAL`.
+#[cfg(feature = "chaos")]
+pub fn should_disconnect() -> bool {
+    roll(DISCONNECT_CHANCE.load(Ordering::Relaxed))
+}
+
+#[cfg(not(feature = "chaos"))]
+#[inline]
+pub const fn should_disconnect() -> bool {
+    false
+}
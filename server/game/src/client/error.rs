@@ -16,6 +16,7 @@ pub enum PacketHandlingError {
     NoHandler(u16),                        // no handler found for this packet ID
     WebRequestError(reqwest::Error),       // error making a web request to the central server
     UnexpectedPlayerData,                  // client sent PlayerDataPacket or PlayerMetadataPacket outside of a level
+    InvalidLevelId,                        // client tried to join a level with the ID 0
     SystemTimeError(SystemTimeError),      // clock went backwards..?
     SocketSendFailed(std::io::Error),      // failed to send data on a socket due to an IO error
     SocketWouldBlock,                      // failed to send data on a socket because operation would block
@@ -82,6 +83,7 @@ impl Display for PacketHandlingError {
             Self::NoHandler(id) => f.write_fmt(format_args!("no packet handler for packet ID {id}")),
             Self::WebRequestError(msg) => f.write_fmt(format_args!("web request error: {msg}")),
             Self::UnexpectedPlayerData => f.write_str("received PlayerDataPacket or SyncPlayerMetadataPacket when not in a level"),
+            Self::InvalidLevelId => f.write_str("tried to join a level with the ID 0"),
             Self::SystemTimeError(msg) => f.write_fmt(format_args!("system time error: {msg}")),
             Self::SocketSendFailed(err) => f.write_fmt(format_args!("socket send failed: {err}")),
             Self::SocketWouldBlock => f.write_str("could not do a non-blocking operation on the socket as it would block"),
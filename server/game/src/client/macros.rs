@@ -33,8 +33,8 @@ macro_rules! gs_handler_sync {
 
 /// call disconnect and return from the function
 macro_rules! gs_disconnect {
-    ($self:ident, $msg:expr) => {
-        $self.kick($msg).await?;
+    ($self:ident, $reason:expr, $msg:expr) => {
+        $self.kick($reason, $msg).await?;
         return Ok(());
     };
 }
@@ -47,17 +47,23 @@ macro_rules! gs_notice {
     };
 }
 
-/// if the client isn't authenticated, invoke `gs_disconnect!` and exit the handler
-macro_rules! gs_needauth {
-    ($self:ident) => {{
-        let account_id = $self.account_id.load(Ordering::Relaxed);
-
-        if account_id == 0 {
-            gs_disconnect!($self, "unauthorized, please try connecting again");
+/// dispatch table for packets, each packet is listed with the access it requires and the call that handles it.
+/// the access is checked before the handler runs, so handlers don't have to check it themselves.
+macro_rules! gs_dispatch {
+    ($self:ident, $packet_id:expr, { $($pkt:ident, $access:expr => $handler:expr,)* }) => {
+        match $packet_id {
+            $(
+                $pkt::PACKET_ID => {
+                    if !$self.check_packet_access($pkt::PACKET_ID, $access).await? {
+                        return Ok(());
+                    }
+
+                    $handler
+                }
+            )*
+            x => Err(PacketHandlingError::NoHandler(x)),
         }
-
-        account_id
-    }};
+    };
 }
 
 /* data stuff */
@@ -216,10 +222,10 @@ macro_rules! gs_inline_encode {
 
 pub(crate) use gs_alloca_check_size;
 pub(crate) use gs_disconnect;
+pub(crate) use gs_dispatch;
 pub(crate) use gs_handler;
 pub(crate) use gs_handler_sync;
 pub(crate) use gs_inline_encode;
-pub(crate) use gs_needauth;
 pub(crate) use gs_with_alloca;
 pub(crate) use gs_with_alloca_guarded;
 pub(crate) use make_uninit;
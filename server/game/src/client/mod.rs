@@ -3,6 +3,7 @@ pub mod macros;
 pub mod socket;
 pub mod state;
 pub mod thread;
+pub mod transport;
 pub mod unauthorized;
 
 pub use error::{PacketHandlingError, Result};
@@ -10,4 +11,5 @@ pub use macros::*;
 pub use socket::ClientSocket;
 pub use state::{AtomicClientThreadState, ClientThreadState};
 pub use thread::{ClientThread, ServerThreadMessage};
+pub use transport::ClientStream;
 pub use unauthorized::{UnauthorizedThread, UnauthorizedThreadOutcome};
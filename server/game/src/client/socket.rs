@@ -1,36 +1,54 @@
 use std::{
-    net::{SocketAddr, SocketAddrV4},
-    sync::OnceLock,
-    time::Duration,
+    net::SocketAddr,
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
 };
 
 use crate::tokio::{
     self,
     io::{AsyncReadExt, AsyncWriteExt},
-    net::TcpStream,
 };
 
 #[allow(unused_imports)]
 use globed_shared::{
     crypto_box::{
         aead::{AeadCore, AeadInPlace, OsRng},
-        ChaChaBox,
+        ChaChaBox, SecretKey,
     },
-    trace,
+    trace, warn,
 };
 
 use super::{
     error::{PacketHandlingError, Result},
     macros::*,
+    ClientStream,
+};
+use crate::{
+    data::*,
+    managers::TrafficCounter,
+    server::GameServer,
+    util::{compression, CompressionConfig},
 };
-use crate::{data::*, server::GameServer};
 
 pub struct ClientSocket {
-    pub socket: TcpStream,
-
-    pub tcp_peer: SocketAddrV4,
-    pub udp_peer: Option<SocketAddrV4>,
-    crypto_box: OnceLock<ChaChaBox>,
+    pub socket: ClientStream,
+
+    pub tcp_peer: SocketAddr,
+    /// the address as the udp socket reported it, which is what packets have to be sent to.
+    /// the server keys clients by `canonical_addr` of it, which differs for IPv4 peers on a dual-stack socket
+    pub udp_peer: Option<SocketAddr>,
+    crypto_box: Option<ChaChaBox>,
+    /// the box used before the last rekey and when it was replaced, kept for `PREVIOUS_KEY_LIFETIME`
+    /// so that packets which were in flight during the switch can still be decrypted
+    previous_crypto_box: Option<(ChaChaBox, Instant)>,
+    /// our ephemeral secret key and the time the rekey was requested, while waiting for the client to respond
+    pending_rekey: Option<(SecretKey, Instant)>,
+    key_created_at: Instant,
+    key_bytes: u64,
+    /// algorithm negotiated in the handshake, used for large packets sent over the stream
+    pub compression: CompressionAlgorithm,
+    /// outgoing traffic, shared with the client thread so it can be attributed to the player's room and level
+    pub traffic: Arc<TrafficCounter>,
     game_server: &'static GameServer,
 }
 
@@ -41,13 +59,52 @@ const MAC_SIZE: usize = 16;
 const MAX_PACKET_SIZE: usize = 65536;
 pub const INLINE_BUFFER_SIZE: usize = 164;
 
+/// How long the client has to respond to a rekey request before getting disconnected.
+const REKEY_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long packets encrypted with the key from before a rekey are still accepted.
+const PREVIOUS_KEY_LIFETIME: Duration = Duration::from_secs(30);
+
+/// Returns after how much time and how many bytes of traffic the session key gets replaced, based on
+/// `GLOBED_GS_REKEY_INTERVAL` (in hours, by default 12) and `GLOBED_GS_REKEY_DATA` (in gigabytes, by default 8).
+/// A limit of 0 disables that trigger.
+fn rekey_limits() -> (Option<Duration>, Option<u64>) {
+    static LIMITS: OnceLock<(Option<Duration>, Option<u64>)> = OnceLock::new();
+
+    *LIMITS.get_or_init(|| {
+        fn var(name: &str, default: u64) -> u64 {
+            match std::env::var(name).ok().map(|x| x.parse::<u64>()) {
+                None => default,
+                Some(Ok(x)) => x,
+                Some(Err(_)) => {
+                    warn!("invalid value for {name}, must be a non-negative integer, using the default ({default})");
+                    default
+                }
+            }
+        }
+
+        let hours = var("GLOBED_GS_REKEY_INTERVAL", 12);
+        let gigabytes = var("GLOBED_GS_REKEY_DATA", 8);
+
+        (
+            Some(Duration::from_secs(hours.saturating_mul(3600))).filter(|x| !x.is_zero()),
+            Some(gigabytes.saturating_mul(1024 * 1024 * 1024)).filter(|x| *x != 0),
+        )
+    })
+}
+
 impl ClientSocket {
-    pub fn new(socket: TcpStream, tcp_peer: SocketAddrV4, game_server: &'static GameServer) -> Self {
+    pub fn new(socket: ClientStream, tcp_peer: SocketAddr, game_server: &'static GameServer) -> Self {
         Self {
             socket,
             tcp_peer,
             udp_peer: None,
-            crypto_box: OnceLock::new(),
+            crypto_box: None,
+            previous_crypto_box: None,
+            pending_rekey: None,
+            key_created_at: Instant::now(),
+            key_bytes: 0,
+            compression: CompressionAlgorithm::None,
+            traffic: Arc::new(TrafficCounter::default()),
             game_server,
         }
     }
@@ -67,7 +124,7 @@ impl ClientSocket {
     #[inline]
     pub async fn recv_and_handle<F>(&mut self, bytes: usize, f: F) -> Result<()>
     where
-        F: async FnOnce(&mut [u8]) -> Result<()>,
+        F: AsyncFnOnce(&mut [u8]) -> Result<()>,
     {
         if bytes > MAX_PACKET_SIZE {
             return Err(PacketHandlingError::PacketTooLong(bytes));
@@ -91,32 +148,86 @@ impl ClientSocket {
         f(data).await
     }
 
-    pub fn init_crypto_box(&self, key: &CryptoPublicKey) -> Result<()> {
-        if self.crypto_box.get().is_some() {
+    pub fn init_crypto_box(&mut self, key: &CryptoPublicKey) -> Result<()> {
+        if self.crypto_box.is_some() {
             return Err(PacketHandlingError::WrongCryptoBoxState);
         }
 
-        self.crypto_box.get_or_init(|| ChaChaBox::new(&key.0, &self.game_server.secret_key));
+        self.crypto_box = Some(ChaChaBox::new(&key.0, &self.game_server.secret_key));
+        self.key_created_at = Instant::now();
+        self.key_bytes = 0;
+
+        Ok(())
+    }
+
+    /// Returns whether the current session key has been in use for long enough (or has encrypted enough data) that it should be replaced.
+    pub fn should_rekey(&self) -> bool {
+        if self.crypto_box.is_none() || self.pending_rekey.is_some() {
+            return false;
+        }
+
+        let (interval, data) = rekey_limits();
+
+        interval.is_some_and(|x| self.key_created_at.elapsed() > x) || data.is_some_and(|x| self.key_bytes > x)
+    }
+
+    /// Returns whether a rekey was started, but the client did not respond to it in time.
+    pub fn rekey_timed_out(&self) -> bool {
+        self.pending_rekey.as_ref().is_some_and(|(_, started)| started.elapsed() > REKEY_TIMEOUT)
+    }
+
+    /// Generates a new ephemeral keypair, returns the public key that should be sent to the client in a `RekeyRequestPacket`.
+    pub fn begin_rekey(&mut self) -> CryptoPublicKey {
+        let secret_key = SecretKey::generate(&mut OsRng);
+        let public_key = secret_key.public_key();
+
+        self.pending_rekey = Some((secret_key, Instant::now()));
+
+        public_key.into()
+    }
+
+    /// Switches to the new key after the client responded with its own new public key.
+    /// Every packet sent from now on is encrypted with the new key, while the old key is only kept for decryption.
+    pub fn finish_rekey(&mut self, key: &CryptoPublicKey) -> Result<()> {
+        let Some((secret_key, _)) = self.pending_rekey.take() else {
+            return Err(PacketHandlingError::WrongCryptoBoxState);
+        };
+
+        self.previous_crypto_box = self
+            .crypto_box
+            .replace(ChaChaBox::new(&key.0, &secret_key))
+            .map(|old| (old, Instant::now()));
+        self.key_created_at = Instant::now();
+        self.key_bytes = 0;
 
         Ok(())
     }
 
-    pub fn set_udp_peer(&mut self, udp_peer: SocketAddrV4) {
+    /// Forgets the key from before the last rekey, once packets encrypted with it can't be in flight anymore.
+    pub fn expire_previous_key(&mut self) {
+        if self
+            .previous_crypto_box
+            .as_ref()
+            .is_some_and(|(_, replaced)| replaced.elapsed() > PREVIOUS_KEY_LIFETIME)
+        {
+            self.previous_crypto_box = None;
+        }
+    }
+
+    pub fn set_udp_peer(&mut self, udp_peer: SocketAddr) {
         self.udp_peer.replace(udp_peer);
     }
 
-    pub fn decrypt<'a>(&self, message: &'a mut [u8]) -> Result<ByteReader<'a>> {
+    pub fn decrypt<'a>(&mut self, message: &'a mut [u8]) -> Result<ByteReader<'a>> {
         if message.len() < PacketHeader::SIZE + NONCE_SIZE + MAC_SIZE {
             return Err(PacketHandlingError::MalformedCiphertext);
         }
 
-        let cbox = self.crypto_box.get();
+        self.expire_previous_key();
 
-        if cbox.is_none() {
+        let Some(cbox) = self.crypto_box.as_ref() else {
             return Err(PacketHandlingError::WrongCryptoBoxState);
-        }
-
-        let cbox = cbox.unwrap();
+        };
 
         let nonce_start = PacketHeader::SIZE;
         let mac_start = nonce_start + NONCE_SIZE;
@@ -130,9 +241,17 @@ impl ClientSocket {
         mac.clone_from_slice(&message[mac_start..ciphertext_start]);
         let mac = mac.into();
 
+        // a failed decryption leaves the buffer untouched, so we can retry with the previous key
         cbox.decrypt_in_place_detached(&nonce, b"", &mut message[ciphertext_start..], &mac)
+            .or_else(|e| {
+                self.previous_crypto_box.as_ref().map_or(Err(e), |(prev, _)| {
+                    prev.decrypt_in_place_detached(&nonce, b"", &mut message[ciphertext_start..], &mac)
+                })
+            })
             .map_err(|_| PacketHandlingError::DecryptionError)?;
 
+        self.key_bytes += message.len() as u64;
+
         Ok(ByteReader::from_bytes(&message[ciphertext_start..]))
     }
 
@@ -189,9 +308,20 @@ impl ClientSocket {
             self.print_packet::<P>(true, Some(if P::ENCRYPTED { "fast + encrypted" } else { "fast" }));
         }
 
+        // tunneled clients can't receive udp packets, so they get them through the stream, unless a datagram can be sent instead
+        let use_tcp = P::SHOULD_USE_TCP || !self.socket.can_send_datagram(PacketHeader::SIZE + NONCE_SIZE + MAC_SIZE + packet_size);
+
+        if use_tcp && self.compression != CompressionAlgorithm::None && packet_size >= CompressionConfig::get().threshold {
+            self.send_packet_compressed::<P, _>(packet_size, encode_fn).await?;
+            self.socket.flush().await?;
+            self.traffic.record_packet();
+
+            return Ok(());
+        }
+
         if P::ENCRYPTED {
             // gs_inline_encode! doesn't work here because the borrow checker is silly :(
-            let header_start = if P::SHOULD_USE_TCP { size_of_types!(u32) } else { 0usize };
+            let header_start = if use_tcp { size_of_types!(u32) } else { 0usize };
 
             let nonce_start = header_start + PacketHeader::SIZE;
             let mac_start = nonce_start + NONCE_SIZE;
@@ -203,7 +333,7 @@ impl ClientSocket {
             let to_send: Result<Option<Vec<u8>>> = gs_with_alloca!(total_size, data, {
                 let mut buf = FastByteBuffer::new(data);
 
-                if P::SHOULD_USE_TCP {
+                if use_tcp {
                     // reserve space for packet length
                     buf.write_u32(0);
                 }
@@ -219,7 +349,7 @@ impl ClientSocket {
                 let raw_data_end = raw_data_start + buf.len();
 
                 // this unwrap is safe, as an encrypted packet can only be sent downstream after the handshake is established.
-                let cbox = self.crypto_box.get().unwrap();
+                let cbox = self.crypto_box.as_ref().unwrap();
 
                 // encrypt in place
                 let nonce = ChaChaBox::generate_nonce(&mut OsRng);
@@ -227,13 +357,15 @@ impl ClientSocket {
                     .encrypt_in_place_detached(&nonce, b"", &mut data[raw_data_start..raw_data_end])
                     .map_err(|_| PacketHandlingError::EncryptionError)?;
 
+                self.key_bytes += (raw_data_end - header_start) as u64;
+
                 // prepend the nonces
-                data[nonce_start..mac_start].copy_from_slice(nonce.as_slice());
+                data[nonce_start..mac_start].copy_from_slice(&nonce);
 
                 // prepend the mac tag
                 data[mac_start..raw_data_start].copy_from_slice(&tag);
 
-                if P::SHOULD_USE_TCP {
+                if use_tcp {
                     // write total packet length
                     let packet_len = (raw_data_end - header_start) as u32;
                     data[..size_of_types!(u32)].copy_from_slice(&packet_len.to_be_bytes());
@@ -242,7 +374,7 @@ impl ClientSocket {
                 // we try a non-blocking send if we can, otherwise fallback to a Vec<u8> and an async send
                 let send_data = &data[..raw_data_end];
 
-                let res = if P::SHOULD_USE_TCP {
+                let res = if use_tcp {
                     self.send_buffer_tcp_immediate(send_data)
                 } else {
                     self.send_buffer_udp_immediate(send_data)
@@ -263,34 +395,90 @@ impl ClientSocket {
             });
 
             if let Some(to_send) = to_send? {
-                if P::SHOULD_USE_TCP {
+                if use_tcp {
                     self.send_buffer_tcp(&to_send).await?;
                 } else {
                     self.send_buffer_udp(&to_send).await?;
                 }
             }
         } else {
-            let prefix_sz = if P::SHOULD_USE_TCP { size_of_types!(u32) } else { 0usize };
+            let prefix_sz = if use_tcp { size_of_types!(u32) } else { 0usize };
 
-            gs_inline_encode!(self, prefix_sz + PacketHeader::SIZE + packet_size, buf, P::SHOULD_USE_TCP, {
+            gs_inline_encode!(self, prefix_sz + PacketHeader::SIZE + packet_size, buf, use_tcp, {
                 buf.write_packet_header::<P>();
                 encode_fn(&mut buf);
             });
         }
 
-        if P::SHOULD_USE_TCP {
+        if use_tcp {
             self.socket.flush().await?;
         }
 
+        self.traffic.record_packet();
+
         Ok(())
     }
 
+    /// encodes the packet into a heap buffer and sends it over the stream, compressed if that makes it any smaller.
+    /// used for large packets only, so the extra allocations don't matter much.
+    async fn send_packet_compressed<P: Packet, F>(&mut self, packet_size: usize, encode_fn: F) -> Result<()>
+    where
+        F: FnOnce(&mut FastByteBuffer),
+    {
+        let mut raw = vec![0u8; packet_size];
+        let mut buf = FastByteBuffer::new(&mut raw);
+        encode_fn(&mut buf);
+        let raw_len = buf.len();
+        raw.truncate(raw_len);
+
+        let (mut payload, compressed) = match compression::compress(self.compression, &raw) {
+            Some(x) if x.len() < raw.len() => (x, true),
+            _ => (raw, false),
+        };
+
+        let header = PacketHeader {
+            packet_id: P::PACKET_ID,
+            encrypted: P::ENCRYPTED,
+            compressed,
+        };
+
+        let encryption_size = if P::ENCRYPTED { NONCE_SIZE + MAC_SIZE } else { 0 };
+        let packet_len = PacketHeader::SIZE + encryption_size + payload.len();
+
+        let mut data = vec![0u8; size_of_types!(u32) + PacketHeader::SIZE];
+        let mut buf = FastByteBuffer::new(&mut data);
+        buf.write_u32(packet_len as u32);
+        buf.write_value(&header);
+
+        if P::ENCRYPTED {
+            // this unwrap is safe, as an encrypted packet can only be sent downstream after the handshake is established.
+            let cbox = self.crypto_box.as_ref().unwrap();
+
+            let nonce = ChaChaBox::generate_nonce(&mut OsRng);
+            let tag = cbox
+                .encrypt_in_place_detached(&nonce, b"", &mut payload)
+                .map_err(|_| PacketHandlingError::EncryptionError)?;
+
+            self.key_bytes += packet_len as u64;
+
+            data.extend_from_slice(&nonce);
+            data.extend_from_slice(&tag);
+        }
+
+        data.extend_from_slice(&payload);
+
+        self.send_buffer_tcp(&data).await
+    }
+
     /// sends a buffer to our peer via the tcp socket
     async fn send_buffer_tcp(&mut self, buffer: &[u8]) -> Result<()> {
         let result = tokio::time::timeout(Duration::from_secs(5), self.socket.write_all(buffer)).await;
 
         match result {
-            Ok(Ok(())) => Ok(()),
+            Ok(Ok(())) => {
+                self.traffic.record_bytes(buffer.len());
+                Ok(())
+            }
             Ok(Err(err)) => Err(PacketHandlingError::SocketSendFailed(err)),
             Err(_) => {
                 // timed out
@@ -307,7 +495,10 @@ impl ClientSocket {
         let result = self.socket.try_write(buffer);
 
         match result {
-            Ok(x) => Ok(x),
+            Ok(x) => {
+                self.traffic.record_bytes(x);
+                Ok(x)
+            }
             Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => Err(PacketHandlingError::SocketWouldBlock),
             Err(e) => Err(e.into()),
         }
@@ -315,13 +506,19 @@ impl ClientSocket {
 
     /// sends a buffer to our peer via the udp socket
     async fn send_buffer_udp(&self, buffer: &[u8]) -> Result<()> {
+        // quic datagrams are never waited on
+        #[cfg(feature = "quic")]
+        if let ClientStream::Quic(_) = &self.socket {
+            return self.send_buffer_udp_immediate(buffer).map(|_| ());
+        }
+
         match self.udp_peer.as_ref() {
             Some(udp_peer) => self
                 .game_server
                 .udp_socket
                 .send_to(buffer, udp_peer)
                 .await
-                .map(|_size| ())
+                .map(|size| self.traffic.record_bytes(size))
                 .map_err(PacketHandlingError::SocketSendFailed),
 
             None => Err(PacketHandlingError::UnableToSendUdp),
@@ -330,14 +527,30 @@ impl ClientSocket {
 
     /// non async version of `send_buffer_udp`
     fn send_buffer_udp_immediate(&self, buffer: &[u8]) -> Result<usize> {
+        #[cfg(feature = "quic")]
+        if let ClientStream::Quic(stream) = &self.socket {
+            return stream
+                .send_datagram(buffer)
+                .map(|()| {
+                    self.traffic.record_bytes(buffer.len());
+                    buffer.len()
+                })
+                .map_err(PacketHandlingError::SocketSendFailed);
+        }
+
         match self.udp_peer.as_ref() {
-            Some(udp_peer) => self.game_server.udp_socket.try_send_to(buffer, SocketAddr::V4(*udp_peer)).map_err(|e| {
-                if e.kind() == std::io::ErrorKind::WouldBlock {
-                    PacketHandlingError::SocketWouldBlock
-                } else {
-                    PacketHandlingError::SocketSendFailed(e)
-                }
-            }),
+            Some(udp_peer) => self
+                .game_server
+                .udp_socket
+                .try_send_to(buffer, *udp_peer)
+                .inspect(|size| self.traffic.record_bytes(*size))
+                .map_err(|e| {
+                    if e.kind() == std::io::ErrorKind::WouldBlock {
+                        PacketHandlingError::SocketWouldBlock
+                    } else {
+                        PacketHandlingError::SocketSendFailed(e)
+                    }
+                }),
 
             None => Err(PacketHandlingError::UnableToSendUdp),
         }
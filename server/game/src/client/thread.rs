@@ -1,9 +1,8 @@
 use std::{
-    collections::VecDeque,
     io::ErrorKind,
-    net::SocketAddrV4,
+    net::SocketAddr,
     sync::{
-        atomic::{AtomicBool, AtomicI32, AtomicU16, AtomicU32, Ordering},
+        atomic::{AtomicBool, AtomicI32, AtomicU16, AtomicU32, AtomicU8, Ordering},
         Arc,
     },
     time::Duration,
@@ -14,15 +13,21 @@ use crate::tokio::{
     sync::{Mutex, Notify},
 };
 use esp::ByteReader;
-use globed_shared::{logger::*, SyncMutex, UserEntry};
-use handlers::game::MAX_VOICE_PACKET_SIZE;
+use globed_shared::{logger::*, unix_timestamp, RoomQuotaLimits, SyncMutex, TrustTier, UserEntry};
+use handlers::{admin::AdminPerm, game::MAX_VOICE_PACKET_SIZE};
 use tokio::time::Instant;
 
 use crate::{
+    chaos,
     data::*,
-    managers::ComputedRole,
+    managers::TrafficCounter,
+    safe_mode,
     server::GameServer,
-    util::{LockfreeMutCell, SimpleRateLimiter},
+    util::{
+        message_queue_capacity, AfkTracker, BackpressureTracker, BudgetLimits, BudgetVerdict, ClassifiedMessage, LatencyEstimator, LevelHistory,
+        LockfreeMutCell, LossEstimator, MessageClass, MessageQueue, PacketBudget, PacketCapture, PacketCategory, SimpleRateLimiter, StateChecker,
+        VoiceCapture,
+    },
 };
 
 pub use super::*;
@@ -31,6 +36,8 @@ pub mod handlers;
 
 pub const INLINE_BUFFER_SIZE: usize = 164;
 pub const THREAD_MICRO_TIMEOUT: Duration = Duration::from_secs(30);
+/// once this many messages are waiting to be handled, the client is considered saturated
+const SATURATED_QUEUE_LEN: usize = 32;
 
 #[derive(Clone)]
 pub enum ServerThreadMessage {
@@ -38,13 +45,66 @@ pub enum ServerThreadMessage {
     Packet(Vec<u8>),
     BroadcastVoice(Arc<VoiceBroadcastPacket>),
     BroadcastText(ChatMessageBroadcastPacket),
+    BroadcastTextDeletion(ChatMessageDeletedPacket),
+    ChatMention(ChatMentionPacket),
     BroadcastNotice(ServerNoticePacket),
     BroadcastInvite(RoomInvitePacket),
     BroadcastRoomInfo(RoomInfoPacket),
+    /// a moderator of the room removed us from it
+    RoomKick(u32),
+    BroadcastProfileChange(PlayerProfileChangedPacket),
+    /// a chat message from a room or level a moderator is following
+    ChatTail(AdminChatTailPacket),
     BroadcastBan(ServerBannedPacket),
     BroadcastMute(ServerMutedPacket),
     BroadcastRoleChange(RolesUpdatedPacket),
-    TerminationNotice(FastString),
+    TerminationNotice(DisconnectReason, FastString),
+    DuplicateLogin(DuplicateLoginNotice),
+    Transfer(ServerTransferPacket),
+    Restarting(ServerRestartingPacket),
+    LevelSnapshot,
+    LevelBlocked(LevelId),
+    /// a countdown on the level that ends at the given moment
+    RoomCountdown(LevelId, std::time::Instant),
+    /// another player on the level completed it
+    LevelCompletion(LevelCompletedBroadcastPacket),
+    /// an event another player on the level sent
+    LevelEvent(LevelEventBroadcastPacket),
+    /// full rate data of the player we are spectating
+    SpectatedData(SpectatedPlayerDataPacket),
+    /// someone started or stopped spectating us, queued through `ClientThread::mark_spectators_changed` so at most one is pending
+    SpectatorsChanged,
+    /// the presence of an account we are subscribed to or have on our friend list has changed
+    PresenceUpdate(i32),
+}
+
+impl ClassifiedMessage for ServerThreadMessage {
+    fn class(&self) -> MessageClass {
+        match self {
+            // udp packets from the client, these can be lost on the way here just as well
+            Self::SmallPacket(_) | Self::Packet(_) | Self::BroadcastVoice(_) | Self::LevelSnapshot | Self::SpectatedData(_) | Self::LevelEvent(_) => {
+                MessageClass::State
+            }
+            Self::BroadcastText(_) | Self::BroadcastTextDeletion(_) | Self::ChatMention(_) | Self::ChatTail(_) => MessageClass::Chat,
+            Self::BroadcastInvite(_)
+            | Self::BroadcastRoomInfo(_)
+            | Self::BroadcastProfileChange(_)
+            | Self::LevelCompletion(_)
+            | Self::PresenceUpdate(_) => MessageClass::Info,
+            Self::BroadcastNotice(_)
+            | Self::BroadcastBan(_)
+            | Self::BroadcastMute(_)
+            | Self::BroadcastRoleChange(_)
+            | Self::TerminationNotice(..)
+            | Self::DuplicateLogin(_)
+            | Self::Transfer(_)
+            | Self::Restarting(_)
+            | Self::LevelBlocked(_)
+            | Self::RoomKick(_)
+            | Self::SpectatorsChanged
+            | Self::RoomCountdown(..) => MessageClass::Control,
+        }
+    }
 }
 
 pub struct ClientThread {
@@ -53,6 +113,8 @@ pub struct ClientThread {
     connection_state: AtomicClientThreadState,
 
     pub secret_key: u32,
+    /// identifies this login in the session list of the player
+    pub session_id: u64,
 
     pub account_id: AtomicI32,
     pub level_id: AtomicLevelId,
@@ -61,18 +123,68 @@ pub struct ClientThread {
     pub account_data: SyncMutex<PlayerAccountData>,
     pub user_entry: SyncMutex<UserEntry>,
     pub user_role: SyncMutex<ComputedRole>,
+    pub trust_tier: SyncMutex<TrustTier>,
 
     pub fragmentation_limit: AtomicU16,
+    pub platform: SyncMutex<ClientPlatform>,
 
     pub is_authorized_admin: AtomicBool,
 
     pub is_invisible: AtomicBool,
-
-    message_queue: Mutex<VecDeque<ServerThreadMessage>>,
+    /// the last levels the player was on, shown to moderators
+    pub level_history: SyncMutex<LevelHistory>,
+
+    /// whether the client already sent its feature usage in this session
+    pub reported_usage: AtomicBool,
+
+    /// estimated packet loss in percent
+    pub packet_loss: AtomicU8,
+    /// whether the packet loss is high enough that we push extra level snapshots to this client
+    pub is_lossy: AtomicBool,
+    /// whether the player has not done anything for a while
+    pub is_afk: AtomicBool,
+    /// how many spectators the client was last told it has
+    spectator_count: AtomicU32,
+    /// the chat channel count on the current level the client was last told about, see `ChatChannelPacket`
+    chat_channel_count: AtomicU32,
+    /// whether a `SpectatorsChanged` message is already queued, so a burst of spectate toggles only queues one
+    spectators_dirty: AtomicBool,
+
+    /// when the last chat message was sent, used for the slow mode of rooms
+    pub last_chat_message: SyncMutex<Option<Instant>>,
+    /// when the player was last told that the voice policy of their room doesn't let them talk
+    pub last_voice_notice: SyncMutex<Option<Instant>>,
+    /// when the player last reported someone
+    pub last_report: SyncMutex<Option<Instant>>,
+    /// when the player last asked for their session list
+    pub last_session_list: SyncMutex<Option<Instant>>,
+    /// the last few seconds of voice data, attached to reports against this player
+    pub voice_capture: SyncMutex<VoiceCapture>,
+
+    /// outgoing traffic of the socket, collected by the game server into per room and level stats
+    pub traffic: Arc<TrafficCounter>,
+
+    message_queue: Mutex<MessageQueue<ServerThreadMessage>>,
     message_notify: Notify,
-    rate_limiter: LockfreeMutCell<SimpleRateLimiter>,
+    packet_budget: LockfreeMutCell<PacketBudget>,
     voice_rate_limiter: LockfreeMutCell<SimpleRateLimiter>,
     chat_rate_limiter: Option<LockfreeMutCell<SimpleRateLimiter>>,
+    loss_estimator: LockfreeMutCell<LossEstimator>,
+    latency_estimator: LockfreeMutCell<LatencyEstimator>,
+    /// the last settings hints that were sent to the client, so we only send them again when they change
+    sent_hints: LockfreeMutCell<Option<SettingsHints>>,
+    snapshot_rate_limiter: LockfreeMutCell<SimpleRateLimiter>,
+    level_switch_rate_limiter: LockfreeMutCell<SimpleRateLimiter>,
+    countdown_rate_limiter: LockfreeMutCell<SimpleRateLimiter>,
+    webhook_rate_limiter: LockfreeMutCell<SimpleRateLimiter>,
+    completion_rate_limiter: LockfreeMutCell<SimpleRateLimiter>,
+    level_event_rate_limiter: LockfreeMutCell<SimpleRateLimiter>,
+    spectate_rate_limiter: LockfreeMutCell<SimpleRateLimiter>,
+    backpressure: LockfreeMutCell<BackpressureTracker>,
+    afk_tracker: LockfreeMutCell<AfkTracker>,
+    state_checker: LockfreeMutCell<StateChecker>,
+
+    capture: Option<SyncMutex<PacketCapture>>,
 
     pub destruction_notify: Arc<Notify>,
 }
@@ -82,15 +194,49 @@ pub enum ClientThreadOutcome {
     Disconnect, // downgrade to unauthorized thread, allow the user to reconnect
 }
 
+/// Which packet budget a packet sent by the client counts against.
+fn packet_category(packet_id: u16) -> PacketCategory {
+    match packet_id {
+        PlayerDataPacket::PACKET_ID | PlayerMetadataPacket::PACKET_ID => PacketCategory::State,
+        SpectatorDataPacket::PACKET_ID => PacketCategory::Spectate,
+        ChatMessagePacket::PACKET_ID | DeleteChatMessagePacket::PACKET_ID | SwitchChatChannelPacket::PACKET_ID => PacketCategory::Chat,
+        VoicePacket::PACKET_ID => PacketCategory::Voice,
+        _ => PacketCategory::Control,
+    }
+}
+
+/// What a client needs before a packet it sent is handled, listed for every packet in the dispatch table.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum PacketAccess {
+    /// any client, even if it's not logged in
+    Connected,
+    /// the client must be logged in
+    LoggedIn,
+    /// the client must be logged into the admin panel, with the given permission
+    Admin(AdminPerm),
+}
+
 impl ClientThread {
     pub fn from_unauthorized(thread: UnauthorizedThread) -> Self {
         let game_server = thread.game_server;
 
-        let (rate_limiter, voice_rate_limiter, chat_rate_limiter) = {
+        let (packet_budget, voice_rate_limiter, chat_rate_limiter, snapshot_rate_limiter) = {
             let conf = game_server.bridge.central_conf.lock();
+            let state_limit = conf.tps + 10;
 
             (
-                SimpleRateLimiter::new(conf.tps as usize + 6, Duration::from_millis(900)),
+                // the hard limits are far above what a legit client sends, even right after a lag spike
+                PacketBudget::new(
+                    BudgetLimits {
+                        soft: state_limit,
+                        hard: state_limit * 4,
+                    },
+                    BudgetLimits { soft: 10, hard: 40 },
+                    BudgetLimits { soft: 10, hard: 40 },
+                    BudgetLimits { soft: 30, hard: 120 },
+                    // spectated players send their data every frame, this allows up to 240 fps
+                    BudgetLimits { soft: 240, hard: 960 },
+                ),
                 SimpleRateLimiter::new(5, Duration::from_millis(1000)),
                 if conf.chat_burst_interval != 0 && conf.chat_burst_limit != 0 {
                     Some(SimpleRateLimiter::new(
@@ -100,9 +246,13 @@ impl ClientThread {
                 } else {
                     None
                 },
+                // at most doubles the amount of level data a lossy client gets
+                SimpleRateLimiter::new(conf.tps as usize, Duration::from_millis(1000)),
             )
         };
 
+        // safety: the unauthorized thread no longer runs
+        let traffic = unsafe { thread.socket.get() }.traffic.clone();
         let account_data = std::mem::take(&mut *thread.account_data.lock());
         let user_entry = std::mem::take(&mut *thread.user_entry.lock()).unwrap_or_default();
         let user_role = std::mem::take(&mut *thread.user_role.lock()).unwrap_or_else(|| game_server.state.role_manager.get_default().clone());
@@ -113,6 +263,7 @@ impl ClientThread {
             connection_state: thread.connection_state,
 
             secret_key: thread.secret_key,
+            session_id: thread.session_id,
 
             account_id: thread.account_id,
             level_id: thread.level_id,
@@ -121,20 +272,60 @@ impl ClientThread {
             account_data: SyncMutex::new(account_data),
             user_entry: SyncMutex::new(user_entry),
             user_role: SyncMutex::new(user_role),
+            trust_tier: thread.trust_tier,
 
             fragmentation_limit: thread.fragmentation_limit,
+            platform: thread.platform,
 
             is_authorized_admin: AtomicBool::new(false),
 
             is_invisible: thread.is_invisible,
+            level_history: thread.level_history,
+
+            reported_usage: AtomicBool::new(false),
+
+            packet_loss: AtomicU8::new(0),
+            is_lossy: AtomicBool::new(false),
+            is_afk: AtomicBool::new(false),
+            spectator_count: AtomicU32::new(0),
+            chat_channel_count: AtomicU32::new(0),
+            spectators_dirty: AtomicBool::new(false),
+
+            last_chat_message: SyncMutex::new(None),
+            last_voice_notice: SyncMutex::new(None),
+            last_report: SyncMutex::new(None),
+            last_session_list: SyncMutex::new(None),
+            voice_capture: SyncMutex::new(VoiceCapture::default()),
 
-            message_queue: Mutex::new(VecDeque::new()),
+            traffic,
+
+            message_queue: Mutex::new(MessageQueue::new(message_queue_capacity())),
             message_notify: Notify::new(),
-            rate_limiter: LockfreeMutCell::new(rate_limiter),
+            packet_budget: LockfreeMutCell::new(packet_budget),
             voice_rate_limiter: LockfreeMutCell::new(voice_rate_limiter),
             chat_rate_limiter: chat_rate_limiter.map(LockfreeMutCell::new),
-
-            destruction_notify: thread.destruction_notify
+            loss_estimator: LockfreeMutCell::new(LossEstimator::new()),
+            latency_estimator: LockfreeMutCell::new(LatencyEstimator::new()),
+            sent_hints: LockfreeMutCell::new(None),
+            snapshot_rate_limiter: LockfreeMutCell::new(snapshot_rate_limiter),
+            // plenty for quickly leaving and rejoining a level, but keeps clients from hopping between levels in a loop
+            level_switch_rate_limiter: LockfreeMutCell::new(SimpleRateLimiter::new(8, Duration::from_secs(10))),
+            countdown_rate_limiter: LockfreeMutCell::new(SimpleRateLimiter::new(3, Duration::from_secs(30))),
+            // every attempt makes a request to the webhook to check it
+            webhook_rate_limiter: LockfreeMutCell::new(SimpleRateLimiter::new(3, Duration::from_secs(60))),
+            // short levels can be beaten every few seconds, nobody wants to see that many celebrations
+            completion_rate_limiter: LockfreeMutCell::new(SimpleRateLimiter::new(2, Duration::from_secs(60))),
+            // events are meant for the occasional effect, not for syncing anything every frame
+            level_event_rate_limiter: LockfreeMutCell::new(SimpleRateLimiter::new(5, Duration::from_secs(5))),
+            // every toggle makes the old and the new target recount their spectators
+            spectate_rate_limiter: LockfreeMutCell::new(SimpleRateLimiter::new(5, Duration::from_secs(10))),
+            backpressure: LockfreeMutCell::new(BackpressureTracker::default()),
+            afk_tracker: LockfreeMutCell::new(AfkTracker::default()),
+            state_checker: LockfreeMutCell::new(StateChecker::default()),
+
+            capture: PacketCapture::for_new_session().map(SyncMutex::new),
+
+            destruction_notify: thread.destruction_notify,
         }
     }
 
@@ -148,12 +339,21 @@ impl ClientThread {
         {
             let mut mq = self.message_queue.lock().await;
             if !mq.is_empty() {
-                return mq.pop_front();
+                self.record_backpressure(mq.len());
+                return mq.pop();
             }
         }
 
         self.message_notify.notified().await;
-        self.message_queue.lock().await.pop_front()
+
+        let mut mq = self.message_queue.lock().await;
+        self.record_backpressure(mq.len());
+        mq.pop()
+    }
+
+    fn record_backpressure(&self, queue_len: usize) {
+        // safety: only we can use the tracker.
+        unsafe { self.backpressure.get_mut() }.record(queue_len >= SATURATED_QUEUE_LEN);
     }
 
     async fn poll_for_tcp_data(&self) -> Result<usize> {
@@ -164,6 +364,7 @@ impl ClientThread {
 
     pub async fn run(&self) -> ClientThreadOutcome {
         let mut last_received_packet = Instant::now();
+        let mut last_chaos_roll = Instant::now();
 
         loop {
             let state = self.connection_state.load();
@@ -177,7 +378,34 @@ impl ClientThread {
 
             // if more than 90 seconds since last packet, disonnect
             if last_received_packet.elapsed().as_secs() > 90 {
-                break self.terminate();
+                // the client is likely not listening anymore, so failing to tell it is fine
+                let _ = self.kick(DisconnectReason::Idle, "").await;
+                break ClientThreadOutcome::Terminate;
+            }
+
+            if last_chaos_roll.elapsed() > chaos::DISCONNECT_ROLL_INTERVAL {
+                last_chaos_roll = Instant::now();
+
+                if chaos::should_disconnect() {
+                    warn!("[{}] chaos: forcing a spurious disconnect", self.account_id.load(Ordering::Relaxed));
+                    break self.disconnect();
+                }
+            }
+
+            if let Err(e) = self.maybe_rekey().await {
+                self.print_error(&e);
+            }
+
+            if let Err(e) = self.maybe_probe_latency().await {
+                self.print_error(&e);
+            }
+
+            if let Err(e) = self.maybe_send_throttle_hint().await {
+                self.print_error(&e);
+            }
+
+            if let Err(e) = self.maybe_update_afk().await {
+                self.print_error(&e);
             }
 
             tokio::select! {
@@ -246,15 +474,76 @@ impl ClientThread {
         ClientThreadOutcome::Disconnect
     }
 
+    /// queue a message for the thread, if the queue is full a less important message might be dropped for it
     pub async fn push_new_message(&self, message: ServerThreadMessage) {
-        self.message_queue.lock().await.push_back(message);
+        let dropped = self.message_queue.lock().await.push(message);
+        if let Some(class) = dropped {
+            self.game_server.message_drops.record(class);
+        }
+
         self.message_notify.notify_one();
     }
 
+    /// let the thread know its spectators changed, does nothing if it already has a pending recount
+    pub async fn mark_spectators_changed(&self) {
+        if !self.spectators_dirty.swap(true, Ordering::Relaxed) {
+            self.push_new_message(ServerThreadMessage::SpectatorsChanged).await;
+        }
+    }
+
+    /// stop counting this client as lossy, must be called once the thread stops running
+    pub fn clear_lossy(&self) {
+        if self.is_lossy.swap(false, Ordering::Relaxed) {
+            self.game_server.state.lossy_player_count.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    /// recompute the roles of the user, as their timed roles may have started or expired since the last time.
+    /// if anything changed, the user is told about their new roles.
+    pub async fn refresh_roles(&self, now: i64) {
+        let roles = self.user_entry.lock().active_roles(now);
+        let role_manager = &self.game_server.state.role_manager;
+        let special_user_data = role_manager.special_user_data(&roles);
+
+        {
+            let mut account_data = self.account_data.lock();
+            if account_data.special_user_data.roles.as_deref() == special_user_data.roles.as_deref() {
+                return;
+            }
+
+            account_data.special_user_data.clone_from(&special_user_data);
+        }
+
+        *self.user_role.lock() = role_manager.compute(&roles);
+
+        self.push_new_message(ServerThreadMessage::BroadcastRoleChange(RolesUpdatedPacket {
+            special_user_data,
+            all_roles: None,
+        }))
+        .await;
+    }
+
+    /// recompute the roles of the user after the role definitions were changed, and send them the new definitions.
+    /// unlike `refresh_roles`, the user is always told, as badges and colors may have changed even if their roles didn't.
+    pub async fn apply_role_definitions(&self, now: i64, all_roles: Vec<GameServerRole>) {
+        let roles = self.user_entry.lock().active_roles(now);
+        let role_manager = &self.game_server.state.role_manager;
+        let special_user_data = role_manager.special_user_data(&roles);
+
+        self.account_data.lock().special_user_data.clone_from(&special_user_data);
+        *self.user_role.lock() = role_manager.compute(&roles);
+
+        self.push_new_message(ServerThreadMessage::BroadcastRoleChange(RolesUpdatedPacket {
+            special_user_data,
+            all_roles: Some(all_roles),
+        }))
+        .await;
+    }
+
     /* private utilities */
 
     /// get the tcp address of the connected peer. do not call this from another clientthread
-    fn get_tcp_peer(&self) -> SocketAddrV4 {
+    fn get_tcp_peer(&self) -> SocketAddr {
         // safety: we trust this function is not called from the oustide
         unsafe { self.socket.get() }.tcp_peer
     }
@@ -273,6 +562,7 @@ impl ClientThread {
                 | PacketHandlingError::NoHandler(_)
                 | PacketHandlingError::DebugOnlyPacket
                 | PacketHandlingError::PacketTooLong(_)
+                | PacketHandlingError::InvalidLevelId
                 | PacketHandlingError::SocketSendFailed(_)
                 | PacketHandlingError::InvalidStreamMarker => {
                     warn!("[{} @ {}] {}", self.account_id.load(Ordering::Relaxed), self.get_tcp_peer(), error);
@@ -304,10 +594,83 @@ impl ClientThread {
         }
     }
 
+    /// start replacing the session key if it's due for it, drop the old one once it expires, and kick the user if they never responded to a previous request
+    async fn maybe_rekey(&self) -> Result<()> {
+        // safety: only we can access our socket.
+        let socket = unsafe { self.socket.get_mut() };
+
+        if socket.rekey_timed_out() {
+            warn!(
+                "[{}] client did not respond to a rekey request, disconnecting",
+                self.account_id.load(Ordering::Relaxed)
+            );
+            return self
+                .kick(DisconnectReason::ProtocolError, "Failed to renew the session encryption key")
+                .await;
+        }
+
+        socket.expire_previous_key();
+
+        if socket.should_rekey() {
+            let key = socket.begin_rekey();
+            self.send_packet_static(&RekeyRequestPacket { key }).await?;
+        }
+
+        Ok(())
+    }
+
+    /// send a latency probe if it's time for the next one
+    async fn maybe_probe_latency(&self) -> Result<()> {
+        // safety: only we can use the estimator.
+        let estimator = unsafe { self.latency_estimator.get_mut() };
+
+        if estimator.should_probe() {
+            let id = estimator.begin_probe();
+            self.send_packet_static(&LatencyProbePacket { id }).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the client is allowed to send a packet that requires `access`. Violations are logged and counted,
+    /// clients that aren't logged in get disconnected and admins without the permission get an error.
+    async fn check_packet_access(&self, packet_id: u16, access: PacketAccess) -> Result<bool> {
+        let allowed = match access {
+            PacketAccess::Connected => true,
+            PacketAccess::LoggedIn => self.authenticated(),
+            PacketAccess::Admin(perm) => self.authenticated() && self._has_perm(perm),
+        };
+
+        if allowed {
+            return Ok(true);
+        }
+
+        self.game_server.permission_violations.fetch_add(1, Ordering::Relaxed);
+
+        warn!(
+            "[{} @ {}] sent packet {packet_id} without the required access ({access:?})",
+            self.account_id.load(Ordering::Relaxed),
+            self.get_tcp_peer()
+        );
+
+        if self.authenticated() {
+            self.send_packet_dynamic(&AdminErrorPacket {
+                code: ErrorCode::NoPermission,
+                message: "no permission",
+            })
+            .await?;
+        } else {
+            self.kick(DisconnectReason::ProtocolError, "unauthorized, please try connecting again")
+                .await?;
+        }
+
+        Ok(false)
+    }
+
     /// call `self.terminate()` and send a message to the user with the reason
-    async fn kick(&self, message: &str) -> Result<()> {
+    async fn kick(&self, reason: DisconnectReason, message: &str) -> Result<()> {
         self.terminate();
-        self.send_packet_dynamic(&ServerDisconnectPacket { message }).await
+        self.send_packet_dynamic(&ServerDisconnectPacket { reason, message }).await
     }
 
     async fn ban(&self, message: FastString, timestamp: i64) -> Result<()> {
@@ -327,13 +690,28 @@ impl ClientThread {
             return false;
         }
 
+        let disabled = self.disabled_features();
+        if (voice && disabled.voice) || (!voice && disabled.chat) {
+            // turned off for the platform of the user
+            return false;
+        }
+
         // check for slowmode stuffs
         if voice {
+            if safe_mode::is_active() {
+                // voice chat is disabled in safe mode
+                return false;
+            }
+
             if len > MAX_VOICE_PACKET_SIZE {
                 // voice packet is too big
                 return false;
             }
 
+            if !self.has_trust(self.game_server.bridge.central_conf.lock().min_trust_voice) {
+                return false;
+            }
+
             // safety: only we can access the rate limiters of our user.
             let block = !unsafe { self.voice_rate_limiter.get_mut().try_tick() };
             if block {
@@ -341,7 +719,7 @@ impl ClientThread {
             }
         } else {
             // if rate limiting is disabled, do not block
-            let block = !self.chat_rate_limiter.as_ref().map_or(true, |x| unsafe { x.get_mut().try_tick() });
+            let block = self.chat_rate_limiter.as_ref().is_some_and(|x| unsafe { !x.get_mut().try_tick() });
 
             if block {
                 return false;
@@ -351,6 +729,44 @@ impl ClientThread {
         true
     }
 
+    /// features that the central server turned off for the platform of the user
+    fn disabled_features(&self) -> DisabledFeatures {
+        let platform = *self.platform.lock();
+        self.game_server.bridge.central_conf.lock().platform_features.for_platform(platform)
+    }
+
+    /// whether the trust tier of the user is high enough for a feature that requires `min_tier`
+    fn has_trust(&self, min_tier: TrustTier) -> bool {
+        *self.trust_tier.lock() >= min_tier
+    }
+
+    /// the room quota of the user: the limits of their highest priority role that has its own, otherwise the default ones.
+    /// admins have no limits.
+    fn room_quota(&self) -> RoomQuotaLimits {
+        if self.user_role.lock().admin {
+            return RoomQuotaLimits::default();
+        }
+
+        let roles = self.user_entry.lock().active_roles(unix_timestamp());
+        let role_manager = &self.game_server.state.role_manager;
+        let conf = self.game_server.bridge.central_conf.lock();
+
+        conf.room_quota
+            .role_overrides
+            .iter()
+            .filter(|x| roles.contains(&x.role))
+            .max_by_key(|x| role_manager.compute_priority(std::slice::from_ref(&x.role)))
+            .map_or(conf.room_quota.default, |x| x.limits)
+    }
+
+    /// whether the user is allowed to talk in voice chat at all, ignoring rate limits
+    pub(crate) fn can_use_voice(&self) -> bool {
+        !self.user_entry.lock().is_muted
+            && !self.disabled_features().voice
+            && !safe_mode::is_active()
+            && self.has_trust(self.game_server.bridge.central_conf.lock().min_trust_voice)
+    }
+
     #[inline]
     async fn recv_and_handle(&self, message_size: usize) -> Result<()> {
         // safety: only we can receive data from our client.
@@ -363,20 +779,77 @@ impl ClientThread {
         match message {
             ServerThreadMessage::Packet(mut packet) => self.handle_packet(&mut packet).await?,
             ServerThreadMessage::SmallPacket((mut packet, len)) => self.handle_packet(&mut packet[..len]).await?,
-            ServerThreadMessage::BroadcastText(text_packet) => self.send_packet_static(&text_packet).await?,
-            ServerThreadMessage::BroadcastVoice(voice_packet) => self.send_packet_dynamic(&*voice_packet).await?,
+            ServerThreadMessage::BroadcastText(text_packet) => {
+                if !self.disabled_features().chat {
+                    self.send_packet_static(&text_packet).await?;
+                }
+            }
+            ServerThreadMessage::BroadcastTextDeletion(packet) => self.send_packet_static(&packet).await?,
+            ServerThreadMessage::ChatMention(packet) => self.send_packet_static(&packet).await?,
+            ServerThreadMessage::BroadcastVoice(voice_packet) => {
+                if !self.disabled_features().voice {
+                    self.send_packet_dynamic(&*voice_packet).await?;
+                }
+            }
             ServerThreadMessage::BroadcastNotice(packet) => {
                 self.send_packet_dynamic(&packet).await?;
                 info!("{} is receiving a notice: {}", self.account_data.lock().name, packet.message);
             }
             ServerThreadMessage::BroadcastInvite(packet) => self.send_packet_static(&packet).await?,
             ServerThreadMessage::BroadcastRoomInfo(packet) => {
-                self.send_packet_static(&packet).await?;
+                self.send_packet_dynamic(&packet).await?;
+            }
+            ServerThreadMessage::RoomKick(room_id) => self.kick_out_of_room(room_id).await?,
+            ServerThreadMessage::BroadcastProfileChange(packet) => self.send_packet_static(&packet).await?,
+            ServerThreadMessage::ChatTail(packet) => {
+                // the moderator might have lost the permission in the meantime
+                if self._has_perm(AdminPerm::Mute) {
+                    self.send_packet_static(&packet).await?;
+                } else {
+                    self.game_server.state.chat_tails.stop(self.account_id.load(Ordering::Relaxed));
+                }
             }
             ServerThreadMessage::BroadcastBan(packet) => self.ban(packet.message, packet.timestamp).await?,
             ServerThreadMessage::BroadcastMute(packet) => self.send_packet_dynamic(&packet).await?,
-            ServerThreadMessage::BroadcastRoleChange(packet) => self.send_packet_static(&packet).await?,
-            ServerThreadMessage::TerminationNotice(message) => self.kick(message.try_to_str()).await?,
+            ServerThreadMessage::BroadcastRoleChange(packet) => self.send_packet_dynamic(&packet).await?,
+            ServerThreadMessage::TerminationNotice(reason, message) => self.kick(reason, message.try_to_str()).await?,
+            ServerThreadMessage::DuplicateLogin(notice) => self.send_packet_static(&DuplicateLoginPacket { notice }).await?,
+            ServerThreadMessage::Transfer(packet) => {
+                self.terminate();
+                self.send_packet_dynamic(&packet).await?;
+            }
+            ServerThreadMessage::Restarting(packet) => self.send_packet_dynamic(&packet).await?,
+            ServerThreadMessage::LevelSnapshot => {
+                // safety: only we can use this ratelimiter.
+                if unsafe { self.snapshot_rate_limiter.get_mut() }.try_tick() {
+                    self.send_level_snapshot().await?;
+                }
+            }
+            ServerThreadMessage::LevelBlocked(level_id) => {
+                // we might have left the level in the meantime
+                if self.level_id.load(Ordering::Relaxed) == level_id {
+                    self.leave_level(self.account_id.load(Ordering::Relaxed)).await?;
+                    self.send_packet_static(&LevelBlockedPacket { level_id }).await?;
+                }
+            }
+            ServerThreadMessage::RoomCountdown(level_id, ends_at) => self.send_room_countdown(level_id, ends_at).await?,
+            ServerThreadMessage::LevelCompletion(packet) => self.send_packet_static(&packet).await?,
+            ServerThreadMessage::LevelEvent(packet) => self.send_packet_dynamic(&packet).await?,
+            ServerThreadMessage::PresenceUpdate(account_id) => self.send_presence(&[account_id]).await?,
+            ServerThreadMessage::SpectatedData(packet) => self.send_packet_static(&packet).await?,
+            ServerThreadMessage::SpectatorsChanged => {
+                // cleared before counting, so a change that happens while we count queues a new recount
+                self.spectators_dirty.store(false, Ordering::Relaxed);
+
+                let account_id = self.account_id.load(Ordering::Relaxed);
+                let count = self
+                    .game_server
+                    .state
+                    .room_manager
+                    .with_any(self.room_id.load(Ordering::Relaxed), |room| room.get_spectators(account_id).len());
+
+                self.update_spectator_count(count as u32).await?;
+            }
         }
 
         Ok(())
@@ -389,17 +862,43 @@ impl ClientThread {
             return Err(PacketHandlingError::MalformedMessage);
         }
 
-        // if we are ratelimited, just discard the packet.
-        // safety: only we can use this ratelimiter.
-        if !unsafe { self.rate_limiter.get_mut() }.try_tick() {
-            return Err(PacketHandlingError::Ratelimited);
-        }
-
         let mut data = ByteReader::from_bytes(message);
         let header = data.read_packet_header()?;
 
+        // only the server compresses packets
+        if header.compressed {
+            return Err(PacketHandlingError::MalformedMessage);
+        }
+
+        // if we are over the budget, just discard the packet, or disconnect if it's been going on for too long.
+        let category = packet_category(header.packet_id);
+        // safety: only we can use the packet budget.
+        match unsafe { self.packet_budget.get_mut() }.record(category) {
+            BudgetVerdict::Allow => {}
+            BudgetVerdict::Drop => return Err(PacketHandlingError::Ratelimited),
+            BudgetVerdict::Disconnect => {
+                let peer = self.get_tcp_peer();
+                warn!(
+                    "[{} @ {}] disconnecting for sending too many {} packets",
+                    self.account_id.load(Ordering::Relaxed),
+                    peer,
+                    category.name()
+                );
+
+                self.game_server.block_flooder(peer.ip());
+                return self
+                    .kick(DisconnectReason::Flooding, &format!("Too many {} packets were sent", category.name()))
+                    .await;
+            }
+        }
+
         // by far the most common packet, so we try it early
         if header.packet_id == PlayerDataPacket::PACKET_ID {
+            if !self.check_packet_access(header.packet_id, PacketAccess::LoggedIn).await? {
+                return Ok(());
+            }
+
+            self.capture_packet(&header, &data);
             return self.handle_player_data(&mut data).await;
         }
 
@@ -417,47 +916,101 @@ impl ClientThread {
             data = unsafe { self.socket.get_mut() }.decrypt(message)?;
         }
 
-        match header.packet_id {
+        self.capture_packet(&header, &data);
+
+        gs_dispatch!(self, header.packet_id, {
             /* connection related */
-            PingPacket::PACKET_ID => self.handle_ping(&mut data).await,
-            KeepalivePacket::PACKET_ID => self.handle_keepalive(&mut data).await,
-            DisconnectPacket::PACKET_ID => self.handle_disconnect(&mut data),
-            ConnectionTestPacket::PACKET_ID => self.handle_connection_test(&mut data).await,
-            KeepaliveTCPPacket::PACKET_ID => self.handle_keepalive_tcp(&mut data).await,
+            PingPacket, PacketAccess::Connected => self.handle_ping(&mut data).await,
+            KeepalivePacket, PacketAccess::LoggedIn => self.handle_keepalive(&mut data).await,
+            DisconnectPacket, PacketAccess::Connected => self.handle_disconnect(&mut data),
+            ConnectionTestPacket, PacketAccess::Connected => self.handle_connection_test(&mut data).await,
+            KeepaliveTCPPacket, PacketAccess::LoggedIn => self.handle_keepalive_tcp(&mut data).await,
+            LatencyProbeResponsePacket, PacketAccess::LoggedIn => self.handle_latency_probe_response(&mut data).await,
+            RekeyResponsePacket, PacketAccess::LoggedIn => self.handle_rekey_response(&mut data).await,
+            ClaimTransferPacket, PacketAccess::LoggedIn => self.handle_claim_transfer(&mut data).await,
 
             /* general */
-            SyncIconsPacket::PACKET_ID => self.handle_sync_icons(&mut data).await,
-            RequestGlobalPlayerListPacket::PACKET_ID => self.handle_request_global_list(&mut data).await,
-            RequestLevelListPacket::PACKET_ID => self.handle_request_level_list(&mut data).await,
-            RequestPlayerCountPacket::PACKET_ID => self.handle_request_player_count(&mut data).await,
-            UpdatePlayerStatusPacket::PACKET_ID => self.handle_set_player_status(&mut data).await,
+            SyncIconsPacket, PacketAccess::LoggedIn => self.handle_sync_icons(&mut data).await,
+            RequestGlobalPlayerListPacket, PacketAccess::LoggedIn => self.handle_request_global_list(&mut data).await,
+            RequestLevelListPacket, PacketAccess::LoggedIn => self.handle_request_level_list(&mut data).await,
+            RequestPlayerCountPacket, PacketAccess::LoggedIn => self.handle_request_player_count(&mut data).await,
+            UpdatePlayerStatusPacket, PacketAccess::LoggedIn => self.handle_set_player_status(&mut data).await,
+            FeatureUsagePacket, PacketAccess::LoggedIn => self.handle_feature_usage(&mut data).await,
+            UpdatePreferencesPacket, PacketAccess::LoggedIn => self.handle_update_preferences(&mut data).await,
+            ReportPlayerPacket, PacketAccess::LoggedIn => self.handle_report_player(&mut data).await,
+            SubscribePresencePacket, PacketAccess::LoggedIn => self.handle_subscribe_presence(&mut data).await,
+            UpdateFriendListPacket, PacketAccess::LoggedIn => self.handle_update_friend_list(&mut data).await,
+            FriendListQueryPacket, PacketAccess::LoggedIn => self.handle_friend_list_query(&mut data).await,
+            RequestSessionListPacket, PacketAccess::LoggedIn => self.handle_request_session_list(&mut data).await,
+            LogoutSessionPacket, PacketAccess::LoggedIn => self.handle_logout_session(&mut data).await,
 
             /* game related */
-            RequestPlayerProfilesPacket::PACKET_ID => self.handle_request_profiles(&mut data).await,
-            LevelJoinPacket::PACKET_ID => self.handle_level_join(&mut data).await,
-            LevelLeavePacket::PACKET_ID => self.handle_level_leave(&mut data).await,
-            PlayerDataPacket::PACKET_ID => self.handle_player_data(&mut data).await,
-            PlayerMetadataPacket::PACKET_ID => self.handle_player_metadata(&mut data).await,
-
-            VoicePacket::PACKET_ID => self.handle_voice(&mut data).await,
-            ChatMessagePacket::PACKET_ID => self.handle_chat_message(&mut data).await,
+            RequestPlayerProfilesPacket, PacketAccess::LoggedIn => self.handle_request_profiles(&mut data).await,
+            LevelJoinPacket, PacketAccess::LoggedIn => self.handle_level_join(&mut data).await,
+            LevelLeavePacket, PacketAccess::LoggedIn => self.handle_level_leave(&mut data).await,
+            PlayerDataPacket, PacketAccess::LoggedIn => self.handle_player_data(&mut data).await,
+            PlayerMetadataPacket, PacketAccess::LoggedIn => self.handle_player_metadata(&mut data).await,
+            LevelCompletedPacket, PacketAccess::LoggedIn => self.handle_level_completed(&mut data).await,
+            SpectatePlayerPacket, PacketAccess::LoggedIn => self.handle_spectate_player(&mut data).await,
+            SpectatorDataPacket, PacketAccess::LoggedIn => self.handle_spectator_data(&mut data).await,
+            LevelEventPacket, PacketAccess::LoggedIn => self.handle_level_event(&mut data).await,
+
+            VoicePacket, PacketAccess::LoggedIn => self.handle_voice(&mut data).await,
+            ChatMessagePacket, PacketAccess::LoggedIn => self.handle_chat_message(&mut data).await,
+            DeleteChatMessagePacket, PacketAccess::LoggedIn => self.handle_delete_chat_message(&mut data).await,
+            SwitchChatChannelPacket, PacketAccess::LoggedIn => self.handle_switch_chat_channel(&mut data).await,
 
             /* room related */
-            CreateRoomPacket::PACKET_ID => self.handle_create_room(&mut data).await,
-            JoinRoomPacket::PACKET_ID => self.handle_join_room(&mut data).await,
-            LeaveRoomPacket::PACKET_ID => self.handle_leave_room(&mut data).await,
-            RequestRoomPlayerListPacket::PACKET_ID => self.handle_request_room_players(&mut data).await,
-            UpdateRoomSettingsPacket::PACKET_ID => self.handle_update_room_settings(&mut data).await,
-            RoomSendInvitePacket::PACKET_ID => self.handle_room_invitation(&mut data).await,
-            RequestRoomListPacket::PACKET_ID => self.handle_request_room_list(&mut data).await,
+            CreateRoomPacket, PacketAccess::LoggedIn => self.handle_create_room(&mut data).await,
+            JoinRoomPacket, PacketAccess::LoggedIn => self.handle_join_room(&mut data).await,
+            LeaveRoomPacket, PacketAccess::LoggedIn => self.handle_leave_room(&mut data).await,
+            RequestRoomPlayerListPacket, PacketAccess::LoggedIn => self.handle_request_room_players(&mut data).await,
+            UpdateRoomSettingsPacket, PacketAccess::LoggedIn => self.handle_update_room_settings(&mut data).await,
+            RoomSendInvitePacket, PacketAccess::LoggedIn => self.handle_room_invitation(&mut data).await,
+            RequestRoomListPacket, PacketAccess::LoggedIn => self.handle_request_room_list(&mut data).await,
+            StartRoomCountdownPacket, PacketAccess::LoggedIn => self.handle_start_room_countdown(&mut data).await,
+            SetRoomWebhookPacket, PacketAccess::LoggedIn => self.handle_set_room_webhook(&mut data).await,
+            SetRoomModeratorPacket, PacketAccess::LoggedIn => self.handle_set_room_moderator(&mut data).await,
+            RoomKickPlayerPacket, PacketAccess::LoggedIn => self.handle_room_kick_player(&mut data).await,
+            SetRoomTagsPacket, PacketAccess::LoggedIn => self.handle_set_room_tags(&mut data).await,
+            SetRoomMemberTagPacket, PacketAccess::LoggedIn => self.handle_set_room_member_tag(&mut data).await,
 
             /* admin related */
-            AdminAuthPacket::PACKET_ID => self.handle_admin_auth(&mut data).await,
-            AdminSendNoticePacket::PACKET_ID => self.handle_admin_send_notice(&mut data).await,
-            AdminDisconnectPacket::PACKET_ID => self.handle_admin_disconnect(&mut data).await,
-            AdminGetUserStatePacket::PACKET_ID => self.handle_admin_get_user_state(&mut data).await,
-            AdminUpdateUserPacket::PACKET_ID => self.handle_admin_update_user(&mut data).await,
-            x => Err(PacketHandlingError::NoHandler(x)),
+            AdminAuthPacket, PacketAccess::LoggedIn => self.handle_admin_auth(&mut data).await,
+            AdminSendNoticePacket, PacketAccess::Admin(AdminPerm::Notice) => self.handle_admin_send_notice(&mut data).await,
+            AdminDisconnectPacket, PacketAccess::Admin(AdminPerm::Kick) => self.handle_admin_disconnect(&mut data).await,
+            AdminGetUserStatePacket, PacketAccess::Admin(AdminPerm::Any) => self.handle_admin_get_user_state(&mut data).await,
+            AdminUpdateUserPacket, PacketAccess::Admin(AdminPerm::Any) => self.handle_admin_update_user(&mut data).await,
+            AdminDebugDumpPacket, PacketAccess::Admin(AdminPerm::Admin) => self.handle_admin_debug_dump(&mut data).await,
+            AdminSetChaosPacket, PacketAccess::Admin(AdminPerm::Admin) => self.handle_admin_set_chaos(&mut data).await,
+            AdminSetLevelBlockedPacket, PacketAccess::Admin(AdminPerm::Ban) => self.handle_admin_set_level_blocked(&mut data).await,
+            AdminSetSlowModePacket, PacketAccess::Admin(AdminPerm::Mute) => self.handle_admin_set_slow_mode(&mut data).await,
+            AdminTakeOverRoomPacket, PacketAccess::Admin(AdminPerm::Kick) => self.handle_admin_take_over_room(&mut data).await,
+            AdminGetPlayerHistoryPacket, PacketAccess::Admin(AdminPerm::Any) => self.handle_admin_get_player_history(&mut data).await,
+            AdminScheduleNoticePacket, PacketAccess::Admin(AdminPerm::Notice) => self.handle_admin_schedule_notice(&mut data).await,
+            AdminGetScheduledNoticesPacket, PacketAccess::Admin(AdminPerm::Notice) => self.handle_admin_get_scheduled_notices(&mut data).await,
+            AdminCancelScheduledNoticePacket, PacketAccess::Admin(AdminPerm::Notice) => self.handle_admin_cancel_scheduled_notice(&mut data).await,
+            AdminGetTrafficStatsPacket, PacketAccess::Admin(AdminPerm::Any) => self.handle_admin_get_traffic_stats(&mut data).await,
+            AdminSpawnBotPacket, PacketAccess::Admin(AdminPerm::SpawnBots) => self.handle_admin_spawn_bot(&mut data).await,
+            AdminMoveBotPacket, PacketAccess::Admin(AdminPerm::SpawnBots) => self.handle_admin_move_bot(&mut data).await,
+            AdminBotChatPacket, PacketAccess::Admin(AdminPerm::SpawnBots) => self.handle_admin_bot_chat(&mut data).await,
+            AdminRemoveBotPacket, PacketAccess::Admin(AdminPerm::SpawnBots) => self.handle_admin_remove_bot(&mut data).await,
+            AdminGetBotsPacket, PacketAccess::Admin(AdminPerm::SpawnBots) => self.handle_admin_get_bots(&mut data).await,
+            AdminCreateInviteCodePacket, PacketAccess::Admin(AdminPerm::Ban) => self.handle_admin_create_invite_code(&mut data).await,
+            AdminGetReportsPacket, PacketAccess::Admin(AdminPerm::Kick) => self.handle_admin_get_reports(&mut data).await,
+            AdminUpdateReportPacket, PacketAccess::Admin(AdminPerm::Kick) => self.handle_admin_update_report(&mut data).await,
+            AdminTailChatPacket, PacketAccess::Admin(AdminPerm::Mute) => self.handle_admin_tail_chat(&mut data).await,
+            AdminSetConfigOverridePacket, PacketAccess::Admin(AdminPerm::Admin) => self.handle_admin_set_config_override(&mut data).await,
+            AdminGetConfigOverridesPacket, PacketAccess::Admin(AdminPerm::Admin) => self.handle_admin_get_config_overrides(&mut data).await,
+            AdminReloadConfigPacket, PacketAccess::Admin(AdminPerm::Admin) => self.handle_admin_reload_config(&mut data).await,
+        })
+    }
+
+    /// if packet capture is enabled, record the packet. `data` must be positioned right at the start of the packet body.
+    #[inline]
+    fn capture_packet(&self, header: &PacketHeader, data: &ByteReader) {
+        if let Some(capture) = self.capture.as_ref() {
+            capture.lock().record(header, &data.as_bytes()[data.get_rpos()..]);
         }
     }
 
@@ -489,3 +1042,72 @@ impl ClientThread {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use globed_shared::{RoomQuotaLimits, RoomQuotaOverride, ServerRole};
+
+    use super::*;
+    use crate::testing::server::{test_client, test_server};
+
+    fn role(id: &str, priority: i32) -> ServerRole {
+        ServerRole {
+            id: id.to_owned(),
+            priority,
+            ..Default::default()
+        }
+    }
+
+    fn quota_override(role: &str, max_owned_rooms: u32) -> RoomQuotaOverride {
+        RoomQuotaOverride {
+            role: role.to_owned(),
+            limits: RoomQuotaLimits {
+                max_owned_rooms,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// a server where `helper` outranks `vip` and both have their own room quota, `plain` outranks both but has none
+    async fn quota_server() -> &'static GameServer {
+        let server = test_server().await;
+
+        let mut conf = server.bridge.central_conf.lock();
+        conf.roles = vec![role("vip", 10), role("helper", 20), role("plain", 30)];
+        conf.room_quota.default.max_owned_rooms = 1;
+        conf.room_quota.role_overrides = vec![quota_override("helper", 3), quota_override("vip", 2)];
+        server.state.role_manager.refresh_from(&conf);
+        drop(conf);
+
+        server
+    }
+
+    async fn quota_with_roles(server: &'static GameServer, roles: &[&str]) -> u32 {
+        let thread = test_client(server, 1).await;
+        thread.user_entry.lock().user_roles = roles.iter().map(|x| (*x).to_owned()).collect();
+        thread.room_quota().max_owned_rooms
+    }
+
+    #[tokio::test]
+    async fn room_quota_uses_highest_priority_override() {
+        let server = quota_server().await;
+
+        assert_eq!(quota_with_roles(server, &[]).await, 1);
+        assert_eq!(quota_with_roles(server, &["vip"]).await, 2);
+        assert_eq!(quota_with_roles(server, &["vip", "helper"]).await, 3);
+        assert_eq!(quota_with_roles(server, &["helper", "vip"]).await, 3);
+
+        // roles without their own limits don't count, even with a higher priority
+        assert_eq!(quota_with_roles(server, &["vip", "plain"]).await, 2);
+        assert_eq!(quota_with_roles(server, &["plain"]).await, 1);
+    }
+
+    #[tokio::test]
+    async fn room_quota_is_unlimited_for_admins() {
+        let server = quota_server().await;
+
+        let thread = test_client(server, 1).await;
+        thread.user_role.lock().admin = true;
+        assert_eq!(thread.room_quota().max_owned_rooms, 0);
+    }
+}
@@ -10,6 +10,8 @@ use super::*;
 use crate::{
     client::error::{PacketHandlingError, Result},
     data::*,
+    events::ServerEvent,
+    scripting::HookResult,
 };
 
 #[allow(unused)]
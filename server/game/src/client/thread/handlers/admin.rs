@@ -1,21 +1,45 @@
-use globed_shared::{info, warn};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use globed_shared::{anyhow, info, reqwest::StatusCode, unix_timestamp, verify_password, verify_totp_once, warn, ReportState, ReportStateChange};
 
 use crate::{
-    managers::ComputedRole,
+    bridge::CentralBridgeError,
+    chaos::{self, ChaosSettings},
+    cluster::ClusterMessage,
+    managers::{Bot, MAX_BOTS, MAX_INVITE_CODES, MAX_INVITE_CODE_USES, MAX_OVERRIDE_DURATION, MAX_SCHEDULED_NOTICES},
     webhook::{BanMuteStateChange, WebhookMessage},
 };
 
 use super::*;
 
+/// the most player count samples that are sent to the admin panel at once
+const MAX_PLAYER_HISTORY_POINTS: usize = 360;
+/// how far ahead notices can be scheduled, in seconds (30 days)
+const MAX_NOTICE_SCHEDULE_AHEAD: i64 = 30 * 86400;
+/// the most rooms and levels that are sent in the traffic stats
+const MAX_TRAFFIC_ENTRIES: u8 = 50;
+/// the highest tps that can be set with a config override
+const MAX_OVERRIDE_TPS: u32 = 240;
+
+/// Picks the error code for a failed request to the central server or the local database.
+fn error_code_of(err: &anyhow::Error) -> ErrorCode {
+    match err.downcast_ref::<CentralBridgeError>() {
+        Some(CentralBridgeError::RequestError(_)) => ErrorCode::CentralUnavailable,
+        // the central server responds with 400 if the user or report does not exist
+        Some(CentralBridgeError::CentralError((status, _))) if *status == StatusCode::BAD_REQUEST => ErrorCode::NotFound,
+        _ => ErrorCode::Internal,
+    }
+}
+
 macro_rules! admin_error {
-    ($self:expr, $msg:expr) => {
-        $self.send_packet_dynamic(&AdminErrorPacket { message: $msg }).await?;
+    ($self:expr, $code:expr, $msg:expr) => {
+        $self.send_packet_dynamic(&AdminErrorPacket { code: $code, message: $msg }).await?;
         return Ok(());
     };
 }
 
-#[derive(Clone, Copy)]
-enum AdminPerm {
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum AdminPerm {
     Any,
     Notice,
     NoticeToEveryone,
@@ -24,115 +48,224 @@ enum AdminPerm {
     Mute,
     Ban,
     EditRoles,
+    SpawnBots,
     Admin,
 }
 
+impl AdminPerm {
+    /// Whether the role has this permission, admins have all of them.
+    pub(crate) fn is_granted(self, role: &ComputedRole) -> bool {
+        if role.admin {
+            return true;
+        }
+
+        match self {
+            Self::Any => role.can_moderate(),
+            Self::Notice => role.notices,
+            Self::NoticeToEveryone => role.notices_to_everyone,
+            Self::Ban => role.ban,
+            Self::Mute => role.mute,
+            Self::Kick => role.kick,
+            Self::KickEveryone => role.kick_everyone,
+            Self::EditRoles => role.edit_role,
+            Self::SpawnBots => role.spawn_bots,
+            Self::Admin => false,
+        }
+    }
+}
+
 impl ClientThread {
     // check if the user is logged in as admin, and if they have the given permission
-    fn _has_perm(&self, perm: AdminPerm) -> bool {
+    pub(crate) fn _has_perm(&self, perm: AdminPerm) -> bool {
         if !self.is_authorized_admin.load(Ordering::Relaxed) {
             return false;
         }
 
-        let role = self.user_role.lock();
-
-        if role.admin {
-            return true;
-        }
-
-        match perm {
-            AdminPerm::Any => role.can_moderate(),
-            AdminPerm::Notice => role.notices,
-            AdminPerm::NoticeToEveryone => role.notices_to_everyone,
-            AdminPerm::Ban => role.ban,
-            AdminPerm::Mute => role.mute,
-            AdminPerm::Kick => role.kick,
-            AdminPerm::KickEveryone => role.kick_everyone,
-            AdminPerm::EditRoles => role.edit_role,
-            AdminPerm::Admin => false,
-        }
+        perm.is_granted(&self.user_role.lock())
     }
 
     fn _update_user_role(&self, from: &ComputedRole) {
         self.user_role.lock().clone_from(from);
     }
 
-    gs_handler!(self, handle_admin_auth, AdminAuthPacket, packet, {
-        let account_id = gs_needauth!(self);
+    /// Checks the TOTP code if the role requires one, sends an error to the user and returns `false` if the check failed.
+    async fn _verify_admin_totp(&self, account_id: i32, role: &ComputedRole, code: &FastString) -> Result<bool> {
+        if !role.has_dangerous_perms() {
+            return Ok(true);
+        }
 
-        // test for the global password first
-        if packet.key.constant_time_compare(&self.game_server.bridge.central_conf.lock().admin_key) {
-            info!(
-                "[{} ({}) @ {}] just logged into the admin panel (with global password)",
-                self.account_data.lock().name,
-                account_id,
-                self.get_tcp_peer()
-            );
+        let secret = self.user_entry.lock().totp_secret.clone().filter(|x| !x.is_empty());
+
+        let Some(secret) = secret else {
+            if self.game_server.bridge.central_conf.lock().require_totp {
+                warn!(
+                    "[{} ({}) @ {}] tried to log into the admin panel without two-factor authentication set up",
+                    self.account_data.lock().name,
+                    account_id,
+                    self.get_tcp_peer()
+                );
+
+                self.send_packet_dynamic(&AdminErrorPacket {
+                    code: ErrorCode::TwoFactorRequired,
+                    message: "Two-factor authentication is required for your role, but it is not set up for your account",
+                })
+                .await?;
+                self.send_packet_static(&AdminAuthFailedPacket).await?;
+
+                return Ok(false);
+            }
+
+            return Ok(true);
+        };
+
+        // don't send AdminAuthFailedPacket here, so the client doesn't forget the saved password when logging in automatically
+        if code.is_empty() {
+            self.send_packet_dynamic(&AdminErrorPacket {
+                code: ErrorCode::TwoFactorRequired,
+                message: "Please enter your two-factor authentication code to log into the admin panel",
+            })
+            .await?;
+
+            return Ok(false);
+        }
+
+        let lockout = &self.game_server.state.totp_lockout;
+        let peer_ip = self.get_tcp_peer().ip();
+
+        if let Some(remaining) = lockout.get_lockout(account_id, peer_ip) {
+            self.send_packet_dynamic(&AdminErrorPacket {
+                code: ErrorCode::RateLimited,
+                message: &format!(
+                    "Too many invalid two-factor codes, please try again in {} seconds",
+                    remaining.as_secs().max(1)
+                ),
+            })
+            .await?;
+
+            return Ok(false);
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("clock went backwards").as_secs();
+
+        // a code can only be used once
+        let accepted = {
+            let mut last_steps = self.game_server.state.totp_last_step.lock();
+            verify_totp_once(&secret, code.to_str().unwrap_or_default(), now, last_steps.entry(account_id).or_default())
+        };
+
+        if accepted {
+            lockout.record_success(account_id);
+            return Ok(true);
+        }
+
+        info!(
+            "[{} ({}) @ {}] entered an invalid two-factor code for the admin panel",
+            self.account_data.lock().name,
+            account_id,
+            self.get_tcp_peer()
+        );
 
-            self.is_authorized_admin.store(true, Ordering::Relaxed);
-            // give super admin perms
-            let role = self.game_server.state.role_manager.get_superadmin();
-            self._update_user_role(&role);
+        let bursts = lockout.record_failure(account_id, peer_ip);
+        self.game_server.report_auth_failure_bursts(bursts).await;
 
-            self.send_packet_dynamic(&AdminAuthSuccessPacket { role }).await?;
+        self.send_packet_static(&AdminAuthFailedPacket).await?;
+        Ok(false)
+    }
+
+    gs_handler!(self, handle_admin_auth, AdminAuthPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let peer_ip = self.get_tcp_peer().ip();
+
+        let lockout = &self.game_server.state.admin_lockout;
+        if let Some(remaining) = lockout.get_lockout(account_id, peer_ip) {
+            self.send_packet_dynamic(&AdminErrorPacket {
+                code: ErrorCode::RateLimited,
+                message: &format!("Too many invalid passwords, please try again in {} seconds", remaining.as_secs().max(1)),
+            })
+            .await?;
 
+            // the password might be right, so the client shouldn't forget it
             return Ok(());
         }
 
-        // test for the per-user password
+        // both passwords can be stored either as argon2 hashes or in plaintext
+        let Ok(key) = packet.key.to_str() else {
+            return self.send_packet_static(&AdminAuthFailedPacket).await;
+        };
+
+        let key = key.to_owned();
+        let admin_key = self.game_server.bridge.central_conf.lock().admin_key.try_to_string();
         let admin_password = self.user_entry.lock().admin_password.clone();
 
-        if admin_password.as_ref().is_some_and(|pwd| !pwd.is_empty()) {
-            let password = admin_password.unwrap();
+        // hashing takes a while, so it's done off the runtime.
+        // test for the global password first, it gives super admin perms, then for the per-user password
+        let (global, user) = tokio::task::spawn_blocking(move || {
+            if verify_password(&key, &admin_key) {
+                (true, false)
+            } else {
+                (false, admin_password.is_some_and(|pwd| !pwd.is_empty() && verify_password(&key, &pwd)))
+            }
+        })
+        .await
+        .map_err(|err| PacketHandlingError::Other(format!("password verification failed: {err}")))?;
+
+        let role = if global {
+            Some(self.game_server.state.role_manager.get_superadmin())
+        } else if user {
+            let roles = self.user_entry.lock().active_roles(unix_timestamp());
+            Some(self.game_server.state.role_manager.compute(&roles))
+        } else {
+            None
+        };
+
+        let Some(role) = role else {
+            info!(
+                "[{} ({}) @ {}] just failed to login to the admin panel",
+                self.account_data.lock().name,
+                account_id,
+                self.get_tcp_peer()
+            );
 
-            if packet.key.constant_time_compare(&password) {
-                info!(
-                    "[{} ({}) @ {}] just logged into the admin panel",
-                    self.account_data.lock().name,
-                    account_id,
-                    self.get_tcp_peer()
-                );
+            let bursts = lockout.record_failure(account_id, peer_ip);
+            self.game_server.report_auth_failure_bursts(bursts).await;
 
-                self.is_authorized_admin.store(true, Ordering::Relaxed);
+            // this is silly tbh
+            // if self.game_server.bridge.has_webhook() {
+            //     let name = self.account_data.lock().name.try_to_string();
+            //
+            //     if let Err(err) = self.game_server.bridge.send_webhook_message(WebhookMessage::AuthFail(name)).await {
+            //         warn!("webhook error: {err}");
+            //     }
+            // }
 
-                let role = self.game_server.state.role_manager.compute(&self.user_entry.lock().user_roles);
-                self._update_user_role(&role);
+            return self.send_packet_static(&AdminAuthFailedPacket).await;
+        };
 
-                self.send_packet_dynamic(&AdminAuthSuccessPacket { role }).await?;
+        lockout.record_success(account_id);
 
-                return Ok(());
-            }
+        if !self._verify_admin_totp(account_id, &role, &packet.totp_code).await? {
+            return Ok(());
         }
 
         info!(
-            "[{} ({}) @ {}] just failed to login to the admin panel with password: {}",
+            "[{} ({}) @ {}] just logged into the admin panel{}",
             self.account_data.lock().name,
             account_id,
             self.get_tcp_peer(),
-            packet.key
+            if global { " (with global password)" } else { "" }
         );
 
-        // this is silly tbh
-        // if self.game_server.bridge.has_webhook() {
-        //     let name = self.account_data.lock().name.try_to_string();
-        //
-        //     if let Err(err) = self.game_server.bridge.send_webhook_message(WebhookMessage::AuthFail(name)).await {
-        //         warn!("webhook error: {err}");
-        //     }
-        // }
+        self.is_authorized_admin.store(true, Ordering::Relaxed);
+        self._update_user_role(&role);
 
-        self.send_packet_static(&AdminAuthFailedPacket).await
+        self.send_packet_dynamic(&AdminAuthSuccessPacket { role }).await
     });
 
     gs_handler!(self, handle_admin_send_notice, AdminSendNoticePacket, packet, {
-        let account_id = gs_needauth!(self);
-
-        if !self._has_perm(AdminPerm::Notice) {
-            return Ok(());
-        }
+        let account_id = self.account_id.load(Ordering::Relaxed);
 
         if packet.message.len() > MAX_NOTICE_SIZE {
-            admin_error!(self, "message is too long");
+            admin_error!(self, ErrorCode::InvalidRequest, "message is too long");
         }
 
         if packet.message.is_empty() {
@@ -145,7 +278,7 @@ impl ClientThread {
         match packet.notice_type {
             AdminSendNoticeType::Everyone => {
                 if !self._has_perm(AdminPerm::NoticeToEveryone) {
-                    admin_error!(self, "no permission");
+                    admin_error!(self, ErrorCode::NoPermission, "no permission");
                 }
 
                 let threads = self
@@ -186,6 +319,10 @@ impl ClientThread {
                 })
                 .await?;
 
+                self.game_server.state.cluster.publish(ClusterMessage::Announcement {
+                    message: notice_packet.message.try_to_string(),
+                });
+
                 for thread in threads {
                     thread.push_new_message(ServerThreadMessage::BroadcastNotice(notice_packet.clone())).await;
                 }
@@ -226,18 +363,18 @@ impl ClientThread {
                     })
                     .await?;
                 } else {
-                    admin_error!(self, "failed to find the user");
+                    admin_error!(self, ErrorCode::NotFound, "failed to find the user");
                 }
             }
 
             AdminSendNoticeType::RoomOrLevel => {
                 if packet.room_id != 0 && !self.game_server.state.room_manager.is_valid_room(packet.room_id) {
-                    admin_error!(self, "unable to send notice, invalid room ID");
+                    admin_error!(self, ErrorCode::NotFound, "unable to send notice, invalid room ID");
                 }
 
                 // if this is a global room, also require the notice to everyone perm
                 if packet.room_id == 0 && !self._has_perm(AdminPerm::NoticeToEveryone) {
-                    admin_error!(self, "no permission");
+                    admin_error!(self, ErrorCode::NoPermission, "no permission");
                 }
 
                 let player_ids = self.game_server.state.room_manager.with_any(packet.room_id, |pm| {
@@ -308,18 +445,12 @@ impl ClientThread {
     });
 
     gs_handler!(self, handle_admin_disconnect, AdminDisconnectPacket, packet, {
-        let _ = gs_needauth!(self);
-
-        if !self._has_perm(AdminPerm::Kick) {
-            return Ok(());
-        }
-
         // to kick everyone, require admin
         if &*packet.player == "@everyone" && self._has_perm(AdminPerm::KickEveryone) {
             let threads: Vec<_> = self.game_server.clients.lock().values().cloned().collect();
             for thread in threads {
                 thread
-                    .push_new_message(ServerThreadMessage::TerminationNotice(packet.message.clone()))
+                    .push_new_message(ServerThreadMessage::TerminationNotice(DisconnectReason::Kicked, packet.message.clone()))
                     .await;
             }
 
@@ -340,7 +471,9 @@ impl ClientThread {
         if let Some(thread) = self.game_server.find_user(&packet.player) {
             let reason_string = packet.message.try_to_string();
 
-            thread.push_new_message(ServerThreadMessage::TerminationNotice(packet.message)).await;
+            thread
+                .push_new_message(ServerThreadMessage::TerminationNotice(DisconnectReason::Kicked, packet.message))
+                .await;
 
             if self.game_server.bridge.has_webhook() {
                 let own_name = self.account_data.lock().name.try_to_string();
@@ -366,44 +499,79 @@ impl ClientThread {
             })
             .await
         } else {
-            admin_error!(self, "failed to find the user");
+            admin_error!(self, ErrorCode::NotFound, "failed to find the user");
         }
     });
 
     gs_handler!(self, handle_admin_get_user_state, AdminGetUserStatePacket, packet, {
-        let _ = gs_needauth!(self);
-
-        if !self._has_perm(AdminPerm::Any) {
-            return Ok(());
-        }
-
         let user = self.game_server.find_user(&packet.player);
         let mut packet = if let Some(user) = user {
             let entry = user.user_entry.lock().clone();
-            let account_data = user.account_data.lock().make_room_preview(0);
+            let account_data = user.account_data.lock().make_room_preview(0, user.is_afk.load(Ordering::Relaxed));
+            let connection = ConnectionInfo {
+                packet_loss: user.packet_loss.load(Ordering::Relaxed),
+                lossy: user.is_lossy.load(Ordering::Relaxed),
+            };
+
+            AdminUserDataPacket {
+                entry,
+                account_data: Some(account_data),
+                connection: Some(connection),
+                level_history: user.level_history.lock().to_vec(),
+            }
+        } else if let Some(recent) = self.game_server.state.recent_players.find(&packet.player) {
+            // they left a moment ago, so we still know their icons, and can look them up by account ID even if they changed their name
+            let account_id = recent.account_data.account_id;
+            let account_data = recent.account_data.make_room_preview(0, false);
+
+            let entry = if self.game_server.has_user_data() {
+                match self.game_server.fetch_user_data(&account_id.to_string()).await {
+                    Ok(x) => x,
+                    Err(err) => {
+                        warn!("error fetching data from the bridge: {err}");
+                        admin_error!(self, error_code_of(&err), &err.to_string());
+                    }
+                }
+            } else if let Some(entry) = recent.user_entry {
+                entry
+            } else {
+                admin_error!(
+                    self,
+                    ErrorCode::Unavailable,
+                    "This cannot be done on a standalone server without a database"
+                );
+            };
 
             AdminUserDataPacket {
                 entry,
                 account_data: Some(account_data),
+                connection: None,
+                level_history: recent.level_history.to_vec(),
             }
         } else {
-            // on a standalone server, if the user is not online we are kinda out of luck
-            if self.game_server.standalone {
-                admin_error!(self, "This cannot be done on a standalone server");
+            // on a standalone server without a database, if the user is not online we are kinda out of luck
+            if !self.game_server.has_user_data() {
+                admin_error!(
+                    self,
+                    ErrorCode::Unavailable,
+                    "This cannot be done on a standalone server without a database"
+                );
             }
 
-            // they are not on the server right now, request data via the bridge
-            let user_entry = match self.game_server.bridge.get_user_data(&packet.player).await {
+            // they are not on the server right now, fetch their data from the central server or the database
+            let user_entry = match self.game_server.fetch_user_data(&packet.player).await {
                 Ok(x) => x,
                 Err(err) => {
                     warn!("error fetching data from the bridge: {err}");
-                    admin_error!(self, &err.to_string());
+                    admin_error!(self, error_code_of(&err), &err.to_string());
                 }
             };
 
             AdminUserDataPacket {
                 entry: user_entry,
                 account_data: None,
+                connection: None,
+                level_history: Vec::new(),
             }
         };
 
@@ -412,19 +580,22 @@ impl ClientThread {
             packet.entry.admin_password = None;
         }
 
+        // nobody can see two-factor secrets, they can only be set up directly in the database
+        packet.entry.totp_secret = None;
+
         self.send_packet_dynamic(&packet).await
     });
 
     gs_handler!(self, handle_admin_update_user, AdminUpdateUserPacket, packet, {
-        let self_account_id = gs_needauth!(self);
-
-        if !self._has_perm(AdminPerm::Any) {
-            admin_error!(self, "No permission (not a mod)");
-        }
-
-        // we cant use bridge in standalone so do nothing
-        if self.game_server.standalone {
-            admin_error!(self, "This cannot be done on a standalone server");
+        let self_account_id = self.account_id.load(Ordering::Relaxed);
+
+        // we cant use bridge in standalone so do nothing, unless there is a local database
+        if !self.game_server.has_user_data() {
+            admin_error!(
+                self,
+                ErrorCode::Unavailable,
+                "This cannot be done on a standalone server without a database"
+            );
         }
 
         let mut new_user_entry = packet.user_entry;
@@ -437,35 +608,47 @@ impl ClientThread {
         let user_entry = if let Some(thread) = thread.as_ref() {
             thread.user_entry.lock().clone()
         } else {
-            match self.game_server.bridge.get_user_data(&target_account_id.to_string()).await {
+            match self.game_server.fetch_user_data(&target_account_id.to_string()).await {
                 Ok(x) => x,
                 Err(err) => {
-                    admin_error!(self, &format!("failed to get user: {err}"));
+                    admin_error!(self, error_code_of(&err), &format!("failed to get user: {err}"));
                 }
             }
         };
 
         let editing_self = self_account_id == target_account_id;
+        let now = unix_timestamp();
 
         // if this user has a higher priority, don't allow editing their roles
 
         new_user_entry.user_roles.retain(|x| !x.is_empty());
-        let my_priority = self.game_server.state.role_manager.compute_priority(&self.user_entry.lock().user_roles);
-        let user_priority = self.game_server.state.role_manager.compute_priority(&user_entry.user_roles);
-        let new_user_priority = self.game_server.state.role_manager.compute_priority(&new_user_entry.user_roles);
+        // expired roles would just stay around forever otherwise
+        new_user_entry.timed_roles.retain(|x| !x.role_id.is_empty() && !x.has_expired(now));
+
+        let own_roles = self.user_entry.lock().active_roles(now);
+        let my_priority = self.game_server.state.role_manager.compute_priority(&own_roles);
+        // for the target, also count timed roles that haven't started yet, so they can't be used to get around the priority checks
+        let user_priority = self.game_server.state.role_manager.compute_priority(&user_entry.assigned_roles());
+        let new_user_priority = self.game_server.state.role_manager.compute_priority(&new_user_entry.assigned_roles());
 
         // if not admin, cant update others passwords
         if !self._has_perm(AdminPerm::Admin) {
             new_user_entry.admin_password.clone_from(&user_entry.admin_password);
         }
 
+        new_user_entry.totp_secret.clone_from(&user_entry.totp_secret);
+        new_user_entry.first_seen = user_entry.first_seen;
+        new_user_entry.violation_count = user_entry.violation_count;
+        new_user_entry.preferences.clone_from(&user_entry.preferences);
+
         // if no edit role perm or the user is higher than us, cant update their roles
         if !self._has_perm(AdminPerm::EditRoles) || (user_priority >= my_priority && !self._has_perm(AdminPerm::Admin) && !editing_self) {
             new_user_entry.user_roles.clone_from(&user_entry.user_roles);
+            new_user_entry.timed_roles.clone_from(&user_entry.timed_roles);
         }
 
         // check what changed
-        let c_user_roles = new_user_entry.user_roles != user_entry.user_roles;
+        let c_user_roles = new_user_entry.user_roles != user_entry.user_roles || new_user_entry.timed_roles != user_entry.timed_roles;
         let c_is_banned = new_user_entry.is_banned != user_entry.is_banned;
         let c_is_muted = new_user_entry.is_muted != user_entry.is_muted;
         let c_is_whitelisted = new_user_entry.is_whitelisted != user_entry.is_whitelisted;
@@ -478,26 +661,30 @@ impl ClientThread {
         if c_user_roles && new_user_priority >= my_priority && !self._has_perm(AdminPerm::Admin) {
             // if we are editing ourselves, allow to assign lower roles
             if !(editing_self && new_user_priority == my_priority) {
-                admin_error!(self, "cannot promote a user to your role or higher");
+                admin_error!(self, ErrorCode::NoPermission, "cannot promote a user to your role or higher");
             }
         }
 
         if (c_is_banned || c_is_whitelisted) && !self._has_perm(AdminPerm::Ban) {
-            admin_error!(self, "no permission to ban/whitelist");
+            admin_error!(self, ErrorCode::NoPermission, "no permission to ban/whitelist");
         }
 
         if c_is_muted && !self._has_perm(AdminPerm::Mute) {
-            admin_error!(self, "no permission to mute");
+            admin_error!(self, ErrorCode::NoPermission, "no permission to mute");
         }
 
         // role validation
-        if !self.game_server.state.role_manager.all_valid(&new_user_entry.user_roles) {
-            admin_error!(self, "attempting to assign an invalid role");
+        if !self.game_server.state.role_manager.all_valid(&new_user_entry.assigned_roles()) {
+            admin_error!(self, ErrorCode::InvalidRequest, "attempting to assign an invalid role");
         }
 
         if let Some(color) = new_user_entry.name_color.as_ref() {
             if color.parse::<Color3B>().is_err() {
-                admin_error!(self, &format!("attempting to assign an invalid name color: {color}"));
+                admin_error!(
+                    self,
+                    ErrorCode::InvalidRequest,
+                    &format!("attempting to assign an invalid name color: {color}")
+                );
             }
         }
 
@@ -514,6 +701,11 @@ impl ClientThread {
             return self.send_packet_dynamic(&AdminSuccessMessagePacket { message: "No changes" }).await;
         }
 
+        // every new ban or mute counts towards the violation count, which lowers the trust tier
+        if (c_is_banned && new_user_entry.is_banned) || (c_is_muted && new_user_entry.is_muted) {
+            new_user_entry.violation_count = new_user_entry.violation_count.saturating_add(1);
+        }
+
         // if not banned and not muted, clear the violation reason and duration
         if !new_user_entry.is_banned && !new_user_entry.is_muted {
             new_user_entry.violation_expiry = None;
@@ -529,17 +721,19 @@ impl ClientThread {
 
             // update the role
             if c_user_roles {
-                let special_data = SpecialUserData::from_user_entry(&new_user_entry, &self.game_server.state.role_manager);
+                let roles = new_user_entry.active_roles(now);
+                let special_data = self.game_server.state.role_manager.special_user_data(&roles);
                 thread.account_data.lock().special_user_data.clone_from(&special_data);
 
                 // tell the user that their roles changed
                 thread
                     .push_new_message(ServerThreadMessage::BroadcastRoleChange(RolesUpdatedPacket {
                         special_user_data: special_data,
+                        all_roles: None,
                     }))
                     .await;
 
-                let new_role = self.game_server.state.role_manager.compute(&new_user_entry.user_roles);
+                let new_role = self.game_server.state.role_manager.compute(&roles);
                 *thread.user_role.lock() = new_role;
             }
 
@@ -572,12 +766,23 @@ impl ClientThread {
 
             res
         } else {
-            // otherwise just make a manual bridge request
-            self.game_server.bridge.update_user_data(&new_user_entry).await.map_err(Into::into)
+            // otherwise just save it manually
+            self.game_server.save_user_data(&new_user_entry).await
         };
 
         match result {
             Ok(()) => {
+                // the player might be connected to another server in the cluster
+                if c_is_banned || c_is_muted || c_violation_reason || c_violation_expiry {
+                    self.game_server.state.cluster.publish(ClusterMessage::Sanction {
+                        account_id: new_user_entry.account_id,
+                        is_banned: new_user_entry.is_banned,
+                        is_muted: new_user_entry.is_muted,
+                        reason: new_user_entry.violation_reason.clone(),
+                        expiry: new_user_entry.violation_expiry,
+                    });
+                }
+
                 let own_name = self.account_data.lock().name.try_to_string();
 
                 info!(
@@ -655,8 +860,888 @@ impl ClientThread {
             }
             Err(err) => {
                 warn!("error from bridge: {err}");
-                admin_error!(self, &err.to_string());
+                admin_error!(self, error_code_of(&err), &err.to_string());
+            }
+        }
+    });
+
+    gs_handler!(self, handle_admin_debug_dump, AdminDebugDumpPacket, _packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        let violations = self.game_server.check_invariants();
+        for violation in &violations {
+            warn!("invariant violated: {violation}");
+        }
+
+        let dump_result = match std::env::current_dir() {
+            Ok(dir) => self.game_server.write_state_dump(&dir.join("state-dumps")).await,
+            Err(err) => Err(err),
+        };
+
+        let dump_path = match dump_result {
+            Ok(x) => x,
+            Err(err) => {
+                warn!("failed to write the state dump: {err}");
+                admin_error!(self, ErrorCode::Internal, &format!("failed to write the state dump: {err}"));
+            }
+        };
+
+        info!(
+            "[{} ({}) @ {}] dumped the server state to {}",
+            self.account_data.lock().name,
+            account_id,
+            self.get_tcp_peer(),
+            dump_path.display()
+        );
+
+        // the full path is only logged, clients don't need to know where the server lives
+        let file_name = dump_path.file_name().map(|x| x.to_string_lossy()).unwrap_or_default();
+
+        let message = if violations.is_empty() {
+            format!("No invariant violations found, state dumped to {file_name}")
+        } else {
+            format!(
+                "{} invariant violations found (first one: {}), state dumped to {file_name}",
+                violations.len(),
+                violations[0],
+            )
+        };
+
+        self.send_packet_dynamic(&AdminSuccessMessagePacket { message: &message }).await
+    });
+
+    gs_handler!(self, handle_admin_set_chaos, AdminSetChaosPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        if !chaos::is_supported() {
+            admin_error!(self, ErrorCode::Unavailable, "This server was built without chaos support");
+        }
+
+        info!(
+            "[{} ({}) @ {}] changed chaos settings",
+            self.account_data.lock().name,
+            account_id,
+            self.get_tcp_peer()
+        );
+
+        chaos::set(ChaosSettings {
+            bridge_delay: packet.bridge_delay,
+            broadcast_drop_chance: packet.broadcast_drop_chance,
+            disconnect_chance: packet.disconnect_chance,
+        });
+
+        self.send_packet_dynamic(&AdminSuccessMessagePacket {
+            message: "Successfully updated chaos settings",
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_set_level_blocked, AdminSetLevelBlockedPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        if packet.level_id <= 0 {
+            admin_error!(self, ErrorCode::InvalidRequest, "invalid level ID");
+        }
+
+        if !self.game_server.state.level_blocklist.set_blocked(packet.level_id, packet.blocked) {
+            return self
+                .send_packet_dynamic(&AdminSuccessMessagePacket {
+                    message: if packet.blocked {
+                        "Level is already blocked"
+                    } else {
+                        "Level is not blocked"
+                    },
+                })
+                .await;
+        }
+
+        info!(
+            "[{} ({}) @ {}] {} level {}",
+            self.account_data.lock().name,
+            account_id,
+            self.get_tcp_peer(),
+            if packet.blocked { "blocked" } else { "unblocked" },
+            packet.level_id
+        );
+
+        if packet.blocked {
+            self.game_server.remove_from_blocked_level(packet.level_id).await;
+        }
+
+        if self.game_server.bridge.has_webhook() {
+            let own_name = self.account_data.lock().name.try_to_string();
+
+            if let Err(err) = self
+                .game_server
+                .bridge
+                .send_webhook_message(WebhookMessage::LevelBlockChanged(own_name, packet.level_id, packet.blocked))
+                .await
+            {
+                warn!("webhook error: {err}");
+            }
+        }
+
+        self.send_packet_dynamic(&AdminSuccessMessagePacket {
+            message: if packet.blocked {
+                "Successfully blocked the level"
+            } else {
+                "Successfully unblocked the level"
+            },
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_set_slow_mode, AdminSetSlowModePacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        if packet.level_id < 0 {
+            admin_error!(self, ErrorCode::InvalidRequest, "invalid level ID");
+        }
+
+        // a level ID applies to the level in every room, otherwise the room itself is changed
+        let target = if packet.level_id != 0 {
+            self.game_server.state.chat.set_level_slow_mode(packet.level_id, packet.seconds);
+            format!("level {}", packet.level_id)
+        } else {
+            let found = self.game_server.state.room_manager.try_with_any(
+                packet.room_id,
+                |room| {
+                    room.settings.slow_mode = packet.seconds;
+                    true
+                },
+                || false,
+            );
+
+            if !found {
+                admin_error!(self, ErrorCode::NotFound, "room not found");
+            }
+
+            if packet.room_id != 0 {
+                self.game_server.broadcast_room_info(packet.room_id).await;
+            }
+
+            format!("room {}", packet.room_id)
+        };
+
+        info!(
+            "[{} ({}) @ {}] set the slow mode of {} to {}s",
+            self.account_data.lock().name,
+            account_id,
+            self.get_tcp_peer(),
+            target,
+            packet.seconds
+        );
+
+        self.send_packet_dynamic(&AdminSuccessMessagePacket {
+            message: "Successfully changed the slow mode",
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_take_over_room, AdminTakeOverRoomPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        if packet.room_id == 0 {
+            admin_error!(self, ErrorCode::InvalidRequest, "the global room cannot be taken over");
+        }
+
+        let room_manager = &self.game_server.state.room_manager;
+
+        let previous = room_manager.try_with_any(
+            packet.room_id,
+            |room| {
+                let previous_owner = std::mem::replace(&mut room.owner, account_id);
+                room.moderators.retain(|id| *id != account_id);
+                Some((previous_owner, room.name.try_to_string()))
+            },
+            || None,
+        );
+
+        let Some((previous_owner, room_name)) = previous else {
+            admin_error!(self, ErrorCode::NotFound, "room not found");
+        };
+
+        // join the room if we aren't in it yet, ignoring the password and the player limit
+        let old_room_id = self.room_id.swap(packet.room_id, Ordering::Relaxed);
+        if old_room_id != packet.room_id {
+            let level_id = self.level_id.load(Ordering::Relaxed);
+
+            if room_manager.remove_with_any(old_room_id, account_id, level_id) {
+                self.game_server.broadcast_room_info(old_room_id).await;
+            }
+
+            room_manager.with_any(packet.room_id, |pm| {
+                pm.manager.create_player(account_id);
+            });
+
+            self.emit_presence_change();
+            self.send_packet_static(&RoomJoinedPacket).await?;
+        }
+
+        self.game_server.broadcast_room_info(packet.room_id).await;
+
+        info!(
+            "[{} ({}) @ {}] took over room {} ({}), previously owned by {}",
+            self.account_data.lock().name,
+            account_id,
+            self.get_tcp_peer(),
+            room_name,
+            packet.room_id,
+            previous_owner
+        );
+
+        if self.game_server.bridge.has_webhook() {
+            let own_name = self.account_data.lock().name.try_to_string();
+
+            if let Err(err) = self
+                .game_server
+                .bridge
+                .send_webhook_message(WebhookMessage::RoomTakenOver(own_name, packet.room_id, room_name, previous_owner))
+                .await
+            {
+                warn!("webhook error: {err}");
             }
         }
+
+        self.send_packet_dynamic(&AdminSuccessMessagePacket {
+            message: "Successfully took over the room",
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_get_player_history, AdminGetPlayerHistoryPacket, packet, {
+        let interval = self.game_server.bridge.central_conf.lock().player_history_interval;
+        if interval == 0 {
+            admin_error!(self, ErrorCode::Unavailable, "player count history is disabled on this server");
+        }
+
+        let since = unix_timestamp() - i64::from(packet.period);
+        let samples = self.game_server.state.player_history.samples_since(since);
+
+        // skip samples evenly so the response doesn't get too big, the client can't show more than that anyway
+        let stride = samples.len().div_ceil(MAX_PLAYER_HISTORY_POINTS).max(1);
+
+        let points = samples
+            .iter()
+            .step_by(stride)
+            .map(|sample| PlayerCountPoint {
+                timestamp: sample.timestamp,
+                count: sample.count_for(packet.room_id, packet.level_id),
+            })
+            .collect();
+
+        self.send_packet_dynamic(&AdminPlayerHistoryPacket {
+            interval: (interval * stride as u64) as u32,
+            points,
+        })
+        .await
     });
+
+    gs_handler!(self, handle_admin_schedule_notice, AdminScheduleNoticePacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        if packet.message.len() > MAX_NOTICE_SIZE {
+            admin_error!(self, ErrorCode::InvalidRequest, "message is too long");
+        }
+
+        if packet.message.is_empty() {
+            admin_error!(self, ErrorCode::InvalidRequest, "message is empty");
+        }
+
+        let now = unix_timestamp();
+        if packet.send_at <= now {
+            admin_error!(self, ErrorCode::InvalidRequest, "the notice must be scheduled for a time in the future");
+        }
+
+        if packet.send_at - now > MAX_NOTICE_SCHEDULE_AHEAD {
+            admin_error!(self, ErrorCode::InvalidRequest, "notices can't be scheduled more than 30 days ahead");
+        }
+
+        let everyone = match packet.notice_type {
+            AdminSendNoticeType::Everyone => true,
+            AdminSendNoticeType::RoomOrLevel => false,
+            AdminSendNoticeType::Person => {
+                admin_error!(self, ErrorCode::InvalidRequest, "notices to a single person can't be scheduled");
+            }
+        };
+
+        if (everyone || packet.room_id == 0) && !self._has_perm(AdminPerm::NoticeToEveryone) {
+            admin_error!(self, ErrorCode::NoPermission, "no permission");
+        }
+
+        if !everyone && packet.room_id != 0 && !self.game_server.state.room_manager.is_valid_room(packet.room_id) {
+            admin_error!(self, ErrorCode::NotFound, "unable to schedule notice, invalid room ID");
+        }
+
+        let name = self.account_data.lock().name.try_to_string();
+        let message = packet.message.try_to_string();
+
+        let id = self.game_server.state.notice_scheduler.schedule(ScheduledNotice {
+            id: 0,
+            send_at: packet.send_at,
+            author: name.clone(),
+            author_id: account_id,
+            everyone,
+            room_id: packet.room_id,
+            level_id: packet.level_id,
+            message: message.clone(),
+        });
+
+        let Some(id) = id else {
+            admin_error!(
+                self,
+                ErrorCode::LimitReached,
+                &format!("too many scheduled notices, at most {MAX_SCHEDULED_NOTICES} are allowed")
+            );
+        };
+
+        info!(
+            "[{name} ({account_id}) @ {}] scheduled notice #{id} for {}: {message}",
+            self.get_tcp_peer(),
+            packet.send_at
+        );
+
+        if self.game_server.bridge.has_webhook() {
+            if let Err(err) = self
+                .game_server
+                .bridge
+                .send_webhook_message(WebhookMessage::NoticeScheduled(name, packet.send_at, message))
+                .await
+            {
+                warn!("webhook error: {err}");
+            }
+        }
+
+        self.send_packet_dynamic(&AdminSuccessMessagePacket {
+            message: &format!("Scheduled notice #{id}"),
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_get_scheduled_notices, AdminGetScheduledNoticesPacket, _packet, {
+        self.send_packet_dynamic(&AdminScheduledNoticesPacket {
+            notices: self.game_server.state.notice_scheduler.list(),
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_cancel_scheduled_notice, AdminCancelScheduledNoticePacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        let Some(notice) = self.game_server.state.notice_scheduler.get(packet.id) else {
+            admin_error!(self, ErrorCode::NotFound, "this notice does not exist or was already sent");
+        };
+
+        // notices scheduled by someone else can only be cancelled by those who could have sent them to everyone
+        if notice.author_id != account_id && !self._has_perm(AdminPerm::NoticeToEveryone) {
+            admin_error!(self, ErrorCode::NoPermission, "no permission to cancel notices scheduled by others");
+        }
+
+        if self.game_server.state.notice_scheduler.cancel(packet.id).is_none() {
+            admin_error!(self, ErrorCode::NotFound, "this notice does not exist or was already sent");
+        }
+
+        let name = self.account_data.lock().name.try_to_string();
+
+        info!(
+            "[{name} ({account_id}) @ {}] cancelled scheduled notice #{} by {}",
+            self.get_tcp_peer(),
+            notice.id,
+            notice.author
+        );
+
+        if self.game_server.bridge.has_webhook() {
+            if let Err(err) = self
+                .game_server
+                .bridge
+                .send_webhook_message(WebhookMessage::ScheduledNoticeCancelled(name, notice.author, notice.message))
+                .await
+            {
+                warn!("webhook error: {err}");
+            }
+        }
+
+        self.send_packet_dynamic(&AdminSuccessMessagePacket {
+            message: &format!("Cancelled notice #{}", packet.id),
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_get_traffic_stats, AdminGetTrafficStatsPacket, packet, {
+        if packet.reset && !self._has_perm(AdminPerm::Admin) {
+            admin_error!(self, ErrorCode::NoPermission, "no permission to reset the traffic stats");
+        }
+
+        let traffic = &self.game_server.state.traffic;
+
+        let since = traffic.since();
+        let (rooms, levels) = traffic.top(usize::from(packet.count.min(MAX_TRAFFIC_ENTRIES)));
+
+        if packet.reset {
+            traffic.reset();
+        }
+
+        self.send_packet_dynamic(&AdminTrafficStatsPacket { since, rooms, levels }).await
+    });
+
+    gs_handler!(self, handle_admin_spawn_bot, AdminSpawnBotPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        let bot_name = packet.name.try_to_str().trim();
+        if bot_name.is_empty() {
+            admin_error!(self, ErrorCode::InvalidRequest, "the bot must have a name");
+        }
+
+        if packet.room_id != 0 && !self.game_server.state.room_manager.is_valid_room(packet.room_id) {
+            admin_error!(self, ErrorCode::NotFound, "this room does not exist");
+        }
+
+        if packet.level_id != 0 && self.game_server.state.level_blocklist.is_blocked(packet.level_id) {
+            admin_error!(self, ErrorCode::LevelBlocked, "this level is blocked");
+        }
+
+        // bots look like the admin who spawned them
+        let (own_name, icons) = {
+            let account_data = self.account_data.lock();
+            (account_data.name.clone(), account_data.icons.clone())
+        };
+
+        let bot = Bot::new(bot_name, packet.room_id, packet.level_id, icons, own_name.clone());
+        let full_name = bot.name.try_to_string();
+
+        let Some(bot_id) = self.game_server.state.bots.spawn(bot) else {
+            admin_error!(self, ErrorCode::LimitReached, &format!("too many bots, at most {MAX_BOTS} are allowed"));
+        };
+
+        // if the room was deleted in the meantime, the bot is removed on the next update
+        self.game_server.state.room_manager.try_with_any(
+            packet.room_id,
+            |room| {
+                room.manager.create_player(bot_id);
+
+                if packet.level_id != 0 {
+                    room.manager.add_to_level(packet.level_id, bot_id);
+                }
+            },
+            || {},
+        );
+
+        let own_name = own_name.try_to_string();
+
+        info!(
+            "[{own_name} ({account_id}) @ {}] spawned bot {full_name} ({bot_id}) in room {} on level {}",
+            self.get_tcp_peer(),
+            packet.room_id,
+            packet.level_id
+        );
+
+        if self.game_server.bridge.has_webhook() {
+            if let Err(err) = self
+                .game_server
+                .bridge
+                .send_webhook_message(WebhookMessage::BotSpawned(own_name, full_name, packet.room_id, packet.level_id))
+                .await
+            {
+                warn!("webhook error: {err}");
+            }
+        }
+
+        self.send_packet_dynamic(&AdminBotsPacket {
+            bots: self.game_server.state.bots.list(),
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_move_bot, AdminMoveBotPacket, packet, {
+        if packet.level_id != 0 && self.game_server.state.level_blocklist.is_blocked(packet.level_id) {
+            admin_error!(self, ErrorCode::LevelBlocked, "this level is blocked");
+        }
+
+        let moved = self.game_server.state.bots.with_bot(packet.account_id, |bot| {
+            let old_level = bot.level_id;
+            bot.level_id = packet.level_id;
+            bot.position = packet.position;
+
+            (bot.room_id, old_level)
+        });
+
+        let Some((room_id, old_level)) = moved else {
+            admin_error!(self, ErrorCode::NotFound, "this bot does not exist");
+        };
+
+        // the new position is sent to players on the next bot update
+        if old_level != packet.level_id {
+            self.game_server
+                .state
+                .room_manager
+                .try_with_any(room_id, |room| room.manager.move_to_level(packet.level_id, packet.account_id), || {});
+        }
+
+        self.send_packet_dynamic(&AdminBotsPacket {
+            bots: self.game_server.state.bots.list(),
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_bot_chat, AdminBotChatPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        if packet.message.is_empty() {
+            return Ok(());
+        }
+
+        let Some(bot) = self.game_server.state.bots.get(packet.account_id) else {
+            admin_error!(self, ErrorCode::NotFound, "this bot does not exist");
+        };
+
+        if bot.level_id == 0 {
+            admin_error!(self, ErrorCode::InvalidRequest, "the bot must be on a level to chat");
+        }
+
+        info!(
+            "[{} ({account_id}) @ {}] made bot {} say: {}",
+            self.account_data.lock().name,
+            self.get_tcp_peer(),
+            bot.name,
+            packet.message
+        );
+
+        self.game_server.state.events.emit(|| ServerEvent::ChatMessage {
+            account_id: bot.account_id,
+            room_id: bot.room_id,
+            level_id: bot.level_id,
+            message: packet.message.try_to_string(),
+        });
+
+        // recorded like any other message, so moderators can delete it
+        let message_id = self.game_server.state.chat.record(bot.account_id, bot.room_id, bot.level_id);
+
+        let cpkt = ChatMessageBroadcastPacket {
+            player_id: bot.account_id,
+            message_id,
+            message: packet.message,
+        };
+
+        self.game_server.broadcast_chat_packet(&cpkt, bot.level_id, bot.room_id).await;
+
+        Ok(())
+    });
+
+    gs_handler!(self, handle_admin_remove_bot, AdminRemoveBotPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        let Some(bot) = self.game_server.state.bots.remove(packet.account_id) else {
+            admin_error!(self, ErrorCode::NotFound, "this bot does not exist");
+        };
+
+        if bot.room_id == 0 || self.game_server.state.room_manager.is_valid_room(bot.room_id) {
+            self.game_server
+                .state
+                .room_manager
+                .remove_with_any(bot.room_id, bot.account_id, bot.level_id);
+        }
+
+        info!(
+            "[{} ({account_id}) @ {}] removed bot {} ({})",
+            self.account_data.lock().name,
+            self.get_tcp_peer(),
+            bot.name,
+            bot.account_id
+        );
+
+        self.send_packet_dynamic(&AdminBotsPacket {
+            bots: self.game_server.state.bots.list(),
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_get_bots, AdminGetBotsPacket, _packet, {
+        self.send_packet_dynamic(&AdminBotsPacket {
+            bots: self.game_server.state.bots.list(),
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_create_invite_code, AdminCreateInviteCodePacket, packet, {
+        if packet.uses == 0 || packet.uses > MAX_INVITE_CODE_USES {
+            admin_error!(
+                self,
+                ErrorCode::InvalidRequest,
+                &format!("an invite code can be used between 1 and {MAX_INVITE_CODE_USES} times")
+            );
+        }
+
+        let Some(code) = self.game_server.state.invite_codes.create(packet.uses) else {
+            admin_error!(
+                self,
+                ErrorCode::LimitReached,
+                &format!("too many invite codes, at most {MAX_INVITE_CODES} are allowed")
+            );
+        };
+
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let own_name = self.account_data.lock().name.try_to_string();
+
+        info!(
+            "[{own_name} ({account_id}) @ {}] created a whitelist invite code with {} uses",
+            self.get_tcp_peer(),
+            packet.uses
+        );
+
+        if self.game_server.bridge.has_webhook() {
+            if let Err(err) = self
+                .game_server
+                .bridge
+                .send_webhook_message(WebhookMessage::InviteCodeCreated(own_name, packet.uses))
+                .await
+            {
+                warn!("webhook error: {err}");
+            }
+        }
+
+        self.send_packet_static(&AdminInviteCodePacket {
+            code: InlineString::new(&code),
+            uses: packet.uses,
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_get_reports, AdminGetReportsPacket, _packet, {
+        self.send_admin_reports().await
+    });
+
+    gs_handler!(self, handle_admin_update_report, AdminUpdateReportPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        let change = ReportStateChange {
+            id: packet.id,
+            moderator_id: account_id,
+            state: packet.state,
+        };
+
+        let report = match self.game_server.update_report_state(&change).await {
+            Ok(x) => x,
+            Err(err) => {
+                warn!("error updating a report: {err}");
+                admin_error!(self, error_code_of(&err), &err.to_string());
+            }
+        };
+
+        // the central server returns the report unchanged if someone else got to it first
+        let expected_handler = if packet.state == ReportState::Open { 0 } else { account_id };
+        if report.state != packet.state || report.handled_by != expected_handler {
+            let message = match report.state {
+                ReportState::Claimed if report.handled_by != account_id => {
+                    let name = self.game_server.get_known_player_name(report.handled_by);
+                    if name.is_empty() {
+                        format!("this report was already claimed by account {}", report.handled_by)
+                    } else {
+                        format!("this report was already claimed by {}", name.try_to_str())
+                    }
+                }
+                ReportState::Resolved | ReportState::Rejected => "this report was already closed".to_owned(),
+                _ => "claim this report first".to_owned(),
+            };
+
+            admin_error!(self, ErrorCode::Conflict, &message);
+        }
+
+        let own_name = self.account_data.lock().name.try_to_string();
+
+        info!(
+            "[{own_name} ({account_id}) @ {}] changed the state of report #{} to {:?}",
+            self.get_tcp_peer(),
+            packet.id,
+            packet.state
+        );
+
+        if self.game_server.bridge.has_webhook() {
+            if let Err(err) = self
+                .game_server
+                .bridge
+                .send_webhook_message(WebhookMessage::ReportStateChanged(own_name, packet.id, packet.state))
+                .await
+            {
+                warn!("webhook error: {err}");
+            }
+        }
+
+        self.send_admin_reports().await
+    });
+
+    gs_handler!(self, handle_admin_tail_chat, AdminTailChatPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let chat_tails = &self.game_server.state.chat_tails;
+
+        let (room_id, level_id) = if packet.enabled {
+            if packet.room_id == 0 && packet.level_id == 0 {
+                admin_error!(self, ErrorCode::InvalidRequest, "pick a level to follow chat in the global room");
+            }
+
+            if packet.room_id != 0 && !self.game_server.state.room_manager.is_valid_room(packet.room_id) {
+                admin_error!(self, ErrorCode::NotFound, "room not found");
+            }
+
+            chat_tails.start(account_id, packet.room_id, packet.level_id);
+            (packet.room_id, packet.level_id)
+        } else if let Some(tail) = chat_tails.stop(account_id) {
+            tail
+        } else {
+            return Ok(());
+        };
+
+        let own_name = self.account_data.lock().name.try_to_string();
+
+        info!(
+            "[{own_name} ({account_id}) @ {}] {} following chat in room {room_id}, level {level_id}",
+            self.get_tcp_peer(),
+            if packet.enabled { "started" } else { "stopped" },
+        );
+
+        if self.game_server.bridge.has_webhook() {
+            if let Err(err) = self
+                .game_server
+                .bridge
+                .send_webhook_message(WebhookMessage::ChatTailChanged(own_name, room_id, level_id, packet.enabled))
+                .await
+            {
+                warn!("webhook error: {err}");
+            }
+        }
+
+        self.send_packet_dynamic(&AdminSuccessMessagePacket {
+            message: if packet.enabled {
+                "Started following chat"
+            } else {
+                "Stopped following chat"
+            },
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_set_config_override, AdminSetConfigOverridePacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let own_name = self.account_data.lock().name.try_to_string();
+        let bridge = &self.game_server.bridge;
+
+        if packet.duration == 0 {
+            let Some(removed) = bridge.remove_config_override(packet.key) else {
+                admin_error!(self, ErrorCode::NotFound, "this value is not overridden");
+            };
+
+            info!(
+                "[{own_name} ({account_id}) @ {}] removed the override of {}, back to {}",
+                self.get_tcp_peer(),
+                packet.key.name(),
+                removed.original
+            );
+
+            if bridge.has_webhook() {
+                if let Err(err) = bridge
+                    .send_webhook_message(WebhookMessage::ConfigOverrideRemoved(Some(own_name), packet.key, removed.original))
+                    .await
+                {
+                    warn!("webhook error: {err}");
+                }
+            }
+
+            return self
+                .send_packet_dynamic(&AdminSuccessMessagePacket {
+                    message: "Successfully removed the override",
+                })
+                .await;
+        }
+
+        if u64::from(packet.duration) > MAX_OVERRIDE_DURATION.as_secs() {
+            admin_error!(self, ErrorCode::InvalidRequest, "overrides can't last longer than a day");
+        }
+
+        let valid = match packet.key {
+            ConfigOverrideKey::Tps => (1..=MAX_OVERRIDE_TPS).contains(&packet.value),
+            ConfigOverrideKey::Maintenance => packet.value <= 1,
+            ConfigOverrideKey::ChatBurstLimit | ConfigOverrideKey::ChatBurstInterval => true,
+        };
+
+        if !valid {
+            admin_error!(self, ErrorCode::InvalidRequest, "invalid value");
+        }
+
+        let expires_at = unix_timestamp() + i64::from(packet.duration);
+        bridge.set_config_override(packet.key, packet.value, expires_at, own_name.clone());
+
+        info!(
+            "[{own_name} ({account_id}) @ {}] overrode {} to {} for {}s",
+            self.get_tcp_peer(),
+            packet.key.name(),
+            packet.value,
+            packet.duration
+        );
+
+        if bridge.has_webhook() {
+            if let Err(err) = bridge
+                .send_webhook_message(WebhookMessage::ConfigOverrideSet(own_name, packet.key, packet.value, expires_at))
+                .await
+            {
+                warn!("webhook error: {err}");
+            }
+        }
+
+        self.send_packet_dynamic(&AdminSuccessMessagePacket {
+            message: "Successfully overrode the value",
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_get_config_overrides, AdminGetConfigOverridesPacket, _packet, {
+        self.send_packet_dynamic(&AdminConfigOverridesPacket {
+            overrides: self.game_server.bridge.config_overrides.list(),
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_reload_config, AdminReloadConfigPacket, _packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let own_name = self.account_data.lock().name.try_to_string();
+
+        if let Err(err) = self.game_server.reload_config().await {
+            warn!("failed to reload the configuration: {err}");
+            admin_error!(self, error_code_of(&err), &err.to_string());
+        }
+
+        info!("[{own_name} ({account_id}) @ {}] reloaded the configuration", self.get_tcp_peer());
+
+        let bridge = &self.game_server.bridge;
+        if bridge.has_webhook() {
+            if let Err(err) = bridge.send_webhook_message(WebhookMessage::ConfigReloaded(own_name)).await {
+                warn!("webhook error: {err}");
+            }
+        }
+
+        self.send_packet_dynamic(&AdminSuccessMessagePacket {
+            message: "Successfully reloaded the configuration",
+        })
+        .await
+    });
+
+    async fn send_admin_reports(&self) -> Result<()> {
+        let reports = match self.game_server.fetch_pending_reports().await {
+            Ok(x) => x,
+            Err(err) => {
+                warn!("error fetching reports: {err}");
+                admin_error!(self, error_code_of(&err), &err.to_string());
+            }
+        };
+
+        let reports = reports
+            .into_iter()
+            .map(|report| AdminReport {
+                reporter_name: self.game_server.get_known_player_name(report.reporter_id),
+                reported_name: self.game_server.get_known_player_name(report.reported_id),
+                handled_by_name: self.game_server.get_known_player_name(report.handled_by),
+                report,
+            })
+            .collect();
+
+        self.send_packet_dynamic(&AdminReportsPacket { reports }).await
+    }
 }
@@ -1,19 +1,26 @@
-use std::sync::atomic::Ordering;
+use std::{sync::atomic::Ordering, time::Duration};
 
 use super::*;
 
+/// bounds of the recommended interpolation window, in milliseconds
+const MIN_INTERPOLATION_WINDOW: u32 = 50;
+const MAX_INTERPOLATION_WINDOW: u32 = 500;
+/// the lowest send rate we recommend to clients with a lossy or saturated connection
+const MIN_SEND_RATE: u32 = 10;
+/// voice bitrate for clients that were asked to send less
+const THROTTLED_VOICE_BITRATE: u32 = 12_000;
+
 impl ClientThread {
     gs_handler!(self, handle_ping, PingPacket, packet, {
         self.send_packet_static(&PingResponsePacket {
             id: packet.id,
             player_count: self.game_server.state.get_player_count(),
+            distance_class: self.game_server.state.get_distance_class(self.get_tcp_peer().ip()),
         })
         .await
     });
 
     gs_handler!(self, handle_keepalive, KeepalivePacket, _packet, {
-        let _ = gs_needauth!(self);
-
         self.send_packet_static(&KeepaliveResponsePacket {
             player_count: self.game_server.state.get_player_count(),
         })
@@ -26,11 +33,161 @@ impl ClientThread {
     });
 
     gs_handler!(self, handle_keepalive_tcp, KeepaliveTCPPacket, _packet, {
-        let _ = gs_needauth!(self);
-
         self.send_packet_static(&KeepaliveTCPResponsePacket).await
     });
 
+    gs_handler!(self, handle_rekey_response, RekeyResponsePacket, packet, {
+        // safety: only we can access our socket.
+        unsafe { self.socket.get_mut() }.finish_rekey(&packet.key)
+    });
+
+    gs_handler!(self, handle_latency_probe_response, LatencyProbeResponsePacket, packet, {
+        // safety: only we can use the estimator.
+        let Some(rtt) = unsafe { self.latency_estimator.get_mut() }.record_response(packet.id) else {
+            return Ok(());
+        };
+
+        let hints = self.compute_settings_hints(rtt);
+
+        // safety: only we can access the sent hints.
+        let sent_hints = unsafe { self.sent_hints.get_mut() };
+        if sent_hints.is_some_and(|sent| sent == hints) {
+            return Ok(());
+        }
+
+        *sent_hints = Some(hints);
+
+        self.send_packet_static(&SettingsHintsPacket { hints }).await
+    });
+
+    /// Picks client settings that suit the connection of this client and the amount of players on its level.
+    fn compute_settings_hints(&self, rtt: Duration) -> SettingsHints {
+        let tps = self.game_server.bridge.central_conf.lock().tps.max(1);
+        let tick_ms = 1000 / tps;
+        let lossy = self.is_lossy.load(Ordering::Relaxed);
+        let loss = u32::from(self.packet_loss.load(Ordering::Relaxed));
+
+        // long enough to cover two missed updates and the jitter, which usually grows together with the round trip time.
+        // rounded up to 25ms so that tiny changes in the rtt don't make us send new hints every time
+        let window = tick_ms * 2 + rtt.as_millis().min(10_000) as u32 / 2 + loss * tick_ms / 10;
+        let interpolation_window = window
+            .div_ceil(25)
+            .saturating_mul(25)
+            .clamp(MIN_INTERPOLATION_WINDOW, MAX_INTERPOLATION_WINDOW);
+
+        let level_id = self.level_id.load(Ordering::Relaxed);
+        let level_players = if level_id == 0 {
+            0
+        } else {
+            let room_id = self.room_id.load(Ordering::Relaxed);
+            self.game_server
+                .state
+                .room_manager
+                .with_any(room_id, |pm| pm.manager.get_player_count_on_level(level_id).unwrap_or(0))
+        };
+
+        // voice is forwarded to everyone on the level, so the more people there are, the less each of them should send
+        let mut voice_bitrate = match level_players {
+            0..=8 => 32_000,
+            9..=24 => 24_000,
+            _ => 16_000,
+        };
+
+        // a lossy connection is usually a congested one, sending less helps it more than it hurts the quality
+        let send_rate = if lossy {
+            voice_bitrate = voice_bitrate.min(16_000);
+            (tps / 2).max(MIN_SEND_RATE).min(tps)
+        } else {
+            tps
+        };
+
+        SettingsHints {
+            interpolation_window: interpolation_window as u16,
+            voice_bitrate,
+            send_rate: send_rate as u16,
+        }
+    }
+
+    /// Tells the client to send less if its packets keep piling up, or that it can go back to normal once they stop.
+    pub(crate) async fn maybe_send_throttle_hint(&self) -> Result<()> {
+        // safety: only we can use the tracker.
+        let Some(throttled) = unsafe { self.backpressure.get_mut() }.take_change() else {
+            return Ok(());
+        };
+
+        let packet = if throttled {
+            let tps = self.game_server.bridge.central_conf.lock().tps.max(1);
+
+            debug!(
+                "[{}] packets are piling up, asking the client to send less",
+                self.account_id.load(Ordering::Relaxed)
+            );
+
+            ThrottleHintPacket {
+                throttled,
+                send_rate: (tps / 2).max(MIN_SEND_RATE).min(tps) as u16,
+                voice_bitrate: THROTTLED_VOICE_BITRATE,
+            }
+        } else {
+            ThrottleHintPacket {
+                throttled,
+                send_rate: 0,
+                voice_bitrate: 0,
+            }
+        };
+
+        self.send_packet_static(&packet).await
+    }
+
+    gs_handler!(self, handle_claim_transfer, ClaimTransferPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        // standalone servers can't take part in transfers, since the tokens are kept by the central server
+        if self.game_server.standalone {
+            return Ok(());
+        }
+
+        let ticket = match self
+            .game_server
+            .bridge
+            .redeem_transfer_token(packet.token.to_str().unwrap_or_default())
+            .await
+        {
+            Ok(x) => x,
+            Err(err) => {
+                warn!("[{account_id} @ {}] failed to redeem a transfer token: {err}", self.get_tcp_peer());
+                return Ok(());
+            }
+        };
+
+        if ticket.account_id != account_id {
+            warn!(
+                "[{account_id} @ {}] tried to claim a transfer of another account ({})",
+                self.get_tcp_peer(),
+                ticket.account_id
+            );
+            return Ok(());
+        }
+
+        let mut reader = ByteReader::from_bytes(&ticket.payload);
+        let Some(transferred) = reader.read_value::<Option<TransferredRoom>>()? else {
+            // they were in the global room, which they already are in
+            return Ok(());
+        };
+
+        let room_manager = &self.game_server.state.room_manager;
+
+        let old_room_id = self.room_id.load(Ordering::Relaxed);
+        let level_id = self.level_id.load(Ordering::Relaxed);
+        room_manager.remove_with_any(old_room_id, account_id, level_id);
+
+        let info = room_manager.join_transferred_room(account_id, &transferred);
+        self.room_id.store(info.id, Ordering::Relaxed);
+        self.emit_presence_change();
+
+        self.send_packet_static(&TransferClaimedPacket { info }).await
+    });
+
     gs_handler!(self, handle_connection_test, ConnectionTestPacket, packet, {
         self.send_packet_dynamic(&ConnectionTestResponsePacket {
             uid: packet.uid,
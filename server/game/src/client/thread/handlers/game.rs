@@ -1,66 +1,420 @@
-use std::sync::{atomic::Ordering, Arc};
+use std::{
+    sync::{atomic::Ordering, Arc},
+    time::Duration,
+};
+
+use globed_shared::{unix_timestamp, ChatFilterLevel, LinkAction, StateViolationAction};
 
 use super::*;
+use crate::{
+    cluster::ClusterMessage,
+    managers::FilterVerdict,
+    tokio::time::Instant,
+    util::{links, mentions},
+    webhook::{BanMuteStateChange, WebhookMessage},
+};
 
 /// max voice packet size in bytes
 pub const MAX_VOICE_PACKET_SIZE: usize = 4096;
+/// how long the author of a chat message can delete it for, moderators can delete messages at any time
+const AUTHOR_DELETE_WINDOW: Duration = Duration::from_secs(60);
+/// voice packets come in many times a second, so the player is only told this often that they can't talk in the room
+const VOICE_NOTICE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// What happens to player data after the player state checks.
+enum StateCheckOutcome {
+    /// the data is sent to others
+    Share,
+    /// the data is not sent to others, but the player still gets the data of everyone else
+    Withhold,
+    /// the player was kicked
+    Kicked,
+}
 
 impl ClientThread {
     gs_handler!(self, handle_level_join, LevelJoinPacket, packet, {
-        let account_id = gs_needauth!(self);
+        self.transition_level(packet.level_id, packet.unlisted).await
+    });
+
+    gs_handler!(self, handle_level_leave, LevelLeavePacket, _packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        self.leave_level(account_id).await
+    });
+
+    /// move the player to a new level, after checking that they are allowed to join it.
+    /// if they aren't, they are removed from their previous level and the client is told why.
+    async fn transition_level(&self, level_id: LevelId, unlisted: bool) -> Result<()> {
+        if level_id == 0 {
+            return Err(PacketHandlingError::InvalidLevelId);
+        }
+
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        // safety: only we can access the rate limiters of our user.
+        if !unsafe { self.level_switch_rate_limiter.get_mut() }.try_tick() {
+            self.leave_level(account_id).await?;
+            return self.reject_level_join(level_id, LevelJoinRejection::TooFast).await;
+        }
+
+        if self.game_server.state.level_blocklist.is_blocked(level_id) {
+            self.leave_level(account_id).await?;
+            return self.send_packet_static(&LevelBlockedPacket { level_id }).await;
+        }
 
-        let old_level = self.level_id.swap(packet.level_id, Ordering::Relaxed);
         let room_id = self.room_id.load(Ordering::Relaxed);
+        let preferences = self.user_entry.lock().preferences.clone();
 
-        self.game_server.state.room_manager.with_any(room_id, |pm| {
-            if old_level != 0 {
-                pm.manager.remove_from_level(old_level, account_id);
-            }
+        // the level ID is changed while holding the room lock, so that nobody sees the player on a level they are not on
+        let (joined, lost_spectators) = self.game_server.state.room_manager.with_any(room_id, |room| {
+            let new_level = if room.settings.allows_level(level_id) { level_id } else { 0 };
+            let hidden = new_level != 0 && room.settings.hides_level(new_level, unlisted, &preferences);
 
-            if packet.level_id != 0 {
-                pm.manager.add_to_level(packet.level_id, account_id);
-            }
+            // spectators watch the player on a level, so they stop once the player goes anywhere else
+            let previous_level = self.level_id.swap(new_level, Ordering::Relaxed);
+            let lost_spectators = previous_level != new_level && room.clear_spectators(account_id);
+
+            self.level_history.lock().record(new_level, unix_timestamp());
+            room.manager.move_to_level(new_level, account_id);
+            room.manager.set_level_hidden(account_id, hidden);
+
+            let joined = (new_level != 0).then(|| {
+                let channel = room.manager.assign_chat_channel(new_level, account_id);
+                (channel, room.manager.get_chat_channel_count(new_level))
+            });
+
+            (joined, lost_spectators)
         });
 
-        Ok(())
-    });
+        self.emit_presence_change();
 
-    gs_handler!(self, handle_level_leave, LevelLeavePacket, _packet, {
-        let account_id = gs_needauth!(self);
+        if lost_spectators {
+            self.update_spectator_count(0).await?;
+        }
 
-        let level_id = self.level_id.swap(0, Ordering::Relaxed);
-        if level_id != 0 {
-            let room_id = self.room_id.load(Ordering::Relaxed);
+        if let Some((_, channel_count)) = joined {
+            self.chat_channel_count.store(channel_count, Ordering::Relaxed);
+        }
 
-            self.game_server.state.room_manager.with_any(room_id, |pm| {
-                pm.manager.remove_from_level(level_id, account_id);
-            });
+        match joined {
+            // chat is split into channels, let the player know which one they ended up in
+            Some((channel, channel_count)) if channel_count != 0 => self.send_packet_static(&ChatChannelPacket { channel, channel_count }).await,
+            Some(_) => Ok(()),
+            None => self.reject_level_join(level_id, LevelJoinRejection::PinnedLevel).await,
+        }
+    }
+
+    async fn reject_level_join(&self, level_id: LevelId, reason: LevelJoinRejection) -> Result<()> {
+        debug!(
+            "[{}] rejecting join of level {level_id}: {reason:?}",
+            self.account_id.load(Ordering::Relaxed)
+        );
+
+        self.send_packet_static(&LevelJoinRejectedPacket { level_id, reason }).await
+    }
+
+    /// remove the player from the level they are currently on, if any. their spectators stop spectating them
+    pub(crate) async fn leave_level(&self, account_id: i32) -> Result<()> {
+        if self.level_id.load(Ordering::Relaxed) == 0 {
+            return Ok(());
+        }
+
+        let room_id = self.room_id.load(Ordering::Relaxed);
+
+        let lost_spectators = self.game_server.state.room_manager.with_any(room_id, |room| {
+            self.level_id.store(0, Ordering::Relaxed);
+            self.level_history.lock().record(0, unix_timestamp());
+            room.manager.move_to_level(0, account_id);
+            room.clear_spectators(account_id)
+        });
+
+        self.emit_presence_change();
+
+        if lost_spectators {
+            self.update_spectator_count(0).await?;
         }
 
         Ok(())
-    });
+    }
 
     gs_handler!(self, handle_player_data, PlayerDataPacket, packet, {
-        let account_id = gs_needauth!(self);
+        let account_id = self.account_id.load(Ordering::Relaxed);
 
         let level_id = self.level_id.load(Ordering::Relaxed);
         if level_id == 0 {
             return Err(PacketHandlingError::UnexpectedPlayerData);
         }
 
+        self.track_packet_loss(packet.sequence);
+        self.record_movement(packet.data.player1.position);
+
+        let share = match self.check_player_state(level_id, &packet.data).await? {
+            StateCheckOutcome::Share => true,
+            StateCheckOutcome::Withhold => false,
+            StateCheckOutcome::Kicked => return Ok(()),
+        };
+
+        // players that can't be heard anyway shouldn't show up as speaking either
+        let mut data = packet.data;
+        if data.is_speaking() && !self.can_use_voice() {
+            data.flags.clear_bit(PlayerData::SPEAKING_FLAG);
+        }
+
         let room_id = self.room_id.load(Ordering::Relaxed);
 
-        let written_players = self.game_server.state.room_manager.with_any(room_id, |pm| {
-            pm.manager.set_player_data(account_id, &packet.data);
+        let (written_players, channel_update) = self.game_server.state.room_manager.with_any(room_id, |pm| {
+            // withheld data isn't stored, so others keep seeing the last data that passed the checks
+            if share {
+                pm.manager.set_player_data(account_id, &data);
+            }
+
+            // the channel count follows the player count, so it changes as others join and leave the level
+            let channel_count = pm.manager.get_chat_channel_count(level_id);
+            let channel_update = (self.chat_channel_count.swap(channel_count, Ordering::Relaxed) != channel_count).then(|| {
+                let mut channel = pm.manager.get_chat_channel(level_id, account_id).unwrap_or(0);
+                // players in a channel that no longer exists are moved to one that does
+                if channel > channel_count {
+                    channel = pm.manager.assign_chat_channel(level_id, account_id);
+                }
+
+                (channel, channel_count)
+            });
+
             // this unwrap should be safe and > 0 given that self.level_id != 0, but we leave a default just in case
-            pm.manager.get_player_count_on_level(level_id).unwrap_or(1) - 1
+            (pm.manager.get_player_count_on_level(level_id).unwrap_or(1) - 1, channel_update)
         });
 
+        if let Some((channel, channel_count)) = channel_update {
+            self.send_packet_static(&ChatChannelPacket { channel, channel_count }).await?;
+        }
+
         // no one else on the level, no need to send a response packet
         if written_players == 0 {
             return Ok(());
         }
 
+        if share {
+            self.game_server.broadcast_level_snapshot(account_id, level_id, room_id).await;
+        }
+
+        self.send_level_data(account_id, level_id, room_id, written_players).await
+    });
+
+    /// Runs the plausibility checks from the player state policy on new player data.
+    async fn check_player_state(&self, level_id: LevelId, data: &PlayerData) -> Result<StateCheckOutcome> {
+        let policy = self.game_server.bridge.central_conf.lock().player_state_policy;
+        if !policy.is_enabled() {
+            return Ok(StateCheckOutcome::Share);
+        }
+
+        // safety: only we can use the checker.
+        let checker = unsafe { self.state_checker.get_mut() };
+
+        let Some(violation) = checker.check(level_id, data, &policy) else {
+            return Ok(StateCheckOutcome::Share);
+        };
+
+        let violations = checker.record_violation();
+        let max_violations = policy.max_violations.max(1);
+
+        if violations < max_violations {
+            return Ok(StateCheckOutcome::Share);
+        }
+
+        // moderators only hear about it once per level, no matter how often the checks fail after that
+        if violations == max_violations {
+            let account_id = self.account_id.load(Ordering::Relaxed);
+            let name = self.account_data.lock().name.try_to_string();
+
+            warn!(
+                "[{name} ({account_id}) @ {}] failed the player state checks on level {level_id}: {violation}",
+                self.get_tcp_peer()
+            );
+
+            if self.game_server.bridge.has_webhook() {
+                if let Err(err) = self
+                    .game_server
+                    .bridge
+                    .send_webhook_message(WebhookMessage::PlayerStateFlagged(name, account_id, level_id, violation.to_string()))
+                    .await
+                {
+                    warn!("webhook error: {err}");
+                }
+            }
+        }
+
+        match policy.action {
+            StateViolationAction::Flag => Ok(StateCheckOutcome::Share),
+            StateViolationAction::Suppress => Ok(StateCheckOutcome::Withhold),
+            StateViolationAction::Kick => {
+                self.kick(
+                    DisconnectReason::ProtocolError,
+                    "Your player data failed the sanity checks of the server.",
+                )
+                .await?;
+                Ok(StateCheckOutcome::Kicked)
+            }
+        }
+    }
+
+    gs_handler!(self, handle_spectate_player, SpectatePlayerPacket, packet, {
+        // safety: only we can access the rate limiters of our user.
+        if !unsafe { self.spectate_rate_limiter.get_mut() }.try_tick() {
+            return self
+                .send_packet_dynamic(&SpectateFailedPacket {
+                    code: ErrorCode::RateLimited,
+                    message: "",
+                })
+                .await;
+        }
+
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let room_id = self.room_id.load(Ordering::Relaxed);
+
+        // a player can only spectate one player at a time, so whoever we spectated before loses a spectator either way
+        let previous = self
+            .game_server
+            .state
+            .room_manager
+            .with_any(room_id, |room| room.remove_spectator(account_id));
+        if let Some(previous) = previous {
+            self.game_server.notify_spectators_changed(previous).await;
+        }
+
+        if packet.player == 0 {
+            return Ok(());
+        }
+
+        match self.start_spectating(account_id, room_id, packet.player) {
+            Ok(target) => {
+                debug!("[{account_id}] spectating {}", packet.player);
+
+                target.mark_spectators_changed().await;
+                self.send_packet_static(&SpectateStartedPacket { player: packet.player }).await
+            }
+            Err(code) => self.send_packet_dynamic(&SpectateFailedPacket { code, message: "" }).await,
+        }
+    });
+
+    /// add the player to the spectators of `target_id` if they are allowed to spectate them, returns the thread of the spectated player
+    fn start_spectating(&self, account_id: i32, room_id: u32, target_id: i32) -> std::result::Result<Arc<ClientThread>, ErrorCode> {
+        if target_id == account_id {
+            return Err(ErrorCode::InvalidRequest);
+        }
+
+        // players that can't be seen in the room can't be spectated either, unless by moderators
+        let can_moderate = self.user_role.lock().can_moderate();
+        let target = self
+            .game_server
+            .get_user_by_id(target_id)
+            .filter(|thread| {
+                thread.room_id.load(Ordering::Relaxed) == room_id
+                    && thread.level_id.load(Ordering::Relaxed) != 0
+                    && (can_moderate || !thread.is_invisible.load(Ordering::Relaxed))
+            })
+            .ok_or(ErrorCode::NotFound)?;
+
+        self.game_server.state.room_manager.with_any(room_id, |room| {
+            let level_hidden = room.manager.get_player_data(target_id).is_none_or(|player| player.level_hidden);
+
+            if level_hidden && !can_moderate {
+                Err(ErrorCode::NotFound)
+            } else if room.add_spectator(target_id, account_id) {
+                Ok(target)
+            } else {
+                Err(ErrorCode::Full)
+            }
+        })
+    }
+
+    gs_handler!(self, handle_spectator_data, SpectatorDataPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        let level_id = self.level_id.load(Ordering::Relaxed);
+        if level_id == 0 {
+            return Err(PacketHandlingError::UnexpectedPlayerData);
+        }
+
+        // this is the same data as in PlayerDataPacket, just sent more often
+        if !matches!(self.check_player_state(level_id, &packet.data).await?, StateCheckOutcome::Share) {
+            return Ok(());
+        }
+
+        let spkt = SpectatedPlayerDataPacket {
+            data: AssociatedPlayerData {
+                account_id,
+                data: packet.data,
+            },
+        };
+
+        let spectators = self
+            .game_server
+            .broadcast_spectated_data(spkt, self.room_id.load(Ordering::Relaxed))
+            .await;
+
+        // the client keeps sending these until it's told that everyone stopped spectating
+        self.update_spectator_count(spectators as u32).await
+    });
+
+    /// let the client know how many players are spectating it, if that changed since it was last told
+    pub(crate) async fn update_spectator_count(&self, count: u32) -> Result<()> {
+        if self.spectator_count.swap(count, Ordering::Relaxed) == count {
+            return Ok(());
+        }
+
+        self.send_packet_static(&SpectatorCountPacket { count }).await
+    }
+
+    /// update the packet loss estimate with the sequence number of a player data packet
+    fn track_packet_loss(&self, sequence: u32) {
+        // safety: only we can use the estimator.
+        let estimator = unsafe { self.loss_estimator.get_mut() };
+
+        let lossy = estimator.record(sequence);
+        self.packet_loss.store(estimator.estimate_percent(), Ordering::Relaxed);
+
+        let Some(lossy) = lossy else {
+            return;
+        };
+
+        self.is_lossy.store(lossy, Ordering::Relaxed);
+
+        let lossy_players = &self.game_server.state.lossy_player_count;
+        if lossy {
+            lossy_players.fetch_add(1, Ordering::Relaxed);
+            debug!(
+                "{} has ~{}% packet loss, sending extra level snapshots",
+                self.account_data.lock().name,
+                estimator.estimate_percent()
+            );
+        } else {
+            lossy_players.fetch_sub(1, Ordering::Relaxed);
+            debug!("{} no longer has high packet loss", self.account_data.lock().name);
+        }
+    }
+
+    /// send a snapshot of the level to a lossy client, outside of the usual responses to its own player data
+    pub(crate) async fn send_level_snapshot(&self) -> Result<()> {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let level_id = self.level_id.load(Ordering::Relaxed);
+        if account_id == 0 || level_id == 0 {
+            return Ok(());
+        }
+
+        let room_id = self.room_id.load(Ordering::Relaxed);
+        let written_players = self.game_server.state.room_manager.with_any(room_id, |pm| {
+            pm.manager.get_player_count_on_level(level_id).unwrap_or(1).saturating_sub(1)
+        });
+
+        if written_players == 0 {
+            return Ok(());
+        }
+
+        self.send_level_data(account_id, level_id, room_id, written_players).await
+    }
+
+    /// send the data of all the other players on the level, in fragments if it doesn't fit into one packet
+    async fn send_level_data(&self, account_id: i32, level_id: LevelId, room_id: u32, written_players: usize) -> Result<()> {
         let calc_size = size_of_types!(u32) + size_of_types!(AssociatedPlayerData) * written_players;
         let fragmentation_limit = self.fragmentation_limit.load(Ordering::Relaxed) as usize;
 
@@ -90,7 +444,7 @@ impl ClientThread {
         }
 
         // get all players into a vec
-        let total_fragments = (calc_size + fragmentation_limit - 1) / fragmentation_limit;
+        let total_fragments = calc_size.div_ceil(fragmentation_limit);
 
         let mut players = Vec::with_capacity(written_players + 4);
 
@@ -109,7 +463,7 @@ impl ClientThread {
             )
         });
 
-        let players_per_fragment = (players.len() + total_fragments - 1) / total_fragments;
+        let players_per_fragment = players.len().div_ceil(total_fragments);
         let calc_size = size_of_types!(u32) + size_of_types!(AssociatedPlayerData) * players_per_fragment;
 
         debug!(
@@ -122,10 +476,10 @@ impl ClientThread {
         }
 
         Ok(())
-    });
+    }
 
     gs_handler!(self, handle_player_metadata, PlayerMetadataPacket, packet, {
-        let account_id = gs_needauth!(self);
+        let account_id = self.account_id.load(Ordering::Relaxed);
 
         let level_id = self.level_id.load(Ordering::Relaxed);
         if level_id == 0 {
@@ -160,9 +514,64 @@ impl ClientThread {
         }
     });
 
-    gs_handler!(self, handle_request_profiles, RequestPlayerProfilesPacket, packet, {
-        let _ = gs_needauth!(self);
+    gs_handler!(self, handle_level_completed, LevelCompletedPacket, packet, {
+        // the player might have left the level right after completing it, so this isn't an error
+        let level_id = self.level_id.load(Ordering::Relaxed);
+        if level_id == 0 || packet.attempts == 0 {
+            return Ok(());
+        }
+
+        // safety: only we can access the rate limiters of our user.
+        if !unsafe { self.completion_rate_limiter.get_mut() }.try_tick() {
+            return Ok(());
+        }
+
+        // invisible moderators shouldn't give themselves away
+        if self.is_invisible.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let packet = LevelCompletedBroadcastPacket {
+            player_id: self.account_id.load(Ordering::Relaxed),
+            player_name: self.account_data.lock().name.clone(),
+            attempts: packet.attempts,
+        };
+
+        let room_id = self.room_id.load(Ordering::Relaxed);
+        self.game_server.broadcast_level_completion(packet, level_id, room_id).await;
+
+        Ok(())
+    });
+
+    gs_handler!(self, handle_level_event, LevelEventPacket, packet, {
+        // the player might have left the level while the event was on its way
+        let level_id = self.level_id.load(Ordering::Relaxed);
+        if level_id == 0 {
+            return Ok(());
+        }
+
+        // safety: only we can access the rate limiters of our user.
+        if !unsafe { self.level_event_rate_limiter.get_mut() }.try_tick() {
+            return Ok(());
+        }
+
+        if self.is_invisible.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let packet = LevelEventBroadcastPacket {
+            player_id: self.account_id.load(Ordering::Relaxed),
+            event_type: packet.event_type,
+            payload: packet.payload,
+        };
+
+        let room_id = self.room_id.load(Ordering::Relaxed);
+        self.game_server.broadcast_level_event(packet, level_id, room_id).await;
+
+        Ok(())
+    });
 
+    gs_handler!(self, handle_request_profiles, RequestPlayerProfilesPacket, packet, {
         let level_id = self.level_id.load(Ordering::Relaxed);
         if level_id == 0 {
             return Err(PacketHandlingError::UnexpectedPlayerData);
@@ -173,7 +582,11 @@ impl ClientThread {
         // if they requested just one player - use the fast heapless path
         if packet.requested != 0 {
             let calc_size = size_of_types!(VarLength) + size_of_types!(PlayerAccountData);
-            let account_data = self.game_server.get_player_account_data(packet.requested);
+            // the player might have just left, while their last player data is still on its way to us
+            let account_data = self
+                .game_server
+                .get_player_account_data(packet.requested)
+                .or_else(|| self.game_server.state.recent_players.get(packet.requested).map(|p| p.account_data));
 
             if let Some(account_data) = account_data {
                 return self
@@ -219,36 +632,450 @@ impl ClientThread {
     /* Note: blocking logic for voice & chat packets is not in here but in the packet receiving function */
 
     gs_handler!(self, handle_voice, VoicePacket, packet, {
-        let account_id = gs_needauth!(self);
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        self.record_activity();
+
+        if let Some((window, _)) = self.game_server.voice_capture_config() {
+            self.voice_capture.lock().push(&packet.data.data, window);
+        }
 
         let vpkt = Arc::new(VoiceBroadcastPacket {
             player_id: account_id,
             data: packet.data,
         });
 
-        self.game_server
+        let allowed = self
+            .game_server
             .broadcast_voice_packet(&vpkt, self.level_id.load(Ordering::Relaxed), self.room_id.load(Ordering::Relaxed))
             .await;
 
+        if !allowed {
+            {
+                let mut last_notice = self.last_voice_notice.lock();
+                if last_notice.is_some_and(|last| last.elapsed() < VOICE_NOTICE_INTERVAL) {
+                    return Ok(());
+                }
+
+                *last_notice = Some(Instant::now());
+            }
+
+            return self
+                .send_packet_dynamic(&ServerNoticePacket {
+                    message: FastString::new("Voice chat is restricted in this room, nobody can hear you."),
+                })
+                .await;
+        }
+
         Ok(())
     });
 
     gs_handler!(self, handle_chat_message, ChatMessagePacket, packet, {
-        let account_id = gs_needauth!(self);
+        let account_id = self.account_id.load(Ordering::Relaxed);
 
         if packet.message.is_empty() {
             return Ok(());
         }
 
+        self.record_activity();
+
+        let level_id = self.level_id.load(Ordering::Relaxed);
+        let room_id = self.room_id.load(Ordering::Relaxed);
+
+        // the server settings can change after the room was created, so check the filter bounds here again
+        let (filter_level, room_slow_mode, room_muted) = {
+            let policy = self.game_server.bridge.central_conf.lock().chat_filter_policy;
+
+            self.game_server.state.room_manager.with_any(room_id, |room| {
+                // room owners and moderators aren't affected by the slow mode or the mute of their own room
+                let manages_room = room_id != 0 && room.can_manage(account_id);
+
+                (
+                    policy.room_level(room.settings.chat_filter.level()),
+                    if manages_room { 0 } else { room.settings.slow_mode },
+                    !manages_room && room.settings.flags.chat_muted,
+                )
+            })
+        };
+
+        if room_muted && !self.user_role.lock().can_moderate() {
+            return self
+                .send_packet_dynamic(&ServerNoticePacket {
+                    message: FastString::new("The chat in this room is muted, only its owner and moderators can send messages."),
+                })
+                .await;
+        }
+
+        let level_slow_mode = if level_id == 0 {
+            0
+        } else {
+            self.game_server.state.chat.level_slow_mode(level_id)
+        };
+
+        let slow_mode = room_slow_mode.max(level_slow_mode);
+
+        if slow_mode != 0 && !self.user_role.lock().bypasses_slow_mode() {
+            let slow_mode = Duration::from_secs(u64::from(slow_mode));
+            let since_last = self.last_chat_message.lock().map(|last| last.elapsed());
+
+            if let Some(since_last) = since_last.filter(|x| *x < slow_mode) {
+                let wait = (slow_mode - since_last).as_secs() + 1;
+
+                return self
+                    .send_packet_dynamic(&ServerNoticePacket {
+                        message: FastString::new(&format!("Slow mode is enabled here, you can send another message in {wait}s.")),
+                    })
+                    .await;
+            }
+        }
+
+        let mut message = packet.message;
+
+        match self
+            .game_server
+            .state
+            .scripts
+            .on_chat(account_id, room_id, level_id, message.to_str().unwrap_or_default())
+        {
+            HookResult::Continue(Some(new_message)) => message = InlineString::new(&new_message),
+            HookResult::Continue(None) => {}
+            HookResult::Cancel(reason) => {
+                if let Some(reason) = reason {
+                    self.send_packet_dynamic(&ServerNoticePacket {
+                        message: FastString::new(&reason),
+                    })
+                    .await?;
+                }
+
+                return Ok(());
+            }
+        }
+
+        let link_action = self
+            .game_server
+            .bridge
+            .central_conf
+            .lock()
+            .chat_link_policy
+            .for_tier(*self.trust_tier.lock());
+
+        if link_action != LinkAction::Allow && links::contains_link(message.to_str().unwrap_or_default()) {
+            match link_action {
+                LinkAction::Allow => {}
+                LinkAction::Strip => {
+                    // the replacement can be longer than the link itself, so cut the message off if it doesn't fit anymore
+                    let stripped = links::strip_links(message.to_str().unwrap_or_default());
+                    message = InlineString::new("");
+                    message.extend_safe(&stripped);
+                }
+                LinkAction::Block => {
+                    self.send_packet_dynamic(&ServerNoticePacket {
+                        message: FastString::new("Your message was not sent, as links are not allowed in chat."),
+                    })
+                    .await?;
+
+                    return Ok(());
+                }
+                LinkAction::Flag => {
+                    if self.game_server.bridge.has_webhook() {
+                        let name = self.account_data.lock().name.try_to_string();
+
+                        if let Err(err) = self
+                            .game_server
+                            .bridge
+                            .send_webhook_message(WebhookMessage::ChatLinkFlagged(name, account_id, message.try_to_string()))
+                            .await
+                        {
+                            warn!("webhook error: {err}");
+                        }
+                    }
+                }
+            }
+        }
+
+        let filter = &self.game_server.state.filter;
+
+        match filter_level {
+            ChatFilterLevel::Off => {}
+            ChatFilterLevel::Standard => {
+                let text = message.to_str().unwrap_or_default();
+
+                if filter.is_bad(text) {
+                    let censored = filter.censor(text);
+                    message = InlineString::new("");
+                    message.extend_safe(&censored);
+                }
+            }
+            ChatFilterLevel::Strict => {
+                if filter.is_bad_strict(message.to_str().unwrap_or_default()) {
+                    return self
+                        .send_packet_dynamic(&ServerNoticePacket {
+                            message: FastString::new("Your message was not sent, as it contains words that are not allowed here."),
+                        })
+                        .await;
+                }
+            }
+        }
+
+        match self.game_server.state.chat_filter.check(message.to_str().unwrap_or_default()) {
+            FilterVerdict::Clean => {}
+            FilterVerdict::Censored(censored) => {
+                message = InlineString::new("");
+                message.extend_safe(&censored);
+            }
+            FilterVerdict::Drop => {
+                return self
+                    .send_packet_dynamic(&ServerNoticePacket {
+                        message: FastString::new("Your message was not sent, as it contains words that are not allowed on this server."),
+                    })
+                    .await;
+            }
+            FilterVerdict::Mute(duration) => {
+                return self.auto_mute(duration).await;
+            }
+        }
+
+        *self.last_chat_message.lock() = Some(Instant::now());
+
+        self.game_server.state.events.emit(|| ServerEvent::ChatMessage {
+            account_id,
+            room_id,
+            level_id,
+            message: message.try_to_string(),
+        });
+
+        let message_id = self.game_server.state.chat.record(account_id, room_id, level_id);
+
         let cpkt = ChatMessageBroadcastPacket {
             player_id: account_id,
-            message: packet.message,
+            message_id,
+            message,
         };
 
+        self.game_server.broadcast_chat_packet(&cpkt, level_id, room_id).await;
+
+        let mentions = mentions::find_mentions(cpkt.message.to_str().unwrap_or_default());
+        if !mentions.is_empty() {
+            let mpkt = ChatMentionPacket {
+                player_id: account_id,
+                message_id,
+            };
+
+            self.game_server.broadcast_chat_mentions(&mpkt, &mentions, level_id, room_id).await;
+        }
+
+        // let the author know the ID too, so they can delete the message later
+        self.send_packet_static(&ChatMessageSentPacket {
+            client_id: packet.client_id,
+            message_id,
+        })
+        .await
+    });
+
+    /// mutes the player after their message was caught by the chat word filter
+    async fn auto_mute(&self, duration: Duration) -> Result<()> {
+        const REASON: &str = "Automatic mute for using words that are not allowed in chat";
+
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let name = self.account_data.lock().name.try_to_string();
+        let expiry = unix_timestamp().saturating_add(i64::try_from(duration.as_secs()).unwrap_or(i64::MAX));
+
+        let result = self
+            .game_server
+            .update_user(self, |user| {
+                user.is_muted = true;
+                user.violation_reason = Some(REASON.to_owned());
+                user.violation_expiry = Some(expiry);
+                user.violation_count = user.violation_count.saturating_add(1);
+                true
+            })
+            .await;
+
+        // the mute is already applied for this session, even if it couldn't be saved
+        if let Err(err) = result {
+            warn!("failed to save the automatic mute of {name} ({account_id}): {err}");
+        }
+
+        info!("[{name} ({account_id}) @ {}] was muted by the chat word filter", self.get_tcp_peer());
+
+        // the player might be connected to another server in the cluster too
+        self.game_server.state.cluster.publish(ClusterMessage::Sanction {
+            account_id,
+            is_banned: self.user_entry.lock().is_banned,
+            is_muted: true,
+            reason: Some(REASON.to_owned()),
+            expiry: Some(expiry),
+        });
+
+        if self.game_server.bridge.has_webhook() {
+            let bmsc = BanMuteStateChange {
+                mod_name: "Chat filter".to_owned(),
+                target_name: name,
+                target_id: account_id,
+                new_state: true,
+                expiry: Some(expiry),
+                reason: Some(REASON.to_owned()),
+            };
+
+            if let Err(err) = self.game_server.bridge.send_webhook_message(WebhookMessage::UserMuteChanged(bmsc)).await {
+                warn!("webhook error: {err}");
+            }
+        }
+
+        self.send_packet_dynamic(&ServerMutedPacket {
+            reason: FastString::new(REASON),
+            timestamp: expiry,
+        })
+        .await
+    }
+
+    gs_handler!(self, handle_switch_chat_channel, SwitchChatChannelPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let level_id = self.level_id.load(Ordering::Relaxed);
+        let room_id = self.room_id.load(Ordering::Relaxed);
+
+        if level_id == 0 {
+            return Ok(());
+        }
+
+        // if the channel doesn't exist (anymore), the player is just told which channel they are still in
+        let (channel, channel_count) = self.game_server.state.room_manager.with_any(room_id, |room| {
+            room.manager.set_chat_channel(level_id, account_id, packet.channel);
+
+            (
+                room.manager.get_chat_channel(level_id, account_id).unwrap_or(0),
+                room.manager.get_chat_channel_count(level_id),
+            )
+        });
+
+        self.send_packet_static(&ChatChannelPacket { channel, channel_count }).await
+    });
+
+    gs_handler!(self, handle_delete_chat_message, DeleteChatMessagePacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        let chat = &self.game_server.state.chat;
+        let Some(sent) = chat.get(packet.message_id) else {
+            return Ok(());
+        };
+
+        let is_author = sent.author == account_id && sent.sent_at.elapsed() < AUTHOR_DELETE_WINDOW;
+        let is_moderator = self.is_authorized_admin.load(Ordering::Relaxed) && self.user_role.lock().can_moderate();
+
+        if !is_author && !is_moderator {
+            return Ok(());
+        }
+
+        if !chat.remove(sent.id) {
+            return Ok(());
+        }
+
+        if sent.author != account_id {
+            info!(
+                "[{} ({account_id})] deleted a chat message from {}",
+                self.account_data.lock().name,
+                sent.author
+            );
+        }
+
         self.game_server
-            .broadcast_chat_packet(&cpkt, self.level_id.load(Ordering::Relaxed), self.room_id.load(Ordering::Relaxed))
+            .broadcast_chat_deletion(&ChatMessageDeletedPacket { message_id: sent.id }, sent.level_id, sent.room_id)
             .await;
 
         Ok(())
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use esp::{ByteBuffer, ByteBufferExtWrite, ByteReader};
+
+    use super::*;
+    use crate::{
+        server::GameServer,
+        testing::server::{test_client, test_server},
+    };
+
+    const LEVEL_ID: LevelId = 1234;
+
+    /// registers a logged in player on `LEVEL_ID` in the global room
+    async fn join_player(server: &'static GameServer, account_id: i32) -> Arc<ClientThread> {
+        let thread = test_client(server, account_id).await;
+        thread.level_id.store(LEVEL_ID, Ordering::Relaxed);
+
+        server.state.room_manager.with_any(0, |room| {
+            room.manager.create_player(account_id);
+            room.manager.add_to_level(LEVEL_ID, account_id);
+        });
+
+        thread
+    }
+
+    async fn send_event(thread: &ClientThread, event_type: u32, payload: &[u8]) {
+        let mut buf = ByteBuffer::new();
+        buf.write_u32(event_type);
+        buf.write_value(&FastVec::<u8, MAX_LEVEL_EVENT_SIZE>::try_from(payload.to_vec()).unwrap());
+
+        let data = buf.into_vec();
+        assert!(thread.handle_level_event(&mut ByteReader::from_bytes(&data)).await.is_ok());
+    }
+
+    async fn received_events(thread: &ClientThread) -> Vec<LevelEventBroadcastPacket> {
+        let mut mq = thread.message_queue.lock().await;
+        std::iter::from_fn(|| mq.pop())
+            .filter_map(|msg| match msg {
+                ServerThreadMessage::LevelEvent(packet) => Some(packet),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn relays_events_to_others_on_the_level() {
+        let server = test_server().await;
+        let sender = join_player(server, 1).await;
+        let receiver = join_player(server, 2).await;
+
+        send_event(&sender, 7, &[1, 2, 3]).await;
+
+        let events = received_events(&receiver).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].player_id, 1);
+        assert_eq!(events[0].event_type, 7);
+        assert_eq!(&*events[0].payload, &[1, 2, 3]);
+
+        // the sender doesn't get their own event back
+        assert!(received_events(&sender).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn drops_events_from_invisible_and_flooding_players() {
+        let server = test_server().await;
+        let sender = join_player(server, 1).await;
+        let receiver = join_player(server, 2).await;
+
+        sender.is_invisible.store(true, Ordering::Relaxed);
+        send_event(&sender, 1, &[]).await;
+        assert!(received_events(&receiver).await.is_empty());
+
+        sender.is_invisible.store(false, Ordering::Relaxed);
+        for _ in 0..10 {
+            send_event(&sender, 1, &[]).await;
+        }
+
+        // 5 events per 5 seconds, the invisible one counted too
+        assert_eq!(received_events(&receiver).await.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn drops_events_outside_of_levels() {
+        let server = test_server().await;
+        let sender = join_player(server, 1).await;
+        let receiver = join_player(server, 2).await;
+
+        sender.level_id.store(0, Ordering::Relaxed);
+        send_event(&sender, 1, &[]).await;
+
+        assert!(received_events(&receiver).await.is_empty());
+    }
+}
@@ -1,16 +1,41 @@
+use std::time::Duration;
+
+use globed_shared::{unix_timestamp, PlayerReport, ReportState};
+
 use super::*;
+use crate::{cluster::ClusterMessage, managers::is_bot_account, tokio::time::Instant, webhook::WebhookMessage};
+
+/// how often a player can report someone
+const REPORT_INTERVAL: Duration = Duration::from_secs(60);
+/// the session list comes from the central server, so players can't ask for it more often than this
+const SESSION_LIST_INTERVAL: Duration = Duration::from_secs(5);
 
 impl ClientThread {
     gs_handler!(self, handle_sync_icons, SyncIconsPacket, packet, {
-        let _ = gs_needauth!(self);
+        let mut icons = packet.icons;
+        icons.strip_unapproved_cosmetic(&self.game_server.bridge.central_conf.lock().approved_cosmetics);
+
+        let changed = {
+            let mut account_data = self.account_data.lock();
+            let changed = account_data.icons != icons;
+            account_data.icons = icons.clone();
+            changed
+        };
+
+        if changed {
+            let account_id = self.account_id.load(Ordering::Relaxed);
+            let level_id = self.level_id.load(Ordering::Relaxed);
+            let room_id = self.room_id.load(Ordering::Relaxed);
+
+            self.game_server
+                .broadcast_profile_change(PlayerProfileChangedPacket { account_id, icons }, level_id, room_id)
+                .await;
+        }
 
-        self.account_data.lock().icons.clone_from(&packet.icons);
         Ok(())
     });
 
     gs_handler!(self, handle_request_global_list, RequestGlobalPlayerListPacket, _packet, {
-        let _ = gs_needauth!(self);
-
         self.send_packet_dynamic(&GlobalPlayerListPacket {
             players: self.game_server.get_player_previews_in_room(0),
         })
@@ -18,19 +43,19 @@ impl ClientThread {
     });
 
     gs_handler!(self, handle_request_level_list, RequestLevelListPacket, _packet, {
-        let _ = gs_needauth!(self);
-
         let room_id = self.room_id.load(Ordering::Relaxed);
 
         let levels = self.game_server.state.room_manager.with_any(room_id, |pm| {
             let mut vec = Vec::with_capacity(pm.manager.get_level_count());
 
             pm.manager.for_each_level(
-                |(level_id, players), _count, vec| {
-                    if !is_editorcollab_level(level_id) {
+                |(level_id, _players), _count, vec| {
+                    // levels where everyone hides their presence are left out
+                    let player_count = pm.manager.get_visible_player_count_on_level(level_id);
+                    if player_count != 0 {
                         vec.push(GlobedLevel {
                             level_id,
-                            player_count: players.len() as u16,
+                            player_count: player_count as u16,
                         });
                     }
 
@@ -46,15 +71,13 @@ impl ClientThread {
     });
 
     gs_handler!(self, handle_request_player_count, RequestPlayerCountPacket, packet, {
-        let _ = gs_needauth!(self);
-
         let room_id = self.room_id.load(Ordering::Relaxed);
 
         let levels = self.game_server.state.room_manager.with_any(room_id, |pm| {
             let mut levels = Vec::with_capacity(0);
 
             for &level_id in &*packet.level_ids {
-                levels.push((level_id, pm.manager.get_player_count_on_level(level_id).unwrap_or(0) as u16));
+                levels.push((level_id, pm.manager.get_visible_player_count_on_level(level_id) as u16));
             }
 
             levels
@@ -64,10 +87,250 @@ impl ClientThread {
     });
 
     gs_handler!(self, handle_set_player_status, UpdatePlayerStatusPacket, packet, {
-        let _ = gs_needauth!(self);
+        if self.is_invisible.swap(packet.is_invisible, Ordering::Relaxed) != packet.is_invisible {
+            self.emit_presence_change();
+        }
+
+        Ok(())
+    });
+
+    gs_handler!(self, handle_update_preferences, UpdatePreferencesPacket, packet, {
+        let mut preferences = packet.preferences;
+        preferences.sanitize();
+
+        if self.is_invisible.swap(preferences.hide_in_lists, Ordering::Relaxed) != preferences.hide_in_lists {
+            self.emit_presence_change();
+        }
+
+        // without a database the preferences only last until the player disconnects
+        let can_save = self.game_server.has_user_data();
+        self.game_server
+            .update_user(self, |user| {
+                let changed = user.preferences != preferences;
+                user.preferences = preferences;
+                changed && can_save
+            })
+            .await?;
 
-        self.is_invisible.store(packet.is_invisible, Ordering::Relaxed);
+        Ok(())
+    });
+
+    gs_handler!(self, handle_feature_usage, FeatureUsagePacket, packet, {
+        // only count one report per session, so that a single client can't skew the numbers
+        if !self.reported_usage.swap(true, Ordering::Relaxed) {
+            self.game_server.state.usage_stats.record(&packet);
+        }
 
         Ok(())
     });
+
+    gs_handler!(self, handle_report_player, ReportPlayerPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let reason = packet.reason.to_str().unwrap_or_default().trim();
+
+        if packet.account_id == account_id || is_bot_account(packet.account_id) || reason.is_empty() {
+            return self.send_packet_static(&PlayerReportedPacket { success: false }).await;
+        }
+
+        let too_soon = {
+            let mut last_report = self.last_report.lock();
+            let too_soon = last_report.is_some_and(|last| last.elapsed() < REPORT_INTERVAL);
+            if !too_soon {
+                *last_report = Some(Instant::now());
+            }
+
+            too_soon
+        };
+
+        if too_soon {
+            return self.send_packet_static(&PlayerReportedPacket { success: false }).await;
+        }
+
+        let report = PlayerReport {
+            id: 0,
+            reporter_id: account_id,
+            reported_id: packet.account_id,
+            reason: reason.to_owned(),
+            state: ReportState::Open,
+            handled_by: 0,
+            created_at: unix_timestamp(),
+        };
+
+        let report_id = match self.game_server.create_report(&report).await {
+            Ok(id) => id,
+            Err(err) => {
+                warn!("failed to save a report: {err}");
+                return self.send_packet_static(&PlayerReportedPacket { success: false }).await;
+            }
+        };
+
+        let own_name = self.account_data.lock().name.try_to_string();
+        let reported_name = self.game_server.get_known_player_name(packet.account_id).try_to_string();
+
+        info!(
+            "[{own_name} ({account_id})] reported {reported_name} ({}), report #{report_id}",
+            packet.account_id
+        );
+
+        let voice_capture = self.attach_voice_capture(report_id, packet.account_id).await;
+
+        if self.game_server.bridge.has_webhook() {
+            if let Err(err) = self
+                .game_server
+                .bridge
+                .send_webhook_message(WebhookMessage::ReportCreated(
+                    report_id,
+                    own_name,
+                    account_id,
+                    reported_name,
+                    packet.account_id,
+                    report.reason,
+                    voice_capture,
+                ))
+                .await
+            {
+                warn!("webhook error: {err}");
+            }
+        }
+
+        self.send_packet_static(&PlayerReportedPacket { success: true }).await
+    });
+
+    /// Seals the recent voice data of the reported player and attaches it to the report.
+    /// Returns the length of the capture, or `None` if nothing was attached.
+    async fn attach_voice_capture(&self, report_id: i64, reported_id: i32) -> Option<Duration> {
+        let (window, key) = self.game_server.voice_capture_config()?;
+        let thread = self.game_server.get_user_by_id(reported_id)?;
+
+        let (sealed, duration) = {
+            let capture = thread.voice_capture.lock();
+            (capture.seal(window, &key)?, capture.duration().min(window))
+        };
+
+        match self.game_server.set_report_voice_capture(report_id, sealed).await {
+            Ok(()) => Some(duration),
+            Err(err) => {
+                warn!("failed to attach a voice capture to report #{report_id}: {err}");
+                None
+            }
+        }
+    }
+
+    gs_handler!(self, handle_subscribe_presence, SubscribePresencePacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        // only friends can be watched, otherwise anyone could follow any account around
+        let friends = self.game_server.state.friends.friends_of(account_id);
+        let accounts = packet
+            .accounts
+            .iter()
+            .copied()
+            .filter(|id| friends.binary_search(id).is_ok())
+            .collect::<Vec<_>>();
+
+        let presence = &self.game_server.state.presence;
+        presence.subscribe(account_id, &accounts);
+
+        let accounts = presence.subscriptions_of(account_id);
+        if accounts.is_empty() {
+            return Ok(());
+        }
+
+        // the current state of everyone, from then on only changes are sent
+        self.send_presence(&accounts).await
+    });
+
+    gs_handler!(self, handle_update_friend_list, UpdateFriendListPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        let friends = &self.game_server.state.friends;
+        friends.set_friends(account_id, &packet.friends);
+
+        // stop watching anyone that is no longer a friend
+        let friends = friends.friends_of(account_id);
+        let presence = &self.game_server.state.presence;
+        let mut subscriptions = presence.subscriptions_of(account_id);
+        let count = subscriptions.len();
+        subscriptions.retain(|id| friends.binary_search(id).is_ok());
+
+        if subscriptions.len() != count {
+            presence.subscribe(account_id, &subscriptions);
+        }
+
+        // new mutual friends can now see which level we are on
+        self.emit_presence_change();
+
+        Ok(())
+    });
+
+    gs_handler!(self, handle_friend_list_query, FriendListQueryPacket, _packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let room_id = self.room_id.load(Ordering::Relaxed);
+
+        let friends = self.game_server.state.friends.friends_of(account_id);
+
+        let mut players = self.game_server.get_presences(&friends, account_id, room_id);
+        players.retain(|player| player.online);
+
+        self.send_packet_dynamic(&FriendListPacket { players }).await
+    });
+
+    gs_handler!(self, handle_request_session_list, RequestSessionListPacket, _packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        {
+            let mut last_request = self.last_session_list.lock();
+            if last_request.is_some_and(|last| last.elapsed() < SESSION_LIST_INTERVAL) {
+                return Ok(());
+            }
+
+            *last_request = Some(Instant::now());
+        }
+
+        let sessions = self.game_server.get_sessions(account_id).await;
+
+        self.send_packet_dynamic(&SessionListPacket {
+            current: self.session_id,
+            sessions,
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_logout_session, LogoutSessionPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        // the client should just disconnect if it wants to log out of this session
+        if packet.session_id == self.session_id {
+            return Ok(());
+        }
+
+        info!("[{account_id}] logging out another session ({:x})", packet.session_id);
+
+        if !self.game_server.logout_session(account_id, packet.session_id).await {
+            // not on this server, so it's either on another one in the cluster or already gone
+            self.game_server.state.cluster.publish(ClusterMessage::SessionLogout {
+                account_id,
+                session_id: packet.session_id,
+            });
+        }
+
+        Ok(())
+    });
+
+    pub(crate) async fn send_presence(&self, accounts: &[i32]) -> Result<()> {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let room_id = self.room_id.load(Ordering::Relaxed);
+        let players = self.game_server.get_presences(accounts, account_id, room_id);
+
+        self.send_packet_dynamic(&PresenceUpdatePacket { players }).await
+    }
+
+    /// Lets the players watching us know that we moved to a different room or level, or changed our visibility.
+    pub(crate) fn emit_presence_change(&self) {
+        self.game_server.state.events.emit(|| ServerEvent::PresenceChanged {
+            account_id: self.account_id.load(Ordering::Relaxed),
+            room_id: self.room_id.load(Ordering::Relaxed),
+            level_id: self.level_id.load(Ordering::Relaxed),
+        });
+    }
 }
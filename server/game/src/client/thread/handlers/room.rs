@@ -1,37 +1,107 @@
+use std::time::{Duration, Instant};
+
+use globed_shared::unix_timestamp;
+
 use super::*;
+use crate::{managers::RoomQuotaExceeded, util::AfkEvent};
+
+/// the longest countdown a room owner can start
+const MAX_COUNTDOWN_SECONDS: u8 = 10;
+
+/// room webhooks can only point to Discord, so that players can't make the server send requests anywhere else
+const ALLOWED_WEBHOOK_PREFIXES: &[&str] = &["https://discord.com/api/webhooks/", "https://discordapp.com/api/webhooks/"];
+const MAX_WEBHOOK_URL_LENGTH: usize = 256;
 
 impl ClientThread {
     gs_handler!(self, handle_create_room, CreateRoomPacket, packet, {
-        let account_id = gs_needauth!(self);
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        self.record_activity();
 
         let room_id: u32 = self.room_id.load(Ordering::Relaxed);
 
         // if we are already in a room, just return the same room info, otherwise create a new one
         let room_info = if room_id == 0 {
+            if !self.has_trust(self.game_server.bridge.central_conf.lock().min_trust_create_room) {
+                return self
+                    .send_packet_dynamic(&RoomCreateFailedPacket {
+                        code: ErrorCode::Restricted,
+                        message: "Your account is not allowed to create rooms on this server yet",
+                    })
+                    .await;
+            }
+
+            let mut room_name = packet.room_name;
+
+            match self
+                .game_server
+                .state
+                .scripts
+                .on_room_create(account_id, room_name.to_str().unwrap_or_default())
+            {
+                HookResult::Continue(Some(new_name)) => room_name = InlineString::new(&new_name),
+                HookResult::Continue(None) => {}
+                HookResult::Cancel(reason) => {
+                    return self
+                        .send_packet_dynamic(&RoomCreateFailedPacket {
+                            code: ErrorCode::Rejected,
+                            message: reason.as_deref().unwrap_or("Room creation was rejected by the server"),
+                        })
+                        .await;
+                }
+            }
+
             // check if data is valid
 
-            let fail_reason: Option<&'static str> = match packet.room_name.to_str() {
+            let failure: Option<(ErrorCode, &'static str)> = match room_name.to_str() {
                 Ok(str) => {
                     if self.game_server.state.filter.is_bad(str) {
-                        Some("Please choose a different room name")
+                        Some((ErrorCode::Rejected, "Please choose a different room name"))
                     } else {
                         None
                     }
                 }
-                Err(_) => Some("invalid room name"),
+                Err(_) => Some((ErrorCode::InvalidRequest, "invalid room name")),
             };
 
-            if let Some(reason) = fail_reason {
-                return self.send_packet_dynamic(&RoomCreateFailedPacket { reason }).await;
+            if let Some((code, message)) = failure {
+                return self.send_packet_dynamic(&RoomCreateFailedPacket { code, message }).await;
             }
 
-            let room_info = self
+            let mut settings = packet.settings;
+            settings.clamp_chat(&self.game_server.bridge.central_conf.lock().chat_filter_policy);
+
+            let quota = self.room_quota();
+            let room_info = match self
                 .game_server
                 .state
                 .room_manager
-                .create_room(account_id, packet.room_name, packet.password, packet.settings);
+                .create_room(account_id, room_name, packet.password, settings, &quota)
+            {
+                Ok(x) => x,
+                Err(err) => {
+                    let code = match err {
+                        RoomQuotaExceeded::OwnedRooms(_) => ErrorCode::Restricted,
+                        RoomQuotaExceeded::Creations(_) | RoomQuotaExceeded::Invites(_) => ErrorCode::RateLimited,
+                    };
+
+                    return self
+                        .send_packet_dynamic(&RoomCreateFailedPacket {
+                            code,
+                            message: &err.to_string(),
+                        })
+                        .await;
+                }
+            };
+
+            self.game_server.state.events.emit(|| ServerEvent::RoomCreated {
+                room_id: room_info.id,
+                owner: account_id,
+                name: room_info.name.try_to_string(),
+            });
 
             self.room_id.store(room_info.id, Ordering::Relaxed);
+            self.emit_presence_change();
             room_info
         } else {
             let room_info = self.game_server.state.room_manager.get_room_info(room_id);
@@ -50,39 +120,52 @@ impl ClientThread {
     });
 
     gs_handler!(self, handle_join_room, JoinRoomPacket, packet, {
-        let account_id = gs_needauth!(self);
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        self.record_activity();
+
+        let peer_ip = self.get_tcp_peer().ip();
+
+        // if someone is guessing room ids, pretend that no room exists
+        if self.game_server.state.room_manager.is_join_limited(peer_ip) {
+            return self.send_room_join_failed(ErrorCode::NotFound, "").await;
+        }
 
         if !self.game_server.state.room_manager.is_valid_room(packet.room_id) {
-            return self
-                .send_packet_static(&RoomJoinFailedPacket {
-                    was_invalid: true,
-                    ..Default::default()
-                })
-                .await;
+            self.game_server.state.room_manager.record_invalid_join(peer_ip);
+
+            return self.send_room_join_failed(ErrorCode::NotFound, "").await;
         }
 
+        let (role_mask, can_moderate) = {
+            let role = self.user_role.lock();
+            (role.role_mask, role.can_moderate())
+        };
+
         // check if we are even able to join the room
-        let (was_invalid, was_protected, was_full) = self.game_server.state.room_manager.try_with_any(
+        let failure = self.game_server.state.room_manager.try_with_any(
             packet.room_id,
             |room| {
                 if !room.verify_password(&packet.password) {
-                    (false, true, false)
+                    Some(ErrorCode::WrongPassword)
                 } else if room.is_full() {
-                    (false, false, true)
+                    Some(ErrorCode::Full)
+                } else if !room.settings.allows_roles(role_mask) && room.owner != account_id && !can_moderate {
+                    Some(ErrorCode::Restricted)
                 } else {
-                    (false, false, false)
+                    None
                 }
             },
-            || (true, false, false),
+            || Some(ErrorCode::NotFound),
         );
 
-        if was_invalid || was_protected || was_full {
+        if let Some(code) = failure {
+            return self.send_room_join_failed(code, "").await;
+        }
+
+        if let HookResult::Cancel(reason) = self.game_server.state.scripts.on_room_join(account_id, packet.room_id) {
             return self
-                .send_packet_static(&RoomJoinFailedPacket {
-                    was_invalid,
-                    was_protected,
-                    was_full,
-                })
+                .send_room_join_failed(ErrorCode::Rejected, reason.as_deref().unwrap_or_default())
                 .await;
         }
 
@@ -102,11 +185,16 @@ impl ClientThread {
             pm.manager.create_player(account_id);
         });
 
+        self.game_server.state.room_manager.accept_invites(account_id, packet.room_id);
+        self.emit_presence_change();
+
         self.send_packet_static(&RoomJoinedPacket).await
     });
 
     gs_handler!(self, handle_leave_room, LeaveRoomPacket, _packet, {
-        let account_id = gs_needauth!(self);
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        self.record_activity();
 
         let room_id = self.room_id.swap(0, Ordering::Relaxed);
         if room_id == 0 {
@@ -124,20 +212,19 @@ impl ClientThread {
 
         // add them to the global room
         self.game_server.state.room_manager.get_global().manager.create_player(account_id);
+        self.emit_presence_change();
 
         // respond with the global room list
         self._respond_with_room_list(0).await
     });
 
     gs_handler!(self, handle_request_room_players, RequestRoomPlayerListPacket, _packet, {
-        let _ = gs_needauth!(self);
-
         let room_id = self.room_id.load(Ordering::Relaxed);
         self._respond_with_room_list(room_id).await
     });
 
     gs_handler!(self, handle_update_room_settings, UpdateRoomSettingsPacket, packet, {
-        let account_id = gs_needauth!(self);
+        let account_id = self.account_id.load(Ordering::Relaxed);
 
         let room_id = self.room_id.load(Ordering::Relaxed);
 
@@ -145,11 +232,14 @@ impl ClientThread {
             return Ok(());
         }
 
+        let mut settings = packet.settings;
+        settings.clamp_chat(&self.game_server.bridge.central_conf.lock().chat_filter_policy);
+
         let mut success = false;
 
         self.game_server.state.room_manager.with_any(room_id, |room| {
-            if room.owner == account_id {
-                room.set_settings(&packet.settings);
+            if room.can_manage(account_id) {
+                room.set_settings(&settings);
                 success = true;
             }
         });
@@ -163,7 +253,7 @@ impl ClientThread {
     });
 
     gs_handler!(self, handle_room_invitation, RoomSendInvitePacket, packet, {
-        let account_id = gs_needauth!(self);
+        let account_id = self.account_id.load(Ordering::Relaxed);
 
         let room_id = self.room_id.load(Ordering::Relaxed);
 
@@ -172,6 +262,15 @@ impl ClientThread {
             return Ok(());
         }
 
+        if !self.has_trust(self.game_server.bridge.central_conf.lock().min_trust_invite) {
+            return self
+                .send_packet_dynamic(&RoomInviteFailedPacket {
+                    code: ErrorCode::Restricted,
+                    message: "Your account is not allowed to send invites on this server yet.",
+                })
+                .await;
+        }
+
         // if we don't have permission to invite, skip
         let room_password = self.game_server.state.room_manager.with_any(room_id, |room| {
             if room.is_protected() && (room.is_public_invites() || room.owner == account_id) {
@@ -186,12 +285,38 @@ impl ClientThread {
         let Some(room_password) = room_password else {
             #[cfg(debug_assertions)]
             debug!("invite from {account_id} rejected, user is unable to invite");
-            return Ok(());
+            return self
+                .send_packet_dynamic(&RoomInviteFailedPacket {
+                    code: ErrorCode::NoPermission,
+                    message: "Only the room owner can invite players to this room.",
+                })
+                .await;
         };
 
         let thread = self.game_server.get_user_by_id(packet.player);
 
         if let Some(thread) = thread {
+            if thread.user_entry.lock().preferences.block_invites {
+                #[cfg(debug_assertions)]
+                debug!("invite from {account_id} rejected, {} blocks invites", packet.player);
+                return Ok(());
+            }
+
+            let quota = self.room_quota();
+            if let Err(err) = self
+                .game_server
+                .state
+                .room_manager
+                .record_invite(account_id, packet.player, room_id, &quota)
+            {
+                return self
+                    .send_packet_dynamic(&RoomInviteFailedPacket {
+                        code: ErrorCode::RateLimited,
+                        message: &err.to_string(),
+                    })
+                    .await;
+            }
+
             let player_data = self.account_data.lock().make_preview();
 
             debug!(
@@ -212,8 +337,6 @@ impl ClientThread {
     });
 
     gs_handler!(self, handle_request_room_list, RequestRoomListPacket, _packet, {
-        let _ = gs_needauth!(self);
-
         let pkt = RoomListPacket {
             rooms: self
                 .game_server
@@ -229,19 +352,363 @@ impl ClientThread {
         self.send_packet_dynamic(&pkt).await
     });
 
+    gs_handler!(self, handle_start_room_countdown, StartRoomCountdownPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let room_id = self.room_id.load(Ordering::Relaxed);
+        let level_id = self.level_id.load(Ordering::Relaxed);
+
+        // there is no owner of the global room, and the countdown is only shown on levels
+        if room_id == 0 || level_id == 0 {
+            return Ok(());
+        }
+
+        let is_owner = self.game_server.state.room_manager.with_any(room_id, |room| room.owner == account_id);
+        if !is_owner {
+            return Ok(());
+        }
+
+        // safety: only we can use this ratelimiter.
+        if !unsafe { self.countdown_rate_limiter.get_mut() }.try_tick() {
+            return Ok(());
+        }
+
+        let seconds = packet.seconds.clamp(1, MAX_COUNTDOWN_SECONDS);
+        let ends_at = Instant::now() + Duration::from_secs(u64::from(seconds));
+
+        debug!("{account_id} started a {seconds}s countdown (room: {room_id}, level: {level_id})");
+
+        self.game_server.state.events.emit(|| ServerEvent::RoomCountdownStarted {
+            account_id,
+            room_id,
+            level_id,
+            seconds,
+        });
+
+        self.game_server.broadcast_room_countdown(level_id, room_id, ends_at).await;
+
+        Ok(())
+    });
+
+    gs_handler!(self, handle_set_room_webhook, SetRoomWebhookPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let room_id = self.room_id.load(Ordering::Relaxed);
+
+        if room_id == 0 || !self.game_server.state.room_manager.with_any(room_id, |room| room.owner == account_id) {
+            return self
+                .send_room_webhook_failed(ErrorCode::NoPermission, "Only the room owner can do this")
+                .await;
+        }
+
+        if !self.user_role.lock().room_webhooks {
+            return self
+                .send_room_webhook_failed(ErrorCode::Restricted, "You don't have a role that allows setting up room webhooks")
+                .await;
+        }
+
+        let url = packet.url.try_to_string();
+
+        if url.is_empty() {
+            self.game_server.state.room_manager.set_webhook(room_id, None);
+            info!("[{account_id}] removed the webhook of room {room_id}");
+
+            return self.send_packet_static(&RoomWebhookSetPacket).await;
+        }
+
+        let valid = url.len() <= MAX_WEBHOOK_URL_LENGTH
+            && ALLOWED_WEBHOOK_PREFIXES.iter().any(|prefix| url.starts_with(prefix))
+            && !url.contains(|c: char| c.is_whitespace() || c.is_control());
+
+        if !valid {
+            return self
+                .send_room_webhook_failed(ErrorCode::InvalidRequest, "Only Discord webhook URLs are supported")
+                .await;
+        }
+
+        // safety: only we can use this ratelimiter.
+        if !unsafe { self.webhook_rate_limiter.get_mut() }.try_tick() {
+            return self.send_room_webhook_failed(ErrorCode::RateLimited, "Try again in a minute").await;
+        }
+
+        if let Err(err) = self.game_server.bridge.verify_webhook(&url).await {
+            debug!("[{account_id}] tried to set an invalid room webhook: {err}");
+            return self.send_room_webhook_failed(ErrorCode::NotFound, "This webhook does not exist").await;
+        }
+
+        // the room might have been deleted while we were checking the webhook
+        if !self.game_server.state.room_manager.set_webhook(room_id, Some(url)) {
+            return self.send_room_webhook_failed(ErrorCode::NotFound, "").await;
+        }
+
+        info!("[{account_id}] set a webhook for room {room_id}");
+
+        self.send_packet_static(&RoomWebhookSetPacket).await
+    });
+
+    gs_handler!(self, handle_set_room_moderator, SetRoomModeratorPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let room_id = self.room_id.load(Ordering::Relaxed);
+
+        if room_id == 0 {
+            return Ok(());
+        }
+
+        // only the owner can pick moderators, and only among the players in the room
+        let changed = self.game_server.state.room_manager.with_any(room_id, |room| {
+            room.owner == account_id
+                && (!packet.moderator || room.manager.players.contains_key(&packet.player))
+                && room.set_moderator(packet.player, packet.moderator)
+        });
+
+        if changed {
+            let action = if packet.moderator { "added" } else { "removed" };
+            info!("[{account_id}] {action} {} as a moderator of room {room_id}", packet.player);
+
+            self.game_server.broadcast_room_info(room_id).await;
+        }
+
+        Ok(())
+    });
+
+    gs_handler!(self, handle_set_room_tags, SetRoomTagsPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let room_id = self.room_id.load(Ordering::Relaxed);
+
+        if room_id == 0 {
+            return Ok(());
+        }
+
+        // tags are shown to everyone in the room, so they go through the same checks as room names
+        let filter = &self.game_server.state.filter;
+        let valid = packet
+            .tags
+            .iter()
+            .all(|tag| tag.name.to_str().is_ok_and(|name| !name.trim().is_empty() && !filter.is_bad(name)));
+
+        if !valid {
+            return self
+                .send_packet_dynamic(&ServerNoticePacket {
+                    message: FastString::new("Please choose different names for the room tags."),
+                })
+                .await;
+        }
+
+        let changed = self.game_server.state.room_manager.with_any(room_id, |room| {
+            if room.owner != account_id {
+                return false;
+            }
+
+            room.set_tags(packet.tags.to_vec());
+            true
+        });
+
+        if changed {
+            info!("[{account_id}] set {} tags in room {room_id}", packet.tags.len());
+            self.game_server.broadcast_room_info(room_id).await;
+        }
+
+        Ok(())
+    });
+
+    gs_handler!(self, handle_set_room_member_tag, SetRoomMemberTagPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let room_id = self.room_id.load(Ordering::Relaxed);
+
+        if room_id == 0 {
+            return Ok(());
+        }
+
+        // the owner and moderators can hand out the tags the owner defined, to players in the room
+        let changed = self.game_server.state.room_manager.with_any(room_id, |room| {
+            room.can_manage(account_id)
+                && (packet.tag.is_none() || room.manager.players.contains_key(&packet.player))
+                && room.set_member_tag(packet.player, packet.tag)
+        });
+
+        if changed {
+            self.game_server.broadcast_room_info(room_id).await;
+        }
+
+        Ok(())
+    });
+
+    gs_handler!(self, handle_room_kick_player, RoomKickPlayerPacket, packet, {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let room_id = self.room_id.load(Ordering::Relaxed);
+
+        if room_id == 0 || packet.player == account_id {
+            return Ok(());
+        }
+
+        // the owner can kick anyone, moderators can only kick players that aren't moderators themselves
+        let allowed = self.game_server.state.room_manager.with_any(room_id, |room| {
+            room.manager.players.contains_key(&packet.player)
+                && room.owner != packet.player
+                && (room.owner == account_id || (room.is_moderator(account_id) && !room.is_moderator(packet.player)))
+        });
+
+        if !allowed {
+            return Ok(());
+        }
+
+        if let Some(thread) = self.game_server.get_user_by_id(packet.player) {
+            info!("[{account_id}] kicked {} out of room {room_id}", packet.player);
+            thread.push_new_message(ServerThreadMessage::RoomKick(room_id)).await;
+        }
+
+        Ok(())
+    });
+
+    /// Tells the client how long until the countdown ends. There is no shared clock between the server and the clients,
+    /// so instead every player gets the remaining time minus their own one way latency, and they all finish at the same moment.
+    pub(crate) async fn send_room_countdown(&self, level_id: LevelId, ends_at: Instant) -> crate::client::Result<()> {
+        // we might have left the level in the meantime
+        if self.level_id.load(Ordering::Relaxed) != level_id {
+            return Ok(());
+        }
+
+        // safety: only we can use the checker.
+        unsafe { self.state_checker.get_mut() }.start_race(level_id);
+
+        // safety: only we can use the estimator.
+        let one_way = unsafe { self.latency_estimator.get() }.rtt().unwrap_or_default() / 2;
+        let starts_in = ends_at.saturating_duration_since(Instant::now()).saturating_sub(one_way);
+
+        self.send_packet_static(&RoomCountdownPacket {
+            level_id,
+            starts_in: starts_in.as_millis() as u32,
+        })
+        .await
+    }
+
+    /// Records that the player did something, taking them out of the AFK state if they were in it.
+    pub(crate) fn record_activity(&self) {
+        // safety: only we can use the tracker.
+        if unsafe { self.afk_tracker.get_mut() }.record_activity() {
+            self.set_afk(false);
+        }
+    }
+
+    /// Like `record_activity`, but only counts if the player actually moved since their last update.
+    pub(crate) fn record_movement(&self, position: Point) {
+        // safety: only we can use the tracker.
+        if unsafe { self.afk_tracker.get_mut() }.record_position(position) {
+            self.set_afk(false);
+        }
+    }
+
+    fn set_afk(&self, afk: bool) {
+        self.is_afk.store(afk, Ordering::Relaxed);
+    }
+
+    /// Marks the player as AFK once they have been inactive for long enough. If they stay AFK in a room with a player limit,
+    /// they are warned, and after the grace period they are moved to the global room to make space for someone else.
+    pub(crate) async fn maybe_update_afk(&self) -> crate::client::Result<()> {
+        let (timeout, room_grace) = {
+            let conf = self.game_server.bridge.central_conf.lock();
+            (conf.afk_timeout, conf.afk_room_grace)
+        };
+
+        // safety: only we can use the tracker.
+        let Some(event) = unsafe { self.afk_tracker.get_mut() }.poll(Duration::from_secs(timeout.into()), Duration::from_secs(room_grace.into()))
+        else {
+            return Ok(());
+        };
+
+        let room_id = self.room_id.load(Ordering::Relaxed);
+        let in_capped_room = room_id != 0 && self.game_server.state.room_manager.with_any(room_id, |room| room.is_capped());
+
+        match event {
+            AfkEvent::WentAfk => {
+                debug!("[{}] went AFK", self.account_id.load(Ordering::Relaxed));
+                self.set_afk(true);
+
+                if in_capped_room && room_grace != 0 {
+                    let message = format!("You seem to be AFK, you will be moved out of this room in {room_grace} seconds unless you come back.");
+
+                    return self
+                        .send_packet_dynamic(&ServerNoticePacket {
+                            message: FastString::new(&message),
+                        })
+                        .await;
+                }
+
+                Ok(())
+            }
+            AfkEvent::RoomGraceOver if in_capped_room => self.move_afk_out_of_room(room_id).await,
+            AfkEvent::RoomGraceOver => Ok(()),
+        }
+    }
+
+    /// Moves an AFK player from their room to the global room.
+    async fn move_afk_out_of_room(&self, room_id: u32) -> crate::client::Result<()> {
+        if !self.move_to_global_room(room_id).await {
+            return Ok(());
+        }
+
+        debug!("[{}] moved AFK player out of room {room_id}", self.account_id.load(Ordering::Relaxed));
+
+        // the client joins its level again by itself
+        self.send_packet_static(&RoomAfkRemovedPacket).await?;
+        self._respond_with_room_list(0).await
+    }
+
+    /// Moves the player to the global room after the owner or a moderator of their room kicked them out of it.
+    pub(crate) async fn kick_out_of_room(&self, room_id: u32) -> crate::client::Result<()> {
+        if !self.move_to_global_room(room_id).await {
+            return Ok(());
+        }
+
+        self.send_packet_static(&RoomKickedPacket).await?;
+        self._respond_with_room_list(0).await
+    }
+
+    /// Moves the player from the room to the global room, the same way as if they left it on their own.
+    /// Returns `false` if they weren't in that room anymore.
+    async fn move_to_global_room(&self, room_id: u32) -> bool {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        // they might have left the room in the meantime
+        if self.room_id.compare_exchange(room_id, 0, Ordering::Relaxed, Ordering::Relaxed).is_err() {
+            return false;
+        }
+
+        let level_id = self.level_id.swap(0, Ordering::Relaxed);
+        if level_id != 0 {
+            self.level_history.lock().record(0, unix_timestamp());
+        }
+
+        if self.game_server.state.room_manager.remove_with_any(room_id, account_id, level_id) {
+            self.game_server.broadcast_room_info(room_id).await;
+        }
+
+        self.game_server.state.room_manager.get_global().manager.create_player(account_id);
+
+        self.emit_presence_change();
+
+        true
+    }
+
+    async fn send_room_join_failed(&self, code: ErrorCode, message: &str) -> crate::client::Result<()> {
+        self.send_packet_dynamic(&RoomJoinFailedPacket { code, message }).await
+    }
+
+    async fn send_room_webhook_failed(&self, code: ErrorCode, message: &str) -> crate::client::Result<()> {
+        self.send_packet_dynamic(&RoomWebhookFailedPacket { code, message }).await
+    }
+
     #[inline]
     async fn _respond_with_room_list(&self, room_id: u32) -> crate::client::Result<()> {
-        let room_info = self
-            .game_server
-            .state
-            .room_manager
-            .with_any(room_id, |room| room.get_room_info(room_id, self.game_server));
+        let (room_info, moderators, tags) = self.game_server.state.room_manager.with_any(room_id, |room| {
+            (room.get_room_info(room_id, self.game_server), room.moderators.clone(), room.get_tags())
+        });
 
         let can_moderate = self.user_role.lock().can_moderate();
 
         self.send_packet_dynamic(&RoomPlayerListPacket {
             room_info,
             players: self.game_server.get_room_player_previews(room_id, can_moderate),
+            moderators,
+            tags,
         })
         .await
     }
@@ -0,0 +1,370 @@
+//! The streams a client can be connected over. Besides plain TCP, clients behind networks that block raw TCP and UDP
+//! can connect over WebSocket (optionally with TLS), which carries the exact same byte stream in binary messages.
+//! Such clients can't reach the UDP socket, so everything that would normally go over UDP is sent through the stream as well.
+//!
+//! Clients can also connect over QUIC, where a bidirectional stream carries the TCP byte stream and unreliable datagrams
+//! replace the UDP socket. Those don't need to claim their thread, as both parts live in the same connection.
+
+use std::{
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+
+#[cfg(feature = "quic")]
+pub use quic::{quic_endpoint, QuicStream};
+#[cfg(feature = "websocket")]
+pub use websocket::{WebSocketAcceptor, WebSocketStream};
+
+pub enum ClientStream {
+    Tcp(TcpStream),
+    #[cfg(feature = "websocket")]
+    WebSocket(Box<WebSocketStream>),
+    #[cfg(feature = "quic")]
+    Quic(Box<QuicStream>),
+}
+
+impl ClientStream {
+    /// For clients that can't reach the UDP socket, returns the address their thread should be registered under
+    /// instead of a UDP peer (see `tunnel_addr`). Such clients never send a `ClaimThreadPacket`.
+    pub fn tunnel_peer(&self) -> Option<SocketAddr> {
+        match self {
+            Self::Tcp(_) => None,
+            #[cfg(feature = "websocket")]
+            Self::WebSocket(_) => Some(crate::util::tunnel_addr()),
+            #[cfg(feature = "quic")]
+            Self::Quic(stream) => Some(stream.peer),
+        }
+    }
+
+    /// Whether a packet of the given size that would normally use UDP can be sent as a datagram,
+    /// instead of having to go through this stream.
+    #[cfg_attr(not(feature = "quic"), allow(unused_variables))]
+    pub fn can_send_datagram(&self, size: usize) -> bool {
+        match self {
+            Self::Tcp(_) => true,
+            #[cfg(feature = "websocket")]
+            Self::WebSocket(_) => false,
+            #[cfg(feature = "quic")]
+            Self::Quic(stream) => stream.max_datagram_size().is_some_and(|max| size <= max),
+        }
+    }
+
+    /// Writes as much as possible without waiting, like `TcpStream::try_write`.
+    /// WebSocket messages can't be sent partially and QUIC streams can't be written to synchronously,
+    /// so for them this always fails with `WouldBlock`.
+    pub fn try_write(&self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.try_write(buf),
+            #[cfg(feature = "websocket")]
+            Self::WebSocket(_) => Err(io::ErrorKind::WouldBlock.into()),
+            #[cfg(feature = "quic")]
+            Self::Quic(_) => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+impl AsyncRead for ClientStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            #[cfg(feature = "websocket")]
+            Self::WebSocket(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+            #[cfg(feature = "quic")]
+            Self::Quic(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for ClientStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            #[cfg(feature = "websocket")]
+            Self::WebSocket(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+            #[cfg(feature = "quic")]
+            Self::Quic(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            #[cfg(feature = "websocket")]
+            Self::WebSocket(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+            #[cfg(feature = "quic")]
+            Self::Quic(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+            #[cfg(feature = "websocket")]
+            Self::WebSocket(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+            #[cfg(feature = "quic")]
+            Self::Quic(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
+
+#[cfg(feature = "websocket")]
+mod websocket {
+    use std::{
+        fs::File,
+        io::{self, BufReader},
+        pin::Pin,
+        sync::Arc,
+        task::{ready, Context, Poll},
+    };
+
+    use futures_util::{Sink, Stream};
+    use globed_shared::anyhow::{self, anyhow, bail};
+    use tokio_rustls::{rustls::ServerConfig, TlsAcceptor};
+    use tokio_tungstenite::tungstenite::{protocol::WebSocketConfig, Error as WsError, Message};
+
+    use crate::tokio::{
+        io::{AsyncRead, AsyncWrite, ReadBuf},
+        net::TcpStream,
+    };
+
+    /// Largest message a client can send, a single packet never comes close to it.
+    const MAX_MESSAGE_SIZE: usize = 256 * 1024;
+
+    trait Io: AsyncRead + AsyncWrite + Unpin + Send + Sync {}
+    impl<T: AsyncRead + AsyncWrite + Unpin + Send + Sync> Io for T {}
+
+    /// A WebSocket connection used as a byte stream. Incoming binary messages are read back to back,
+    /// and every write is sent as one binary message.
+    pub struct WebSocketStream {
+        inner: tokio_tungstenite::WebSocketStream<Box<dyn Io>>,
+        /// the part of the last message that didn't fit into the reader's buffer yet
+        pending: Vec<u8>,
+        pending_pos: usize,
+    }
+
+    fn to_io_error(err: WsError) -> io::Error {
+        match err {
+            WsError::Io(err) => err,
+            WsError::ConnectionClosed | WsError::AlreadyClosed => io::ErrorKind::ConnectionAborted.into(),
+            err => io::Error::other(err),
+        }
+    }
+
+    impl AsyncRead for WebSocketStream {
+        fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            let this = self.get_mut();
+
+            loop {
+                if this.pending_pos < this.pending.len() {
+                    let len = buf.remaining().min(this.pending.len() - this.pending_pos);
+                    buf.put_slice(&this.pending[this.pending_pos..this.pending_pos + len]);
+                    this.pending_pos += len;
+
+                    return Poll::Ready(Ok(()));
+                }
+
+                match ready!(Pin::new(&mut this.inner).poll_next(cx)) {
+                    Some(Ok(Message::Binary(data))) => {
+                        this.pending = data;
+                        this.pending_pos = 0;
+                    }
+                    // pings are answered by tungstenite on its own
+                    Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_))) => {}
+                    Some(Ok(Message::Text(_))) => {
+                        return Poll::Ready(Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "text messages are not part of the protocol",
+                        )));
+                    }
+                    // reading nothing means the end of the stream
+                    Some(Ok(Message::Close(_))) | None => return Poll::Ready(Ok(())),
+                    Some(Err(err)) => return Poll::Ready(Err(to_io_error(err))),
+                }
+            }
+        }
+    }
+
+    impl AsyncWrite for WebSocketStream {
+        fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+
+            ready!(Pin::new(&mut this.inner).poll_ready(cx)).map_err(to_io_error)?;
+            Pin::new(&mut this.inner).start_send(Message::Binary(buf.to_vec())).map_err(to_io_error)?;
+
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_flush(cx).map_err(to_io_error)
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_close(cx).map_err(to_io_error)
+        }
+    }
+
+    /// Turns accepted TCP connections into WebSocket streams, doing the TLS handshake first if a certificate is configured.
+    #[derive(Clone)]
+    pub struct WebSocketAcceptor {
+        tls: Option<TlsAcceptor>,
+    }
+
+    impl WebSocketAcceptor {
+        /// Loads the certificate chain and private key from `GLOBED_GS_WS_TLS_CERT` and `GLOBED_GS_WS_TLS_KEY` (both PEM files).
+        /// If neither is set, connections are accepted without TLS.
+        pub fn from_env() -> anyhow::Result<Self> {
+            let cert = std::env::var("GLOBED_GS_WS_TLS_CERT").ok();
+            let key = std::env::var("GLOBED_GS_WS_TLS_KEY").ok();
+
+            let tls = match (cert, key) {
+                (None, None) => None,
+                (Some(cert), Some(key)) => {
+                    let certs = rustls_pemfile::certs(&mut BufReader::new(File::open(&cert)?)).collect::<Result<Vec<_>, _>>()?;
+                    let private_key = rustls_pemfile::private_key(&mut BufReader::new(File::open(&key)?))?
+                        .ok_or_else(|| anyhow!("no private key found in {key}"))?;
+
+                    let config = ServerConfig::builder().with_no_client_auth().with_single_cert(certs, private_key)?;
+                    Some(TlsAcceptor::from(Arc::new(config)))
+                }
+                _ => bail!("GLOBED_GS_WS_TLS_CERT and GLOBED_GS_WS_TLS_KEY must be set together"),
+            };
+
+            Ok(Self { tls })
+        }
+
+        pub fn is_tls(&self) -> bool {
+            self.tls.is_some()
+        }
+
+        pub async fn accept(&self, stream: TcpStream) -> anyhow::Result<WebSocketStream> {
+            let stream: Box<dyn Io> = match &self.tls {
+                Some(tls) => Box::new(tls.accept(stream).await?),
+                None => Box::new(stream),
+            };
+
+            let config = WebSocketConfig {
+                max_message_size: Some(MAX_MESSAGE_SIZE),
+                max_frame_size: Some(MAX_MESSAGE_SIZE),
+                ..Default::default()
+            };
+
+            let inner = tokio_tungstenite::accept_async_with_config(stream, Some(config)).await?;
+
+            Ok(WebSocketStream {
+                inner,
+                pending: Vec::new(),
+                pending_pos: 0,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "quic")]
+mod quic {
+    use std::{
+        fs::File,
+        io::{self, BufReader},
+        net::SocketAddr,
+        pin::Pin,
+        sync::Arc,
+        task::{Context, Poll},
+    };
+
+    use globed_shared::anyhow::{self, anyhow};
+    use quinn::{Connection, Endpoint, EndpointConfig, Incoming, RecvStream, SendStream, ServerConfig, TransportConfig};
+
+    use crate::{
+        tokio::io::{AsyncRead, AsyncWrite, ReadBuf},
+        util::{bind_udp, tunnel_addr},
+    };
+
+    /// A QUIC connection of a client. The first bidirectional stream the client opens is used like the TCP connection,
+    /// while datagrams are used instead of the UDP socket.
+    pub struct QuicStream {
+        connection: Connection,
+        send: SendStream,
+        recv: RecvStream,
+        /// the address the thread is registered under, as datagrams don't come from the UDP socket
+        pub peer: SocketAddr,
+    }
+
+    impl QuicStream {
+        /// Finishes the handshake of an incoming connection and waits for the client to open its stream.
+        pub async fn accept(incoming: Incoming) -> anyhow::Result<Self> {
+            let connection = incoming.await?;
+            let (send, recv) = connection.accept_bi().await?;
+
+            Ok(Self {
+                connection,
+                send,
+                recv,
+                peer: tunnel_addr(),
+            })
+        }
+
+        pub fn connection(&self) -> &Connection {
+            &self.connection
+        }
+
+        /// Returns the largest datagram that can currently be sent, or `None` if the client doesn't accept datagrams.
+        pub fn max_datagram_size(&self) -> Option<usize> {
+            self.connection.max_datagram_size()
+        }
+
+        /// Sends an unreliable datagram, never waits.
+        pub fn send_datagram(&self, buf: &[u8]) -> io::Result<()> {
+            self.connection.send_datagram(buf.to_vec().into()).map_err(io::Error::other)
+        }
+    }
+
+    impl AsyncRead for QuicStream {
+        fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().recv).poll_read(cx, buf)
+        }
+    }
+
+    impl AsyncWrite for QuicStream {
+        fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            // quinn's `SendStream` has an inherent `poll_write` with a different error type, call the trait one
+            AsyncWrite::poll_write(Pin::new(&mut self.get_mut().send), cx, buf)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().send).poll_flush(cx)
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().send).poll_shutdown(cx)
+        }
+    }
+
+    /// Creates a QUIC endpoint on the given address, with the certificate chain and private key from
+    /// `GLOBED_GS_QUIC_TLS_CERT` and `GLOBED_GS_QUIC_TLS_KEY` (both PEM files). Unlike WebSocket, QUIC can't work without TLS.
+    pub fn quic_endpoint(address: SocketAddr) -> anyhow::Result<Endpoint> {
+        let cert = std::env::var("GLOBED_GS_QUIC_TLS_CERT").map_err(|_| anyhow!("GLOBED_GS_QUIC_TLS_CERT is not set"))?;
+        let key = std::env::var("GLOBED_GS_QUIC_TLS_KEY").map_err(|_| anyhow!("GLOBED_GS_QUIC_TLS_KEY is not set"))?;
+
+        let certs = rustls_pemfile::certs(&mut BufReader::new(File::open(&cert)?)).collect::<Result<Vec<_>, _>>()?;
+        let private_key =
+            rustls_pemfile::private_key(&mut BufReader::new(File::open(&key)?))?.ok_or_else(|| anyhow!("no private key found in {key}"))?;
+
+        // a client only ever needs the one stream, everything else goes through datagrams
+        let mut transport = TransportConfig::default();
+        transport.max_concurrent_bidi_streams(1u8.into()).max_concurrent_uni_streams(0u8.into());
+
+        let mut config = ServerConfig::with_single_cert(certs, private_key)?;
+        config.transport_config(Arc::new(transport));
+
+        let runtime = quinn::default_runtime().ok_or_else(|| anyhow!("no async runtime found"))?;
+        let socket = bind_udp(address)?.into_std()?;
+
+        Ok(Endpoint::new(EndpointConfig::default(), Some(config), socket, runtime)?)
+    }
+}
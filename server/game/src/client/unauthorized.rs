@@ -1,26 +1,28 @@
 use std::{
-    net::SocketAddrV4,
+    net::{IpAddr, SocketAddr},
     sync::{
-        atomic::{AtomicI32, AtomicU16, AtomicU32, Ordering, AtomicBool},
-        Arc,
+        atomic::{AtomicBool, AtomicI32, AtomicU16, AtomicU32, Ordering},
+        Arc, OnceLock,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 #[allow(unused_imports)]
 use globed_shared::{
     debug, info,
     rand::{self, Rng},
-    warn, SyncMutex, UserEntry, MIN_CLIENT_VERSION, PROTOCOL_VERSION,
+    unix_timestamp, warn, NameCheck, NameRejection, SyncMutex, TrustTier, UserEntry, MIN_CLIENT_VERSION, PROTOCOL_VERSION,
 };
 
 use super::*;
 use crate::{
     data::*,
-    managers::ComputedRole,
-    server::GameServer,
-    tokio::{self, net::TcpStream, sync::Notify},
-    util::LockfreeMutCell,
+    events::ServerEvent,
+    managers::{ClaimRefusal, ROOM_REJOIN_WINDOW},
+    scripting::HookResult,
+    server::{max_datagram_size, DuplicateLoginOutcome, GameServer},
+    tokio::{self, sync::Notify},
+    util::{address_prefix, CompressionConfig, LevelHistory, LockfreeMutCell},
 };
 
 /// `UnauthorizedThread` is a thread that can be formed for 2 reasons:
@@ -38,6 +40,8 @@ pub struct UnauthorizedThread {
     pub connection_state: AtomicClientThreadState,
 
     pub secret_key: u32,
+    /// identifies this login in the session list of the player, stays the same while the connection is being recovered
+    pub session_id: u64,
 
     pub account_id: AtomicI32,
     pub level_id: AtomicLevelId,
@@ -46,19 +50,30 @@ pub struct UnauthorizedThread {
     pub account_data: SyncMutex<PlayerAccountData>,
     pub user_entry: SyncMutex<Option<UserEntry>>,
     pub user_role: SyncMutex<Option<ComputedRole>>,
+    pub trust_tier: SyncMutex<TrustTier>,
 
     pub fragmentation_limit: AtomicU16,
+    pub platform: SyncMutex<ClientPlatform>,
 
-    pub claim_udp_peer: SyncMutex<Option<SocketAddrV4>>,
+    pub claim_udp_peer: SyncMutex<Option<SocketAddr>>,
     pub claim_udp_notify: Notify,
 
-    pub recover_stream: SyncMutex<Option<(TcpStream, SocketAddrV4)>>,
+    pub recover_stream: SyncMutex<Option<(ClientStream, SocketAddr)>>,
     pub recover_notify: Notify,
 
     pub terminate_notify: Notify,
 
     pub destruction_notify: Arc<Notify>,
     pub is_invisible: AtomicBool,
+    /// kept around while the client is reconnecting, so it isn't lost with the connection
+    pub level_history: SyncMutex<LevelHistory>,
+
+    /// address the thread was created for, used for limiting the amount of pending connections per IP
+    pub peer_ip: IpAddr,
+    /// when the client started logging in (or recovering), stale threads get evicted by the game server
+    pub pending_since: SyncMutex<Instant>,
+    /// whether this thread belongs to a logged in client that lost its connection, rather than a new connection
+    pub downgraded: bool,
 }
 
 pub enum UnauthorizedThreadOutcome {
@@ -66,16 +81,49 @@ pub enum UnauthorizedThreadOutcome {
     Terminate,
 }
 
+/// What loading the account of a player that is logging in resulted in.
+enum LoadedUser {
+    Loaded(Box<UserEntry>),
+    /// standalone server without a database, there is no account to load
+    Unavailable,
+    /// the player can't log in, and was already told why
+    Rejected,
+}
+
 const TIMEOUT: Duration = Duration::from_secs(90);
+const DEFAULT_RECONNECT_GRACE_PERIOD: Duration = Duration::from_secs(90);
+
+/// Returns how long a client that lost its connection can take to recover it, based on `GLOBED_GS_RECONNECT_GRACE_PERIOD`
+/// (in seconds, by default `DEFAULT_RECONNECT_GRACE_PERIOD`). Until then, the player stays in their room and on their level.
+fn reconnect_grace_period() -> Duration {
+    static PERIOD: OnceLock<Duration> = OnceLock::new();
+
+    *PERIOD.get_or_init(|| {
+        let Ok(value) = std::env::var("GLOBED_GS_RECONNECT_GRACE_PERIOD") else {
+            return DEFAULT_RECONNECT_GRACE_PERIOD;
+        };
+
+        if let Ok(secs) = value.parse::<u64>() {
+            Duration::from_secs(secs)
+        } else {
+            warn!(
+                "invalid value for GLOBED_GS_RECONNECT_GRACE_PERIOD, must be a number of seconds, using the default ({})",
+                DEFAULT_RECONNECT_GRACE_PERIOD.as_secs()
+            );
+            DEFAULT_RECONNECT_GRACE_PERIOD
+        }
+    })
+}
 
 impl UnauthorizedThread {
-    pub fn new(socket: TcpStream, peer: SocketAddrV4, game_server: &'static GameServer) -> Self {
+    pub fn new(socket: ClientStream, peer: SocketAddr, game_server: &'static GameServer) -> Self {
         Self {
             game_server,
             socket: LockfreeMutCell::new(ClientSocket::new(socket, peer, game_server)),
             connection_state: AtomicClientThreadState::default(),
 
             secret_key: rand::thread_rng().gen(),
+            session_id: rand::thread_rng().gen(),
 
             account_id: AtomicI32::new(0),
             level_id: AtomicLevelId::new(0),
@@ -84,8 +132,10 @@ impl UnauthorizedThread {
             account_data: SyncMutex::new(PlayerAccountData::default()),
             user_entry: SyncMutex::new(None),
             user_role: SyncMutex::new(None),
+            trust_tier: SyncMutex::new(TrustTier::default()),
 
             fragmentation_limit: AtomicU16::new(0),
+            platform: SyncMutex::new(ClientPlatform::default()),
 
             claim_udp_peer: SyncMutex::new(None),
             claim_udp_notify: Notify::new(),
@@ -97,16 +147,25 @@ impl UnauthorizedThread {
 
             destruction_notify: Arc::new(Notify::new()),
             is_invisible: AtomicBool::new(false),
+            level_history: SyncMutex::new(LevelHistory::default()),
+
+            peer_ip: peer.ip(),
+            pending_since: SyncMutex::new(Instant::now()),
+            downgraded: false,
         }
     }
 
     pub fn downgrade(thread: ClientThread) -> Self {
+        // safety: the thread no longer runs
+        let peer_ip = unsafe { thread.socket.get() }.tcp_peer.ip();
+
         Self {
             game_server: thread.game_server,
             socket: thread.socket,
             connection_state: AtomicClientThreadState::new(ClientThreadState::Disconnected),
 
             secret_key: thread.secret_key,
+            session_id: thread.session_id,
 
             account_id: thread.account_id,
             level_id: thread.level_id,
@@ -115,8 +174,10 @@ impl UnauthorizedThread {
             account_data: SyncMutex::new(std::mem::take(&mut *thread.account_data.lock())),
             user_entry: SyncMutex::new(Some(std::mem::take(&mut *thread.user_entry.lock()))),
             user_role: SyncMutex::new(Some(std::mem::take(&mut *thread.user_role.lock()))),
+            trust_tier: thread.trust_tier,
 
             fragmentation_limit: thread.fragmentation_limit,
+            platform: thread.platform,
 
             claim_udp_peer: SyncMutex::new(None),
             claim_udp_notify: Notify::new(),
@@ -128,6 +189,11 @@ impl UnauthorizedThread {
 
             destruction_notify: thread.destruction_notify,
             is_invisible: thread.is_invisible,
+            level_history: thread.level_history,
+
+            peer_ip,
+            pending_since: SyncMutex::new(Instant::now()),
+            downgraded: true,
         }
     }
 
@@ -142,7 +208,7 @@ impl UnauthorizedThread {
 
                 /* disconnected state, wait until another tcp stream tries to recover us */
                 ClientThreadState::Disconnected => tokio::select! {
-                    x = tokio::time::timeout(TIMEOUT, self.wait_for_recovered()) => match x {
+                    x = tokio::time::timeout(reconnect_grace_period(), self.wait_for_recovered()) => match x {
                         Ok((stream, tcp_peer)) => {
                             // we just got recovered yay
                             let socket = self.get_socket();
@@ -164,6 +230,7 @@ impl UnauthorizedThread {
                                 continue;
                             }
 
+                            *self.pending_since.lock() = Instant::now();
                             self.connection_state.store(ClientThreadState::Unclaimed);
                         }
                         Err(_) => {
@@ -180,10 +247,17 @@ impl UnauthorizedThread {
                 /* unauthorized state, wait until the user sends a handshake and a LoginPacket */
                 ClientThreadState::Unauthorized => tokio::select! {
                     x = tokio::time::timeout(TIMEOUT, self.get_socket().poll_for_tcp_data()) => match x {
-                        Ok(Ok(datalen)) => match self.recv_and_handle(datalen).await {
-                            Ok(()) => {}
-                            Err(e) => {
-                                warn!("error on an unauth thread: {e}");
+                        // a client could announce a packet and then never send it, so allow evicting the thread here as well
+                        Ok(Ok(datalen)) => tokio::select! {
+                            result = self.recv_and_handle(datalen) => match result {
+                                Ok(()) => {}
+                                Err(e) => {
+                                    warn!("error on an unauth thread: {e}");
+                                    self.terminate();
+                                }
+                            },
+
+                            () = self.wait_for_termianted() => {
                                 self.terminate();
                             }
                         },
@@ -227,13 +301,13 @@ impl UnauthorizedThread {
         }
     }
 
-    pub fn claim(&self, udp_peer: SocketAddrV4) {
+    pub fn claim(&self, udp_peer: SocketAddr) {
         *self.claim_udp_peer.lock() = Some(udp_peer);
         self.claim_udp_notify.notify_one();
     }
 
-    pub fn recover(&self, tcp_stream: TcpStream, peer: SocketAddrV4) {
-        *self.recover_stream.lock() = Some((tcp_stream, peer));
+    pub fn recover(&self, stream: ClientStream, peer: SocketAddr) {
+        *self.recover_stream.lock() = Some((stream, peer));
         self.recover_notify.notify_one();
     }
 
@@ -253,6 +327,11 @@ impl UnauthorizedThread {
         let mut data = ByteReader::from_bytes(message);
         let header = data.read_packet_header()?;
 
+        // only the server compresses packets
+        if header.compressed {
+            return Err(PacketHandlingError::MalformedMessage);
+        }
+
         // reject cleartext credentials
         if header.packet_id == LoginPacket::PACKET_ID && !header.encrypted {
             return Err(PacketHandlingError::MalformedLoginAttempt);
@@ -289,11 +368,20 @@ impl UnauthorizedThread {
         }
 
         socket.init_crypto_box(&packet.key)?;
+
+        // only enabled after the response, as the client doesn't know the algorithm until then
+        let compression = CompressionConfig::get().negotiate(packet.compression);
+
         socket
             .send_packet_static(&CryptoHandshakeResponsePacket {
                 key: self.game_server.public_key.clone().into(),
+                compression,
             })
-            .await
+            .await?;
+
+        socket.compression = compression;
+
+        Ok(())
     });
 
     gs_handler!(self, handle_login, LoginPacket, packet, {
@@ -305,12 +393,24 @@ impl UnauthorizedThread {
 
         // disconnect if server is under maintenance
         if self.game_server.bridge.central_conf.lock().maintenance {
-            gs_disconnect!(self, "The server is currently under maintenance, please try connecting again later.");
+            gs_disconnect!(self, DisconnectReason::Maintenance, "");
+        }
+
+        let peer_ip = self.get_tcp_peer().ip();
+        if !self.game_server.state.rate_limiter.try_login(peer_ip) {
+            socket
+                .send_packet_dynamic(&LoginFailedPacket {
+                    code: ErrorCode::RateLimited,
+                    message: "Too many login attempts from your network, please try again later.",
+                })
+                .await?;
+            return Ok(());
         }
 
         if packet.fragmentation_limit < 1300 {
             gs_disconnect!(
                 self,
+                DisconnectReason::ProtocolError,
                 &format!(
                     "The client fragmentation limit is too low ({} bytes) to be accepted",
                     packet.fragmentation_limit
@@ -319,21 +419,52 @@ impl UnauthorizedThread {
         }
 
         self.fragmentation_limit.store(packet.fragmentation_limit, Ordering::Relaxed);
+        *self.platform.lock() = ClientPlatform::from_login_string(packet.platform.try_to_str());
 
         if packet.account_id <= 0 || packet.user_id <= 0 {
             let message = format!(
                 "Invalid account/user ID was sent ({} and {}). Please note that you must be signed into a Geometry Dash account before connecting.",
                 packet.account_id, packet.user_id
             );
-            socket.send_packet_dynamic(&LoginFailedPacket { message: &message }).await?;
+            socket
+                .send_packet_dynamic(&LoginFailedPacket {
+                    code: ErrorCode::InvalidRequest,
+                    message: &message,
+                })
+                .await?;
             return Ok(());
         }
 
         // skip authentication if standalone
         let standalone = self.game_server.standalone;
+        // account IDs can't be verified on standalone servers, so a token from an earlier login is the only proof that this is the same player.
+        // the name is only claimed once the login succeeds, so that a rejected login can't take it
+        let mut token_name = None;
         let player_name = if standalone {
+            token_name = self
+                .game_server
+                .bridge
+                .token_issuer
+                .lock()
+                .validate(packet.account_id, packet.user_id, packet.token.try_to_str())
+                .ok();
+
+            let name_claims = &self.game_server.state.name_claims;
+            if let Err(refusal) = name_claims.check(packet.account_id, packet.name.try_to_str(), token_name.as_deref()) {
+                Self::send_claim_refusal(socket, refusal).await?;
+                return Ok(());
+            }
+
             packet.name
         } else {
+            // only the address can be checked before the token, otherwise anyone could lock others out of their account
+            // by sending bad tokens with their account ID
+            let auth_lockout = &self.game_server.state.auth_lockout;
+            if let Some(lockout) = auth_lockout.get_address_lockout(peer_ip) {
+                Self::send_lockout(socket, lockout).await?;
+                return Ok(());
+            }
+
             // lets verify the given token
             let result = {
                 self.game_server
@@ -344,58 +475,133 @@ impl UnauthorizedThread {
             };
 
             match result {
-                Ok(x) => InlineString::new(&x),
+                Ok(x) => {
+                    auth_lockout.record_success(packet.account_id);
+                    InlineString::new(&x)
+                }
+                Err(_) if let Some(lockout) = auth_lockout.get_account_lockout(packet.account_id) => {
+                    Self::send_lockout(socket, lockout).await?;
+                    return Ok(());
+                }
                 Err(err) => {
+                    let bursts = auth_lockout.record_failure(packet.account_id, peer_ip);
+                    self.game_server.report_auth_failure_bursts(bursts).await;
+
                     let mut message = FastString::new("authentication failed: ");
                     message.extend(err.error_message());
 
-                    socket.send_packet_dynamic(&LoginFailedPacket { message: &message }).await?;
+                    socket
+                        .send_packet_dynamic(&LoginFailedPacket {
+                            code: ErrorCode::InvalidToken,
+                            message: &message,
+                        })
+                        .await?;
                     return Ok(());
                 }
             }
         };
 
-        // check if the user is already logged in, kick the other instance
-        self.game_server.check_already_logged_in(packet.account_id).await?;
+        // a rejected name is checked for before anything else, as the check is instant and the user data load below
+        // would otherwise already redeem the invite code of a login that is going to be refused anyway
+        let name_check = self.game_server.bridge.central_conf.lock().name_policy.check(player_name.try_to_str());
+        let flagged_name = match name_check {
+            NameCheck::Allowed => None,
+            NameCheck::Flagged(matched) => Some(matched),
+            NameCheck::Rejected(reason) => {
+                let message = match reason {
+                    NameRejection::Blocked => ErrorMessage::new("name-rejected-blocked"),
+                    NameRejection::InvisibleChars => ErrorMessage::new("name-rejected-invisible"),
+                    NameRejection::Blank => ErrorMessage::new("name-rejected-blank"),
+                };
+
+                socket.send_packet_static(&LoginNameRejectedPacket { message }).await?;
+                return Ok(());
+            }
+        };
 
-        // fetch data from the central
-        if !standalone {
-            let user_entry = match self.game_server.bridge.get_user_data(&packet.account_id.to_string()).await {
-                Ok(user) if user.is_banned => {
-                    socket
-                        .send_packet_dynamic(&ServerBannedPacket {
-                            message: FastString::new(&user.violation_reason.as_ref().map_or_else(|| "No reason given".to_owned(), |x| x.clone())),
-                            timestamp: user.violation_expiry.unwrap_or_default(),
-                        })
-                        .await?;
+        // reporting a flagged name doesn't affect the login, so do it while the user data is being fetched
+        let report_name = async {
+            if let Some(matched) = flagged_name {
+                self.game_server
+                    .report_flagged_name(packet.account_id, player_name.try_to_string(), matched)
+                    .await;
+            }
+        };
 
-                    return Ok(());
-                }
-                Ok(user) if self.game_server.bridge.is_whitelist() && !user.is_whitelisted => {
-                    socket
-                        .send_packet_dynamic(&LoginFailedPacket {
-                            message: "This server has whitelist enabled and your account has not been allowed.",
-                        })
-                        .await?;
+        let (loaded, ()) = tokio::join!(
+            self.load_user_entry(packet.account_id, packet.invite_code.try_to_str(), player_name.try_to_str()),
+            report_name
+        );
 
-                    return Ok(());
-                }
-                Ok(user) => user,
-                Err(err) => {
-                    let mut message = InlineString::<256>::new("failed to fetch user data: ");
-                    message.extend_safe(&err.to_string());
+        // the roles are computed once here, and reused for everything below
+        let roles = match loaded? {
+            LoadedUser::Loaded(user_entry) => self.set_user_entry(*user_entry),
+            LoadedUser::Unavailable => Vec::new(),
+            LoadedUser::Rejected => return Ok(()),
+        };
 
-                    socket.send_packet_dynamic(&LoginFailedPacket { message: &message }).await?;
-                    return Ok(());
-                }
-            };
+        if let HookResult::Cancel(reason) = self.game_server.state.scripts.on_login(packet.account_id, &player_name.try_to_string()) {
+            socket
+                .send_packet_dynamic(&LoginFailedPacket {
+                    code: ErrorCode::Rejected,
+                    message: reason.as_deref().unwrap_or("Login was rejected by the server"),
+                })
+                .await?;
 
-            *self.user_role.lock() = Some(self.game_server.state.role_manager.compute(&user_entry.user_roles));
-            *self.user_entry.lock() = Some(user_entry);
+            return Ok(());
+        }
+
+        if standalone {
+            let name_claims = &self.game_server.state.name_claims;
+            if let Err(refusal) = name_claims.try_claim(packet.account_id, player_name.try_to_str(), token_name.as_deref()) {
+                Self::send_claim_refusal(socket, refusal).await?;
+                return Ok(());
+            }
+        }
+
+        // check if the user is already logged in, and either kick the other instance or reject this one.
+        // this only happens once nothing else can refuse the login, so that a rejected login can't log out the player's other session
+        let duplicate_login = self.game_server.check_already_logged_in(packet.account_id, &roles).await;
+
+        if duplicate_login == DuplicateLoginOutcome::TimedOut {
+            socket
+                .send_packet_dynamic(&LoginFailedPacket {
+                    code: ErrorCode::Busy,
+                    message: "Your previous session is still being logged out, please try again in a moment.",
+                })
+                .await?;
+
+            return Ok(());
+        }
+
+        if duplicate_login == DuplicateLoginOutcome::Rejected {
+            socket
+                .send_packet_static(&DuplicateLoginPacket {
+                    notice: DuplicateLoginNotice::Rejected,
+                })
+                .await?;
+
+            socket
+                .send_packet_dynamic(&LoginFailedPacket {
+                    code: ErrorCode::AlreadyLoggedIn,
+                    message: "You are already logged in from a different place.",
+                })
+                .await?;
+
+            return Ok(());
         }
 
         self.account_id.store(packet.account_id, Ordering::Relaxed);
         self.game_server.state.inc_player_count(); // increment player count
+        self.game_server.state.usage_stats.record_login(*self.platform.lock());
+        self.game_server.start_session(AccountSession {
+            account_id: packet.account_id,
+            session_id: self.session_id,
+            platform: *self.platform.lock(),
+            ip_prefix: address_prefix(self.peer_ip),
+            login_time: unix_timestamp(),
+            logout_time: 0,
+        });
 
         info!(
             "[{} ({}) @ {}] Login successful, platform: {}",
@@ -405,34 +611,216 @@ impl UnauthorizedThread {
             packet.platform
         );
 
+        self.game_server.state.events.emit(|| ServerEvent::PlayerJoined {
+            account_id: packet.account_id,
+            name: player_name.try_to_string(),
+        });
+
+        let mut icons = packet.icons.clone();
+        icons.strip_unapproved_cosmetic(&self.game_server.bridge.central_conf.lock().approved_cosmetics);
+
         {
             let mut account_data = self.account_data.lock();
             account_data.account_id = packet.account_id;
             account_data.user_id = packet.user_id;
-            account_data.name = player_name;
-            account_data.icons.clone_from(&packet.icons);
+            account_data.name = player_name.clone();
+            account_data.icons = icons;
 
-            let user_entry = self.user_entry.lock();
-            if let Some(user_entry) = &*user_entry {
-                let sud = SpecialUserData::from_user_entry(user_entry, &self.game_server.state.role_manager);
-
-                account_data.special_user_data = sud;
-            }
+            account_data.special_user_data = self.game_server.state.role_manager.special_user_data(&roles);
         };
 
-        // add them to the global room
-        self.game_server.state.room_manager.get_global().manager.create_player(packet.account_id);
+        // put them back into their room if the server was just restarted or they left it shortly before, otherwise add them to the global room
+        let room_manager = &self.game_server.state.room_manager;
+        let recent = self.game_server.state.recent_players.remove(packet.account_id);
+
+        // carry over the level history, so that moderators can still see where they were before leaving
+        if let Some(recent) = &recent {
+            self.level_history.lock().clone_from(&recent.level_history);
+        }
+
+        let resumed_room = room_manager.resume_session(packet.account_id).or_else(|| {
+            let recent = recent.filter(|p| p.room_id != 0 && p.left_at.elapsed() < ROOM_REJOIN_WINDOW)?;
+            let role = self
+                .user_role
+                .lock()
+                .clone()
+                .unwrap_or_else(|| self.game_server.state.role_manager.get_default());
+
+            room_manager.rejoin_room(packet.account_id, recent.room_id, &role)
+        });
+
+        if let Some(info) = &resumed_room {
+            self.room_id.store(info.id, Ordering::Relaxed);
+        } else {
+            self.game_server.state.room_manager.get_global().manager.create_player(packet.account_id);
+        }
 
         self.send_login_success().await?;
 
+        if standalone {
+            let token = self
+                .game_server
+                .bridge
+                .token_issuer
+                .lock()
+                .generate(packet.account_id, packet.user_id, player_name.try_to_str());
+
+            self.get_socket()
+                .send_packet_dynamic(&StandaloneTokenPacket {
+                    token: FastString::new(&token),
+                })
+                .await?;
+        }
+
+        // preferences are saved with the account, so the client might not know about them yet
+        let preferences = self.user_entry.lock().as_ref().map(|entry| entry.preferences.clone());
+        let hide_in_lists = preferences.as_ref().is_some_and(|x| x.hide_in_lists);
+
+        if let Some(preferences) = preferences {
+            self.get_socket().send_packet_dynamic(&PreferencesPacket { preferences }).await?;
+        }
+
+        if duplicate_login == DuplicateLoginOutcome::ReplacedOld {
+            self.get_socket()
+                .send_packet_static(&DuplicateLoginPacket {
+                    notice: DuplicateLoginNotice::ReplacedOther,
+                })
+                .await?;
+        }
+
+        if let Some(info) = resumed_room {
+            self.get_socket().send_packet_static(&TransferClaimedPacket { info }).await?;
+        }
+
         self.connection_state.store(ClientThreadState::Unclaimed); // as we still need ClaimThreadPacket to arrive
-        self.is_invisible.store(packet.is_invisible, Ordering::Relaxed);
+        self.is_invisible.store(packet.is_invisible || hide_in_lists, Ordering::Relaxed);
 
         Ok(())
     });
 
+    /// Fetches the account of a player that is logging in, from the central server or the local database.
+    /// If the player can't log in (banned, not whitelisted, or the fetch failed), tells them why and returns `LoadedUser::Rejected`.
+    async fn load_user_entry(&self, account_id: i32, invite_code: &str, player_name: &str) -> Result<LoadedUser> {
+        let socket = self.get_socket();
+
+        if !self.game_server.standalone {
+            let user_entry = match self.game_server.bridge.get_user_data(&account_id.to_string()).await {
+                Ok(user) if user.is_banned => {
+                    socket
+                        .send_packet_dynamic(&ServerBannedPacket {
+                            message: FastString::new(&user.violation_reason.as_ref().map_or_else(|| "No reason given".to_owned(), |x| x.clone())),
+                            timestamp: user.violation_expiry.unwrap_or_default(),
+                        })
+                        .await?;
+
+                    return Ok(LoadedUser::Rejected);
+                }
+                Ok(mut user) if self.game_server.bridge.is_whitelist() && !user.is_whitelisted => {
+                    let code = invite_code.trim();
+
+                    if code.is_empty() || !self.redeem_invite_code(&mut user, code, player_name).await {
+                        socket
+                            .send_packet_static(&LoginWhitelistRequiredPacket {
+                                invalid_code: !code.is_empty(),
+                            })
+                            .await?;
+
+                        return Ok(LoadedUser::Rejected);
+                    }
+
+                    user
+                }
+                Ok(user) => user,
+                Err(err) => {
+                    let mut message = InlineString::<256>::new("failed to fetch user data: ");
+                    message.extend_safe(&err.to_string());
+
+                    socket
+                        .send_packet_dynamic(&LoginFailedPacket {
+                            code: ErrorCode::CentralUnavailable,
+                            message: &message,
+                        })
+                        .await?;
+                    return Ok(LoadedUser::Rejected);
+                }
+            };
+
+            Ok(LoadedUser::Loaded(Box::new(user_entry)))
+        } else if let Some(store) = &self.game_server.state.store {
+            // standalone servers with a local database keep their own accounts
+            match store.record_login(account_id, player_name).await {
+                Ok(user) if user.is_banned => {
+                    socket
+                        .send_packet_dynamic(&ServerBannedPacket {
+                            message: FastString::new(user.violation_reason.as_deref().unwrap_or("No reason given")),
+                            timestamp: user.violation_expiry.unwrap_or_default(),
+                        })
+                        .await?;
+
+                    Ok(LoadedUser::Rejected)
+                }
+                Ok(mut user) => {
+                    // account IDs are not verified on standalone servers, so anyone could pretend to be a moderator
+                    user.user_roles.clear();
+                    user.timed_roles.clear();
+                    Ok(LoadedUser::Loaded(Box::new(user)))
+                }
+                Err(err) => {
+                    warn!("failed to load user data from the database: {err}");
+                    socket
+                        .send_packet_dynamic(&LoginFailedPacket {
+                            code: ErrorCode::Internal,
+                            message: "failed to load user data",
+                        })
+                        .await?;
+
+                    Ok(LoadedUser::Rejected)
+                }
+            }
+        } else {
+            Ok(LoadedUser::Unavailable)
+        }
+    }
+
+    /// Stores the user entry of a player that is logging in, along with the data computed from it. Returns their active roles.
+    fn set_user_entry(&self, user_entry: UserEntry) -> Vec<String> {
+        let trust_account_age = self.game_server.bridge.central_conf.lock().trust_account_age;
+        let now = unix_timestamp();
+        let roles = user_entry.active_roles(now);
+
+        *self.trust_tier.lock() = TrustTier::compute(&user_entry, trust_account_age, now);
+        *self.user_role.lock() = Some(self.game_server.state.role_manager.compute(&roles));
+        *self.user_entry.lock() = Some(user_entry);
+
+        roles
+    }
+
+    /// Uses up the invite code and whitelists the account, returns `false` if the code is invalid or the account couldn't be saved.
+    async fn redeem_invite_code(&self, user: &mut UserEntry, code: &str, player_name: &str) -> bool {
+        let invite_codes = &self.game_server.state.invite_codes;
+        if !invite_codes.redeem(code) {
+            return false;
+        }
+
+        user.is_whitelisted = true;
+
+        if let Err(err) = self.game_server.save_user_data(user).await {
+            warn!("failed to whitelist {player_name} ({}) with an invite code: {err}", user.account_id);
+            invite_codes.refund(code);
+            return false;
+        }
+
+        info!("{player_name} ({}) was whitelisted with an invite code", user.account_id);
+
+        true
+    }
+
     async fn send_login_success(&self) -> Result<()> {
-        let tps = self.game_server.bridge.central_conf.lock().tps;
+        let (tps, disabled_features) = {
+            let conf = self.game_server.bridge.central_conf.lock();
+            (conf.tps, conf.platform_features.for_platform(*self.platform.lock()))
+        };
+
         let all_roles = self.game_server.state.role_manager.get_all_roles();
         let special_user_data = self.account_data.lock().special_user_data.clone();
 
@@ -443,12 +831,20 @@ impl UnauthorizedThread {
                 all_roles,
                 secret_key: self.secret_key,
                 special_user_data,
+                max_datagram_size: max_datagram_size() as u32,
+                disabled_features,
             })
             .await
     }
 
     /// Blocks until we get notified that we got claimed by a UDP socket.
     async fn wait_for_claimed(&self) {
+        // tunneled clients never send a ClaimThreadPacket, as they can't reach the udp socket
+        if let Some(peer) = self.get_socket().socket.tunnel_peer() {
+            self.get_socket().udp_peer = Some(peer);
+            return;
+        }
+
         {
             let mut p = self.claim_udp_peer.lock();
             if p.is_some() {
@@ -469,7 +865,7 @@ impl UnauthorizedThread {
     }
 
     /// Blocks until we get notified that we got recovered and have an assigned TCP stream
-    async fn wait_for_recovered(&self) -> (TcpStream, SocketAddrV4) {
+    async fn wait_for_recovered(&self) -> (ClientStream, SocketAddr) {
         {
             let mut p = self.recover_stream.lock();
             if p.is_some() {
@@ -500,6 +896,24 @@ impl UnauthorizedThread {
         self.terminate_notify.notify_one();
     }
 
+    /// Returns `true` if the client is still logging in, or claiming a thread it recovered.
+    pub fn is_pending(&self) -> bool {
+        matches!(
+            self.connection_state.load(),
+            ClientThreadState::Unauthorized | ClientThreadState::Unclaimed
+        )
+    }
+
+    /// Returns `true` if the client lost its connection and hasn't finished recovering it yet.
+    /// Its player is still in the room and on the level, and stays visible to others until then.
+    pub fn is_awaiting_recovery(&self) -> bool {
+        self.downgraded
+            && matches!(
+                self.connection_state.load(),
+                ClientThreadState::Disconnected | ClientThreadState::Unclaimed
+            )
+    }
+
     /// do not call from another thread unless this thread is NOT running.
     /// otherwise always invokes undefined behavior.
     #[allow(clippy::mut_from_ref)]
@@ -508,14 +922,42 @@ impl UnauthorizedThread {
     }
 
     /// get the tcp address of the connected peer. do not call this from another clientthread
-    fn get_tcp_peer(&self) -> SocketAddrV4 {
+    fn get_tcp_peer(&self) -> SocketAddr {
         self.get_socket().tcp_peer
     }
 
     /// terminate and send a message to the user with the reason
-    async fn kick(&self, message: &str) -> Result<()> {
+    async fn kick(&self, reason: DisconnectReason, message: &str) -> Result<()> {
         self.terminate();
-        self.get_socket().send_packet_dynamic(&ServerDisconnectPacket { message }).await
+        self.get_socket().send_packet_dynamic(&ServerDisconnectPacket { reason, message }).await
+    }
+
+    async fn send_lockout(socket: &mut ClientSocket, lockout: Duration) -> Result<()> {
+        let message = format!(
+            "Too many failed login attempts, please try again in {} seconds.",
+            lockout.as_secs().max(1)
+        );
+
+        socket
+            .send_packet_dynamic(&LoginFailedPacket {
+                code: ErrorCode::RateLimited,
+                message: &message,
+            })
+            .await
+    }
+
+    async fn send_claim_refusal(socket: &mut ClientSocket, refusal: ClaimRefusal) -> Result<()> {
+        let message = match refusal {
+            ClaimRefusal::NameTaken => "Someone else is already using this name on this server.",
+            ClaimRefusal::AccountTaken => "Someone else is already logged into this account on this server.",
+        };
+
+        socket
+            .send_packet_dynamic(&LoginFailedPacket {
+                code: ErrorCode::NameTaken,
+                message,
+            })
+            .await
     }
 
     pub fn upgrade(self) -> ClientThread {
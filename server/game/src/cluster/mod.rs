@@ -0,0 +1,71 @@
+//! Synchronization between multiple game servers that are part of one deployment, over Redis pub/sub.
+//! Only functional when the server is built with the `redis` feature.
+
+#[cfg(feature = "redis")]
+mod pubsub;
+
+use std::sync::OnceLock;
+
+use globed_shared::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::{server::GameServer, tokio::sync::mpsc};
+
+/// A message sent to every other game server in the deployment.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClusterMessage {
+    /// notice sent by an admin to everyone on the server
+    Announcement { message: String },
+    /// a player was banned, muted, unbanned or unmuted
+    Sanction {
+        account_id: i32,
+        is_banned: bool,
+        is_muted: bool,
+        reason: Option<String>,
+        expiry: Option<i64>,
+    },
+    /// a player logged in, any other server that still has them connected should disconnect them
+    PlayerJoined { account_id: i32 },
+    /// a player logged out one of their sessions from another device, whichever server has it should disconnect it
+    SessionLogout { account_id: i32, session_id: u64 },
+}
+
+#[derive(Default)]
+pub struct ClusterLink {
+    outgoing: OnceLock<mpsc::Sender<ClusterMessage>>,
+}
+
+impl ClusterLink {
+    /// Whether this server is connected to other servers.
+    pub fn is_enabled(&self) -> bool {
+        self.outgoing.get().is_some()
+    }
+
+    /// Sends the message to every other server. Does nothing if this server is not part of a cluster.
+    pub fn publish(&self, message: ClusterMessage) {
+        let Some(outgoing) = self.outgoing.get() else {
+            return;
+        };
+
+        if outgoing.try_send(message).is_err() {
+            warn!("outgoing cluster message queue is full, dropping a message");
+        }
+    }
+}
+
+/// Connects to the Redis server in `GLOBED_GS_REDIS_URL`. Does nothing if the variable is not set.
+pub fn start(game_server: &'static GameServer) {
+    let Ok(url) = std::env::var("GLOBED_GS_REDIS_URL") else {
+        return;
+    };
+
+    #[cfg(feature = "redis")]
+    pubsub::start(game_server, &url);
+
+    #[cfg(not(feature = "redis"))]
+    {
+        let _ = (game_server, url);
+        warn!("GLOBED_GS_REDIS_URL is set, but the server was built without the `redis` feature, ignoring");
+    }
+}
@@ -0,0 +1,284 @@
+use std::{sync::atomic::Ordering, time::Duration};
+
+use futures_util::StreamExt;
+use globed_shared::{
+    anyhow, error, info,
+    rand::{self, Rng},
+    warn,
+};
+use redis::{aio::ConnectionManager, AsyncCommands, Client};
+use serde::{Deserialize, Serialize};
+
+use super::ClusterMessage;
+use crate::{
+    client::ServerThreadMessage,
+    data::{DisconnectReason, FastString, ServerBannedPacket, ServerMutedPacket, ServerNoticePacket},
+    events::ServerEvent,
+    server::GameServer,
+    tokio::{
+        self,
+        sync::{broadcast::error::RecvError, mpsc},
+    },
+};
+
+const CHANNEL: &str = "globed:cluster";
+/// How many outgoing messages can be queued before new ones get dropped.
+const OUTGOING_QUEUE_SIZE: usize = 1024;
+/// Presence of an instance expires after this many seconds, so that players of a crashed server don't stay online forever.
+const PRESENCE_TTL_SECS: i64 = 60;
+const PRESENCE_REFRESH_INTERVAL: Duration = Duration::from_secs(20);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    /// random ID of the server that sent the message, so that servers can ignore their own messages
+    instance: String,
+    message: ClusterMessage,
+}
+
+pub fn start(game_server: &'static GameServer, url: &str) {
+    let client = match Client::open(url) {
+        Ok(x) => x,
+        Err(e) => {
+            error!("invalid Redis URL: {e}");
+            return;
+        }
+    };
+
+    let instance = format!("{:016x}", rand::thread_rng().gen::<u64>());
+    info!("Joining the cluster as instance {instance}");
+
+    let (tx, rx) = mpsc::channel(OUTGOING_QUEUE_SIZE);
+    let _ = game_server.state.cluster.outgoing.set(tx);
+
+    let events = game_server.state.events.subscribe();
+
+    tokio::spawn(run_publisher(game_server, client.clone(), instance.clone(), rx, events));
+    tokio::spawn(run_subscriber(game_server, client, instance));
+}
+
+/// Sends messages to other servers and keeps the list of players online on this server up to date.
+async fn run_publisher(
+    game_server: &'static GameServer,
+    client: Client,
+    instance: String,
+    mut outgoing: mpsc::Receiver<ClusterMessage>,
+    mut events: tokio::sync::broadcast::Receiver<ServerEvent>,
+) {
+    // the connection manager reconnects by itself, this only fails if the first connection can't be made
+    let mut conn = loop {
+        match ConnectionManager::new(client.clone()).await {
+            Ok(x) => break x,
+            Err(e) => {
+                warn!("failed to connect to Redis: {e}, retrying in {}s", RECONNECT_DELAY.as_secs());
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    };
+
+    let presence_key = format!("globed:presence:{instance}");
+    let mut presence_refresh = tokio::time::interval(PRESENCE_REFRESH_INTERVAL);
+    // set whenever talking to Redis fails, as updates to the presence could have been lost in the meantime
+    let mut resync_presence = true;
+
+    loop {
+        let result: redis::RedisResult<()> = tokio::select! {
+            message = outgoing.recv() => {
+                let Some(message) = message else {
+                    break;
+                };
+
+                publish(&mut conn, &instance, message).await
+            }
+
+            event = events.recv() => match event {
+                Ok(ServerEvent::PlayerJoined { account_id, name }) => {
+                    let res: redis::RedisResult<()> = conn.hset(&presence_key, account_id, name).await;
+
+                    match res {
+                        Ok(()) => publish(&mut conn, &instance, ClusterMessage::PlayerJoined { account_id }).await,
+                        Err(e) => Err(e),
+                    }
+                }
+                Ok(ServerEvent::PlayerLeft { account_id }) => conn.hdel(&presence_key, account_id).await,
+                Ok(_) => Ok(()),
+                Err(RecvError::Lagged(count)) => {
+                    warn!("cluster sync is too slow to keep up, {count} events were skipped");
+                    Ok(())
+                }
+                Err(RecvError::Closed) => break,
+            },
+
+            _ = presence_refresh.tick() => {
+                let res = refresh_presence(&mut conn, &presence_key, game_server, resync_presence).await;
+                resync_presence = res.is_err();
+                res
+            }
+        };
+
+        if let Err(e) = result {
+            warn!("failed to sync with the cluster: {e}");
+            resync_presence = true;
+        }
+    }
+}
+
+/// Keeps the list of players online on this server from expiring. If `resync` is set or the list is gone
+/// (after Redis restarted, or the connection was down for longer than the TTL), it's written again from scratch.
+async fn refresh_presence(conn: &mut ConnectionManager, key: &str, game_server: &GameServer, resync: bool) -> redis::RedisResult<()> {
+    if !resync {
+        let exists: bool = conn.expire(key, PRESENCE_TTL_SECS).await?;
+        if exists {
+            return Ok(());
+        }
+    }
+
+    let players = game_server
+        .clients
+        .lock()
+        .values()
+        .filter(|thr| thr.authenticated())
+        .map(|thr| (thr.account_id.load(Ordering::Relaxed), thr.account_data.lock().name.try_to_string()))
+        .collect::<Vec<_>>();
+
+    let mut pipe = redis::pipe();
+    pipe.atomic().del(key).ignore();
+
+    if !players.is_empty() {
+        pipe.hset_multiple(key, &players).ignore().expire(key, PRESENCE_TTL_SECS).ignore();
+    }
+
+    pipe.query_async(conn).await
+}
+
+async fn publish(conn: &mut ConnectionManager, instance: &str, message: ClusterMessage) -> redis::RedisResult<()> {
+    let envelope = Envelope {
+        instance: instance.to_owned(),
+        message,
+    };
+
+    let payload = match serde_json::to_string(&envelope) {
+        Ok(x) => x,
+        Err(e) => {
+            warn!("failed to encode a cluster message: {e}");
+            return Ok(());
+        }
+    };
+
+    conn.publish(CHANNEL, payload).await
+}
+
+/// Receives messages from other servers, reconnecting whenever the connection is lost.
+async fn run_subscriber(game_server: &'static GameServer, client: Client, instance: String) {
+    loop {
+        if let Err(e) = subscribe(game_server, &client, &instance).await {
+            warn!("lost connection to Redis: {e}");
+        }
+
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn subscribe(game_server: &'static GameServer, client: &Client, instance: &str) -> anyhow::Result<()> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(CHANNEL).await?;
+
+    let mut messages = pubsub.on_message();
+
+    while let Some(message) = messages.next().await {
+        let payload: String = message.get_payload()?;
+
+        let envelope: Envelope = match serde_json::from_str(&payload) {
+            Ok(x) => x,
+            Err(e) => {
+                warn!("received an invalid cluster message: {e}");
+                continue;
+            }
+        };
+
+        if envelope.instance != instance {
+            handle_message(game_server, envelope.message).await;
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_message(game_server: &'static GameServer, message: ClusterMessage) {
+    match message {
+        ClusterMessage::Announcement { message } => {
+            let threads = game_server
+                .clients
+                .lock()
+                .values()
+                .filter(|thr| thr.authenticated())
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let packet = ServerNoticePacket {
+                message: FastString::new(&message),
+            };
+
+            for thread in threads {
+                thread.push_new_message(ServerThreadMessage::BroadcastNotice(packet.clone())).await;
+            }
+        }
+
+        ClusterMessage::Sanction {
+            account_id,
+            is_banned,
+            is_muted,
+            reason,
+            expiry,
+        } => {
+            let Some(thread) = game_server.get_user_by_id(account_id) else {
+                return;
+            };
+
+            let (was_banned, was_muted) = {
+                let mut user_entry = thread.user_entry.lock();
+                let previous = (user_entry.is_banned, user_entry.is_muted);
+
+                user_entry.is_banned = is_banned;
+                user_entry.is_muted = is_muted;
+                user_entry.violation_reason.clone_from(&reason);
+                user_entry.violation_expiry = expiry;
+
+                previous
+            };
+
+            let reason = FastString::new(reason.as_deref().unwrap_or_default());
+
+            if is_banned && !was_banned {
+                thread
+                    .push_new_message(ServerThreadMessage::BroadcastBan(ServerBannedPacket {
+                        message: reason,
+                        timestamp: expiry.unwrap_or(0),
+                    }))
+                    .await;
+            } else if is_muted && !was_muted {
+                thread
+                    .push_new_message(ServerThreadMessage::BroadcastMute(ServerMutedPacket {
+                        reason,
+                        timestamp: expiry.unwrap_or(0),
+                    }))
+                    .await;
+            }
+        }
+
+        ClusterMessage::PlayerJoined { account_id } => {
+            if let Some(thread) = game_server.get_user_by_id(account_id) {
+                thread
+                    .push_new_message(ServerThreadMessage::TerminationNotice(
+                        DisconnectReason::DuplicateLogin,
+                        FastString::new("You logged in from another server."),
+                    ))
+                    .await;
+            }
+        }
+
+        ClusterMessage::SessionLogout { account_id, session_id } => {
+            game_server.logout_session(account_id, session_id).await;
+        }
+    }
+}
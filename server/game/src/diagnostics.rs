@@ -0,0 +1,204 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::Ordering,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use globed_shared::IntMap;
+use serde_json::json;
+
+use crate::{
+    data::LevelId,
+    managers::{is_bot_account, Room},
+    server::GameServer,
+    tokio,
+};
+
+/// Basic info about a player thread, collected so that we don't hold the thread map locked while inspecting rooms.
+struct ThreadSnapshot {
+    account_id: i32,
+    room_id: u32,
+    level_id: LevelId,
+    authorized: bool,
+}
+
+impl GameServer {
+    fn snapshot_threads(&self) -> Vec<ThreadSnapshot> {
+        let mut threads: Vec<_> = self
+            .clients
+            .lock()
+            .values()
+            .filter(|thr| thr.authenticated())
+            .map(|thr| ThreadSnapshot {
+                account_id: thr.account_id.load(Ordering::Relaxed),
+                room_id: thr.room_id.load(Ordering::Relaxed),
+                level_id: thr.level_id.load(Ordering::Relaxed),
+                authorized: true,
+            })
+            .collect();
+
+        // disconnected threads that are waiting to be recovered still hold their place in rooms
+        threads.extend(
+            self.unauthorized_clients
+                .lock()
+                .iter()
+                .filter(|thr| thr.account_id.load(Ordering::Relaxed) != 0)
+                .map(|thr| ThreadSnapshot {
+                    account_id: thr.account_id.load(Ordering::Relaxed),
+                    room_id: thr.room_id.load(Ordering::Relaxed),
+                    level_id: thr.level_id.load(Ordering::Relaxed),
+                    authorized: false,
+                }),
+        );
+
+        threads
+    }
+
+    /// Validates that the thread maps, the room manager and the player count all agree with each other.
+    /// Returns a list of human readable descriptions of violated invariants, empty if everything is fine.
+    pub fn check_invariants(&self) -> Vec<String> {
+        let threads = self.snapshot_threads();
+        let mut violations = Vec::new();
+
+        let player_count = self.state.get_player_count() as usize;
+        if player_count != threads.len() {
+            violations.push(format!(
+                "player count is {player_count}, but there are {} authenticated threads",
+                threads.len()
+            ));
+        }
+
+        // with concurrent sessions allowed, some accounts can legitimately have more than one thread
+        let concurrent_allowed = {
+            let policy = &self.bridge.central_conf.lock().duplicate_login_policy;
+            policy.max_concurrent_sessions > 1 && !policy.concurrent_roles.is_empty()
+        };
+
+        let mut seen = IntMap::<i32, u32>::default();
+
+        for thread in &threads {
+            if let Some(other_room) = seen.insert(thread.account_id, thread.room_id).filter(|_| !concurrent_allowed) {
+                violations.push(format!(
+                    "account {} has more than one thread (rooms {} and {})",
+                    thread.account_id, other_room, thread.room_id
+                ));
+            }
+
+            if thread.room_id != 0 && !self.state.room_manager.is_valid_room(thread.room_id) {
+                violations.push(format!(
+                    "account {} is in a room that does not exist ({})",
+                    thread.account_id, thread.room_id
+                ));
+                continue;
+            }
+
+            self.state.room_manager.with_any(thread.room_id, |room| {
+                if room.manager.get_player_data(thread.account_id).is_none() {
+                    violations.push(format!(
+                        "account {} is missing from the player list of room {}",
+                        thread.account_id, thread.room_id
+                    ));
+                }
+
+                if thread.level_id != 0 && !room.manager.get_level(thread.level_id).is_some_and(|x| x.contains(&thread.account_id)) {
+                    violations.push(format!(
+                        "account {} is missing from level {} in room {}",
+                        thread.account_id, thread.level_id, thread.room_id
+                    ));
+                }
+            });
+        }
+
+        // now the other way around, check for players in rooms that don't belong to any thread (bots never have one)
+        let mut check_room = |room_id: u32, room: &Room| {
+            for account_id in room.manager.players.keys() {
+                if !is_bot_account(*account_id) && seen.get(account_id) != Some(&room_id) {
+                    violations.push(format!("room {room_id} contains account {account_id}, which has no thread in that room"));
+                }
+            }
+
+            for (level_id, players) in &room.manager.levels {
+                for account_id in players {
+                    if !room.manager.players.contains_key(account_id) {
+                        violations.push(format!(
+                            "level {level_id} in room {room_id} contains account {account_id}, which is not in the room"
+                        ));
+                    }
+                }
+            }
+        };
+
+        check_room(0, &self.state.room_manager.get_global());
+
+        for (room_id, room) in self.state.room_manager.get_rooms().iter() {
+            check_room(*room_id, room);
+        }
+
+        violations
+    }
+
+    /// Writes a snapshot of the server state into a JSON file inside `dir`, returns the path of the created file.
+    /// Names, addresses, passwords and other user data are left out, so the file can be attached to bug reports.
+    pub async fn write_state_dump(&self, dir: &Path) -> std::io::Result<PathBuf> {
+        let threads = self.snapshot_threads();
+        let violations = self.check_invariants();
+
+        let room_to_json = |room_id: u32, room: &Room| {
+            json!({
+                "id": room_id,
+                "owner": room.owner,
+                "protected": room.is_protected(),
+                "hidden": room.is_hidden(),
+                "player_limit": room.settings.player_limit,
+                "players": room.manager.players.keys().collect::<Vec<_>>(),
+                "levels": room
+                    .manager
+                    .levels
+                    .iter()
+                    .map(|(level_id, players)| json!({ "id": level_id, "players": players }))
+                    .collect::<Vec<_>>(),
+            })
+        };
+
+        let mut rooms = vec![room_to_json(0, &self.state.room_manager.get_global())];
+        rooms.extend(self.state.room_manager.get_rooms().iter().map(|(id, room)| room_to_json(*id, room)));
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_secs()).unwrap_or(0);
+
+        let dump = json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "timestamp": timestamp,
+            "standalone": self.standalone,
+            "player_count": self.state.get_player_count(),
+            "client_threads": self.clients.lock().len(),
+            "unauthorized_threads": self.unauthorized_clients.lock().len(),
+            "unclaimed_threads": self.unclaimed_threads.lock().len(),
+            "stale_evictions": self.stale_evictions.load(Ordering::Relaxed),
+            "pending_rejections": self.pending_rejections.load(Ordering::Relaxed),
+            "banned_rejections": self.banned_rejections.load(Ordering::Relaxed),
+            "rate_limit_rejections": self.rate_limit_rejections.load(Ordering::Relaxed),
+            "permission_violations": self.permission_violations.load(Ordering::Relaxed),
+            "flood_disconnects": self.flood_disconnects.load(Ordering::Relaxed),
+            "buffered_fragment_bytes": self.fragments.lock().buffered_size(),
+            "threads": threads
+                .iter()
+                .map(|thr| json!({
+                    "account_id": thr.account_id,
+                    "room_id": thr.room_id,
+                    "level_id": thr.level_id,
+                    "connected": thr.authorized,
+                }))
+                .collect::<Vec<_>>(),
+            "rooms": rooms,
+            "invariant_violations": violations,
+        });
+
+        let data = serde_json::to_vec_pretty(&dump)?;
+
+        tokio::fs::create_dir_all(dir).await?;
+        let path = dir.join(format!("state-dump-{timestamp}.json"));
+        tokio::fs::write(&path, data).await?;
+
+        Ok(path)
+    }
+}
@@ -0,0 +1,73 @@
+//! Server-wide event bus. Anything that wants to react to things happening on the server (like plugins) can subscribe to it,
+//! without the emitting code having to know about any of the subscribers.
+
+use serde::Serialize;
+
+use crate::{data::LevelId, tokio::sync::broadcast};
+
+/// How many events can be buffered for a slow subscriber before it starts missing them.
+const EVENT_BUFFER_SIZE: usize = 1024;
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    PlayerJoined {
+        account_id: i32,
+        name: String,
+    },
+    PlayerLeft {
+        account_id: i32,
+    },
+    ChatMessage {
+        account_id: i32,
+        room_id: u32,
+        level_id: LevelId,
+        message: String,
+    },
+    RoomCreated {
+        room_id: u32,
+        owner: i32,
+        name: String,
+    },
+    /// the player moved to a different room or level
+    PresenceChanged {
+        account_id: i32,
+        room_id: u32,
+        level_id: LevelId,
+    },
+    RoomCountdownStarted {
+        account_id: i32,
+        room_id: u32,
+        level_id: LevelId,
+        seconds: u8,
+    },
+}
+
+pub struct EventBus {
+    sender: broadcast::Sender<ServerEvent>,
+}
+
+impl EventBus {
+    /// Sends the event to every subscriber. The closure is only invoked if there is anyone listening,
+    /// so that building the event costs nothing when the server has no subscribers.
+    pub fn emit(&self, event: impl FnOnce() -> ServerEvent) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+
+        // this only fails if every subscriber is gone in the meantime
+        let _ = self.sender.send(event());
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ServerEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(EVENT_BUFFER_SIZE).0,
+        }
+    }
+}
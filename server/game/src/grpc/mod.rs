@@ -0,0 +1,26 @@
+//! gRPC admin and integration API, for external panels and bots. Only functional when the server is built with the `grpc` feature.
+
+#[cfg(feature = "grpc")]
+mod service;
+
+use crate::server::GameServer;
+
+/// Starts the gRPC server on the address in `GLOBED_GS_GRPC_ADDRESS`. Does nothing if the variable is not set.
+pub fn start(game_server: &'static GameServer) {
+    let Ok(address) = std::env::var("GLOBED_GS_GRPC_ADDRESS") else {
+        return;
+    };
+
+    #[cfg(feature = "grpc")]
+    crate::tokio::spawn(async move {
+        if let Err(e) = service::run(game_server, &address).await {
+            globed_shared::error!("failed to run the gRPC server: {e}");
+        }
+    });
+
+    #[cfg(not(feature = "grpc"))]
+    {
+        let _ = (game_server, address);
+        globed_shared::warn!("GLOBED_GS_GRPC_ADDRESS is set, but the server was built without the `grpc` feature, ignoring");
+    }
+}
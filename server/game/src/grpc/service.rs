@@ -0,0 +1,357 @@
+use std::{net::SocketAddr, pin::Pin, sync::atomic::Ordering};
+
+use globed_shared::{
+    anyhow::{self, bail},
+    info, warn,
+};
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    Stream, StreamExt,
+};
+use tonic::{
+    transport::{Certificate, Identity, Server, ServerTlsConfig},
+    Request, Response, Status,
+};
+
+use crate::{
+    client::{ClientThread, ServerThreadMessage},
+    data::{ClientPlatform, DisconnectReason, FastString, ServerNoticePacket},
+    events::ServerEvent,
+    managers::Room,
+    server::GameServer,
+};
+
+#[allow(clippy::all, clippy::pedantic)]
+mod proto {
+    tonic::include_proto!("globed.admin.v1");
+}
+
+use proto::{
+    game_server_admin_server::{GameServerAdmin, GameServerAdminServer},
+    *,
+};
+
+struct AdminService {
+    game_server: &'static GameServer,
+}
+
+pub async fn run(game_server: &'static GameServer, address: &str) -> anyhow::Result<()> {
+    let address: SocketAddr = address.parse()?;
+    let tls = load_tls_config()?;
+
+    // without mTLS anyone who can reach the port has full admin access
+    if tls.is_none() && !address.ip().is_loopback() {
+        bail!(
+            "refusing to listen on {address} without mTLS, either use a loopback address or set GLOBED_GS_GRPC_TLS_CERT, GLOBED_GS_GRPC_TLS_KEY and GLOBED_GS_GRPC_TLS_CLIENT_CA"
+        );
+    }
+
+    let mut builder = Server::builder();
+
+    if let Some(tls) = tls {
+        builder = builder.tls_config(tls)?;
+        info!("gRPC API listening on {address} (mTLS)");
+    } else {
+        info!("gRPC API listening on {address}");
+    }
+
+    builder
+        .add_service(GameServerAdminServer::new(AdminService { game_server }))
+        .serve(address)
+        .await?;
+
+    Ok(())
+}
+
+/// Loads the server identity and the CA used to verify clients. Returns `None` if none of the variables are set.
+fn load_tls_config() -> anyhow::Result<Option<ServerTlsConfig>> {
+    let cert = std::env::var("GLOBED_GS_GRPC_TLS_CERT").ok();
+    let key = std::env::var("GLOBED_GS_GRPC_TLS_KEY").ok();
+    let client_ca = std::env::var("GLOBED_GS_GRPC_TLS_CLIENT_CA").ok();
+
+    match (cert, key, client_ca) {
+        (None, None, None) => Ok(None),
+        (Some(cert), Some(key), Some(client_ca)) => {
+            let identity = Identity::from_pem(std::fs::read(cert)?, std::fs::read(key)?);
+            let client_ca = Certificate::from_pem(std::fs::read(client_ca)?);
+
+            // setting a client CA makes client certificates mandatory
+            Ok(Some(ServerTlsConfig::new().identity(identity).client_ca_root(client_ca)))
+        }
+        _ => bail!("GLOBED_GS_GRPC_TLS_CERT, GLOBED_GS_GRPC_TLS_KEY and GLOBED_GS_GRPC_TLS_CLIENT_CA must be set together"),
+    }
+}
+
+impl AdminService {
+    fn authenticated_threads(&self) -> Vec<std::sync::Arc<ClientThread>> {
+        self.game_server
+            .clients
+            .lock()
+            .values()
+            .filter(|thr| thr.authenticated())
+            .cloned()
+            .collect()
+    }
+}
+
+fn player_from_thread(thread: &ClientThread) -> Player {
+    let account_data = thread.account_data.lock();
+
+    Player {
+        account_id: account_data.account_id,
+        user_id: account_data.user_id,
+        name: account_data.name.try_to_string(),
+        room_id: thread.room_id.load(Ordering::Relaxed),
+        level_id: thread.level_id.load(Ordering::Relaxed),
+    }
+}
+
+fn room_summary(room_id: u32, room: &Room) -> proto::Room {
+    proto::Room {
+        id: room_id,
+        owner: room.owner,
+        name: room.name.try_to_string(),
+        player_count: room.manager.players.len() as u32,
+        player_limit: room.settings.player_limit,
+        protected: room.is_protected(),
+        hidden: room.is_hidden(),
+    }
+}
+
+fn event_to_proto(event: ServerEvent) -> Event {
+    let event = match event {
+        ServerEvent::PlayerJoined { account_id, name } => event::Event::PlayerJoined(PlayerJoined { account_id, name }),
+        ServerEvent::PlayerLeft { account_id } => event::Event::PlayerLeft(PlayerLeft { account_id }),
+        ServerEvent::ChatMessage {
+            account_id,
+            room_id,
+            level_id,
+            message,
+        } => event::Event::ChatMessage(ChatMessage {
+            account_id,
+            room_id,
+            level_id,
+            message,
+        }),
+        ServerEvent::RoomCreated { room_id, owner, name } => event::Event::RoomCreated(RoomCreated { room_id, owner, name }),
+        ServerEvent::PresenceChanged {
+            account_id,
+            room_id,
+            level_id,
+        } => event::Event::PresenceChanged(PresenceChanged {
+            account_id,
+            room_id,
+            level_id,
+        }),
+        ServerEvent::RoomCountdownStarted {
+            account_id,
+            room_id,
+            level_id,
+            seconds,
+        } => event::Event::RoomCountdownStarted(RoomCountdownStarted {
+            account_id,
+            room_id,
+            level_id,
+            seconds: u32::from(seconds),
+        }),
+    };
+
+    Event { event: Some(event) }
+}
+
+#[tonic::async_trait]
+impl GameServerAdmin for AdminService {
+    type SubscribeEventsStream = Pin<Box<dyn Stream<Item = Result<Event, Status>> + Send>>;
+
+    async fn list_players(&self, _request: Request<ListPlayersRequest>) -> Result<Response<ListPlayersResponse>, Status> {
+        let players = self.authenticated_threads().iter().map(|thr| player_from_thread(thr)).collect();
+
+        Ok(Response::new(ListPlayersResponse { players }))
+    }
+
+    async fn kick(&self, request: Request<KickRequest>) -> Result<Response<KickResponse>, Status> {
+        let request = request.into_inner();
+
+        let Some(thread) = self.game_server.get_user_by_id(request.account_id) else {
+            return Err(Status::not_found("player is not online"));
+        };
+
+        // an empty reason is fine, the client already tells the user that they were kicked
+        info!("[gRPC] kicking {} ({})", request.account_id, request.reason);
+
+        thread
+            .push_new_message(ServerThreadMessage::TerminationNotice(
+                DisconnectReason::Kicked,
+                FastString::new(&request.reason),
+            ))
+            .await;
+
+        Ok(Response::new(KickResponse {}))
+    }
+
+    async fn broadcast(&self, request: Request<BroadcastRequest>) -> Result<Response<BroadcastResponse>, Status> {
+        let request = request.into_inner();
+
+        if request.message.is_empty() {
+            return Err(Status::invalid_argument("message must not be empty"));
+        }
+
+        let threads = self
+            .authenticated_threads()
+            .into_iter()
+            .filter(|thr| request.room_id.is_none_or(|room_id| thr.room_id.load(Ordering::Relaxed) == room_id))
+            .collect::<Vec<_>>();
+
+        let packet = ServerNoticePacket {
+            message: FastString::new(&request.message),
+        };
+
+        for thread in &threads {
+            thread.push_new_message(ServerThreadMessage::BroadcastNotice(packet.clone())).await;
+        }
+
+        Ok(Response::new(BroadcastResponse { sent: threads.len() as u32 }))
+    }
+
+    async fn transfer(&self, request: Request<TransferRequest>) -> Result<Response<TransferResponse>, Status> {
+        let request = request.into_inner();
+
+        if request.address.is_empty() {
+            return Err(Status::invalid_argument("address must not be empty"));
+        }
+
+        let threads = self
+            .authenticated_threads()
+            .into_iter()
+            .filter(|thr| {
+                request
+                    .account_id
+                    .is_none_or(|account_id| thr.account_id.load(Ordering::Relaxed) == account_id)
+            })
+            .filter(|thr| request.room_id.is_none_or(|room_id| thr.room_id.load(Ordering::Relaxed) == room_id))
+            .collect::<Vec<_>>();
+
+        info!("[gRPC] moving {} players to {}", threads.len(), request.address);
+
+        let transferred = self
+            .game_server
+            .transfer_players(&threads, &request.address)
+            .await
+            .map_err(|err| Status::failed_precondition(err.to_string()))?;
+
+        Ok(Response::new(TransferResponse {
+            transferred: transferred as u32,
+        }))
+    }
+
+    async fn list_rooms(&self, _request: Request<ListRoomsRequest>) -> Result<Response<ListRoomsResponse>, Status> {
+        let room_manager = &self.game_server.state.room_manager;
+
+        let mut rooms = vec![room_summary(0, &room_manager.get_global())];
+        rooms.extend(room_manager.get_rooms().iter().map(|(id, room)| room_summary(*id, room)));
+
+        Ok(Response::new(ListRoomsResponse { rooms }))
+    }
+
+    async fn get_room(&self, request: Request<GetRoomRequest>) -> Result<Response<RoomDetails>, Status> {
+        let room_id = request.into_inner().room_id;
+
+        let details = self.game_server.state.room_manager.try_with_any(
+            room_id,
+            |room| {
+                Some(RoomDetails {
+                    room: Some(room_summary(room_id, room)),
+                    players: room.manager.players.keys().copied().collect(),
+                    levels: room
+                        .manager
+                        .levels
+                        .iter()
+                        .map(|(level_id, players)| Level {
+                            level_id: *level_id,
+                            players: players.clone(),
+                        })
+                        .collect(),
+                })
+            },
+            || None,
+        );
+
+        details.map(Response::new).ok_or_else(|| Status::not_found("room does not exist"))
+    }
+
+    async fn get_metrics(&self, request: Request<GetMetricsRequest>) -> Result<Response<Metrics>, Status> {
+        let game_server = self.game_server;
+        let usage = game_server.state.usage_stats.get();
+
+        let history_since = request.into_inner().history_since;
+        let player_count_history = if history_since == 0 {
+            Vec::new()
+        } else {
+            game_server
+                .state
+                .player_history
+                .samples_since(history_since)
+                .into_iter()
+                .map(|sample| PlayerCountSample {
+                    timestamp: sample.timestamp,
+                    total: sample.total,
+                    rooms: sample.rooms.into_iter().collect(),
+                    levels: sample.levels.into_iter().collect(),
+                })
+                .collect()
+        };
+
+        // every lock has to be released before the next one is taken, temporaries in the struct expression below
+        // would live until the end of it, and taking rooms while holding clients deadlocks with the broadcasts
+        let client_threads = game_server.clients.lock().len() as u32;
+        let unauthorized_threads = game_server.unauthorized_clients.lock().len() as u32;
+        let unclaimed_threads = game_server.unclaimed_threads.lock().len() as u32;
+        let room_count = game_server.state.room_manager.get_rooms().len() as u32;
+        let players_per_platform = game_server.count_players_per_platform();
+
+        Ok(Response::new(Metrics {
+            player_count: game_server.state.get_player_count(),
+            client_threads,
+            unauthorized_threads,
+            unclaimed_threads,
+            room_count,
+            stale_evictions: game_server.stale_evictions.load(Ordering::Relaxed),
+            pending_rejections: game_server.pending_rejections.load(Ordering::Relaxed),
+            permission_violations: game_server.permission_violations.load(Ordering::Relaxed),
+            flood_disconnects: game_server.flood_disconnects.load(Ordering::Relaxed),
+            address_blocks: game_server.state.rate_limiter.block_count(),
+            rate_limit_rejections: game_server.rate_limit_rejections.load(Ordering::Relaxed),
+            message_drops: game_server
+                .message_drops
+                .get()
+                .into_iter()
+                .map(|(name, count)| (name.to_owned(), count))
+                .collect(),
+            players_per_platform: ClientPlatform::ALL
+                .iter()
+                .zip(players_per_platform)
+                .map(|(platform, count)| (platform.name().to_owned(), count as u32))
+                .collect(),
+            logins_per_platform: usage.logins.into_iter().map(|(name, count)| (name.to_owned(), count)).collect(),
+            feature_usage: Some(FeatureUsage {
+                reports: usage.reports,
+                voice_enabled: usage.voice_enabled,
+                platforms: usage.platforms.into_iter().map(|(name, count)| (name.to_owned(), count)).collect(),
+                changed_settings: usage.changed_settings.into_iter().map(|(name, count)| (name.to_owned(), count)).collect(),
+            }),
+            player_count_history,
+        }))
+    }
+
+    async fn subscribe_events(&self, _request: Request<SubscribeEventsRequest>) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        let stream = BroadcastStream::new(self.game_server.state.events.subscribe()).filter_map(|event| match event {
+            Ok(event) => Some(Ok(event_to_proto(event))),
+            Err(BroadcastStreamRecvError::Lagged(count)) => {
+                warn!("gRPC event subscriber is too slow to keep up, {count} events were skipped");
+                None
+            }
+        });
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
@@ -0,0 +1,27 @@
+//! Token-authenticated HTTP admin API, for server owners building their own tooling.
+//! Only functional when the server is built with the `http_api` feature.
+
+#[cfg(feature = "http_api")]
+mod service;
+
+use crate::server::GameServer;
+
+/// Starts the HTTP API on the address in `GLOBED_GS_HTTP_ADDRESS`. Does nothing if the variable is not set.
+pub fn start(game_server: &'static GameServer) {
+    let Ok(address) = std::env::var("GLOBED_GS_HTTP_ADDRESS") else {
+        return;
+    };
+
+    #[cfg(feature = "http_api")]
+    crate::tokio::spawn(async move {
+        if let Err(e) = service::run(game_server, &address).await {
+            globed_shared::error!("failed to run the HTTP API: {e}");
+        }
+    });
+
+    #[cfg(not(feature = "http_api"))]
+    {
+        let _ = (game_server, address);
+        globed_shared::warn!("GLOBED_GS_HTTP_ADDRESS is set, but the server was built without the `http_api` feature, ignoring");
+    }
+}
@@ -0,0 +1,523 @@
+use std::{
+    net::SocketAddr,
+    sync::{atomic::Ordering, Arc},
+};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use globed_shared::{
+    anyhow::{self, bail},
+    constant_time_eq, info, unix_timestamp, warn, Secret,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::{thread::handlers::admin::AdminPerm, ClientThread, ServerThreadMessage},
+    cluster::ClusterMessage,
+    data::{DisconnectReason, FastString, ServerBannedPacket, ServerNoticePacket, UserEntry, MAX_NOTICE_SIZE},
+    server::GameServer,
+    tokio::net::TcpListener,
+    webhook::{BanMuteStateChange, WebhookMessage},
+};
+
+/// A token that can be used to access the API, it has the permissions of the role it was given.
+struct ApiToken {
+    role: String,
+    token: Secret,
+}
+
+struct ApiState {
+    game_server: &'static GameServer,
+    tokens: Vec<ApiToken>,
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self(status, message.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        #[derive(Serialize)]
+        struct Body {
+            error: String,
+        }
+
+        (self.0, Json(Body { error: self.1 })).into_response()
+    }
+}
+
+pub async fn run(game_server: &'static GameServer, address: &str) -> anyhow::Result<()> {
+    let address: SocketAddr = address.parse()?;
+    let tokens = load_tokens()?;
+
+    let roles = tokens.iter().map(|x| x.role.clone()).collect::<Vec<_>>();
+    if !game_server.state.role_manager.all_valid(&roles) {
+        warn!("some of the roles in GLOBED_GS_HTTP_TOKENS don't exist, their tokens have no permissions until they are added");
+    }
+
+    // the tokens are sent with every request, so they shouldn't go over the network unencrypted
+    if !address.ip().is_loopback() {
+        warn!("the HTTP API is listening on {address}, make sure it is only reachable through a reverse proxy with TLS");
+    }
+
+    let state = Arc::new(ApiState { game_server, tokens });
+
+    let app = Router::new()
+        .route("/status", get(server_status))
+        .route("/players", get(list_players))
+        .route("/players/:account_id/kick", post(kick_player))
+        .route("/players/:account_id/ban", post(ban_player))
+        .route("/notice", post(send_notice))
+        .route("/rooms/:room_id/close", post(close_room))
+        .with_state(state);
+
+    let listener = TcpListener::bind(address).await?;
+    info!("HTTP API listening on {address}");
+
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+/// Reads the tokens from `GLOBED_GS_HTTP_TOKENS`, which are `role:token` pairs separated by whitespace or commas.
+/// The variable can also reference a file or another environment variable, see `Secret::resolve`.
+fn load_tokens() -> anyhow::Result<Vec<ApiToken>> {
+    let Ok(spec) = std::env::var("GLOBED_GS_HTTP_TOKENS") else {
+        bail!("GLOBED_GS_HTTP_TOKENS must be set when using the HTTP API");
+    };
+
+    let value = Secret::resolve(&spec).map_err(anyhow::Error::msg)?;
+
+    let mut tokens = Vec::new();
+    for entry in value.expose().split(|c: char| c.is_whitespace() || c == ',').filter(|x| !x.is_empty()) {
+        match entry.split_once(':') {
+            Some((role, token)) if !role.is_empty() && !token.is_empty() => tokens.push(ApiToken {
+                role: role.to_owned(),
+                token: Secret::new(token),
+            }),
+            _ => bail!("invalid entry in GLOBED_GS_HTTP_TOKENS, expected every token to be in the `role:token` format"),
+        }
+    }
+
+    if tokens.is_empty() {
+        bail!("GLOBED_GS_HTTP_TOKENS does not contain any tokens");
+    }
+
+    Ok(tokens)
+}
+
+impl ApiState {
+    /// Checks the bearer token of the request and whether its role has the given permission,
+    /// returns the name that the action is logged under.
+    fn authorize(&self, headers: &HeaderMap, perm: AdminPerm) -> ApiResult<String> {
+        let token = headers
+            .get(AUTHORIZATION)
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| x.strip_prefix("Bearer "))
+            .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "missing bearer token"))?;
+
+        // compare against every token, so the time taken doesn't reveal which one was close
+        let found = self.tokens.iter().fold(None, |found, x| {
+            if constant_time_eq(x.token.expose().as_bytes(), token.as_bytes()) {
+                Some(x)
+            } else {
+                found
+            }
+        });
+
+        let Some(found) = found else {
+            return Err(ApiError::new(StatusCode::UNAUTHORIZED, "invalid token"));
+        };
+
+        // roles can change whenever the configuration is refreshed, so this is computed every time
+        let role = self.game_server.state.role_manager.compute(std::slice::from_ref(&found.role));
+        if !perm.is_granted(&role) {
+            return Err(ApiError::new(
+                StatusCode::FORBIDDEN,
+                "the role of this token doesn't have the permission for this",
+            ));
+        }
+
+        Ok(format!("HTTP API ({})", found.role))
+    }
+
+    fn authenticated_threads(&self) -> Vec<Arc<ClientThread>> {
+        self.game_server
+            .clients
+            .lock()
+            .values()
+            .filter(|thr| thr.authenticated())
+            .cloned()
+            .collect()
+    }
+
+    fn online_player(&self, account_id: i32) -> ApiResult<Arc<ClientThread>> {
+        self.game_server
+            .get_user_by_id(account_id)
+            .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "player is not online"))
+    }
+
+    async fn send_webhook(&self, message: WebhookMessage) {
+        if !self.game_server.bridge.has_webhook() {
+            return;
+        }
+
+        if let Err(err) = self.game_server.bridge.send_webhook_message(message).await {
+            warn!("webhook error: {err}");
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Player {
+    account_id: i32,
+    user_id: i32,
+    name: String,
+    room_id: u32,
+    level_id: i64,
+}
+
+async fn list_players(State(state): State<Arc<ApiState>>, headers: HeaderMap) -> ApiResult<Json<Vec<Player>>> {
+    state.authorize(&headers, AdminPerm::Any)?;
+
+    let players = state
+        .authenticated_threads()
+        .iter()
+        .map(|thread| {
+            let account_data = thread.account_data.lock();
+
+            Player {
+                account_id: account_data.account_id,
+                user_id: account_data.user_id,
+                name: account_data.name.try_to_string(),
+                room_id: thread.room_id.load(Ordering::Relaxed),
+                level_id: thread.level_id.load(Ordering::Relaxed),
+            }
+        })
+        .collect();
+
+    Ok(Json(players))
+}
+
+#[derive(Deserialize)]
+struct StatusQuery {
+    /// only include player count samples recorded after this time (seconds since unix epoch)
+    #[serde(default)]
+    since: i64,
+}
+
+#[derive(Serialize)]
+struct PlayerCountSample {
+    timestamp: i64,
+    total: u32,
+    rooms: Vec<(u32, u32)>,
+    levels: Vec<(i64, u32)>,
+}
+
+#[derive(Serialize)]
+struct ServerStatus {
+    player_count: u32,
+    room_count: usize,
+    player_count_history: Vec<PlayerCountSample>,
+}
+
+async fn server_status(State(state): State<Arc<ApiState>>, headers: HeaderMap, Query(query): Query<StatusQuery>) -> ApiResult<Json<ServerStatus>> {
+    state.authorize(&headers, AdminPerm::Any)?;
+
+    let game_server = state.game_server;
+    let player_count_history = game_server
+        .state
+        .player_history
+        .samples_since(query.since)
+        .into_iter()
+        .map(|sample| PlayerCountSample {
+            timestamp: sample.timestamp,
+            total: sample.total,
+            rooms: sample.rooms,
+            levels: sample.levels,
+        })
+        .collect();
+
+    Ok(Json(ServerStatus {
+        player_count: game_server.state.get_player_count(),
+        room_count: game_server.state.room_manager.get_rooms().len(),
+        player_count_history,
+    }))
+}
+
+/// Reasons are shown to the player the same way as notices, so they have the same length limit.
+fn check_reason(reason: &str) -> ApiResult<()> {
+    if reason.len() > MAX_NOTICE_SIZE {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("reason can be at most {MAX_NOTICE_SIZE} bytes long"),
+        ));
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct KickRequest {
+    /// shown to the player, the client already tells them that they were kicked if empty
+    #[serde(default)]
+    reason: String,
+}
+
+async fn kick_player(
+    State(state): State<Arc<ApiState>>,
+    headers: HeaderMap,
+    Path(account_id): Path<i32>,
+    Json(request): Json<KickRequest>,
+) -> ApiResult<StatusCode> {
+    let mod_name = state.authorize(&headers, AdminPerm::Kick)?;
+    check_reason(&request.reason)?;
+    let thread = state.online_player(account_id)?;
+
+    let target_name = thread.account_data.lock().name.try_to_string();
+    info!("[{mod_name}] kicking {target_name} ({account_id}): {}", request.reason);
+
+    thread
+        .push_new_message(ServerThreadMessage::TerminationNotice(
+            DisconnectReason::Kicked,
+            FastString::new(&request.reason),
+        ))
+        .await;
+
+    state
+        .send_webhook(WebhookMessage::KickPerson(mod_name, target_name, account_id, request.reason))
+        .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct BanRequest {
+    #[serde(default)]
+    reason: Option<String>,
+    /// when the ban ends, in seconds since the unix epoch, permanent if not set
+    #[serde(default)]
+    expiry: Option<i64>,
+}
+
+async fn ban_player(
+    State(state): State<Arc<ApiState>>,
+    headers: HeaderMap,
+    Path(account_id): Path<i32>,
+    Json(request): Json<BanRequest>,
+) -> ApiResult<StatusCode> {
+    let mod_name = state.authorize(&headers, AdminPerm::Ban)?;
+    let game_server = state.game_server;
+
+    if !game_server.has_user_data() {
+        return Err(ApiError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "This cannot be done on a standalone server without a database",
+        ));
+    }
+
+    if request.expiry.is_some_and(|expiry| expiry <= unix_timestamp()) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "the expiry is in the past"));
+    }
+
+    check_reason(request.reason.as_deref().unwrap_or_default())?;
+
+    let ban = |user: &mut UserEntry| {
+        // every new ban counts towards the violation count, which lowers the trust tier
+        if !user.is_banned {
+            user.violation_count = user.violation_count.saturating_add(1);
+        }
+
+        user.is_banned = true;
+        user.violation_reason.clone_from(&request.reason);
+        user.violation_expiry = request.expiry;
+    };
+
+    // if they are online, update them live, else change their saved data
+    let thread = game_server.get_user_by_id(account_id);
+    let (result, user_entry) = if let Some(thread) = thread.as_ref() {
+        let result = game_server
+            .update_user(thread, |user| {
+                ban(user);
+                true
+            })
+            .await;
+
+        (result, thread.user_entry.lock().clone())
+    } else {
+        let mut user_entry = game_server
+            .fetch_user_data(&account_id.to_string())
+            .await
+            .map_err(|err| ApiError::new(StatusCode::BAD_GATEWAY, format!("failed to get user: {err}")))?;
+
+        ban(&mut user_entry);
+        (game_server.save_user_data(&user_entry).await, user_entry)
+    };
+
+    if let Err(err) = result {
+        warn!("[{mod_name}] failed to ban {account_id}: {err}");
+        return Err(ApiError::new(StatusCode::BAD_GATEWAY, err.to_string()));
+    }
+
+    if let Some(thread) = thread {
+        thread
+            .push_new_message(ServerThreadMessage::BroadcastBan(ServerBannedPacket {
+                message: FastString::new(request.reason.as_deref().unwrap_or_default()),
+                timestamp: request.expiry.unwrap_or(0),
+            }))
+            .await;
+    }
+
+    // the player might be connected to another server in the cluster
+    game_server.state.cluster.publish(ClusterMessage::Sanction {
+        account_id,
+        is_banned: true,
+        is_muted: user_entry.is_muted,
+        reason: request.reason.clone(),
+        expiry: request.expiry,
+    });
+
+    let target_name = user_entry.user_name.clone().unwrap_or_else(|| "<unknown>".to_owned());
+    info!("[{mod_name}] banned {target_name} ({account_id})");
+
+    state
+        .send_webhook(WebhookMessage::UserBanChanged(BanMuteStateChange {
+            mod_name,
+            target_name,
+            target_id: account_id,
+            new_state: true,
+            expiry: request.expiry,
+            reason: request.reason,
+        }))
+        .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct NoticeRequest {
+    message: String,
+    /// if set, only this player receives the notice
+    #[serde(default)]
+    account_id: Option<i32>,
+    /// if set, only players in this room receive the notice. if neither is set, everyone does
+    #[serde(default)]
+    room_id: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct NoticeResponse {
+    sent: usize,
+}
+
+async fn send_notice(State(state): State<Arc<ApiState>>, headers: HeaderMap, Json(request): Json<NoticeRequest>) -> ApiResult<Json<NoticeResponse>> {
+    // same as in the admin panel, the global room counts as everyone
+    let room_id = request.room_id.filter(|x| *x != 0);
+    let perm = if request.account_id.is_some() || room_id.is_some() {
+        AdminPerm::Notice
+    } else {
+        AdminPerm::NoticeToEveryone
+    };
+
+    let mod_name = state.authorize(&headers, perm)?;
+
+    if request.message.is_empty() || request.message.len() > MAX_NOTICE_SIZE {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("message must be between 1 and {MAX_NOTICE_SIZE} bytes long"),
+        ));
+    }
+
+    let packet = ServerNoticePacket {
+        message: FastString::new(&request.message),
+    };
+
+    if let Some(account_id) = request.account_id {
+        let thread = state.online_player(account_id)?;
+
+        let target_name = thread.account_data.lock().name.try_to_string();
+        info!("[{mod_name}] sending a notice to {target_name}: {}", request.message);
+
+        thread.push_new_message(ServerThreadMessage::BroadcastNotice(packet)).await;
+        state
+            .send_webhook(WebhookMessage::NoticeToPerson(mod_name, target_name, request.message))
+            .await;
+
+        return Ok(Json(NoticeResponse { sent: 1 }));
+    }
+
+    if room_id.is_some_and(|x| !state.game_server.state.room_manager.is_valid_room(x)) {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, "room does not exist"));
+    }
+
+    let threads = state
+        .authenticated_threads()
+        .into_iter()
+        .filter(|thr| room_id.is_none_or(|room_id| thr.room_id.load(Ordering::Relaxed) == room_id))
+        .collect::<Vec<_>>();
+
+    info!("[{mod_name}] sending a notice to {} people: {}", threads.len(), request.message);
+
+    for thread in &threads {
+        thread.push_new_message(ServerThreadMessage::BroadcastNotice(packet.clone())).await;
+    }
+
+    if room_id.is_some() {
+        state
+            .send_webhook(WebhookMessage::NoticeToSelection(mod_name, threads.len(), request.message))
+            .await;
+    } else {
+        state.game_server.state.cluster.publish(ClusterMessage::Announcement {
+            message: request.message.clone(),
+        });
+
+        state
+            .send_webhook(WebhookMessage::NoticeToEveryone(mod_name, threads.len(), request.message))
+            .await;
+    }
+
+    Ok(Json(NoticeResponse { sent: threads.len() }))
+}
+
+async fn close_room(State(state): State<Arc<ApiState>>, headers: HeaderMap, Path(room_id): Path<u32>) -> ApiResult<StatusCode> {
+    let mod_name = state.authorize(&headers, AdminPerm::Kick)?;
+
+    if room_id == 0 {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "the global room cannot be closed"));
+    }
+
+    let Some((room_name, players)) = state.game_server.state.room_manager.close_room(room_id) else {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, "room does not exist"));
+    };
+
+    info!("[{mod_name}] closed room {room_name} ({room_id}) with {} players", players.len());
+
+    let notice = ServerNoticePacket {
+        message: FastString::new("The room you were in was closed by the server staff."),
+    };
+
+    // the room is already gone, this moves everyone who was in it to the global room
+    for account_id in &players {
+        if let Some(thread) = state.game_server.get_user_by_id(*account_id) {
+            thread.push_new_message(ServerThreadMessage::BroadcastNotice(notice.clone())).await;
+            thread.push_new_message(ServerThreadMessage::RoomKick(room_id)).await;
+        }
+    }
+
+    state
+        .send_webhook(WebhookMessage::RoomClosed(mod_name, room_id, room_name, players.len()))
+        .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
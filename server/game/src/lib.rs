@@ -1,4 +1,4 @@
-#![feature(sync_unsafe_cell, duration_constructors, async_closure)]
+#![feature(sync_unsafe_cell, duration_constructors)]
 #![allow(
     clippy::must_use_candidate,
     clippy::module_name_repetitions,
@@ -11,11 +11,26 @@
 )]
 
 pub mod bridge;
+pub mod chaos;
 pub mod client;
-pub mod data;
+pub mod cluster;
+pub use globed_protocol as data;
+pub mod diagnostics;
+pub mod events;
+pub mod grpc;
+pub mod http_api;
+pub mod local_config;
 pub mod managers;
+pub mod plugins;
+pub mod replication;
+pub mod safe_mode;
+pub mod scripting;
 pub mod server;
+pub mod shutdown;
+pub mod snapshot;
 pub mod state;
+pub mod store;
+pub mod testing;
 pub mod util;
 pub mod webhook;
 
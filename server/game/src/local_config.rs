@@ -0,0 +1,86 @@
+//! Configuration read from local files: the word list in `word-filter.txt`, and on standalone servers the file in
+//! `GLOBED_GS_CONFIG`, which takes the place of the configuration a central server would send.
+//! Both are read again when the server gets `SIGHUP` or an admin asks for it, see `GameServer::reload_config`.
+
+use std::path::{Path, PathBuf};
+
+use globed_shared::{
+    anyhow::{self, anyhow, bail},
+    GameServerBootData,
+};
+
+use crate::server::GameServer;
+
+/// Returns the path of `word-filter.txt`, the one in the working directory is preferred over the one next to the executable.
+fn word_filter_path() -> Option<PathBuf> {
+    let in_cwd = std::env::current_dir().ok().map(|dir| dir.join("word-filter.txt"));
+    let in_exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("word-filter.txt")));
+
+    [in_cwd, in_exe_dir].into_iter().flatten().find(|path| path.exists())
+}
+
+/// Reads the words in `word-filter.txt`, one per line. Returns an empty list if there is no such file.
+pub fn load_filter_words() -> anyhow::Result<Vec<String>> {
+    let Some(path) = word_filter_path() else {
+        return Ok(Vec::new());
+    };
+
+    let content = std::fs::read_to_string(&path).map_err(|e| anyhow!("failed to read {}: {e}", path.display()))?;
+
+    Ok(content.lines().map(|x| x.to_owned()).collect())
+}
+
+/// Returns the path in `GLOBED_GS_CONFIG`, if set.
+pub fn config_path() -> Option<PathBuf> {
+    std::env::var("GLOBED_GS_CONFIG").ok().filter(|x| !x.is_empty()).map(PathBuf::from)
+}
+
+/// Reads the configuration of a standalone server from a JSON file, in the same format a central server can send it in.
+/// Missing fields get their default values, except for the admin key, which is kept from `current` so it doesn't change on every reload.
+pub fn read_config(path: &Path, current: &GameServerBootData) -> anyhow::Result<GameServerBootData> {
+    let content = std::fs::read_to_string(path)?;
+    let value: serde_json::Value = serde_json::from_str(&content)?;
+
+    if !value.is_object() {
+        bail!("expected a JSON object");
+    }
+
+    let keep_admin_key = value.get("admin_key").is_none();
+
+    let mut data: GameServerBootData = serde_json::from_value(value)?;
+    if keep_admin_key {
+        data.admin_key = current.admin_key.clone();
+    }
+
+    Ok(data)
+}
+
+/// Reloads the configuration whenever the server gets `SIGHUP`. Not available on Windows, where an admin can still reload it in-game.
+pub fn start(game_server: &'static GameServer) {
+    #[cfg(unix)]
+    crate::tokio::spawn(async move {
+        use crate::tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(x) => x,
+            Err(e) => {
+                globed_shared::warn!("failed to listen for SIGHUP, the configuration can only be reloaded by an admin: {e}");
+                return;
+            }
+        };
+
+        while hangups.recv().await.is_some() {
+            globed_shared::info!("SIGHUP received, reloading the configuration");
+
+            match game_server.reload_config().await {
+                Ok(()) => globed_shared::info!("Configuration reloaded"),
+                Err(e) => globed_shared::error!("failed to reload the configuration: {e}"),
+            }
+        }
+    });
+
+    #[cfg(not(unix))]
+    let _ = game_server;
+}
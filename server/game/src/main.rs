@@ -1,4 +1,4 @@
-#![feature(sync_unsafe_cell, duration_constructors, async_closure, iter_collect_into)]
+#![feature(sync_unsafe_cell, duration_constructors)]
 #![allow(
     clippy::must_use_candidate,
     clippy::module_name_repetitions,
@@ -19,33 +19,47 @@ use tokio;
 
 use std::{
     error::Error,
-    net::{IpAddr, Ipv4Addr, SocketAddr},
+    net::{IpAddr, SocketAddr},
 };
 
 use bridge::{CentralBridge, CentralBridgeError};
-use globed_shared::{log::Log, *};
+use globed_shared::{geoip::GeoIpDatabase, *};
 use reqwest::StatusCode;
 use state::ServerState;
-use tokio::{
-    fs::File,
-    io::AsyncReadExt,
-    net::{TcpListener, UdpSocket},
-};
+use store::LocalStore;
 
+use managers::NAME_CLAIM_DURATION;
 use server::GameServer;
+use testing::SoakTestConfig;
+use util::{bind_tcp, bind_udp, ServerGeoIp, SocketOptions};
 
 pub mod bridge;
+pub mod chaos;
 pub mod client;
-pub mod data;
+pub mod cluster;
+pub use ::globed_protocol as data;
+pub mod diagnostics;
+pub mod events;
+pub mod grpc;
+pub mod http_api;
+pub mod local_config;
 pub mod managers;
+pub mod plugins;
+pub mod replication;
+pub mod safe_mode;
+pub mod scripting;
 pub mod server;
+pub mod shutdown;
+pub mod snapshot;
 pub mod state;
+pub mod store;
+pub mod testing;
 pub mod util;
 pub mod webhook;
 
 struct StartupConfiguration {
     bind_address: SocketAddr,
-    central_data: Option<(String, String)>,
+    central_data: Option<(String, Secret)>,
 }
 
 fn abort_misconfig() -> ! {
@@ -61,6 +75,30 @@ fn censor_key(key: &str, keep_first_n_chars: usize) -> String {
     format!("{}{}", &key[..keep_first_n_chars], "*".repeat(key.len() - keep_first_n_chars))
 }
 
+/// Returns the secret key used for signing session tokens on a standalone server. It's read from the file at
+/// `GLOBED_GS_STANDALONE_SECRET_FILE` (by default `standalone-secret.txt`), and generated if the file doesn't exist yet,
+/// so that tokens stay valid across restarts.
+async fn standalone_token_secret() -> String {
+    let path = std::env::var("GLOBED_GS_STANDALONE_SECRET_FILE").unwrap_or_else(|_| "standalone-secret.txt".to_owned());
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(secret) if !secret.trim().is_empty() => return secret.trim().to_owned(),
+        Ok(_) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => {
+            error!("failed to read the token secret from {path}: {err}");
+            abort_misconfig();
+        }
+    }
+
+    let secret = generate_alphanum_string(64);
+    if let Err(err) = tokio::fs::write(&path, &secret).await {
+        warn!("failed to save the token secret to {path}, tokens will not stay valid after a restart: {err}");
+    }
+
+    secret
+}
+
 fn parse_configuration() -> StartupConfiguration {
     let mut args = std::env::args();
 
@@ -85,12 +123,14 @@ fn parse_configuration() -> StartupConfiguration {
         Ok(x) => x,
         Err(_) => {
             // try to parse it as an ip addr and use a default port
-            match bind_address.parse::<Ipv4Addr>() {
-                Ok(x) => SocketAddr::new(IpAddr::V4(x), DEFAULT_GAME_SERVER_PORT),
+            match bind_address.parse::<IpAddr>() {
+                Ok(x) => SocketAddr::new(x, DEFAULT_GAME_SERVER_PORT),
                 Err(e) => {
                     error!("failed to parse the given IP address ({bind_address}): {e}");
-                    warn!("hint: you have to provide a valid IPv4 address with an optional port number");
-                    warn!("hint: for example \"0.0.0.0\" or \"0.0.0.0:{DEFAULT_GAME_SERVER_PORT}\"");
+                    warn!("hint: you have to provide a valid IPv4 or IPv6 address with an optional port number");
+                    warn!(
+                        "hint: for example \"0.0.0.0\", \"0.0.0.0:{DEFAULT_GAME_SERVER_PORT}\", \"::\" or \"[::]:{DEFAULT_GAME_SERVER_PORT}\" (IPv6 addresses also accept IPv4 clients)"
+                    );
                     abort_misconfig();
                 }
             }
@@ -133,7 +173,14 @@ fn parse_configuration() -> StartupConfiguration {
         abort_misconfig();
     }
 
-    let central_pw = arg.unwrap();
+    // the password can also be read from a file or another environment variable, i.e. `file:/run/secrets/globed`
+    let central_pw = match Secret::resolve(&arg.unwrap()) {
+        Ok(x) => x,
+        Err(err) => {
+            error!("{err}");
+            abort_misconfig();
+        }
+    };
 
     // full configuration with a central server
     StartupConfiguration {
@@ -160,12 +207,19 @@ async fn main() -> Result<(), Box<dyn Error>> {
         abort_misconfig();
     }
 
-    // set the interrupt handler to flush the logfile and exit
+    chaos::init_from_env();
+    safe_mode::init_from_env();
+
+    // set the interrupt handler to notify players, flush the logfile and exit
 
     if let Err(e) = ctrlc::set_handler(move || {
-        warn!("Interrupt signal received, terminating the server");
-        Logger::instance("globed_game_server", true).flush();
-        std::process::exit(1);
+        if shutdown::begin_countdown() {
+            warn!("Interrupt signal received, the server will shut down once players were notified (send it again to shut down right away)");
+            return;
+        }
+
+        warn!("Interrupt signal received, shutting down the server (send it again to exit without waiting for clients)");
+        shutdown::shut_down();
     }) {
         warn!("error setting up interrupt handler: {e}");
     }
@@ -182,42 +236,75 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let startup_config = parse_configuration();
     let standalone = startup_config.central_data.is_none();
 
+    let soak_config = SoakTestConfig::from_env();
+    if soak_config.is_some() && !standalone {
+        error!("soak test mode can only be used on a standalone server");
+        warn!("hint: unset the GLOBED_GS_SOAK_CLIENTS environment variable or start the server without a central server");
+        abort_misconfig();
+    }
+
     // check if there's a word filter
-    let word_filter_path = std::env::current_exe()
-        .expect("failed to get current executable")
-        .parent()
-        .unwrap()
-        .join("word-filter.txt");
-
-    let word_filter_path2 = std::env::current_dir().expect("failed to get current dir").join("word-filter.txt");
-
-    let chosen = match (word_filter_path.exists(), word_filter_path2.exists()) {
-        (_, true) => Some(word_filter_path2),
-        (true, false) => Some(word_filter_path),
-        (false, false) => None,
-    };
+    let filter_words = local_config::load_filter_words().unwrap_or_else(|e| {
+        warn!("{e}");
+        Vec::new()
+    });
+
+    let filter_words_count = filter_words.len();
 
-    let mut filter_words = Vec::new();
-    if let Some(chosen) = chosen {
-        let mut content = String::new();
+    let mut state = ServerState::new(&filter_words);
 
-        match File::open(chosen).await {
-            Ok(mut file) => {
-                file.read_to_string(&mut content).await?;
+    // open the local database, only useful on standalone servers, as otherwise the central server keeps all the data
+    if let Ok(database_path) = std::env::var("GLOBED_GS_DATABASE") {
+        if standalone {
+            match LocalStore::open(&database_path).await {
+                Ok(store) => state.store = Some(store),
+                Err(err) => {
+                    error!("failed to open the database at {database_path}: {err}");
+                    abort_misconfig();
+                }
             }
-            Err(e) => warn!("failed to open word-filter.txt: {e}"),
+        } else {
+            warn!("GLOBED_GS_DATABASE is set, but the server is not standalone, ignoring");
         }
-
-        content.lines().map(|x| x.to_owned()).collect_into(&mut filter_words);
     }
 
-    let filter_words_count = filter_words.len();
+    // open the GeoIP database, used for telling clients how far away they are from this server
+    if let Ok(database_path) = std::env::var("GLOBED_GS_GEOIP_DB") {
+        let location = std::env::var("GLOBED_GS_LOCATION").ok().and_then(|x| geoip::Coordinates::parse(&x));
+
+        match (GeoIpDatabase::open(&database_path), location) {
+            (Ok(database), Some(location)) => state.geoip = Some(ServerGeoIp::new(database, location)),
+            (Err(err), _) => warn!("failed to open the GeoIP database at {database_path}: {err}"),
+            (_, None) => warn!("GLOBED_GS_GEOIP_DB is set, but GLOBED_GS_LOCATION is missing or invalid, ignoring"),
+        }
+    }
 
-    let state = ServerState::new(&filter_words);
     let bridge = if standalone {
         warn!("Starting in standalone mode, authentication is disabled");
         warn!("Note: use Direct Connection option in-game to connect, Add Server cannot be used.");
-        CentralBridge::new("", "")
+
+        // names are claimed by the first player to use them, and the claim is kept with a locally signed token
+        let bridge = CentralBridge::new("", Secret::default());
+        let token_secret = standalone_token_secret().await;
+        {
+            let mut issuer = bridge.token_issuer.lock();
+            issuer.set_secret_key(&token_secret);
+            issuer.set_expiration_period(NAME_CLAIM_DURATION);
+        }
+
+        // the configuration a central server would send can be given in a local file instead
+        if let Some(path) = local_config::config_path() {
+            let result = local_config::read_config(&path, &bridge.central_conf.lock());
+            match result {
+                Ok(config) => bridge.set_local_config(config),
+                Err(err) => {
+                    error!("failed to read the configuration from {}: {err}", path.display());
+                    abort_misconfig();
+                }
+            }
+        }
+
+        bridge
     } else {
         let (central_url, central_pw) = startup_config.central_data.unwrap();
 
@@ -229,7 +316,11 @@ async fn main() -> Result<(), Box<dyn Error>> {
             abort_misconfig();
         }
 
-        let bridge = CentralBridge::new(&central_url, &central_pw);
+        if local_config::config_path().is_some() {
+            warn!("GLOBED_GS_CONFIG is set, but the server is not standalone, ignoring");
+        }
+
+        let bridge = CentralBridge::new(&central_url, central_pw);
 
         info!("Retrieving config from the central server..");
 
@@ -264,6 +355,11 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 warn!("hint: make sure that both the central and the game servers are on the latest version");
                 abort_misconfig();
             }
+            Err(CentralBridgeError::MalformedJson(err)) => {
+                error!("failed to parse the JSON data sent by the central server: {err}");
+                warn!("hint: make sure that the central server sends boot data in the same format as the official one");
+                abort_misconfig();
+            }
             Err(CentralBridgeError::ProtocolMismatch(protocol)) => {
                 error!("incompatible protocol versions!");
                 error!("this game server is on v{PROTOCOL_VERSION}, while the central server uses v{}", protocol);
@@ -299,7 +395,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         debug!("* Token expiry: {} seconds", gsbd.token_expiry);
         debug!("* Maintenance: {}", if gsbd.maintenance { "yes" } else { "no" });
 
-        debug!("* Token secret key: '{}'", censor_key(&gsbd.secret_key2, 4));
+        debug!("* Token secret key: '{}'", censor_key(gsbd.secret_key2.expose(), 4));
 
         if standalone {
             debug!("* Admin key: '{}'", gsbd.admin_key);
@@ -322,11 +418,22 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
 
         state.role_manager.refresh_from(&gsbd);
+        state.level_blocklist.refresh_from(&gsbd);
+        state.ip_bans.refresh_from(&gsbd);
+        state.chat_filter.refresh_from(&gsbd);
+
+        if !state.ip_bans.is_empty() {
+            debug!("Banned addresses and ranges: {}", state.ip_bans.len());
+        }
+
+        if !state.chat_filter.is_empty() {
+            debug!("Chat word filter entries: {}", state.chat_filter.len());
+        }
     }
 
     // bind the UDP socket
 
-    let udp_socket = match UdpSocket::bind(&startup_config.bind_address).await {
+    let udp_socket = match bind_udp(startup_config.bind_address) {
         Ok(x) => x,
         Err(err) => {
             error!("Failed to bind the UDP socket with address {}: {err}", startup_config.bind_address);
@@ -340,7 +447,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // bind the TCP socket
 
-    let tcp_socket = match TcpListener::bind(&startup_config.bind_address).await {
+    let tcp_socket = match bind_tcp(startup_config.bind_address) {
         Ok(x) => x,
         Err(err) => {
             error!("Failed to bind the TCP socket with address {}: {err}", startup_config.bind_address);
@@ -349,12 +456,26 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
     };
 
+    // apply socket options from the environment, a failure here is not fatal as the defaults still work
+
+    let socket_options = SocketOptions::get();
+
+    if let Err(err) = socket_options.apply_udp(&udp_socket) {
+        warn!("failed to apply options to the UDP socket: {err}");
+    }
+
+    if let Err(err) = socket_options.apply_tcp_listener(&tcp_socket) {
+        warn!("failed to apply options to the TCP socket: {err}");
+    }
+
     // create and run the server
 
     let server = GameServer::new(tcp_socket, udp_socket, state, bridge, standalone);
     let server = Box::leak(Box::new(server));
 
-    Box::pin(server.run()).await;
+    if let Some(soak_config) = soak_config {
+        testing::soak::start(server, startup_config.bind_address, soak_config);
+    }
 
-    Ok(())
+    Box::pin(server.run()).await
 }
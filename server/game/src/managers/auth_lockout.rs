@@ -0,0 +1,210 @@
+use std::{
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+use globed_shared::{IntMap, SyncMutex};
+
+use crate::util::address_key;
+
+/// How many failed attempts are allowed before a lockout kicks in.
+const FREE_ATTEMPTS: u32 = 3;
+/// Length of the first lockout, every next failure doubles it.
+const BASE_LOCKOUT: Duration = Duration::from_secs(5);
+const MAX_LOCKOUT: Duration = Duration::from_secs(15 * 60);
+/// Failure records are forgotten after there were no failures for this long.
+const FAILURE_MEMORY: Duration = Duration::from_secs(30 * 60);
+/// Amount of failures that is considered a burst worth alerting about. Alerts are sent again every time the count doubles.
+const BURST_THRESHOLD: u32 = 10;
+/// Expired records are only cleaned up once a map grows past this size.
+const CLEANUP_THRESHOLD: usize = 1024;
+/// Hard cap on the amount of records of one kind, once it's reached the older half of them is forgotten.
+const MAX_RECORDS: usize = 16384;
+
+struct FailureRecord {
+    failures: u32,
+    last_failure: Instant,
+    locked_until: Instant,
+}
+
+impl FailureRecord {
+    fn is_expired(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_failure) > FAILURE_MEMORY && now >= self.locked_until
+    }
+
+    /// Registers a failure, returns `true` if the failure count just reached a burst threshold.
+    fn add_failure(&mut self, now: Instant) -> bool {
+        if self.is_expired(now) {
+            self.failures = 0;
+        }
+
+        self.failures += 1;
+        self.last_failure = now;
+
+        if self.failures > FREE_ATTEMPTS {
+            let exponent = (self.failures - FREE_ATTEMPTS - 1).min(16);
+            let lockout = BASE_LOCKOUT.saturating_mul(1 << exponent).min(MAX_LOCKOUT);
+            self.locked_until = now + lockout;
+        }
+
+        self.failures.is_multiple_of(BURST_THRESHOLD) && (self.failures / BURST_THRESHOLD).is_power_of_two()
+    }
+
+    fn remaining_lockout(&self, now: Instant) -> Option<Duration> {
+        Some(self.locked_until.saturating_duration_since(now)).filter(|x| !x.is_zero())
+    }
+}
+
+impl Default for FailureRecord {
+    fn default() -> Self {
+        let now = Instant::now();
+        Self {
+            failures: 0,
+            last_failure: now,
+            locked_until: now,
+        }
+    }
+}
+
+/// Failure records of one kind, which never grow past `MAX_RECORDS`.
+struct FailureRecords<K> {
+    records: IntMap<K, FailureRecord>,
+    /// the size the map has to reach before it's swept again, so that a map full of valid records isn't swept on every failure
+    next_sweep: usize,
+}
+
+impl<K> Default for FailureRecords<K> {
+    fn default() -> Self {
+        Self {
+            records: IntMap::default(),
+            next_sweep: CLEANUP_THRESHOLD,
+        }
+    }
+}
+
+impl<K> FailureRecords<K> {
+    /// Forgets expired records once the map grew to `next_sweep`, and the older half of them if that wasn't enough to get under `MAX_RECORDS`.
+    fn sweep(&mut self, now: Instant) {
+        if self.records.len() < self.next_sweep {
+            return;
+        }
+
+        self.records.retain(|_, record| !record.is_expired(now));
+
+        if self.records.len() >= MAX_RECORDS {
+            let mut last_failures = self.records.values().map(|record| record.last_failure).collect::<Vec<_>>();
+            let middle = last_failures.len() / 2;
+            let cutoff = *last_failures.select_nth_unstable(middle).1;
+
+            self.records.retain(|_, record| record.last_failure > cutoff);
+        }
+
+        self.next_sweep = (self.records.len() * 2).clamp(CLEANUP_THRESHOLD, MAX_RECORDS);
+    }
+}
+
+pub enum AuthFailureBurst {
+    Account(i32, u32), // account id, failure count
+    Address(IpAddr, u32),
+}
+
+/// Keeps track of failed login attempts per account and per IP address, and locks them out for exponentially increasing periods of time.
+#[derive(Default)]
+pub struct AuthLockoutManager {
+    accounts: SyncMutex<FailureRecords<i32>>,
+    addresses: SyncMutex<FailureRecords<u64>>,
+}
+
+impl AuthLockoutManager {
+    /// Returns the remaining lockout time if either the account or the address is currently locked out.
+    pub fn get_lockout(&self, account_id: i32, address: IpAddr) -> Option<Duration> {
+        self.get_account_lockout(account_id).max(self.get_address_lockout(address))
+    }
+
+    /// Returns the remaining lockout time of the account. Anyone can fail logins with any account ID,
+    /// so this must only be checked once the attempt failed, never to turn away a login that would succeed.
+    pub fn get_account_lockout(&self, account_id: i32) -> Option<Duration> {
+        self.accounts
+            .lock()
+            .records
+            .get(&account_id)
+            .and_then(|x| x.remaining_lockout(Instant::now()))
+    }
+
+    /// Returns the remaining lockout time of the address.
+    pub fn get_address_lockout(&self, address: IpAddr) -> Option<Duration> {
+        self.addresses
+            .lock()
+            .records
+            .get(&address_key(address))
+            .and_then(|x| x.remaining_lockout(Instant::now()))
+    }
+
+    /// Registers a failed login attempt, returns the bursts that should be reported.
+    pub fn record_failure(&self, account_id: i32, address: IpAddr) -> Vec<AuthFailureBurst> {
+        let now = Instant::now();
+        let mut bursts = Vec::new();
+
+        {
+            let mut accounts = self.accounts.lock();
+            accounts.sweep(now);
+
+            let record = accounts.records.entry(account_id).or_default();
+            if record.add_failure(now) {
+                bursts.push(AuthFailureBurst::Account(account_id, record.failures));
+            }
+        }
+
+        {
+            let mut addresses = self.addresses.lock();
+            addresses.sweep(now);
+
+            let record = addresses.records.entry(address_key(address)).or_default();
+            if record.add_failure(now) {
+                bursts.push(AuthFailureBurst::Address(address, record.failures));
+            }
+        }
+
+        bursts
+    }
+
+    /// Clears the failure record of an account after a successful login. The address record is left alone,
+    /// so that a single valid account can't be used to reset the lockout of an address that is stuffing credentials.
+    pub fn record_success(&self, account_id: i32) {
+        self.accounts.lock().records.remove(&account_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_are_capped() {
+        let manager = AuthLockoutManager::default();
+        let address = IpAddr::from([203, 0, 113, 7]);
+
+        for account_id in 0..(MAX_RECORDS as i32 * 2) {
+            manager.record_failure(account_id, address);
+            assert!(manager.accounts.lock().records.len() <= MAX_RECORDS);
+        }
+
+        // the newest records are the ones that are kept
+        assert!(manager.accounts.lock().records.contains_key(&(MAX_RECORDS as i32 * 2 - 1)));
+        assert!(!manager.accounts.lock().records.contains_key(&0));
+    }
+
+    #[test]
+    fn valid_records_are_not_swept_every_time() {
+        let mut records = FailureRecords::<i32>::default();
+        let now = Instant::now();
+
+        for account_id in 0..CLEANUP_THRESHOLD as i32 {
+            records.records.entry(account_id).or_default().add_failure(now);
+        }
+
+        records.sweep(now);
+        assert_eq!(records.records.len(), CLEANUP_THRESHOLD);
+        assert_eq!(records.next_sweep, CLEANUP_THRESHOLD * 2);
+    }
+}
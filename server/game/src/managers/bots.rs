@@ -0,0 +1,152 @@
+use std::{
+    sync::atomic::{AtomicI32, Ordering},
+    time::Instant,
+};
+
+use globed_shared::{IntMap, SyncMutex};
+
+use crate::data::*;
+
+/// How many bots can exist on the server at once.
+pub const MAX_BOTS: usize = 16;
+/// Prepended to the name of every bot, so other players can always tell them apart from real people.
+pub const BOT_NAME_PREFIX: &str = "[BOT] ";
+
+/// Real accounts always have a positive ID, bots get negative IDs so they can never collide.
+#[inline]
+pub const fn is_bot_account(account_id: i32) -> bool {
+    account_id < 0
+}
+
+/// A synthetic player spawned by an admin, for testing moderation tools and demonstrating features.
+#[derive(Clone)]
+pub struct Bot {
+    pub account_id: i32,
+    pub name: InlineString<MAX_NAME_SIZE>,
+    pub room_id: u32,
+    pub level_id: LevelId,
+    pub position: Point,
+    pub icons: PlayerIconData,
+    pub spawned_by: InlineString<MAX_NAME_SIZE>,
+    pub spawned_at: Instant,
+}
+
+impl Bot {
+    pub fn new(name: &str, room_id: u32, level_id: LevelId, icons: PlayerIconData, spawned_by: InlineString<MAX_NAME_SIZE>) -> Self {
+        let mut full_name = InlineString::new(BOT_NAME_PREFIX);
+        full_name.extend_safe(name);
+
+        Self {
+            account_id: 0,
+            name: full_name,
+            room_id,
+            level_id,
+            position: Point::default(),
+            icons,
+            spawned_by,
+            spawned_at: Instant::now(),
+        }
+    }
+
+    pub fn to_info(&self) -> BotInfo {
+        BotInfo {
+            account_id: self.account_id,
+            name: self.name.clone(),
+            room_id: self.room_id,
+            level_id: self.level_id,
+            position: self.position,
+            spawned_by: self.spawned_by.clone(),
+        }
+    }
+
+    pub fn make_account_data(&self) -> PlayerAccountData {
+        PlayerAccountData {
+            account_id: self.account_id,
+            user_id: self.account_id,
+            name: self.name.clone(),
+            icons: self.icons.clone(),
+            special_user_data: SpecialUserData::default(),
+        }
+    }
+
+    /// Builds the player data of the bot. The timestamp keeps increasing even if the bot doesn't move,
+    /// as clients only show a player once they have received frames with different timestamps.
+    pub fn make_player_data(&self) -> PlayerData {
+        let icon = SpecificIconData {
+            position: self.position,
+            icon_type: PlayerIconType::Cube,
+            ..Default::default()
+        };
+
+        PlayerData {
+            timestamp: FiniteF32::new(self.spawned_at.elapsed().as_secs_f32()).unwrap_or_default(),
+            player1: icon.clone(),
+            player2: icon,
+            ..Default::default()
+        }
+    }
+}
+
+/// Keeps track of bots. The bots themselves are regular players in the `LevelManager` of their room,
+/// so their data is sent to everyone on the level without any special handling.
+#[derive(Default)]
+pub struct BotManager {
+    bots: SyncMutex<IntMap<i32, Bot>>,
+    next_id: AtomicI32,
+}
+
+impl BotManager {
+    /// Registers the bot and returns its account ID, or `None` if there are already too many bots.
+    /// The account ID of the passed bot is ignored.
+    pub fn spawn(&self, mut bot: Bot) -> Option<i32> {
+        let mut bots = self.bots.lock();
+        if bots.len() >= MAX_BOTS {
+            return None;
+        }
+
+        bot.account_id = -(self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+
+        let id = bot.account_id;
+        bots.insert(id, bot);
+
+        Some(id)
+    }
+
+    pub fn remove(&self, account_id: i32) -> Option<Bot> {
+        self.bots.lock().remove(&account_id)
+    }
+
+    pub fn get(&self, account_id: i32) -> Option<Bot> {
+        self.bots.lock().get(&account_id).cloned()
+    }
+
+    /// Runs `f` on the bot and returns the result, or `None` if there is no such bot.
+    pub fn with_bot<R>(&self, account_id: i32, f: impl FnOnce(&mut Bot) -> R) -> Option<R> {
+        self.bots.lock().get_mut(&account_id).map(f)
+    }
+
+    /// Returns all bots, the oldest first.
+    pub fn list(&self) -> Vec<BotInfo> {
+        let mut bots = self.bots.lock().values().map(Bot::to_info).collect::<Vec<_>>();
+        bots.sort_by_key(|bot| -bot.account_id);
+        bots
+    }
+
+    pub fn in_room(&self, room_id: u32) -> Vec<Bot> {
+        self.bots.lock().values().filter(|bot| bot.room_id == room_id).cloned().collect()
+    }
+
+    /// Returns the account ID, room and current player data of every bot.
+    pub fn make_player_data(&self) -> Vec<(i32, u32, PlayerData)> {
+        self.bots
+            .lock()
+            .values()
+            .map(|bot| (bot.account_id, bot.room_id, bot.make_player_data()))
+            .collect()
+    }
+
+    /// Forgets bots whose room doesn't exist anymore.
+    pub fn retain_rooms<F: Fn(u32) -> bool>(&self, exists: F) {
+        self.bots.lock().retain(|_, bot| bot.room_id == 0 || exists(bot.room_id));
+    }
+}
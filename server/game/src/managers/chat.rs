@@ -0,0 +1,109 @@
+use std::{collections::VecDeque, time::Instant};
+
+use globed_shared::{IntMap, SyncMutex};
+
+use crate::data::LevelId;
+
+/// How many of the most recent messages are remembered. Older messages can no longer be deleted.
+const MAX_TRACKED_MESSAGES: usize = 4096;
+
+/// A chat message that was sent recently, kept so that it can be deleted later.
+#[derive(Clone, Copy)]
+pub struct SentMessage {
+    pub id: u32,
+    pub author: i32,
+    pub room_id: u32,
+    pub level_id: LevelId,
+    pub sent_at: Instant,
+}
+
+struct ChatManagerInner {
+    last_id: u32,
+    /// ordered by ID, since IDs only ever increase
+    messages: VecDeque<SentMessage>,
+}
+
+/// Assigns IDs to chat messages and keeps track of who sent them and where.
+pub struct ChatManager {
+    inner: SyncMutex<ChatManagerInner>,
+    /// slow mode of levels in seconds, set by moderators
+    level_slow_modes: SyncMutex<IntMap<LevelId, u32>>,
+}
+
+impl ChatManager {
+    pub fn new() -> Self {
+        Self {
+            inner: SyncMutex::new(ChatManagerInner {
+                last_id: 0,
+                messages: VecDeque::new(),
+            }),
+            level_slow_modes: SyncMutex::new(IntMap::default()),
+        }
+    }
+
+    /// Registers a new message and returns its ID. IDs start at 1, 0 is never a valid ID.
+    pub fn record(&self, author: i32, room_id: u32, level_id: LevelId) -> u32 {
+        let mut inner = self.inner.lock();
+
+        inner.last_id = inner.last_id.checked_add(1).unwrap_or(1);
+        let id = inner.last_id;
+
+        // after wrapping around, older messages would break the ordering
+        if inner.messages.back().is_some_and(|msg| msg.id > id) {
+            inner.messages.clear();
+        }
+
+        if inner.messages.len() >= MAX_TRACKED_MESSAGES {
+            inner.messages.pop_front();
+        }
+
+        inner.messages.push_back(SentMessage {
+            id,
+            author,
+            room_id,
+            level_id,
+            sent_at: Instant::now(),
+        });
+
+        id
+    }
+
+    pub fn get(&self, id: u32) -> Option<SentMessage> {
+        let inner = self.inner.lock();
+        let idx = inner.messages.binary_search_by_key(&id, |msg| msg.id).ok()?;
+
+        inner.messages.get(idx).copied()
+    }
+
+    /// Forgets about the message, returns `false` if it was already deleted or is too old.
+    pub fn remove(&self, id: u32) -> bool {
+        let mut inner = self.inner.lock();
+
+        match inner.messages.binary_search_by_key(&id, |msg| msg.id) {
+            Ok(idx) => inner.messages.remove(idx).is_some(),
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the slow mode of the level in seconds, 0 if there is none.
+    pub fn level_slow_mode(&self, level_id: LevelId) -> u32 {
+        self.level_slow_modes.lock().get(&level_id).copied().unwrap_or(0)
+    }
+
+    /// Sets the slow mode of the level, 0 to disable it.
+    pub fn set_level_slow_mode(&self, level_id: LevelId, seconds: u32) {
+        let mut slow_modes = self.level_slow_modes.lock();
+
+        if seconds == 0 {
+            slow_modes.remove(&level_id);
+        } else {
+            slow_modes.insert(level_id, seconds);
+        }
+    }
+}
+
+impl Default for ChatManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
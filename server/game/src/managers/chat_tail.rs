@@ -0,0 +1,33 @@
+use globed_shared::{IntMap, SyncMutex};
+
+use crate::data::LevelId;
+
+/// Keeps track of the moderators that are reading the chat of a room or a level without being there.
+/// Every moderator can follow one place at a time.
+#[derive(Default)]
+pub struct ChatTailManager {
+    /// moderator : (room id, level id), a level id of 0 means every level in the room
+    tails: SyncMutex<IntMap<i32, (u32, LevelId)>>,
+}
+
+impl ChatTailManager {
+    /// Starts following the chat of a room or level, replacing what the moderator followed before.
+    pub fn start(&self, moderator: i32, room_id: u32, level_id: LevelId) {
+        self.tails.lock().insert(moderator, (room_id, level_id));
+    }
+
+    /// Stops following chat, returns what the moderator was following.
+    pub fn stop(&self, moderator: i32) -> Option<(u32, LevelId)> {
+        self.tails.lock().remove(&moderator)
+    }
+
+    /// Returns the moderators that follow the chat of the given level in the given room.
+    pub fn followers_of(&self, room_id: u32, level_id: LevelId) -> Vec<i32> {
+        self.tails
+            .lock()
+            .iter()
+            .filter(|(_, (tail_room, tail_level))| *tail_room == room_id && (*tail_level == 0 || *tail_level == level_id))
+            .map(|(moderator, _)| *moderator)
+            .collect()
+    }
+}
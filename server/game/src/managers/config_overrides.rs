@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use globed_shared::{GameServerBootData, SyncMutex};
+
+use crate::data::*;
+
+/// Overrides can't be set for longer than this, so a forgotten one doesn't stick around forever.
+pub const MAX_OVERRIDE_DURATION: Duration = Duration::from_secs(60 * 60 * 24);
+
+fn read_value(conf: &GameServerBootData, key: ConfigOverrideKey) -> u32 {
+    match key {
+        ConfigOverrideKey::Tps => conf.tps,
+        ConfigOverrideKey::Maintenance => u32::from(conf.maintenance),
+        ConfigOverrideKey::ChatBurstLimit => conf.chat_burst_limit,
+        ConfigOverrideKey::ChatBurstInterval => conf.chat_burst_interval,
+    }
+}
+
+fn write_value(conf: &mut GameServerBootData, key: ConfigOverrideKey, value: u32) {
+    match key {
+        ConfigOverrideKey::Tps => conf.tps = value,
+        ConfigOverrideKey::Maintenance => conf.maintenance = value != 0,
+        ConfigOverrideKey::ChatBurstLimit => conf.chat_burst_limit = value,
+        ConfigOverrideKey::ChatBurstInterval => conf.chat_burst_interval = value,
+    }
+}
+
+/// Config values that admins overrode on this game server. Overrides are applied on top of every config
+/// refresh from the central server, and the original values come back once they expire.
+/// They only live in memory, so a restart clears them.
+#[derive(Default)]
+pub struct ConfigOverrideManager {
+    overrides: SyncMutex<Vec<ConfigOverride>>,
+}
+
+impl ConfigOverrideManager {
+    /// Overrides the value in `conf`, replacing any existing override of the same key.
+    pub fn set(&self, conf: &mut GameServerBootData, key: ConfigOverrideKey, value: u32, expires_at: i64, author: String) {
+        let mut overrides = self.overrides.lock();
+
+        // keep the value from the central server if this replaces another override
+        let original = match overrides.iter().position(|x| x.key == key) {
+            Some(idx) => overrides.remove(idx).original,
+            None => read_value(conf, key),
+        };
+
+        write_value(conf, key, value);
+
+        overrides.push(ConfigOverride {
+            key,
+            value,
+            original,
+            expires_at,
+            author,
+        });
+    }
+
+    /// Removes the override and puts the original value back, returns the removed override.
+    pub fn remove(&self, conf: &mut GameServerBootData, key: ConfigOverrideKey) -> Option<ConfigOverride> {
+        let mut overrides = self.overrides.lock();
+
+        let idx = overrides.iter().position(|x| x.key == key)?;
+        let removed = overrides.remove(idx);
+        write_value(conf, key, removed.original);
+
+        Some(removed)
+    }
+
+    /// Applies all overrides on top of freshly fetched config, remembering its values as the new originals.
+    pub fn apply(&self, conf: &mut GameServerBootData) {
+        for entry in &mut *self.overrides.lock() {
+            entry.original = read_value(conf, entry.key);
+            write_value(conf, entry.key, entry.value);
+        }
+    }
+
+    /// Removes overrides that expired at `now`, putting their original values back. Returns the removed overrides.
+    pub fn expire(&self, conf: &mut GameServerBootData, now: i64) -> Vec<ConfigOverride> {
+        let mut overrides = self.overrides.lock();
+
+        let (expired, active): (Vec<_>, Vec<_>) = overrides.drain(..).partition(|x| x.expires_at <= now);
+        *overrides = active;
+
+        for entry in &expired {
+            write_value(conf, entry.key, entry.original);
+        }
+
+        expired
+    }
+
+    pub fn list(&self) -> Vec<ConfigOverride> {
+        self.overrides.lock().clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overrides.lock().is_empty()
+    }
+}
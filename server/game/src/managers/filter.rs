@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+use aho_corasick::AhoCorasick;
+use globed_shared::{warn, GameServerBootData, SyncMutex, WordFilterAction};
+use regex::{Regex, RegexBuilder};
+
+/// Limit on the compiled size of a single pattern, so a bad pattern in the config can't take up too much memory.
+const MAX_PATTERN_SIZE: usize = 1 << 20;
+
+/// What has to happen to a chat message after checking it against the filter.
+pub enum FilterVerdict {
+    /// nothing matched, the message can be sent as-is
+    Clean,
+    /// the matches were replaced with asterisks, this should be sent instead
+    Censored(String),
+    /// the message must not be sent
+    Drop,
+    /// the message must not be sent, and the player should be muted for the given duration
+    Mute(Duration),
+}
+
+#[derive(Default)]
+struct CompiledFilter {
+    words: Option<AhoCorasick>,
+    patterns: Vec<Regex>,
+    action: WordFilterAction,
+    mute_duration: Duration,
+}
+
+/// The chat word filter from the central server configuration, applied to every chat message before it is broadcast.
+/// Unlike the word list of the game server, it can also hold regular expressions, and it applies regardless of the filter level of the room.
+#[derive(Default)]
+pub struct FilterManager {
+    inner: SyncMutex<CompiledFilter>,
+}
+
+impl FilterManager {
+    pub fn refresh_from(&self, gsbd: &GameServerBootData) {
+        let conf = &gsbd.chat_word_filter;
+
+        let words = conf.words.iter().map(|x| x.trim()).filter(|x| !x.is_empty()).collect::<Vec<_>>();
+        let words = if words.is_empty() {
+            None
+        } else {
+            match AhoCorasick::builder().ascii_case_insensitive(true).build(&words) {
+                Ok(x) => Some(x),
+                Err(e) => {
+                    warn!("failed to build the chat word filter, ignoring its words: {e}");
+                    None
+                }
+            }
+        };
+
+        let patterns = conf
+            .patterns
+            .iter()
+            .filter_map(
+                |pattern| match RegexBuilder::new(pattern).case_insensitive(true).size_limit(MAX_PATTERN_SIZE).build() {
+                    Ok(x) => Some(x),
+                    Err(e) => {
+                        warn!("ignoring invalid pattern in chat_word_filter: {pattern} ({e})");
+                        None
+                    }
+                },
+            )
+            .collect();
+
+        *self.inner.lock() = CompiledFilter {
+            words,
+            patterns,
+            action: conf.action,
+            mute_duration: Duration::from_secs(u64::from(conf.mute_duration)),
+        };
+    }
+
+    pub fn check(&self, message: &str) -> FilterVerdict {
+        let filter = self.inner.lock();
+
+        let mut matches = Vec::new();
+
+        if let Some(words) = &filter.words {
+            matches.extend(words.find_iter(message).map(|x| (x.start(), x.end())));
+        }
+
+        for pattern in &filter.patterns {
+            matches.extend(pattern.find_iter(message).filter(|x| !x.is_empty()).map(|x| (x.start(), x.end())));
+        }
+
+        if matches.is_empty() {
+            return FilterVerdict::Clean;
+        }
+
+        match filter.action {
+            WordFilterAction::Censor => FilterVerdict::Censored(censor(message, &mut matches)),
+            WordFilterAction::Drop => FilterVerdict::Drop,
+            WordFilterAction::Mute => FilterVerdict::Mute(filter.mute_duration),
+        }
+    }
+
+    /// Amount of words and valid patterns in the filter.
+    pub fn len(&self) -> usize {
+        let filter = self.inner.lock();
+        filter.words.as_ref().map_or(0, AhoCorasick::patterns_len) + filter.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Replaces the given byte ranges of `content` with asterisks, ranges can overlap.
+fn censor(content: &str, ranges: &mut [(usize, usize)]) -> String {
+    ranges.sort_unstable();
+
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+
+    for &(start, end) in ranges.iter() {
+        if end <= last {
+            continue;
+        }
+
+        let start = start.max(last);
+
+        out.push_str(&content[last..start]);
+        out.push_str(&"*".repeat(content[start..end].chars().count()));
+        last = end;
+    }
+
+    out.push_str(&content[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use globed_shared::ChatWordFilter;
+
+    use super::*;
+
+    fn filter(words: &[&str], patterns: &[&str], action: WordFilterAction) -> FilterManager {
+        let gsbd = GameServerBootData {
+            chat_word_filter: ChatWordFilter {
+                words: words.iter().map(ToString::to_string).collect(),
+                patterns: patterns.iter().map(ToString::to_string).collect(),
+                action,
+                mute_duration: 60,
+            },
+            ..Default::default()
+        };
+
+        let filter = FilterManager::default();
+        filter.refresh_from(&gsbd);
+        filter
+    }
+
+    #[test]
+    fn censors_matches() {
+        let filter = filter(&["bad", " ", "worse"], &[r"\d{3}-\d{4}"], WordFilterAction::Censor);
+        assert_eq!(filter.len(), 3);
+
+        assert!(matches!(filter.check("all good here"), FilterVerdict::Clean));
+        assert!(matches!(filter.check("BAD and Worse"), FilterVerdict::Censored(x) if x == "*** and *****"));
+        assert!(matches!(filter.check("call 555-1234"), FilterVerdict::Censored(x) if x == "call ********"));
+    }
+
+    #[test]
+    fn censors_overlapping_and_unicode_matches() {
+        let filter = filter(&["abc", "żółw"], &["bcd"], WordFilterAction::Censor);
+
+        assert!(matches!(filter.check("xabcdx"), FilterVerdict::Censored(x) if x == "x****x"));
+        assert!(matches!(filter.check("a żółw!"), FilterVerdict::Censored(x) if x == "a ****!"));
+    }
+
+    #[test]
+    fn drops_matches() {
+        let filter = filter(&["bad"], &[], WordFilterAction::Drop);
+
+        assert!(matches!(filter.check("fine"), FilterVerdict::Clean));
+        assert!(matches!(filter.check("so bad"), FilterVerdict::Drop));
+    }
+
+    #[test]
+    fn mutes_on_match() {
+        let filter = filter(&[], &["^spam+$"], WordFilterAction::Mute);
+
+        assert!(matches!(filter.check("spam and eggs"), FilterVerdict::Clean));
+        assert!(matches!(filter.check("SPAMMMM"), FilterVerdict::Mute(d) if d == Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn ignores_invalid_and_empty_patterns() {
+        let filter = filter(&[], &["(unclosed", "x*"], WordFilterAction::Drop);
+        assert_eq!(filter.len(), 1);
+
+        // `x*` matches an empty string everywhere, which must not count as a match
+        assert!(matches!(filter.check("hello"), FilterVerdict::Clean));
+        assert!(matches!(filter.check("xx"), FilterVerdict::Drop));
+    }
+
+    #[test]
+    fn empty_filter() {
+        let filter = FilterManager::default();
+
+        assert!(filter.is_empty());
+        assert!(matches!(filter.check("anything"), FilterVerdict::Clean));
+    }
+}
@@ -0,0 +1,82 @@
+use globed_shared::{IntMap, SyncMutex};
+
+use crate::data::MAX_FRIEND_LIST_SIZE;
+
+#[derive(Default)]
+struct FriendIndex {
+    /// account : its friend list, sorted
+    lists: IntMap<i32, Vec<i32>>,
+    /// account : accounts that have it in their friend list
+    listed_by: IntMap<i32, Vec<i32>>,
+}
+
+/// The Geometry Dash friend lists of online players, uploaded by the client after logging in.
+/// Players are told when anyone on their list comes online, goes offline or moves around, and mutual friends
+/// (who have each other on their lists) can see which level the other one is on, even from a different room.
+#[derive(Default)]
+pub struct FriendManager {
+    index: SyncMutex<FriendIndex>,
+}
+
+impl FriendManager {
+    /// Replaces the friend list of the player, an empty list removes it.
+    pub fn set_friends(&self, account_id: i32, friends: &[i32]) {
+        let mut index = self.index.lock();
+        Self::remove_list(&mut index, account_id);
+
+        if friends.is_empty() {
+            return;
+        }
+
+        let mut friends = friends.to_vec();
+        friends.sort_unstable();
+        friends.dedup();
+        friends.retain(|id| *id != account_id);
+        friends.truncate(MAX_FRIEND_LIST_SIZE);
+
+        for friend in &friends {
+            index.listed_by.entry(*friend).or_default().push(account_id);
+        }
+
+        index.lists.insert(account_id, friends);
+    }
+
+    /// Removes the friend list of a player, must be called once they disconnect.
+    pub fn remove(&self, account_id: i32) {
+        Self::remove_list(&mut self.index.lock(), account_id);
+    }
+
+    /// Returns the friend list of the player.
+    pub fn friends_of(&self, account_id: i32) -> Vec<i32> {
+        self.index.lock().lists.get(&account_id).cloned().unwrap_or_default()
+    }
+
+    /// Returns the online players that have the given account on their friend list.
+    pub fn listed_by(&self, account_id: i32) -> Vec<i32> {
+        self.index.lock().listed_by.get(&account_id).cloned().unwrap_or_default()
+    }
+
+    /// Whether both players are online and have each other on their friend lists.
+    pub fn are_mutual(&self, first: i32, second: i32) -> bool {
+        let index = self.index.lock();
+        let lists = |a: i32, b: i32| index.lists.get(&a).is_some_and(|list| list.binary_search(&b).is_ok());
+
+        lists(first, second) && lists(second, first)
+    }
+
+    fn remove_list(index: &mut FriendIndex, account_id: i32) {
+        let Some(friends) = index.lists.remove(&account_id) else {
+            return;
+        };
+
+        for friend in friends {
+            if let Some(listed_by) = index.listed_by.get_mut(&friend) {
+                listed_by.retain(|id| *id != account_id);
+
+                if listed_by.is_empty() {
+                    index.listed_by.remove(&friend);
+                }
+            }
+        }
+    }
+}
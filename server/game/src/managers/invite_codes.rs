@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use globed_shared::{generate_alphanum_string, SyncMutex};
+
+/// How many invite codes can exist at once.
+pub const MAX_INVITE_CODES: usize = 64;
+/// How many accounts a single invite code can whitelist at most.
+pub const MAX_INVITE_CODE_USES: u32 = 100;
+pub const INVITE_CODE_LENGTH: usize = 10;
+
+/// Whitelist invite codes minted by admins. A player who isn't whitelisted yet can enter one when logging in,
+/// which whitelists their account and uses up the code once. Codes are only kept in memory, so they don't survive a restart.
+#[derive(Default)]
+pub struct InviteCodeManager {
+    /// code : uses left
+    codes: SyncMutex<HashMap<String, u32>>,
+}
+
+impl InviteCodeManager {
+    /// Creates a code that can be used `uses` times, or returns `None` if there are already too many codes.
+    pub fn create(&self, uses: u32) -> Option<String> {
+        let mut codes = self.codes.lock();
+        if codes.len() >= MAX_INVITE_CODES {
+            return None;
+        }
+
+        let code = loop {
+            let code = generate_alphanum_string(INVITE_CODE_LENGTH).to_uppercase();
+            if !codes.contains_key(&code) {
+                break code;
+            }
+        };
+
+        codes.insert(code.clone(), uses.clamp(1, MAX_INVITE_CODE_USES));
+
+        Some(code)
+    }
+
+    /// Uses up the code once, returns `false` if it doesn't exist or has no uses left. Codes are not case sensitive.
+    pub fn redeem(&self, code: &str) -> bool {
+        let code = code.trim().to_uppercase();
+        let mut codes = self.codes.lock();
+
+        let Some(uses) = codes.get_mut(&code) else {
+            return false;
+        };
+
+        *uses -= 1;
+        if *uses == 0 {
+            codes.remove(&code);
+        }
+
+        true
+    }
+
+    /// Gives a use back to a code that was redeemed, for when whitelisting the account failed afterwards.
+    pub fn refund(&self, code: &str) {
+        *self.codes.lock().entry(code.trim().to_uppercase()).or_insert(0) += 1;
+    }
+}
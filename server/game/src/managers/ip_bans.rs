@@ -0,0 +1,90 @@
+use std::net::IpAddr;
+
+use globed_shared::{warn, GameServerBootData, SyncMutex};
+use ipnet::{Ipv4Net, Ipv6Net};
+use iprange::IpRange;
+
+struct BannedRanges {
+    v4: IpRange<Ipv4Net>,
+    v6: IpRange<Ipv6Net>,
+    /// amount of entries in the configuration, before overlapping ranges were merged
+    entries: usize,
+}
+
+impl Default for BannedRanges {
+    fn default() -> Self {
+        Self {
+            v4: IpRange::new(),
+            v6: IpRange::new(),
+            entries: 0,
+        }
+    }
+}
+
+/// Addresses and ranges banned in the central server configuration. They are checked right after a connection is accepted,
+/// before anything is spawned for it, so banned hosts can't take up resources with repeated handshakes.
+#[derive(Default)]
+pub struct IpBanManager {
+    inner: SyncMutex<BannedRanges>,
+}
+
+/// Parses a single address (`1.2.3.4`) or a range in CIDR notation (`1.2.3.0/24`).
+fn parse_entry(entry: &str, ranges: &mut BannedRanges) -> bool {
+    let entry = entry.trim();
+
+    if let Ok(addr) = entry.parse::<IpAddr>() {
+        match addr {
+            IpAddr::V4(addr) => {
+                ranges.v4.add(Ipv4Net::from(addr));
+            }
+            IpAddr::V6(addr) => {
+                ranges.v6.add(Ipv6Net::from(addr));
+            }
+        }
+    } else if let Ok(net) = entry.parse::<Ipv4Net>() {
+        ranges.v4.add(net);
+    } else if let Ok(net) = entry.parse::<Ipv6Net>() {
+        ranges.v6.add(net);
+    } else {
+        return false;
+    }
+
+    true
+}
+
+impl IpBanManager {
+    pub fn refresh_from(&self, gsbd: &GameServerBootData) {
+        let mut ranges = BannedRanges::default();
+
+        for entry in &gsbd.banned_ips {
+            if parse_entry(entry, &mut ranges) {
+                ranges.entries += 1;
+            } else {
+                warn!("ignoring invalid entry in banned_ips: {entry}");
+            }
+        }
+
+        ranges.v4.simplify();
+        ranges.v6.simplify();
+
+        *self.inner.lock() = ranges;
+    }
+
+    pub fn is_banned(&self, address: IpAddr) -> bool {
+        let inner = self.inner.lock();
+
+        match address {
+            IpAddr::V4(addr) => inner.v4.contains(&addr),
+            IpAddr::V6(addr) => inner.v6.contains(&addr),
+        }
+    }
+
+    /// Amount of banned addresses and ranges in the configuration.
+    pub fn len(&self) -> usize {
+        self.inner.lock().entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
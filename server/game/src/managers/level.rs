@@ -5,11 +5,20 @@ use crate::data::{
     PlayerMetadata,
 };
 
+/// Chat on a level is only split into channels once more than this many players are on it.
+pub const CHAT_CHANNEL_THRESHOLD: usize = 60;
+/// How many players a single chat channel is meant for, new players are put in the emptiest channel.
+pub const CHAT_CHANNEL_SIZE: usize = 40;
+
 #[derive(Default)]
 pub struct LevelManagerPlayer {
     pub account_id: i32,
     pub data: PlayerData,
     pub meta: PlayerMetadata,
+    /// whether the player's level is hidden from room player lists, level lists and player counts
+    pub level_hidden: bool,
+    /// chat channel on the current level, only matters if the level has more than `CHAT_CHANNEL_THRESHOLD` players
+    pub chat_channel: u32,
 }
 
 impl LevelManagerPlayer {
@@ -20,7 +29,7 @@ impl LevelManagerPlayer {
         }
     }
 
-    pub fn to_borrowed_associated_data(&self) -> BorrowedAssociatedPlayerData {
+    pub fn to_borrowed_associated_data(&self) -> BorrowedAssociatedPlayerData<'_> {
         BorrowedAssociatedPlayerData {
             account_id: self.account_id,
             data: &self.data,
@@ -34,7 +43,7 @@ impl LevelManagerPlayer {
         }
     }
 
-    pub fn to_borrowed_associated_meta(&self) -> BorrowedAssociatedPlayerMetadata {
+    pub fn to_borrowed_associated_meta(&self) -> BorrowedAssociatedPlayerMetadata<'_> {
         BorrowedAssociatedPlayerMetadata {
             account_id: self.account_id,
             data: &self.meta,
@@ -105,6 +114,91 @@ impl LevelManager {
         self.levels.get(&level_id).map(Vec::len)
     }
 
+    /// get the amount of players on a level that don't hide their presence on it
+    pub fn get_visible_player_count_on_level(&self, level_id: LevelId) -> usize {
+        self.levels.get(&level_id).map_or(0, |ids| {
+            ids.iter()
+                .filter(|id| self.players.get(id).is_none_or(|player| !player.level_hidden))
+                .count()
+        })
+    }
+
+    /// set whether others can see which level the player is on, inserting a new entry if it doesn't already exist
+    pub fn set_level_hidden(&mut self, account_id: i32, hidden: bool) {
+        self.get_or_create_player(account_id).level_hidden = hidden;
+    }
+
+    /// get the account IDs of all players that hide which level they are on
+    pub fn get_hidden_players(&self) -> Vec<i32> {
+        self.players
+            .values()
+            .filter(|player| player.level_hidden)
+            .map(|player| player.account_id)
+            .collect()
+    }
+
+    /// get the amount of chat channels on a level, or 0 if the level doesn't have enough players for its chat to be split
+    pub fn get_chat_channel_count(&self, level_id: LevelId) -> u32 {
+        let players = self.get_player_count_on_level(level_id).unwrap_or(0);
+
+        if players > CHAT_CHANNEL_THRESHOLD {
+            players.div_ceil(CHAT_CHANNEL_SIZE) as u32
+        } else {
+            0
+        }
+    }
+
+    /// get the chat channel of a player on a level, or `None` if chat on the level isn't split into channels
+    pub fn get_chat_channel(&self, level_id: LevelId, account_id: i32) -> Option<u32> {
+        if self.get_chat_channel_count(level_id) == 0 {
+            None
+        } else {
+            self.players.get(&account_id).map(|player| player.chat_channel)
+        }
+    }
+
+    /// whether a player can see chat messages sent in the given channel, see `get_chat_channel`
+    pub fn is_in_chat_channel(&self, account_id: i32, channel: Option<u32>) -> bool {
+        channel.is_none_or(|channel| self.players.get(&account_id).is_some_and(|player| player.chat_channel == channel))
+    }
+
+    /// put a player who just joined a level into the chat channel with the least players.
+    /// channels are assigned even while the level is below the threshold, so they are already balanced once it is reached.
+    pub fn assign_chat_channel(&mut self, level_id: LevelId, account_id: i32) -> u32 {
+        let Some(ids) = self.levels.get(&level_id) else {
+            return 0;
+        };
+
+        let channel_count = ids.len().div_ceil(CHAT_CHANNEL_SIZE).max(1);
+        let mut counts = vec![0usize; channel_count];
+
+        for player in ids.iter().filter(|&&id| id != account_id).filter_map(|id| self.players.get(id)) {
+            if let Some(count) = counts.get_mut((player.chat_channel as usize).wrapping_sub(1)) {
+                *count += 1;
+            }
+        }
+
+        // channels are numbered from 1
+        let channel = counts
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, count)| **count)
+            .map_or(1, |(idx, _)| idx as u32 + 1);
+        self.get_or_create_player(account_id).chat_channel = channel;
+
+        channel
+    }
+
+    /// move a player to another chat channel on their level, returns `false` if the channel doesn't exist
+    pub fn set_chat_channel(&mut self, level_id: LevelId, account_id: i32, channel: u32) -> bool {
+        if channel == 0 || channel > self.get_chat_channel_count(level_id) {
+            return false;
+        }
+
+        self.get_or_create_player(account_id).chat_channel = channel;
+        true
+    }
+
     /// get the total amount of players
     pub fn get_total_player_count(&self) -> usize {
         self.players.len()
@@ -152,6 +246,23 @@ impl LevelManager {
         }
     }
 
+    /// move a player to a level (or out of all levels if `level_id` is 0), removing them from every other level they are on.
+    /// unlike a `remove_from_level` + `add_to_level` pair, this can't leave the player counted on two levels
+    /// if the level they were previously on is out of date.
+    pub fn move_to_level(&mut self, level_id: LevelId, account_id: i32) {
+        self.levels.retain(|&id, players| {
+            if id != level_id {
+                players.retain(|&x| x != account_id);
+            }
+
+            !players.is_empty()
+        });
+
+        if level_id != 0 {
+            self.add_to_level(level_id, account_id);
+        }
+    }
+
     /// remove a player from a level given a level ID and an account ID
     pub fn remove_from_level(&mut self, level_id: LevelId, account_id: i32) {
         let should_remove_level = self.levels.get_mut(&level_id).is_some_and(|level| {
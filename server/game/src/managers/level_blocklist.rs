@@ -0,0 +1,53 @@
+use globed_shared::{GameServerBootData, IntSet, SyncMutex};
+
+use crate::data::LevelId;
+
+#[derive(Default)]
+struct Blocklist {
+    /// levels blocked in the central server configuration
+    configured: IntSet<LevelId>,
+    /// changes made by moderators at runtime, kept until the server restarts
+    added: IntSet<LevelId>,
+    removed: IntSet<LevelId>,
+}
+
+/// Keeps track of levels that players are not allowed to join, for example levels made for harassing someone.
+#[derive(Default)]
+pub struct LevelBlocklistManager {
+    inner: SyncMutex<Blocklist>,
+}
+
+impl LevelBlocklistManager {
+    pub fn refresh_from(&self, gsbd: &GameServerBootData) {
+        self.inner.lock().configured = gsbd.blocked_levels.iter().copied().collect();
+    }
+
+    pub fn is_blocked(&self, level_id: LevelId) -> bool {
+        let inner = self.inner.lock();
+        inner.added.contains(&level_id) || (inner.configured.contains(&level_id) && !inner.removed.contains(&level_id))
+    }
+
+    /// Blocks or unblocks a level until the server restarts, returns `false` if the level was already in the requested state.
+    pub fn set_blocked(&self, level_id: LevelId, blocked: bool) -> bool {
+        if self.is_blocked(level_id) == blocked {
+            return false;
+        }
+
+        let mut inner = self.inner.lock();
+        let configured = inner.configured.contains(&level_id);
+
+        if blocked {
+            inner.removed.remove(&level_id);
+            if !configured {
+                inner.added.insert(level_id);
+            }
+        } else {
+            inner.added.remove(&level_id);
+            if configured {
+                inner.removed.insert(level_id);
+            }
+        }
+
+        true
+    }
+}
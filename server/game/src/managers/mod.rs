@@ -1,7 +1,47 @@
+mod auth_lockout;
+mod bots;
+mod chat;
+mod chat_tail;
+mod config_overrides;
+mod filter;
+mod friends;
+mod invite_codes;
+mod ip_bans;
 mod level;
+mod level_blocklist;
+mod name_claims;
+mod notice_scheduler;
+mod player_history;
+mod presence;
+mod rate_limit;
+mod recent_players;
 mod role;
 mod room;
+mod server_query;
+mod sessions;
+mod traffic;
+mod usage_stats;
 
+pub use auth_lockout::{AuthFailureBurst, AuthLockoutManager};
+pub use bots::{is_bot_account, Bot, BotManager, MAX_BOTS};
+pub use chat::{ChatManager, SentMessage};
+pub use chat_tail::ChatTailManager;
+pub use config_overrides::{ConfigOverrideManager, MAX_OVERRIDE_DURATION};
+pub use filter::{FilterManager, FilterVerdict};
+pub use friends::FriendManager;
+pub use invite_codes::{InviteCodeManager, MAX_INVITE_CODES, MAX_INVITE_CODE_USES};
+pub use ip_bans::IpBanManager;
 pub use level::LevelManager;
-pub use role::{ComputedRole, GameServerRole, RoleManager};
-pub use room::RoomManager;
+pub use level_blocklist::LevelBlocklistManager;
+pub use name_claims::{ClaimRefusal, NameClaimManager, NAME_CLAIM_DURATION};
+pub use notice_scheduler::{NoticeScheduler, MAX_SCHEDULED_NOTICES};
+pub use player_history::{PlayerCountSample, PlayerHistoryManager};
+pub use presence::PresenceManager;
+pub use rate_limit::{ConnectionSlot, IpLimits, RateLimiter};
+pub use recent_players::{RecentPlayer, RecentPlayerCache, ROOM_REJOIN_WINDOW};
+pub use role::RoleManager;
+pub use room::{Room, RoomManager, RoomQuotaExceeded, RoomWebhook};
+pub use server_query::ServerQueryManager;
+pub use sessions::SessionManager;
+pub use traffic::{TrafficCounter, TrafficStatsManager};
+pub use usage_stats::{UsageStats, UsageStatsManager};
@@ -0,0 +1,163 @@
+use std::time::{Duration, Instant};
+
+use globed_shared::{IntMap, SyncMutex};
+use rustc_hash::FxHashMap;
+
+/// How long a name stays claimed after the last login with it, also the lifetime of standalone session tokens.
+pub const NAME_CLAIM_DURATION: Duration = Duration::from_secs(60 * 60 * 24);
+
+struct NameClaim {
+    name: String,
+    expires_at: Instant,
+}
+
+pub enum ClaimRefusal {
+    /// another account logged in with this name recently
+    NameTaken,
+    /// this account logged in recently, and the login has no valid token to prove it's the same player
+    AccountTaken,
+}
+
+/// On standalone servers account IDs aren't verified, so the first login with a name claims it, and the claim
+/// is then tied to the session token the player got. Logins that would take over a claimed name or account are refused.
+#[derive(Default)]
+pub struct NameClaimManager {
+    /// account ID -> claim
+    claims: SyncMutex<IntMap<i32, NameClaim>>,
+    /// lowercase name -> account ID
+    names: SyncMutex<FxHashMap<String, i32>>,
+}
+
+impl NameClaimManager {
+    /// Checks whether the account could claim the name, without claiming it yet.
+    /// `token_name` is the name in the session token of the login, if it had a valid one for this account.
+    pub fn check(&self, account_id: i32, name: &str, token_name: Option<&str>) -> Result<(), ClaimRefusal> {
+        let claims = self.claims.lock();
+        let names = self.names.lock();
+
+        Self::check_claim(&claims, &names, account_id, &name.to_lowercase(), token_name, Instant::now()).map(|_| ())
+    }
+
+    /// Claims the name for the account, or refreshes the existing claim. Must only be called once the login succeeded.
+    /// `token_name` is the name in the session token of the login, if it had a valid one for this account.
+    pub fn try_claim(&self, account_id: i32, name: &str, token_name: Option<&str>) -> Result<(), ClaimRefusal> {
+        let now = Instant::now();
+        let key = name.to_lowercase();
+
+        let mut claims = self.claims.lock();
+        let mut names = self.names.lock();
+
+        if let Some(owner) = Self::check_claim(&claims, &names, account_id, &key, token_name, now)? {
+            claims.remove(&owner);
+        }
+
+        // the player changed their name, free up the old one
+        if let Some(claim) = claims.get(&account_id) {
+            if claim.name != key {
+                names.remove(&claim.name);
+            }
+        }
+
+        names.insert(key.clone(), account_id);
+        claims.insert(
+            account_id,
+            NameClaim {
+                name: key,
+                expires_at: now + NAME_CLAIM_DURATION,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Returns the account whose claim on the name has to be dropped, if another account has it.
+    fn check_claim(
+        claims: &IntMap<i32, NameClaim>,
+        names: &FxHashMap<String, i32>,
+        account_id: i32,
+        key: &str,
+        token_name: Option<&str>,
+        now: Instant,
+    ) -> Result<Option<i32>, ClaimRefusal> {
+        let live = |account_id: &i32| claims.get(account_id).is_some_and(|claim| claim.expires_at > now);
+
+        // claims are only kept in memory, so a token with this name is the only proof that the name was
+        // claimed by this account before the server restarted, in which case it wins over any newer claim
+        let owns_name = token_name.is_some_and(|x| x.to_lowercase() == key);
+
+        let previous_owner = names.get(key).copied().filter(|owner| *owner != account_id);
+        if previous_owner.as_ref().is_some_and(live) && !owns_name {
+            return Err(ClaimRefusal::NameTaken);
+        }
+
+        if live(&account_id) && token_name.is_none() {
+            return Err(ClaimRefusal::AccountTaken);
+        }
+
+        Ok(previous_owner)
+    }
+
+    /// Forgets expired claims.
+    pub fn sweep(&self) {
+        let now = Instant::now();
+
+        let mut claims = self.claims.lock();
+        let mut names = self.names.lock();
+
+        claims.retain(|account_id, claim| {
+            let live = claim.expires_at > now;
+            if !live && names.get(&claim.name) == Some(account_id) {
+                names.remove(&claim.name);
+            }
+            live
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_taken_names() {
+        let claims = NameClaimManager::default();
+        assert!(claims.try_claim(1, "Player", None).is_ok());
+
+        assert!(matches!(claims.check(2, "player", None), Err(ClaimRefusal::NameTaken)));
+        assert!(matches!(claims.try_claim(2, "PLAYER", None), Err(ClaimRefusal::NameTaken)));
+        assert!(matches!(claims.try_claim(1, "Player", None), Err(ClaimRefusal::AccountTaken)));
+        assert!(claims.try_claim(1, "Player", Some("Player")).is_ok());
+    }
+
+    #[test]
+    fn check_does_not_claim() {
+        let claims = NameClaimManager::default();
+        assert!(claims.check(1, "player", None).is_ok());
+        assert!(claims.try_claim(2, "player", None).is_ok());
+    }
+
+    #[test]
+    fn renaming_frees_old_name() {
+        let claims = NameClaimManager::default();
+        assert!(claims.try_claim(1, "old", None).is_ok());
+        assert!(claims.try_claim(1, "new", Some("old")).is_ok());
+
+        assert!(claims.try_claim(2, "old", None).is_ok());
+        assert!(matches!(claims.try_claim(2, "new", Some("old")), Err(ClaimRefusal::NameTaken)));
+    }
+
+    #[test]
+    fn token_reclaims_name_after_restart() {
+        // the claims of the owner are gone after a restart, and someone else took the name first
+        let claims = NameClaimManager::default();
+        assert!(claims.try_claim(2, "player", None).is_ok());
+
+        // a token for a different name proves nothing about this one
+        assert!(matches!(claims.try_claim(1, "player", Some("other")), Err(ClaimRefusal::NameTaken)));
+        assert!(matches!(claims.try_claim(1, "player", None), Err(ClaimRefusal::NameTaken)));
+
+        assert!(claims.try_claim(1, "player", Some("Player")).is_ok());
+        assert!(matches!(claims.try_claim(2, "player", None), Err(ClaimRefusal::NameTaken)));
+        assert!(claims.try_claim(2, "someone", None).is_ok());
+    }
+}
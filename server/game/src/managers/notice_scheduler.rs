@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use globed_shared::SyncMutex;
+
+use crate::data::*;
+
+/// How many notices can be waiting to be sent at once.
+pub const MAX_SCHEDULED_NOTICES: usize = 64;
+
+/// Keeps notices until they are due. Notices only live in memory, so they are lost when the server restarts.
+#[derive(Default)]
+pub struct NoticeScheduler {
+    notices: SyncMutex<Vec<ScheduledNotice>>,
+    next_id: AtomicU32,
+}
+
+impl NoticeScheduler {
+    /// Schedules the notice and returns its ID, or `None` if there are already too many scheduled notices.
+    /// The ID of the passed notice is ignored.
+    pub fn schedule(&self, mut notice: ScheduledNotice) -> Option<u32> {
+        let mut notices = self.notices.lock();
+        if notices.len() >= MAX_SCHEDULED_NOTICES {
+            return None;
+        }
+
+        notice.id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let id = notice.id;
+
+        notices.push(notice);
+        notices.sort_by_key(|x| x.send_at);
+
+        Some(id)
+    }
+
+    /// Removes the notice with the given ID and returns it, if it wasn't sent yet.
+    pub fn cancel(&self, id: u32) -> Option<ScheduledNotice> {
+        let mut notices = self.notices.lock();
+        let idx = notices.iter().position(|x| x.id == id)?;
+
+        Some(notices.remove(idx))
+    }
+
+    pub fn get(&self, id: u32) -> Option<ScheduledNotice> {
+        self.notices.lock().iter().find(|x| x.id == id).cloned()
+    }
+
+    /// Returns all notices that weren't sent yet, the earliest first.
+    pub fn list(&self) -> Vec<ScheduledNotice> {
+        self.notices.lock().clone()
+    }
+
+    /// Removes and returns all notices that are due at the given time (seconds since unix epoch).
+    pub fn take_due(&self, now: i64) -> Vec<ScheduledNotice> {
+        let mut notices = self.notices.lock();
+        let due = notices.partition_point(|x| x.send_at <= now);
+
+        notices.drain(..due).collect()
+    }
+}
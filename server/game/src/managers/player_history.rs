@@ -0,0 +1,85 @@
+use std::{
+    cmp::Reverse,
+    collections::VecDeque,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use globed_shared::{unix_timestamp, IntMap, SyncMutex};
+
+use crate::data::LevelId;
+
+/// Only the busiest levels are kept in a sample, so that samples don't grow with the amount of levels being played.
+const MAX_SAMPLE_LEVELS: usize = 16;
+
+#[derive(Clone)]
+pub struct PlayerCountSample {
+    /// seconds since unix epoch
+    pub timestamp: i64,
+    pub total: u32,
+    /// player count of every room, except the global room
+    pub rooms: Vec<(u32, u32)>,
+    /// player count of the busiest levels, across all rooms
+    pub levels: Vec<(LevelId, u32)>,
+}
+
+impl PlayerCountSample {
+    /// Returns the player count on the given level if it's not 0, otherwise in the given room if it's not 0, otherwise the total.
+    /// Levels and rooms that aren't in the sample count as empty.
+    pub fn count_for(&self, room_id: u32, level_id: LevelId) -> u32 {
+        if level_id != 0 {
+            self.levels.iter().find(|(id, _)| *id == level_id).map_or(0, |(_, count)| *count)
+        } else if room_id != 0 {
+            self.rooms.iter().find(|(id, _)| *id == room_id).map_or(0, |(_, count)| *count)
+        } else {
+            self.total
+        }
+    }
+}
+
+/// Ring buffer of player counts sampled at a regular interval, oldest samples are dropped first.
+#[derive(Default)]
+pub struct PlayerHistoryManager {
+    samples: SyncMutex<VecDeque<PlayerCountSample>>,
+    capacity: AtomicUsize,
+}
+
+impl PlayerHistoryManager {
+    pub fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+
+        let mut samples = self.samples.lock();
+        while samples.len() > capacity {
+            samples.pop_front();
+        }
+    }
+
+    pub fn record(&self, total: u32, rooms: Vec<(u32, u32)>, levels: IntMap<LevelId, u32>) {
+        let capacity = self.capacity.load(Ordering::Relaxed);
+        if capacity == 0 {
+            return;
+        }
+
+        let mut levels = levels.into_iter().collect::<Vec<_>>();
+        levels.sort_unstable_by_key(|&(_, count)| Reverse(count));
+        levels.truncate(MAX_SAMPLE_LEVELS);
+
+        let sample = PlayerCountSample {
+            timestamp: unix_timestamp(),
+            total,
+            rooms,
+            levels,
+        };
+
+        let mut samples = self.samples.lock();
+        if samples.len() >= capacity {
+            samples.pop_front();
+        }
+
+        samples.push_back(sample);
+    }
+
+    /// Returns all samples recorded after the given time (seconds since unix epoch), oldest first.
+    pub fn samples_since(&self, since: i64) -> Vec<PlayerCountSample> {
+        self.samples.lock().iter().filter(|x| x.timestamp > since).cloned().collect()
+    }
+}
@@ -0,0 +1,73 @@
+use globed_shared::{IntMap, SyncMutex};
+
+use crate::data::MAX_PRESENCE_SUBSCRIPTIONS;
+
+#[derive(Default)]
+struct PresenceIndex {
+    /// watched account : accounts that want to know about it
+    watchers: IntMap<i32, Vec<i32>>,
+    /// subscriber : accounts it watches
+    subscriptions: IntMap<i32, Vec<i32>>,
+}
+
+/// Keeps track of which players want to know when other players come online, go offline or move around,
+/// indexed by the watched account, so that finding who to tell about a change doesn't need to go through every subscription.
+#[derive(Default)]
+pub struct PresenceManager {
+    index: SyncMutex<PresenceIndex>,
+}
+
+impl PresenceManager {
+    /// Replaces the accounts watched by the subscriber, an empty list removes the subscription.
+    pub fn subscribe(&self, subscriber: i32, accounts: &[i32]) {
+        let mut index = self.index.lock();
+        Self::remove_subscriber(&mut index, subscriber);
+
+        if accounts.is_empty() {
+            return;
+        }
+
+        let mut accounts = accounts.to_vec();
+        accounts.sort_unstable();
+        accounts.dedup();
+        accounts.retain(|id| *id != subscriber);
+        accounts.truncate(MAX_PRESENCE_SUBSCRIPTIONS);
+
+        for account_id in &accounts {
+            index.watchers.entry(*account_id).or_default().push(subscriber);
+        }
+
+        index.subscriptions.insert(subscriber, accounts);
+    }
+
+    /// Removes the subscription of a player, must be called once they disconnect.
+    pub fn unsubscribe(&self, subscriber: i32) {
+        Self::remove_subscriber(&mut self.index.lock(), subscriber);
+    }
+
+    /// Returns the accounts that want to know about changes to the given account.
+    pub fn subscribers_of(&self, account_id: i32) -> Vec<i32> {
+        self.index.lock().watchers.get(&account_id).cloned().unwrap_or_default()
+    }
+
+    /// Returns the accounts the subscriber watches.
+    pub fn subscriptions_of(&self, subscriber: i32) -> Vec<i32> {
+        self.index.lock().subscriptions.get(&subscriber).cloned().unwrap_or_default()
+    }
+
+    fn remove_subscriber(index: &mut PresenceIndex, subscriber: i32) {
+        let Some(accounts) = index.subscriptions.remove(&subscriber) else {
+            return;
+        };
+
+        for account_id in accounts {
+            if let Some(watchers) = index.watchers.get_mut(&account_id) {
+                watchers.retain(|id| *id != subscriber);
+
+                if watchers.is_empty() {
+                    index.watchers.remove(&account_id);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,511 @@
+use std::{
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use globed_shared::{debug, warn, IntMap, SyncMutex};
+
+use crate::util::address_key;
+
+const DEFAULT_MAX_CONNECTIONS: usize = 16;
+const DEFAULT_MAX_LOGINS: u32 = 20;
+const DEFAULT_MAX_PACKETS: u32 = 2000;
+
+const LOGIN_WINDOW: Duration = Duration::from_secs(60);
+const PACKET_WINDOW: Duration = Duration::from_secs(1);
+/// Length of the first block, every next offense doubles it.
+const BASE_BLOCK: Duration = Duration::from_secs(60);
+const MAX_BLOCK: Duration = Duration::from_secs(60 * 60);
+/// Offenses are forgotten after an address behaved for this long.
+const OFFENSE_MEMORY: Duration = Duration::from_secs(60 * 60);
+
+/// Limits per IP address, read from environment variables. A limit of 0 disables it.
+pub struct IpLimits {
+    /// open connections over any transport
+    pub max_connections: usize,
+    /// login attempts per minute
+    pub max_logins: u32,
+    /// udp packets per second
+    pub max_packets: u32,
+}
+
+fn parse_env<T: std::str::FromStr + std::fmt::Display>(name: &str, default: T) -> T {
+    match std::env::var(name).ok().map(|x| x.trim().parse::<T>()) {
+        None => default,
+        Some(Ok(x)) => x,
+        Some(Err(_)) => {
+            warn!("invalid value for {name}, must be a number, using the default ({default})");
+            default
+        }
+    }
+}
+
+impl IpLimits {
+    pub fn from_env() -> Self {
+        Self {
+            max_connections: parse_env("GLOBED_GS_MAX_CONNECTIONS_PER_IP", DEFAULT_MAX_CONNECTIONS),
+            max_logins: parse_env("GLOBED_GS_MAX_LOGINS_PER_IP", DEFAULT_MAX_LOGINS),
+            max_packets: parse_env("GLOBED_GS_MAX_PACKETS_PER_IP", DEFAULT_MAX_PACKETS),
+        }
+    }
+
+    /// Returns the limits of this server, read from the environment on the first call.
+    pub fn get() -> &'static Self {
+        static LIMITS: OnceLock<IpLimits> = OnceLock::new();
+        LIMITS.get_or_init(Self::from_env)
+    }
+}
+
+/// Counts events in fixed windows, the count starts over once `window` has passed since the first event.
+struct WindowCounter {
+    count: u32,
+    started: Instant,
+}
+
+impl WindowCounter {
+    fn new(now: Instant) -> Self {
+        Self { count: 0, started: now }
+    }
+
+    /// Counts an event, returns the amount of events in the current window.
+    fn tick(&mut self, now: Instant, window: Duration) -> u32 {
+        if now.saturating_duration_since(self.started) >= window {
+            self.count = 0;
+            self.started = now;
+        }
+
+        self.count = self.count.saturating_add(1);
+        self.count
+    }
+}
+
+/// A block that gets longer with every offense, until the address behaves for `OFFENSE_MEMORY`.
+struct Block {
+    offenses: u32,
+    last_offense: Option<Instant>,
+    until: Instant,
+}
+
+impl Block {
+    fn new(now: Instant) -> Self {
+        Self {
+            offenses: 0,
+            last_offense: None,
+            until: now,
+        }
+    }
+
+    fn is_active(&self, now: Instant) -> bool {
+        now < self.until
+    }
+
+    /// Blocks for a time that doubles with every offense, returns how long the block lasts.
+    fn add_offense(&mut self, now: Instant) -> Duration {
+        if !self.has_recent_offense(now) {
+            self.offenses = 0;
+        }
+
+        let exponent = self.offenses.min(16);
+        let block = BASE_BLOCK.saturating_mul(1 << exponent).min(MAX_BLOCK);
+
+        self.offenses += 1;
+        self.last_offense = Some(now);
+        self.until = now + block;
+
+        block
+    }
+
+    fn has_recent_offense(&self, now: Instant) -> bool {
+        self.last_offense
+            .is_some_and(|last| now.saturating_duration_since(last) <= OFFENSE_MEMORY)
+    }
+
+    fn is_idle(&self, now: Instant) -> bool {
+        !self.is_active(now) && !self.has_recent_offense(now)
+    }
+}
+
+struct AddressRecord {
+    connections: usize,
+    logins: WindowCounter,
+    packets: WindowCounter,
+    /// blocks everything, connections included
+    block: Block,
+    /// only blocks udp packets, so that going over the packet limit doesn't lock a player out of reconnecting
+    packet_block: Block,
+}
+
+impl AddressRecord {
+    fn new(now: Instant) -> Self {
+        Self {
+            connections: 0,
+            logins: WindowCounter::new(now),
+            packets: WindowCounter::new(now),
+            block: Block::new(now),
+            packet_block: Block::new(now),
+        }
+    }
+
+    fn is_blocked(&self, now: Instant) -> bool {
+        self.block.is_active(now)
+    }
+
+    /// Whether the record holds nothing that a fresh one wouldn't.
+    fn is_idle(&self, now: Instant) -> bool {
+        self.connections == 0
+            && self.block.is_idle(now)
+            && self.packet_block.is_idle(now)
+            && now.saturating_duration_since(self.logins.started) >= LOGIN_WINDOW
+    }
+}
+
+/// Limits how many connections, logins and udp packets a single IP address can make,
+/// and temporarily blocks addresses that go over them, for longer every time they do it again.
+pub struct RateLimiter {
+    limits: &'static IpLimits,
+    addresses: SyncMutex<IntMap<u64, AddressRecord>>,
+    /// amount of times an address got blocked
+    blocks: AtomicU64,
+    /// whether loopback addresses skip all limits, set in the soak test mode where every synthetic client connects from loopback
+    exempt_loopback: AtomicBool,
+}
+
+/// An open connection, counted towards the limit of its address until this is dropped.
+pub struct ConnectionSlot<'a> {
+    limiter: &'a RateLimiter,
+    key: u64,
+}
+
+impl Drop for ConnectionSlot<'_> {
+    fn drop(&mut self) {
+        if let Some(record) = self.limiter.addresses.lock().get_mut(&self.key) {
+            record.connections = record.connections.saturating_sub(1);
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self {
+            limits: IpLimits::get(),
+            addresses: SyncMutex::new(IntMap::default()),
+            blocks: AtomicU64::new(0),
+            exempt_loopback: AtomicBool::new(false),
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Makes loopback addresses skip all limits and blocks, used by the soak test mode.
+    pub fn exempt_loopback(&self) {
+        self.exempt_loopback.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the address skips all limits and blocks.
+    pub fn is_exempt(&self, address: IpAddr) -> bool {
+        address.is_loopback() && self.exempt_loopback.load(Ordering::Relaxed)
+    }
+
+    /// Returns a slot for a new connection, or `None` if the address is blocked or already has too many connections open.
+    pub fn try_connect(&self, address: IpAddr) -> Option<ConnectionSlot<'_>> {
+        let key = address_key(address);
+        let now = Instant::now();
+        let exempt = self.is_exempt(address);
+
+        let mut addresses = self.addresses.lock();
+        let record = addresses.entry(key).or_insert_with(|| AddressRecord::new(now));
+
+        // going over the connection limit isn't punished, many players can share an address behind a NAT
+        if !exempt && (record.is_blocked(now) || (self.limits.max_connections != 0 && record.connections >= self.limits.max_connections)) {
+            return None;
+        }
+
+        record.connections += 1;
+
+        Some(ConnectionSlot { limiter: self, key })
+    }
+
+    /// Counts a login attempt, returns `false` if the address is blocked or made too many attempts in the last minute.
+    pub fn try_login(&self, address: IpAddr) -> bool {
+        self.try_tick(address, self.limits.max_logins, LOGIN_WINDOW, |record| {
+            (&mut record.logins, &mut record.block)
+        })
+    }
+
+    /// Counts a udp packet, returns `false` if it should be dropped.
+    /// Going over the packet limit only blocks further packets, not connections.
+    pub fn try_packet(&self, address: IpAddr) -> bool {
+        self.try_tick(address, self.limits.max_packets, PACKET_WINDOW, |record| {
+            (&mut record.packets, &mut record.packet_block)
+        })
+    }
+
+    fn try_tick(
+        &self,
+        address: IpAddr,
+        limit: u32,
+        window: Duration,
+        select: impl Fn(&mut AddressRecord) -> (&mut WindowCounter, &mut Block),
+    ) -> bool {
+        if self.is_exempt(address) {
+            return true;
+        }
+
+        if limit == 0 {
+            return !self.is_blocked(address);
+        }
+
+        let now = Instant::now();
+
+        let mut addresses = self.addresses.lock();
+        let record = addresses.entry(address_key(address)).or_insert_with(|| AddressRecord::new(now));
+
+        if record.is_blocked(now) {
+            return false;
+        }
+
+        let (counter, block) = select(record);
+        if block.is_active(now) {
+            return false;
+        }
+
+        let count = counter.tick(now, window);
+        if count <= limit {
+            return true;
+        }
+
+        let block = block.add_offense(now);
+        self.blocks.fetch_add(1, Ordering::Relaxed);
+        debug!("blocking {address} for {}s, it went over a rate limit", block.as_secs());
+
+        false
+    }
+
+    /// Blocks the address for a time that doubles with every offense, used when one of its clients got disconnected for flooding.
+    pub fn punish(&self, address: IpAddr) {
+        if self.is_exempt(address) {
+            return;
+        }
+
+        let now = Instant::now();
+
+        let block = self
+            .addresses
+            .lock()
+            .entry(address_key(address))
+            .or_insert_with(|| AddressRecord::new(now))
+            .block
+            .add_offense(now);
+
+        self.blocks.fetch_add(1, Ordering::Relaxed);
+        debug!("blocking {address} for {}s after flooding", block.as_secs());
+    }
+
+    pub fn is_blocked(&self, address: IpAddr) -> bool {
+        let now = Instant::now();
+        self.addresses
+            .lock()
+            .get(&address_key(address))
+            .is_some_and(|record| record.is_blocked(now))
+    }
+
+    pub fn block_count(&self) -> u64 {
+        self.blocks.load(Ordering::Relaxed)
+    }
+
+    /// Forgets addresses that have no open connections and no recent offenses.
+    pub fn sweep(&self) {
+        let now = Instant::now();
+        self.addresses.lock().retain(|_, record| !record.is_idle(now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    const ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7));
+
+    fn limiter(max_connections: usize, max_logins: u32, max_packets: u32) -> RateLimiter {
+        RateLimiter {
+            limits: Box::leak(Box::new(IpLimits {
+                max_connections,
+                max_logins,
+                max_packets,
+            })),
+            addresses: SyncMutex::new(IntMap::default()),
+            blocks: AtomicU64::new(0),
+            exempt_loopback: AtomicBool::new(false),
+        }
+    }
+
+    #[test]
+    fn window_resets() {
+        let start = Instant::now();
+        let mut counter = WindowCounter::new(start);
+
+        assert_eq!(counter.tick(start, PACKET_WINDOW), 1);
+        assert_eq!(counter.tick(start + PACKET_WINDOW / 2, PACKET_WINDOW), 2);
+        assert_eq!(counter.tick(start + PACKET_WINDOW, PACKET_WINDOW), 1);
+        assert_eq!(counter.tick(start + PACKET_WINDOW, PACKET_WINDOW), 2);
+    }
+
+    #[test]
+    fn blocks_double_up_to_max() {
+        let start = Instant::now();
+        let mut block = Block::new(start);
+        assert!(!block.is_active(start));
+
+        let mut expected = BASE_BLOCK;
+        let mut now = start;
+        while expected < MAX_BLOCK {
+            assert_eq!(block.add_offense(now), expected);
+            assert!(block.is_active(now + expected - Duration::from_millis(1)));
+            assert!(!block.is_active(now + expected));
+
+            now += expected;
+            expected *= 2;
+        }
+
+        assert_eq!(block.add_offense(now), MAX_BLOCK);
+        assert_eq!(block.add_offense(now + MAX_BLOCK), MAX_BLOCK);
+    }
+
+    #[test]
+    fn offenses_are_forgotten() {
+        let start = Instant::now();
+        let mut block = Block::new(start);
+
+        assert_eq!(block.add_offense(start), BASE_BLOCK);
+        assert_eq!(block.add_offense(start + BASE_BLOCK), BASE_BLOCK * 2);
+
+        let later = start + BASE_BLOCK + OFFENSE_MEMORY + Duration::from_secs(1);
+        assert!(block.is_idle(later));
+        assert_eq!(block.add_offense(later), BASE_BLOCK);
+    }
+
+    #[test]
+    fn idle_records() {
+        let start = Instant::now();
+        let mut record = AddressRecord::new(start);
+        let after_window = start + LOGIN_WINDOW;
+
+        assert!(!record.is_idle(start));
+        assert!(record.is_idle(after_window));
+
+        record.connections = 1;
+        assert!(!record.is_idle(after_window));
+        record.connections = 0;
+
+        record.packet_block.add_offense(start);
+        assert!(!record.is_idle(after_window));
+        assert!(!record.is_idle(start + OFFENSE_MEMORY));
+        assert!(record.is_idle(start + OFFENSE_MEMORY + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn sweep_keeps_busy_addresses() {
+        let limiter = limiter(0, 0, 0);
+        let slot = limiter.try_connect(ADDRESS);
+        assert!(slot.is_some());
+
+        let now = Instant::now();
+        let Some(old) = now.checked_sub(LOGIN_WINDOW) else {
+            return;
+        };
+
+        limiter.addresses.lock().insert(1, AddressRecord::new(old));
+        limiter.addresses.lock().get_mut(&address_key(ADDRESS)).unwrap().logins.started = old;
+
+        limiter.sweep();
+        assert!(!limiter.addresses.lock().contains_key(&1));
+        assert!(limiter.addresses.lock().contains_key(&address_key(ADDRESS)));
+
+        drop(slot);
+        limiter.sweep();
+        assert!(limiter.addresses.lock().is_empty());
+    }
+
+    #[test]
+    fn login_limit_blocks_connections() {
+        let limiter = limiter(0, 2, 0);
+
+        assert!(limiter.try_login(ADDRESS));
+        assert!(limiter.try_login(ADDRESS));
+        assert!(!limiter.try_login(ADDRESS));
+
+        assert!(limiter.is_blocked(ADDRESS));
+        assert!(limiter.try_connect(ADDRESS).is_none());
+        assert!(!limiter.try_packet(ADDRESS));
+        assert_eq!(limiter.block_count(), 1);
+    }
+
+    #[test]
+    fn packet_limit_only_blocks_packets() {
+        let limiter = limiter(0, 0, 3);
+
+        for _ in 0..3 {
+            assert!(limiter.try_packet(ADDRESS));
+        }
+
+        assert!(!limiter.try_packet(ADDRESS));
+        assert!(!limiter.try_packet(ADDRESS));
+        assert_eq!(limiter.block_count(), 1);
+
+        assert!(!limiter.is_blocked(ADDRESS));
+        assert!(limiter.try_connect(ADDRESS).is_some());
+        assert!(limiter.try_login(ADDRESS));
+    }
+
+    #[test]
+    fn connection_limit_is_not_punished() {
+        let limiter = limiter(2, 0, 0);
+
+        let first = limiter.try_connect(ADDRESS);
+        let second = limiter.try_connect(ADDRESS);
+        assert!(first.is_some() && second.is_some());
+        assert!(limiter.try_connect(ADDRESS).is_none());
+        assert!(!limiter.is_blocked(ADDRESS));
+
+        drop(first);
+        assert!(limiter.try_connect(ADDRESS).is_some());
+        assert_eq!(limiter.block_count(), 0);
+    }
+
+    #[test]
+    fn exempt_loopback_skips_limits() {
+        let limiter = limiter(1, 1, 1);
+        let loopback = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+        let first = limiter.try_connect(loopback);
+        assert!(first.is_some());
+        assert!(limiter.try_connect(loopback).is_none());
+
+        limiter.exempt_loopback();
+        assert!(limiter.is_exempt(loopback));
+        assert!(!limiter.is_exempt(ADDRESS));
+
+        let slots: Vec<_> = (0..20).map(|_| limiter.try_connect(loopback)).collect();
+        assert!(slots.iter().all(Option::is_some));
+
+        for _ in 0..20 {
+            assert!(limiter.try_login(loopback));
+            assert!(limiter.try_packet(loopback));
+        }
+
+        limiter.punish(loopback);
+        assert!(!limiter.is_blocked(loopback));
+        assert_eq!(limiter.block_count(), 0);
+
+        // other addresses are still limited
+        let other = limiter.try_connect(ADDRESS);
+        assert!(other.is_some());
+        assert!(limiter.try_connect(ADDRESS).is_none());
+    }
+}
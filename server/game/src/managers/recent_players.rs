@@ -0,0 +1,92 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use globed_shared::{SyncMutex, UserEntry};
+
+use crate::{
+    data::{LevelId, PlayerAccountData},
+    util::LevelHistory,
+};
+
+/// How many players that left are remembered at once. When full, the least recently used one is forgotten.
+const MAX_RECENT_PLAYERS: usize = 256;
+/// How long a player is remembered after leaving.
+const RECENT_PLAYER_TTL: Duration = Duration::from_secs(15 * 60);
+/// How long after leaving a player is put back into their room when they log in again.
+pub const ROOM_REJOIN_WINDOW: Duration = Duration::from_secs(120);
+
+/// A player that disconnected recently, along with where they were.
+#[derive(Clone)]
+pub struct RecentPlayer {
+    pub account_data: PlayerAccountData,
+    pub user_entry: Option<UserEntry>,
+    pub room_id: u32,
+    pub level_id: LevelId,
+    pub level_history: LevelHistory,
+    pub left_at: Instant,
+}
+
+/// Remembers players that just left the server, so they can still be looked up by moderators and other players
+/// for a while, and put back into their room if they come back soon. Only kept in memory.
+#[derive(Default)]
+pub struct RecentPlayerCache {
+    /// ordered from the least to the most recently used
+    players: SyncMutex<VecDeque<RecentPlayer>>,
+}
+
+impl RecentPlayerCache {
+    /// Remembers a player that just left, replacing an older entry of the same account.
+    pub fn insert(&self, player: RecentPlayer) {
+        let mut players = self.players.lock();
+
+        let account_id = player.account_data.account_id;
+        players.retain(|p| p.account_data.account_id != account_id);
+
+        if players.len() >= MAX_RECENT_PLAYERS {
+            players.pop_front();
+        }
+
+        players.push_back(player);
+    }
+
+    /// Finds a player by account ID.
+    pub fn get(&self, account_id: i32) -> Option<RecentPlayer> {
+        self.find_by(|p| p.account_data.account_id == account_id)
+    }
+
+    /// If the passed string is numeric, finds a player by account ID, else by their account name.
+    pub fn find(&self, name: &str) -> Option<RecentPlayer> {
+        if let Ok(account_id) = name.parse::<i32>() {
+            self.get(account_id)
+        } else {
+            self.find_by(|p| p.account_data.name.eq_ignore_ascii_case(name))
+        }
+    }
+
+    /// Forgets a player, returning their entry. Called when they log back in.
+    pub fn remove(&self, account_id: i32) -> Option<RecentPlayer> {
+        let mut players = self.players.lock();
+        Self::remove_expired(&mut players);
+
+        let idx = players.iter().position(|p| p.account_data.account_id == account_id)?;
+        players.remove(idx)
+    }
+
+    fn find_by<F: Fn(&RecentPlayer) -> bool>(&self, f: F) -> Option<RecentPlayer> {
+        let mut players = self.players.lock();
+        Self::remove_expired(&mut players);
+
+        // move the player to the back, as they were just used
+        let idx = players.iter().position(f)?;
+        let player = players.remove(idx)?;
+        players.push_back(player.clone());
+
+        Some(player)
+    }
+
+    fn remove_expired(players: &mut VecDeque<RecentPlayer>) {
+        players.retain(|p| p.left_at.elapsed() < RECENT_PLAYER_TTL);
+    }
+}
@@ -6,36 +6,6 @@ pub struct RoleManager {
     roles: SyncMutex<IntMap<u8, ServerRole>>,
 }
 
-#[derive(Encodable, Decodable, DynamicSize, Default, Clone)]
-pub struct GameServerRole {
-    pub int_id: u8,
-    pub role: ServerRole,
-}
-
-#[derive(Encodable, Decodable, DynamicSize, Default, Clone)]
-#[allow(clippy::struct_excessive_bools)]
-pub struct ComputedRole {
-    pub priority: i32,
-    pub badge_icon: FastString,
-    pub name_color: Option<RichColor>,
-    pub chat_color: Option<Color3B>,
-
-    pub notices: bool,
-    pub notices_to_everyone: bool,
-    pub kick: bool,
-    pub kick_everyone: bool,
-    pub mute: bool,
-    pub ban: bool,
-    pub edit_role: bool,
-    pub admin: bool,
-}
-
-impl ComputedRole {
-    pub fn can_moderate(&self) -> bool {
-        self.notices || self.notices_to_everyone || self.kick || self.kick_everyone || self.mute || self.ban || self.edit_role || self.admin
-    }
-}
-
 impl RoleManager {
     pub fn refresh_from(&self, gsbd: &GameServerBootData) {
         let mut roles = self.roles.lock();
@@ -91,6 +61,17 @@ impl RoleManager {
             .collect()
     }
 
+    /// Converts the role IDs of a player into the int IDs that are sent to other players.
+    pub fn special_user_data(&self, role_ids: &[String]) -> SpecialUserData {
+        if role_ids.is_empty() {
+            SpecialUserData { roles: None }
+        } else {
+            SpecialUserData {
+                roles: Some(self.role_ids_to_int_ids(role_ids)),
+            }
+        }
+    }
+
     pub fn compute(&self, user_roles: &[String]) -> ComputedRole {
         let mut computed = ComputedRole {
             priority: i32::MIN,
@@ -99,11 +80,13 @@ impl RoleManager {
 
         let roles = self.roles.lock();
         for role_id in user_roles {
-            let Some(role) = roles.values().find(|x| x.id == *role_id) else {
+            let Some((int_id, role)) = roles.iter().find(|(_, x)| x.id == *role_id) else {
                 warn!("trying to assign an invalid role to a user: {role_id}");
                 continue;
             };
 
+            computed.role_mask |= 1u64.checked_shl(u32::from(*int_id)).unwrap_or(0);
+
             let is_higher = role.priority > computed.priority;
 
             // if lower, update only if it's empty
@@ -155,6 +138,9 @@ impl RoleManager {
                 computed.ban = true;
                 computed.edit_role = true;
                 computed.admin = true;
+                computed.slow_mode_exempt = true;
+                computed.spawn_bots = true;
+                computed.room_webhooks = true;
             } else {
                 computed.notices |= role.notices;
                 computed.notices_to_everyone |= role.notices_to_everyone;
@@ -163,6 +149,9 @@ impl RoleManager {
                 computed.mute |= role.mute;
                 computed.ban |= role.ban;
                 computed.edit_role |= role.edit_role;
+                computed.slow_mode_exempt |= role.slow_mode_exempt;
+                computed.spawn_bots |= role.spawn_bots;
+                computed.room_webhooks |= role.room_webhooks;
             }
 
             if is_higher {
@@ -211,6 +200,9 @@ impl RoleManager {
             ban: true,
             edit_role: true,
             admin: true,
+            slow_mode_exempt: true,
+            spawn_bots: true,
+            room_webhooks: true,
             ..Default::default()
         }
     }
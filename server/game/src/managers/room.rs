@@ -1,25 +1,48 @@
-use std::sync::OnceLock;
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    net::IpAddr,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
 
 use esp::InlineString;
 use globed_shared::{
-    rand::{self, Rng},
-    IntMap, SyncMutex, SyncMutexGuard,
+    rand::{rngs::OsRng, Rng},
+    warn, IntMap, RoomQuotaLimits, SyncMutex, SyncMutexGuard,
 };
 
 use crate::{
-    data::{LevelId, RoomInfo, RoomListingInfo, RoomSettings, ROOM_ID_LENGTH},
+    data::{ComputedRole, LevelId, RoomInfo, RoomListingInfo, RoomMemberTag, RoomSettings, RoomTag, RoomTags, TransferredRoom, ROOM_ID_LENGTH},
     server::GameServer,
+    util::{address_key, SimpleRateLimiter},
+    webhook::WebhookMessage,
 };
 
-use super::LevelManager;
+use super::{is_bot_account, LevelManager};
 
 #[derive(Default)]
 pub struct Room {
     pub owner: i32,
+    /// players the owner allowed to kick players, mute the chat and change the settings of the room
+    pub moderators: Vec<i32>,
+    /// spectated player -> players spectating them
+    pub spectators: IntMap<i32, Vec<i32>>,
+    /// tags the owner defined, independent of server roles
+    pub tags: Vec<RoomTag>,
+    /// player -> index of their tag in `tags`
+    pub member_tags: IntMap<i32, u8>,
     pub name: InlineString<32>,
     pub password: InlineString<16>,
     pub manager: LevelManager,
     pub settings: RoomSettings,
+    pub webhook: Option<RoomWebhook>,
+}
+
+/// A webhook the owner of a room set up, along with the events that weren't sent to it yet.
+pub struct RoomWebhook {
+    pub url: String,
+    pending: Vec<WebhookMessage>,
 }
 
 #[derive(Default)]
@@ -27,20 +50,125 @@ pub struct RoomManager {
     rooms: SyncMutex<IntMap<u32, Room>>,
     global: SyncMutex<Room>,
     game_server: OnceLock<&'static GameServer>,
+    /// join attempts of nonexistent rooms, per IP address, to prevent enumerating room IDs
+    invalid_joins: SyncMutex<IntMap<u64, SimpleRateLimiter>>,
+    /// rooms created for players moved from another game server and when, by their transfer group
+    transferred_rooms: SyncMutex<IntMap<u64, (u32, Instant)>>,
+    /// rooms that players were in before a warm restart, waiting for them to log back in
+    pending_resumes: SyncMutex<IntMap<i32, u32>>,
+    /// recent room creations and invites of every account, for the room quota
+    quota_usage: SyncMutex<IntMap<i32, QuotaUsage>>,
+}
+
+/// A room quota limit that an account reached, see `RoomQuotaPolicy`.
+pub enum RoomQuotaExceeded {
+    OwnedRooms(u32),
+    Creations(u32),
+    Invites(u32),
 }
 
-// i.e. if ROOM_ID_LENGTH is 6 we should have a range 100_000..1_000_000
-const ROOM_ID_START: u32 = 10_u32.pow(ROOM_ID_LENGTH as u32 - 1);
-const ROOM_ID_END: u32 = 10_u32.pow(ROOM_ID_LENGTH as u32);
+impl Display for RoomQuotaExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OwnedRooms(limit) => write!(f, "You can't own more than {limit} rooms at once"),
+            Self::Creations(limit) => write!(f, "You can only create {limit} rooms per hour, please try again later"),
+            Self::Invites(limit) => write!(
+                f,
+                "You already have {limit} invites that weren't accepted yet, please wait for them to be accepted or expire"
+            ),
+        }
+    }
+}
+
+struct OutstandingInvite {
+    invitee: i32,
+    room_id: u32,
+    sent_at: Instant,
+}
+
+#[derive(Default)]
+struct QuotaUsage {
+    /// when the account created rooms in the last hour, oldest first
+    creations: VecDeque<Instant>,
+    invites: Vec<OutstandingInvite>,
+}
+
+impl QuotaUsage {
+    /// Forgets about creations older than an hour and expired invites, returns `true` if nothing is left.
+    fn prune(&mut self, now: Instant) -> bool {
+        while self.creations.front().is_some_and(|t| now.duration_since(*t) >= ROOM_CREATION_WINDOW) {
+            self.creations.pop_front();
+        }
+
+        self.invites.retain(|invite| now.duration_since(invite.sent_at) < INVITE_LIFETIME);
+
+        self.creations.is_empty() && self.invites.is_empty()
+    }
+}
+
+/// How many events can wait for the next send to a room webhook, anything past that is dropped
+const MAX_PENDING_WEBHOOK_EVENTS: usize = 50;
+/// How many events are sent to a room webhook at once, Discord doesn't accept more embeds in a single message
+const WEBHOOK_BATCH_SIZE: usize = 10;
+
+/// How many moderators a single room can have
+const MAX_ROOM_MODERATORS: usize = 16;
+
+/// How many players can spectate a single player at once
+const MAX_SPECTATORS: usize = 8;
+
+/// The period `creations_per_hour` of the room quota applies to
+const ROOM_CREATION_WINDOW: Duration = Duration::from_secs(60 * 60);
+/// How long an invite counts towards the room quota of the inviter if it isn't accepted
+const INVITE_LIFETIME: Duration = Duration::from_secs(5 * 60);
+
+/// The largest room ID length that still fits in a `u32`
+const MAX_ROOM_ID_LENGTH: u32 = 9;
+
+/// How long a room moved from another game server waits for its owner to arrive, before it is given to someone else.
+/// Longer than the transfer tokens are valid for, so the owner can't arrive after that.
+const TRANSFER_OWNER_WINDOW: Duration = Duration::from_secs(90);
+
+/// How many joins of nonexistent rooms are allowed from a single IP address per `INVALID_JOIN_PERIOD`
+const INVALID_JOIN_LIMIT: usize = 5;
+const INVALID_JOIN_PERIOD: Duration = Duration::from_secs(30);
+
+/// Returns the range room IDs are picked from, based on the amount of digits set in `GLOBED_GS_ROOM_ID_LENGTH`
+/// (by default `ROOM_ID_LENGTH`). For example, with 6 digits this is `100_000..1_000_000`.
+fn room_id_range() -> (u32, u32) {
+    static RANGE: OnceLock<(u32, u32)> = OnceLock::new();
+
+    *RANGE.get_or_init(|| {
+        let default = ROOM_ID_LENGTH as u32;
+
+        let length = match std::env::var("GLOBED_GS_ROOM_ID_LENGTH").ok().map(|x| x.parse::<u32>()) {
+            None => default,
+            Some(Ok(x)) if (default..=MAX_ROOM_ID_LENGTH).contains(&x) => x,
+            Some(_) => {
+                warn!(
+                    "invalid value for GLOBED_GS_ROOM_ID_LENGTH, must be between {default} and {MAX_ROOM_ID_LENGTH}, using the default ({default})"
+                );
+                default
+            }
+        };
+
+        (10_u32.pow(length - 1), 10_u32.pow(length))
+    })
+}
 
 impl Room {
     pub fn new(owner: i32, name: InlineString<32>, password: InlineString<16>, settings: RoomSettings, manager: LevelManager) -> Self {
         Self {
             owner,
+            moderators: Vec::new(),
+            spectators: IntMap::default(),
+            tags: Vec::new(),
+            member_tags: IntMap::default(),
             name,
             password,
             manager,
             settings,
+            webhook: None,
         }
     }
 
@@ -49,11 +177,12 @@ impl Room {
         let was_owner = self.owner == player;
 
         if was_owner {
-            // rotate the owner
-            let mut rotate_to: i32 = 0;
+            // rotate the owner, moderators are picked first
+            let mut rotate_to: i32 = self.moderators.iter().copied().find(|id| *id != player).unwrap_or(0);
             self.manager.for_each_player(
                 |rp, _, rotate_to| {
-                    if *rotate_to == 0 && rp.account_id != player {
+                    // bots can't own rooms
+                    if *rotate_to == 0 && rp.account_id != player && !is_bot_account(rp.account_id) {
                         *rotate_to = rp.account_id;
                     }
                     true
@@ -64,12 +193,138 @@ impl Room {
             self.owner = rotate_to;
         }
 
+        let owner = self.owner;
+        self.moderators.retain(|id| *id != player && *id != owner);
+        self.spectators.remove(&player);
+        self.remove_spectator(player);
+        self.member_tags.remove(&player);
         self.manager.remove_player(player);
 
         was_owner
     }
 
+    /// Forgets moderators and tags of players that aren't in the room, and if the owner isn't in it either,
+    /// gives the room to a moderator that is, or to anyone else otherwise.
+    fn replace_absent_owner(&mut self) {
+        let manager = &self.manager;
+        self.moderators.retain(|id| manager.get_player_data(*id).is_some());
+        self.member_tags.retain(|id, _| manager.get_player_data(*id).is_some());
+
+        if self.manager.get_player_data(self.owner).is_some() {
+            return;
+        }
+
+        // moderators that are here are picked first
+        let mut new_owner = self.moderators.first().copied().unwrap_or(0);
+        self.manager.for_each_player(
+            |rp, _, new_owner| {
+                // bots can't own rooms
+                if *new_owner == 0 && !is_bot_account(rp.account_id) {
+                    *new_owner = rp.account_id;
+                }
+                true
+            },
+            &mut new_owner,
+        );
+
+        self.owner = new_owner;
+        self.moderators.retain(|id| *id != new_owner);
+    }
+
+    pub fn is_moderator(&self, account_id: i32) -> bool {
+        self.moderators.contains(&account_id)
+    }
+
+    /// Whether the player is the owner or one of the moderators of the room.
+    pub fn can_manage(&self, account_id: i32) -> bool {
+        self.owner == account_id || self.is_moderator(account_id)
+    }
+
+    /// Makes the player a moderator or takes the role away, returns `false` if nothing changed or the room already has `MAX_ROOM_MODERATORS`.
+    pub fn set_moderator(&mut self, account_id: i32, moderator: bool) -> bool {
+        if !moderator {
+            let count = self.moderators.len();
+            self.moderators.retain(|id| *id != account_id);
+            return self.moderators.len() != count;
+        }
+
+        if self.can_manage(account_id) || is_bot_account(account_id) || self.moderators.len() >= MAX_ROOM_MODERATORS {
+            return false;
+        }
+
+        self.moderators.push(account_id);
+        true
+    }
+
     #[inline]
+    /// Replaces the tags of the room, players whose tag no longer exists lose it.
+    pub fn set_tags(&mut self, tags: Vec<RoomTag>) {
+        self.tags = tags;
+
+        let count = self.tags.len();
+        self.member_tags.retain(|_, tag| usize::from(*tag) < count);
+    }
+
+    /// Gives the player a tag, or takes it away if `tag` is `None`. Returns `false` if nothing changed or the tag doesn't exist.
+    pub fn set_member_tag(&mut self, account_id: i32, tag: Option<u8>) -> bool {
+        match tag {
+            Some(tag) if usize::from(tag) < self.tags.len() => self.member_tags.insert(account_id, tag) != Some(tag),
+            Some(_) => false,
+            None => self.member_tags.remove(&account_id).is_some(),
+        }
+    }
+
+    pub fn get_tags(&self) -> RoomTags {
+        RoomTags {
+            tags: self.tags.clone(),
+            members: self
+                .member_tags
+                .iter()
+                .map(|(&account_id, &tag)| RoomMemberTag { account_id, tag })
+                .collect(),
+        }
+    }
+
+    /// Makes `spectator` spectate `target`, returns `false` if the target already has `MAX_SPECTATORS` spectators.
+    /// The spectator should stop spectating whoever they spectated before first.
+    pub fn add_spectator(&mut self, target: i32, spectator: i32) -> bool {
+        let spectators = self.spectators.entry(target).or_default();
+
+        if spectators.len() >= MAX_SPECTATORS {
+            return false;
+        }
+
+        spectators.push(spectator);
+        true
+    }
+
+    /// Stops everyone from spectating `target`, returns whether anyone was.
+    pub fn clear_spectators(&mut self, target: i32) -> bool {
+        self.spectators.remove(&target).is_some()
+    }
+
+    /// Stops the player from spectating anyone, returns who they were spectating.
+    pub fn remove_spectator(&mut self, spectator: i32) -> Option<i32> {
+        let (target, spectators) = self.spectators.iter_mut().find(|(_, spectators)| spectators.contains(&spectator))?;
+        let target = *target;
+
+        spectators.retain(|id| *id != spectator);
+        if spectators.is_empty() {
+            self.spectators.remove(&target);
+        }
+
+        Some(target)
+    }
+
+    pub fn get_spectators(&self, target: i32) -> &[i32] {
+        self.spectators.get(&target).map_or(&[][..], Vec::as_slice)
+    }
+
+    /// Returns every spectated player along with how many spectators they have.
+    pub fn get_spectator_counts(&self) -> Vec<(i32, usize)> {
+        self.spectators.iter().map(|(target, spectators)| (*target, spectators.len())).collect()
+    }
+
     pub fn set_settings(&mut self, settings: &RoomSettings) {
         self.settings.clone_from(settings);
     }
@@ -116,6 +371,11 @@ impl Room {
         self.password.is_empty() || self.password == *pwd
     }
 
+    /// whether the room has a limit on how many players can be in it
+    pub fn is_capped(&self) -> bool {
+        self.settings.flags.two_player || self.settings.player_limit != 0
+    }
+
     pub fn is_full(&self) -> bool {
         let player_count = self.manager.get_total_player_count();
 
@@ -175,54 +435,296 @@ impl RoomManager {
         self.rooms.lock()
     }
 
-    /// Creates a new room, adds the given player, removes them from the global room, and returns the room ID
-    pub fn create_room(&self, account_id: i32, name: InlineString<32>, password: InlineString<16>, settings: RoomSettings) -> RoomInfo {
-        let rooms = self.rooms.lock();
+    /// Creates a new room, adds the given player, removes them from the global room, and returns the room ID.
+    /// Fails if the player reached one of the limits of their room quota.
+    pub fn create_room(
+        &self,
+        account_id: i32,
+        name: InlineString<32>,
+        password: InlineString<16>,
+        settings: RoomSettings,
+        limits: &RoomQuotaLimits,
+    ) -> Result<RoomInfo, RoomQuotaExceeded> {
+        let now = Instant::now();
+
+        // held until the room exists, so multiple sessions of the same account can't create rooms past the limits at the same time
+        let mut quota_usage = self.quota_usage.lock();
+        quota_usage.retain(|_, usage| !usage.prune(now));
+
+        let usage = quota_usage.entry(account_id).or_default();
+        if limits.creations_per_hour != 0 && usage.creations.len() >= limits.creations_per_hour as usize {
+            return Err(RoomQuotaExceeded::Creations(limits.creations_per_hour));
+        }
 
-        // in case we accidentally generate an existing room id, keep looping until we find a suitable id
-        let room_id = loop {
-            let room_id = rand::thread_rng().gen_range(ROOM_ID_START..ROOM_ID_END);
-            if !rooms.contains_key(&room_id) {
-                break room_id;
+        let mut rooms = self.rooms.lock();
+
+        if limits.max_owned_rooms != 0 && rooms.values().filter(|room| room.owner == account_id).count() >= limits.max_owned_rooms as usize {
+            return Err(RoomQuotaExceeded::OwnedRooms(limits.max_owned_rooms));
+        }
+
+        usage.creations.push_back(now);
+
+        // the lock is held until the room is inserted, so nobody can take the id in the meantime.
+        let room_id = Self::_generate_room_id(&rooms);
+
+        let room = self._create_room(&mut rooms, room_id, account_id, name, password, settings);
+        drop(rooms);
+        drop(quota_usage);
+
+        self.get_global().remove_player(account_id);
+
+        Ok(room)
+    }
+
+    /// Counts an invite towards the room quota of the inviter, until the invited player joins the room or the invite expires.
+    /// Inviting the same player to the same room again replaces the old invite. Fails if the inviter has too many invites waiting.
+    pub fn record_invite(&self, account_id: i32, invitee: i32, room_id: u32, limits: &RoomQuotaLimits) -> Result<(), RoomQuotaExceeded> {
+        let now = Instant::now();
+
+        let mut quota_usage = self.quota_usage.lock();
+        quota_usage.retain(|_, usage| !usage.prune(now));
+
+        let usage = quota_usage.entry(account_id).or_default();
+        usage.invites.retain(|invite| invite.invitee != invitee || invite.room_id != room_id);
+
+        if limits.max_outstanding_invites != 0 && usage.invites.len() >= limits.max_outstanding_invites as usize {
+            return Err(RoomQuotaExceeded::Invites(limits.max_outstanding_invites));
+        }
+
+        usage.invites.push(OutstandingInvite {
+            invitee,
+            room_id,
+            sent_at: now,
+        });
+
+        Ok(())
+    }
+
+    /// Marks the invites of a player to a room as accepted, so they don't count towards the room quota of the inviters anymore.
+    pub fn accept_invites(&self, invitee: i32, room_id: u32) {
+        for usage in self.quota_usage.lock().values_mut() {
+            usage.invites.retain(|invite| invite.invitee != invitee || invite.room_id != room_id);
+        }
+    }
+
+    /// Adds a player that was moved from another game server to the same room that the other players from their old room are in,
+    /// creating it if they are the first one to arrive. Removes them from the global room, and returns the room info.
+    pub fn join_transferred_room(&self, account_id: i32, transferred: &TransferredRoom) -> RoomInfo {
+        let mut transferred_rooms = self.transferred_rooms.lock();
+        let mut rooms = self.rooms.lock();
+
+        // forget about rooms that have been deleted since
+        transferred_rooms.retain(|_, (room_id, _)| rooms.contains_key(room_id));
+
+        let existing = transferred_rooms.get(&transferred.group).map(|(room_id, _)| *room_id);
+
+        let room_info = if let Some((room_id, room)) = existing.and_then(|id| rooms.get_mut(&id).map(|room| (id, room))) {
+            room.manager.create_player(account_id);
+            room.get_room_info(room_id, self.get_game_server())
+        } else {
+            let room_id = Self::_generate_room_id(&rooms);
+            transferred_rooms.insert(transferred.group, (room_id, Instant::now()));
+
+            // the room keeps its owner even if someone else arrives first, see `expire_transfers`
+            let mut pm = LevelManager::new();
+            pm.create_player(account_id);
+
+            let mut room = Room::new(
+                transferred.owner,
+                transferred.name.clone(),
+                transferred.password.clone(),
+                transferred.settings,
+                pm,
+            );
+
+            room.moderators.clone_from(&transferred.moderators);
+            room.set_tags(transferred.tags.tags.clone());
+            for member in &transferred.tags.members {
+                room.set_member_tag(member.account_id, Some(member.tag));
             }
+
+            let room_info = room.get_room_info(room_id, self.get_game_server());
+            rooms.insert(room_id, room);
+
+            room_info
         };
 
         drop(rooms);
-
-        let room = self._create_room(room_id, account_id, name, password, settings);
+        drop(transferred_rooms);
 
         self.get_global().remove_player(account_id);
 
-        room
+        room_info
+    }
+
+    /// Returns the transfer data for the given room, so that its players can be moved to another game server together.
+    pub fn get_transfer_data(&self, room_id: u32, group: u64) -> Option<TransferredRoom> {
+        self.get_rooms().get(&room_id).map(|room| TransferredRoom {
+            group,
+            owner: room.owner,
+            moderators: room.moderators.clone(),
+            tags: room.get_tags(),
+            name: room.name.clone(),
+            password: room.password.clone(),
+            settings: room.settings,
+        })
+    }
+
+    /// Stops waiting for the players of rooms moved from another game server once `TRANSFER_OWNER_WINDOW` has passed,
+    /// and gives rooms whose owner did not arrive to another player.
+    pub fn expire_transfers(&self) {
+        let mut transferred_rooms = self.transferred_rooms.lock();
+        let mut rooms = self.rooms.lock();
+
+        transferred_rooms.retain(|_, (room_id, created_at)| {
+            if created_at.elapsed() < TRANSFER_OWNER_WINDOW {
+                return rooms.contains_key(room_id);
+            }
+
+            if let Some(room) = rooms.get_mut(room_id) {
+                room.replace_absent_owner();
+            }
+
+            false
+        });
+    }
+
+    /// Recreates an empty room from a snapshot taken before a restart, keeping the same ID.
+    pub fn restore_room(
+        &self,
+        room_id: u32,
+        owner: i32,
+        moderators: Vec<i32>,
+        name: InlineString<32>,
+        password: InlineString<16>,
+        settings: RoomSettings,
+    ) {
+        let mut room = Room::new(owner, name, password, settings, LevelManager::new());
+        room.moderators = moderators;
+
+        self.rooms.lock().insert(room_id, room);
+    }
+
+    /// Remembers the room a player was in before a restart, so they can be put back into it once they log in again.
+    pub fn add_pending_resume(&self, account_id: i32, room_id: u32) {
+        self.pending_resumes.lock().insert(account_id, room_id);
+    }
+
+    /// Returns the players that were in a room before a restart and didn't log back in yet, along with their room.
+    pub fn get_pending_resumes(&self) -> Vec<(i32, u32)> {
+        self.pending_resumes.lock().iter().map(|(k, v)| (*k, *v)).collect()
+    }
+
+    /// Puts a player that just logged in back into the room they were in before a restart, and returns the room info.
+    /// Returns `None` if there is nothing to resume, in which case the player should be put into the global room.
+    pub fn resume_session(&self, account_id: i32) -> Option<RoomInfo> {
+        let room_id = self.pending_resumes.lock().remove(&account_id)?;
+
+        let mut rooms = self.rooms.lock();
+        let room = rooms.get_mut(&room_id)?;
+        room.manager.create_player(account_id);
+
+        Some(room.get_room_info(room_id, self.get_game_server()))
+    }
+
+    /// Puts a player that just logged in back into the room they left shortly before, and returns the room info.
+    /// Returns `None` if the room no longer exists, is full, or is not open to their roles.
+    pub fn rejoin_room(&self, account_id: i32, room_id: u32, role: &ComputedRole) -> Option<RoomInfo> {
+        let mut rooms = self.rooms.lock();
+        let room = rooms.get_mut(&room_id)?;
+
+        if room.is_full() || (!room.settings.allows_roles(role.role_mask) && room.owner != account_id && !role.can_moderate()) {
+            return None;
+        }
+
+        room.manager.create_player(account_id);
+
+        Some(room.get_room_info(room_id, self.get_game_server()))
+    }
+
+    /// Stops waiting for players from before a restart. Deletes restored rooms that nobody came back to,
+    /// and gives rooms whose owner did not come back to another player.
+    pub fn finish_restore(&self) {
+        let pending = std::mem::take(&mut *self.pending_resumes.lock());
+        if pending.is_empty() {
+            return;
+        }
+
+        let mut rooms = self.rooms.lock();
+        rooms.retain(|_, room| room.manager.get_total_player_count() != 0);
+
+        for room in rooms.values_mut() {
+            room.replace_absent_owner();
+        }
+    }
+
+    /// Returns `true` if the given address tried to join too many nonexistent rooms recently, and should not be allowed to join any room.
+    pub fn is_join_limited(&self, address: IpAddr) -> bool {
+        self.invalid_joins
+            .lock()
+            .get(&address_key(address))
+            .is_some_and(SimpleRateLimiter::is_limited)
+    }
+
+    /// Registers an attempt to join a room that does not exist.
+    pub fn record_invalid_join(&self, address: IpAddr) {
+        let mut invalid_joins = self.invalid_joins.lock();
+
+        // forget about addresses that haven't tried anything in a while
+        if invalid_joins.len() >= 1024 {
+            invalid_joins.retain(|_, limiter| limiter.since_last_refill() < INVALID_JOIN_PERIOD * 2);
+        }
+
+        invalid_joins
+            .entry(address_key(address))
+            .or_insert_with(|| SimpleRateLimiter::new(INVALID_JOIN_LIMIT, INVALID_JOIN_PERIOD))
+            .try_tick();
     }
 
     pub fn is_valid_room(&self, room_id: u32) -> bool {
         self.rooms.lock().contains_key(&room_id)
     }
 
-    /// Deletes a room if there are no players in it
+    /// Deletes a room if there are no players in it, bots don't count as they can't leave on their own
     pub fn maybe_remove_room(&self, room_id: u32) {
         let mut rooms = self.rooms.lock();
 
-        let to_remove = rooms.get(&room_id).is_some_and(|room| room.manager.get_total_player_count() == 0);
+        let to_remove = rooms
+            .get(&room_id)
+            .is_some_and(|room| room.manager.players.keys().all(|account_id| is_bot_account(*account_id)));
 
         if to_remove {
             rooms.remove(&room_id);
         }
     }
 
+    /// Removes the room right away, so nobody else can join it. Returns its name and the players that were in it
+    /// (bots excluded), who still have to be moved to the global room, or `None` if the room doesn't exist.
+    pub fn close_room(&self, room_id: u32) -> Option<(String, Vec<i32>)> {
+        let room = self.rooms.lock().remove(&room_id)?;
+
+        let players = room.manager.players.keys().copied().filter(|id| !is_bot_account(*id)).collect();
+
+        Some((room.name.try_to_string(), players))
+    }
+
     // Removes the player from the given room, returns `true` if the player was the owner of the room,
     // and either a new owner has now been chosen, or the room has been deleted.
+    // If the room no longer exists (for example because it was closed), nothing happens and `false` is returned.
     pub fn remove_with_any(&self, room_id: u32, account_id: i32, level_id: LevelId) -> bool {
-        let was_owner = self.with_any(room_id, |pm| {
-            let was_owner = pm.remove_player(account_id);
+        let was_owner = self.try_with_any(
+            room_id,
+            |pm| {
+                let was_owner = pm.remove_player(account_id);
 
-            if level_id != 0 {
-                pm.manager.remove_from_level(level_id, account_id);
-            }
+                if level_id != 0 {
+                    pm.manager.move_to_level(0, account_id);
+                }
 
-            was_owner
-        });
+                was_owner
+            },
+            || false,
+        );
 
         // delete the room if there are no more players there
         if room_id != 0 {
@@ -232,13 +734,98 @@ impl RoomManager {
         was_owner
     }
 
+    /// Sets or removes (if `url` is `None`) the webhook of a room, returns `false` if the room doesn't exist.
+    pub fn set_webhook(&self, room_id: u32, url: Option<String>) -> bool {
+        self.rooms
+            .lock()
+            .get_mut(&room_id)
+            .map(|room| room.webhook = url.map(|url| RoomWebhook { url, pending: Vec::new() }))
+            .is_some()
+    }
+
+    /// Queues an event for the webhook of a room, if it has one. The closure gets the name of the room.
+    pub fn queue_webhook_event<F: FnOnce(String) -> WebhookMessage>(&self, room_id: u32, f: F) {
+        let mut rooms = self.rooms.lock();
+
+        let Some(room) = rooms.get_mut(&room_id) else {
+            return;
+        };
+
+        let name = room.name.try_to_string();
+
+        if let Some(webhook) = room.webhook.as_mut() {
+            if webhook.pending.len() < MAX_PENDING_WEBHOOK_EVENTS {
+                webhook.pending.push(f(name));
+            }
+        }
+    }
+
+    pub fn has_webhook(&self, room_id: u32) -> bool {
+        self.rooms.lock().get(&room_id).is_some_and(|room| room.webhook.is_some())
+    }
+
+    /// Takes the next batch of events of every room webhook that has any, along with the room ID and the URL to send them to.
+    pub fn take_webhook_batches(&self) -> Vec<(u32, String, Vec<WebhookMessage>)> {
+        self.rooms
+            .lock()
+            .iter_mut()
+            .filter_map(|(room_id, room)| room.webhook.as_mut().map(|webhook| (*room_id, webhook)))
+            .filter(|(_, webhook)| !webhook.pending.is_empty())
+            .map(|(room_id, webhook)| {
+                let count = webhook.pending.len().min(WEBHOOK_BATCH_SIZE);
+                (room_id, webhook.url.clone(), webhook.pending.drain(..count).collect())
+            })
+            .collect()
+    }
+
     pub fn get_room_info(&self, room_id: u32) -> Option<RoomInfo> {
         self.try_with_any(room_id, |room| Some(room.get_room_info(room_id, self.get_game_server())), || None)
     }
 
-    fn _create_room(&self, room_id: u32, owner: i32, name: InlineString<32>, password: InlineString<16>, settings: RoomSettings) -> RoomInfo {
-        let mut rooms = self.rooms.lock();
+    /// Counts the players in every room except the global room, and on every level across all rooms.
+    pub fn count_players(&self) -> (Vec<(u32, u32)>, IntMap<LevelId, u32>) {
+        let count_level = |(level_id, players): (LevelId, &Vec<i32>), _: usize, levels: &mut IntMap<LevelId, u32>| {
+            *levels.entry(level_id).or_default() += players.len() as u32;
+            true
+        };
+
+        let mut levels = IntMap::default();
+        self.get_global().manager.for_each_level(count_level, &mut levels);
+
+        let rooms = self
+            .get_rooms()
+            .iter()
+            .map(|(room_id, room)| {
+                room.manager.for_each_level(count_level, &mut levels);
+                (*room_id, room.manager.get_total_player_count() as u32)
+            })
+            .collect();
+
+        (rooms, levels)
+    }
+
+    /// Generates a random ID that is not used by any room yet.
+    fn _generate_room_id(rooms: &IntMap<u32, Room>) -> u32 {
+        let (start, end) = room_id_range();
+
+        // room ids must not be guessable, so use the OS rng. in case we generate an existing room id, keep looping until we find a suitable id.
+        loop {
+            let room_id = OsRng.gen_range(start..end);
+            if !rooms.contains_key(&room_id) {
+                break room_id;
+            }
+        }
+    }
 
+    fn _create_room(
+        &self,
+        rooms: &mut IntMap<u32, Room>,
+        room_id: u32,
+        owner: i32,
+        name: InlineString<32>,
+        password: InlineString<16>,
+        settings: RoomSettings,
+    ) -> RoomInfo {
         let mut pm = LevelManager::new();
         if owner != 0 {
             pm.create_player(owner);
@@ -252,3 +839,148 @@ impl RoomManager {
         room_info
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::server::test_server;
+
+    fn limits(max_owned_rooms: u32, creations_per_hour: u32, max_outstanding_invites: u32) -> RoomQuotaLimits {
+        RoomQuotaLimits {
+            max_owned_rooms,
+            creations_per_hour,
+            max_outstanding_invites,
+        }
+    }
+
+    fn create(manager: &RoomManager, account_id: i32, limits: &RoomQuotaLimits) -> Result<RoomInfo, RoomQuotaExceeded> {
+        manager.create_room(
+            account_id,
+            InlineString::new("room"),
+            InlineString::default(),
+            RoomSettings::default(),
+            limits,
+        )
+    }
+
+    /// pretends that `elapsed` has passed for the quota of the account
+    fn age_quota(manager: &RoomManager, account_id: i32, elapsed: Duration) {
+        if let Some(usage) = manager.quota_usage.lock().get_mut(&account_id) {
+            usage.prune(Instant::now() + elapsed);
+        }
+    }
+
+    #[tokio::test]
+    async fn limits_creations_per_hour() {
+        let manager = &test_server().await.state.room_manager;
+        let limits = limits(0, 2, 0);
+
+        assert!(create(manager, 1, &limits).is_ok());
+        assert!(create(manager, 1, &limits).is_ok());
+        assert!(matches!(create(manager, 1, &limits), Err(RoomQuotaExceeded::Creations(2))));
+
+        // other accounts have their own quota
+        assert!(create(manager, 2, &limits).is_ok());
+
+        age_quota(manager, 1, ROOM_CREATION_WINDOW);
+        assert!(create(manager, 1, &limits).is_ok());
+    }
+
+    #[tokio::test]
+    async fn limits_owned_rooms() {
+        let manager = &test_server().await.state.room_manager;
+        let limits = limits(1, 0, 0);
+
+        let room = create(manager, 1, &limits).ok().unwrap();
+        assert!(matches!(create(manager, 1, &limits), Err(RoomQuotaExceeded::OwnedRooms(1))));
+
+        // a room stops counting once it's gone
+        manager.close_room(room.id);
+        assert!(create(manager, 1, &limits).is_ok());
+    }
+
+    #[tokio::test]
+    async fn limits_outstanding_invites() {
+        let manager = &test_server().await.state.room_manager;
+        let limits = limits(0, 0, 2);
+
+        assert!(manager.record_invite(1, 10, 100, &limits).is_ok());
+        assert!(manager.record_invite(1, 11, 100, &limits).is_ok());
+        assert!(matches!(manager.record_invite(1, 12, 100, &limits), Err(RoomQuotaExceeded::Invites(2))));
+
+        // inviting the same player to the same room again replaces the old invite
+        assert!(manager.record_invite(1, 10, 100, &limits).is_ok());
+
+        // accepted invites don't count anymore, invites to other rooms are left alone
+        manager.accept_invites(10, 200);
+        assert!(manager.record_invite(1, 12, 100, &limits).is_err());
+        manager.accept_invites(10, 100);
+        assert!(manager.record_invite(1, 12, 100, &limits).is_ok());
+
+        // and neither do expired ones
+        age_quota(manager, 1, INVITE_LIFETIME);
+        assert!(manager.record_invite(1, 13, 100, &limits).is_ok());
+        assert!(manager.record_invite(1, 14, 100, &limits).is_ok());
+    }
+
+    fn transferred_room(owner: i32, moderators: Vec<i32>) -> TransferredRoom {
+        TransferredRoom {
+            group: 1,
+            owner,
+            moderators,
+            tags: RoomTags::default(),
+            name: InlineString::new("room"),
+            password: InlineString::default(),
+            settings: RoomSettings::default(),
+        }
+    }
+
+    /// pretends that `TRANSFER_OWNER_WINDOW` has passed since the transferred rooms were created
+    fn age_transfers(manager: &RoomManager) {
+        for (_, created_at) in manager.transferred_rooms.lock().values_mut() {
+            if let Some(old) = created_at.checked_sub(TRANSFER_OWNER_WINDOW) {
+                *created_at = old;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn transferred_room_keeps_owner() {
+        let manager = &test_server().await.state.room_manager;
+        let transferred = transferred_room(1, vec![2]);
+
+        let room = manager.join_transferred_room(3, &transferred);
+        assert_eq!(manager.join_transferred_room(1, &transferred).id, room.id);
+
+        age_transfers(manager);
+        manager.expire_transfers();
+
+        let rooms = manager.get_rooms();
+        let room = rooms.get(&room.id).unwrap();
+        assert_eq!(room.owner, 1);
+        // the moderator never arrived
+        assert!(room.moderators.is_empty());
+    }
+
+    #[tokio::test]
+    async fn transferred_room_without_owner() {
+        let manager = &test_server().await.state.room_manager;
+        let transferred = transferred_room(1, vec![2]);
+
+        let room = manager.join_transferred_room(3, &transferred);
+        manager.join_transferred_room(2, &transferred);
+
+        // the owner still has time to arrive
+        manager.expire_transfers();
+        assert_eq!(manager.get_rooms().get(&room.id).unwrap().owner, 1);
+
+        age_transfers(manager);
+        manager.expire_transfers();
+
+        // moderators that arrived are picked first
+        let rooms = manager.get_rooms();
+        let room = rooms.get(&room.id).unwrap();
+        assert_eq!(room.owner, 2);
+        assert!(room.moderators.is_empty());
+    }
+}
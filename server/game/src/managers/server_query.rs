@@ -0,0 +1,85 @@
+use std::{net::IpAddr, time::Duration};
+
+use globed_shared::{
+    hmac::{Hmac, Mac},
+    rand,
+    sha2::Sha256,
+    unix_timestamp, IntMap, SyncMutex,
+};
+
+use crate::util::{address_key, SimpleRateLimiter};
+
+/// How many query packets (including token requests) a single address can send per `PER_ADDRESS_PERIOD`.
+const PER_ADDRESS_LIMIT: usize = 6;
+const PER_ADDRESS_PERIOD: Duration = Duration::from_secs(10);
+/// How many query packets the server answers per second in total, no matter where they come from.
+const GLOBAL_LIMIT: usize = 200;
+/// Tokens are valid for the window they were issued in and the one after it.
+const TOKEN_WINDOW_SECS: i64 = 30;
+/// Rate limiters of addresses are only cleaned up once the map grows past this size.
+const CLEANUP_THRESHOLD: usize = 1024;
+
+/// Protects the unauthenticated server query from abuse. Every address is rate limited, and full server info
+/// is only sent to an address that echoes a token it got earlier, which proves it can receive packets sent to it.
+/// Tokens are derived from the address and the current time with a key that is generated on startup, so nothing has to be stored.
+pub struct ServerQueryManager {
+    key: [u8; 32],
+    limiters: SyncMutex<IntMap<u64, SimpleRateLimiter>>,
+    global_limiter: SyncMutex<SimpleRateLimiter>,
+}
+
+impl Default for ServerQueryManager {
+    fn default() -> Self {
+        Self {
+            key: rand::random(),
+            limiters: SyncMutex::new(IntMap::default()),
+            global_limiter: SyncMutex::new(SimpleRateLimiter::new(GLOBAL_LIMIT, Duration::from_secs(1))),
+        }
+    }
+}
+
+impl ServerQueryManager {
+    /// Returns `true` if a query from this address should be answered.
+    pub fn try_query(&self, address: IpAddr) -> bool {
+        let allowed = {
+            let mut limiters = self.limiters.lock();
+
+            // forget about addresses that haven't sent anything in a while
+            if limiters.len() >= CLEANUP_THRESHOLD {
+                limiters.retain(|_, limiter| limiter.since_last_refill() < PER_ADDRESS_PERIOD * 2);
+            }
+
+            limiters
+                .entry(address_key(address))
+                .or_insert_with(|| SimpleRateLimiter::new(PER_ADDRESS_LIMIT, PER_ADDRESS_PERIOD))
+                .try_tick()
+        };
+
+        allowed && self.global_limiter.lock().try_tick()
+    }
+
+    /// Returns the token the given address has to echo to get the server info.
+    pub fn make_token(&self, address: IpAddr) -> u32 {
+        self.token_for_window(address, unix_timestamp() / TOKEN_WINDOW_SECS)
+    }
+
+    pub fn verify_token(&self, address: IpAddr, token: u32) -> bool {
+        let window = unix_timestamp() / TOKEN_WINDOW_SECS;
+
+        token != 0 && (token == self.token_for_window(address, window) || token == self.token_for_window(address, window - 1))
+    }
+
+    fn token_for_window(&self, address: IpAddr, window: i64) -> u32 {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).expect("hmac accepts keys of any size");
+        match address {
+            IpAddr::V4(x) => mac.update(&x.octets()),
+            IpAddr::V6(x) => mac.update(&x.octets()),
+        }
+        mac.update(&window.to_be_bytes());
+
+        let hash = mac.finalize().into_bytes();
+
+        // 0 is what clients send when they don't have a token yet
+        u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]).max(1)
+    }
+}
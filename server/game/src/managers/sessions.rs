@@ -0,0 +1,45 @@
+use globed_shared::{AccountSession, IntMap, SyncMutex};
+
+/// Sessions of the players logged into this server. They are also reported to the central server, which knows about
+/// sessions on other servers and the ones that ended recently, so these are only used when it can't be asked.
+#[derive(Default)]
+pub struct SessionManager {
+    sessions: SyncMutex<IntMap<i32, Vec<AccountSession>>>,
+}
+
+impl SessionManager {
+    pub fn add(&self, session: AccountSession) {
+        self.sessions.lock().entry(session.account_id).or_default().push(session);
+    }
+
+    /// Removes the session, returns `false` if it wasn't on this server.
+    pub fn remove(&self, account_id: i32, session_id: u64) -> bool {
+        let mut sessions = self.sessions.lock();
+
+        let Some(list) = sessions.get_mut(&account_id) else {
+            return false;
+        };
+
+        let len = list.len();
+        list.retain(|x| x.session_id != session_id);
+        let removed = list.len() != len;
+
+        if list.is_empty() {
+            sessions.remove(&account_id);
+        }
+
+        removed
+    }
+
+    /// Returns the sessions of the account on this server, newest first.
+    pub fn get(&self, account_id: i32) -> Vec<AccountSession> {
+        let mut list = self.sessions.lock().get(&account_id).cloned().unwrap_or_default();
+        list.sort_by_key(|x| std::cmp::Reverse(x.login_time));
+        list
+    }
+
+    /// Returns every session on this server.
+    pub fn all(&self) -> Vec<AccountSession> {
+        self.sessions.lock().values().flatten().cloned().collect()
+    }
+}
@@ -0,0 +1,175 @@
+use std::{
+    cmp::Reverse,
+    sync::atomic::{AtomicI64, AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use globed_shared::{unix_timestamp, IntMap, SyncMutex};
+
+use crate::data::{LevelId, TrafficEntry};
+
+/// Levels that nobody received any traffic on for this long are forgotten, as there are far more levels than rooms.
+const LEVEL_IDLE_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Outgoing traffic of a single connection, counted by its socket and periodically collected into `TrafficStatsManager`.
+#[derive(Default)]
+pub struct TrafficCounter {
+    bytes: AtomicU64,
+    packets: AtomicU64,
+}
+
+impl TrafficCounter {
+    #[inline]
+    pub fn record_bytes(&self, bytes: usize) {
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn record_packet(&self) {
+        self.packets.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the amount of bytes and packets sent since the last call.
+    pub fn take(&self) -> (u64, u64) {
+        (self.bytes.swap(0, Ordering::Relaxed), self.packets.swap(0, Ordering::Relaxed))
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+struct Totals {
+    bytes: u64,
+    packets: u64,
+}
+
+impl Totals {
+    fn add(&mut self, bytes: u64, packets: u64) {
+        self.bytes = self.bytes.saturating_add(bytes);
+        self.packets = self.packets.saturating_add(packets);
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    rooms: IntMap<u32, Totals>,
+    /// totals of every level and when they last had any traffic
+    levels: IntMap<LevelId, (Totals, Instant)>,
+}
+
+/// Egress traffic per room and per level, so operators can tell which rooms use the most bandwidth.
+/// Traffic is attributed to the room and level the receiving player was in when it was collected.
+#[derive(Default)]
+pub struct TrafficStatsManager {
+    inner: SyncMutex<Inner>,
+    /// seconds since unix epoch, when counting started (or was last reset)
+    since: AtomicI64,
+}
+
+impl TrafficStatsManager {
+    pub fn record(&self, room_id: u32, level_id: LevelId, bytes: u64, packets: u64) {
+        if bytes == 0 && packets == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock();
+        inner.rooms.entry(room_id).or_default().add(bytes, packets);
+
+        if level_id != 0 {
+            let now = Instant::now();
+            let (totals, last_active) = inner.levels.entry(level_id).or_insert_with(|| (Totals::default(), now));
+            totals.add(bytes, packets);
+            *last_active = now;
+        }
+    }
+
+    /// Forgets rooms that don't exist anymore, so the map doesn't grow with every room ever created.
+    pub fn retain_rooms<F: Fn(u32) -> bool>(&self, exists: F) {
+        self.inner.lock().rooms.retain(|id, _| *id == 0 || exists(*id));
+    }
+
+    /// Forgets levels that had no traffic for `LEVEL_IDLE_TIMEOUT` before `now`.
+    pub fn forget_idle_levels(&self, now: Instant) {
+        self.inner
+            .lock()
+            .levels
+            .retain(|_, (_, last_active)| now.saturating_duration_since(*last_active) < LEVEL_IDLE_TIMEOUT);
+    }
+
+    /// Returns the `count` rooms and levels with the most outgoing bytes, the highest first.
+    /// Levels that were idle for a while are not included, see `forget_idle_levels`.
+    pub fn top(&self, count: usize) -> (Vec<TrafficEntry>, Vec<TrafficEntry>) {
+        let inner = self.inner.lock();
+
+        let mut rooms = inner
+            .rooms
+            .iter()
+            .map(|(&room_id, totals)| TrafficEntry {
+                room_id,
+                level_id: 0,
+                bytes: totals.bytes,
+                packets: totals.packets,
+            })
+            .collect::<Vec<_>>();
+
+        let mut levels = inner
+            .levels
+            .iter()
+            .map(|(&level_id, (totals, _))| TrafficEntry {
+                room_id: 0,
+                level_id,
+                bytes: totals.bytes,
+                packets: totals.packets,
+            })
+            .collect::<Vec<_>>();
+
+        drop(inner);
+
+        for list in [&mut rooms, &mut levels] {
+            list.sort_unstable_by_key(|entry| Reverse(entry.bytes));
+            list.truncate(count);
+        }
+
+        (rooms, levels)
+    }
+
+    /// Returns when counting started, in seconds since unix epoch.
+    pub fn since(&self) -> i64 {
+        match self.since.load(Ordering::Relaxed) {
+            0 => unix_timestamp(),
+            x => x,
+        }
+    }
+
+    pub fn start(&self) {
+        self.since.store(unix_timestamp(), Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        let mut inner = self.inner.lock();
+        inner.rooms.clear();
+        inner.levels.clear();
+
+        self.start();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forgets_idle_levels() {
+        let traffic = TrafficStatsManager::default();
+        traffic.record(0, 1, 100, 1);
+        traffic.record(0, 2, 50, 1);
+
+        traffic.forget_idle_levels(Instant::now());
+        assert_eq!(traffic.top(10).1.len(), 2);
+
+        traffic.forget_idle_levels(Instant::now() + LEVEL_IDLE_TIMEOUT);
+        let (rooms, levels) = traffic.top(10);
+        assert!(levels.is_empty());
+        // rooms are kept for as long as they exist
+        assert_eq!(rooms.len(), 1);
+        assert_eq!(rooms[0].bytes, 150);
+    }
+}
@@ -0,0 +1,67 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::data::{ChangedSettingsFlags, ClientPlatform, FeatureUsagePacket};
+
+/// Feature usage reported by clients that opted in, plus logins per platform. Only the totals are kept, nothing that could tell who sent a report.
+#[derive(Default)]
+pub struct UsageStatsManager {
+    reports: AtomicU64,
+    voice_enabled: AtomicU64,
+    platforms: [AtomicU64; ClientPlatform::COUNT],
+    changed_settings: [AtomicU64; ChangedSettingsFlags::CATEGORIES.len()],
+    logins: [AtomicU64; ClientPlatform::COUNT],
+}
+
+/// Totals since the server started, for metrics.
+pub struct UsageStats {
+    pub reports: u64,
+    pub voice_enabled: u64,
+    pub platforms: Vec<(&'static str, u64)>,
+    pub changed_settings: Vec<(&'static str, u64)>,
+    pub logins: Vec<(&'static str, u64)>,
+}
+
+impl UsageStatsManager {
+    pub fn record(&self, report: &FeatureUsagePacket) {
+        self.reports.fetch_add(1, Ordering::Relaxed);
+
+        if report.voice_enabled {
+            self.voice_enabled.fetch_add(1, Ordering::Relaxed);
+        }
+
+        self.platforms[report.platform as usize].fetch_add(1, Ordering::Relaxed);
+
+        for (counter, changed) in self.changed_settings.iter().zip(report.changed_settings.as_array()) {
+            if changed {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Unlike `record`, this counts every successful login, regardless of whether the client opted into usage reports.
+    pub fn record_login(&self, platform: ClientPlatform) {
+        self.logins[platform as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> UsageStats {
+        UsageStats {
+            reports: self.reports.load(Ordering::Relaxed),
+            voice_enabled: self.voice_enabled.load(Ordering::Relaxed),
+            platforms: Self::per_platform(&self.platforms),
+            changed_settings: ChangedSettingsFlags::CATEGORIES
+                .iter()
+                .zip(&self.changed_settings)
+                .map(|(category, count)| (*category, count.load(Ordering::Relaxed)))
+                .collect(),
+            logins: Self::per_platform(&self.logins),
+        }
+    }
+
+    fn per_platform(counters: &[AtomicU64; ClientPlatform::COUNT]) -> Vec<(&'static str, u64)> {
+        ClientPlatform::ALL
+            .iter()
+            .zip(counters)
+            .map(|(platform, count)| (platform.name(), count.load(Ordering::Relaxed)))
+            .collect()
+    }
+}
@@ -0,0 +1,368 @@
+use std::path::{Path, PathBuf};
+
+use globed_shared::{
+    anyhow::{self, anyhow, bail},
+    error, info, warn,
+};
+use wasmtime::{Caller, Config, Engine, Extern, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc};
+
+use super::{
+    storage::{PluginStorage, MAX_KEY_SIZE, MAX_VALUE_SIZE},
+    PLUGIN_API_VERSION,
+};
+use crate::{
+    client::ServerThreadMessage,
+    data::{FastString, ServerNoticePacket},
+    events::ServerEvent,
+    server::GameServer,
+    tokio::{runtime::Handle, sync::broadcast},
+};
+
+/// Amount of fuel a plugin gets for every call, so that an infinite loop can't hang the plugin host forever.
+const FUEL_PER_CALL: u64 = 50_000_000;
+/// After this many calls in a row fail, the plugin gets disabled.
+const MAX_CONSECUTIVE_FAILURES: u32 = 10;
+/// How large the linear memory of a plugin can grow, in bytes.
+const MAX_PLUGIN_MEMORY: usize = 64 * 1024 * 1024;
+
+struct PluginState {
+    name: String,
+    game_server: &'static GameServer,
+    runtime: Handle,
+    storage: PluginStorage,
+    limits: StoreLimits,
+}
+
+struct Plugin {
+    store: Store<PluginState>,
+    memory: Memory,
+    alloc: TypedFunc<u32, u32>,
+    on_event: TypedFunc<(u32, u32), ()>,
+    failures: u32,
+}
+
+impl Plugin {
+    fn load(engine: &Engine, linker: &Linker<PluginState>, path: &Path, game_server: &'static GameServer, runtime: Handle) -> anyhow::Result<Self> {
+        let name = path
+            .file_stem()
+            .and_then(|x| x.to_str())
+            .ok_or_else(|| anyhow!("invalid file name"))?
+            .to_owned();
+
+        let storage = PluginStorage::load(path.with_extension("kv.json"))?;
+        let module = Module::from_file(engine, path)?;
+
+        let mut store = Store::new(
+            engine,
+            PluginState {
+                name,
+                game_server,
+                runtime,
+                storage,
+                limits: StoreLimitsBuilder::new().memory_size(MAX_PLUGIN_MEMORY).instances(1).build(),
+            },
+        );
+
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(FUEL_PER_CALL)?;
+        let instance = linker.instantiate(&mut store, &module)?;
+
+        let version = instance
+            .get_typed_func::<(), u32>(&mut store, "globed_plugin_api_version")?
+            .call(&mut store, ())?;
+
+        if version != PLUGIN_API_VERSION {
+            bail!("plugin was made for API version {version}, but the server uses version {PLUGIN_API_VERSION}");
+        }
+
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| anyhow!("plugin does not export its memory"))?;
+        let alloc = instance.get_typed_func::<u32, u32>(&mut store, "globed_alloc")?;
+        let on_event = instance.get_typed_func::<(u32, u32), ()>(&mut store, "globed_on_event")?;
+
+        // initialization is optional
+        if let Ok(init) = instance.get_typed_func::<(), ()>(&mut store, "globed_init") {
+            store.set_fuel(FUEL_PER_CALL)?;
+            init.call(&mut store, ())?;
+        }
+
+        Ok(Self {
+            store,
+            memory,
+            alloc,
+            on_event,
+            failures: 0,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.store.data().name
+    }
+
+    /// Copies the encoded event into the memory of the plugin and invokes its event handler.
+    /// The buffer is owned by the plugin afterwards.
+    fn deliver(&mut self, event: &[u8]) -> anyhow::Result<()> {
+        self.store.set_fuel(FUEL_PER_CALL)?;
+
+        let len = u32::try_from(event.len())?;
+        let ptr = self.alloc.call(&mut self.store, len)?;
+        self.memory.write(&mut self.store, ptr as usize, event)?;
+        self.on_event.call(&mut self.store, (ptr, len))?;
+
+        Ok(())
+    }
+}
+
+pub fn start(game_server: &'static GameServer, dir: PathBuf) {
+    let runtime = Handle::current();
+
+    // subscribe right away, so that no events are missed while the plugins are loading
+    let receiver = game_server.state.events.subscribe();
+
+    let result = std::thread::Builder::new().name("plugin-host".to_owned()).spawn(move || {
+        let plugins = match load_plugins(&dir, game_server, &runtime) {
+            Ok(x) => x,
+            Err(e) => {
+                error!("failed to load plugins from {}: {e}", dir.display());
+                return;
+            }
+        };
+
+        if plugins.is_empty() {
+            warn!("no plugins found in {}", dir.display());
+            return;
+        }
+
+        run(plugins, receiver);
+    });
+
+    if let Err(e) = result {
+        error!("failed to start the plugin host: {e}");
+    }
+}
+
+fn load_plugins(dir: &Path, game_server: &'static GameServer, runtime: &Handle) -> anyhow::Result<Vec<Plugin>> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+
+    let engine = Engine::new(&config)?;
+    let linker = make_linker(&engine)?;
+
+    let mut paths = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|x| x.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .collect::<Vec<_>>();
+
+    // load in a predictable order, so that plugins can rely on it
+    paths.sort();
+
+    let mut plugins = Vec::new();
+
+    for path in paths {
+        match Plugin::load(&engine, &linker, &path, game_server, runtime.clone()) {
+            Ok(plugin) => {
+                info!("Loaded plugin {}", plugin.name());
+                plugins.push(plugin);
+            }
+            Err(e) => error!("failed to load plugin {}: {e}", path.display()),
+        }
+    }
+
+    Ok(plugins)
+}
+
+fn run(mut plugins: Vec<Plugin>, mut receiver: broadcast::Receiver<ServerEvent>) {
+    loop {
+        let event = match receiver.blocking_recv() {
+            Ok(x) => x,
+            Err(broadcast::error::RecvError::Lagged(count)) => {
+                warn!("plugins are too slow to keep up, {count} events were skipped");
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+
+        let encoded = match serde_json::to_vec(&event) {
+            Ok(x) => x,
+            Err(e) => {
+                error!("failed to encode an event for plugins: {e}");
+                continue;
+            }
+        };
+
+        plugins.retain_mut(|plugin| match plugin.deliver(&encoded) {
+            Ok(()) => {
+                plugin.failures = 0;
+                true
+            }
+            Err(e) => {
+                warn!("plugin {} failed to handle an event: {e}", plugin.name());
+                plugin.failures += 1;
+
+                if plugin.failures >= MAX_CONSECUTIVE_FAILURES {
+                    error!("plugin {} failed too many times in a row, disabling it", plugin.name());
+                    false
+                } else {
+                    true
+                }
+            }
+        });
+
+        if plugins.is_empty() {
+            warn!("every plugin has been disabled, stopping the plugin host");
+            break;
+        }
+    }
+}
+
+/* host functions, imported by plugins from the `globed` module */
+
+fn make_linker(engine: &Engine) -> anyhow::Result<Linker<PluginState>> {
+    let mut linker = Linker::new(engine);
+
+    linker.func_wrap(
+        "globed",
+        "log",
+        |mut caller: Caller<'_, PluginState>, ptr: u32, len: u32| -> anyhow::Result<()> {
+            let message = read_string(&mut caller, ptr, len)?;
+            info!("[plugin {}] {message}", caller.data().name);
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        "globed",
+        "send_notice",
+        |mut caller: Caller<'_, PluginState>, account_id: i32, ptr: u32, len: u32| -> anyhow::Result<i32> {
+            let message = read_string(&mut caller, ptr, len)?;
+            let state = caller.data();
+
+            let Some(thread) = state.game_server.get_user_by_id(account_id) else {
+                return Ok(-1);
+            };
+
+            state
+                .runtime
+                .block_on(thread.push_new_message(ServerThreadMessage::BroadcastNotice(ServerNoticePacket {
+                    message: FastString::new(&message),
+                })));
+
+            Ok(0)
+        },
+    )?;
+
+    linker.func_wrap(
+        "globed",
+        "broadcast_notice",
+        |mut caller: Caller<'_, PluginState>, ptr: u32, len: u32| -> anyhow::Result<i32> {
+            let message = read_string(&mut caller, ptr, len)?;
+            let state = caller.data();
+
+            let threads = state
+                .game_server
+                .clients
+                .lock()
+                .values()
+                .filter(|thr| thr.authenticated())
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let packet = ServerNoticePacket {
+                message: FastString::new(&message),
+            };
+
+            state.runtime.block_on(async {
+                for thread in &threads {
+                    thread.push_new_message(ServerThreadMessage::BroadcastNotice(packet.clone())).await;
+                }
+            });
+
+            Ok(i32::try_from(threads.len()).unwrap_or(i32::MAX))
+        },
+    )?;
+
+    linker.func_wrap(
+        "globed",
+        "kv_get",
+        |mut caller: Caller<'_, PluginState>, key_ptr: u32, key_len: u32, out_ptr: u32, out_cap: u32| -> anyhow::Result<i32> {
+            let key = read_string(&mut caller, key_ptr, key_len)?;
+            let memory = get_memory(&mut caller)?;
+            let (data, state) = memory.data_and_store_mut(&mut caller);
+
+            let Some(value) = state.storage.get(&key) else {
+                return Ok(-1);
+            };
+
+            // if the buffer is too small, only report the needed size, so that the plugin can retry with a larger one
+            if value.len() <= out_cap as usize {
+                let out = out_ptr as usize;
+                data.get_mut(out..out + value.len())
+                    .ok_or_else(|| anyhow!("out of bounds memory access"))?
+                    .copy_from_slice(value.as_bytes());
+            }
+
+            Ok(i32::try_from(value.len())?)
+        },
+    )?;
+
+    linker.func_wrap(
+        "globed",
+        "kv_set",
+        |mut caller: Caller<'_, PluginState>, key_ptr: u32, key_len: u32, value_ptr: u32, value_len: u32| -> anyhow::Result<i32> {
+            if key_len as usize > MAX_KEY_SIZE || value_len as usize > MAX_VALUE_SIZE {
+                return Ok(-1);
+            }
+
+            let key = read_string(&mut caller, key_ptr, key_len)?;
+            let value = read_string(&mut caller, value_ptr, value_len)?;
+            let state = caller.data_mut();
+
+            Ok(match state.storage.set(key, value) {
+                Ok(()) => 0,
+                Err(e) => {
+                    warn!("[plugin {}] failed to store a value: {e}", state.name);
+                    -1
+                }
+            })
+        },
+    )?;
+
+    linker.func_wrap(
+        "globed",
+        "kv_delete",
+        |mut caller: Caller<'_, PluginState>, key_ptr: u32, key_len: u32| -> anyhow::Result<i32> {
+            let key = read_string(&mut caller, key_ptr, key_len)?;
+            let state = caller.data_mut();
+
+            Ok(match state.storage.delete(&key) {
+                Ok(()) => 0,
+                Err(e) => {
+                    warn!("[plugin {}] failed to delete a value: {e}", state.name);
+                    -1
+                }
+            })
+        },
+    )?;
+
+    Ok(linker)
+}
+
+fn get_memory(caller: &mut Caller<'_, PluginState>) -> anyhow::Result<Memory> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| anyhow!("plugin does not export its memory"))
+}
+
+fn read_string(caller: &mut Caller<'_, PluginState>, ptr: u32, len: u32) -> anyhow::Result<String> {
+    let memory = get_memory(caller)?;
+    let start = ptr as usize;
+
+    let bytes = memory
+        .data(&*caller)
+        .get(start..start + len as usize)
+        .ok_or_else(|| anyhow!("out of bounds memory access"))?;
+
+    Ok(String::from_utf8(bytes.to_vec())?)
+}
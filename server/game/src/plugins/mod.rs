@@ -0,0 +1,30 @@
+//! WASM plugins, which let server owners add custom behavior without forking the server.
+//! Only functional when the server is built with the `plugins` feature, see the readme for the guest API.
+
+#[cfg(feature = "plugins")]
+mod host;
+#[cfg(feature = "plugins")]
+mod storage;
+
+use crate::server::GameServer;
+
+/// Version of the guest API, plugins must export `globed_plugin_api_version` returning this exact value.
+/// Bump this whenever the imports, exports or the event format change in a backwards incompatible way.
+pub const PLUGIN_API_VERSION: u32 = 1;
+
+/// Loads every plugin from the directory in `GLOBED_GS_PLUGIN_DIR` and starts delivering events to them.
+/// Does nothing if the variable is not set.
+pub fn start(game_server: &'static GameServer) {
+    let Ok(dir) = std::env::var("GLOBED_GS_PLUGIN_DIR") else {
+        return;
+    };
+
+    #[cfg(feature = "plugins")]
+    host::start(game_server, dir.into());
+
+    #[cfg(not(feature = "plugins"))]
+    {
+        let _ = (game_server, dir);
+        globed_shared::warn!("GLOBED_GS_PLUGIN_DIR is set, but the server was built without the `plugins` feature, ignoring");
+    }
+}
@@ -0,0 +1,62 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use globed_shared::anyhow::{self, bail};
+
+/// Maximum length of a single key, in bytes.
+pub const MAX_KEY_SIZE: usize = 256;
+/// Maximum length of a single value, in bytes.
+pub const MAX_VALUE_SIZE: usize = 65536;
+/// Maximum amount of keys a single plugin can store.
+const MAX_KEYS: usize = 16384;
+
+/// Persistent key-value storage of a single plugin, saved as a JSON file next to the plugin.
+pub struct PluginStorage {
+    path: PathBuf,
+    data: BTreeMap<String, String>,
+}
+
+impl PluginStorage {
+    pub fn load(path: PathBuf) -> anyhow::Result<Self> {
+        let data = if path.exists() {
+            serde_json::from_str(&std::fs::read_to_string(&path)?)?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self { path, data })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.data.get(key).map(String::as_str)
+    }
+
+    pub fn set(&mut self, key: String, value: String) -> anyhow::Result<()> {
+        if key.len() > MAX_KEY_SIZE || value.len() > MAX_VALUE_SIZE {
+            bail!("key or value is too long");
+        }
+
+        if self.data.len() >= MAX_KEYS && !self.data.contains_key(&key) {
+            bail!("too many keys");
+        }
+
+        self.data.insert(key, value);
+        self.save()
+    }
+
+    pub fn delete(&mut self, key: &str) -> anyhow::Result<()> {
+        if self.data.remove(key).is_some() {
+            self.save()?;
+        }
+
+        Ok(())
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        // write to a temporary file first, so a crash midway can't leave a corrupted file behind
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string(&self.data)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+
+        Ok(())
+    }
+}
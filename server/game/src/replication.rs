@@ -0,0 +1,586 @@
+//! Warm standby. An active server started with `GLOBED_GS_REPLICA_ADDRESS` keeps sending a snapshot of its rooms
+//! to a standby server started with `GLOBED_GS_STANDBY_ADDRESS`. The standby takes over the last snapshot it got when
+//! the active server hands off to it before stopping, or when an admin promotes it with `SIGUSR1` after a crash,
+//! so that players who reconnect to it find their rooms where they left them. It never takes over just because the
+//! stream went quiet, as that can just as well be a network problem, and then both servers would own the same rooms.
+//!
+//! The stream is encrypted and authenticated with a key derived from `GLOBED_GS_REPLICATION_KEY`,
+//! which must be the same on both servers. The standby starts every connection by sending a random challenge, every sealed
+//! frame carries that challenge and its number in the connection, so frames recorded from another connection or
+//! out of order are rejected. The first frame is a small hello, and the standby doesn't read anything bigger until it gets a valid one.
+
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use globed_shared::{
+    anyhow::{self, anyhow, bail},
+    crypto_secretbox::{aead::Aead, KeyInit, XSalsa20Poly1305},
+    debug, info,
+    rand::{rngs::OsRng, RngCore},
+    sha2::{Digest, Sha256},
+    unix_timestamp, warn, Secret,
+};
+
+use crate::{
+    server::GameServer,
+    snapshot::{self, Snapshot},
+    tokio::{
+        self,
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        sync::{Notify, Semaphore},
+        time::{sleep, timeout},
+    },
+};
+
+/// How often the active server sends a snapshot to the standby.
+const REPLICATION_INTERVAL: Duration = Duration::from_secs(5);
+/// How long the active server waits before reconnecting to the standby after the connection was lost.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// How long the standby waits without hearing from the active server before it warns about it and drops the connection.
+const STREAM_TIMEOUT: Duration = Duration::from_secs(15);
+/// How long a stopping server waits for the standby to be told to take over.
+const HANDOFF_TIMEOUT: Duration = Duration::from_secs(3);
+/// Frames bigger than this are rejected, a snapshot of even a very busy server is far smaller.
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+/// How many connections the standby handles at once, any more are closed right away.
+const MAX_CONNECTIONS: usize = 4;
+/// How long a new connection has to send its hello frame.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+/// Hello frames bigger than this are rejected, before the connection is authenticated nothing big is ever read.
+const MAX_HELLO_SIZE: usize = 128;
+/// Hellos whose timestamp is further than this (in seconds) from the clock of the standby are rejected, so that old ones can't be replayed.
+const MAX_HELLO_SKEW: i64 = 60;
+const HELLO_MAGIC: &[u8] = b"globed-replication";
+/// Size of the random challenge the standby sends at the start of every connection.
+const CHALLENGE_SIZE: usize = 32;
+
+/// Ties the frames of a connection to it and to their order, so that frames recorded earlier, for example a hand off
+/// from a previous planned shutdown, can't be replayed. Every sealed frame starts with the challenge and the frame number.
+struct Channel<'a> {
+    cipher: &'a XSalsa20Poly1305,
+    challenge: [u8; CHALLENGE_SIZE],
+    /// number of the next frame, the hello is 0
+    counter: u64,
+}
+
+impl<'a> Channel<'a> {
+    fn new(cipher: &'a XSalsa20Poly1305, challenge: [u8; CHALLENGE_SIZE]) -> Self {
+        Self {
+            cipher,
+            challenge,
+            counter: 0,
+        }
+    }
+
+    /// Seals the next frame of the connection.
+    fn seal(&mut self, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut plaintext = Vec::with_capacity(CHALLENGE_SIZE + 8 + data.len());
+        plaintext.extend_from_slice(&self.challenge);
+        plaintext.extend_from_slice(&self.counter.to_be_bytes());
+        plaintext.extend_from_slice(data);
+
+        self.counter += 1;
+        seal(self.cipher, &plaintext)
+    }
+
+    /// Opens the next frame of the connection, fails if it was sealed for another connection or isn't the next one.
+    fn open(&mut self, frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let plaintext = open(self.cipher, frame)?;
+
+        let (challenge, rest) = plaintext.split_at_checked(CHALLENGE_SIZE).ok_or_else(|| anyhow!("frame is too short"))?;
+        let (counter, data) = rest.split_at_checked(8).ok_or_else(|| anyhow!("frame is too short"))?;
+
+        if challenge != self.challenge {
+            bail!("frame was sealed for another connection");
+        }
+
+        let counter = u64::from_be_bytes(counter.try_into()?);
+        if counter != self.counter {
+            bail!("frame {counter} is out of order, expected frame {}", self.counter);
+        }
+
+        self.counter += 1;
+        Ok(data.to_vec())
+    }
+}
+
+/// The first byte of every frame after the hello says what it is.
+const FRAME_SNAPSHOT: u8 = 0;
+/// Sent by the active server when it stops on purpose, right after its last snapshot.
+const FRAME_HANDOFF: u8 = 1;
+
+/// Set on the active server once it replicates, so that stopping knows whether there is a standby to hand off to.
+static REPLICATING: AtomicBool = AtomicBool::new(false);
+/// Set once the standby was told to take over, the active server stops replicating after that.
+static HANDED_OFF: AtomicBool = AtomicBool::new(false);
+static HANDOFF_REQUESTED: Notify = Notify::const_new();
+static HANDOFF_SENT: Notify = Notify::const_new();
+
+enum Frame {
+    Snapshot(Snapshot),
+    HandOff,
+}
+
+/// State of a standby server.
+struct Standby {
+    game_server: &'static GameServer,
+    cipher: XSalsa20Poly1305,
+    /// the newest snapshot received from the active server
+    latest: Mutex<Option<Snapshot>>,
+    /// when the last snapshot was received
+    last_received: Mutex<Option<Instant>>,
+    /// set once the standby took over, after that the stream is ignored
+    promoted: AtomicBool,
+    /// set once the silence of the active server was logged, so that it's only logged once per outage
+    silence_reported: AtomicBool,
+}
+
+/// Starts replication, depending on the environment variables. Does nothing if neither address is set.
+pub fn start(game_server: &'static GameServer) {
+    let replica = std::env::var("GLOBED_GS_REPLICA_ADDRESS").ok();
+    let standby = std::env::var("GLOBED_GS_STANDBY_ADDRESS").ok();
+
+    if replica.is_none() && standby.is_none() {
+        return;
+    }
+
+    let cipher = match make_cipher() {
+        Ok(cipher) => cipher,
+        Err(e) => {
+            warn!("replication is disabled: {e}");
+            return;
+        }
+    };
+
+    match (replica, standby) {
+        (Some(_), Some(_)) => {
+            warn!("both GLOBED_GS_REPLICA_ADDRESS and GLOBED_GS_STANDBY_ADDRESS are set, a server can't be both, replication is disabled");
+        }
+        (Some(address), None) => {
+            REPLICATING.store(true, Ordering::SeqCst);
+            tokio::spawn(run_sender(game_server, cipher, address));
+        }
+        (None, Some(address)) => {
+            let standby: &'static Standby = Box::leak(Box::new(Standby {
+                game_server,
+                cipher,
+                latest: Mutex::new(None),
+                last_received: Mutex::new(None),
+                promoted: AtomicBool::new(false),
+                silence_reported: AtomicBool::new(false),
+            }));
+
+            tokio::spawn(async move {
+                if let Err(e) = standby.run(&address).await {
+                    warn!("failed to run the replication listener: {e}");
+                }
+            });
+
+            tokio::spawn(standby.watch());
+            tokio::spawn(standby.listen_for_promotion());
+        }
+        (None, None) => unreachable!(),
+    }
+}
+
+/// The key is derived from `GLOBED_GS_REPLICATION_KEY`, which can also reference a file or another environment variable.
+fn make_cipher() -> anyhow::Result<XSalsa20Poly1305> {
+    let spec = std::env::var("GLOBED_GS_REPLICATION_KEY").map_err(|_| anyhow!("GLOBED_GS_REPLICATION_KEY is not set"))?;
+    let secret = Secret::resolve(&spec).map_err(anyhow::Error::msg)?;
+
+    if secret.expose().is_empty() {
+        bail!("GLOBED_GS_REPLICATION_KEY is empty");
+    }
+
+    let key = Sha256::digest(secret.expose().as_bytes());
+    Ok(XSalsa20Poly1305::new(&key))
+}
+
+/// Encrypts the data, the frame is the nonce followed by the ciphertext.
+fn seal(cipher: &XSalsa20Poly1305, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut nonce = [0u8; XSalsa20Poly1305::NONCE_SIZE];
+    OsRng.fill_bytes(&mut nonce);
+
+    let ciphertext = cipher
+        .encrypt((&nonce).into(), data)
+        .map_err(|_| anyhow!("failed to encrypt the snapshot"))?;
+
+    let mut frame = Vec::with_capacity(nonce.len() + ciphertext.len());
+    frame.extend_from_slice(&nonce);
+    frame.extend_from_slice(&ciphertext);
+
+    Ok(frame)
+}
+
+fn encode_frame(frame: &Frame) -> Vec<u8> {
+    match frame {
+        Frame::Snapshot(snapshot) => {
+            let mut data = vec![FRAME_SNAPSHOT];
+            data.extend_from_slice(&snapshot.encode());
+            data
+        }
+        Frame::HandOff => vec![FRAME_HANDOFF],
+    }
+}
+
+fn decode_frame(data: &[u8]) -> anyhow::Result<Frame> {
+    match data.split_first() {
+        Some((&FRAME_SNAPSHOT, snapshot)) => Ok(Frame::Snapshot(Snapshot::decode(snapshot)?)),
+        Some((&FRAME_HANDOFF, [])) => Ok(Frame::HandOff),
+        Some((kind, _)) => bail!("invalid frame kind {kind}"),
+        None => bail!("empty frame"),
+    }
+}
+
+/// The hello frame is the magic followed by the current unix timestamp, sealed like any other frame.
+fn make_hello(channel: &mut Channel, timestamp: i64) -> anyhow::Result<Vec<u8>> {
+    let mut hello = Vec::with_capacity(HELLO_MAGIC.len() + 8);
+    hello.extend_from_slice(HELLO_MAGIC);
+    hello.extend_from_slice(&timestamp.to_be_bytes());
+
+    channel.seal(&hello)
+}
+
+fn verify_hello(channel: &mut Channel, frame: &[u8], now: i64) -> anyhow::Result<()> {
+    let hello = channel.open(frame)?;
+
+    let timestamp = hello
+        .strip_prefix(HELLO_MAGIC)
+        .and_then(|x| <[u8; 8]>::try_from(x).ok())
+        .map(i64::from_be_bytes)
+        .ok_or_else(|| anyhow!("malformed hello"))?;
+
+    if now.abs_diff(timestamp) > MAX_HELLO_SKEW.unsigned_abs() {
+        bail!("hello is too old, or the clocks of the servers are too far apart");
+    }
+
+    Ok(())
+}
+
+async fn write_frame(stream: &mut TcpStream, frame: &[u8]) -> anyhow::Result<()> {
+    stream.write_u32(frame.len() as u32).await?;
+    stream.write_all(frame).await?;
+    Ok(())
+}
+
+async fn read_frame(stream: &mut TcpStream, max_size: usize) -> anyhow::Result<Vec<u8>> {
+    let len = stream.read_u32().await? as usize;
+    if len > max_size {
+        bail!("frame too big ({len} bytes)");
+    }
+
+    let mut frame = vec![0u8; len];
+    stream.read_exact(&mut frame).await?;
+
+    Ok(frame)
+}
+
+fn open(cipher: &XSalsa20Poly1305, frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+    if frame.len() < XSalsa20Poly1305::NONCE_SIZE + XSalsa20Poly1305::TAG_SIZE {
+        bail!("frame is too short");
+    }
+
+    let (nonce, ciphertext) = frame.split_at(XSalsa20Poly1305::NONCE_SIZE);
+
+    cipher
+        .decrypt(nonce.into(), ciphertext)
+        .map_err(|_| anyhow!("failed to decrypt the snapshot, is GLOBED_GS_REPLICATION_KEY the same on both servers?"))
+}
+
+async fn run_sender(game_server: &'static GameServer, cipher: XSalsa20Poly1305, address: String) {
+    info!("Replicating rooms to the standby server at {address}");
+
+    while !HANDED_OFF.load(Ordering::SeqCst) {
+        match TcpStream::connect(&address).await {
+            Ok(mut stream) => {
+                debug!("connected to the standby server at {address}");
+
+                if let Err(e) = send_snapshots(game_server, &cipher, &mut stream).await {
+                    warn!("lost the connection to the standby server at {address}: {e}");
+                }
+            }
+            Err(e) => debug!("failed to connect to the standby server at {address}: {e}"),
+        }
+
+        sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn send_snapshots(game_server: &'static GameServer, cipher: &XSalsa20Poly1305, stream: &mut TcpStream) -> anyhow::Result<()> {
+    let mut challenge = [0u8; CHALLENGE_SIZE];
+    timeout(HELLO_TIMEOUT, stream.read_exact(&mut challenge))
+        .await
+        .map_err(|_| anyhow!("no challenge received in time"))??;
+
+    let mut channel = Channel::new(cipher, challenge);
+    write_frame(stream, &make_hello(&mut channel, unix_timestamp())?).await?;
+
+    loop {
+        let frame = channel.seal(&encode_frame(&Frame::Snapshot(Snapshot::capture(game_server))))?;
+        write_frame(stream, &frame).await?;
+
+        tokio::select! {
+            () = sleep(REPLICATION_INTERVAL) => {}
+            () = HANDOFF_REQUESTED.notified() => break,
+        }
+    }
+
+    // the server is stopping and nobody was disconnected yet, send the rooms as they are and tell the standby to take over
+    let frame = channel.seal(&encode_frame(&Frame::Snapshot(Snapshot::capture(game_server))))?;
+    write_frame(stream, &frame).await?;
+    write_frame(stream, &channel.seal(&encode_frame(&Frame::HandOff))?).await?;
+    stream.flush().await?;
+
+    HANDED_OFF.store(true, Ordering::SeqCst);
+    HANDOFF_SENT.notify_one();
+
+    Ok(())
+}
+
+/// Tells the standby to take over, called when the server stops on purpose, before anyone is disconnected.
+/// Does nothing if the server doesn't replicate to a standby.
+pub async fn hand_off() {
+    if !REPLICATING.load(Ordering::SeqCst) {
+        return;
+    }
+
+    HANDOFF_REQUESTED.notify_one();
+
+    if timeout(HANDOFF_TIMEOUT, HANDOFF_SENT.notified()).await.is_ok() {
+        info!("Told the standby server to take over the rooms");
+    } else {
+        warn!("failed to tell the standby server to take over in time, it has to be promoted by hand");
+    }
+}
+
+impl Standby {
+    async fn run(&'static self, address: &str) -> anyhow::Result<()> {
+        let listener = TcpListener::bind(address).await?;
+        let connections = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+        info!("Waiting for snapshots from the active server on {address}");
+
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(x) => x,
+                Err(e) => {
+                    warn!("failed to accept a replication connection: {e}");
+                    continue;
+                }
+            };
+
+            let Ok(permit) = connections.clone().try_acquire_owned() else {
+                debug!("too many replication connections, closing the one from {peer}");
+                continue;
+            };
+
+            tokio::spawn(async move {
+                if let Err(e) = self.receive_snapshots(stream, peer).await {
+                    warn!("replication connection from {peer} closed: {e}");
+                }
+
+                drop(permit);
+            });
+        }
+    }
+
+    async fn receive_snapshots(&self, mut stream: TcpStream, peer: SocketAddr) -> anyhow::Result<()> {
+        let mut challenge = [0u8; CHALLENGE_SIZE];
+        OsRng.fill_bytes(&mut challenge);
+        stream.write_all(&challenge).await?;
+
+        let mut channel = Channel::new(&self.cipher, challenge);
+
+        let hello = timeout(HELLO_TIMEOUT, read_frame(&mut stream, MAX_HELLO_SIZE))
+            .await
+            .map_err(|_| anyhow!("no hello received in time"))??;
+        verify_hello(&mut channel, &hello, unix_timestamp())?;
+
+        debug!("active server connected from {peer}");
+
+        loop {
+            // snapshots come every few seconds, a connection that goes quiet for longer would only hold up a slot
+            let frame = timeout(STREAM_TIMEOUT, read_frame(&mut stream, MAX_FRAME_SIZE))
+                .await
+                .map_err(|_| anyhow!("no snapshot received in {}s", STREAM_TIMEOUT.as_secs()))??;
+
+            if self.promoted.load(Ordering::Relaxed) {
+                continue;
+            }
+
+            let snapshot = match decode_frame(&channel.open(&frame)?)? {
+                Frame::Snapshot(snapshot) => snapshot,
+                Frame::HandOff => {
+                    self.promote("The active server is shutting down");
+                    return Ok(());
+                }
+            };
+
+            let mut latest = self.latest.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+            if latest.as_ref().is_none_or(|s| s.created_at <= snapshot.created_at) {
+                *latest = Some(snapshot);
+            }
+            drop(latest);
+
+            *self.last_received.lock().unwrap_or_else(std::sync::PoisonError::into_inner) = Some(Instant::now());
+            self.silence_reported.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Warns when the active server stops sending snapshots. The standby doesn't take over on its own,
+    /// as it can't tell a crashed server apart from a network problem between the two.
+    async fn watch(&'static self) {
+        while !self.promoted.load(Ordering::Relaxed) {
+            sleep(Duration::from_secs(1)).await;
+
+            let last_received = *self.last_received.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+            if !last_received.is_some_and(|t| t.elapsed() > STREAM_TIMEOUT) || self.silence_reported.swap(true, Ordering::Relaxed) {
+                continue;
+            }
+
+            warn!(
+                "The active server stopped replicating {}s ago. If it is down, send SIGUSR1 to this server to take over its rooms",
+                STREAM_TIMEOUT.as_secs()
+            );
+        }
+    }
+
+    /// Takes over the last snapshot when the server gets `SIGUSR1`. Not available on Windows, where the standby only takes over
+    /// when the active server hands off to it.
+    async fn listen_for_promotion(&'static self) {
+        #[cfg(unix)]
+        {
+            use crate::tokio::signal::unix::{signal, SignalKind};
+
+            let mut signals = match signal(SignalKind::user_defined1()) {
+                Ok(x) => x,
+                Err(e) => {
+                    warn!("failed to listen for SIGUSR1, the standby can only take over when the active server hands off to it: {e}");
+                    return;
+                }
+            };
+
+            if signals.recv().await.is_some() {
+                self.promote("SIGUSR1 received");
+            }
+        }
+    }
+
+    fn promote(&self, reason: &str) {
+        if self.promoted.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let snapshot = self.latest.lock().unwrap_or_else(std::sync::PoisonError::into_inner).take();
+        match snapshot {
+            Some(snapshot) if snapshot.is_expired() => {
+                warn!("{reason}, but the last snapshot from the active server is too old to take over");
+            }
+            Some(snapshot) => {
+                warn!("{reason}, taking over the rooms of the active server");
+                snapshot::restore(self.game_server, snapshot);
+            }
+            None => warn!("{reason}, but no snapshot was received from the active server"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cipher(key: &str) -> XSalsa20Poly1305 {
+        XSalsa20Poly1305::new(&Sha256::digest(key.as_bytes()))
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let cipher = cipher("key");
+        let frame = seal(&cipher, b"snapshot").unwrap();
+
+        assert_eq!(open(&cipher, &frame).unwrap(), b"snapshot");
+        assert!(open(&self::cipher("other key"), &frame).is_err());
+        assert!(open(&cipher, &frame[..XSalsa20Poly1305::NONCE_SIZE]).is_err());
+    }
+
+    const CHALLENGE: [u8; CHALLENGE_SIZE] = [7; CHALLENGE_SIZE];
+
+    #[test]
+    fn accepts_fresh_hello() {
+        let cipher = cipher("key");
+        let now = 1_700_000_000;
+
+        let hello = make_hello(&mut Channel::new(&cipher, CHALLENGE), now).unwrap();
+        assert!(hello.len() <= MAX_HELLO_SIZE);
+
+        assert!(verify_hello(&mut Channel::new(&cipher, CHALLENGE), &hello, now).is_ok());
+        assert!(verify_hello(&mut Channel::new(&cipher, CHALLENGE), &hello, now + MAX_HELLO_SKEW).is_ok());
+        assert!(verify_hello(&mut Channel::new(&cipher, CHALLENGE), &hello, now - MAX_HELLO_SKEW).is_ok());
+    }
+
+    #[test]
+    fn rejects_bad_hello() {
+        let cipher = cipher("key");
+        let other_cipher = self::cipher("other key");
+        let now = 1_700_000_000;
+        let hello = make_hello(&mut Channel::new(&cipher, CHALLENGE), now).unwrap();
+        let receiver = || Channel::new(&cipher, CHALLENGE);
+
+        assert!(verify_hello(&mut receiver(), &hello, now + MAX_HELLO_SKEW + 1).is_err());
+        assert!(verify_hello(&mut Channel::new(&other_cipher, CHALLENGE), &hello, now).is_err());
+        assert!(verify_hello(&mut receiver(), &receiver().seal(b"not a hello").unwrap(), now).is_err());
+
+        // a snapshot frame is not a hello, even though it's sealed with the right key
+        assert!(verify_hello(&mut receiver(), &receiver().seal(&[0u8; 64]).unwrap(), now).is_err());
+
+        // a hello captured from another connection has the wrong challenge
+        assert!(verify_hello(&mut Channel::new(&cipher, [8; CHALLENGE_SIZE]), &hello, now).is_err());
+    }
+
+    #[test]
+    fn rejects_replayed_handoff() {
+        let cipher = cipher("key");
+        let now = 1_700_000_000;
+
+        // a connection recorded during an earlier planned shutdown
+        let mut old = Channel::new(&cipher, CHALLENGE);
+        let old_hello = make_hello(&mut old, now).unwrap();
+        let old_handoff = old.seal(&encode_frame(&Frame::HandOff)).unwrap();
+
+        // the standby picks a new challenge for every connection, so neither frame is accepted on a new one
+        let mut standby = Channel::new(&cipher, [8; CHALLENGE_SIZE]);
+        assert!(verify_hello(&mut standby, &old_hello, now).is_err());
+        assert!(standby.open(&old_handoff).is_err());
+
+        // even with a fresh hello, the hand off belongs to the old connection
+        let mut sender = Channel::new(&cipher, [8; CHALLENGE_SIZE]);
+        let hello = make_hello(&mut sender, now).unwrap();
+        assert!(verify_hello(&mut standby, &hello, now).is_ok());
+        assert!(standby.open(&old_handoff).is_err());
+
+        // frames of the same connection can't be replayed or reordered either
+        let snapshot = sender.seal(&[FRAME_SNAPSHOT]).unwrap();
+        let handoff = sender.seal(&encode_frame(&Frame::HandOff)).unwrap();
+        assert!(standby.open(&handoff).is_err());
+        assert!(standby.open(&snapshot).is_ok());
+        assert!(standby.open(&snapshot).is_err());
+        assert!(matches!(decode_frame(&standby.open(&handoff).unwrap()), Ok(Frame::HandOff)));
+    }
+
+    #[test]
+    fn decodes_frame_kinds() {
+        assert!(matches!(decode_frame(&encode_frame(&Frame::HandOff)), Ok(Frame::HandOff)));
+
+        // a hand off never carries anything, and unknown or empty frames are rejected
+        assert!(decode_frame(&[FRAME_HANDOFF, 0]).is_err());
+        assert!(decode_frame(&[0xff]).is_err());
+        assert!(decode_frame(&[]).is_err());
+        assert!(decode_frame(&[FRAME_SNAPSHOT, 1, 2, 3]).is_err());
+    }
+}
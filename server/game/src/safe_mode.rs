@@ -0,0 +1,127 @@
+//! Safe mode, entered on startup when the server appears to be stuck in a crash loop. When `GLOBED_GS_CRASH_LOOP_FILE` is set,
+//! every startup is recorded in that file, and if the server was started too many times in a short period without shutting down
+//! cleanly, optional subsystems (voice, plugins, scripts and webhooks) are disabled and logging becomes more verbose,
+//! so that a misbehaving feature can't keep the whole server down. Setting `GLOBED_GS_SAFE_MODE=1` forces safe mode.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use globed_shared::{info, log, warn, LogLevelFilter};
+
+use crate::tokio::{self, time::sleep};
+
+/// Startups within this period are considered rapid restarts.
+const CRASH_LOOP_WINDOW: Duration = Duration::from_secs(600);
+/// Safe mode is entered on this many startups within `CRASH_LOOP_WINDOW` (including the current one).
+const CRASH_LOOP_STARTUPS: usize = 4;
+/// After running for this long, the server is considered stable and the recorded startups are forgotten.
+const STABLE_UPTIME: Duration = Duration::from_secs(300);
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static STARTUP_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+fn current_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |x| x.as_secs())
+}
+
+/// Whether the server is running in safe mode.
+#[inline]
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Records this startup and decides whether the server should run in safe mode. Must be called once, after the logger is set up.
+pub fn init_from_env() {
+    let forced = std::env::var("GLOBED_GS_SAFE_MODE").is_ok_and(|x| x == "1" || x == "true");
+
+    let crash_loop = match std::env::var("GLOBED_GS_CRASH_LOOP_FILE") {
+        Ok(path) => {
+            let path = PathBuf::from(path);
+            let startups = record_startup(&path);
+            let _ = STARTUP_FILE.set(path);
+
+            if startups >= CRASH_LOOP_STARTUPS {
+                warn!(
+                    "The server was started {startups} times in the last {} minutes, assuming a crash loop",
+                    CRASH_LOOP_WINDOW.as_secs() / 60
+                );
+                true
+            } else {
+                false
+            }
+        }
+        Err(_) => false,
+    };
+
+    if !forced && !crash_loop {
+        return;
+    }
+
+    ACTIVE.store(true, Ordering::Relaxed);
+
+    if log::max_level() < LogLevelFilter::Debug {
+        log::set_max_level(LogLevelFilter::Debug);
+    }
+
+    warn!("Starting in safe mode: voice chat, plugins, scripts and webhooks are disabled");
+    if crash_loop {
+        warn!(
+            "hint: check the logs of the previous runs for the cause. the startup record is cleared after {} minutes of uptime or a clean shutdown, and the next start after that is a normal one",
+            STABLE_UPTIME.as_secs() / 60
+        );
+    }
+}
+
+/// Returns the amount of recent startups, including this one.
+fn record_startup(path: &Path) -> usize {
+    let now = current_time();
+
+    let mut startups = match std::fs::read_to_string(path) {
+        Ok(content) => content
+            .lines()
+            .filter_map(|line| line.trim().parse::<u64>().ok())
+            .filter(|time| now.saturating_sub(*time) <= CRASH_LOOP_WINDOW.as_secs())
+            .collect::<Vec<_>>(),
+        Err(_) => Vec::new(),
+    };
+
+    startups.push(now);
+
+    let content = startups.iter().map(|x| format!("{x}\n")).collect::<String>();
+    if let Err(err) = std::fs::write(path, content) {
+        warn!("failed to write the startup record to {}: {err}", path.display());
+    }
+
+    startups.len()
+}
+
+/// Forgets the recorded startups, so the next startup is not counted towards a crash loop.
+pub fn clear_startups() {
+    if let Some(path) = STARTUP_FILE.get() {
+        if let Err(err) = std::fs::remove_file(path) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                warn!("failed to remove the startup record at {}: {err}", path.display());
+            }
+        }
+    }
+}
+
+/// Clears the recorded startups once the server has been running for long enough.
+pub fn start() {
+    if STARTUP_FILE.get().is_none() {
+        return;
+    }
+
+    tokio::spawn(async {
+        sleep(STABLE_UPTIME).await;
+
+        info!("Server has been running stably, clearing the startup record");
+        clear_startups();
+    });
+}
@@ -0,0 +1,290 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::Duration,
+};
+
+use async_watcher::{notify::RecursiveMode, AsyncDebouncer};
+use globed_shared::{anyhow, error, info, parking_lot::RwLock, warn};
+use rhai::{module_resolvers::DummyModuleResolver, CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+
+use super::HookResult;
+use crate::{
+    client::ServerThreadMessage,
+    data::{FastString, LevelId, ServerNoticePacket, MAX_MESSAGE_SIZE},
+    server::GameServer,
+    tokio,
+};
+
+/// Maximum amount of operations a single hook call can do, so that a broken script can't stall the server.
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_ROOM_NAME_SIZE: usize = 31;
+
+/// Needed by the native functions, which can't capture a reference to the server.
+static GAME_SERVER: OnceLock<&'static GameServer> = OnceLock::new();
+
+struct Script {
+    name: String,
+    ast: AST,
+    /// names of all functions defined in the script, to skip scripts that don't handle an event
+    functions: Vec<String>,
+}
+
+pub struct ScriptEngine {
+    engine: Engine,
+    scripts: RwLock<Vec<Script>>,
+}
+
+impl Default for ScriptEngine {
+    fn default() -> Self {
+        let mut engine = Engine::new();
+
+        // sandboxing, scripts have no way to access files or the rest of the system
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(65536);
+        engine.set_max_array_size(4096);
+        engine.set_max_map_size(4096);
+
+        engine.on_print(|text| info!("[script] {text}"));
+        engine.on_debug(|text, source, _pos| info!("[script {}] {text}", source.unwrap_or("unknown")));
+
+        engine.register_fn("notice", |account_id: i64, message: &str| {
+            let Some(game_server) = GAME_SERVER.get() else {
+                return;
+            };
+
+            let Some(thread) = i32::try_from(account_id).ok().and_then(|id| game_server.get_user_by_id(id)) else {
+                return;
+            };
+
+            let packet = ServerNoticePacket {
+                message: FastString::new(message),
+            };
+
+            // hooks are called from client threads, so this can't block
+            tokio::spawn(async move {
+                thread.push_new_message(ServerThreadMessage::BroadcastNotice(packet)).await;
+            });
+        });
+
+        engine.register_fn("broadcast", |message: &str| {
+            let Some(game_server) = GAME_SERVER.get() else {
+                return;
+            };
+
+            let threads = game_server
+                .clients
+                .lock()
+                .values()
+                .filter(|thr| thr.authenticated())
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let packet = ServerNoticePacket {
+                message: FastString::new(message),
+            };
+
+            tokio::spawn(async move {
+                for thread in threads {
+                    thread.push_new_message(ServerThreadMessage::BroadcastNotice(packet.clone())).await;
+                }
+            });
+        });
+
+        Self {
+            engine,
+            scripts: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+impl ScriptEngine {
+    pub fn start(&'static self, game_server: &'static GameServer, dir: PathBuf) {
+        let _ = GAME_SERVER.set(game_server);
+
+        self.reload(&dir);
+
+        tokio::spawn(async move {
+            if let Err(e) = self.watch(&dir).await {
+                error!("failed to watch the script directory, scripts won't be reloaded: {e}");
+            }
+        });
+    }
+
+    async fn watch(&self, dir: &Path) -> anyhow::Result<()> {
+        let (mut debouncer, mut file_events) = AsyncDebouncer::new_with_channel(Duration::from_secs(1), Some(Duration::from_secs(1))).await?;
+
+        debouncer.watcher().watch(dir, RecursiveMode::NonRecursive)?;
+
+        while let Some(_event) = file_events.recv().await {
+            self.reload(dir);
+        }
+
+        Ok(())
+    }
+
+    /// Compiles every script in the directory and replaces the currently loaded ones.
+    /// Scripts that fail to compile are skipped, the rest still get loaded.
+    fn reload(&self, dir: &Path) {
+        let mut paths = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|x| x.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                error!("failed to read the script directory {}: {e}", dir.display());
+                return;
+            }
+        };
+
+        // scripts are run in alphabetical order
+        paths.sort();
+
+        let mut scripts = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            let name = path.file_stem().map(|x| x.to_string_lossy().into_owned()).unwrap_or_default();
+
+            match self.engine.compile_file(path.clone()) {
+                Ok(ast) => {
+                    let functions = ast.iter_functions().map(|f| f.name.to_owned()).collect();
+                    scripts.push(Script { name, ast, functions });
+                }
+                Err(e) => error!("failed to compile script {}: {e}", path.display()),
+            }
+        }
+
+        info!("Loaded {} scripts", scripts.len());
+        *self.scripts.write() = scripts;
+    }
+
+    /// Calls the hook in every script that defines it, passing the event as `this`.
+    /// Returns the event after all modifications, or the cancellation reason if any script set `this.cancel`.
+    fn run_hook(&self, hook: &str, mut event: Map) -> Result<Map, Option<String>> {
+        let scripts = self.scripts.read();
+
+        for script in scripts.iter().filter(|s| s.functions.iter().any(|f| f == hook)) {
+            let mut this = Dynamic::from_map(event.clone());
+            let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut this);
+
+            if let Err(e) = self
+                .engine
+                .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &script.ast, hook, ())
+            {
+                // leave the event as it was before the failing script
+                warn!("script {} failed in {hook}: {e}", script.name);
+                continue;
+            }
+
+            let Some(modified) = this.try_cast::<Map>() else {
+                warn!("script {} replaced the event in {hook} with something that is not an object", script.name);
+                continue;
+            };
+
+            if modified.get("cancel").and_then(|x| x.as_bool().ok()).unwrap_or(false) {
+                let reason = modified
+                    .get("reason")
+                    .and_then(|x| x.clone().into_string().ok())
+                    .filter(|x| !x.is_empty());
+
+                return Err(reason);
+            }
+
+            event = modified;
+        }
+
+        Ok(event)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.scripts.read().is_empty()
+    }
+
+    pub fn on_login(&self, account_id: i32, name: &str) -> HookResult<()> {
+        if self.is_empty() {
+            return HookResult::Continue(());
+        }
+
+        let mut event = Map::new();
+        event.insert("account_id".into(), i64::from(account_id).into());
+        event.insert("name".into(), name.into());
+
+        match self.run_hook("on_login", event) {
+            Ok(_) => HookResult::Continue(()),
+            Err(reason) => HookResult::Cancel(reason),
+        }
+    }
+
+    pub fn on_chat(&self, account_id: i32, room_id: u32, level_id: LevelId, message: &str) -> HookResult<Option<String>> {
+        if self.is_empty() {
+            return HookResult::Continue(None);
+        }
+
+        let mut event = Map::new();
+        event.insert("account_id".into(), i64::from(account_id).into());
+        event.insert("room_id".into(), i64::from(room_id).into());
+        event.insert("level_id".into(), level_id.into());
+        event.insert("message".into(), message.into());
+
+        match self.run_hook("on_chat", event) {
+            Ok(event) => HookResult::Continue(changed_string(&event, "message", message, MAX_MESSAGE_SIZE - 1)),
+            Err(reason) => HookResult::Cancel(reason),
+        }
+    }
+
+    pub fn on_room_create(&self, account_id: i32, name: &str) -> HookResult<Option<String>> {
+        if self.is_empty() {
+            return HookResult::Continue(None);
+        }
+
+        let mut event = Map::new();
+        event.insert("account_id".into(), i64::from(account_id).into());
+        event.insert("name".into(), name.into());
+
+        match self.run_hook("on_room_create", event) {
+            Ok(event) => HookResult::Continue(changed_string(&event, "name", name, MAX_ROOM_NAME_SIZE)),
+            Err(reason) => HookResult::Cancel(reason),
+        }
+    }
+
+    pub fn on_room_join(&self, account_id: i32, room_id: u32) -> HookResult<()> {
+        if self.is_empty() {
+            return HookResult::Continue(());
+        }
+
+        let mut event = Map::new();
+        event.insert("account_id".into(), i64::from(account_id).into());
+        event.insert("room_id".into(), i64::from(room_id).into());
+
+        match self.run_hook("on_room_join", event) {
+            Ok(_) => HookResult::Continue(()),
+            Err(reason) => HookResult::Cancel(reason),
+        }
+    }
+}
+
+/// Returns the string field if it was changed by a script, truncated so that it fits into the packet.
+/// Fields that were changed to a non-string are ignored.
+fn changed_string(event: &Map, key: &str, original: &str, max_len: usize) -> Option<String> {
+    let mut value = event.get(key)?.clone().into_string().ok()?;
+
+    if value == original {
+        return None;
+    }
+
+    if value.len() > max_len {
+        let mut end = max_len;
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        value.truncate(end);
+    }
+
+    Some(value)
+}
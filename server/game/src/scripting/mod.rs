@@ -0,0 +1,82 @@
+//! Scripting hooks, a lighter alternative to plugins. Scripts can inspect, modify or cancel chat messages, logins and room events.
+//! Only functional when the server is built with the `scripting` feature, otherwise every hook lets the event through unchanged.
+
+#[cfg(feature = "scripting")]
+mod engine;
+
+use crate::{data::LevelId, server::GameServer};
+
+/// Outcome of running the hooks of every script for an event.
+pub enum HookResult<T> {
+    /// The event should go through, possibly with data modified by a script.
+    Continue(T),
+    /// A script cancelled the event, with an optional reason that can be shown to the player.
+    Cancel(Option<String>),
+}
+
+#[derive(Default)]
+pub struct ScriptHooks {
+    #[cfg(feature = "scripting")]
+    engine: engine::ScriptEngine,
+}
+
+#[cfg(feature = "scripting")]
+impl ScriptHooks {
+    pub fn on_login(&self, account_id: i32, name: &str) -> HookResult<()> {
+        self.engine.on_login(account_id, name)
+    }
+
+    /// Returns the new message if a script changed it.
+    pub fn on_chat(&self, account_id: i32, room_id: u32, level_id: LevelId, message: &str) -> HookResult<Option<String>> {
+        self.engine.on_chat(account_id, room_id, level_id, message)
+    }
+
+    /// Returns the new room name if a script changed it.
+    pub fn on_room_create(&self, account_id: i32, name: &str) -> HookResult<Option<String>> {
+        self.engine.on_room_create(account_id, name)
+    }
+
+    pub fn on_room_join(&self, account_id: i32, room_id: u32) -> HookResult<()> {
+        self.engine.on_room_join(account_id, room_id)
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+impl ScriptHooks {
+    #[inline]
+    pub fn on_login(&self, _account_id: i32, _name: &str) -> HookResult<()> {
+        HookResult::Continue(())
+    }
+
+    #[inline]
+    pub fn on_chat(&self, _account_id: i32, _room_id: u32, _level_id: LevelId, _message: &str) -> HookResult<Option<String>> {
+        HookResult::Continue(None)
+    }
+
+    #[inline]
+    pub fn on_room_create(&self, _account_id: i32, _name: &str) -> HookResult<Option<String>> {
+        HookResult::Continue(None)
+    }
+
+    #[inline]
+    pub fn on_room_join(&self, _account_id: i32, _room_id: u32) -> HookResult<()> {
+        HookResult::Continue(())
+    }
+}
+
+/// Loads every script from the directory in `GLOBED_GS_SCRIPT_DIR` and reloads them whenever the directory changes.
+/// Does nothing if the variable is not set.
+pub fn start(game_server: &'static GameServer) {
+    let Ok(dir) = std::env::var("GLOBED_GS_SCRIPT_DIR") else {
+        return;
+    };
+
+    #[cfg(feature = "scripting")]
+    game_server.state.scripts.engine.start(game_server, dir.into());
+
+    #[cfg(not(feature = "scripting"))]
+    {
+        let _ = (game_server, dir);
+        globed_shared::warn!("GLOBED_GS_SCRIPT_DIR is set, but the server was built without the `scripting` feature, ignoring");
+    }
+}
@@ -1,49 +1,133 @@
 use std::{
     collections::VecDeque,
-    net::{SocketAddr, SocketAddrV4},
-    sync::{atomic::Ordering, Arc},
-    time::Duration,
+    net::{IpAddr, SocketAddr},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock,
+    },
+    time::{Duration, Instant},
 };
 
+use futures_util::future::join_all;
 use globed_shared::{
     anyhow::{self, anyhow, bail},
     crypto_box::{aead::OsRng, PublicKey, SecretKey},
     esp::ByteBufferExtWrite as _,
     logger::*,
-    SyncMutex, UserEntry,
+    rand,
+    reqwest::StatusCode,
+    unix_timestamp, DuplicateLoginAction, IntMap, IntSet, PlayerReport, ReportStateChange, RoleDefinitions, SyncMutex, TransferTicket, UserEntry,
+    PROTOCOL_VERSION,
 };
 use rustc_hash::FxHashMap;
-use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
-    net::TcpStream,
-    sync::Notify,
-};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 #[allow(unused_imports)]
 use crate::tokio::sync::oneshot; // no way
 
 use crate::{
-    client::{ClientThreadState, PacketHandlingError},
+    client::{ClientStream, ClientThreadState, PacketHandlingError},
     tokio::{
         self,
         net::{TcpListener, UdpSocket},
+        sync::broadcast::error::RecvError,
     },
 };
 
 use crate::{
     bridge::{self, CentralBridge},
+    chaos,
     client::{thread::ClientThreadOutcome, unauthorized::UnauthorizedThread, ClientThread, ServerThreadMessage, UnauthorizedThreadOutcome},
+    cluster::{self, ClusterMessage},
     data::*,
+    events::ServerEvent,
+    grpc, http_api, local_config,
+    managers::{is_bot_account, AuthFailureBurst, ConnectionSlot, RecentPlayer},
+    plugins, replication, safe_mode, scripting, shutdown, snapshot,
     state::ServerState,
+    store,
+    util::{address_key, canonical_addr, FragmentReassembler, MessageDropCounters, SocketOptions},
+    webhook::WebhookMessage,
 };
 
+#[cfg(feature = "quic")]
+use crate::client::transport::{quic_endpoint, QuicStream};
+#[cfg(feature = "websocket")]
+use crate::{client::transport::WebSocketAcceptor, util::bind_tcp};
+
 const INLINE_BUFFER_SIZE: usize = 164;
-const MAX_UDP_PACKET_SIZE: usize = 65536;
+const DEFAULT_MAX_DATAGRAM_SIZE: usize = 65536;
+const MIN_DATAGRAM_SIZE: usize = 1200;
 const LARGE_BUFFER_SIZE: usize = 2usize.pow(19); // 2^19, 0.5mb
 
 const MARKER_CONN_INITIAL: u8 = 0xe0;
 const MARKER_CONN_RECOVERY: u8 = 0xe1;
 
+/// How long a client has to log in (or claim a recovered thread) after connecting, before its thread gets evicted
+const HANDSHAKE_DEADLINE: Duration = Duration::from_secs(30);
+const PENDING_SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+const SCHEDULED_NOTICE_INTERVAL: Duration = Duration::from_secs(5);
+const TRAFFIC_COLLECT_INTERVAL: Duration = Duration::from_secs(10);
+const BOT_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+const DEFAULT_MAX_PENDING_PER_IP: usize = 8;
+/// how many pending reports are sent to moderators at once, same as on the central server
+const MAX_PENDING_REPORTS: u32 = 100;
+/// upper limit on `voice_capture_seconds`, to keep the memory used for captures in check
+const MAX_VOICE_CAPTURE_SECONDS: u32 = 120;
+/// how often the queued room events are sent to the room webhooks
+const ROOM_WEBHOOK_INTERVAL: Duration = Duration::from_secs(5);
+/// how long to wait before asking the central server about role changes again, after a failed request
+const ROLES_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+/// how long a login waits for the old sessions it replaces to clean up, before giving up
+const DUPLICATE_LOGIN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Returns how many connections from one IP address can be logging in at the same time,
+/// based on `GLOBED_GS_MAX_PENDING_PER_IP` (by default `DEFAULT_MAX_PENDING_PER_IP`).
+fn max_pending_per_ip() -> usize {
+    static LIMIT: OnceLock<usize> = OnceLock::new();
+
+    *LIMIT.get_or_init(|| match std::env::var("GLOBED_GS_MAX_PENDING_PER_IP").ok().map(|x| x.parse::<usize>()) {
+        None => DEFAULT_MAX_PENDING_PER_IP,
+        Some(Ok(x)) if x > 0 => x,
+        Some(_) => {
+            warn!("invalid value for GLOBED_GS_MAX_PENDING_PER_IP, must be a positive number, using the default ({DEFAULT_MAX_PENDING_PER_IP})");
+            DEFAULT_MAX_PENDING_PER_IP
+        }
+    })
+}
+
+/// Returns how big a single udp datagram can be, based on `GLOBED_GS_MAX_DATAGRAM_SIZE` (by default `DEFAULT_MAX_DATAGRAM_SIZE`).
+/// Clients split bigger packets into fragments, which are put back together by the server.
+pub fn max_datagram_size() -> usize {
+    static SIZE: OnceLock<usize> = OnceLock::new();
+
+    *SIZE.get_or_init(|| match std::env::var("GLOBED_GS_MAX_DATAGRAM_SIZE").ok().map(|x| x.parse::<usize>()) {
+        None => DEFAULT_MAX_DATAGRAM_SIZE,
+        Some(Ok(x)) if (MIN_DATAGRAM_SIZE..=DEFAULT_MAX_DATAGRAM_SIZE).contains(&x) => x,
+        Some(_) => {
+            warn!(
+                "invalid value for GLOBED_GS_MAX_DATAGRAM_SIZE, must be between {MIN_DATAGRAM_SIZE} and {DEFAULT_MAX_DATAGRAM_SIZE}, using the default ({DEFAULT_MAX_DATAGRAM_SIZE})"
+            );
+            DEFAULT_MAX_DATAGRAM_SIZE
+        }
+    })
+}
+
+/// What `GameServer::check_already_logged_in` decided about a new login.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateLoginOutcome {
+    /// the account had no other sessions
+    FirstSession,
+    /// the other sessions stay connected next to the new one
+    Concurrent,
+    /// other sessions were disconnected to make room for the new one
+    ReplacedOld,
+    /// the new login must be refused
+    Rejected,
+    /// other sessions had to be disconnected, but did not finish cleaning up in time
+    TimedOut,
+}
+
 enum EitherClientThread {
     Authorized(Arc<ClientThread>),
     Unauthorized(Arc<UnauthorizedThread>),
@@ -55,14 +139,32 @@ pub struct GameServer {
     pub tcp_socket: TcpListener,
     pub udp_socket: UdpSocket,
     /// map udp peer : thread
-    pub clients: SyncMutex<FxHashMap<SocketAddrV4, Arc<ClientThread>>>,
+    pub clients: SyncMutex<FxHashMap<SocketAddr, Arc<ClientThread>>>,
+    /// map account id : threads, same threads as in `clients`. usually one per account, unless the roles of the player allow concurrent sessions
+    pub accounts: SyncMutex<IntMap<i32, Vec<Arc<ClientThread>>>>,
     pub unauthorized_clients: SyncMutex<VecDeque<Arc<UnauthorizedThread>>>,
     pub unclaimed_threads: SyncMutex<VecDeque<Arc<ClientThread>>>,
+    /// amount of connections that were closed for not logging in before `HANDSHAKE_DEADLINE`
+    pub stale_evictions: AtomicU64,
+    /// amount of connections that were refused because of too many pending connections from the same address
+    pub pending_rejections: AtomicU64,
+    /// amount of connections that were refused because the address is banned
+    pub banned_rejections: AtomicU64,
+    /// amount of connections that were refused because the address is blocked or has too many connections open
+    pub rate_limit_rejections: AtomicU64,
+    /// amount of packets that were rejected because the client wasn't logged in or lacked the admin permission for them
+    pub permission_violations: AtomicU64,
+    /// amount of clients that were disconnected for sending too many packets
+    pub flood_disconnects: AtomicU64,
+    /// amount of messages that were dropped because the queue of a client thread was full
+    pub message_drops: MessageDropCounters,
     pub secret_key: SecretKey,
     pub public_key: PublicKey,
     pub bridge: CentralBridge,
     pub standalone: bool,
     pub large_packet_buffer: SyncMutex<Box<[u8]>>,
+    /// udp packets that arrived in fragments and are still missing some of them
+    pub fragments: SyncMutex<FragmentReassembler>,
 }
 
 impl GameServer {
@@ -75,13 +177,22 @@ impl GameServer {
             tcp_socket,
             udp_socket,
             clients: SyncMutex::new(FxHashMap::default()),
+            accounts: SyncMutex::new(IntMap::default()),
             unauthorized_clients: SyncMutex::new(VecDeque::new()),
             unclaimed_threads: SyncMutex::new(VecDeque::new()),
+            stale_evictions: AtomicU64::new(0),
+            pending_rejections: AtomicU64::new(0),
+            banned_rejections: AtomicU64::new(0),
+            rate_limit_rejections: AtomicU64::new(0),
+            permission_violations: AtomicU64::new(0),
+            flood_disconnects: AtomicU64::new(0),
+            message_drops: MessageDropCounters::default(),
             secret_key,
             public_key,
             bridge,
             standalone,
             large_packet_buffer: SyncMutex::new(vec![0; LARGE_BUFFER_SIZE].into_boxed_slice()),
+            fragments: SyncMutex::new(FragmentReassembler::default()),
         }
     }
 
@@ -94,6 +205,26 @@ impl GameServer {
 
         self.state.room_manager.set_game_server(self);
 
+        // optional subsystems that could be causing a crash loop are not started in safe mode
+        if safe_mode::is_active() {
+            warn!("Safe mode is active, plugins and scripts are not loaded");
+        } else {
+            plugins::start(self);
+            scripting::start(self);
+        }
+
+        safe_mode::start();
+        grpc::start(self);
+        http_api::start(self);
+        local_config::start(self);
+        self.start_websocket_listener();
+        self.start_quic_listener();
+        store::start(self);
+        cluster::start(self);
+        snapshot::start(self);
+        replication::start(self);
+        shutdown::start(self);
+
         // spawn central conf refresher (runs every 5 minutes)
         if !self.standalone {
             tokio::spawn(async {
@@ -109,19 +240,43 @@ impl GameServer {
                 }
             });
 
-            // spawn the role info refresher as well (slightly less common)
+            // the central server answers this as soon as the role definitions change, so players see new badges right away
             tokio::spawn(async move {
-                let mut interval = tokio::time::interval(Duration::from_mins(30));
-                interval.tick().await;
+                let mut revision = self.bridge.central_conf.lock().roles_revision;
 
                 loop {
-                    interval.tick().await;
-                    let cc = self.bridge.central_conf.lock();
-                    self.state.role_manager.refresh_from(&cc);
+                    match self.bridge.wait_for_roles(revision).await {
+                        Ok(defs) => {
+                            if defs.revision != revision {
+                                revision = defs.revision;
+                                self.apply_role_definitions(defs).await;
+                            }
+                        }
+                        Err(e) => {
+                            warn!("failed to wait for role changes from the central server: {e}");
+                            tokio::time::sleep(ROLES_RETRY_INTERVAL).await;
+                        }
+                    }
                 }
             });
         }
 
+        // evict clients that connected but never finished logging in
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PENDING_SWEEP_INTERVAL);
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+                self.sweep_pending_threads();
+                self.fragments.lock().remove_expired();
+                self.state.rate_limiter.sweep();
+                self.state.room_manager.expire_transfers();
+                self.state.name_claims.sweep();
+                self.expire_config_overrides().await;
+            }
+        });
+
         // print some useful stats every once in a bit
         let interval = self.bridge.central_conf.lock().status_print_interval;
 
@@ -137,10 +292,101 @@ impl GameServer {
             });
         }
 
+        // keep a history of player counts, for metrics and the admin panel
+        let (interval, size) = {
+            let conf = self.bridge.central_conf.lock();
+            (conf.player_history_interval, conf.player_history_size)
+        };
+
+        if interval != 0 && size != 0 {
+            self.state.player_history.set_capacity(size as usize);
+
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(Duration::from_secs(interval));
+
+                loop {
+                    interval.tick().await;
+                    self.record_player_counts();
+                }
+            });
+        }
+
+        // attribute the traffic of every connection to the room and level it's in
+        self.state.traffic.start();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(TRAFFIC_COLLECT_INTERVAL);
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+                self.collect_traffic_stats();
+            }
+        });
+
+        // keep the player data of bots fresh, so clients keep rendering them
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(BOT_UPDATE_INTERVAL);
+
+            loop {
+                interval.tick().await;
+                self.update_bots();
+            }
+        });
+
+        // send notices that admins scheduled once they are due
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SCHEDULED_NOTICE_INTERVAL);
+
+            loop {
+                interval.tick().await;
+                self.send_scheduled_notices().await;
+            }
+        });
+
+        // tell players about the accounts they watch coming online, going offline or moving around
+        let mut events = self.state.events.subscribe();
+
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => self.notify_presence_subscribers(&event).await,
+                    Err(RecvError::Lagged(count)) => warn!("presence updates are too slow to keep up, {count} events were skipped"),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        // tell room webhooks about players coming and going
+        let mut events = self.state.events.subscribe();
+
+        tokio::spawn(async move {
+            // the room every player is in, so that we know which room they left
+            let mut player_rooms = IntMap::default();
+
+            loop {
+                match events.recv().await {
+                    Ok(event) => self.queue_room_webhook_events(&event, &mut player_rooms),
+                    Err(RecvError::Lagged(count)) => warn!("room webhooks are too slow to keep up, {count} events were skipped"),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        // events are sent in batches, so that a busy room doesn't get its webhook rate limited by Discord
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(ROOM_WEBHOOK_INTERVAL);
+
+            loop {
+                interval.tick().await;
+                self.send_room_webhooks().await;
+            }
+        });
+
         // spawn the udp packet handler
 
         tokio::spawn(async move {
-            let mut buf = [0u8; MAX_UDP_PACKET_SIZE];
+            let mut buf = vec![0u8; max_datagram_size()];
 
             loop {
                 match self.recv_and_handle_udp(&mut buf).await {
@@ -168,32 +414,219 @@ impl GameServer {
         }
     }
 
+    /// Returns a connection slot if a new connection from the given address should be accepted.
+    /// The slot must be kept for as long as the connection is open.
+    pub(crate) fn admit_connection(&self, peer: SocketAddr) -> Option<ConnectionSlot<'_>> {
+        if self.state.ip_bans.is_banned(peer.ip()) {
+            self.banned_rejections.fetch_add(1, Ordering::Relaxed);
+            debug!("rejecting connection from {peer}, the address is banned");
+            return None;
+        }
+
+        // soak test clients all connect from loopback, and log in all at once
+        if !self.state.rate_limiter.is_exempt(peer.ip()) && self.pending_thread_count(peer.ip()) >= max_pending_per_ip() {
+            self.pending_rejections.fetch_add(1, Ordering::Relaxed);
+            debug!("rejecting connection from {peer}, too many pending connections from this address");
+            return None;
+        }
+
+        let slot = self.state.rate_limiter.try_connect(peer.ip());
+        if slot.is_none() {
+            self.rate_limit_rejections.fetch_add(1, Ordering::Relaxed);
+            debug!("rejecting connection from {peer}, the address is blocked or has too many connections open");
+        }
+
+        slot
+    }
+
     async fn accept_connection(&'static self) -> anyhow::Result<()> {
         let (socket, peer) = self.tcp_socket.accept().await?;
+        let peer = canonical_addr(peer);
 
-        let peer = match peer {
-            SocketAddr::V4(x) => x,
-            SocketAddr::V6(_) => bail!("rejecting request from ipv6 host"),
+        let Some(slot) = self.admit_connection(peer) else {
+            return Ok(());
         };
 
         debug!("accepting tcp connection from {peer}");
 
-        tokio::spawn(self.client_loop(socket, peer));
+        if let Err(err) = SocketOptions::get().apply_tcp(&socket) {
+            warn!("failed to apply socket options to the connection from {peer}: {err}");
+        }
+
+        tokio::spawn(self.client_loop(ClientStream::Tcp(socket), peer, slot));
+
+        Ok(())
+    }
+
+    /// Starts accepting WebSocket connections on `GLOBED_GS_WS_ADDRESS`, for clients whose network blocks raw TCP and UDP.
+    /// Does nothing if the variable is not set.
+    #[cfg(feature = "websocket")]
+    fn start_websocket_listener(&'static self) {
+        let Ok(address) = std::env::var("GLOBED_GS_WS_ADDRESS") else {
+            return;
+        };
+
+        let result = address
+            .parse::<SocketAddr>()
+            .map_err(anyhow::Error::from)
+            .and_then(|addr| Ok((bind_tcp(addr)?, WebSocketAcceptor::from_env()?)));
+
+        let (listener, acceptor) = match result {
+            Ok(x) => x,
+            Err(err) => {
+                error!("failed to start the WebSocket listener on {address}: {err}");
+                return;
+            }
+        };
+
+        info!(
+            "Accepting WebSocket connections on {address}{}",
+            if acceptor.is_tls() { " (TLS)" } else { "" }
+        );
+
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = self.accept_websocket_connection(&listener, &acceptor).await {
+                    error!("Failed to accept a WebSocket connection: {err}");
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                }
+            }
+        });
+    }
+
+    #[cfg(not(feature = "websocket"))]
+    #[allow(clippy::unused_self)]
+    fn start_websocket_listener(&'static self) {
+        if std::env::var("GLOBED_GS_WS_ADDRESS").is_ok() {
+            warn!("GLOBED_GS_WS_ADDRESS is set, but the server was built without the `websocket` feature, ignoring");
+        }
+    }
+
+    #[cfg(feature = "websocket")]
+    async fn accept_websocket_connection(&'static self, listener: &TcpListener, acceptor: &WebSocketAcceptor) -> anyhow::Result<()> {
+        let (socket, peer) = listener.accept().await?;
+        let peer = canonical_addr(peer);
+
+        let Some(slot) = self.admit_connection(peer) else {
+            return Ok(());
+        };
+
+        debug!("accepting websocket connection from {peer}");
+
+        if let Err(err) = SocketOptions::get().apply_tcp(&socket) {
+            warn!("failed to apply socket options to the connection from {peer}: {err}");
+        }
+
+        let acceptor = acceptor.clone();
+
+        tokio::spawn(async move {
+            // like with tcp, no thread exists until the handshake is done, so it needs its own deadline
+            match tokio::time::timeout(HANDSHAKE_DEADLINE, acceptor.accept(socket)).await {
+                Ok(Ok(stream)) => self.client_loop(ClientStream::WebSocket(Box::new(stream)), peer, slot).await,
+                Ok(Err(err)) => debug!("websocket handshake with {peer} failed: {err}"),
+                Err(_) => {
+                    self.stale_evictions.fetch_add(1, Ordering::Relaxed);
+                    debug!("closing websocket connection from {peer}, the handshake did not finish in time");
+                }
+            }
+        });
 
         Ok(())
     }
 
+    /// Starts accepting QUIC connections on `GLOBED_GS_QUIC_ADDRESS`, where streams and datagrams replace the TCP and UDP sockets.
+    /// Does nothing if the variable is not set.
+    #[cfg(feature = "quic")]
+    fn start_quic_listener(&'static self) {
+        let Ok(address) = std::env::var("GLOBED_GS_QUIC_ADDRESS") else {
+            return;
+        };
+
+        let endpoint = match address.parse::<SocketAddr>().map_err(anyhow::Error::from).and_then(quic_endpoint) {
+            Ok(x) => x,
+            Err(err) => {
+                error!("failed to start the QUIC listener on {address}: {err}");
+                return;
+            }
+        };
+
+        info!("Accepting QUIC connections on {address}");
+
+        tokio::spawn(async move {
+            while let Some(incoming) = endpoint.accept().await {
+                self.accept_quic_connection(incoming);
+            }
+
+            error!("QUIC endpoint was closed, no longer accepting QUIC connections");
+        });
+    }
+
+    #[cfg(not(feature = "quic"))]
+    #[allow(clippy::unused_self)]
+    fn start_quic_listener(&'static self) {
+        if std::env::var("GLOBED_GS_QUIC_ADDRESS").is_ok() {
+            warn!("GLOBED_GS_QUIC_ADDRESS is set, but the server was built without the `quic` feature, ignoring");
+        }
+    }
+
+    #[cfg(feature = "quic")]
+    fn accept_quic_connection(&'static self, incoming: quinn::Incoming) {
+        let peer = canonical_addr(incoming.remote_address());
+
+        let Some(slot) = self.admit_connection(peer) else {
+            incoming.refuse();
+            return;
+        };
+
+        debug!("accepting quic connection from {peer}");
+
+        tokio::spawn(async move {
+            let stream = match tokio::time::timeout(HANDSHAKE_DEADLINE, QuicStream::accept(incoming)).await {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(err)) => {
+                    debug!("quic handshake with {peer} failed: {err}");
+                    return;
+                }
+                Err(_) => {
+                    self.stale_evictions.fetch_add(1, Ordering::Relaxed);
+                    debug!("closing quic connection from {peer}, the handshake did not finish in time");
+                    return;
+                }
+            };
+
+            // datagrams are handled exactly like udp packets, they stop once the connection is closed
+            let connection = stream.connection().clone();
+            let tunnel_peer = stream.peer;
+
+            tokio::spawn(async move {
+                while let Ok(datagram) = connection.read_datagram().await {
+                    if let Err(err) = self.dispatch_quic_datagram(&datagram, tunnel_peer).await {
+                        debug!("failed to handle a quic datagram from {peer}: {err}");
+                    }
+                }
+            });
+
+            self.client_loop(ClientStream::Quic(Box::new(stream)), peer, slot).await;
+        });
+    }
+
     #[allow(clippy::manual_let_else, clippy::too_many_lines)]
-    async fn client_loop(&'static self, mut socket: TcpStream, peer: SocketAddrV4) {
+    async fn client_loop(&'static self, mut socket: ClientStream, peer: SocketAddr, _slot: ConnectionSlot<'static>) {
         // wait for incoming data, client should tell us whether it's an initial login or a recovery.
-        let result: crate::client::Result<bool> = async {
+        let marker = async {
             match socket.read_u8().await? {
                 MARKER_CONN_INITIAL => Ok(false),
                 MARKER_CONN_RECOVERY => Ok(true),
                 _ => Err(PacketHandlingError::InvalidStreamMarker),
             }
-        }
-        .await;
+        };
+
+        // no thread exists yet at this point, so the sweeper can't see this connection
+        let Ok(result) = tokio::time::timeout(HANDSHAKE_DEADLINE, marker).await else {
+            self.stale_evictions.fetch_add(1, Ordering::Relaxed);
+            debug!("closing connection from {peer}, no data was sent");
+            return;
+        };
 
         let mut either_thread: EitherClientThread;
 
@@ -317,12 +750,21 @@ impl GameServer {
                     // upgrade to an authorized ClientThread and add it into clients map
                     let thread = Arc::new(thread.upgrade());
 
-                    self.clients.lock().insert(udp_peer, thread.clone());
+                    self.clients.lock().insert(canonical_addr(udp_peer), thread.clone());
+                    self.accounts
+                        .lock()
+                        .entry(thread.account_id.load(Ordering::Relaxed))
+                        .or_default()
+                        .push(thread.clone());
+
+                    // only now the player can be seen by others, so this is when their friends are told they are online
+                    thread.emit_presence_change();
 
                     either_thread = EitherClientThread::Authorized(thread);
                 }
                 EitherClientThread::Authorized(thread) => {
                     let outcome = thread.run().await;
+                    thread.clear_lossy();
 
                     // thread has terminated, remove it from the map.
 
@@ -331,7 +773,20 @@ impl GameServer {
                         // safety: this is pretty unsafe
                         // TODO
                         let udp_peer = unsafe { thread.socket.get() }.udp_peer.expect("no udp peer in established thread");
+                        let udp_peer = canonical_addr(udp_peer);
                         clients.remove(&udp_peer);
+                        self.fragments.lock().remove_peer(udp_peer);
+                    }
+
+                    {
+                        let account_id = thread.account_id.load(Ordering::Relaxed);
+                        let mut accounts = self.accounts.lock();
+                        if let Some(threads) = accounts.get_mut(&account_id) {
+                            threads.retain(|x| !Arc::ptr_eq(x, thread));
+                            if threads.is_empty() {
+                                accounts.remove(&account_id);
+                            }
+                        }
                     }
 
                     // wait until there are no more references to the thread
@@ -390,36 +845,92 @@ impl GameServer {
     }
 
     async fn recv_and_handle_udp(&self, buf: &mut [u8]) -> anyhow::Result<()> {
-        let (len, peer) = self.udp_socket.recv_from(buf).await?;
+        // replies have to go to the address as the socket reported it, on a dual-stack socket IPv4 peers are IPv4-mapped
+        // IPv6 addresses and some platforms refuse to send to a plain IPv4 one. the canonical form is only used as a key
+        let (len, raw_peer) = self.udp_socket.recv_from(buf).await?;
+        let peer = canonical_addr(raw_peer);
 
-        let peer = match peer {
-            SocketAddr::V4(x) => x,
-            SocketAddr::V6(_) => bail!("rejecting request from ipv6 host"),
-        };
+        if self.state.ip_bans.is_banned(peer.ip()) {
+            return Ok(());
+        }
 
         // if it's a ping packet, we can handle it here. otherwise we send it to the appropriate thread.
-        if !self.try_udp_handle(&buf[..len], peer).await? {
-            let thread = { self.clients.lock().get(&peer).cloned() };
-            if let Some(thread) = thread {
-                thread
-                    .push_new_message(if len <= INLINE_BUFFER_SIZE {
-                        let mut inline_buf = [0u8; INLINE_BUFFER_SIZE];
-                        inline_buf[..len].clone_from_slice(&buf[..len]);
+        if self.try_udp_handle(&buf[..len], raw_peer).await? {
+            return Ok(());
+        }
 
-                        ServerThreadMessage::SmallPacket((inline_buf, len))
-                    } else {
-                        ServerThreadMessage::Packet(buf[..len].to_vec())
-                    })
-                    .await;
+        let thread = { self.clients.lock().get(&peer).cloned() };
+        let Some(thread) = thread else {
+            return Ok(());
+        };
+
+        // only packets from an existing client are counted, as the source address of any other datagram could be spoofed
+        // to get somebody else's address blocked
+        if !self.state.rate_limiter.try_packet(peer.ip()) {
+            return Ok(());
+        }
+
+        self.push_udp_packet(&thread, &buf[..len], peer).await
+    }
+
+    /// Sends a packet that arrived as a quic datagram to the thread of the given peer, if there is one.
+    #[cfg(feature = "quic")]
+    async fn dispatch_quic_datagram(&self, data: &[u8], peer: SocketAddr) -> anyhow::Result<()> {
+        let thread = { self.clients.lock().get(&peer).cloned() };
+        let Some(thread) = thread else {
+            return Ok(());
+        };
+
+        self.push_udp_packet(&thread, data, peer).await
+    }
+
+    /// Sends a packet that arrived over udp (or as a quic datagram) to the thread of its peer.
+    async fn push_udp_packet(&self, thread: &ClientThread, data: &[u8], peer: SocketAddr) -> anyhow::Result<()> {
+        // fragments are only buffered for peers with a thread, so that nobody else can make us hold onto memory
+        if let Some(packet) = self.try_reassemble(data, peer)? {
+            if let Some(packet) = packet {
+                thread.push_new_message(ServerThreadMessage::Packet(packet)).await;
             }
+
+            return Ok(());
         }
 
+        let len = data.len();
+
+        thread
+            .push_new_message(if len <= INLINE_BUFFER_SIZE {
+                let mut inline_buf = [0u8; INLINE_BUFFER_SIZE];
+                inline_buf[..len].clone_from_slice(data);
+
+                ServerThreadMessage::SmallPacket((inline_buf, len))
+            } else {
+                ServerThreadMessage::Packet(data.to_vec())
+            })
+            .await;
+
         Ok(())
     }
 
+    /// Returns `None` if the datagram is not a fragment, otherwise `Some` with the whole packet if this was its last missing fragment.
+    fn try_reassemble(&self, data: &[u8], peer: SocketAddr) -> anyhow::Result<Option<Option<Vec<u8>>>> {
+        let mut byte_reader = ByteReader::from_bytes(data);
+        let header = byte_reader.read_packet_header().map_err(|e| anyhow!("{e}"))?;
+
+        if header.packet_id != UdpFragmentPacket::PACKET_ID {
+            return Ok(None);
+        }
+
+        let UdpFragmentPacket { message_id, index, count } = UdpFragmentPacket::decode_from_reader(&mut byte_reader).map_err(|e| anyhow!("{e}"))?;
+        let payload = &data[PacketHeader::SIZE + UdpFragmentPacket::ENCODED_SIZE..];
+
+        let packet = self.fragments.lock().insert(peer, message_id, index, count, payload);
+
+        Ok(Some(packet))
+    }
+
     /* various calls for other threads */
 
-    pub fn claim_thread(&self, udp_addr: SocketAddrV4, secret_key: u32) -> bool {
+    pub fn claim_thread(&self, udp_addr: SocketAddr, secret_key: u32) -> bool {
         let thread = self.unauthorized_clients.lock().iter().find(|x| x.secret_key == secret_key).cloned();
 
         if let Some(thread) = thread {
@@ -430,24 +941,241 @@ impl GameServer {
         }
     }
 
-    pub async fn broadcast_voice_packet(&self, vpkt: &Arc<VoiceBroadcastPacket>, level_id: LevelId, room_id: u32) {
-        self.broadcast_user_message(&ServerThreadMessage::BroadcastVoice(vpkt.clone()), vpkt.player_id, level_id, room_id)
-            .await;
+    /// send a voice packet to everyone on the level who can hear it under the voice policy of the room,
+    /// returns `false` if the policy doesn't let the player talk at all
+    pub async fn broadcast_voice_packet(&self, vpkt: &Arc<VoiceBroadcastPacket>, level_id: LevelId, room_id: u32) -> bool {
+        let origin_id = vpkt.player_id;
+
+        let threads = self.state.room_manager.with_any(room_id, |pm| {
+            let policy = pm.settings.voice_policy;
+            if !policy.allows_speaker(room_id != 0 && pm.can_manage(origin_id)) {
+                return None;
+            }
+
+            let players = pm.manager.get_level(level_id).map_or(&[][..], Vec::as_slice);
+            let spectators = pm.get_spectators(origin_id);
+
+            if players.is_empty() && spectators.is_empty() {
+                return Some(Vec::new());
+            }
+
+            let position_of = |account_id: i32| pm.manager.get_player_data(account_id).map(|p| p.data.player1.position);
+            let speaker_position = position_of(origin_id);
+
+            let in_range = |account_id: i32| {
+                speaker_position
+                    .zip(position_of(account_id))
+                    .is_some_and(|(a, b)| (a.x.get() - b.x.get()).hypot(a.y.get() - b.y.get()) <= PROXIMITY_VOICE_RANGE)
+            };
+
+            // spectators always hear the player they are watching
+            let hears = |account_id: i32| {
+                spectators.contains(&account_id) || (players.contains(&account_id) && (policy != RoomVoicePolicy::Proximity || in_range(account_id)))
+            };
+
+            Some(
+                self.clients
+                    .lock()
+                    .values()
+                    .filter(|thread| {
+                        let account_id = thread.account_id.load(Ordering::Relaxed);
+                        account_id != origin_id && hears(account_id)
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            )
+        });
+
+        let Some(threads) = threads else {
+            return false;
+        };
+
+        let msg = ServerThreadMessage::BroadcastVoice(vpkt.clone());
+
+        for thread in threads {
+            if chaos::should_drop_broadcast() {
+                continue;
+            }
+
+            thread.push_new_message(msg.clone()).await;
+        }
+
+        true
     }
 
+    /// send a chat message to everyone on the level, or only to the author's chat channel if the level has many players
     pub async fn broadcast_chat_packet(&self, tpkt: &ChatMessageBroadcastPacket, level_id: LevelId, room_id: u32) {
-        self.broadcast_user_message(&ServerThreadMessage::BroadcastText(tpkt.clone()), tpkt.player_id, level_id, room_id)
+        let threads: Vec<_> = self.state.room_manager.with_any(room_id, |pm| {
+            let Some(players) = pm.manager.get_level(level_id) else {
+                return Vec::new();
+            };
+
+            let channel = pm.manager.get_chat_channel(level_id, tpkt.player_id);
+
+            self.clients
+                .lock()
+                .values()
+                .filter(|thread| {
+                    let account_id = thread.account_id.load(Ordering::Relaxed);
+                    account_id != tpkt.player_id && players.contains(&account_id) && pm.manager.is_in_chat_channel(account_id, channel)
+                })
+                .cloned()
+                .collect()
+        });
+
+        // moderators following this chat get it even if they aren't there, unless they already got it normally
+        let followers = self.state.chat_tails.followers_of(room_id, level_id);
+        if !followers.is_empty() {
+            let packet = AdminChatTailPacket {
+                room_id,
+                level_id,
+                player_id: tpkt.player_id,
+                player_name: self.get_known_player_name(tpkt.player_id),
+                message_id: tpkt.message_id,
+                message: tpkt.message.clone(),
+            };
+
+            let moderators: Vec<_> = self
+                .clients
+                .lock()
+                .values()
+                .filter(|thread| {
+                    let account_id = thread.account_id.load(Ordering::Relaxed);
+                    followers.contains(&account_id) && !threads.iter().any(|thr| Arc::ptr_eq(thr, thread))
+                })
+                .cloned()
+                .collect();
+
+            for thread in moderators {
+                thread.push_new_message(ServerThreadMessage::ChatTail(packet.clone())).await;
+            }
+        }
+
+        for thread in threads {
+            if chaos::should_drop_broadcast() {
+                continue;
+            }
+
+            thread.push_new_message(ServerThreadMessage::BroadcastText(tpkt.clone())).await;
+        }
+    }
+
+    /// tell everyone who could have seen the message (including its author) that it was deleted
+    pub async fn broadcast_chat_deletion(&self, packet: &ChatMessageDeletedPacket, level_id: LevelId, room_id: u32) {
+        self.broadcast_user_message(&ServerThreadMessage::BroadcastTextDeletion(packet.clone()), 0, level_id, room_id)
             .await;
     }
 
-    /// iterate over every player in this list and run F
-    #[inline]
-    pub fn for_each_player<F, A>(&self, ids: &[i32], f: F, additional: &mut A) -> usize
-    where
-        F: Fn(&PlayerAccountData, usize, &mut A) -> bool,
-    {
-        self.clients
-            .lock()
+    /// start a countdown for everyone on the level, including the room owner who started it
+    pub async fn broadcast_room_countdown(&self, level_id: LevelId, room_id: u32, ends_at: Instant) {
+        self.broadcast_user_message(&ServerThreadMessage::RoomCountdown(level_id, ends_at), 0, level_id, room_id)
+            .await;
+    }
+
+    /// celebrate a level completion with everyone else on the level
+    pub async fn broadcast_level_completion(&self, packet: LevelCompletedBroadcastPacket, level_id: LevelId, room_id: u32) {
+        let origin_id = packet.player_id;
+        self.broadcast_user_message(&ServerThreadMessage::LevelCompletion(packet), origin_id, level_id, room_id)
+            .await;
+    }
+
+    /// relay a level event to everyone else on the level
+    pub async fn broadcast_level_event(&self, packet: LevelEventBroadcastPacket, level_id: LevelId, room_id: u32) {
+        let origin_id = packet.player_id;
+        self.broadcast_user_message(&ServerThreadMessage::LevelEvent(packet), origin_id, level_id, room_id)
+            .await;
+    }
+
+    /// relay the full rate data of a spectated player to their spectators, returns how many spectators they have
+    pub async fn broadcast_spectated_data(&self, packet: SpectatedPlayerDataPacket, room_id: u32) -> usize {
+        let origin_id = packet.data.account_id;
+        self.broadcast_spectator_message(&ServerThreadMessage::SpectatedData(packet), origin_id, room_id)
+            .await
+    }
+
+    /// tell a player that someone started or stopped spectating them
+    pub async fn notify_spectators_changed(&self, account_id: i32) {
+        if let Some(thread) = self.get_user_by_id(account_id) {
+            thread.mark_spectators_changed().await;
+        }
+    }
+
+    /// tell the players mentioned in a chat message about it, only players who could see the message can be mentioned
+    pub async fn broadcast_chat_mentions(&self, packet: &ChatMentionPacket, names: &[&str], level_id: LevelId, room_id: u32) {
+        let threads: Vec<_> = self.state.room_manager.with_any(room_id, |pm| {
+            let Some(players) = pm.manager.get_level(level_id) else {
+                return Vec::new();
+            };
+
+            let channel = pm.manager.get_chat_channel(level_id, packet.player_id);
+
+            self.clients
+                .lock()
+                .values()
+                .filter(|thread| {
+                    let account_id = thread.account_id.load(Ordering::Relaxed);
+                    account_id != packet.player_id
+                        && players.contains(&account_id)
+                        && pm.manager.is_in_chat_channel(account_id, channel)
+                        && names.iter().any(|name| thread.account_data.lock().name.eq_ignore_ascii_case(name))
+                })
+                .cloned()
+                .collect()
+        });
+
+        for thread in threads {
+            thread.push_new_message(ServerThreadMessage::ChatMention(packet.clone())).await;
+        }
+    }
+
+    /// push an extra level snapshot to everyone on the level with a lossy connection, as they are likely to miss regular updates
+    pub async fn broadcast_level_snapshot(&self, origin_id: i32, level_id: LevelId, room_id: u32) {
+        if self.state.lossy_player_count.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+
+        // this runs for every player data packet, so only copy the IDs out of the room and resolve them outside of the lock
+        let players: Vec<i32> = self.state.room_manager.with_any(room_id, |pm| {
+            pm.manager
+                .get_level(level_id)
+                .map(|players| players.iter().copied().filter(|id| *id != origin_id).collect())
+                .unwrap_or_default()
+        });
+
+        if players.is_empty() {
+            return;
+        }
+
+        let threads = self.get_users_by_ids(&players);
+
+        for thread in threads.iter().filter(|thread| thread.is_lossy.load(Ordering::Relaxed)) {
+            thread.push_new_message(ServerThreadMessage::LevelSnapshot).await;
+        }
+    }
+
+    /// make everyone on a level that just got blocked leave it, in every room
+    pub async fn remove_from_blocked_level(&self, level_id: LevelId) {
+        let threads: Vec<_> = self
+            .clients
+            .lock()
+            .values()
+            .filter(|thread| thread.level_id.load(Ordering::Relaxed) == level_id)
+            .cloned()
+            .collect();
+
+        for thread in threads {
+            thread.push_new_message(ServerThreadMessage::LevelBlocked(level_id)).await;
+        }
+    }
+
+    /// iterate over every player in this list and run F
+    #[inline]
+    pub fn for_each_player<F, A>(&self, ids: &[i32], f: F, additional: &mut A) -> usize
+    where
+        F: Fn(&PlayerAccountData, usize, &mut A) -> bool,
+    {
+        self.clients
+            .lock()
             .values()
             .filter(|thread| ids.contains(&thread.account_id.load(Ordering::Relaxed)))
             .map(|thread| thread.account_data.lock().clone())
@@ -486,20 +1214,53 @@ impl GameServer {
     where
         F: Fn(&PlayerRoomPreviewAccountData, usize, &mut A) -> bool,
     {
+        // players who hide which level they are on (by default everyone in the editor) are shown as not being on any level
+        let (hidden, spectator_counts) = self.state.room_manager.try_with_any(
+            room_id,
+            |room| (room.manager.get_hidden_players(), room.get_spectator_counts()),
+            || (Vec::new(), Vec::new()),
+        );
+
+        let visible_level = |account_id: i32, level_id: LevelId| if hidden.contains(&account_id) { 0 } else { level_id };
+
+        // players can hide how many spectators they have in their preferences
+        let make_preview = |thread: &ClientThread| {
+            let account_id = thread.account_id.load(Ordering::Relaxed);
+            let level_id = visible_level(account_id, thread.level_id.load(Ordering::Relaxed));
+            let is_afk = thread.is_afk.load(Ordering::Relaxed);
+            let mut preview = thread.account_data.lock().make_room_preview(level_id, is_afk);
+
+            if !thread.user_entry.lock().preferences.hide_spectator_count {
+                preview.spectators = spectator_counts
+                    .iter()
+                    .find(|(target, _)| *target == account_id)
+                    .map_or(0, |(_, count)| *count as u16);
+            }
+
+            preview
+        };
+
         self.clients
             .lock()
             .values()
             .filter(|thr| thr.authenticated() && thr.room_id.load(Ordering::Relaxed) == room_id && (!thr.is_invisible.load(Ordering::Relaxed) || can_moderate))
-            .map(|thread| {
-                let mut level_id = thread.level_id.load(Ordering::Relaxed);
-
-                // if they are in editorcollab, show no level
-                if is_editorcollab_level(level_id) {
-                    level_id = 0;
-                }
-
-                thread.account_data.lock().make_room_preview(level_id)
-            })
+            .map(|thread| make_preview(thread))
+            .chain(
+                self.recovering_threads()
+                    .into_iter()
+                    .filter(|thr| thr.room_id.load(Ordering::Relaxed) == room_id && (!thr.is_invisible.load(Ordering::Relaxed) || can_moderate))
+                    .map(|thread| {
+                        let level_id = visible_level(thread.account_id.load(Ordering::Relaxed), thread.level_id.load(Ordering::Relaxed));
+                        thread.account_data.lock().make_room_preview(level_id, false)
+                    }),
+            )
+            .chain(
+                self.state
+                    .bots
+                    .in_room(room_id)
+                    .into_iter()
+                    .map(|bot| bot.make_account_data().make_room_preview(bot.level_id, false)),
+            )
             .fold(0, |count, preview| count + usize::from(f(&preview, count, additional)))
     }
 
@@ -569,74 +1330,432 @@ impl GameServer {
         })
     }
 
+    /// Returns the amount of logged in players on each client platform, in the order of `ClientPlatform::ALL`.
+    pub fn count_players_per_platform(&self) -> [usize; ClientPlatform::COUNT] {
+        let mut counts = [0; ClientPlatform::COUNT];
+
+        for thread in self.clients.lock().values().filter(|thr| thr.authenticated()) {
+            counts[*thread.platform.lock() as usize] += 1;
+        }
+
+        counts
+    }
+
     #[inline]
     pub fn get_player_account_data(&self, account_id: i32) -> Option<PlayerAccountData> {
-        self.clients
+        if is_bot_account(account_id) {
+            return self.state.bots.get(account_id).map(|bot| bot.make_account_data());
+        }
+
+        let data = self
+            .clients
             .lock()
             .values()
             .find(|thr| thr.account_id.load(Ordering::Relaxed) == account_id)
-            .map(|thr| thr.account_data.lock().clone())
+            .map(|thr| thr.account_data.lock().clone());
+
+        // players that are reconnecting stay visible, so others don't see them leave and join again
+        data.or_else(|| {
+            self.recovering_threads()
+                .into_iter()
+                .find(|thr| thr.account_id.load(Ordering::Relaxed) == account_id)
+                .map(|thr| thr.account_data.lock().clone())
+        })
+    }
+
+    /// Returns the name of a player who is online or left a moment ago, or an empty string if we don't know them.
+    pub fn get_known_player_name(&self, account_id: i32) -> InlineString<MAX_NAME_SIZE> {
+        self.get_player_account_data(account_id)
+            .or_else(|| self.state.recent_players.get(account_id).map(|player| player.account_data))
+            .map(|data| data.name)
+            .unwrap_or_default()
     }
 
     #[inline]
     pub fn get_player_preview_data(&self, account_id: i32) -> Option<PlayerPreviewAccountData> {
-        self.clients
+        if is_bot_account(account_id) {
+            return self.state.bots.get(account_id).map(|bot| bot.make_account_data().make_preview());
+        }
+
+        let data = self
+            .clients
             .lock()
             .values()
             .find(|thr| thr.account_id.load(Ordering::Relaxed) == account_id)
-            .map(|thr| thr.account_data.lock().make_preview())
+            .map(|thr| thr.account_data.lock().make_preview());
+
+        data.or_else(|| {
+            self.recovering_threads()
+                .into_iter()
+                .find(|thr| thr.account_id.load(Ordering::Relaxed) == account_id)
+                .map(|thr| thr.account_data.lock().make_preview())
+        })
     }
 
-    /// If someone is already logged in under the given account ID, logs them out.
-    /// Additionally, blocks until the appropriate cleanup has been done.
-    pub async fn check_already_logged_in(&self, account_id: i32) -> anyhow::Result<()> {
-        let wait = async |notify: Arc<Notify>| -> anyhow::Result<()> {
-            // we want to wait until the player has been removed from any managers and such.
+    /// Logs and sends webhook alerts about bursts of failed authentication attempts.
+    pub async fn report_auth_failure_bursts(&self, bursts: Vec<AuthFailureBurst>) {
+        for burst in bursts {
+            let (subject, failures) = match burst {
+                AuthFailureBurst::Account(account_id, failures) => (format!("Account {account_id}"), failures),
+                AuthFailureBurst::Address(address, failures) => (format!("Address {address}"), failures),
+            };
+
+            warn!("{subject} failed to authenticate {failures} times, possible token guessing or credential stuffing");
+
+            if self.bridge.has_webhook() {
+                if let Err(err) = self
+                    .bridge
+                    .send_webhook_message(WebhookMessage::LoginFailureBurst(subject, failures))
+                    .await
+                {
+                    warn!("webhook error: {err}");
+                }
+            }
+        }
+    }
 
-            match tokio::time::timeout(Duration::from_secs(3), notify.notified()).await {
-                Ok(()) => Ok(()),
-                Err(_) => Err(anyhow!("timed out waiting for the thread to disconnect")),
+    /// Reports a player whose name matched one of the flagged substrings of the name policy.
+    pub async fn report_flagged_name(&self, account_id: i32, name: String, matched: String) {
+        info!("player name {name} ({account_id}) is flagged by the name policy (matched \"{matched}\")");
+
+        if self.bridge.has_webhook() {
+            if let Err(err) = self
+                .bridge
+                .send_webhook_message(WebhookMessage::NameFlagged(name, account_id, matched))
+                .await
+            {
+                warn!("webhook error: {err}");
             }
-        };
+        }
+    }
 
-        while let Some(thread) = {
-            let clients = self.clients.lock();
-            clients.values().find(|thr| thr.account_id.load(Ordering::Relaxed) == account_id).cloned()
-        } {
+    /// Applies the duplicate login policy to a player logging in with the given roles, and notifies their other sessions.
+    /// If older sessions have to be logged out, blocks until the appropriate cleanup has been done, or until `DUPLICATE_LOGIN_TIMEOUT` passes.
+    pub async fn check_already_logged_in(&self, account_id: i32, roles: &[String]) -> DuplicateLoginOutcome {
+        let policy = self.bridge.central_conf.lock().duplicate_login_policy.clone();
+        let limit = policy.session_limit(roles);
+
+        let sessions = self
+            .clients
+            .lock()
+            .values()
+            .filter(|thr| thr.account_id.load(Ordering::Relaxed) == account_id)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // sessions that lost their connection and are waiting for it to be recovered
+        let disconnected = self
+            .unauthorized_clients
+            .lock()
+            .iter()
+            .filter(|thr| thr.account_id.load(Ordering::Relaxed) == account_id)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let existing = sessions.len() + disconnected.len();
+
+        if existing == 0 {
+            return DuplicateLoginOutcome::FirstSession;
+        }
+
+        if existing < limit {
+            for thread in &sessions {
+                thread
+                    .push_new_message(ServerThreadMessage::DuplicateLogin(DuplicateLoginNotice::Concurrent))
+                    .await;
+            }
+
+            return DuplicateLoginOutcome::Concurrent;
+        }
+
+        if policy.action == DuplicateLoginAction::RejectNew {
+            for thread in &sessions {
+                thread
+                    .push_new_message(ServerThreadMessage::DuplicateLogin(DuplicateLoginNotice::RejectedOther))
+                    .await;
+            }
+
+            return DuplicateLoginOutcome::Rejected;
+        }
+
+        // make room for the new session, sessions that are already disconnected go first.
+        // all of them are told to terminate before waiting, so that they clean up at the same time rather than one after another
+        let mut to_kick = existing + 1 - limit;
+        let mut destruction_notifies = Vec::with_capacity(to_kick);
+
+        for thread in disconnected.into_iter().take(to_kick) {
+            to_kick -= 1;
+            thread.request_termination();
+            destruction_notifies.push(thread.destruction_notify.clone());
+        }
+
+        for thread in sessions.into_iter().take(to_kick) {
             thread
-                .push_new_message(ServerThreadMessage::TerminationNotice(FastString::new(
-                    "Someone logged into the same account from a different place.",
-                )))
+                .push_new_message(ServerThreadMessage::DuplicateLogin(DuplicateLoginNotice::Replaced))
                 .await;
+            thread
+                .push_new_message(ServerThreadMessage::TerminationNotice(
+                    DisconnectReason::DuplicateLogin,
+                    FastString::new(""),
+                ))
+                .await;
+
+            destruction_notifies.push(thread.destruction_notify.clone());
+        }
 
-            let destruction_notify = thread.destruction_notify.clone();
-            drop(thread);
+        // we want to wait until the old sessions have been removed from any managers and such.
+        let cleanup = join_all(destruction_notifies.iter().map(|notify| notify.notified()));
 
-            wait(destruction_notify).await?;
+        if tokio::time::timeout(DUPLICATE_LOGIN_TIMEOUT, cleanup).await.is_err() {
+            warn!("timed out waiting for old sessions of {account_id} to disconnect");
+            return DuplicateLoginOutcome::TimedOut;
         }
 
-        while let Some(thread) = {
-            let clients = self.unauthorized_clients.lock();
-            clients.iter().find(|thr| thr.account_id.load(Ordering::Relaxed) == account_id).cloned()
-        } {
-            thread.request_termination();
+        DuplicateLoginOutcome::ReplacedOld
+    }
 
-            let destruction_notify = thread.destruction_notify.clone();
-            drop(thread);
+    /// Remembers the new session of a player, and reports it to the central server in the background.
+    pub fn start_session(&'static self, session: AccountSession) {
+        self.state.sessions.add(session.clone());
 
-            wait(destruction_notify).await?;
+        if self.standalone {
+            return;
         }
 
-        Ok(())
+        tokio::spawn(async move {
+            if let Err(e) = self.bridge.start_session(&session).await {
+                warn!("failed to report a session of {} to the central server: {e}", session.account_id);
+            }
+        });
+    }
+
+    fn end_session(&'static self, account_id: i32, session_id: u64) {
+        if !self.state.sessions.remove(account_id, session_id) || self.standalone {
+            return;
+        }
+
+        tokio::spawn(async move {
+            if let Err(e) = self.bridge.end_session(account_id, session_id).await {
+                warn!("failed to report the end of a session of {account_id} to the central server: {e}");
+            }
+        });
+    }
+
+    /// Returns the active and recent sessions of the account on every server. Standalone servers, and servers that can't reach
+    /// the central server, only know about the active sessions on this server.
+    pub async fn get_sessions(&self, account_id: i32) -> Vec<AccountSession> {
+        if !self.standalone {
+            match self.bridge.get_sessions(account_id).await {
+                Ok(sessions) => return sessions,
+                Err(e) => warn!("failed to get the sessions of {account_id} from the central server: {e}"),
+            }
+        }
+
+        self.state.sessions.get(account_id)
+    }
+
+    /// Logs out the session of the account, if it's on this server. Returns `false` if it wasn't found.
+    pub async fn logout_session(&self, account_id: i32, session_id: u64) -> bool {
+        let is_session = |thr_account_id: i32, thr_session_id: u64| thr_account_id == account_id && thr_session_id == session_id;
+
+        let thread = self
+            .clients
+            .lock()
+            .values()
+            .find(|thr| is_session(thr.account_id.load(Ordering::Relaxed), thr.session_id))
+            .cloned();
+
+        if let Some(thread) = thread {
+            thread
+                .push_new_message(ServerThreadMessage::TerminationNotice(
+                    DisconnectReason::DuplicateLogin,
+                    FastString::new("You were logged out from another device."),
+                ))
+                .await;
+
+            return true;
+        }
+
+        // sessions that lost their connection can't be told anything, they just don't get to recover it
+        let thread = self
+            .unauthorized_clients
+            .lock()
+            .iter()
+            .find(|thr| is_session(thr.account_id.load(Ordering::Relaxed), thr.session_id))
+            .cloned();
+
+        if let Some(thread) = thread {
+            thread.request_termination();
+            return true;
+        }
+
+        false
     }
 
     /// Find a thread by account ID
     pub fn get_user_by_id(&self, account_id: i32) -> Option<Arc<ClientThread>> {
-        self.clients
+        self.accounts.lock().get(&account_id).and_then(|threads| threads.first()).cloned()
+    }
+
+    /// Get the threads of all the given accounts, including every session of accounts that are logged in from multiple places.
+    pub fn get_users_by_ids(&self, account_ids: &[i32]) -> Vec<Arc<ClientThread>> {
+        let accounts = self.accounts.lock();
+        account_ids.iter().filter_map(|id| accounts.get(id)).flatten().cloned().collect()
+    }
+
+    /// Get the presence of every given account, as seen by the player `viewer_id` in the room `viewer_room_id`.
+    /// Invisible players are shown as offline.
+    pub fn get_presences(&self, accounts: &[i32], viewer_id: i32, viewer_room_id: u32) -> Vec<PlayerPresence> {
+        let wanted = accounts.iter().copied().collect::<IntSet<i32>>();
+
+        let threads = self
+            .clients
             .lock()
             .values()
-            .find(|thr| thr.account_id.load(Ordering::Relaxed) == account_id)
+            .filter(|thr| wanted.contains(&thr.account_id.load(Ordering::Relaxed)) && !thr.is_invisible.load(Ordering::Relaxed))
             .cloned()
+            .collect::<Vec<_>>();
+
+        accounts
+            .iter()
+            .map(|&account_id| {
+                let Some(thread) = threads.iter().find(|thr| thr.account_id.load(Ordering::Relaxed) == account_id) else {
+                    return PlayerPresence {
+                        account_id,
+                        ..Default::default()
+                    };
+                };
+
+                let room_id = thread.room_id.load(Ordering::Relaxed);
+                let (room_hidden, level_hidden) = self.state.room_manager.try_with_any(
+                    room_id,
+                    |room| {
+                        let level_hidden = room.manager.get_player_data(account_id).is_some_and(|player| player.level_hidden);
+                        (room.is_hidden(), level_hidden)
+                    },
+                    || (false, false),
+                );
+
+                // the level is only shown to players in the same room, same as in the room player list,
+                // and to mutual friends unless the room is hidden
+                let level_visible =
+                    !level_hidden && (room_id == viewer_room_id || (!room_hidden && self.state.friends.are_mutual(viewer_id, account_id)));
+
+                PlayerPresence {
+                    account_id,
+                    online: true,
+                    room_id: if room_hidden { 0 } else { room_id },
+                    level_id: if level_visible { thread.level_id.load(Ordering::Relaxed) } else { 0 },
+                }
+            })
+            .collect()
+    }
+
+    /// Tells everyone who subscribed to the presence of the player in the event, or has them on their friend list, about the change.
+    async fn notify_presence_subscribers(&self, event: &ServerEvent) {
+        let account_id = match event {
+            ServerEvent::PlayerLeft { account_id } | ServerEvent::PresenceChanged { account_id, .. } => *account_id,
+            _ => return,
+        };
+
+        // most players aren't watched by anyone, so this is usually where we stop.
+        // players who subscribed to someone that is also on their friend list would be told twice otherwise
+        let mut subscribers = self.state.presence.subscribers_of(account_id);
+        subscribers.extend(self.state.friends.listed_by(account_id));
+        subscribers.sort_unstable();
+        subscribers.dedup();
+
+        if subscribers.is_empty() {
+            return;
+        }
+
+        for thread in self.get_users_by_ids(&subscribers) {
+            thread.push_new_message(ServerThreadMessage::PresenceUpdate(account_id)).await;
+        }
+    }
+
+    /// Queues the events that room owners can get on their room webhooks.
+    fn queue_room_webhook_events(&self, event: &ServerEvent, player_rooms: &mut IntMap<i32, u32>) {
+        let rooms = &self.state.room_manager;
+
+        match event {
+            ServerEvent::PresenceChanged { account_id, room_id, .. } => {
+                let previous = player_rooms.insert(*account_id, *room_id).unwrap_or(0);
+
+                // moving between levels of the same room
+                if previous == *room_id {
+                    return;
+                }
+
+                let left = previous != 0 && rooms.has_webhook(previous);
+                let joined = *room_id != 0 && rooms.has_webhook(*room_id);
+
+                if !left && !joined {
+                    return;
+                }
+
+                let name = self.get_known_player_name(*account_id).try_to_string();
+
+                if left {
+                    rooms.queue_webhook_event(previous, |room| WebhookMessage::RoomPlayerLeft(room, name.clone()));
+                }
+
+                if joined {
+                    rooms.queue_webhook_event(*room_id, |room| WebhookMessage::RoomPlayerJoined(room, name));
+                }
+            }
+
+            ServerEvent::PlayerLeft { account_id } => {
+                let Some(room_id) = player_rooms.remove(account_id) else {
+                    return;
+                };
+
+                if room_id != 0 && rooms.has_webhook(room_id) {
+                    let name = self.get_known_player_name(*account_id).try_to_string();
+                    rooms.queue_webhook_event(room_id, |room| WebhookMessage::RoomPlayerLeft(room, name));
+                }
+            }
+
+            ServerEvent::RoomCountdownStarted {
+                account_id,
+                room_id,
+                level_id,
+                seconds,
+            } if rooms.has_webhook(*room_id) => {
+                let name = self.get_known_player_name(*account_id).try_to_string();
+                rooms.queue_webhook_event(*room_id, |room| WebhookMessage::RoomCountdownStarted(room, name, *level_id, *seconds));
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Sends the next batch of queued events to every room webhook. Webhooks that were deleted on Discord's side are removed.
+    async fn send_room_webhooks(&self) {
+        // same as the admin webhook, nothing is sent in safe mode
+        if safe_mode::is_active() {
+            return;
+        }
+
+        let batches = self.state.room_manager.take_webhook_batches();
+
+        let results = join_all(
+            batches
+                .iter()
+                .map(|(_, url, messages)| self.bridge.send_webhook_messages_to(url, messages)),
+        )
+        .await;
+
+        for ((room_id, _, _), result) in batches.iter().zip(results) {
+            match result {
+                Ok(()) => {}
+                Err(bridge::CentralBridgeError::WebhookError((status, _))) if status == StatusCode::NOT_FOUND => {
+                    info!("the webhook of room {room_id} no longer exists, removing it");
+                    self.state.room_manager.set_webhook(*room_id, None);
+                }
+                Err(err) => warn!("room webhook error (room {room_id}): {err}"),
+            }
+        }
     }
 
     /// If the passed string is numeric, tries to find a user by account ID, else by their account name.
@@ -674,39 +1793,211 @@ impl GameServer {
 
         if result {
             let user_entry = thread.user_entry.lock().clone();
-            self.bridge.update_user_data(&user_entry).await?;
+            self.save_user_data(&user_entry).await?;
         }
 
         Ok(())
     }
 
+    /// Whether user data can be fetched and saved, which is always possible unless the server is standalone without a local database.
+    pub fn has_user_data(&self) -> bool {
+        !self.standalone || self.state.store.is_some()
+    }
+
+    /// Fetches the user by name or account ID, from the central server or from the local database on standalone servers.
+    /// The database is only ever opened on standalone servers.
+    pub async fn fetch_user_data(&self, player: &str) -> anyhow::Result<UserEntry> {
+        let Some(store) = &self.state.store else {
+            return Ok(self.bridge.get_user_data(player).await?);
+        };
+
+        let user = if let Ok(account_id) = player.parse::<i32>() {
+            store.get_user(account_id).await?
+        } else {
+            store.get_user_by_name(player).await?
+        };
+
+        user.ok_or_else(|| anyhow!("failed to find the user"))
+    }
+
+    /// Saves the user to the central server, or to the local database on standalone servers.
+    pub async fn save_user_data(&self, user_entry: &UserEntry) -> anyhow::Result<()> {
+        if let Some(store) = &self.state.store {
+            store.update_user(user_entry).await?;
+        } else {
+            self.bridge.update_user_data(user_entry).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Saves a new report to the central server, or to the local database on standalone servers. Returns the ID of the report.
+    pub async fn create_report(&self, report: &PlayerReport) -> anyhow::Result<i64> {
+        if let Some(store) = &self.state.store {
+            Ok(store.create_report(report.reporter_id, report.reported_id, &report.reason).await?)
+        } else if self.standalone {
+            bail!("reports cannot be sent on a standalone server without a database");
+        } else {
+            Ok(self.bridge.create_report(report).await?)
+        }
+    }
+
+    /// How much voice data of every player is kept for reports and who it's sealed for, `None` if capturing is disabled.
+    pub fn voice_capture_config(&self) -> Option<(Duration, PublicKey)> {
+        let conf = self.bridge.central_conf.lock();
+
+        let key = conf.voice_capture_key.filter(|_| conf.voice_capture_seconds != 0)?;
+        let window = Duration::from_secs(u64::from(conf.voice_capture_seconds.min(MAX_VOICE_CAPTURE_SECONDS)));
+
+        Some((window, PublicKey::from(key)))
+    }
+
+    /// Attaches a sealed voice capture to a report, the same way the report itself was saved.
+    pub async fn set_report_voice_capture(&self, report_id: i64, capture: Vec<u8>) -> anyhow::Result<()> {
+        if let Some(store) = &self.state.store {
+            if !store.set_report_voice_capture(report_id, &capture).await? {
+                bail!("report #{report_id} does not exist");
+            }
+
+            Ok(())
+        } else if self.standalone {
+            bail!("reports cannot be sent on a standalone server without a database");
+        } else {
+            Ok(self.bridge.set_report_voice_capture(report_id, capture).await?)
+        }
+    }
+
+    /// Fetches the reports that are open or claimed, newest first.
+    pub async fn fetch_pending_reports(&self) -> anyhow::Result<Vec<PlayerReport>> {
+        if let Some(store) = &self.state.store {
+            Ok(store.get_pending_reports(MAX_PENDING_REPORTS).await?)
+        } else if self.standalone {
+            bail!("reports cannot be used on a standalone server without a database");
+        } else {
+            Ok(self.bridge.get_pending_reports().await?)
+        }
+    }
+
+    /// Changes the state of a report, returns the report as it is afterwards, which is unchanged if the moderator wasn't allowed to change it.
+    pub async fn update_report_state(&self, change: &ReportStateChange) -> anyhow::Result<PlayerReport> {
+        if let Some(store) = &self.state.store {
+            store
+                .update_report_state(change)
+                .await?
+                .ok_or_else(|| anyhow!("this report does not exist"))
+        } else if self.standalone {
+            bail!("reports cannot be used on a standalone server without a database");
+        } else {
+            Ok(self.bridge.update_report_state(change).await?)
+        }
+    }
+
+    /// Moves the given players to the game server at `address`. Players that are in the same room will end up in one room on the new server,
+    /// with the same settings. Returns the amount of players that were told to move.
+    pub async fn transfer_players(&self, threads: &[Arc<ClientThread>], address: &str) -> anyhow::Result<usize> {
+        if self.standalone {
+            bail!("transferring players is not possible on a standalone server");
+        }
+
+        // every room gets its own random group, so that rooms with the same ID on different servers can't get mixed up
+        let mut groups = FxHashMap::<u32, u64>::default();
+        let mut transferred = 0;
+
+        for thread in threads {
+            let account_id = thread.account_id.load(Ordering::Relaxed);
+            let room_id = thread.room_id.load(Ordering::Relaxed);
+
+            let room = if room_id == 0 {
+                None
+            } else {
+                let group = *groups.entry(room_id).or_insert_with(rand::random);
+                self.state.room_manager.get_transfer_data(room_id, group)
+            };
+
+            let mut payload = ByteBuffer::new();
+            payload.write_value(&room);
+
+            let ticket = TransferTicket {
+                account_id,
+                payload: payload.into_vec(),
+            };
+
+            let token = match self.bridge.create_transfer_token(&ticket).await {
+                Ok(x) => x,
+                Err(err) => {
+                    warn!("failed to create a transfer token for {account_id}: {err}");
+                    continue;
+                }
+            };
+
+            thread
+                .push_new_message(ServerThreadMessage::Transfer(ServerTransferPacket {
+                    address: FastString::new(address),
+                    token: FastString::new(&token),
+                }))
+                .await;
+
+            transferred += 1;
+        }
+
+        Ok(transferred)
+    }
+
     /* private handling stuff */
 
-    /// broadcast a message to all people on the level
+    /// broadcast a message to all people on the level, and to the spectators of the player it came from even if they are somewhere else
     async fn broadcast_user_message(&self, msg: &ServerThreadMessage, origin_id: i32, level_id: LevelId, room_id: u32) {
         let threads = self.state.room_manager.with_any(room_id, |pm| {
-            let players = pm.manager.get_level(level_id);
+            let players = pm.manager.get_level(level_id).map_or(&[][..], Vec::as_slice);
+            let spectators = pm.get_spectators(origin_id);
 
-            if let Some(players) = players {
-                self.clients
-                    .lock()
-                    .values()
-                    .filter(|thread| {
-                        let account_id = thread.account_id.load(Ordering::Relaxed);
-                        account_id != origin_id && players.contains(&account_id)
-                    })
-                    .cloned()
-                    .collect()
-            } else {
-                Vec::new()
+            if players.is_empty() && spectators.is_empty() {
+                return Vec::new();
             }
+
+            self.clients
+                .lock()
+                .values()
+                .filter(|thread| {
+                    let account_id = thread.account_id.load(Ordering::Relaxed);
+                    account_id != origin_id && (players.contains(&account_id) || spectators.contains(&account_id))
+                })
+                .cloned()
+                .collect()
         });
 
         for thread in threads {
+            if chaos::should_drop_broadcast() {
+                continue;
+            }
+
             thread.push_new_message(msg.clone()).await;
         }
     }
 
+    /// send a message only to the players spectating `origin_id`, returns how many of them there are
+    async fn broadcast_spectator_message(&self, msg: &ServerThreadMessage, origin_id: i32, room_id: u32) -> usize {
+        // this runs every frame for spectated players, so only copy the few IDs out of the room and resolve them outside of the lock
+        let spectators = self.state.room_manager.with_any(room_id, |pm| pm.get_spectators(origin_id).to_vec());
+        if spectators.is_empty() {
+            return 0;
+        }
+
+        let threads = self.get_users_by_ids(&spectators);
+
+        let count = threads.len();
+
+        for thread in threads {
+            if chaos::should_drop_broadcast() {
+                continue;
+            }
+
+            thread.push_new_message(msg.clone()).await;
+        }
+
+        count
+    }
+
     /// broadcast a message to all people in a room
     pub async fn broadcast_room_message(&self, msg: &ServerThreadMessage, origin_id: i32, room_id: u32) {
         let threads: Vec<_> = self
@@ -721,31 +2012,96 @@ impl GameServer {
             .collect();
 
         for thread in threads {
+            if chaos::should_drop_broadcast() {
+                continue;
+            }
+
             thread.push_new_message(msg.clone()).await;
         }
     }
 
+    /// Tells the players that can see the player about their new icons: everyone on the same level,
+    /// or everyone in the room if it's not the global one. The room info is sent again if they own the room, as it includes their icons.
+    pub async fn broadcast_profile_change(&self, packet: PlayerProfileChangedPacket, level_id: LevelId, room_id: u32) {
+        let account_id = packet.account_id;
+        let msg = ServerThreadMessage::BroadcastProfileChange(packet);
+
+        if room_id == 0 {
+            self.broadcast_user_message(&msg, account_id, level_id, room_id).await;
+            return;
+        }
+
+        self.broadcast_room_message(&msg, account_id, room_id).await;
+
+        let is_owner = self.state.room_manager.try_with_any(room_id, |room| room.owner == account_id, || false);
+        if is_owner {
+            self.broadcast_room_info(room_id).await;
+        }
+    }
+
+    /// Tells every logged in player that the server is going down.
+    pub async fn broadcast_restart(&self, packet: ServerRestartingPacket) {
+        let threads: Vec<_> = self.clients.lock().values().filter(|thr| thr.authenticated()).cloned().collect();
+        let msg = ServerThreadMessage::Restarting(packet);
+
+        for thread in threads {
+            thread.push_new_message(msg.clone()).await;
+        }
+    }
+
+    /// Disconnects every client, returns how many there were.
+    pub async fn disconnect_all(&self, reason: DisconnectReason, message: &str) -> usize {
+        let threads: Vec<_> = self.clients.lock().values().cloned().collect();
+        let msg = ServerThreadMessage::TerminationNotice(reason, FastString::new(message));
+
+        for thread in &threads {
+            thread.push_new_message(msg.clone()).await;
+        }
+
+        threads.len()
+    }
+
     /// send `RoomInfoPacket` to all players in a room
     pub async fn broadcast_room_info(&self, room_id: u32) {
         if room_id == 0 {
             return;
         }
 
-        let info = self
-            .state
-            .room_manager
-            .try_with_any(room_id, |room| Some(room.get_room_info(room_id, self)), || None);
+        let info = self.state.room_manager.try_with_any(
+            room_id,
+            |room| Some((room.get_room_info(room_id, self), room.moderators.clone(), room.get_tags())),
+            || None,
+        );
 
-        if let Some(info) = info {
-            let pkt = RoomInfoPacket { info };
+        if let Some((info, moderators, tags)) = info {
+            let pkt = RoomInfoPacket { info, moderators, tags };
 
             self.broadcast_room_message(&ServerThreadMessage::BroadcastRoomInfo(pkt), 0, room_id)
                 .await;
         }
     }
 
+    fn make_server_query_info(&self) -> ServerQueryInfo {
+        let (tps, maintenance, whitelist) = {
+            let conf = self.bridge.central_conf.lock();
+            (conf.tps, conf.maintenance, conf.whitelist)
+        };
+
+        ServerQueryInfo {
+            protocol: PROTOCOL_VERSION,
+            version: InlineString::new(env!("CARGO_PKG_VERSION")),
+            player_count: self.state.get_player_count(),
+            room_count: self.state.room_manager.get_rooms().values().filter(|room| !room.is_hidden()).count() as u32,
+            tps,
+            maintenance,
+            whitelist,
+        }
+    }
+
     /// Try to handle a packet that is not addressed to a specific thread, but to the game server.
-    async fn try_udp_handle(&self, data: &[u8], peer: SocketAddrV4) -> anyhow::Result<bool> {
+    /// `raw_peer` is the address as the socket reported it, see `recv_and_handle_udp`.
+    async fn try_udp_handle(&self, data: &[u8], raw_peer: SocketAddr) -> anyhow::Result<bool> {
+        let peer = canonical_addr(raw_peer);
         let mut byte_reader = ByteReader::from_bytes(data);
         let header = byte_reader.read_packet_header().map_err(|e| anyhow!("{e}"))?;
 
@@ -755,6 +2111,7 @@ impl GameServer {
                 let response = PingResponsePacket {
                     id: pkt.id,
                     player_count: self.state.get_player_count(),
+                    distance_class: self.state.get_distance_class(peer.ip()),
                 };
 
                 let mut buf_array = [0u8; PacketHeader::SIZE + PingResponsePacket::ENCODED_SIZE];
@@ -764,14 +2121,53 @@ impl GameServer {
 
                 let send_bytes = buf.as_bytes();
 
-                self.udp_socket.send_to(send_bytes, peer).await?;
+                self.udp_socket.send_to(send_bytes, raw_peer).await?;
+
+                Ok(true)
+            }
+
+            ServerQueryPacket::PACKET_ID => {
+                let pkt = ServerQueryPacket::decode_from_reader(&mut byte_reader).map_err(|e| anyhow!("{e}"))?;
+                let address = peer.ip();
+
+                if !self.state.server_query.try_query(address) {
+                    return Ok(true);
+                }
+
+                if self.state.server_query.verify_token(address, pkt.token) {
+                    let response = ServerQueryResponsePacket {
+                        id: pkt.id,
+                        info: self.make_server_query_info(),
+                    };
+
+                    let mut buf_array = [0u8; PacketHeader::SIZE + ServerQueryResponsePacket::ENCODED_SIZE];
+                    let mut buf = FastByteBuffer::new(&mut buf_array);
+                    buf.write_packet_header::<ServerQueryResponsePacket>();
+                    buf.write_value(&response);
+
+                    self.udp_socket.send_to(buf.as_bytes(), raw_peer).await?;
+                } else {
+                    // the address might be spoofed, so without a token we only send something as big as the query
+                    let response = ServerQueryTokenPacket {
+                        id: pkt.id,
+                        token: self.state.server_query.make_token(address),
+                    };
+
+                    let mut buf_array = [0u8; PacketHeader::SIZE + ServerQueryTokenPacket::ENCODED_SIZE];
+                    let mut buf = FastByteBuffer::new(&mut buf_array);
+                    buf.write_packet_header::<ServerQueryTokenPacket>();
+                    buf.write_value(&response);
+
+                    self.udp_socket.send_to(buf.as_bytes(), raw_peer).await?;
+                }
 
                 Ok(true)
             }
 
             ClaimThreadPacket::PACKET_ID => {
                 let pkt = ClaimThreadPacket::decode_from_reader(&mut byte_reader).map_err(|e| anyhow!("{e}"))?;
-                if !self.claim_thread(peer, pkt.secret_key) {
+                // the thread sends to the address it's claimed with, so it gets the raw one
+                if !self.claim_thread(raw_peer, pkt.secret_key) {
                     warn!("udp peer {peer} tried to claim an invalid thread (with key {})", pkt.secret_key);
 
                     // send a ClaimThreadFailedPacket
@@ -781,7 +2177,7 @@ impl GameServer {
 
                     let send_bytes = buf.as_bytes();
 
-                    self.udp_socket.send_to(send_bytes, peer).await?;
+                    self.udp_socket.send_to(send_bytes, raw_peer).await?;
                 }
 
                 Ok(true)
@@ -791,35 +2187,73 @@ impl GameServer {
         }
     }
 
-    async fn post_disconnect_cleanup(&self, thread: EitherClientThread) {
-        let (account_id, level_id, room_id) = match thread {
-            EitherClientThread::Authorized(thread) => {
-                thread.destruction_notify.notify_one();
-
-                (
-                    thread.account_id.load(Ordering::Relaxed),
-                    thread.level_id.load(Ordering::Relaxed),
-                    thread.room_id.load(Ordering::Relaxed),
-                )
-            }
-            EitherClientThread::Unauthorized(thread) => {
-                thread.destruction_notify.notify_one();
-
-                (
-                    thread.account_id.load(Ordering::Relaxed),
-                    thread.level_id.load(Ordering::Relaxed),
-                    thread.room_id.load(Ordering::Relaxed),
-                )
-            }
+    async fn post_disconnect_cleanup(&'static self, thread: EitherClientThread) {
+        let (account_id, session_id, level_id, room_id, destruction_notify) = match &thread {
+            EitherClientThread::Authorized(thread) => (
+                thread.account_id.load(Ordering::Relaxed),
+                thread.session_id,
+                thread.level_id.load(Ordering::Relaxed),
+                thread.room_id.load(Ordering::Relaxed),
+                thread.destruction_notify.clone(),
+            ),
+            EitherClientThread::Unauthorized(thread) => (
+                thread.account_id.load(Ordering::Relaxed),
+                thread.session_id,
+                thread.level_id.load(Ordering::Relaxed),
+                thread.room_id.load(Ordering::Relaxed),
+                thread.destruction_notify.clone(),
+            ),
             EitherClientThread::None => unreachable!(),
         };
 
+        // this must be checked before notifying, as a new session of the same account may be waiting for us to finish
+        let other_session = account_id != 0 && self.has_other_session(account_id, &thread);
+
+        destruction_notify.notify_one();
+
         if account_id == 0 {
             return;
         }
 
         // decrement player count
         self.state.dec_player_count();
+        self.end_session(account_id, session_id);
+
+        // the account is still logged in elsewhere, so it has to stay in its room
+        if other_session {
+            return;
+        }
+
+        self.state.events.emit(|| ServerEvent::PlayerLeft { account_id });
+        self.state.presence.unsubscribe(account_id);
+        self.state.friends.remove(account_id);
+        self.state.chat_tails.stop(account_id);
+
+        // remember them for a while, so they can still be looked up and put back into their room if they come back soon
+        let (account_data, user_entry, mut level_history) = match &thread {
+            EitherClientThread::Authorized(thread) => (
+                thread.account_data.lock().clone(),
+                Some(thread.user_entry.lock().clone()),
+                thread.level_history.lock().clone(),
+            ),
+            EitherClientThread::Unauthorized(thread) => (
+                thread.account_data.lock().clone(),
+                thread.user_entry.lock().clone(),
+                thread.level_history.lock().clone(),
+            ),
+            EitherClientThread::None => unreachable!(),
+        };
+
+        level_history.record(0, unix_timestamp());
+
+        self.state.recent_players.insert(RecentPlayer {
+            account_data,
+            user_entry,
+            room_id,
+            level_id,
+            level_history,
+            left_at: Instant::now(),
+        });
 
         // remove from the player manager and the level if they are on one
         let was_owner = self.state.room_manager.remove_with_any(room_id, account_id, level_id);
@@ -830,6 +2264,158 @@ impl GameServer {
         }
     }
 
+    /// Returns whether the account has a session other than the given thread, connected or waiting for recovery.
+    fn has_other_session(&self, account_id: i32, thread: &EitherClientThread) -> bool {
+        let in_clients = self.clients.lock().values().any(|thr| {
+            thr.account_id.load(Ordering::Relaxed) == account_id && !matches!(thread, EitherClientThread::Authorized(x) if Arc::ptr_eq(x, thr))
+        });
+
+        in_clients
+            || self.unauthorized_clients.lock().iter().any(|thr| {
+                thr.account_id.load(Ordering::Relaxed) == account_id && !matches!(thread, EitherClientThread::Unauthorized(x) if Arc::ptr_eq(x, thr))
+            })
+    }
+
+    /// Returns the threads of clients that lost their connection and are waiting to recover it.
+    fn recovering_threads(&self) -> Vec<Arc<UnauthorizedThread>> {
+        self.unauthorized_clients
+            .lock()
+            .iter()
+            .filter(|thr| thr.is_awaiting_recovery())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the amount of threads from this address that haven't finished logging in yet.
+    fn pending_thread_count(&self, address: IpAddr) -> usize {
+        let key = address_key(address);
+
+        self.unauthorized_clients
+            .lock()
+            .iter()
+            .filter(|thr| address_key(thr.peer_ip) == key && thr.is_pending())
+            .count()
+    }
+
+    /// Temporarily blocks the address, called when one of its clients got disconnected for flooding.
+    pub fn block_flooder(&self, address: IpAddr) {
+        self.flood_disconnects.fetch_add(1, Ordering::Relaxed);
+        self.state.rate_limiter.punish(address);
+    }
+
+    /// Terminates every thread that has been logging in for longer than `HANDSHAKE_DEADLINE`.
+    fn sweep_pending_threads(&self) {
+        let stale: Vec<_> = self
+            .unauthorized_clients
+            .lock()
+            .iter()
+            .filter(|thr| thr.is_pending() && thr.pending_since.lock().elapsed() > HANDSHAKE_DEADLINE)
+            .cloned()
+            .collect();
+
+        if stale.is_empty() {
+            return;
+        }
+
+        for thread in &stale {
+            thread.request_termination();
+        }
+
+        self.stale_evictions.fetch_add(stale.len() as u64, Ordering::Relaxed);
+        debug!("evicted {} threads that did not log in on time", stale.len());
+    }
+
+    fn collect_traffic_stats(&self) {
+        let threads = self
+            .clients
+            .lock()
+            .values()
+            .filter(|thr| thr.authenticated())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for thread in threads {
+            let (bytes, packets) = thread.traffic.take();
+            let room_id = thread.room_id.load(Ordering::Relaxed);
+            let level_id = thread.level_id.load(Ordering::Relaxed);
+
+            self.state.traffic.record(room_id, level_id, bytes, packets);
+        }
+
+        self.state.traffic.retain_rooms(|room_id| self.state.room_manager.is_valid_room(room_id));
+        self.state.traffic.forget_idle_levels(Instant::now());
+    }
+
+    fn update_bots(&self) {
+        self.state.bots.retain_rooms(|room_id| self.state.room_manager.is_valid_room(room_id));
+
+        for (account_id, room_id, data) in self.state.bots.make_player_data() {
+            // don't recreate bots that were removed in the meantime
+            self.state.room_manager.try_with_any(
+                room_id,
+                |room| {
+                    if let Some(player) = room.manager.players.get_mut(&account_id) {
+                        player.data = data;
+                    }
+                },
+                || {},
+            );
+        }
+    }
+
+    async fn expire_config_overrides(&self) {
+        for entry in self.bridge.expire_config_overrides(unix_timestamp()) {
+            info!("override of {} by {} expired, back to {}", entry.key.name(), entry.author, entry.original);
+
+            if self.bridge.has_webhook() {
+                if let Err(err) = self
+                    .bridge
+                    .send_webhook_message(WebhookMessage::ConfigOverrideRemoved(None, entry.key, entry.original))
+                    .await
+                {
+                    warn!("webhook error: {err}");
+                }
+            }
+        }
+    }
+
+    async fn send_scheduled_notices(&self) {
+        for notice in self.state.notice_scheduler.take_due(unix_timestamp()) {
+            info!("sending scheduled notice #{} by {}: {}", notice.id, notice.author, notice.message);
+
+            let msg = ServerThreadMessage::BroadcastNotice(ServerNoticePacket {
+                message: FastString::new(&notice.message),
+            });
+
+            if notice.everyone {
+                self.state.cluster.publish(ClusterMessage::Announcement {
+                    message: notice.message.clone(),
+                });
+
+                let threads = self
+                    .clients
+                    .lock()
+                    .values()
+                    .filter(|thr| thr.authenticated())
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                for thread in threads {
+                    thread.push_new_message(msg.clone()).await;
+                }
+            } else if notice.level_id == 0 {
+                self.broadcast_room_message(&msg, 0, notice.room_id).await;
+            } else {
+                self.broadcast_user_message(&msg, 0, notice.level_id, notice.room_id).await;
+            }
+        }
+    }
+
+    fn record_player_counts(&self) {
+        let (rooms, levels) = self.state.room_manager.count_players();
+        self.state.player_history.record(self.state.get_player_count(), rooms, levels);
+    }
+
     fn print_server_status(&self) {
         info!("Current server stats");
         info!(
@@ -838,29 +2424,134 @@ impl GameServer {
             self.clients.lock().len(),
             self.unclaimed_threads.lock().len(),
         );
+        info!(
+            "Stale connections evicted: {}, connections refused: {}, banned connections refused: {}, rate limited connections refused: {}, packets rejected for missing permissions: {}, flood disconnects: {}, addresses blocked: {}",
+            self.stale_evictions.load(Ordering::Relaxed),
+            self.pending_rejections.load(Ordering::Relaxed),
+            self.banned_rejections.load(Ordering::Relaxed),
+            self.rate_limit_rejections.load(Ordering::Relaxed),
+            self.permission_violations.load(Ordering::Relaxed),
+            self.flood_disconnects.load(Ordering::Relaxed),
+            self.state.rate_limiter.block_count()
+        );
+
+        if self.message_drops.total() != 0 {
+            info!(
+                "Messages dropped from full queues: {}",
+                self.message_drops
+                    .get()
+                    .into_iter()
+                    .map(|(class, count)| format!("{class}: {count}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
         info!("Amount of rooms: {}", self.state.room_manager.get_rooms().len());
         info!(
             "People in the global room: {}",
             self.state.room_manager.get_global().manager.get_total_player_count()
         );
+
+        let per_platform = self.count_players_per_platform();
+        info!(
+            "Players per platform: {}",
+            ClientPlatform::ALL
+                .iter()
+                .zip(per_platform)
+                .map(|(platform, count)| format!("{}: {count}", platform.name()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        let usage = self.state.usage_stats.get();
+        if usage.reports != 0 {
+            info!("Feature usage reports: {} (voice enabled: {})", usage.reports, usage.voice_enabled);
+        }
+
         info!("-------------------------------------------");
     }
 
     async fn refresh_bootdata(&self) -> bridge::Result<()> {
         self.bridge.refresh_boot_data().await?;
+        self.apply_bootdata().await;
+
+        Ok(())
+    }
+
+    /// Reads the configuration again and applies it, see `local_config`. Standalone servers read it from the `GLOBED_GS_CONFIG` file,
+    /// other servers fetch it from the central server. The word list is read again in both cases.
+    pub async fn reload_config(&self) -> anyhow::Result<()> {
+        let filter_words = local_config::load_filter_words()?;
+
+        if !self.standalone {
+            self.bridge.refresh_boot_data().await?;
+        } else if let Some(path) = local_config::config_path() {
+            let config = local_config::read_config(&path, &self.bridge.central_conf.lock())?;
+            self.bridge.set_local_config(config);
+            self.state.role_manager.refresh_from(&self.bridge.central_conf.lock());
+        }
+
+        self.state.filter.set_words(&filter_words);
+        self.apply_bootdata().await;
+
+        Ok(())
+    }
+
+    /// Applies the configuration in `bridge.central_conf` after it was changed.
+    async fn apply_bootdata(&self) {
+        {
+            let conf = self.bridge.central_conf.lock();
+            self.state.level_blocklist.refresh_from(&conf);
+            self.state.ip_bans.refresh_from(&conf);
+            self.state.chat_filter.refresh_from(&conf);
+        }
 
         // if we are now under maintenance, disconnect everyone who's still connected
         if self.bridge.is_maintenance() {
             let threads: Vec<_> = self.clients.lock().values().cloned().collect();
             for thread in threads {
                 thread
-                    .push_new_message(ServerThreadMessage::TerminationNotice(FastString::new(
-                        "The server is now under maintenance, please try connecting again later",
-                    )))
+                    .push_new_message(ServerThreadMessage::TerminationNotice(DisconnectReason::Maintenance, FastString::new("")))
                     .await;
             }
+        } else {
+            self.refresh_timed_roles().await;
         }
+    }
 
-        Ok(())
+    /// Replaces the role definitions with the ones sent by the central server, and sends them to everyone online.
+    async fn apply_role_definitions(&self, defs: RoleDefinitions) {
+        {
+            let mut conf = self.bridge.central_conf.lock();
+            conf.roles = defs.roles;
+            conf.roles_revision = defs.revision;
+            self.state.role_manager.refresh_from(&conf);
+        }
+
+        let all_roles = self.state.role_manager.get_all_roles();
+        let threads: Vec<_> = self.clients.lock().values().filter(|thr| thr.authenticated()).cloned().collect();
+
+        info!("Role definitions were changed, sending them to {} players", threads.len());
+
+        let now = unix_timestamp();
+        for thread in threads {
+            thread.apply_role_definitions(now, all_roles.clone()).await;
+        }
+    }
+
+    /// Recomputes the roles of everyone online who has timed roles, in case some of them started or expired.
+    async fn refresh_timed_roles(&self) {
+        let threads: Vec<_> = self
+            .clients
+            .lock()
+            .values()
+            .filter(|thr| thr.authenticated() && !thr.user_entry.lock().timed_roles.is_empty())
+            .cloned()
+            .collect();
+
+        let now = unix_timestamp();
+        for thread in threads {
+            thread.refresh_roles(now).await;
+        }
     }
 }
@@ -0,0 +1,155 @@
+//! Graceful shutdown. When the server is told to stop, players are first told that it is going down and how long they have left,
+//! along with another server they can move to, so that the client can offer them to reconnect instead of just showing an error.
+//! Then the rooms are saved to the snapshot and handed off to the standby if there is one, everyone is disconnected, and the central server is told that their sessions ended.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use globed_shared::{info, log::Log, warn, Logger};
+
+use crate::{
+    data::*,
+    replication, safe_mode,
+    server::GameServer,
+    snapshot,
+    tokio::{runtime::Handle, time::sleep},
+};
+
+/// Default for `GLOBED_GS_RESTART_NOTICE`.
+const DEFAULT_RESTART_NOTICE: u64 = 10;
+/// Longest allowed notice, players won't wait around for longer than this anyway.
+const MAX_RESTART_NOTICE: u64 = 300;
+/// How long to wait for the clients to disconnect before exiting anyway.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+static SHUTDOWN_TARGET: OnceLock<(&'static GameServer, Handle)> = OnceLock::new();
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+/// set once the players are being disconnected
+static DRAINING: AtomicBool = AtomicBool::new(false);
+
+/// Returns how long players are given between the notice and the shutdown, based on `GLOBED_GS_RESTART_NOTICE`
+/// (by default `DEFAULT_RESTART_NOTICE` seconds). Zero if the server should shut down right away.
+fn notice_duration() -> Duration {
+    static DURATION: OnceLock<Duration> = OnceLock::new();
+
+    *DURATION.get_or_init(|| {
+        let secs = match std::env::var("GLOBED_GS_RESTART_NOTICE").ok().map(|x| x.trim().parse::<u64>()) {
+            None => DEFAULT_RESTART_NOTICE,
+            Some(Ok(x)) if x <= MAX_RESTART_NOTICE => x,
+            Some(_) => {
+                warn!(
+                    "invalid value for GLOBED_GS_RESTART_NOTICE, must be at most {MAX_RESTART_NOTICE}, using the default ({DEFAULT_RESTART_NOTICE})"
+                );
+                DEFAULT_RESTART_NOTICE
+            }
+        };
+
+        Duration::from_secs(secs)
+    })
+}
+
+/// Makes the interrupt handler notify and disconnect players before the server goes down.
+pub fn start(game_server: &'static GameServer) {
+    let _ = SHUTDOWN_TARGET.set((game_server, Handle::current()));
+}
+
+/// Tells every player that the server is going down and schedules the shutdown for when the countdown is over.
+/// Returns `false` if the server should shut down right away instead: the notice is disabled, nobody is online,
+/// the server didn't finish starting, or a countdown was already started before (the signal was sent twice).
+pub fn begin_countdown() -> bool {
+    if SHUTTING_DOWN.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+
+    let Some((game_server, handle)) = SHUTDOWN_TARGET.get() else {
+        return false;
+    };
+
+    let delay = notice_duration();
+    let player_count = game_server.state.get_player_count();
+
+    if delay.is_zero() || player_count == 0 {
+        return false;
+    }
+
+    let packet = ServerRestartingPacket {
+        seconds: delay.as_secs() as u32,
+        alternate_address: FastString::new(&std::env::var("GLOBED_GS_ALTERNATE_ADDRESS").unwrap_or_default()),
+    };
+
+    info!("Telling {player_count} players that the server is shutting down in {}s", packet.seconds);
+
+    // supervisors like Docker only wait 10 seconds before killing the process, which might be before the countdown is over
+    snapshot::save_now();
+
+    handle.spawn(async move {
+        game_server.broadcast_restart(packet).await;
+        sleep(delay).await;
+
+        // unless the signal was sent again in the meantime, which started it already
+        if !DRAINING.swap(true, Ordering::SeqCst) {
+            drain_and_exit(game_server).await;
+        }
+    });
+
+    true
+}
+
+/// Disconnects everyone and exits once they are gone, without a countdown. If that is already happening
+/// (the signal was sent again), exits right away instead.
+pub fn shut_down() {
+    let Some((game_server, handle)) = SHUTDOWN_TARGET.get() else {
+        terminate();
+    };
+
+    if DRAINING.swap(true, Ordering::SeqCst) {
+        terminate();
+    }
+
+    handle.spawn(drain_and_exit(game_server));
+}
+
+async fn drain_and_exit(game_server: &'static GameServer) {
+    // has to be saved before anyone is disconnected, as rooms are deleted once their last player leaves
+    snapshot::save_on_shutdown();
+    replication::hand_off().await;
+
+    let sessions = game_server.state.sessions.all();
+    let client_count = game_server
+        .disconnect_all(DisconnectReason::ServerShutdown, "The server is shutting down")
+        .await;
+
+    info!("Disconnecting {client_count} clients");
+
+    let deadline = Instant::now() + DRAIN_TIMEOUT;
+    while !game_server.clients.lock().is_empty() && Instant::now() < deadline {
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    let remaining = game_server.clients.lock().len();
+    if remaining != 0 {
+        warn!("{remaining} clients did not disconnect in time, shutting down anyway");
+    }
+
+    if !game_server.standalone {
+        match game_server.bridge.notify_shutdown(&sessions).await {
+            Ok(()) => info!("Notified the central server, ended {} sessions", sessions.len()),
+            Err(e) => warn!("failed to notify the central server about the shutdown: {e}"),
+        }
+    }
+
+    terminate();
+}
+
+/// Saves what has to survive the shutdown and exits the process.
+pub fn terminate() -> ! {
+    snapshot::save_on_shutdown();
+    safe_mode::clear_startups();
+    Logger::instance("globed_game_server", true).flush();
+    std::process::exit(1);
+}
@@ -0,0 +1,315 @@
+//! Warm restarts. When `GLOBED_GS_SNAPSHOT` is set, the rooms and the players in them are saved to that file
+//! when the server is shut down and periodically while it runs, and restored on the next startup,
+//! so that neither a quick restart nor a crash breaks up every room.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use globed_shared::{
+    anyhow::{self, anyhow, bail},
+    debug, info, warn,
+};
+
+use crate::{
+    data::*,
+    server::GameServer,
+    tokio::{self, time::sleep},
+};
+
+const SNAPSHOT_MAGIC: &[u8] = b"globed-snapshot";
+/// Bump whenever the format of `Snapshot` changes, older snapshots are then ignored.
+const SNAPSHOT_VERSION: u16 = 5;
+/// Snapshots older than this are not restored, players have long given up on reconnecting by then.
+const MAX_SNAPSHOT_AGE: Duration = Duration::from_secs(300);
+/// How long players have to log back in before their restored rooms are deleted.
+const RESUME_WINDOW: Duration = Duration::from_secs(120);
+/// Default for `GLOBED_GS_SNAPSHOT_INTERVAL`.
+const DEFAULT_SNAPSHOT_INTERVAL: u64 = 30;
+
+static SNAPSHOT_TARGET: OnceLock<(&'static GameServer, PathBuf)> = OnceLock::new();
+/// held while a snapshot is written, so a periodic save can't interleave with the one on shutdown
+static SAVE_LOCK: Mutex<()> = Mutex::new(());
+/// set by `save_on_shutdown`, no snapshots are taken after that, as players are being disconnected and their rooms deleted
+static FINAL_SAVED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Encodable, Decodable, DynamicSize)]
+struct RoomSnapshot {
+    id: u32,
+    owner: i32,
+    moderators: Vec<i32>,
+    tags: RoomTags,
+    name: InlineString<32>,
+    password: InlineString<16>,
+    settings: RoomSettings,
+}
+
+#[derive(Encodable, Decodable, DynamicSize)]
+struct SessionSnapshot {
+    account_id: i32,
+    room_id: u32,
+}
+
+#[derive(Encodable, Decodable, DynamicSize)]
+pub(crate) struct Snapshot {
+    /// unix timestamp of when the snapshot was taken
+    pub created_at: u64,
+    rooms: Vec<RoomSnapshot>,
+    sessions: Vec<SessionSnapshot>,
+}
+
+fn current_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |x| x.as_secs())
+}
+
+impl Snapshot {
+    pub fn capture(game_server: &GameServer) -> Self {
+        let rooms = game_server
+            .state
+            .room_manager
+            .get_rooms()
+            .iter()
+            .map(|(&id, room)| RoomSnapshot {
+                id,
+                owner: room.owner,
+                moderators: room.moderators.clone(),
+                tags: room.get_tags(),
+                name: room.name.clone(),
+                password: room.password.clone(),
+                settings: room.settings,
+            })
+            .collect();
+
+        // players in the global room don't need to be restored
+        let mut sessions: Vec<_> = game_server
+            .clients
+            .lock()
+            .values()
+            .filter(|thr| thr.authenticated() && thr.room_id.load(Ordering::Relaxed) != 0)
+            .map(|thr| SessionSnapshot {
+                account_id: thr.account_id.load(Ordering::Relaxed),
+                room_id: thr.room_id.load(Ordering::Relaxed),
+            })
+            .collect();
+
+        // players from an earlier snapshot that didn't log back in yet
+        sessions.extend(
+            game_server
+                .state
+                .room_manager
+                .get_pending_resumes()
+                .into_iter()
+                .map(|(account_id, room_id)| SessionSnapshot { account_id, room_id }),
+        );
+
+        Self {
+            created_at: current_time(),
+            rooms,
+            sessions,
+        }
+    }
+
+    /// Encodes the snapshot along with a header that identifies the format, see `decode`.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = ByteBuffer::with_capacity(SNAPSHOT_MAGIC.len() + 2 + self.encoded_size());
+        buf.write_bytes(SNAPSHOT_MAGIC);
+        buf.write_u16(SNAPSHOT_VERSION);
+        buf.write_value(self);
+
+        buf.into_vec()
+    }
+
+    pub fn decode(data: &[u8]) -> anyhow::Result<Self> {
+        let mut reader = ByteReader::from_bytes(data);
+        let magic = reader.read_bytes(SNAPSHOT_MAGIC.len()).map_err(|e| anyhow!("{e}"))?;
+        if magic != SNAPSHOT_MAGIC {
+            bail!("not a snapshot file");
+        }
+
+        let version = reader.read_u16().map_err(|e| anyhow!("{e}"))?;
+        if version != SNAPSHOT_VERSION {
+            bail!("snapshot was made by an incompatible server version ({version}, expected {SNAPSHOT_VERSION})");
+        }
+
+        reader.read_value::<Self>().map_err(|e| anyhow!("{e}"))
+    }
+
+    /// Whether the snapshot is too old to be restored.
+    pub fn is_expired(&self) -> bool {
+        current_time().saturating_sub(self.created_at) > MAX_SNAPSHOT_AGE.as_secs()
+    }
+
+    fn encode_to_file(&self, path: &Path) -> anyhow::Result<()> {
+        let data = self.encode();
+
+        // write to a temporary file first, so a crash in the middle of saving doesn't leave a broken snapshot behind
+        let _guard = SAVE_LOCK.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, data)?;
+        std::fs::rename(&tmp_path, path)?;
+
+        Ok(())
+    }
+
+    fn decode_from_file(path: &Path) -> anyhow::Result<Self> {
+        Self::decode(&std::fs::read(path)?)
+    }
+}
+
+/// Returns how often a snapshot is saved while the server runs, based on `GLOBED_GS_SNAPSHOT_INTERVAL`
+/// (by default `DEFAULT_SNAPSHOT_INTERVAL` seconds). `None` if periodic snapshots are disabled.
+fn snapshot_interval() -> Option<Duration> {
+    let secs = match std::env::var("GLOBED_GS_SNAPSHOT_INTERVAL").ok().map(|x| x.trim().parse::<u64>()) {
+        None => DEFAULT_SNAPSHOT_INTERVAL,
+        Some(Ok(x)) => x,
+        Some(Err(_)) => {
+            warn!("invalid value for GLOBED_GS_SNAPSHOT_INTERVAL, must be a number, using the default ({DEFAULT_SNAPSHOT_INTERVAL})");
+            DEFAULT_SNAPSHOT_INTERVAL
+        }
+    };
+
+    (secs != 0).then(|| Duration::from_secs(secs))
+}
+
+/// Restores the snapshot from `GLOBED_GS_SNAPSHOT` if there is one, and makes sure a new one gets saved periodically and on shutdown.
+/// Does nothing if the variable is not set.
+pub fn start(game_server: &'static GameServer) {
+    let Ok(path) = std::env::var("GLOBED_GS_SNAPSHOT") else {
+        return;
+    };
+
+    let path = PathBuf::from(path);
+
+    if path.exists() {
+        match Snapshot::decode_from_file(&path) {
+            Ok(snapshot) if snapshot.is_expired() => {
+                info!("Ignoring the snapshot at {}, as it is too old", path.display());
+            }
+            Ok(snapshot) => restore(game_server, snapshot),
+            Err(e) => warn!("failed to load the snapshot at {}: {e}", path.display()),
+        }
+
+        // a snapshot must never be restored twice, players would get pulled into rooms they have left since
+        if let Err(e) = std::fs::remove_file(&path) {
+            warn!("failed to delete the snapshot at {}: {e}", path.display());
+        }
+    }
+
+    if let Some(interval) = snapshot_interval() {
+        let path = path.clone();
+
+        tokio::spawn(async move {
+            loop {
+                sleep(interval).await;
+
+                if is_final_saved() {
+                    return;
+                }
+
+                // capturing only takes the locks for a moment, encoding and writing the file is left to the blocking pool
+                let snapshot = Snapshot::capture(game_server);
+                let file_path = path.clone();
+
+                let result = tokio::task::spawn_blocking(move || snapshot.encode_to_file(&file_path).map(|()| snapshot.rooms.len())).await;
+
+                match result {
+                    Ok(Ok(rooms)) => debug!("saved {rooms} rooms to the snapshot"),
+                    Ok(Err(e)) => warn!("failed to save the snapshot to {}: {e}", path.display()),
+                    Err(e) => warn!("failed to save the snapshot: {e}"),
+                }
+            }
+        });
+    }
+
+    let _ = SNAPSHOT_TARGET.set((game_server, path));
+}
+
+/// Recreates the rooms from the snapshot, and puts the players that were in them back once they log in.
+pub(crate) fn restore(game_server: &'static GameServer, snapshot: Snapshot) {
+    let room_manager = &game_server.state.room_manager;
+
+    for room in &snapshot.rooms {
+        room_manager.restore_room(
+            room.id,
+            room.owner,
+            room.moderators.clone(),
+            room.name.clone(),
+            room.password.clone(),
+            room.settings,
+        );
+        room_manager.with_any(room.id, |restored| {
+            restored.set_tags(room.tags.tags.clone());
+            for member in &room.tags.members {
+                restored.member_tags.insert(member.account_id, member.tag);
+            }
+        });
+    }
+
+    for session in &snapshot.sessions {
+        room_manager.add_pending_resume(session.account_id, session.room_id);
+    }
+
+    info!(
+        "Restored {} rooms from the snapshot, waiting for {} players to reconnect",
+        snapshot.rooms.len(),
+        snapshot.sessions.len()
+    );
+
+    tokio::spawn(async move {
+        sleep(RESUME_WINDOW).await;
+        game_server.state.room_manager.finish_restore();
+    });
+}
+
+/// Whether the snapshot was already saved for the shutdown.
+pub(crate) fn is_final_saved() -> bool {
+    FINAL_SAVED.load(Ordering::SeqCst)
+}
+
+/// Saves a snapshot of the server right away, without counting as the final one. Used when the shutdown countdown starts,
+/// so that the rooms survive even if the process is killed before the countdown is over. Does nothing if snapshots are disabled.
+pub fn save_now() {
+    if is_final_saved() {
+        return;
+    }
+
+    let Some((game_server, path)) = SNAPSHOT_TARGET.get() else {
+        return;
+    };
+
+    let snapshot = Snapshot::capture(game_server);
+
+    match snapshot.encode_to_file(path) {
+        Ok(()) => info!("Saved {} rooms to the snapshot at {}", snapshot.rooms.len(), path.display()),
+        Err(e) => warn!("failed to save the snapshot to {}: {e}", path.display()),
+    }
+}
+
+/// Saves a snapshot of the server, called when the server is going down, before players are disconnected.
+/// Only the first call does anything, and nothing is saved if snapshots are disabled.
+pub fn save_on_shutdown() {
+    if FINAL_SAVED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let Some((game_server, path)) = SNAPSHOT_TARGET.get() else {
+        return;
+    };
+
+    let snapshot = Snapshot::capture(game_server);
+
+    match snapshot.encode_to_file(path) {
+        Ok(()) => info!(
+            "Saved {} rooms and {} players to the snapshot at {}",
+            snapshot.rooms.len(),
+            snapshot.sessions.len(),
+            path.display()
+        ),
+        Err(e) => warn!("failed to save the snapshot to {}: {e}", path.display()),
+    }
+}
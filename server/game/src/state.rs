@@ -1,15 +1,67 @@
 use crate::{
-    managers::{RoleManager, RoomManager},
-    util::WordFilter,
+    cluster::ClusterLink,
+    events::EventBus,
+    managers::{
+        AuthLockoutManager, BotManager, ChatManager, ChatTailManager, FilterManager, FriendManager, InviteCodeManager, IpBanManager,
+        LevelBlocklistManager, NameClaimManager, NoticeScheduler, PlayerHistoryManager, PresenceManager, RateLimiter, RecentPlayerCache, RoleManager,
+        RoomManager, ServerQueryManager, SessionManager, TrafficStatsManager, UsageStatsManager,
+    },
+    scripting::ScriptHooks,
+    store::LocalStore,
+    util::{ServerGeoIp, WordFilter},
+};
+use globed_shared::{geoip::DistanceClass, IntMap, SyncMutex};
+use std::{
+    net::IpAddr,
+    sync::atomic::{AtomicU32, Ordering},
 };
-use std::sync::atomic::{AtomicU32, Ordering};
 
 #[derive(Default)]
 pub struct ServerState {
     pub player_count: AtomicU32,
+    /// amount of players with a lossy connection, so we don't have to look for them when there are none
+    pub lossy_player_count: AtomicU32,
     pub room_manager: RoomManager,
     pub role_manager: RoleManager,
+    pub level_blocklist: LevelBlocklistManager,
     pub filter: WordFilter,
+    /// chat word filter from the central server configuration
+    pub chat_filter: FilterManager,
+    /// addresses banned in the central server configuration
+    pub ip_bans: IpBanManager,
+    /// connection, login and udp packet limits per IP address
+    pub rate_limiter: RateLimiter,
+    pub auth_lockout: AuthLockoutManager,
+    pub totp_lockout: AuthLockoutManager,
+    /// failed admin panel passwords, checked before hashing so the hashes can't be used to tie up the runtime
+    pub admin_lockout: AuthLockoutManager,
+    /// names taken by players on a standalone server
+    pub name_claims: NameClaimManager,
+    /// last used TOTP time step for every account, to prevent reusing codes
+    pub totp_last_step: SyncMutex<IntMap<i32, u64>>,
+    pub events: EventBus,
+    pub chat: ChatManager,
+    /// moderators reading the chat of rooms and levels they aren't in
+    pub chat_tails: ChatTailManager,
+    pub usage_stats: UsageStatsManager,
+    pub player_history: PlayerHistoryManager,
+    pub notice_scheduler: NoticeScheduler,
+    pub traffic: TrafficStatsManager,
+    pub bots: BotManager,
+    pub server_query: ServerQueryManager,
+    pub invite_codes: InviteCodeManager,
+    pub recent_players: RecentPlayerCache,
+    pub presence: PresenceManager,
+    /// friend lists of online players
+    pub friends: FriendManager,
+    /// sessions of the players on this server, for their session lists
+    pub sessions: SessionManager,
+    pub scripts: ScriptHooks,
+    /// local database, only on standalone servers that have one configured
+    pub store: Option<LocalStore>,
+    pub cluster: ClusterLink,
+    /// used for estimating the distance to clients, only when a GeoIP database and the server location are configured
+    pub geoip: Option<ServerGeoIp>,
 }
 
 impl ServerState {
@@ -31,4 +83,8 @@ impl ServerState {
     pub fn dec_player_count(&self) {
         self.player_count.fetch_sub(1, Ordering::SeqCst);
     }
+
+    pub fn get_distance_class(&self, address: IpAddr) -> DistanceClass {
+        self.geoip.as_ref().map_or(DistanceClass::Unknown, |geoip| geoip.distance_class(address))
+    }
 }
@@ -0,0 +1,393 @@
+//! Embedded SQLite database for data that should survive restarts on standalone servers,
+//! which don't have a central server to keep it for them.
+//! Rooms are not stored here, they are kept in the snapshot file instead (see `crate::snapshot`).
+
+use std::{
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use globed_shared::{anyhow, warn, PlayerReport, ReportState, ReportStateChange, UserEntry, UserPreferences};
+use sqlx::{
+    prelude::*,
+    query,
+    sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow},
+};
+
+use crate::{
+    data::LevelId,
+    events::ServerEvent,
+    server::GameServer,
+    tokio::{self, sync::broadcast::error::RecvError},
+};
+
+/// How often the player count is saved.
+const PLAYER_COUNT_INTERVAL: Duration = Duration::from_secs(600);
+/// How long player count history is kept.
+const PLAYER_COUNT_RETENTION: Duration = Duration::from_days(31);
+/// Default for `GLOBED_GS_CHAT_LOG_DAYS`.
+const DEFAULT_CHAT_LOG_DAYS: u64 = 30;
+
+pub struct LocalStore {
+    pool: SqlitePool,
+    chat_log_retention: Duration,
+}
+
+#[derive(Clone, Copy)]
+enum DailyStat {
+    Logins,
+    ChatMessages,
+    RoomsCreated,
+}
+
+impl DailyStat {
+    const fn column(self) -> &'static str {
+        match self {
+            Self::Logins => "logins",
+            Self::ChatMessages => "chat_messages",
+            Self::RoomsCreated => "rooms_created",
+        }
+    }
+}
+
+fn current_time() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| i64::try_from(x.as_secs()).unwrap_or(i64::MAX))
+}
+
+fn time_before(duration: Duration) -> i64 {
+    current_time().saturating_sub(i64::try_from(duration.as_secs()).unwrap_or(i64::MAX))
+}
+
+fn user_from_row(row: &SqliteRow) -> Result<UserEntry, sqlx::Error> {
+    let user_roles: Option<String> = row.try_get("user_roles")?;
+    let mut user_roles = user_roles.map_or(Vec::new(), |s| s.split(',').map(|x| x.to_owned()).collect::<Vec<_>>());
+    user_roles.retain(|x| !x.is_empty());
+
+    let preferences: Option<String> = row.try_get("preferences")?;
+    let preferences = preferences
+        .map_or(Ok(UserPreferences::default()), |s| serde_json::from_str(&s))
+        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+    Ok(UserEntry {
+        account_id: row.try_get("account_id")?,
+        user_name: row.try_get("user_name")?,
+        name_color: row.try_get("name_color")?,
+        user_roles,
+        is_banned: row.try_get("is_banned")?,
+        is_muted: row.try_get("is_muted")?,
+        is_whitelisted: row.try_get("is_whitelisted")?,
+        admin_password: None,
+        totp_secret: None,
+        violation_reason: row.try_get("violation_reason")?,
+        violation_expiry: row.try_get("violation_expiry")?,
+        first_seen: row.try_get("first_seen")?,
+        violation_count: row.try_get("violation_count")?,
+        timed_roles: Vec::new(),
+        preferences,
+    })
+}
+
+fn report_from_row(row: &SqliteRow) -> Result<PlayerReport, sqlx::Error> {
+    let state: u8 = row.try_get("state")?;
+
+    Ok(PlayerReport {
+        id: row.try_get("id")?,
+        reporter_id: row.try_get("reporter_id")?,
+        reported_id: row.try_get("reported_id")?,
+        reason: row.try_get("reason")?,
+        state: ReportState::from_u8(state).ok_or_else(|| sqlx::Error::Decode(format!("invalid report state {state}").into()))?,
+        handled_by: row.try_get("handled_by")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+impl LocalStore {
+    /// Opens (or creates) the database at the given path and brings its schema up to date.
+    pub async fn open(path: &str) -> anyhow::Result<Self> {
+        let options = SqliteConnectOptions::from_str(path)?.create_if_missing(true);
+        let pool = SqlitePoolOptions::new().max_connections(4).connect_with(options).await?;
+
+        sqlx::migrate!().run(&pool).await?;
+
+        let chat_log_days = std::env::var("GLOBED_GS_CHAT_LOG_DAYS")
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(DEFAULT_CHAT_LOG_DAYS);
+
+        Ok(Self {
+            pool,
+            chat_log_retention: Duration::from_days(chat_log_days),
+        })
+    }
+
+    /* local accounts */
+
+    pub async fn get_user(&self, account_id: i32) -> Result<Option<UserEntry>, sqlx::Error> {
+        let row = query("SELECT * FROM users WHERE account_id = ?")
+            .bind(account_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        self.unwrap_user(row).await
+    }
+
+    pub async fn get_user_by_name(&self, name: &str) -> Result<Option<UserEntry>, sqlx::Error> {
+        // an exact match gets selected first, then any name containing the given one
+        let row = query("SELECT * FROM users WHERE user_name LIKE ? OR user_name LIKE ? ORDER BY (user_name LIKE ?) DESC")
+            .bind(name)
+            .bind(format!("%{name}%"))
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        self.unwrap_user(row).await
+    }
+
+    pub async fn update_user(&self, user: &UserEntry) -> Result<(), sqlx::Error> {
+        query(
+            "INSERT INTO users (account_id, user_name, name_color, user_roles, is_banned, is_muted, is_whitelisted, violation_reason, violation_expiry, first_seen, violation_count, preferences)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    ON CONFLICT(account_id) DO UPDATE SET user_name = excluded.user_name, name_color = excluded.name_color, user_roles = excluded.user_roles,
+                    is_banned = excluded.is_banned, is_muted = excluded.is_muted, is_whitelisted = excluded.is_whitelisted, violation_reason = excluded.violation_reason,
+                    violation_expiry = excluded.violation_expiry, first_seen = excluded.first_seen, violation_count = excluded.violation_count,
+                    preferences = excluded.preferences",
+        )
+        .bind(user.account_id)
+        .bind(&user.user_name)
+        .bind(&user.name_color)
+        .bind(user.user_roles.join(","))
+        .bind(user.is_banned)
+        .bind(user.is_muted)
+        .bind(user.is_whitelisted)
+        .bind(&user.violation_reason)
+        .bind(user.violation_expiry)
+        .bind(user.first_seen)
+        .bind(user.violation_count)
+        .bind(serde_json::to_string(&user.preferences).unwrap_or_default())
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+    }
+
+    /// Creates the account if it doesn't exist yet and updates the name and the last login time, then returns the account.
+    pub async fn record_login(&self, account_id: i32, name: &str) -> Result<UserEntry, sqlx::Error> {
+        let now = current_time();
+
+        query(
+            "INSERT INTO users (account_id, user_name, first_seen, last_seen) VALUES (?, ?, ?, ?)
+                    ON CONFLICT(account_id) DO UPDATE SET user_name = excluded.user_name, last_seen = excluded.last_seen",
+        )
+        .bind(account_id)
+        .bind(name)
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(self.get_user(account_id).await?.unwrap_or_else(|| UserEntry::new(account_id)))
+    }
+
+    /// Converts the row into a user and lifts their ban/mute if it expired.
+    async fn unwrap_user(&self, row: Option<SqliteRow>) -> Result<Option<UserEntry>, sqlx::Error> {
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let mut user = user_from_row(&row)?;
+
+        if (user.is_banned || user.is_muted) && user.violation_expiry.is_some_and(|expiry| current_time() > expiry) {
+            user.is_banned = false;
+            user.is_muted = false;
+            user.violation_reason = None;
+            user.violation_expiry = None;
+
+            self.update_user(&user).await?;
+        }
+
+        Ok(Some(user))
+    }
+
+    /* reports */
+
+    /// Saves a new open report and returns its ID.
+    pub async fn create_report(&self, reporter_id: i32, reported_id: i32, reason: &str) -> Result<i64, sqlx::Error> {
+        let result = query("INSERT INTO reports (reporter_id, reported_id, reason, created_at) VALUES (?, ?, ?, ?)")
+            .bind(reporter_id)
+            .bind(reported_id)
+            .bind(reason)
+            .bind(current_time())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Stores the sealed voice capture of the reported player, returns `false` if the report doesn't exist.
+    pub async fn set_report_voice_capture(&self, id: i64, data: &[u8]) -> Result<bool, sqlx::Error> {
+        let result = query("UPDATE reports SET voice_capture = ? WHERE id = ?")
+            .bind(data)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    pub async fn get_report(&self, id: i64) -> Result<Option<PlayerReport>, sqlx::Error> {
+        let row = query("SELECT * FROM reports WHERE id = ?").bind(id).fetch_optional(&self.pool).await?;
+
+        row.as_ref().map(report_from_row).transpose()
+    }
+
+    /// Returns reports that are open or claimed, newest first.
+    pub async fn get_pending_reports(&self, limit: u32) -> Result<Vec<PlayerReport>, sqlx::Error> {
+        let rows = query("SELECT * FROM reports WHERE state IN (?, ?) ORDER BY id DESC LIMIT ?")
+            .bind(ReportState::Open as u8)
+            .bind(ReportState::Claimed as u8)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(report_from_row).collect()
+    }
+
+    /// Changes the state of a report if the moderator is allowed to, see `ReportState::can_change_to`.
+    /// Returns the report as it is afterwards, so the caller can tell whether it changed, or `None` if it doesn't exist.
+    pub async fn update_report_state(&self, change: &ReportStateChange) -> Result<Option<PlayerReport>, sqlx::Error> {
+        let Some(report) = self.get_report(change.id).await? else {
+            return Ok(None);
+        };
+
+        if !report.state.can_change_to(change.state, report.handled_by, change.moderator_id) {
+            return Ok(Some(report));
+        }
+
+        let handled_by = if change.state == ReportState::Open { 0 } else { change.moderator_id };
+
+        // only update if nobody else changed the report in the meantime
+        query("UPDATE reports SET state = ?, handled_by = ? WHERE id = ? AND state = ? AND handled_by = ?")
+            .bind(change.state as u8)
+            .bind(handled_by)
+            .bind(change.id)
+            .bind(report.state as u8)
+            .bind(report.handled_by)
+            .execute(&self.pool)
+            .await?;
+
+        self.get_report(change.id).await
+    }
+
+    /* chat log and stats */
+
+    async fn log_chat_message(&self, account_id: i32, room_id: u32, level_id: LevelId, message: &str) -> Result<(), sqlx::Error> {
+        query("INSERT INTO chat_log (log_time, account_id, room_id, level_id, message) VALUES (?, ?, ?, ?, ?)")
+            .bind(current_time())
+            .bind(account_id)
+            .bind(room_id)
+            .bind(level_id)
+            .bind(message)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+    }
+
+    async fn bump_daily_stat(&self, stat: DailyStat) -> Result<(), sqlx::Error> {
+        let column = stat.column();
+
+        query(&format!(
+            "INSERT INTO daily_stats (day, {column}) VALUES (?, 1) ON CONFLICT(day) DO UPDATE SET {column} = {column} + 1"
+        ))
+        .bind(current_time() / 86400)
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+    }
+
+    async fn record_event(&self, event: &ServerEvent) -> Result<(), sqlx::Error> {
+        match event {
+            ServerEvent::PlayerJoined { .. } => self.bump_daily_stat(DailyStat::Logins).await,
+            ServerEvent::PlayerLeft { .. } | ServerEvent::PresenceChanged { .. } | ServerEvent::RoomCountdownStarted { .. } => Ok(()),
+            ServerEvent::ChatMessage {
+                account_id,
+                room_id,
+                level_id,
+                message,
+            } => {
+                self.log_chat_message(*account_id, *room_id, *level_id, message).await?;
+                self.bump_daily_stat(DailyStat::ChatMessages).await
+            }
+            ServerEvent::RoomCreated { .. } => self.bump_daily_stat(DailyStat::RoomsCreated).await,
+        }
+    }
+
+    async fn record_player_count(&self, count: u32) -> Result<(), sqlx::Error> {
+        let now = current_time();
+
+        query("INSERT INTO player_counts (log_time, count) VALUES (?, ?)")
+            .bind(now)
+            .bind(count)
+            .execute(&self.pool)
+            .await?;
+
+        query("INSERT INTO daily_stats (day, peak_players) VALUES (?, ?) ON CONFLICT(day) DO UPDATE SET peak_players = MAX(peak_players, excluded.peak_players)")
+            .bind(now / 86400)
+            .bind(count)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Deletes chat messages and player counts that are older than their retention period.
+    async fn prune(&self) -> Result<(), sqlx::Error> {
+        query("DELETE FROM chat_log WHERE log_time < ?")
+            .bind(time_before(self.chat_log_retention))
+            .execute(&self.pool)
+            .await?;
+
+        query("DELETE FROM player_counts WHERE log_time < ?")
+            .bind(time_before(PLAYER_COUNT_RETENTION))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Starts recording the chat log and stats into the database, if there is one.
+pub fn start(game_server: &'static GameServer) {
+    let Some(store) = &game_server.state.store else {
+        return;
+    };
+
+    let mut events = game_server.state.events.subscribe();
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PLAYER_COUNT_INTERVAL);
+
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => {
+                        if let Err(e) = store.record_event(&event).await {
+                            warn!("failed to save an event to the database: {e}");
+                        }
+                    }
+                    Err(RecvError::Lagged(count)) => warn!("database is too slow to keep up, {count} events were not saved"),
+                    Err(RecvError::Closed) => break,
+                },
+                _ = interval.tick() => {
+                    if let Err(e) = store.record_player_count(game_server.state.get_player_count()).await {
+                        warn!("failed to save the player count to the database: {e}");
+                    }
+
+                    if let Err(e) = store.prune().await {
+                        warn!("failed to delete old data from the database: {e}");
+                    }
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,332 @@
+use std::{net::SocketAddr, time::Duration};
+
+use crate::tokio::{
+    self,
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpStream, UdpSocket,
+    },
+    sync::mpsc,
+};
+
+use globed_shared::{
+    anyhow::{self, anyhow, bail},
+    crypto_box::{
+        aead::{AeadCore, AeadInPlace, OsRng},
+        ChaChaBox, SecretKey,
+    },
+    PROTOCOL_VERSION,
+};
+
+use crate::data::*;
+
+const MARKER_CONN_INITIAL: u8 = 0xe0;
+
+const NONCE_SIZE: usize = 24;
+const MAC_SIZE: usize = 16;
+
+const MAX_PACKET_SIZE: usize = 65536;
+const RECV_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A raw packet received from the server, with the header already parsed and the payload decrypted.
+pub struct ReceivedPacket {
+    pub packet_id: u16,
+    pub payload: Vec<u8>,
+}
+
+impl ReceivedPacket {
+    pub fn decode<P: Decodable>(&self) -> anyhow::Result<P> {
+        ByteReader::from_bytes(&self.payload)
+            .read_value()
+            .map_err(|e| anyhow!("failed to decode packet {}: {e}", self.packet_id))
+    }
+}
+
+/// Minimal implementation of the client side of the protocol, used for replaying captures and load testing.
+/// Never use this to connect to a server that does not belong to you, there is no authentication (the server must be standalone).
+pub struct TestClient {
+    writer: OwnedWriteHalf,
+    udp_socket: std::sync::Arc<UdpSocket>,
+    secret_key: SecretKey,
+    crypto_box: Option<ChaChaBox>,
+    incoming: mpsc::UnboundedReceiver<Vec<u8>>,
+    pub account_id: i32,
+}
+
+impl TestClient {
+    /// Connects to the server and performs the crypto handshake.
+    pub async fn connect(address: SocketAddr) -> anyhow::Result<Self> {
+        let stream = TcpStream::connect(address).await?;
+        stream.set_nodelay(true)?;
+
+        let udp_socket = UdpSocket::bind(if address.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }).await?;
+        udp_socket.connect(address).await?;
+        let udp_socket = std::sync::Arc::new(udp_socket);
+
+        let (reader, writer) = stream.into_split();
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(Self::tcp_reader(reader, tx.clone()));
+        tokio::spawn(Self::udp_reader(udp_socket.clone(), tx));
+
+        let mut client = Self {
+            writer,
+            udp_socket,
+            secret_key: SecretKey::generate(&mut OsRng),
+            crypto_box: None,
+            incoming: rx,
+            account_id: 0,
+        };
+
+        client.writer.write_u8(MARKER_CONN_INITIAL).await?;
+        client.handshake().await?;
+
+        Ok(client)
+    }
+
+    async fn tcp_reader(mut reader: OwnedReadHalf, tx: mpsc::UnboundedSender<Vec<u8>>) {
+        while let Ok(len) = reader.read_u32().await {
+            let len = len as usize;
+            if len > MAX_PACKET_SIZE {
+                break;
+            }
+
+            let mut buf = vec![0u8; len];
+            if reader.read_exact(&mut buf).await.is_err() || tx.send(buf).is_err() {
+                break;
+            }
+        }
+    }
+
+    async fn udp_reader(socket: std::sync::Arc<UdpSocket>, tx: mpsc::UnboundedSender<Vec<u8>>) {
+        let mut buf = vec![0u8; MAX_PACKET_SIZE];
+
+        loop {
+            let Ok(len) = socket.recv(&mut buf).await else {
+                break;
+            };
+
+            if tx.send(buf[..len].to_vec()).is_err() {
+                break;
+            }
+        }
+    }
+
+    async fn handshake(&mut self) -> anyhow::Result<()> {
+        self.send_packet(&CryptoHandshakeStartPacket {
+            protocol: PROTOCOL_VERSION,
+            key: self.secret_key.public_key().into(),
+            compression: CompressionSupport::default(),
+        })
+        .await?;
+
+        loop {
+            let packet = self.recv_packet().await?;
+
+            match packet.packet_id {
+                CryptoHandshakeResponsePacket::PACKET_ID => {
+                    let response: CryptoHandshakeResponsePacket = packet.decode()?;
+                    self.crypto_box = Some(ChaChaBox::new(&response.key.0, &self.secret_key));
+                    return Ok(());
+                }
+                ProtocolMismatchPacket::PACKET_ID => bail!("protocol mismatch, the server uses a different protocol version"),
+                _ => {}
+            }
+        }
+    }
+
+    /// Logs in and claims the thread. Only works on standalone servers, as no valid token is sent.
+    pub async fn login(&mut self, account_id: i32, user_id: i32, name: &str) -> anyhow::Result<()> {
+        self.send_packet(&LoginPacket {
+            account_id,
+            user_id,
+            name: InlineString::new(name),
+            token: FastString::new(""),
+            icons: PlayerIconData::default(),
+            fragmentation_limit: 65000,
+            platform: InlineString::new("globed-test-client"),
+            is_invisible: false,
+            invite_code: InlineString::new(""),
+        })
+        .await?;
+
+        let secret_key = loop {
+            let packet = self.recv_packet().await?;
+
+            match packet.packet_id {
+                LoggedInPacket::PACKET_ID => break packet.decode::<LoggedInPacket>()?.secret_key,
+                LoginFailedPacket::PACKET_ID => {
+                    let (code, message): (ErrorCode, FastString) = packet.decode()?;
+                    bail!("login failed ({code:?}): {message}");
+                }
+                ServerDisconnectPacket::PACKET_ID | ServerBannedPacket::PACKET_ID => {
+                    let message: FastString = packet.decode()?;
+                    bail!("login failed: {message}");
+                }
+                _ => {}
+            }
+        };
+
+        self.account_id = account_id;
+
+        // the claim packet is sent over udp, so it might get lost. keepalive packets only get a response once we are claimed.
+        for _ in 0..5 {
+            self.send_packet_udp(&ClaimThreadPacket { secret_key }).await?;
+            self.send_packet(&KeepaliveTCPPacket).await?;
+
+            let claimed = tokio::time::timeout(Duration::from_secs(2), async {
+                loop {
+                    match self.recv_packet().await {
+                        Ok(packet) if packet.packet_id == KeepaliveTCPResponsePacket::PACKET_ID => return Ok(()),
+                        Ok(packet) if packet.packet_id == ClaimThreadFailedPacket::PACKET_ID => {
+                            return Err(anyhow!("failed to claim the thread"));
+                        }
+                        Ok(_) => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+            })
+            .await;
+
+            match claimed {
+                Ok(res) => return res,
+                Err(_) => continue,
+            }
+        }
+
+        bail!("failed to claim the thread, server did not respond")
+    }
+
+    /// Sends a disconnect packet, the connection is unusable afterwards.
+    pub async fn disconnect(&mut self) -> anyhow::Result<()> {
+        self.send_packet(&DisconnectPacket).await?;
+        self.writer.shutdown().await?;
+        Ok(())
+    }
+
+    pub async fn send_packet<P: Packet + Encodable>(&mut self, packet: &P) -> anyhow::Result<()> {
+        let mut buf = ByteBuffer::new();
+        buf.write_value(packet);
+
+        self.send_raw(P::PACKET_ID, P::ENCRYPTED, buf.as_bytes()).await
+    }
+
+    pub async fn send_packet_udp<P: Packet + Encodable>(&mut self, packet: &P) -> anyhow::Result<()> {
+        let mut buf = ByteBuffer::new();
+        buf.write_value(packet);
+
+        let data = self.encode_raw(P::PACKET_ID, P::ENCRYPTED, buf.as_bytes())?;
+        self.udp_socket.send(&data).await?;
+        Ok(())
+    }
+
+    /// Sends a packet over TCP, given the ID and the unencrypted packet body. Encrypts the body if `encrypted` is `true`.
+    pub async fn send_raw(&mut self, packet_id: u16, encrypted: bool, payload: &[u8]) -> anyhow::Result<()> {
+        let data = self.encode_raw(packet_id, encrypted, payload)?;
+
+        self.writer.write_u32(data.len() as u32).await?;
+        self.writer.write_all(&data).await?;
+        self.writer.flush().await?;
+
+        Ok(())
+    }
+
+    fn encode_raw(&self, packet_id: u16, encrypted: bool, payload: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut buf = ByteBuffer::new();
+        buf.write_value(&PacketHeader {
+            packet_id,
+            encrypted,
+            compressed: false,
+        });
+
+        if !encrypted {
+            buf.write_bytes(payload);
+            return Ok(buf.into_vec());
+        }
+
+        let cbox = self
+            .crypto_box
+            .as_ref()
+            .ok_or_else(|| anyhow!("attempting to encrypt before the handshake"))?;
+
+        let mut ciphertext = payload.to_vec();
+        let nonce = ChaChaBox::generate_nonce(&mut OsRng);
+        let tag = cbox
+            .encrypt_in_place_detached(&nonce, b"", &mut ciphertext)
+            .map_err(|_| anyhow!("encryption failed"))?;
+
+        buf.write_bytes(&nonce);
+        buf.write_bytes(&tag);
+        buf.write_bytes(&ciphertext);
+
+        Ok(buf.into_vec())
+    }
+
+    /// Waits for the next packet from the server (either TCP or UDP), and decrypts it if needed.
+    pub async fn recv_packet(&mut self) -> anyhow::Result<ReceivedPacket> {
+        let data = tokio::time::timeout(RECV_TIMEOUT, self.incoming.recv())
+            .await
+            .map_err(|_| anyhow!("timed out waiting for a packet"))?
+            .ok_or_else(|| anyhow!("connection closed"))?;
+
+        self.decode_raw(data)
+    }
+
+    /// Like `recv_packet` but returns `None` immediately if there are no packets waiting.
+    pub fn try_recv_packet(&mut self) -> anyhow::Result<Option<ReceivedPacket>> {
+        match self.incoming.try_recv() {
+            Ok(data) => self.decode_raw(data).map(Some),
+            Err(mpsc::error::TryRecvError::Empty) => Ok(None),
+            Err(mpsc::error::TryRecvError::Disconnected) => bail!("connection closed"),
+        }
+    }
+
+    fn decode_raw(&self, mut data: Vec<u8>) -> anyhow::Result<ReceivedPacket> {
+        let header: PacketHeader = ByteReader::from_bytes(&data)
+            .read_value()
+            .map_err(|e| anyhow!("malformed packet header: {e}"))?;
+
+        // no compression is advertised in the handshake, so the server must never send compressed packets
+        if header.compressed {
+            bail!("received a compressed packet without negotiating compression");
+        }
+
+        if !header.encrypted {
+            return Ok(ReceivedPacket {
+                packet_id: header.packet_id,
+                payload: data.split_off(PacketHeader::SIZE),
+            });
+        }
+
+        if data.len() < PacketHeader::SIZE + NONCE_SIZE + MAC_SIZE {
+            bail!("malformed ciphertext");
+        }
+
+        let cbox = self
+            .crypto_box
+            .as_ref()
+            .ok_or_else(|| anyhow!("received an encrypted packet before the handshake"))?;
+
+        let nonce_start = PacketHeader::SIZE;
+        let mac_start = nonce_start + NONCE_SIZE;
+        let ciphertext_start = mac_start + MAC_SIZE;
+
+        let mut nonce = [0u8; NONCE_SIZE];
+        nonce.clone_from_slice(&data[nonce_start..mac_start]);
+        let nonce = nonce.into();
+
+        let mut mac = [0u8; MAC_SIZE];
+        mac.clone_from_slice(&data[mac_start..ciphertext_start]);
+        let mac = mac.into();
+
+        let mut payload = data.split_off(ciphertext_start);
+        cbox.decrypt_in_place_detached(&nonce, b"", &mut payload, &mac)
+            .map_err(|_| anyhow!("decryption failed"))?;
+
+        Ok(ReceivedPacket {
+            packet_id: header.packet_id,
+            payload,
+        })
+    }
+}
@@ -0,0 +1,7 @@
+pub mod client;
+#[cfg(test)]
+pub mod server;
+pub mod soak;
+
+pub use client::{ReceivedPacket, TestClient};
+pub use soak::SoakTestConfig;
@@ -0,0 +1,59 @@
+//! Helpers for unit tests that need a game server and logged in players, without any real clients behind them.
+
+use std::sync::{atomic::Ordering, Arc};
+
+use globed_shared::Secret;
+
+use crate::{
+    bridge::CentralBridge,
+    client::{ClientStream, ClientThread, UnauthorizedThread},
+    server::GameServer,
+    state::ServerState,
+    tokio::net::{TcpListener, TcpStream, UdpSocket},
+};
+
+/// Creates a standalone game server on random local ports. It never runs, so nothing is read from the sockets.
+pub async fn test_server() -> &'static GameServer {
+    let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    let server = Box::leak(Box::new(GameServer::new(
+        tcp,
+        udp,
+        ServerState::new(&[]),
+        CentralBridge::new("", Secret::default()),
+        true,
+    )));
+
+    server.state.room_manager.set_game_server(server);
+    server
+}
+
+/// Creates the thread of a client that connected but hasn't logged in yet, and registers it in the server like a real one.
+pub async fn test_pending_client(server: &'static GameServer) -> Arc<UnauthorizedThread> {
+    let addr = server.tcp_socket.local_addr().unwrap();
+    let (stream, (_, peer)) = crate::tokio::try_join!(TcpStream::connect(addr), server.tcp_socket.accept()).unwrap();
+
+    let thread = Arc::new(UnauthorizedThread::new(ClientStream::Tcp(stream), peer, server));
+    server.unauthorized_clients.lock().push_back(thread.clone());
+
+    thread
+}
+
+/// Creates the thread of a player that is logged in as `account_id`, and registers it in the server like a real one.
+pub async fn test_client(server: &'static GameServer, account_id: i32) -> Arc<ClientThread> {
+    let addr = server.tcp_socket.local_addr().unwrap();
+    let (stream, (_, peer)) = crate::tokio::try_join!(TcpStream::connect(addr), server.tcp_socket.accept()).unwrap();
+
+    let thread = Arc::new(ClientThread::from_unauthorized(UnauthorizedThread::new(
+        ClientStream::Tcp(stream),
+        peer,
+        server,
+    )));
+    thread.account_id.store(account_id, Ordering::Relaxed);
+
+    server.clients.lock().insert(peer, thread.clone());
+    server.accounts.lock().entry(account_id).or_default().push(thread.clone());
+
+    thread
+}
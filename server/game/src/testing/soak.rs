@@ -0,0 +1,313 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use crate::tokio::{self, time::Instant};
+use globed_shared::{
+    anyhow, debug, error, info,
+    rand::{self, Rng},
+    warn,
+};
+
+use super::TestClient;
+use crate::{data::*, server::GameServer};
+
+/// Account IDs of synthetic clients start from here, so they don't collide with real accounts.
+const SYNTHETIC_ACCOUNT_ID_BASE: i32 = 1_900_000_000;
+/// Reports before this much time has passed are not used as a memory baseline, to let allocations settle.
+const WARMUP_TIME: Duration = Duration::from_secs(120);
+/// How many consecutive reports the amount of unclaimed threads must grow for, to be considered a leak.
+const UNCLAIMED_GROWTH_REPORTS: usize = 3;
+/// Maximum allowed resident memory growth compared to the baseline.
+const MAX_MEMORY_GROWTH: f64 = 1.5;
+/// How many player data packets a synthetic client sends per second while on a level.
+const SYNTHETIC_TPS: u32 = 30;
+
+pub struct SoakTestConfig {
+    pub clients: usize,
+    pub report_interval: Duration,
+}
+
+impl SoakTestConfig {
+    /// Reads the soak test configuration from `GLOBED_GS_SOAK_CLIENTS` and `GLOBED_GS_SOAK_REPORT_INTERVAL`,
+    /// returns `None` if the soak test mode is not enabled.
+    pub fn from_env() -> Option<Self> {
+        let clients = std::env::var("GLOBED_GS_SOAK_CLIENTS").ok()?.parse::<usize>().ok().filter(|x| *x > 0)?;
+
+        let report_interval = std::env::var("GLOBED_GS_SOAK_REPORT_INTERVAL")
+            .ok()
+            .and_then(|x| x.parse::<u64>().ok())
+            .filter(|x| *x > 0)
+            .unwrap_or(60);
+
+        Some(Self {
+            clients,
+            report_interval: Duration::from_secs(report_interval),
+        })
+    }
+}
+
+#[derive(Default, Clone)]
+struct SoakSample {
+    threads: usize,
+    unauthorized: usize,
+    unclaimed: usize,
+    authenticated: usize,
+    player_count: u32,
+    rooms: usize,
+    global_room_players: usize,
+    resident_memory: Option<u64>,
+}
+
+#[derive(Default)]
+struct SoakCounters {
+    sessions: AtomicU64,
+    failed_sessions: AtomicU64,
+    packets_sent: AtomicU64,
+}
+
+/// Spawns synthetic clients connecting to our own server and periodically checks that the server state stays consistent.
+pub fn start(server: &'static GameServer, bind_address: SocketAddr, config: SoakTestConfig) {
+    // we can't connect to an unspecified address, so use loopback instead
+    let address = if bind_address.ip().is_unspecified() {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), bind_address.port())
+    } else {
+        bind_address
+    };
+
+    warn!(
+        "Soak test mode enabled, spawning {} synthetic clients connecting to {address}",
+        config.clients
+    );
+
+    // every synthetic client connects from loopback, so the per-address limits would cut most of them off
+    server.state.rate_limiter.exempt_loopback();
+
+    let counters: &'static SoakCounters = Box::leak(Box::default());
+
+    for idx in 0..config.clients {
+        tokio::spawn(async move {
+            // stagger the connections a little
+            let delay = Duration::from_millis(rand::thread_rng().gen_range(0..5000));
+            tokio::time::sleep(delay).await;
+
+            loop {
+                let result = run_synthetic_session(address, SYNTHETIC_ACCOUNT_ID_BASE + idx as i32, counters).await;
+
+                counters.sessions.fetch_add(1, Ordering::Relaxed);
+
+                if let Err(e) = result {
+                    counters.failed_sessions.fetch_add(1, Ordering::Relaxed);
+                    debug!("synthetic client {idx} session failed: {e}");
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        });
+    }
+
+    tokio::spawn(monitor(server, config, counters));
+}
+
+/// A single session of a synthetic client: login, play a couple of levels, then either disconnect gracefully or just drop the connection.
+async fn run_synthetic_session(address: SocketAddr, account_id: i32, counters: &SoakCounters) -> anyhow::Result<()> {
+    let mut client = TestClient::connect(address).await?;
+    client
+        .login(account_id, account_id, &format!("soak{}", account_id - SYNTHETIC_ACCOUNT_ID_BASE))
+        .await?;
+
+    let levels = rand::thread_rng().gen_range(1..4);
+
+    for _ in 0..levels {
+        let (level_id, duration) = {
+            let mut rng = rand::thread_rng();
+            (rng.gen_range(1..=20) as LevelId, Duration::from_secs(rng.gen_range(5..60)))
+        };
+
+        client.send_packet(&LevelJoinPacket { level_id, unlisted: false }).await?;
+
+        let started = Instant::now();
+        let mut sequence = 0;
+        let mut interval = tokio::time::interval(Duration::from_secs(1) / SYNTHETIC_TPS);
+
+        while started.elapsed() < duration {
+            interval.tick().await;
+            sequence += 1;
+
+            client
+                .send_packet(&PlayerDataPacket {
+                    sequence,
+                    data: PlayerData::default(),
+                })
+                .await?;
+
+            // discard everything the server sends us
+            while client.try_recv_packet()?.is_some() {}
+
+            counters.packets_sent.fetch_add(1, Ordering::Relaxed);
+        }
+
+        client.send_packet(&RequestPlayerProfilesPacket { requested: 0 }).await?;
+        client.send_packet(&LevelLeavePacket).await?;
+    }
+
+    // sometimes exercise the abrupt disconnect path as well, leaving the thread waiting for a recovery
+    if rand::thread_rng().gen_bool(0.25) {
+        drop(client);
+    } else {
+        client.disconnect().await?;
+    }
+
+    Ok(())
+}
+
+fn get_resident_memory() -> Option<u64> {
+    // only supported on linux, assume 4kb pages
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let resident_pages = statm.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+
+    Some(resident_pages * 4096)
+}
+
+fn take_sample(server: &GameServer) -> SoakSample {
+    let (threads, authenticated) = {
+        let clients = server.clients.lock();
+        (clients.len(), clients.values().filter(|thr| thr.authenticated()).count())
+    };
+
+    // disconnected threads waiting for recovery still count as players
+    let (unauthorized, lingering) = {
+        let unauthorized = server.unauthorized_clients.lock();
+        (
+            unauthorized.len(),
+            unauthorized.iter().filter(|thr| thr.account_id.load(Ordering::Relaxed) != 0).count(),
+        )
+    };
+
+    SoakSample {
+        threads,
+        unauthorized,
+        unclaimed: server.unclaimed_threads.lock().len(),
+        authenticated: authenticated + lingering,
+        player_count: server.state.get_player_count(),
+        rooms: server.state.room_manager.get_rooms().len(),
+        global_room_players: server.state.room_manager.get_global().manager.get_total_player_count(),
+        resident_memory: get_resident_memory(),
+    }
+}
+
+async fn monitor(server: &'static GameServer, config: SoakTestConfig, counters: &'static SoakCounters) {
+    let started = Instant::now();
+
+    let mut interval = tokio::time::interval(config.report_interval);
+    interval.tick().await;
+
+    let mut memory_baseline: Option<u64> = None;
+    let mut last_unclaimed = 0usize;
+    let mut unclaimed_growth = 0usize;
+    let mut total_violations = 0usize;
+
+    loop {
+        interval.tick().await;
+
+        let sample = take_sample(server);
+        let mut violations = Vec::new();
+
+        // every synthetic client has at most one live thread and one thread waiting for recovery
+        if sample.threads + sample.unauthorized > config.clients * 2 {
+            violations.push(format!(
+                "thread map leak: {} client threads and {} unauthorized threads for {} synthetic clients",
+                sample.threads, sample.unauthorized, config.clients
+            ));
+        }
+
+        // player count, room and level membership
+        violations.extend(server.check_invariants());
+
+        if sample.unclaimed > last_unclaimed {
+            unclaimed_growth += 1;
+        } else {
+            unclaimed_growth = 0;
+        }
+
+        last_unclaimed = sample.unclaimed;
+
+        if unclaimed_growth >= UNCLAIMED_GROWTH_REPORTS {
+            violations.push(format!(
+                "unclaimed threads keep growing ({} after {} consecutive reports)",
+                sample.unclaimed, unclaimed_growth
+            ));
+        }
+
+        if let Some(memory) = sample.resident_memory {
+            match memory_baseline {
+                None if started.elapsed() >= WARMUP_TIME => memory_baseline = Some(memory),
+                Some(baseline) if memory as f64 > baseline as f64 * MAX_MEMORY_GROWTH => {
+                    violations.push(format!("memory usage grew from {} KiB to {} KiB", baseline / 1024, memory / 1024));
+                }
+                _ => {}
+            }
+        }
+
+        total_violations += violations.len();
+
+        info!("Soak test report ({} minutes in)", started.elapsed().as_secs() / 60);
+        info!(
+            "Sessions: {} ({} failed), player data packets sent: {}",
+            counters.sessions.load(Ordering::Relaxed),
+            counters.failed_sessions.load(Ordering::Relaxed),
+            counters.packets_sent.load(Ordering::Relaxed)
+        );
+        info!(
+            "Threads: {} (unauthorized: {}, unclaimed: {}), player count: {}, authenticated: {}",
+            sample.threads, sample.unauthorized, sample.unclaimed, sample.player_count, sample.authenticated
+        );
+        info!("Rooms: {}, people in the global room: {}", sample.rooms, sample.global_room_players);
+
+        if let Some(memory) = sample.resident_memory {
+            info!(
+                "Resident memory: {} KiB (baseline: {})",
+                memory / 1024,
+                memory_baseline.map_or_else(|| "not yet taken".to_owned(), |x| format!("{} KiB", x / 1024))
+            );
+        }
+
+        for violation in &violations {
+            error!("Invariant violated: {violation}");
+        }
+
+        info!("Invariant violations so far: {total_violations}");
+        info!("-------------------------------------------");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::server::{test_pending_client, test_server};
+
+    /// more than the default limits of pending and open connections per address
+    const CLIENTS: usize = 20;
+
+    #[tokio::test]
+    async fn soak_clients_are_not_limited() {
+        let server = test_server().await;
+        let peer = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 4202);
+
+        let mut pending = Vec::new();
+        for _ in 0..CLIENTS {
+            pending.push(test_pending_client(server).await);
+        }
+
+        assert!(server.admit_connection(peer).is_none());
+        assert_eq!(server.pending_rejections.load(Ordering::Relaxed), 1);
+
+        server.state.rate_limiter.exempt_loopback();
+
+        let slots: Vec<_> = (0..CLIENTS).map(|_| server.admit_connection(peer)).collect();
+        assert!(slots.iter().all(Option::is_some));
+        assert_eq!(server.pending_rejections.load(Ordering::Relaxed), 1);
+        assert_eq!(server.rate_limit_rejections.load(Ordering::Relaxed), 0);
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use ipnet::{Ipv4Net, Ipv6Net};
+
+/// Turns IPv4 addresses mapped into IPv6 (which is how IPv4 clients show up on a dual-stack socket) back into plain IPv4 ones,
+/// so that a client has the same address over TCP and UDP no matter how the sockets were bound.
+pub fn canonical_addr(addr: SocketAddr) -> SocketAddr {
+    SocketAddr::new(addr.ip().to_canonical(), addr.port())
+}
+
+/// Returns the key used for limits per IP address. IPv6 users usually get a whole /64 network,
+/// so all addresses in it count as the same one, otherwise the limits could be dodged by switching addresses.
+pub fn address_key(address: IpAddr) -> u64 {
+    match address.to_canonical() {
+        IpAddr::V4(x) => u64::from(u32::from(x)),
+        // the upper half of routable IPv6 addresses is never this small, so it can't clash with an IPv4 address
+        IpAddr::V6(x) => (u128::from(x) >> 64) as u64,
+    }
+}
+
+/// Returns the network of the address (the /24 of an IPv4 address, or the /48 of an IPv6 one) in CIDR notation.
+/// Shown to players in their session list, so they can tell their devices apart without their full address being stored anywhere.
+pub fn address_prefix(address: IpAddr) -> String {
+    match address.to_canonical() {
+        IpAddr::V4(x) => Ipv4Net::new(x, 24).map(|net| net.trunc().to_string()).unwrap_or_default(),
+        IpAddr::V6(x) => Ipv6Net::new(x, 48).map(|net| net.trunc().to_string()).unwrap_or_default(),
+    }
+}
+
+/// Returns a new made up address, used in place of the UDP address of a client that is connected over a tunnel (such as WebSocket or QUIC).
+/// The addresses come from the IPv6 discard-only prefix (`100::/64`), so they never clash with the address of a real UDP peer.
+pub fn tunnel_addr() -> SocketAddr {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    SocketAddr::new(IpAddr::V6(Ipv6Addr::from((0x0100u128 << 112) | u128::from(id))), 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, net::Ipv4Addr};
+
+    use super::*;
+
+    #[test]
+    fn canonical_addr_unmaps_ipv4() {
+        let v4 = Ipv4Addr::new(203, 0, 113, 7);
+        let mapped = SocketAddr::new(IpAddr::V6(v4.to_ipv6_mapped()), 4201);
+
+        assert_eq!(canonical_addr(mapped), SocketAddr::new(IpAddr::V4(v4), 4201));
+        assert_eq!(
+            canonical_addr(SocketAddr::new(IpAddr::V4(v4), 4201)),
+            SocketAddr::new(IpAddr::V4(v4), 4201)
+        );
+
+        let v6 = SocketAddr::new(IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)), 4201);
+        assert_eq!(canonical_addr(v6), v6);
+    }
+
+    #[test]
+    fn address_key_groups_ipv6_by_64() {
+        let a = Ipv6Addr::new(0x2001, 0xdb8, 1, 2, 0, 0, 0, 1);
+        let b = Ipv6Addr::new(0x2001, 0xdb8, 1, 2, 0xffff, 0xffff, 0xffff, 0xffff);
+        let other = Ipv6Addr::new(0x2001, 0xdb8, 1, 3, 0, 0, 0, 1);
+
+        assert_eq!(address_key(IpAddr::V6(a)), address_key(IpAddr::V6(b)));
+        assert_ne!(address_key(IpAddr::V6(a)), address_key(IpAddr::V6(other)));
+    }
+
+    #[test]
+    fn address_key_distinguishes_ipv4() {
+        let a = Ipv4Addr::new(203, 0, 113, 7);
+        let b = Ipv4Addr::new(203, 0, 113, 8);
+
+        assert_ne!(address_key(IpAddr::V4(a)), address_key(IpAddr::V4(b)));
+        assert_eq!(address_key(IpAddr::V4(a)), address_key(IpAddr::V6(a.to_ipv6_mapped())));
+        assert_ne!(
+            address_key(IpAddr::V4(a)),
+            address_key(IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)))
+        );
+    }
+
+    #[test]
+    fn tunnel_addrs_are_unique() {
+        let addrs = (0..1000).map(|_| tunnel_addr()).collect::<HashSet<_>>();
+        assert_eq!(addrs.len(), 1000);
+
+        for addr in addrs {
+            let IpAddr::V6(ip) = addr.ip() else {
+                panic!("tunnel address {addr} is not IPv6");
+            };
+
+            // never an IPv4 peer on a dual-stack socket, and never the same /64 as a routable address
+            assert_eq!(ip.to_ipv4_mapped(), None);
+            assert_eq!(ip.segments()[..4], [0x0100, 0, 0, 0]);
+        }
+    }
+}
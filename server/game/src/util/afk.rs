@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+use crate::data::types::Point;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AfkEvent {
+    /// The player has been inactive for longer than the timeout.
+    WentAfk,
+    /// The player stayed AFK for the whole grace period after being warned.
+    RoomGraceOver,
+}
+
+/// Keeps track of when a player last did anything, to tell when they went AFK.
+/// Not thread safe on its own.
+pub struct AfkTracker {
+    last_activity: Instant,
+    afk_since: Option<Instant>,
+    grace_over: bool,
+    last_position: Option<Point>,
+}
+
+impl Default for AfkTracker {
+    fn default() -> Self {
+        Self {
+            last_activity: Instant::now(),
+            afk_since: None,
+            grace_over: false,
+            last_position: None,
+        }
+    }
+}
+
+impl AfkTracker {
+    /// Records any activity of the player. Returns `true` if the player was AFK until now.
+    pub fn record_activity(&mut self) -> bool {
+        self.last_activity = Instant::now();
+        self.grace_over = false;
+        self.afk_since.take().is_some()
+    }
+
+    /// Records the position of the player, which only counts as activity if they actually moved.
+    /// Returns `true` if the player was AFK until now.
+    pub fn record_position(&mut self, position: Point) -> bool {
+        if self.last_position.replace(position) == Some(position) {
+            return false;
+        }
+
+        self.record_activity()
+    }
+
+    /// Returns an event if the AFK state of the player should change, each event is only returned once.
+    /// A zero `timeout` disables AFK detection, a zero `room_grace` means `RoomGraceOver` never happens.
+    pub fn poll(&mut self, timeout: Duration, room_grace: Duration) -> Option<AfkEvent> {
+        if timeout.is_zero() {
+            return None;
+        }
+
+        let now = Instant::now();
+
+        match self.afk_since {
+            None if now - self.last_activity >= timeout => {
+                self.afk_since = Some(now);
+                Some(AfkEvent::WentAfk)
+            }
+            Some(since) if !self.grace_over && !room_grace.is_zero() && now - since >= room_grace => {
+                self.grace_over = true;
+                Some(AfkEvent::RoomGraceOver)
+            }
+            _ => None,
+        }
+    }
+}
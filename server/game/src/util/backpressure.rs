@@ -0,0 +1,49 @@
+use std::time::{Duration, Instant};
+
+/// How long a client has to be saturated without a break before it's asked to send less.
+const SATURATION_PERIOD: Duration = Duration::from_secs(2);
+/// How long a throttled client has to stay below saturation before it's told it can go back to normal.
+/// Longer than `SATURATION_PERIOD`, so that a client on the edge doesn't keep flipping back and forth.
+const RECOVERY_PERIOD: Duration = Duration::from_secs(10);
+
+/// Tracks whether the packets of a client are persistently piling up faster than we handle them,
+/// so that it can be asked to slow down before we have to start dropping its packets.
+/// Not thread safe on its own.
+#[derive(Default)]
+pub struct BackpressureTracker {
+    saturated_since: Option<Instant>,
+    calm_since: Option<Instant>,
+    throttled: bool,
+    pending_change: Option<bool>,
+}
+
+impl BackpressureTracker {
+    /// Records whether the client is saturated right now.
+    pub fn record(&mut self, saturated: bool) {
+        let now = Instant::now();
+
+        if saturated {
+            self.calm_since = None;
+            let since = *self.saturated_since.get_or_insert(now);
+
+            if !self.throttled && now - since >= SATURATION_PERIOD {
+                self.throttled = true;
+                self.pending_change = Some(true);
+            }
+        } else {
+            self.saturated_since = None;
+            let since = *self.calm_since.get_or_insert(now);
+
+            if self.throttled && now - since >= RECOVERY_PERIOD {
+                self.throttled = false;
+                self.pending_change = Some(false);
+            }
+        }
+    }
+
+    /// Returns `Some(true)` if the client should now be throttled, `Some(false)` if it no longer should be,
+    /// or `None` if nothing changed since the last call.
+    pub fn take_change(&mut self) -> Option<bool> {
+        self.pending_change.take()
+    }
+}
@@ -0,0 +1,571 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Sender},
+        OnceLock,
+    },
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use globed_shared::{rand::random, warn, PROTOCOL_VERSION};
+
+use crate::data::*;
+
+/*
+* Capture file format (all integers are big endian):
+*
+* header: magic (8 bytes), format version (u16), protocol version (u16)
+* then repeated until EOF:
+*   time since the start of the session in microseconds (u64), packet id (u16), encrypted flag (u8), payload length (u32), payload
+*
+* The payload is the packet data right after the header, after decryption, with free-form text scrubbed.
+*/
+
+const CAPTURE_MAGIC: &[u8; 8] = b"GLBDCAP\0";
+const CAPTURE_FORMAT_VERSION: u16 = 1;
+/// Size of the header of every packet in a capture file.
+const RECORD_HEADER_SIZE: u64 = 8 + 2 + 1 + 4;
+/// Default for `GLOBED_GS_CAPTURE_MAX_SIZE`, in megabytes.
+const DEFAULT_CAPTURE_MAX_SIZE: u64 = 64;
+/// How many bytes can wait for the writer thread across all captures, packets that don't fit are left out.
+const MAX_QUEUED_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Bytes of packets sent to the writer thread that it didn't write yet.
+static QUEUED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the directory where packet captures should be saved, if capturing is enabled via `GLOBED_GS_CAPTURE_DIR`.
+pub fn capture_dir() -> Option<&'static Path> {
+    static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+    DIR.get_or_init(|| std::env::var("GLOBED_GS_CAPTURE_DIR").ok().filter(|x| !x.is_empty()).map(PathBuf::from))
+        .as_deref()
+}
+
+/// Returns the maximum size of a single capture file in bytes, set via `GLOBED_GS_CAPTURE_MAX_SIZE` (in megabytes), 0 if unlimited.
+fn capture_max_size() -> u64 {
+    static SIZE: OnceLock<u64> = OnceLock::new();
+
+    *SIZE.get_or_init(|| {
+        let megabytes = std::env::var("GLOBED_GS_CAPTURE_MAX_SIZE")
+            .ok()
+            .and_then(|x| x.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_CAPTURE_MAX_SIZE);
+
+        megabytes.saturating_mul(1024 * 1024)
+    })
+}
+
+enum CaptureWrite {
+    Open(u64, PathBuf),
+    Write(u64, Vec<u8>),
+    Close(u64),
+}
+
+/// Returns the sender for the thread that does all the file operations for captures, so that they never block the async runtime.
+fn capture_writer() -> &'static Sender<CaptureWrite> {
+    static WRITER: OnceLock<Sender<CaptureWrite>> = OnceLock::new();
+
+    WRITER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::Builder::new()
+            .name("capture-writer".to_owned())
+            .spawn(move || run_capture_writer(&rx))
+            .expect("failed to spawn the packet capture writer thread");
+
+        tx
+    })
+}
+
+fn run_capture_writer(rx: &mpsc::Receiver<CaptureWrite>) {
+    let mut files = HashMap::<u64, BufWriter<File>>::new();
+
+    while let Ok(message) = rx.recv() {
+        match message {
+            CaptureWrite::Open(id, path) => match open_capture_file(&path) {
+                Ok(writer) => {
+                    files.insert(id, writer);
+                }
+                Err(e) => warn!("failed to create packet capture file at {path:?}: {e}"),
+            },
+
+            CaptureWrite::Write(id, data) => {
+                QUEUED_BYTES.fetch_sub(data.len() as u64, Ordering::Relaxed);
+
+                let Some(writer) = files.get_mut(&id) else {
+                    continue;
+                };
+
+                if let Err(e) = writer.write_all(&data) {
+                    warn!("failed to write to a packet capture: {e}");
+                    files.remove(&id);
+                }
+            }
+
+            CaptureWrite::Close(id) => {
+                if let Some(mut writer) = files.remove(&id) {
+                    let _ = writer.flush();
+                }
+            }
+        }
+    }
+}
+
+fn open_capture_file(path: &Path) -> std::io::Result<BufWriter<File>> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(CAPTURE_MAGIC)?;
+    writer.write_all(&CAPTURE_FORMAT_VERSION.to_be_bytes())?;
+    writer.write_all(&PROTOCOL_VERSION.to_be_bytes())?;
+
+    Ok(writer)
+}
+
+/// Records inbound packets of a single session into a capture file.
+/// The file itself is written to on a separate thread, if it falls behind then packets are left out.
+pub struct PacketCapture {
+    id: u64,
+    started: Instant,
+    /// bytes queued for writing so far, not counting the file header
+    written: u64,
+    max_size: u64,
+    truncated: bool,
+}
+
+impl PacketCapture {
+    /// Creates a new capture file in the capture directory, returns `None` if capturing is disabled.
+    /// The file is named after a random session id, so that the account id of the user is not leaked.
+    pub fn for_new_session() -> Option<Self> {
+        let dir = capture_dir()?;
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_secs()).unwrap_or(0);
+        let path = dir.join(format!("{timestamp}-{:08x}.gcap", random::<u32>()));
+
+        Some(Self::create(path, capture_max_size()))
+    }
+
+    /// Creates a capture file at the given path, that stops recording once `max_size` bytes of packets were written to it (0 for no limit).
+    pub fn create(path: PathBuf, max_size: u64) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+        if capture_writer().send(CaptureWrite::Open(id, path)).is_err() {
+            warn!("the packet capture writer thread is gone");
+        }
+
+        Self {
+            id,
+            started: Instant::now(),
+            written: 0,
+            max_size,
+            truncated: false,
+        }
+    }
+
+    /// Record a packet. `payload` must be the decrypted packet data, without the header.
+    pub fn record(&mut self, header: &PacketHeader, payload: &[u8]) {
+        if self.truncated {
+            return;
+        }
+
+        let size = RECORD_HEADER_SIZE + payload.len() as u64;
+        if self.max_size != 0 && self.written + size > self.max_size {
+            warn!(
+                "a packet capture reached the size limit of {} bytes, no more packets will be recorded in it",
+                self.max_size
+            );
+            self.truncated = true;
+            return;
+        }
+
+        let elapsed = self.started.elapsed().as_micros() as u64;
+
+        let mut payload = payload.to_vec();
+        scrub_packet(header.packet_id, &mut payload);
+
+        let mut data = Vec::with_capacity(size as usize);
+        data.extend_from_slice(&elapsed.to_be_bytes());
+        data.extend_from_slice(&header.packet_id.to_be_bytes());
+        data.push(u8::from(header.encrypted));
+        data.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        data.extend_from_slice(&payload);
+
+        // every packet is written as a whole, so leaving one out doesn't break the file
+        if QUEUED_BYTES.fetch_add(size, Ordering::Relaxed) + size > MAX_QUEUED_BYTES {
+            QUEUED_BYTES.fetch_sub(size, Ordering::Relaxed);
+            return;
+        }
+
+        if capture_writer().send(CaptureWrite::Write(self.id, data)).is_ok() {
+            self.written += size;
+        } else {
+            QUEUED_BYTES.fetch_sub(size, Ordering::Relaxed);
+            self.truncated = true;
+        }
+    }
+}
+
+impl Drop for PacketCapture {
+    fn drop(&mut self) {
+        let _ = capture_writer().send(CaptureWrite::Close(self.id));
+    }
+}
+
+/// A field that can be blanked, by overwriting every byte of its text while keeping the length.
+trait Blank {
+    fn blank(&mut self);
+}
+
+impl Blank for FastString {
+    fn blank(&mut self) {
+        *self = FastString::from_buffer(&vec![b'*'; self.len()]);
+    }
+}
+
+impl<const N: usize> Blank for InlineString<N> {
+    fn blank(&mut self) {
+        *self = InlineString::from_slice(&vec![b'*'; self.len()]);
+    }
+}
+
+impl Blank for String {
+    fn blank(&mut self) {
+        *self = "*".repeat(self.len());
+    }
+}
+
+impl<T: Blank> Blank for Option<T> {
+    fn blank(&mut self) {
+        if let Some(value) = self {
+            value.blank();
+        }
+    }
+}
+
+/// Decodes the payload as `P`, lets `scrub` blank its fields and encodes it back in place.
+/// Blanking keeps the length of every field, so the packet still fits exactly. Payloads that don't decode are zeroed,
+/// as there's no telling where their text is.
+fn scrub_decoded<P: Encodable + Decodable>(payload: &mut [u8], scrub: impl FnOnce(&mut P)) {
+    let Ok(mut packet) = ByteReader::from_bytes(payload).read_value::<P>() else {
+        payload.fill(0);
+        return;
+    };
+
+    scrub(&mut packet);
+
+    let mut buf = ByteBuffer::with_capacity(payload.len());
+    buf.write_value(&packet);
+
+    // anything after the packet is zeroed as well
+    let encoded = buf.as_bytes();
+    let len = encoded.len().min(payload.len());
+    payload[..len].copy_from_slice(&encoded[..len]);
+    payload[len..].fill(0);
+}
+
+/// Removes personally identifiable or sensitive data (free-form text and credentials) from a packet payload.
+/// Lengths are preserved, so that the scrubbed packet still decodes the same way as the original.
+/// Returns `false` if the packet has no scrub rule and was left as is.
+pub fn scrub_packet(packet_id: u16, payload: &mut [u8]) -> bool {
+    match packet_id {
+        ChatMessagePacket::PACKET_ID => scrub_decoded(payload, |p: &mut ChatMessagePacket| p.message.blank()),
+        ClaimTransferPacket::PACKET_ID => scrub_decoded(payload, |p: &mut ClaimTransferPacket| p.token.blank()),
+        SetRoomWebhookPacket::PACKET_ID => scrub_decoded(payload, |p: &mut SetRoomWebhookPacket| p.url.blank()),
+        AdminSendNoticePacket::PACKET_ID => scrub_decoded(payload, |p: &mut AdminSendNoticePacket| {
+            p.player.blank();
+            p.message.blank();
+        }),
+        AdminScheduleNoticePacket::PACKET_ID => scrub_decoded(payload, |p: &mut AdminScheduleNoticePacket| p.message.blank()),
+        AdminSpawnBotPacket::PACKET_ID => scrub_decoded(payload, |p: &mut AdminSpawnBotPacket| p.name.blank()),
+        AdminBotChatPacket::PACKET_ID => scrub_decoded(payload, |p: &mut AdminBotChatPacket| p.message.blank()),
+        ReportPlayerPacket::PACKET_ID => scrub_decoded(payload, |p: &mut ReportPlayerPacket| p.reason.blank()),
+        // the name and the platform are shown to everyone anyway
+        LoginPacket::PACKET_ID => scrub_decoded(payload, |p: &mut LoginPacket| {
+            p.token.blank();
+            p.invite_code.blank();
+        }),
+        CreateRoomPacket::PACKET_ID => scrub_decoded(payload, |p: &mut CreateRoomPacket| p.password.blank()),
+        JoinRoomPacket::PACKET_ID => scrub_decoded(payload, |p: &mut JoinRoomPacket| p.password.blank()),
+        AdminAuthPacket::PACKET_ID => scrub_decoded(payload, |p: &mut AdminAuthPacket| {
+            p.key.blank();
+            p.totp_code.blank();
+        }),
+        AdminDisconnectPacket::PACKET_ID => scrub_decoded(payload, |p: &mut AdminDisconnectPacket| {
+            p.player.blank();
+            p.message.blank();
+        }),
+        AdminGetUserStatePacket::PACKET_ID => scrub_decoded(payload, |p: &mut AdminGetUserStatePacket| p.player.blank()),
+        AdminUpdateUserPacket::PACKET_ID => scrub_decoded(payload, |p: &mut AdminUpdateUserPacket| {
+            let user = &mut p.user_entry;
+            user.admin_password.blank();
+            user.totp_secret.blank();
+            user.violation_reason.blank();
+        }),
+        _ => return false,
+    }
+
+    true
+}
+
+pub struct CapturedPacket {
+    /// time since the start of the session, in microseconds
+    pub timestamp: u64,
+    pub packet_id: u16,
+    pub encrypted: bool,
+    pub payload: Vec<u8>,
+}
+
+/// Reads packets from a capture file created by `PacketCapture`.
+pub struct CaptureReader {
+    reader: BufReader<File>,
+    pub protocol: u16,
+}
+
+impl CaptureReader {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+
+        if &magic != CAPTURE_MAGIC {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "not a globed packet capture file"));
+        }
+
+        let mut buf = [0u8; 2];
+        reader.read_exact(&mut buf)?;
+        let version = u16::from_be_bytes(buf);
+
+        if version != CAPTURE_FORMAT_VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unsupported capture format version: {version}"),
+            ));
+        }
+
+        reader.read_exact(&mut buf)?;
+        let protocol = u16::from_be_bytes(buf);
+
+        Ok(Self { reader, protocol })
+    }
+
+    /// Reads the next packet, returns `Ok(None)` when the end of the file is reached.
+    pub fn next_packet(&mut self) -> std::io::Result<Option<CapturedPacket>> {
+        let mut timestamp = [0u8; 8];
+
+        match self.reader.read_exact(&mut timestamp) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        let mut meta = [0u8; 7];
+        self.reader.read_exact(&mut meta)?;
+
+        let packet_id = u16::from_be_bytes([meta[0], meta[1]]);
+        let encrypted = meta[2] != 0;
+        let len = u32::from_be_bytes([meta[3], meta[4], meta[5], meta[6]]) as usize;
+
+        let mut payload = vec![0u8; len];
+        self.reader.read_exact(&mut payload)?;
+
+        Ok(Some(CapturedPacket {
+            timestamp: u64::from_be_bytes(timestamp),
+            packet_id,
+            encrypted,
+            payload,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// client packets with text that everyone can see anyway, so they are captured as is
+    const PUBLIC_TEXT_PACKETS: &[u16] = &[UpdatePreferencesPacket::PACKET_ID, SetRoomTagsPacket::PACKET_ID];
+
+    const PROTOCOL_SOURCES: &[&str] = &[
+        include_str!("../../../protocol/src/packets/client/admin.rs"),
+        include_str!("../../../protocol/src/packets/client/connection.rs"),
+        include_str!("../../../protocol/src/packets/client/game.rs"),
+        include_str!("../../../protocol/src/packets/client/general.rs"),
+        include_str!("../../../protocol/src/packets/client/room.rs"),
+        include_str!("../../../protocol/src/types/audio_frame.rs"),
+        include_str!("../../../protocol/src/types/cocos.rs"),
+        include_str!("../../../protocol/src/types/crypto.rs"),
+        include_str!("../../../protocol/src/types/game.rs"),
+        include_str!("../../../protocol/src/types/gd.rs"),
+        include_str!("../../../protocol/src/types/misc.rs"),
+        include_str!("../../../protocol/src/types/role.rs"),
+        include_str!("../../../protocol/src/types/room.rs"),
+        include_str!("../../../protocol/src/types/user.rs"),
+    ];
+
+    struct ParsedStruct {
+        name: String,
+        packet_id: Option<u16>,
+        body: String,
+    }
+
+    fn parse_structs(source: &str) -> Vec<ParsedStruct> {
+        let mut structs = Vec::new();
+        let mut packet_id = None;
+        let mut lines = source.lines();
+
+        while let Some(line) = lines.next() {
+            let line = line.trim();
+
+            if let Some(rest) = line.strip_prefix("#[packet(id = ") {
+                packet_id = rest.split(|c: char| !c.is_ascii_digit()).next().and_then(|x| x.parse().ok());
+            } else if let Some(rest) = line.strip_prefix("pub struct ") {
+                let name = rest.split(|c: char| !(c.is_alphanumeric() || c == '_')).next().unwrap_or_default();
+
+                let mut body = String::new();
+                if rest.ends_with('{') {
+                    for line in lines.by_ref().take_while(|line| *line != "}") {
+                        body.push_str(line);
+                        body.push('\n');
+                    }
+                }
+
+                structs.push(ParsedStruct {
+                    name: name.to_owned(),
+                    packet_id: packet_id.take(),
+                    body,
+                });
+            }
+        }
+
+        structs
+    }
+
+    /// ids of all client packets that contain text, directly or in one of their fields
+    fn text_packet_ids() -> Vec<u16> {
+        let structs = PROTOCOL_SOURCES.iter().flat_map(|source| parse_structs(source)).collect::<Vec<_>>();
+
+        let mut with_text = HashSet::new();
+        loop {
+            let before = with_text.len();
+
+            for parsed in &structs {
+                let has_text = parsed
+                    .body
+                    .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .any(|word| word.ends_with("String") || with_text.contains(word));
+
+                if has_text {
+                    with_text.insert(parsed.name.as_str());
+                }
+            }
+
+            if with_text.len() == before {
+                break;
+            }
+        }
+
+        structs
+            .iter()
+            .filter(|parsed| with_text.contains(parsed.name.as_str()))
+            .filter_map(|parsed| parsed.packet_id)
+            .collect()
+    }
+
+    #[test]
+    fn every_text_packet_has_a_scrub_rule() {
+        let ids = text_packet_ids();
+        assert!(ids.contains(&LoginPacket::PACKET_ID) && ids.contains(&AdminUpdateUserPacket::PACKET_ID));
+
+        for id in ids {
+            if PUBLIC_TEXT_PACKETS.contains(&id) {
+                continue;
+            }
+
+            assert!(scrub_packet(id, &mut []), "client packet {id} contains text but has no scrub rule");
+        }
+    }
+
+    #[test]
+    fn scrubs_login_credentials() {
+        let packet = LoginPacket {
+            account_id: 1,
+            user_id: 2,
+            name: InlineString::new("player"),
+            token: FastString::new("secret-token"),
+            icons: PlayerIconData::default(),
+            fragmentation_limit: 1300,
+            platform: InlineString::new("windows"),
+            is_invisible: false,
+            invite_code: InlineString::new("invite"),
+        };
+
+        let mut buf = ByteBuffer::new();
+        buf.write_value(&packet);
+        let mut payload = buf.into_vec();
+        let len = payload.len();
+
+        assert!(scrub_packet(LoginPacket::PACKET_ID, &mut payload));
+        assert_eq!(payload.len(), len);
+
+        let scrubbed = ByteReader::from_bytes(&payload).read_value::<LoginPacket>().unwrap();
+        assert_eq!(scrubbed.name.try_to_str(), "player");
+        assert_eq!(scrubbed.platform.try_to_str(), "windows");
+        assert_eq!(scrubbed.token.try_to_str(), "************");
+        assert_eq!(scrubbed.invite_code.try_to_str(), "******");
+    }
+
+    #[test]
+    fn stops_recording_at_the_size_limit() {
+        let path = std::env::temp_dir().join(format!("globed-capture-test-{:08x}.gcap", random::<u32>()));
+        let header = PacketHeader {
+            packet_id: 12345,
+            encrypted: false,
+            compressed: false,
+        };
+
+        // room for 3 packets with a 10 byte payload, but not for a 4th one
+        let mut capture = PacketCapture::create(path.clone(), 4 * (RECORD_HEADER_SIZE + 10) - 1);
+        for _ in 0..5 {
+            capture.record(&header, &[7; 10]);
+        }
+        drop(capture);
+
+        // the file is written on another thread
+        let deadline = Instant::now() + std::time::Duration::from_secs(5);
+        let packets = loop {
+            let packets = CaptureReader::open(&path).ok().map(|mut reader| {
+                let mut packets = Vec::new();
+                while let Ok(Some(packet)) = reader.next_packet() {
+                    packets.push(packet);
+                }
+
+                packets
+            });
+
+            match packets {
+                Some(packets) if packets.len() == 3 => break packets,
+                _ if Instant::now() > deadline => panic!("capture file wasn't written in time"),
+                _ => std::thread::sleep(std::time::Duration::from_millis(10)),
+            }
+        };
+
+        let _ = std::fs::remove_file(&path);
+        assert!(packets.iter().all(|packet| packet.packet_id == 12345 && packet.payload == [7; 10]));
+    }
+
+    #[test]
+    fn zeroes_malformed_payloads() {
+        let mut payload = vec![0xff; 5];
+        assert!(scrub_packet(JoinRoomPacket::PACKET_ID, &mut payload));
+        assert_eq!(payload, [0; 5]);
+    }
+}
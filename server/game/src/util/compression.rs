@@ -0,0 +1,129 @@
+use std::sync::OnceLock;
+
+use globed_shared::warn;
+
+use crate::data::{CompressionAlgorithm, CompressionSupport};
+
+const DEFAULT_THRESHOLD: usize = 1024;
+/// Packets bigger than this are sent uncompressed, as the client refuses to decompress them (`MAX_DECOMPRESSED_SIZE` in `game_socket.cpp`).
+const MAX_UNCOMPRESSED_SIZE: usize = 1 << 19;
+
+/// Compression settings of this server, read from environment variables.
+pub struct CompressionConfig {
+    /// algorithm used for clients that support it, `None` disables compression
+    pub preferred: CompressionAlgorithm,
+    /// packets whose payload is smaller than this many bytes are never compressed
+    pub threshold: usize,
+}
+
+impl CompressionConfig {
+    pub fn from_env() -> Self {
+        let preferred = match std::env::var("GLOBED_GS_COMPRESSION").ok().as_deref().map(str::trim) {
+            None | Some("lz4") => CompressionAlgorithm::Lz4,
+            Some("off" | "none") => CompressionAlgorithm::None,
+            Some(x) => {
+                warn!("invalid value for GLOBED_GS_COMPRESSION ({x}), must be one of: lz4, off. Using lz4");
+                CompressionAlgorithm::Lz4
+            }
+        };
+
+        let threshold = match std::env::var("GLOBED_GS_COMPRESSION_THRESHOLD").ok().map(|x| x.trim().parse::<usize>()) {
+            None => DEFAULT_THRESHOLD,
+            Some(Ok(x)) => x,
+            Some(Err(_)) => {
+                warn!("invalid value for GLOBED_GS_COMPRESSION_THRESHOLD, must be a number, using the default ({DEFAULT_THRESHOLD})");
+                DEFAULT_THRESHOLD
+            }
+        };
+
+        Self { preferred, threshold }
+    }
+
+    /// Returns the settings of this server, read from the environment on the first call.
+    pub fn get() -> &'static Self {
+        static CONFIG: OnceLock<CompressionConfig> = OnceLock::new();
+        CONFIG.get_or_init(Self::from_env)
+    }
+
+    /// Picks the algorithm for a client, the preferred one if the client supports it, otherwise none.
+    pub fn negotiate(&self, support: CompressionSupport) -> CompressionAlgorithm {
+        if support.supports(self.preferred) {
+            self.preferred
+        } else {
+            CompressionAlgorithm::None
+        }
+    }
+}
+
+/// Compresses the data, prefixed with its uncompressed size as a big endian u32 so the receiver knows how much to allocate.
+/// Returns `None` if the algorithm is `None` or the data is too big for the client to decompress.
+pub fn compress(algorithm: CompressionAlgorithm, data: &[u8]) -> Option<Vec<u8>> {
+    if data.len() > MAX_UNCOMPRESSED_SIZE {
+        return None;
+    }
+
+    let compressed = match algorithm {
+        CompressionAlgorithm::None => return None,
+        CompressionAlgorithm::Lz4 => lz4_flex::block::compress(data),
+    };
+
+    let mut out = Vec::with_capacity(4 + compressed.len());
+    out.extend_from_slice(&u32::try_from(data.len()).ok()?.to_be_bytes());
+    out.extend_from_slice(&compressed);
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(preferred: CompressionAlgorithm) -> CompressionConfig {
+        CompressionConfig {
+            preferred,
+            threshold: DEFAULT_THRESHOLD,
+        }
+    }
+
+    fn sample() -> Vec<u8> {
+        (0..4096u32).flat_map(|x| (x % 64).to_le_bytes()).collect()
+    }
+
+    fn split_size(data: &[u8]) -> (usize, &[u8]) {
+        let (size, rest) = data.split_at(4);
+        (u32::from_be_bytes(size.try_into().unwrap()) as usize, rest)
+    }
+
+    #[test]
+    fn lz4_round_trip() {
+        let data = sample();
+        let compressed = compress(CompressionAlgorithm::Lz4, &data).unwrap();
+        assert!(compressed.len() < data.len());
+
+        let (size, block) = split_size(&compressed);
+        assert_eq!(size, data.len());
+        assert_eq!(lz4_flex::block::decompress(block, size).unwrap(), data);
+    }
+
+    #[test]
+    fn none_does_not_compress() {
+        assert!(compress(CompressionAlgorithm::None, &sample()).is_none());
+    }
+
+    #[test]
+    fn does_not_compress_oversized() {
+        let data = vec![0u8; MAX_UNCOMPRESSED_SIZE + 1];
+        assert!(compress(CompressionAlgorithm::Lz4, &data).is_none());
+        assert!(compress(CompressionAlgorithm::Lz4, &data[..MAX_UNCOMPRESSED_SIZE]).is_some());
+    }
+
+    #[test]
+    fn negotiates_supported_algorithm() {
+        let lz4 = CompressionSupport { lz4: true };
+        let neither = CompressionSupport::default();
+
+        assert_eq!(config(CompressionAlgorithm::Lz4).negotiate(lz4), CompressionAlgorithm::Lz4);
+        assert_eq!(config(CompressionAlgorithm::Lz4).negotiate(neither), CompressionAlgorithm::None);
+        assert_eq!(config(CompressionAlgorithm::None).negotiate(lz4), CompressionAlgorithm::None);
+    }
+}
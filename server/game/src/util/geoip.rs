@@ -0,0 +1,20 @@
+use std::net::IpAddr;
+
+use globed_shared::geoip::{Coordinates, DistanceClass, GeoIpDatabase};
+
+/// Location of this server together with the database used to locate clients.
+pub struct ServerGeoIp {
+    database: GeoIpDatabase,
+    location: Coordinates,
+}
+
+impl ServerGeoIp {
+    pub fn new(database: GeoIpDatabase, location: Coordinates) -> Self {
+        Self { database, location }
+    }
+
+    /// Estimates how far away the client with the given address is from this server.
+    pub fn distance_class(&self, address: IpAddr) -> DistanceClass {
+        self.database.distance_class(&self.location, address)
+    }
+}
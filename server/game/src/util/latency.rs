@@ -0,0 +1,76 @@
+use std::time::{Duration, Instant};
+
+/// How often a client is probed for its round trip time.
+const PROBE_INTERVAL: Duration = Duration::from_secs(20);
+/// A probe that wasn't answered within this time is considered lost, and a new one can be sent.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Measures the round trip time of a client, by sending it probes that it has to echo back.
+/// Not thread safe on its own.
+pub struct LatencyEstimator {
+    next_id: u32,
+    /// ID of the probe that we are waiting for, and when it was sent
+    pending: Option<(u32, Instant)>,
+    last_probe: Option<Instant>,
+    /// smoothed round trip time
+    rtt: Option<Duration>,
+}
+
+impl LatencyEstimator {
+    pub const fn new() -> Self {
+        Self {
+            next_id: 0,
+            pending: None,
+            last_probe: None,
+            rtt: None,
+        }
+    }
+
+    pub fn should_probe(&self) -> bool {
+        match (self.pending, self.last_probe) {
+            (Some((_, sent_at)), _) => sent_at.elapsed() > PROBE_TIMEOUT,
+            (None, Some(last_probe)) => last_probe.elapsed() > PROBE_INTERVAL,
+            (None, None) => true,
+        }
+    }
+
+    /// Starts a new probe and returns its ID.
+    pub fn begin_probe(&mut self) -> u32 {
+        let now = Instant::now();
+
+        self.next_id = self.next_id.wrapping_add(1);
+        self.pending = Some((self.next_id, now));
+        self.last_probe = Some(now);
+
+        self.next_id
+    }
+
+    /// Returns the smoothed round trip time, or `None` if no probe was answered yet.
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt
+    }
+
+    /// Records the response to a probe. Returns the new smoothed round trip time, or `None` if this was not the probe we were waiting for.
+    pub fn record_response(&mut self, id: u32) -> Option<Duration> {
+        let (pending_id, sent_at) = self.pending?;
+        if pending_id != id {
+            return None;
+        }
+
+        self.pending = None;
+
+        let sample = sent_at.elapsed();
+
+        // same smoothing as TCP uses, so that a single slow probe doesn't change much
+        let rtt = self.rtt.map_or(sample, |rtt| (rtt * 7 + sample) / 8);
+        self.rtt = Some(rtt);
+
+        Some(rtt)
+    }
+}
+
+impl Default for LatencyEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
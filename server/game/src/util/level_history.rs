@@ -0,0 +1,48 @@
+use std::collections::VecDeque;
+
+use crate::data::{LevelId, LevelVisit};
+
+/// How many levels are remembered per player. When full, the oldest one is forgotten.
+const MAX_LEVEL_HISTORY: usize = 10;
+
+/// The last levels a player was on, with when they joined and left them, so moderators can see where they have been.
+/// Not thread safe on its own.
+#[derive(Default, Clone)]
+pub struct LevelHistory {
+    visits: VecDeque<LevelVisit>,
+}
+
+impl LevelHistory {
+    /// Records that the player moved to the given level, or left their level if `level_id` is 0.
+    pub fn record(&mut self, level_id: LevelId, now: i64) {
+        if let Some(last) = self.visits.back_mut() {
+            if last.left_at == 0 {
+                // rejoining the same level without leaving it doesn't count as a new visit
+                if last.level_id == level_id {
+                    return;
+                }
+
+                last.left_at = now;
+            }
+        }
+
+        if level_id == 0 {
+            return;
+        }
+
+        if self.visits.len() >= MAX_LEVEL_HISTORY {
+            self.visits.pop_front();
+        }
+
+        self.visits.push_back(LevelVisit {
+            level_id,
+            joined_at: now,
+            left_at: 0,
+        });
+    }
+
+    /// Returns the visits ordered from the oldest to the newest.
+    pub fn to_vec(&self) -> Vec<LevelVisit> {
+        self.visits.iter().copied().collect()
+    }
+}
@@ -0,0 +1,117 @@
+//! Detection of links in chat messages. Only whole words are considered, and to avoid catching
+//! things like `lol.ok` a word must either have a scheme, start with `www.`, have a path, or end in a commonly abused TLD.
+
+/// Top-level domains that are recognized even without a scheme or a path.
+const COMMON_TLDS: &[&str] = &[
+    "com", "net", "org", "gg", "io", "co", "me", "xyz", "ru", "ly", "tk", "to", "us", "uk", "de", "info", "site", "online", "link", "app", "dev",
+    "shop", "store", "club", "fun", "live", "pro", "biz", "top", "click",
+];
+
+/// Hosts used for Discord invites, these are links even with no path at all.
+const INVITE_HOSTS: &[&str] = &["discord.gg", "discord.com/invite", "discordapp.com/invite", "dsc.gg"];
+
+/// Replaces every link with this.
+const STRIPPED_LINK: &str = "[link removed]";
+
+fn is_valid_label(label: &str) -> bool {
+    !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// Returns `true` if the given word looks like a link.
+fn is_link(word: &str) -> bool {
+    let word = word
+        .trim_matches(|c: char| matches!(c, '(' | ')' | '<' | '>' | '"' | '\'' | ',' | '!' | '?'))
+        .to_ascii_lowercase();
+
+    if word.contains("://") || word.starts_with("www.") {
+        return true;
+    }
+
+    if INVITE_HOSTS.iter().any(|host| word.contains(host)) {
+        return true;
+    }
+
+    let (host, path) = word.split_once('/').map_or((word.as_str(), None), |(host, path)| (host, Some(path)));
+    let host = host.trim_end_matches('.');
+
+    let Some((name, tld)) = host.rsplit_once('.') else {
+        return false;
+    };
+
+    let valid_name = name.split('.').all(is_valid_label);
+    let valid_tld = tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic());
+
+    valid_name && valid_tld && (path.is_some() || COMMON_TLDS.contains(&tld))
+}
+
+/// Returns `true` if the message contains any links.
+pub fn contains_link(message: &str) -> bool {
+    message.split_whitespace().any(is_link)
+}
+
+/// Replaces every link in the message, keeping everything else (including the whitespace) as is.
+/// Words are split the same way as in [`contains_link`], so anything it finds gets removed here.
+pub fn strip_links(message: &str) -> String {
+    let mut out = String::with_capacity(message.len());
+
+    for part in message.split_inclusive(char::is_whitespace) {
+        let word = part.trim_end_matches(char::is_whitespace);
+
+        if is_link(word) {
+            out.push_str(STRIPPED_LINK);
+            out.push_str(&part[word.len()..]);
+        } else {
+            out.push_str(part);
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_links() {
+        for word in [
+            "https://example.com",
+            "http://localhost:8080",
+            "www.example",
+            "example.com",
+            "Example.COM",
+            "(example.com)",
+            "example.net.",
+            "sub.example.xyz",
+            "example.abc/path",
+            "discord.gg/abcdef",
+            "discord.com/invite/abcdef",
+            "dsc.gg/x",
+        ] {
+            assert!(is_link(word), "{word} should be a link");
+        }
+    }
+
+    #[test]
+    fn ignores_non_links() {
+        for word in ["", "hello", "lol.ok", "gg.wp", "1.5", "v2.2.0", "...", "a..com", "e-mail.", ".com"] {
+            assert!(!is_link(word), "{word} should not be a link");
+        }
+    }
+
+    #[test]
+    fn contains_link_checks_every_word() {
+        assert!(contains_link("come play at example.com tonight"));
+        assert!(contains_link("join   discord.gg/abc"));
+        assert!(!contains_link("gg wp, that was 1.5 seconds"));
+    }
+
+    #[test]
+    fn strip_links_keeps_other_words() {
+        assert_eq!(strip_links("join discord.gg/abc now"), format!("join {STRIPPED_LINK} now"));
+        assert_eq!(strip_links("no links here"), "no links here");
+        assert_eq!(strip_links("a  www.b.c"), format!("a  {STRIPPED_LINK}"));
+        assert_eq!(strip_links("a\twww.b.c\nexample.com"), format!("a\t{STRIPPED_LINK}\n{STRIPPED_LINK}"));
+        assert!(!contains_link(&strip_links("see\u{3000}example.com\r\ndiscord.gg/abc")));
+    }
+}
@@ -0,0 +1,88 @@
+/// How many packets we expect to receive before updating the estimate.
+const WINDOW_SIZE: u32 = 60;
+/// A jump in sequence numbers bigger than this means the client reset its counter (e.g. left and rejoined a level), not that it lost packets.
+const MAX_GAP: u32 = WINDOW_SIZE * 5;
+/// Once the estimated loss goes above this, the client is considered lossy.
+const LOSSY_THRESHOLD: f32 = 0.1;
+/// A lossy client has to go below this to stop being considered lossy,
+/// so that a connection hovering around the threshold doesn't keep flipping back and forth.
+const RECOVERED_THRESHOLD: f32 = 0.05;
+
+/// Estimates packet loss of a client from the sequence numbers of the player data packets it sends us.
+/// Only measures loss in one direction, but on a bad connection both directions are usually equally bad.
+/// Not thread safe on its own.
+pub struct LossEstimator {
+    last_sequence: Option<u32>,
+    received: u32,
+    expected: u32,
+    estimate: f32,
+    lossy: bool,
+}
+
+impl LossEstimator {
+    pub const fn new() -> Self {
+        Self {
+            last_sequence: None,
+            received: 0,
+            expected: 0,
+            estimate: 0.0,
+            lossy: false,
+        }
+    }
+
+    /// Records a received packet. Returns `Some` with the new state if the client just became lossy or stopped being lossy.
+    pub fn record(&mut self, sequence: u32) -> Option<bool> {
+        let Some(last_sequence) = self.last_sequence else {
+            self.last_sequence = Some(sequence);
+            return None;
+        };
+
+        // a late or duplicated packet, it was already counted as lost and arrives too late to be useful anyway
+        if sequence <= last_sequence && last_sequence - sequence < MAX_GAP {
+            return None;
+        }
+
+        self.last_sequence = Some(sequence);
+
+        let gap = sequence.wrapping_sub(last_sequence);
+        if gap > MAX_GAP {
+            return None;
+        }
+
+        self.received += 1;
+        self.expected += gap;
+
+        if self.expected < WINDOW_SIZE {
+            return None;
+        }
+
+        let loss = 1.0 - self.received as f32 / self.expected as f32;
+        self.estimate = (self.estimate + loss) / 2.0;
+        self.received = 0;
+        self.expected = 0;
+
+        let lossy = if self.lossy {
+            self.estimate > RECOVERED_THRESHOLD
+        } else {
+            self.estimate > LOSSY_THRESHOLD
+        };
+
+        if lossy == self.lossy {
+            None
+        } else {
+            self.lossy = lossy;
+            Some(lossy)
+        }
+    }
+
+    /// Estimated packet loss in percent.
+    pub fn estimate_percent(&self) -> u8 {
+        (self.estimate * 100.0).round() as u8
+    }
+}
+
+impl Default for LossEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
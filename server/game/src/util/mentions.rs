@@ -0,0 +1,34 @@
+//! Parsing of `@name` mentions in chat messages. Whether a mentioned name belongs to anyone is up to the caller.
+
+use globed_shared::MAX_NAME_SIZE;
+
+/// At most this many different players can be mentioned in a single message, the rest are ignored.
+pub const MAX_MENTIONS: usize = 5;
+
+/// Returns the names mentioned in the message, without the `@` and without duplicates (ignoring case).
+pub fn find_mentions(message: &str) -> Vec<&str> {
+    let mut names: Vec<&str> = Vec::new();
+
+    for word in message.split_whitespace() {
+        let Some(name) = word.strip_prefix('@') else {
+            continue;
+        };
+
+        // allow punctuation right after the name, like in "@name, hi"
+        let name = name.trim_end_matches(|c: char| !c.is_ascii_alphanumeric() && c != '_');
+
+        if name.is_empty() || name.len() > MAX_NAME_SIZE || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            continue;
+        }
+
+        if !names.iter().any(|x| x.eq_ignore_ascii_case(name)) {
+            names.push(name);
+
+            if names.len() == MAX_MENTIONS {
+                break;
+            }
+        }
+    }
+
+    names
+}
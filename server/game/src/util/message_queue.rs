@@ -0,0 +1,217 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+};
+
+use globed_shared::warn;
+
+const DEFAULT_QUEUE_CAPACITY: usize = 256;
+const MIN_QUEUE_CAPACITY: usize = 16;
+
+/// Returns how many messages can wait for a client thread before some of them get dropped,
+/// based on `GLOBED_GS_MESSAGE_QUEUE_SIZE` (by default `DEFAULT_QUEUE_CAPACITY`).
+pub fn message_queue_capacity() -> usize {
+    static CAPACITY: OnceLock<usize> = OnceLock::new();
+
+    *CAPACITY.get_or_init(|| match std::env::var("GLOBED_GS_MESSAGE_QUEUE_SIZE").ok().map(|x| x.parse::<usize>()) {
+        None => DEFAULT_QUEUE_CAPACITY,
+        Some(Ok(x)) if x >= MIN_QUEUE_CAPACITY => x,
+        Some(_) => {
+            warn!(
+                "invalid value for GLOBED_GS_MESSAGE_QUEUE_SIZE, must be at least {MIN_QUEUE_CAPACITY}, using the default ({DEFAULT_QUEUE_CAPACITY})"
+            );
+            DEFAULT_QUEUE_CAPACITY
+        }
+    })
+}
+
+/// What kind of message is waiting in a queue, which decides what happens to it once the queue is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageClass {
+    /// player data, voice and level snapshots, only the newest ones matter
+    State,
+    /// chat messages and everything related to them
+    Chat,
+    /// room info, invites, profile and presence changes
+    Info,
+    /// kicks, bans, transfers and similar, never dropped
+    Control,
+}
+
+impl MessageClass {
+    pub const COUNT: usize = 4;
+    pub const ALL: [Self; Self::COUNT] = [Self::State, Self::Chat, Self::Info, Self::Control];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::State => "state",
+            Self::Chat => "chat",
+            Self::Info => "info",
+            Self::Control => "control",
+        }
+    }
+}
+
+pub trait ClassifiedMessage {
+    fn class(&self) -> MessageClass;
+}
+
+/// A queue with a soft capacity. Once it's full, a new state message replaces the oldest queued state message,
+/// other droppable messages are turned away, and control messages are queued anyway. Not thread safe on its own.
+pub struct MessageQueue<T> {
+    queue: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T: ClassifiedMessage> MessageQueue<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            queue: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Queues the message, returns the class of the message that had to be dropped for it, if any.
+    pub fn push(&mut self, message: T) -> Option<MessageClass> {
+        let class = message.class();
+
+        if self.queue.len() < self.capacity || class == MessageClass::Control {
+            self.queue.push_back(message);
+            return None;
+        }
+
+        if class == MessageClass::State {
+            if let Some(idx) = self.queue.iter().position(|x| x.class() == MessageClass::State) {
+                self.queue.remove(idx);
+                self.queue.push_back(message);
+                return Some(class);
+            }
+        }
+
+        Some(class)
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.queue.pop_front()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+/// Amount of messages dropped from full queues, for every class.
+#[derive(Default)]
+pub struct MessageDropCounters {
+    counts: [AtomicU64; MessageClass::COUNT],
+}
+
+impl MessageDropCounters {
+    pub fn record(&self, class: MessageClass) {
+        self.counts[class as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> Vec<(&'static str, u64)> {
+        MessageClass::ALL
+            .iter()
+            .zip(&self.counts)
+            .map(|(class, count)| (class.name(), count.load(Ordering::Relaxed)))
+            .collect()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().map(|x| x.load(Ordering::Relaxed)).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl ClassifiedMessage for (MessageClass, u32) {
+        fn class(&self) -> MessageClass {
+            self.0
+        }
+    }
+
+    fn drain(queue: &mut MessageQueue<(MessageClass, u32)>) -> Vec<u32> {
+        std::iter::from_fn(|| queue.pop()).map(|(_, id)| id).collect()
+    }
+
+    #[test]
+    fn queues_in_order_below_capacity() {
+        let mut queue = MessageQueue::new(3);
+        assert!(queue.is_empty());
+
+        assert_eq!(queue.push((MessageClass::Chat, 1)), None);
+        assert_eq!(queue.push((MessageClass::State, 2)), None);
+        assert_eq!(queue.push((MessageClass::Info, 3)), None);
+        assert_eq!(queue.len(), 3);
+
+        assert_eq!(drain(&mut queue), [1, 2, 3]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn state_replaces_oldest_state() {
+        let mut queue = MessageQueue::new(3);
+        queue.push((MessageClass::Chat, 1));
+        queue.push((MessageClass::State, 2));
+        queue.push((MessageClass::State, 3));
+
+        assert_eq!(queue.push((MessageClass::State, 4)), Some(MessageClass::State));
+        assert_eq!(queue.len(), 3);
+        assert_eq!(drain(&mut queue), [1, 3, 4]);
+    }
+
+    #[test]
+    fn state_is_refused_without_queued_state() {
+        let mut queue = MessageQueue::new(2);
+        queue.push((MessageClass::Chat, 1));
+        queue.push((MessageClass::Info, 2));
+
+        assert_eq!(queue.push((MessageClass::State, 3)), Some(MessageClass::State));
+        assert_eq!(drain(&mut queue), [1, 2]);
+    }
+
+    #[test]
+    fn chat_and_info_are_refused_when_full() {
+        let mut queue = MessageQueue::new(2);
+        queue.push((MessageClass::State, 1));
+        queue.push((MessageClass::State, 2));
+
+        assert_eq!(queue.push((MessageClass::Chat, 3)), Some(MessageClass::Chat));
+        assert_eq!(queue.push((MessageClass::Info, 4)), Some(MessageClass::Info));
+        assert_eq!(drain(&mut queue), [1, 2]);
+    }
+
+    #[test]
+    fn control_is_always_queued() {
+        let mut queue = MessageQueue::new(2);
+        queue.push((MessageClass::State, 1));
+        queue.push((MessageClass::Chat, 2));
+
+        assert_eq!(queue.push((MessageClass::Control, 3)), None);
+        assert_eq!(queue.push((MessageClass::Control, 4)), None);
+        assert_eq!(queue.len(), 4);
+        assert_eq!(drain(&mut queue), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn drop_counters() {
+        let counters = MessageDropCounters::default();
+        counters.record(MessageClass::State);
+        counters.record(MessageClass::State);
+        counters.record(MessageClass::Info);
+
+        assert_eq!(counters.total(), 3);
+        assert_eq!(counters.get(), [("state", 2), ("chat", 0), ("info", 1), ("control", 0)]);
+    }
+}
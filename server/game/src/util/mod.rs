@@ -1,9 +1,41 @@
+pub mod address;
+pub mod afk;
+pub mod backpressure;
+pub mod capture;
 pub mod channel;
+pub mod compression;
+pub mod geoip;
+pub mod latency;
+pub mod level_history;
+pub mod links;
 pub mod lockfreemutcell;
+pub mod loss;
+pub mod mentions;
+pub mod message_queue;
+pub mod packet_budget;
 pub mod rate_limiter;
+pub mod reassembly;
+pub mod socket_options;
+pub mod state_check;
+pub mod voice_capture;
 pub mod word_filter;
 
+pub use address::{address_key, address_prefix, canonical_addr, tunnel_addr};
+pub use afk::{AfkEvent, AfkTracker};
+pub use backpressure::BackpressureTracker;
+pub use capture::{CaptureReader, CapturedPacket, PacketCapture};
 pub use channel::{SenderDropped, TokioChannel};
+pub use compression::CompressionConfig;
+pub use geoip::ServerGeoIp;
+pub use latency::LatencyEstimator;
+pub use level_history::LevelHistory;
 pub use lockfreemutcell::LockfreeMutCell;
+pub use loss::LossEstimator;
+pub use message_queue::{message_queue_capacity, ClassifiedMessage, MessageClass, MessageDropCounters, MessageQueue};
+pub use packet_budget::{BudgetLimits, BudgetVerdict, PacketBudget, PacketCategory};
 pub use rate_limiter::SimpleRateLimiter;
+pub use reassembly::FragmentReassembler;
+pub use socket_options::{bind_tcp, bind_udp, SocketOptions};
+pub use state_check::{StateChecker, StateViolation};
+pub use voice_capture::VoiceCapture;
 pub use word_filter::WordFilter;
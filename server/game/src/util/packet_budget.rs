@@ -0,0 +1,253 @@
+use std::time::{Duration, Instant};
+
+/// How long one counting window lasts.
+const WINDOW: Duration = Duration::from_secs(1);
+/// In how many windows the hard limit can be broken before the client gets disconnected.
+/// Windows that stay under the hard limit take one of these back, so a burst of queued packets after a lag spike is forgiven.
+const GRACE_WINDOWS: u8 = 3;
+
+/// What kind of packet the client sent, each kind has its own budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketCategory {
+    /// player data and metadata, sent many times per second
+    State,
+    /// text chat
+    Chat,
+    /// voice chat
+    Voice,
+    /// everything else, such as room and level management or pings
+    Control,
+    /// player data sent every frame while the player is being spectated
+    Spectate,
+}
+
+impl PacketCategory {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::State => "state",
+            Self::Chat => "chat",
+            Self::Voice => "voice",
+            Self::Control => "control",
+            Self::Spectate => "spectate",
+        }
+    }
+}
+
+/// What to do with a packet after counting it against the budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetVerdict {
+    /// the packet is within the budget
+    Allow,
+    /// the soft limit was exceeded, the packet should be dropped
+    Drop,
+    /// the hard limit was exceeded for too long, the client should be disconnected
+    Disconnect,
+}
+
+/// Packets per `WINDOW` of a category. Going over `soft` drops the excess,
+/// going over `hard` in more than `GRACE_WINDOWS` windows disconnects the client.
+#[derive(Debug, Clone, Copy)]
+pub struct BudgetLimits {
+    pub soft: u32,
+    pub hard: u32,
+}
+
+struct CategoryBudget {
+    limits: BudgetLimits,
+    window_start: Instant,
+    count: u32,
+    strikes: u8,
+    /// whether the hard limit was already broken in the current window
+    struck: bool,
+}
+
+impl CategoryBudget {
+    fn new(limits: BudgetLimits, now: Instant) -> Self {
+        Self {
+            limits,
+            window_start: now,
+            count: 0,
+            strikes: 0,
+            struck: false,
+        }
+    }
+
+    fn record(&mut self, now: Instant) -> BudgetVerdict {
+        let elapsed = now - self.window_start;
+        if elapsed >= WINDOW {
+            // every window that stayed under the hard limit takes a strike back, including the ones without any packets
+            let windows = elapsed.as_nanos() / WINDOW.as_nanos();
+            let quiet_windows = if self.struck { windows - 1 } else { windows };
+            self.strikes = self.strikes.saturating_sub(quiet_windows.min(u128::from(u8::MAX)) as u8);
+
+            self.window_start = now;
+            self.count = 0;
+            self.struck = false;
+        }
+
+        self.count = self.count.saturating_add(1);
+
+        if self.count > self.limits.hard && !self.struck {
+            self.struck = true;
+            self.strikes += 1;
+
+            if self.strikes >= GRACE_WINDOWS {
+                return BudgetVerdict::Disconnect;
+            }
+        }
+
+        if self.count > self.limits.soft {
+            BudgetVerdict::Drop
+        } else {
+            BudgetVerdict::Allow
+        }
+    }
+}
+
+/// Counts the packets a client sends, separately for every `PacketCategory`,
+/// so that flooding one kind of packet doesn't eat into the budget of the others.
+/// Not thread safe on its own.
+pub struct PacketBudget {
+    state: CategoryBudget,
+    chat: CategoryBudget,
+    voice: CategoryBudget,
+    control: CategoryBudget,
+    spectate: CategoryBudget,
+}
+
+impl PacketBudget {
+    pub fn new(state: BudgetLimits, chat: BudgetLimits, voice: BudgetLimits, control: BudgetLimits, spectate: BudgetLimits) -> Self {
+        let now = Instant::now();
+
+        Self {
+            state: CategoryBudget::new(state, now),
+            chat: CategoryBudget::new(chat, now),
+            voice: CategoryBudget::new(voice, now),
+            control: CategoryBudget::new(control, now),
+            spectate: CategoryBudget::new(spectate, now),
+        }
+    }
+
+    /// Counts a packet of the given category and returns what should happen to it.
+    pub fn record(&mut self, category: PacketCategory) -> BudgetVerdict {
+        let now = Instant::now();
+
+        match category {
+            PacketCategory::State => self.state.record(now),
+            PacketCategory::Chat => self.chat.record(now),
+            PacketCategory::Voice => self.voice.record(now),
+            PacketCategory::Control => self.control.record(now),
+            PacketCategory::Spectate => self.spectate.record(now),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: BudgetLimits = BudgetLimits { soft: 2, hard: 4 };
+
+    #[test]
+    fn categories_have_separate_budgets() {
+        let small = BudgetLimits { soft: 1, hard: 100 };
+        let mut budget = PacketBudget::new(small, small, small, small, small);
+
+        for category in [
+            PacketCategory::State,
+            PacketCategory::Chat,
+            PacketCategory::Voice,
+            PacketCategory::Control,
+            PacketCategory::Spectate,
+        ] {
+            assert_eq!(budget.record(category), BudgetVerdict::Allow, "{}", category.name());
+            assert_eq!(budget.record(category), BudgetVerdict::Drop, "{}", category.name());
+        }
+    }
+
+    #[test]
+    fn drops_over_soft_limit_and_resets_each_window() {
+        let start = Instant::now();
+        let mut budget = CategoryBudget::new(LIMITS, start);
+
+        assert_eq!(budget.record(start), BudgetVerdict::Allow);
+        assert_eq!(budget.record(start), BudgetVerdict::Allow);
+        assert_eq!(budget.record(start), BudgetVerdict::Drop);
+
+        let next = start + WINDOW;
+        assert_eq!(budget.record(next), BudgetVerdict::Allow);
+        assert_eq!(budget.record(next), BudgetVerdict::Allow);
+        assert_eq!(budget.record(next), BudgetVerdict::Drop);
+    }
+
+    #[test]
+    fn disconnects_after_grace_windows() {
+        let start = Instant::now();
+        let mut budget = CategoryBudget::new(LIMITS, start);
+
+        for window in 0..GRACE_WINDOWS as u32 {
+            let now = start + WINDOW * window;
+            for _ in 0..LIMITS.hard {
+                assert_ne!(budget.record(now), BudgetVerdict::Disconnect);
+            }
+
+            let expected = if window + 1 == GRACE_WINDOWS as u32 {
+                BudgetVerdict::Disconnect
+            } else {
+                BudgetVerdict::Drop
+            };
+            assert_eq!(budget.record(now), expected);
+            // the hard limit only counts once per window
+            assert_eq!(budget.record(now), BudgetVerdict::Drop);
+        }
+    }
+
+    #[test]
+    fn quiet_windows_forgive_strikes() {
+        let start = Instant::now();
+        let mut budget = CategoryBudget::new(LIMITS, start);
+        let mut now = start;
+
+        // alternate between breaking the hard limit and staying under it, which never reaches the grace limit
+        for _ in 0..10 {
+            for _ in 0..=LIMITS.hard {
+                assert_ne!(budget.record(now), BudgetVerdict::Disconnect);
+            }
+
+            now += WINDOW;
+            assert_eq!(budget.record(now), BudgetVerdict::Allow);
+            now += WINDOW;
+        }
+    }
+
+    #[test]
+    fn silence_forgives_every_quiet_window() {
+        let start = Instant::now();
+        let mut budget = CategoryBudget::new(LIMITS, start);
+        let mut now = start;
+
+        // two strikes, one short of a disconnect
+        for _ in 0..GRACE_WINDOWS - 1 {
+            for _ in 0..=LIMITS.hard {
+                assert_ne!(budget.record(now), BudgetVerdict::Disconnect);
+            }
+            now += WINDOW;
+        }
+
+        // staying silent for as many windows takes both strikes back at once, so it takes three more to get disconnected
+        now += WINDOW * u32::from(GRACE_WINDOWS);
+        for window in 0..GRACE_WINDOWS {
+            for _ in 0..LIMITS.hard {
+                budget.record(now);
+            }
+
+            let expected = if window + 1 == GRACE_WINDOWS {
+                BudgetVerdict::Disconnect
+            } else {
+                BudgetVerdict::Drop
+            };
+            assert_eq!(budget.record(now), expected);
+            now += WINDOW;
+        }
+    }
+}
@@ -35,6 +35,11 @@ impl SimpleRateLimiter {
         }
     }
 
+    /// Returns `true` if the next `try_tick` would fail, without consuming anything.
+    pub fn is_limited(&self) -> bool {
+        self.count == 0 && self.last_refill.elapsed() <= self.period
+    }
+
     /// Returns the amount of time since last refill
     pub fn since_last_refill(&self) -> Duration {
         self.last_refill.elapsed()
@@ -0,0 +1,262 @@
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use rustc_hash::FxHashMap;
+
+/// How long the missing fragments of a packet are waited for, before the ones that did arrive are thrown away.
+const FRAGMENT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How big a packet can be after putting it back together.
+const MAX_REASSEMBLED_SIZE: usize = 2usize.pow(18); // 256kb
+/// How many fragments a single packet can be split into.
+const MAX_FRAGMENTS: usize = 512;
+/// How many packets one peer can be sending in fragments at the same time.
+const MAX_MESSAGES_PER_PEER: usize = 4;
+/// How many bytes of incomplete packets all peers together can have buffered.
+const MAX_TOTAL_SIZE: usize = 2usize.pow(26); // 64mb
+
+struct PartialPacket {
+    started: Instant,
+    fragments: Vec<Option<Vec<u8>>>,
+    received: usize,
+    size: usize,
+}
+
+#[derive(Default)]
+struct PeerBuffer {
+    /// message id : packet
+    packets: Vec<(u16, PartialPacket)>,
+    size: usize,
+}
+
+impl PeerBuffer {
+    /// Removes the packets that have been waiting for too long, returns how many bytes were freed.
+    fn remove_expired(&mut self, now: Instant) -> usize {
+        let before = self.size;
+
+        self.packets.retain(|(_, packet)| {
+            let expired = now - packet.started >= FRAGMENT_TIMEOUT;
+            if expired {
+                self.size -= packet.size;
+            }
+
+            !expired
+        });
+
+        before - self.size
+    }
+}
+
+/// Puts udp packets back together that were too big to be sent in one datagram.
+/// Fragments that would go over one of the memory limits are dropped, which means the whole packet gets lost, like any other lost udp packet.
+/// Not thread safe on its own.
+#[derive(Default)]
+pub struct FragmentReassembler {
+    peers: FxHashMap<SocketAddr, PeerBuffer>,
+    total_size: usize,
+}
+
+impl FragmentReassembler {
+    /// Adds a fragment of a packet sent by the peer. Returns the whole packet once its last fragment arrives.
+    pub fn insert(&mut self, peer: SocketAddr, message_id: u16, index: u16, count: u16, data: &[u8]) -> Option<Vec<u8>> {
+        let (index, count) = (index as usize, count as usize);
+
+        if !(2..=MAX_FRAGMENTS).contains(&count) || index >= count || data.is_empty() {
+            return None;
+        }
+
+        let now = Instant::now();
+        let buffer = self.peers.entry(peer).or_default();
+
+        let pos = if let Some(pos) = buffer.packets.iter().position(|(id, _)| *id == message_id) {
+            pos
+        } else {
+            if buffer.packets.len() >= MAX_MESSAGES_PER_PEER {
+                self.total_size -= buffer.remove_expired(now);
+
+                if buffer.packets.len() >= MAX_MESSAGES_PER_PEER {
+                    return None;
+                }
+            }
+
+            buffer.packets.push((
+                message_id,
+                PartialPacket {
+                    started: now,
+                    fragments: vec![None; count],
+                    received: 0,
+                    size: 0,
+                },
+            ));
+
+            buffer.packets.len() - 1
+        };
+
+        let packet = &mut buffer.packets[pos].1;
+
+        if packet.fragments.len() != count
+            || packet.fragments[index].is_some()
+            || packet.size + data.len() > MAX_REASSEMBLED_SIZE
+            || self.total_size + data.len() > MAX_TOTAL_SIZE
+        {
+            return None;
+        }
+
+        packet.fragments[index] = Some(data.to_vec());
+        packet.received += 1;
+        packet.size += data.len();
+        buffer.size += data.len();
+        self.total_size += data.len();
+
+        if packet.received < count {
+            return None;
+        }
+
+        let (_, packet) = buffer.packets.swap_remove(pos);
+        buffer.size -= packet.size;
+        self.total_size -= packet.size;
+
+        if buffer.packets.is_empty() {
+            self.peers.remove(&peer);
+        }
+
+        let mut out = Vec::with_capacity(packet.size);
+        for fragment in packet.fragments.into_iter().flatten() {
+            out.extend_from_slice(&fragment);
+        }
+
+        Some(out)
+    }
+
+    /// Throws away the incomplete packets of a peer, must be called once it disconnects.
+    pub fn remove_peer(&mut self, peer: SocketAddr) {
+        if let Some(buffer) = self.peers.remove(&peer) {
+            self.total_size -= buffer.size;
+        }
+    }
+
+    /// Throws away the packets that are still missing fragments after `FRAGMENT_TIMEOUT`.
+    pub fn remove_expired(&mut self) {
+        let now = Instant::now();
+
+        self.peers.retain(|_, buffer| {
+            self.total_size -= buffer.remove_expired(now);
+            !buffer.packets.is_empty()
+        });
+    }
+
+    /// Returns how many bytes of incomplete packets are buffered right now.
+    pub fn buffered_size(&self) -> usize {
+        self.total_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    fn peer(n: u16) -> SocketAddr {
+        SocketAddr::from((Ipv4Addr::LOCALHOST, n))
+    }
+
+    #[test]
+    fn reassembles_out_of_order() {
+        let mut reassembler = FragmentReassembler::default();
+
+        assert_eq!(reassembler.insert(peer(1), 7, 2, 3, b"ghi"), None);
+        assert_eq!(reassembler.insert(peer(1), 7, 0, 3, b"abc"), None);
+        assert_eq!(reassembler.buffered_size(), 6);
+        assert_eq!(reassembler.insert(peer(1), 7, 1, 3, b"def").as_deref(), Some(&b"abcdefghi"[..]));
+        assert_eq!(reassembler.buffered_size(), 0);
+    }
+
+    #[test]
+    fn ignores_duplicates() {
+        let mut reassembler = FragmentReassembler::default();
+
+        assert_eq!(reassembler.insert(peer(1), 1, 0, 2, b"ab"), None);
+        assert_eq!(reassembler.insert(peer(1), 1, 0, 2, b"xy"), None);
+        assert_eq!(reassembler.buffered_size(), 2);
+        assert_eq!(reassembler.insert(peer(1), 1, 1, 2, b"cd").as_deref(), Some(&b"abcd"[..]));
+    }
+
+    #[test]
+    fn rejects_invalid_fragments() {
+        let mut reassembler = FragmentReassembler::default();
+
+        assert_eq!(reassembler.insert(peer(1), 1, 0, 1, b"a"), None);
+        assert_eq!(reassembler.insert(peer(1), 1, 2, 2, b"a"), None);
+        assert_eq!(reassembler.insert(peer(1), 1, 0, 2, b""), None);
+        assert_eq!(reassembler.buffered_size(), 0);
+
+        // fragment count must be the same for every fragment of a packet
+        assert_eq!(reassembler.insert(peer(1), 1, 0, 2, b"a"), None);
+        assert_eq!(reassembler.insert(peer(1), 1, 1, 3, b"b"), None);
+        assert_eq!(reassembler.buffered_size(), 1);
+    }
+
+    #[test]
+    fn limits_fragment_count() {
+        let mut reassembler = FragmentReassembler::default();
+
+        assert_eq!(reassembler.insert(peer(1), 1, 0, MAX_FRAGMENTS as u16 + 1, b"a"), None);
+        assert_eq!(reassembler.buffered_size(), 0);
+
+        assert_eq!(reassembler.insert(peer(1), 1, 0, MAX_FRAGMENTS as u16, b"a"), None);
+        assert_eq!(reassembler.buffered_size(), 1);
+    }
+
+    #[test]
+    fn limits_packet_size() {
+        let mut reassembler = FragmentReassembler::default();
+
+        assert_eq!(reassembler.insert(peer(1), 1, 0, 2, &vec![0; MAX_REASSEMBLED_SIZE]), None);
+        assert_eq!(reassembler.insert(peer(1), 1, 1, 2, b"a"), None);
+        assert_eq!(reassembler.buffered_size(), MAX_REASSEMBLED_SIZE);
+    }
+
+    #[test]
+    fn limits_packets_per_peer() {
+        let mut reassembler = FragmentReassembler::default();
+
+        for id in 0..MAX_MESSAGES_PER_PEER as u16 {
+            assert_eq!(reassembler.insert(peer(1), id, 0, 2, b"a"), None);
+        }
+
+        let id = MAX_MESSAGES_PER_PEER as u16;
+        assert_eq!(reassembler.insert(peer(1), id, 0, 2, b"a"), None);
+        assert_eq!(reassembler.insert(peer(1), id, 1, 2, b"b"), None);
+        assert_eq!(reassembler.buffered_size(), MAX_MESSAGES_PER_PEER);
+
+        // other peers are not affected, and completing a packet frees a slot
+        assert_eq!(reassembler.insert(peer(2), id, 0, 2, b"a"), None);
+        assert_eq!(reassembler.insert(peer(1), 0, 1, 2, b"b").as_deref(), Some(&b"ab"[..]));
+        assert_eq!(reassembler.insert(peer(1), id, 0, 2, b"a"), None);
+        assert_eq!(reassembler.insert(peer(1), id, 1, 2, b"b").as_deref(), Some(&b"ab"[..]));
+    }
+
+    #[test]
+    fn limits_total_size() {
+        let mut reassembler = FragmentReassembler::default();
+        let fragment = vec![0; MAX_REASSEMBLED_SIZE];
+        let peers = MAX_TOTAL_SIZE / (MAX_REASSEMBLED_SIZE * MAX_MESSAGES_PER_PEER);
+
+        for n in 0..peers as u16 {
+            for id in 0..MAX_MESSAGES_PER_PEER as u16 {
+                assert_eq!(reassembler.insert(peer(n), id, 0, 2, &fragment), None);
+            }
+        }
+
+        assert_eq!(reassembler.buffered_size(), MAX_TOTAL_SIZE);
+        assert_eq!(reassembler.insert(peer(u16::MAX), 0, 0, 2, b"a"), None);
+        assert_eq!(reassembler.buffered_size(), MAX_TOTAL_SIZE);
+
+        reassembler.remove_peer(peer(0));
+        assert_eq!(reassembler.buffered_size(), MAX_TOTAL_SIZE - MAX_REASSEMBLED_SIZE * MAX_MESSAGES_PER_PEER);
+        assert_eq!(reassembler.insert(peer(u16::MAX), 0, 0, 2, b"a"), None);
+        assert_eq!(reassembler.insert(peer(u16::MAX), 0, 1, 2, b"b").as_deref(), Some(&b"ab"[..]));
+    }
+}
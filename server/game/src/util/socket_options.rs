@@ -0,0 +1,144 @@
+use std::{net::SocketAddr, sync::OnceLock, time::Duration};
+
+use globed_shared::warn;
+use socket2::{Domain, Protocol, SockRef, Socket, TcpKeepalive, Type};
+
+use crate::tokio::net::{TcpListener, TcpStream, UdpSocket};
+
+/// Socket options read from environment variables. Every option is optional, when unset the OS default is kept.
+#[derive(Default)]
+pub struct SocketOptions {
+    pub tcp_nodelay: Option<bool>,
+    /// idle time before TCP keepalive probes are sent, `Some(Duration::ZERO)` disables keepalive
+    pub tcp_keepalive: Option<Duration>,
+    /// time between two TCP keepalive probes
+    pub tcp_keepalive_interval: Option<Duration>,
+    pub tcp_send_buffer: Option<usize>,
+    pub tcp_recv_buffer: Option<usize>,
+    pub udp_send_buffer: Option<usize>,
+    pub udp_recv_buffer: Option<usize>,
+}
+
+fn parse_env<T: std::str::FromStr>(name: &str, what: &str) -> Option<T> {
+    let value = std::env::var(name).ok()?;
+
+    match value.trim().parse::<T>() {
+        Ok(x) => Some(x),
+        Err(_) => {
+            warn!("invalid value for {name}, must be {what}, using the OS default");
+            None
+        }
+    }
+}
+
+fn parse_env_bool(name: &str) -> Option<bool> {
+    match std::env::var(name).ok()?.trim() {
+        "1" | "true" => Some(true),
+        "0" | "false" => Some(false),
+        _ => {
+            warn!("invalid value for {name}, must be 0 or 1, using the OS default");
+            None
+        }
+    }
+}
+
+impl SocketOptions {
+    pub fn from_env() -> Self {
+        Self {
+            tcp_nodelay: parse_env_bool("GLOBED_GS_TCP_NODELAY"),
+            tcp_keepalive: parse_env("GLOBED_GS_TCP_KEEPALIVE", "a number of seconds").map(Duration::from_secs),
+            tcp_keepalive_interval: parse_env("GLOBED_GS_TCP_KEEPALIVE_INTERVAL", "a number of seconds").map(Duration::from_secs),
+            tcp_send_buffer: parse_env("GLOBED_GS_TCP_SEND_BUFFER", "a size in bytes"),
+            tcp_recv_buffer: parse_env("GLOBED_GS_TCP_RECV_BUFFER", "a size in bytes"),
+            udp_send_buffer: parse_env("GLOBED_GS_UDP_SEND_BUFFER", "a size in bytes"),
+            udp_recv_buffer: parse_env("GLOBED_GS_UDP_RECV_BUFFER", "a size in bytes"),
+        }
+    }
+
+    /// Returns the options of this server, read from the environment on the first call.
+    pub fn get() -> &'static Self {
+        static OPTIONS: OnceLock<SocketOptions> = OnceLock::new();
+        OPTIONS.get_or_init(Self::from_env)
+    }
+
+    /// Applies the buffer sizes to the listening socket, so that they are already in effect during the TCP handshake.
+    pub fn apply_tcp_listener(&self, listener: &TcpListener) -> std::io::Result<()> {
+        let sock = SockRef::from(listener);
+        Self::apply_buffers(&sock, self.tcp_send_buffer, self.tcp_recv_buffer)
+    }
+
+    /// Applies all TCP options to a newly accepted connection.
+    pub fn apply_tcp(&self, stream: &TcpStream) -> std::io::Result<()> {
+        if let Some(nodelay) = self.tcp_nodelay {
+            stream.set_nodelay(nodelay)?;
+        }
+
+        let sock = SockRef::from(stream);
+
+        match self.tcp_keepalive {
+            Some(Duration::ZERO) => sock.set_keepalive(false)?,
+            Some(time) => {
+                let mut keepalive = TcpKeepalive::new().with_time(time);
+                if let Some(interval) = self.tcp_keepalive_interval {
+                    keepalive = keepalive.with_interval(interval);
+                }
+
+                sock.set_tcp_keepalive(&keepalive)?;
+            }
+            None => {}
+        }
+
+        Self::apply_buffers(&sock, self.tcp_send_buffer, self.tcp_recv_buffer)
+    }
+
+    pub fn apply_udp(&self, socket: &UdpSocket) -> std::io::Result<()> {
+        Self::apply_buffers(&SockRef::from(socket), self.udp_send_buffer, self.udp_recv_buffer)
+    }
+
+    fn apply_buffers(sock: &SockRef<'_>, send: Option<usize>, recv: Option<usize>) -> std::io::Result<()> {
+        if let Some(size) = send {
+            sock.set_send_buffer_size(size)?;
+        }
+
+        if let Some(size) = recv {
+            sock.set_recv_buffer_size(size)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Creates a non-blocking socket for the address. IPv6 sockets also accept IPv4 clients,
+/// so binding to `::` works for both, no matter what the OS default is.
+fn new_socket(address: SocketAddr, ty: Type, protocol: Protocol) -> std::io::Result<Socket> {
+    let socket = Socket::new(Domain::for_address(address), ty, Some(protocol))?;
+
+    if address.is_ipv6() {
+        socket.set_only_v6(false)?;
+    }
+
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
+
+/// Binds a UDP socket to the address, see `new_socket`.
+pub fn bind_udp(address: SocketAddr) -> std::io::Result<UdpSocket> {
+    let socket = new_socket(address, Type::DGRAM, Protocol::UDP)?;
+    socket.bind(&address.into())?;
+
+    UdpSocket::from_std(socket.into())
+}
+
+/// Binds a TCP listener to the address, see `new_socket`.
+pub fn bind_tcp(address: SocketAddr) -> std::io::Result<TcpListener> {
+    let socket = new_socket(address, Type::STREAM, Protocol::TCP)?;
+
+    // same as what tokio does, so that the server can be restarted right away
+    #[cfg(not(windows))]
+    socket.set_reuse_address(true)?;
+
+    socket.bind(&address.into())?;
+    socket.listen(1024)?;
+
+    TcpListener::from_std(socket.into())
+}
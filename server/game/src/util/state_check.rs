@@ -0,0 +1,253 @@
+use std::fmt::Display;
+
+use globed_shared::PlayerStatePolicy;
+
+use crate::data::{types::Point, LevelId, PlayerData};
+
+/// player data that was sent less than this many seconds (of level time) after the last checked one isn't checked for speed,
+/// as tiny distances over tiny intervals are too noisy
+const MIN_SPEED_INTERVAL: f32 = 0.1;
+/// a percentage this low means the player started over, so it's fine for it to go down
+const RESTART_PERCENTAGE: f32 = 1.0;
+/// how far the percentage can go down without it counting, to allow for rounding on the client
+const PERCENTAGE_TOLERANCE: f32 = 0.5;
+
+/// A plausibility check the player data of a player failed.
+pub enum StateViolation {
+    /// the player moved faster than the limit, in units per second
+    TooFast(f32),
+    /// the percentage was outside of 0-100
+    InvalidPercentage(f32),
+    /// the percentage went down without the player dying or restarting
+    PercentageDecreased { from: f32, to: f32 },
+}
+
+impl Display for StateViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooFast(speed) => write!(f, "moved at {speed:.0} units per second"),
+            Self::InvalidPercentage(percentage) => write!(f, "sent an invalid percentage ({percentage})"),
+            Self::PercentageDecreased { from, to } => write!(f, "percentage went down from {from:.1}% to {to:.1}%"),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct CheckedState {
+    timestamp: f32,
+    last_death: f32,
+    position: Point,
+    percentage: f32,
+}
+
+impl CheckedState {
+    fn from_data(data: &PlayerData) -> Self {
+        Self {
+            timestamp: data.timestamp.get(),
+            last_death: data.last_death_timestamp.get(),
+            position: data.player1.position,
+            percentage: data.current_percentage.get(),
+        }
+    }
+
+    /// Whether the player respawned or restarted the level between the two states, which makes them not comparable.
+    fn started_over(&self, previous: &Self) -> bool {
+        (self.last_death - previous.last_death).abs() > f32::EPSILON || self.timestamp < previous.timestamp
+    }
+}
+
+/// Compares the player data of a player with what they sent before on the same level, see `PlayerStatePolicy`.
+/// Not thread safe on its own.
+#[derive(Default)]
+pub struct StateChecker {
+    level_id: LevelId,
+    /// the last state that was checked for speed
+    speed_reference: Option<CheckedState>,
+    /// the last state that was checked for the percentage
+    last_state: Option<CheckedState>,
+    /// whether a room countdown started a race on this level. practice mode and platformer checkpoints can make the percentage go down,
+    /// so it's only expected to keep going up during races
+    racing: bool,
+    violations: u32,
+}
+
+impl StateChecker {
+    /// Checks the new player data, returns the first check it failed. Everything is forgotten when the level changes.
+    pub fn check(&mut self, level_id: LevelId, data: &PlayerData, policy: &PlayerStatePolicy) -> Option<StateViolation> {
+        if self.level_id != level_id {
+            *self = Self { level_id, ..Self::default() };
+        }
+
+        let state = CheckedState::from_data(data);
+
+        let percentage = if policy.check_percentage { self.check_percentage(state) } else { None };
+        let speed = if policy.max_speed > 0.0 {
+            self.check_speed(state, data, policy.max_speed)
+        } else {
+            None
+        };
+
+        percentage.or(speed)
+    }
+
+    /// Called when a room countdown on the level ends, from then on the percentage can only go up until the player leaves the level.
+    pub fn start_race(&mut self, level_id: LevelId) {
+        if self.level_id != level_id {
+            *self = Self { level_id, ..Self::default() };
+        }
+
+        self.racing = true;
+        self.last_state = None;
+    }
+
+    /// Counts a failed check, returns how many there were on the current level.
+    pub fn record_violation(&mut self) -> u32 {
+        self.violations = self.violations.saturating_add(1);
+        self.violations
+    }
+
+    fn check_percentage(&mut self, state: CheckedState) -> Option<StateViolation> {
+        let previous = self.last_state.replace(state);
+
+        if !(0.0..=100.0).contains(&state.percentage) {
+            return Some(StateViolation::InvalidPercentage(state.percentage));
+        }
+
+        let previous = previous.filter(|_| self.racing)?;
+
+        if state.started_over(&previous) || state.percentage <= RESTART_PERCENTAGE {
+            return None;
+        }
+
+        (state.percentage < previous.percentage - PERCENTAGE_TOLERANCE).then_some(StateViolation::PercentageDecreased {
+            from: previous.percentage,
+            to: state.percentage,
+        })
+    }
+
+    fn check_speed(&mut self, state: CheckedState, data: &PlayerData, max_speed: f32) -> Option<StateViolation> {
+        let Some(reference) = self.speed_reference else {
+            self.speed_reference = Some(state);
+            return None;
+        };
+
+        // spider teleports move the player across the screen in a single frame
+        if state.started_over(&reference) || data.player1.spider_teleport_data.is_some() {
+            self.speed_reference = Some(state);
+            return None;
+        }
+
+        let elapsed = state.timestamp - reference.timestamp;
+        if elapsed < MIN_SPEED_INTERVAL {
+            return None;
+        }
+
+        self.speed_reference = Some(state);
+
+        let dx = state.position.x.get() - reference.position.x.get();
+        let dy = state.position.y.get() - reference.position.y.get();
+        let speed = dx.hypot(dy) / elapsed;
+
+        (speed > max_speed).then_some(StateViolation::TooFast(speed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::FiniteF32;
+
+    const LEVEL: LevelId = 1;
+
+    fn policy(max_speed: f32, check_percentage: bool) -> PlayerStatePolicy {
+        PlayerStatePolicy {
+            max_speed,
+            check_percentage,
+            ..PlayerStatePolicy::default()
+        }
+    }
+
+    fn data(timestamp: f32, x: f32, percentage: f32, last_death: f32) -> PlayerData {
+        let mut data = PlayerData {
+            timestamp: FiniteF32::new(timestamp).unwrap(),
+            last_death_timestamp: FiniteF32::new(last_death).unwrap(),
+            current_percentage: FiniteF32::new(percentage).unwrap(),
+            ..PlayerData::default()
+        };
+        data.player1.position.x = FiniteF32::new(x).unwrap();
+        data
+    }
+
+    #[test]
+    fn flags_fast_movement() {
+        let policy = policy(1000.0, false);
+        let mut checker = StateChecker::default();
+
+        assert!(checker.check(LEVEL, &data(1.0, 0.0, 10.0, 0.0), &policy).is_none());
+        assert!(checker.check(LEVEL, &data(2.0, 500.0, 20.0, 0.0), &policy).is_none());
+        assert!(matches!(
+            checker.check(LEVEL, &data(3.0, 5000.0, 30.0, 0.0), &policy),
+            Some(StateViolation::TooFast(_))
+        ));
+
+        // respawning can move the player anywhere
+        assert!(checker.check(LEVEL, &data(4.0, 0.0, 0.0, 3.5), &policy).is_none());
+    }
+
+    #[test]
+    fn ignores_tiny_intervals() {
+        let policy = policy(1000.0, false);
+        let mut checker = StateChecker::default();
+
+        assert!(checker.check(LEVEL, &data(1.0, 0.0, 10.0, 0.0), &policy).is_none());
+        assert!(checker.check(LEVEL, &data(1.01, 20.0, 10.0, 0.0), &policy).is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_percentage() {
+        let policy = policy(0.0, true);
+        let mut checker = StateChecker::default();
+
+        assert!(matches!(
+            checker.check(LEVEL, &data(1.0, 0.0, 150.0, 0.0), &policy),
+            Some(StateViolation::InvalidPercentage(_))
+        ));
+    }
+
+    #[test]
+    fn percentage_only_has_to_go_up_during_races() {
+        let policy = policy(0.0, true);
+        let mut checker = StateChecker::default();
+
+        assert!(checker.check(LEVEL, &data(1.0, 0.0, 50.0, 0.0), &policy).is_none());
+        assert!(checker.check(LEVEL, &data(2.0, 0.0, 20.0, 0.0), &policy).is_none());
+
+        checker.start_race(LEVEL);
+        assert!(checker.check(LEVEL, &data(3.0, 0.0, 30.0, 0.0), &policy).is_none());
+        assert!(checker.check(LEVEL, &data(4.0, 0.0, 29.8, 0.0), &policy).is_none());
+        assert!(matches!(
+            checker.check(LEVEL, &data(5.0, 0.0, 10.0, 0.0), &policy),
+            Some(StateViolation::PercentageDecreased { .. })
+        ));
+
+        // dying lets it go back down
+        assert!(checker.check(LEVEL, &data(6.0, 0.0, 5.0, 5.5), &policy).is_none());
+
+        // the race is over once the player changes the level
+        assert!(checker.check(2, &data(1.0, 0.0, 50.0, 0.0), &policy).is_none());
+        assert!(checker.check(2, &data(2.0, 0.0, 20.0, 0.0), &policy).is_none());
+    }
+
+    #[test]
+    fn counts_violations_per_level() {
+        let mut checker = StateChecker::default();
+        let policy = policy(1000.0, false);
+        checker.check(LEVEL, &data(1.0, 0.0, 0.0, 0.0), &policy);
+
+        assert_eq!(checker.record_violation(), 1);
+        assert_eq!(checker.record_violation(), 2);
+
+        checker.check(2, &data(1.0, 0.0, 0.0, 0.0), &policy);
+        assert_eq!(checker.record_violation(), 1);
+    }
+}
@@ -0,0 +1,89 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use esp::{ByteBuffer, ByteBufferExt, ByteBufferExtWrite};
+use globed_shared::crypto_box::{
+    aead::{Aead, AeadCore, OsRng},
+    ChaChaBox, PublicKey, SecretKey,
+};
+
+/*
+* Sealed capture format:
+*
+* ephemeral public key (32 bytes), nonce (24 bytes), then the encrypted payload (with a 16 byte tag at the end),
+* which can be opened with `ChaChaBox::new(ephemeral_key, staff_secret_key)` (same as libsodium `crypto_box_open_easy`).
+*
+* The payload (all integers are big endian):
+*   frame count (u32), then for every frame:
+*   milliseconds since the first frame (u32), length (u16), the voice data exactly as the client sent it
+*/
+
+/// upper limit on how much voice data is kept for a single player, no matter how long the window is
+const MAX_CAPTURE_SIZE: usize = 512 * 1024;
+
+/// The last few seconds of voice data sent by a player, so that it can be attached to a report against them.
+#[derive(Default)]
+pub struct VoiceCapture {
+    frames: VecDeque<(Instant, Vec<u8>)>,
+    size: usize,
+}
+
+impl VoiceCapture {
+    /// Adds a frame and forgets the ones that are older than `window`.
+    pub fn push(&mut self, data: &[u8], window: Duration) {
+        let now = Instant::now();
+
+        self.frames.push_back((now, data.to_vec()));
+        self.size += data.len();
+
+        while let Some((time, frame)) = self.frames.front() {
+            if now.duration_since(*time) <= window && self.size <= MAX_CAPTURE_SIZE {
+                break;
+            }
+
+            self.size -= frame.len();
+            self.frames.pop_front();
+        }
+    }
+
+    /// Length of the capture, from the first frame to the last one.
+    pub fn duration(&self) -> Duration {
+        match (self.frames.front(), self.frames.back()) {
+            (Some((first, _)), Some((last, _))) => last.duration_since(*first),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Encrypts the frames that are not older than `window` for the holder of the secret key that belongs to `key`.
+    /// Returns `None` if there is nothing to send.
+    pub fn seal(&self, window: Duration, key: &PublicKey) -> Option<Vec<u8>> {
+        let now = Instant::now();
+        let frames = self
+            .frames
+            .iter()
+            .filter(|(time, _)| now.duration_since(*time) <= window)
+            .collect::<Vec<_>>();
+        let (first, _) = frames.first()?;
+
+        let mut buffer = ByteBuffer::with_capacity(4 + self.size + frames.len() * 6);
+        buffer.write_u32(u32::try_from(frames.len()).unwrap_or(u32::MAX));
+
+        for (time, frame) in &frames {
+            buffer.write_u32(u32::try_from(time.duration_since(*first).as_millis()).unwrap_or(u32::MAX));
+            buffer.write_byte_array(frame);
+        }
+
+        let ephemeral = SecretKey::generate(&mut OsRng);
+        let nonce = ChaChaBox::generate_nonce(&mut OsRng);
+        let encrypted = ChaChaBox::new(key, &ephemeral).encrypt(&nonce, buffer.as_bytes()).ok()?;
+
+        let mut out = Vec::with_capacity(32 + nonce.len() + encrypted.len());
+        out.extend_from_slice(ephemeral.public_key().as_bytes());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&encrypted);
+
+        Some(out)
+    }
+}
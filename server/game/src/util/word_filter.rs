@@ -1,21 +1,61 @@
 use aho_corasick::AhoCorasick;
+use globed_shared::{warn, SyncMutex};
 
 pub struct WordFilter {
-    algo: AhoCorasick,
+    algo: SyncMutex<AhoCorasick>,
+}
+
+fn build(words: &[String]) -> Result<AhoCorasick, aho_corasick::BuildError> {
+    AhoCorasick::builder().ascii_case_insensitive(true).build(words)
 }
 
 impl WordFilter {
     pub fn new(words: &[String]) -> Self {
         Self {
-            algo: AhoCorasick::builder()
-                .ascii_case_insensitive(true)
-                .build(words)
-                .expect("failed to create word filter"),
+            algo: SyncMutex::new(build(words).expect("failed to create word filter")),
+        }
+    }
+
+    /// Replaces the filtered words, used when the word list is reloaded. The old words are kept if the new ones can't be used.
+    pub fn set_words(&self, words: &[String]) {
+        match build(words) {
+            Ok(algo) => *self.algo.lock() = algo,
+            Err(e) => warn!("failed to create word filter, keeping the old words: {e}"),
         }
     }
 
+    /// the matcher is cheap to clone, so it's not locked while searching
+    fn algo(&self) -> AhoCorasick {
+        self.algo.lock().clone()
+    }
+
     pub fn is_bad(&self, content: &str) -> bool {
-        self.algo.find(content).is_some()
+        self.algo().find(content).is_some()
+    }
+
+    /// Like `is_bad`, but also catches words that are split up with spaces or symbols.
+    pub fn is_bad_strict(&self, content: &str) -> bool {
+        if self.is_bad(content) {
+            return true;
+        }
+
+        let letters: String = content.chars().filter(|c| c.is_alphanumeric()).collect();
+        self.is_bad(&letters)
+    }
+
+    /// Replaces every filtered word in `content` with asterisks.
+    pub fn censor(&self, content: &str) -> String {
+        let mut out = String::with_capacity(content.len());
+        let mut last = 0;
+
+        for mat in self.algo().find_iter(content) {
+            out.push_str(&content[last..mat.start()]);
+            out.extend(std::iter::repeat_n('*', content[mat.start()..mat.end()].chars().count()));
+            last = mat.end();
+        }
+
+        out.push_str(&content[last..]);
+        out
     }
 }
 
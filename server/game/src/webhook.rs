@@ -1,5 +1,10 @@
+use std::time::Duration;
+
+use globed_shared::ReportState;
 use serde::Serialize;
 
+use crate::data::{ConfigOverrideKey, LevelId};
+
 pub struct BanMuteStateChange {
     pub mod_name: String,
     pub target_name: String,
@@ -21,6 +26,28 @@ pub enum WebhookMessage {
     UserViolationMetaChanged(String, String, bool, bool, Option<i64>, Option<String>), // mod username, username, is_banned, is_muted, expiry, reason
     UserRolesChanged(String, String, Vec<String>, Vec<String>),                        // mod username, username, old roles, new roles
     UserNameColorChanged(String, String, Option<String>, Option<String>),              // mod username, username, old color, new color
+    LoginFailureBurst(String, u32),                                                    // account id or ip address, failure count
+    ChatLinkFlagged(String, i32, String),                                              // username, account id, message
+    LevelBlockChanged(String, i64, bool),                                              // mod username, level id, blocked
+    RoomTakenOver(String, u32, String, i32),                                           // mod username, room id, room name, previous owner account id
+    RoomClosed(String, u32, String, usize),                                            // mod username, room id, room name, player count
+    NameFlagged(String, i32, String),                                                  // username, account id, matched substring
+    PlayerStateFlagged(String, i32, LevelId, String),                                  // username, account id, level id, failed check
+    NoticeScheduled(String, i64, String),                                              // username, send time, message
+    ScheduledNoticeCancelled(String, String, String),                                  // username, author username, message
+    BotSpawned(String, String, u32, LevelId),                                          // username, bot name, room id, level id
+    InviteCodeCreated(String, u32),                                                    // username, uses
+    ReportCreated(i64, String, i32, String, i32, String, Option<Duration>),            // id, reporter name/id, reported name/id, reason, voice length
+    ReportStateChanged(String, i64, ReportState),                                      // mod username, report id, new state
+    ChatTailChanged(String, u32, LevelId, bool),                                       // mod username, room id, level id, started
+    ConfigOverrideSet(String, ConfigOverrideKey, u32, i64),                            // username, key, value, expires at
+    ConfigOverrideRemoved(Option<String>, ConfigOverrideKey, u32),                     // username (none if it expired), key, restored value
+    ConfigReloaded(String),                                                            // username
+
+    // sent to the webhook of a room instead of the admin webhook
+    RoomPlayerJoined(String, String),                  // room name, username
+    RoomPlayerLeft(String, String),                    // room name, username
+    RoomCountdownStarted(String, String, LevelId, u8), // room name, username, level id, seconds
 }
 
 #[derive(Serialize)]
@@ -73,7 +100,7 @@ pub struct WebhookOpts<'a> {
 }
 
 #[allow(clippy::too_many_lines)]
-pub fn embed_for_message(message: &WebhookMessage) -> Option<WebhookEmbed> {
+pub fn embed_for_message(message: &WebhookMessage) -> Option<WebhookEmbed<'_>> {
     match message {
         WebhookMessage::AuthFail(_user_name) => None,
         WebhookMessage::NoticeToEveryone(username, player_count, message) => Some(WebhookEmbed {
@@ -314,6 +341,307 @@ pub fn embed_for_message(message: &WebhookMessage) -> Option<WebhookEmbed> {
                 },
             ],
         }),
+        WebhookMessage::LoginFailureBurst(subject, failures) => Some(WebhookEmbed {
+            title: "Repeated authentication failures".to_owned(),
+            color: hex_color_to_decimal("#e8604d"),
+            author: None,
+            description: Some(format!(
+                "{subject} failed to authenticate {failures} times, it is now temporarily locked out."
+            )),
+            footer: None,
+            fields: Vec::new(),
+        }),
+        WebhookMessage::ChatLinkFlagged(user_name, account_id, message) => Some(WebhookEmbed {
+            title: "Link sent in chat".to_owned(),
+            color: hex_color_to_decimal("#e8d34d"),
+            author: Some(WebhookAuthor {
+                name: format!("{user_name} ({account_id})"),
+                icon_url: None,
+            }),
+            description: Some(message.clone()),
+            footer: None,
+            fields: Vec::new(),
+        }),
+        WebhookMessage::NameFlagged(user_name, account_id, matched) => Some(WebhookEmbed {
+            title: "Flagged player name".to_owned(),
+            color: hex_color_to_decimal("#e8d34d"),
+            author: Some(WebhookAuthor {
+                name: format!("{user_name} ({account_id})"),
+                icon_url: None,
+            }),
+            description: Some(format!("Matched: {matched}")),
+            footer: None,
+            fields: Vec::new(),
+        }),
+        WebhookMessage::PlayerStateFlagged(user_name, account_id, level_id, violation) => Some(WebhookEmbed {
+            title: "Implausible player data".to_owned(),
+            color: hex_color_to_decimal("#e8d34d"),
+            author: Some(WebhookAuthor {
+                name: format!("{user_name} ({account_id})"),
+                icon_url: None,
+            }),
+            description: Some(format!("Last failed check: {violation}")),
+            footer: None,
+            fields: vec![WebhookField {
+                name: "Level ID",
+                value: level_id.to_string(),
+                inline: Some(true),
+            }],
+        }),
+        WebhookMessage::LevelBlockChanged(mod_name, level_id, blocked) => Some(WebhookEmbed {
+            title: if *blocked { "Level blocked" } else { "Level unblocked" }.to_owned(),
+            color: hex_color_to_decimal(if *blocked { "#de3023" } else { "#31bd31" }),
+            author: None,
+            description: Some(format!("Level ID: {level_id}")),
+            footer: None,
+            fields: vec![WebhookField {
+                name: "Performed by",
+                value: mod_name.clone(),
+                inline: Some(true),
+            }],
+        }),
+        WebhookMessage::RoomTakenOver(mod_name, room_id, room_name, previous_owner) => Some(WebhookEmbed {
+            title: "Room taken over".to_owned(),
+            color: hex_color_to_decimal("#e8d34d"),
+            author: None,
+            description: Some(format!("Room: {room_name} ({room_id})\nPrevious owner: {previous_owner}")),
+            footer: None,
+            fields: vec![WebhookField {
+                name: "Performed by",
+                value: mod_name.clone(),
+                inline: Some(true),
+            }],
+        }),
+        WebhookMessage::RoomClosed(mod_name, room_id, room_name, player_count) => Some(WebhookEmbed {
+            title: "Room closed".to_owned(),
+            color: hex_color_to_decimal("#e8d34d"),
+            author: None,
+            description: Some(format!("Room: {room_name} ({room_id})\nPlayers moved out: {player_count}")),
+            footer: None,
+            fields: vec![WebhookField {
+                name: "Performed by",
+                value: mod_name.clone(),
+                inline: Some(true),
+            }],
+        }),
+        WebhookMessage::NoticeScheduled(username, send_at, message) => Some(WebhookEmbed {
+            title: "Notice scheduled".to_owned(),
+            color: hex_color_to_decimal("#4dace8"),
+            author: None,
+            description: Some(message.clone()),
+            footer: None,
+            fields: vec![
+                WebhookField {
+                    name: "Performed by",
+                    value: username.clone(),
+                    inline: Some(true),
+                },
+                WebhookField {
+                    name: "Sent at",
+                    value: format!("<t:{send_at}:f>"),
+                    inline: Some(true),
+                },
+            ],
+        }),
+        WebhookMessage::ScheduledNoticeCancelled(username, author, message) => Some(WebhookEmbed {
+            title: "Scheduled notice cancelled".to_owned(),
+            color: hex_color_to_decimal("#e8d34d"),
+            author: None,
+            description: Some(message.clone()),
+            footer: None,
+            fields: vec![
+                WebhookField {
+                    name: "Performed by",
+                    value: username.clone(),
+                    inline: Some(true),
+                },
+                WebhookField {
+                    name: "Scheduled by",
+                    value: author.clone(),
+                    inline: Some(true),
+                },
+            ],
+        }),
+        WebhookMessage::BotSpawned(username, bot_name, room_id, level_id) => Some(WebhookEmbed {
+            title: "Bot spawned".to_owned(),
+            color: hex_color_to_decimal("#9b59b6"),
+            author: None,
+            description: Some(format!("Bot: {bot_name}\nRoom: {room_id}\nLevel: {level_id}")),
+            footer: None,
+            fields: vec![WebhookField {
+                name: "Performed by",
+                value: username.clone(),
+                inline: Some(true),
+            }],
+        }),
+        WebhookMessage::InviteCodeCreated(username, uses) => Some(WebhookEmbed {
+            title: "Whitelist invite code created".to_owned(),
+            color: hex_color_to_decimal("#1abc9c"),
+            author: None,
+            description: Some(format!("Uses: {uses}")),
+            footer: None,
+            fields: vec![WebhookField {
+                name: "Performed by",
+                value: username.clone(),
+                inline: Some(true),
+            }],
+        }),
+        WebhookMessage::ReportCreated(report_id, reporter_name, reporter_id, user_name, account_id, reason, voice_capture) => Some(WebhookEmbed {
+            title: format!("Report #{report_id}"),
+            color: hex_color_to_decimal("#e8604d"),
+            author: Some(WebhookAuthor {
+                name: format!("{user_name} ({account_id})"),
+                icon_url: None,
+            }),
+            description: Some(reason.clone()),
+            footer: None,
+            fields: {
+                let mut fields = vec![WebhookField {
+                    name: "Reported by",
+                    value: format!("{reporter_name} ({reporter_id})"),
+                    inline: Some(true),
+                }];
+
+                if let Some(duration) = voice_capture {
+                    fields.push(WebhookField {
+                        name: "Voice capture",
+                        value: format!("{}s, attached to the report", duration.as_secs().max(1)),
+                        inline: Some(true),
+                    });
+                }
+
+                fields
+            },
+        }),
+        WebhookMessage::ReportStateChanged(mod_name, report_id, state) => Some(WebhookEmbed {
+            title: match state {
+                ReportState::Open => format!("Report #{report_id} unclaimed"),
+                ReportState::Claimed => format!("Report #{report_id} claimed"),
+                ReportState::Resolved => format!("Report #{report_id} resolved"),
+                ReportState::Rejected => format!("Report #{report_id} rejected"),
+            },
+            color: hex_color_to_decimal(match state {
+                ReportState::Open => "#e8604d",
+                ReportState::Claimed => "#e8d34d",
+                ReportState::Resolved => "#31bd31",
+                ReportState::Rejected => "#95a5a6",
+            }),
+            author: None,
+            description: None,
+            footer: None,
+            fields: vec![WebhookField {
+                name: "Performed by",
+                value: mod_name.clone(),
+                inline: Some(true),
+            }],
+        }),
+        WebhookMessage::ChatTailChanged(mod_name, room_id, level_id, started) => Some(WebhookEmbed {
+            title: if *started {
+                "Chat tail started".to_owned()
+            } else {
+                "Chat tail stopped".to_owned()
+            },
+            color: hex_color_to_decimal(if *started { "#e8d34d" } else { "#95a5a6" }),
+            author: None,
+            description: Some(if *level_id == 0 {
+                format!("Room: {room_id}\nLevel: all levels")
+            } else {
+                format!("Room: {room_id}\nLevel: {level_id}")
+            }),
+            footer: None,
+            fields: vec![WebhookField {
+                name: "Performed by",
+                value: mod_name.clone(),
+                inline: Some(true),
+            }],
+        }),
+        WebhookMessage::ConfigOverrideSet(username, key, value, expires_at) => Some(WebhookEmbed {
+            title: "Config value overridden".to_owned(),
+            color: hex_color_to_decimal("#e8d34d"),
+            author: None,
+            description: Some(format!("{}: {value}", key.name())),
+            footer: None,
+            fields: vec![
+                WebhookField {
+                    name: "Performed by",
+                    value: username.clone(),
+                    inline: Some(true),
+                },
+                WebhookField {
+                    name: "Expires",
+                    value: format!("<t:{expires_at}:R>"),
+                    inline: Some(true),
+                },
+            ],
+        }),
+        WebhookMessage::ConfigOverrideRemoved(username, key, value) => Some(WebhookEmbed {
+            title: if username.is_some() {
+                "Config override removed".to_owned()
+            } else {
+                "Config override expired".to_owned()
+            },
+            color: hex_color_to_decimal("#95a5a6"),
+            author: None,
+            description: Some(format!("{} is back to {value}", key.name())),
+            footer: None,
+            fields: username
+                .iter()
+                .map(|username| WebhookField {
+                    name: "Performed by",
+                    value: username.clone(),
+                    inline: Some(true),
+                })
+                .collect(),
+        }),
+        WebhookMessage::ConfigReloaded(username) => Some(WebhookEmbed {
+            title: "Configuration reloaded".to_owned(),
+            color: hex_color_to_decimal("#95a5a6"),
+            author: None,
+            description: None,
+            footer: None,
+            fields: vec![WebhookField {
+                name: "Performed by",
+                value: username.clone(),
+                inline: Some(true),
+            }],
+        }),
+        WebhookMessage::RoomPlayerJoined(room_name, username) => Some(WebhookEmbed {
+            title: format!("{username} joined the room"),
+            color: hex_color_to_decimal("#31bd31"),
+            author: None,
+            description: None,
+            footer: Some(WebhookFooter {
+                text: room_name,
+                icon_url: None,
+            }),
+            fields: Vec::new(),
+        }),
+        WebhookMessage::RoomPlayerLeft(room_name, username) => Some(WebhookEmbed {
+            title: format!("{username} left the room"),
+            color: hex_color_to_decimal("#95a5a6"),
+            author: None,
+            description: None,
+            footer: Some(WebhookFooter {
+                text: room_name,
+                icon_url: None,
+            }),
+            fields: Vec::new(),
+        }),
+        WebhookMessage::RoomCountdownStarted(room_name, username, level_id, seconds) => Some(WebhookEmbed {
+            title: format!("Countdown started ({seconds}s)"),
+            color: hex_color_to_decimal("#4dace8"),
+            author: None,
+            description: Some(format!("Level: {level_id}")),
+            footer: Some(WebhookFooter {
+                text: room_name,
+                icon_url: None,
+            }),
+            fields: vec![WebhookField {
+                name: "Started by",
+                value: username.clone(),
+                inline: Some(true),
+            }],
+        }),
     }
 }
 
@@ -0,0 +1,28 @@
+/// version of the protocol, clients and servers on different versions can't talk to each other
+pub const PROTOCOL_VERSION: u16 = 9;
+
+/// maximum characters in a `ServerNoticePacket` or `ServerDisconnectPacket` (164)
+pub const MAX_NOTICE_SIZE: usize = 224;
+/// maximum characters in a user message (156)
+pub const MAX_MESSAGE_SIZE: usize = 156;
+/// maximum characters in the reason of a player report (128)
+pub const MAX_REPORT_REASON_SIZE: usize = 128;
+/// maximum characters in the name of a bot, without the `[BOT] ` prefix (18)
+pub const MAX_BOT_NAME_SIZE: usize = 18;
+/// maximum characters in a user's name (24). they can only be 15 chars max but we give headroom just in case
+pub const MAX_NAME_SIZE: usize = 24;
+/// how many accounts a single player can watch at once, about the size of a big friend list (256)
+pub const MAX_PRESENCE_SUBSCRIPTIONS: usize = 256;
+/// how many accounts can be in the friend list a client uploads, anything past that is ignored (512)
+pub const MAX_FRIEND_LIST_SIZE: usize = 512;
+/// how many tags the owner of a room can define (8)
+pub const MAX_ROOM_TAGS: usize = 8;
+/// maximum characters in the name of a room tag (16)
+pub const MAX_ROOM_TAG_NAME_SIZE: usize = 16;
+/// maximum bytes in the payload of a level event (64)
+pub const MAX_LEVEL_EVENT_SIZE: usize = 64;
+/// amount of chars in a room id string (6)
+pub const ROOM_ID_LENGTH: usize = 6;
+
+// this should be the PlayerData size plus some headroom
+pub const SMALL_PACKET_LIMIT: usize = 96;
@@ -1,3 +1,19 @@
+//! Packets and data types of the Globed protocol, as they are sent between the game server and clients.
+//! Everything here can be encoded and decoded with `esp`, and doesn't depend on any of the servers,
+//! so bots, load testers and other tools can use the exact same types as the game server.
+
+#![allow(
+    clippy::must_use_candidate,
+    clippy::module_name_repetitions,
+    clippy::cast_possible_truncation,
+    clippy::missing_errors_doc,
+    clippy::missing_panics_doc,
+    clippy::wildcard_imports
+)]
+
+// the `Packet` derive refers to types by the crate name, so it works both here and in crates that depend on us
+extern crate self as globed_protocol;
+
 pub mod consts;
 pub mod packets;
 pub mod types;
@@ -6,7 +22,6 @@ pub mod types;
 pub use consts::*;
 pub use esp::*;
 pub use globed_derive::*;
-pub use globed_shared::MAX_NAME_SIZE;
 pub use packets::*;
 pub use types::*;
 
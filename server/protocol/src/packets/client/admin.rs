@@ -0,0 +1,203 @@
+use crate::*;
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 19000, encrypted = true)]
+pub struct AdminAuthPacket {
+    pub key: FastString,
+    pub totp_code: FastString, // empty if not provided
+}
+
+#[derive(Encodable, Decodable, Clone, Copy)]
+#[repr(u8)]
+pub enum AdminSendNoticeType {
+    Everyone = 0,
+    RoomOrLevel = 1,
+    Person = 2,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 19001)]
+pub struct AdminSendNoticePacket {
+    pub notice_type: AdminSendNoticeType,
+    pub room_id: u32,
+    pub level_id: LevelId,
+    pub player: FastString,
+    pub message: FastString,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 19002)]
+pub struct AdminDisconnectPacket {
+    pub player: FastString,
+    pub message: FastString,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 19003)]
+pub struct AdminGetUserStatePacket {
+    pub player: FastString,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 19004)]
+pub struct AdminUpdateUserPacket {
+    pub user_entry: UserEntry,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19005)]
+pub struct AdminDebugDumpPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19006)]
+pub struct AdminSetChaosPacket {
+    pub bridge_delay: u32,
+    pub broadcast_drop_chance: u8,
+    pub disconnect_chance: u8,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19007)]
+pub struct AdminSetLevelBlockedPacket {
+    pub level_id: LevelId,
+    pub blocked: bool,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19008)]
+pub struct AdminSetSlowModePacket {
+    pub room_id: u32,
+    pub level_id: LevelId,
+    /// 0 to disable slow mode
+    pub seconds: u32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19009)]
+pub struct AdminTakeOverRoomPacket {
+    pub room_id: u32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19010)]
+pub struct AdminGetPlayerHistoryPacket {
+    /// 0 for the whole server
+    pub room_id: u32,
+    /// 0 for all levels, takes priority over the room
+    pub level_id: LevelId,
+    /// how far back to go, in seconds
+    pub period: u32,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 19011)]
+pub struct AdminScheduleNoticePacket {
+    /// notices to a single person can't be scheduled
+    pub notice_type: AdminSendNoticeType,
+    pub room_id: u32,
+    pub level_id: LevelId,
+    pub message: FastString,
+    /// seconds since unix epoch
+    pub send_at: i64,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19012)]
+pub struct AdminGetScheduledNoticesPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19013)]
+pub struct AdminCancelScheduledNoticePacket {
+    pub id: u32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19014)]
+pub struct AdminGetTrafficStatsPacket {
+    /// how many rooms and levels to return
+    pub count: u8,
+    /// start counting from zero after returning the stats
+    pub reset: bool,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 19015)]
+pub struct AdminSpawnBotPacket {
+    pub room_id: u32,
+    /// 0 to only put the bot in the room
+    pub level_id: LevelId,
+    /// `[BOT]` is prepended to the name
+    pub name: InlineString<MAX_BOT_NAME_SIZE>,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19016)]
+pub struct AdminMoveBotPacket {
+    pub account_id: i32,
+    /// the bot can move to another level, but not to another room
+    pub level_id: LevelId,
+    pub position: Point,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 19017)]
+pub struct AdminBotChatPacket {
+    pub account_id: i32,
+    pub message: InlineString<MAX_MESSAGE_SIZE>,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19018)]
+pub struct AdminRemoveBotPacket {
+    pub account_id: i32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19019)]
+pub struct AdminGetBotsPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19020)]
+pub struct AdminCreateInviteCodePacket {
+    /// how many accounts can be whitelisted with the code
+    pub uses: u32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19021)]
+pub struct AdminGetReportsPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19022)]
+pub struct AdminUpdateReportPacket {
+    pub id: i64,
+    /// `Claimed` to claim the report, `Open` to give it back, `Resolved` or `Rejected` to close it
+    pub state: ReportState,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19023)]
+pub struct AdminTailChatPacket {
+    pub room_id: u32,
+    /// 0 to follow every level in the room
+    pub level_id: LevelId,
+    /// false to stop following chat, the room and level are ignored then
+    pub enabled: bool,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19024)]
+pub struct AdminSetConfigOverridePacket {
+    pub key: ConfigOverrideKey,
+    pub value: u32,
+    /// in seconds, 0 to remove the override
+    pub duration: u32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19025)]
+pub struct AdminGetConfigOverridesPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19026)]
+pub struct AdminReloadConfigPacket;
@@ -0,0 +1,96 @@
+use crate::*;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 10000)]
+pub struct PingPacket {
+    pub id: u32,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 10001)]
+pub struct CryptoHandshakeStartPacket {
+    pub protocol: u16,
+    pub key: CryptoPublicKey,
+    pub compression: CompressionSupport,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 10002)]
+pub struct KeepalivePacket;
+
+pub const MAX_TOKEN_SIZE: usize = 164;
+pub const MAX_INVITE_CODE_SIZE: usize = 16;
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 10003, encrypted = true)]
+pub struct LoginPacket {
+    pub account_id: i32,
+    pub user_id: i32,
+    pub name: InlineString<MAX_NAME_SIZE>,
+    pub token: FastString,
+    pub icons: PlayerIconData,
+    pub fragmentation_limit: u16,
+    pub platform: InlineString<72>,
+    pub is_invisible: bool,
+    /// whitelist invite code, empty if the player doesn't have one
+    pub invite_code: InlineString<MAX_INVITE_CODE_SIZE>,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 10005)]
+pub struct ClaimThreadPacket {
+    pub secret_key: u32,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 10006)]
+pub struct DisconnectPacket;
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 10007)]
+pub struct KeepaliveTCPPacket;
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 10008, encrypted = true)]
+pub struct RekeyResponsePacket {
+    pub key: CryptoPublicKey,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 10009, encrypted = true)]
+pub struct ClaimTransferPacket {
+    pub token: FastString,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 10010)]
+pub struct LatencyProbeResponsePacket {
+    pub id: u32,
+}
+
+// unauthenticated query from a server browser, only handled over udp
+#[derive(Packet, Decodable)]
+#[packet(id = 10011)]
+pub struct ServerQueryPacket {
+    pub id: u32,
+    /// 0 to request a token, otherwise a token from a `ServerQueryTokenPacket` sent to this address
+    pub token: u32,
+}
+
+/// One piece of a udp packet that was too big to fit into one datagram, the data of the piece follows right after it.
+/// Only handled by the server itself, once all pieces are here they are put back together and handled like a normal packet.
+#[derive(Packet, Decodable, StaticSize)]
+#[packet(id = 10012)]
+pub struct UdpFragmentPacket {
+    /// the same for all pieces of one packet, different for every packet the client splits
+    pub message_id: u16,
+    pub index: u16,
+    pub count: u16,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 10200)]
+pub struct ConnectionTestPacket {
+    pub uid: u32,
+    pub data: Vec<u8>,
+}
@@ -0,0 +1,89 @@
+use crate::*;
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 12000)]
+pub struct RequestPlayerProfilesPacket {
+    pub requested: i32, // 0 to get all ppl on the level
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 12001)]
+pub struct LevelJoinPacket {
+    pub level_id: LevelId,
+    /// whether the level is unlisted, so the player can be hidden from others if they want
+    pub unlisted: bool,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 12002)]
+pub struct LevelLeavePacket;
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 12003)]
+pub struct PlayerDataPacket {
+    /// increases by one with every packet, used for estimating packet loss
+    pub sequence: u32,
+    pub data: PlayerData,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 12004)]
+pub struct PlayerMetadataPacket {
+    pub data: PlayerMetadata,
+}
+
+// sent by clients that opted into sharing their level completions
+#[derive(Packet, Decodable)]
+#[packet(id = 12005)]
+pub struct LevelCompletedPacket {
+    pub attempts: u32,
+}
+
+// start spectating another player in the room, 0 stops spectating
+#[derive(Packet, Decodable)]
+#[packet(id = 12006)]
+pub struct SpectatePlayerPacket {
+    pub player: i32,
+}
+
+// sent every frame instead of at the usual rate while the player has spectators, only relayed to the spectators
+#[derive(Packet, Decodable)]
+#[packet(id = 12007)]
+pub struct SpectatorDataPacket {
+    pub data: PlayerData,
+}
+
+// a small event relayed to everyone else on the level, e.g. to play an effect for all of them at once.
+// the server doesn't know what the types mean, they are up to the clients
+#[derive(Packet, Decodable)]
+#[packet(id = 12008)]
+pub struct LevelEventPacket {
+    pub event_type: u32,
+    pub payload: FastVec<u8, MAX_LEVEL_EVENT_SIZE>,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 12010, encrypted = true)]
+pub struct VoicePacket {
+    pub data: FastEncodedAudioFrame,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 12011, encrypted = true)]
+pub struct ChatMessagePacket {
+    /// picked by the client and echoed back in `ChatMessageSentPacket`, so it knows which message got which ID
+    pub client_id: u32,
+    pub message: InlineString<MAX_MESSAGE_SIZE>,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 12012, encrypted = true)]
+pub struct DeleteChatMessagePacket {
+    pub message_id: u32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 12013)]
+pub struct SwitchChatChannelPacket {
+    pub channel: u32,
+}
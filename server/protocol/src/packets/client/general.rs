@@ -0,0 +1,77 @@
+use crate::*;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11000)]
+pub struct SyncIconsPacket {
+    pub icons: PlayerIconData,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11001)]
+pub struct RequestGlobalPlayerListPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11002)]
+pub struct RequestLevelListPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11003)]
+pub struct RequestPlayerCountPacket {
+    pub level_ids: FastVec<LevelId, 128>,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11004)]
+pub struct UpdatePlayerStatusPacket {
+    pub is_invisible: bool,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11005)]
+pub struct FeatureUsagePacket {
+    pub platform: ClientPlatform,
+    pub voice_enabled: bool,
+    pub changed_settings: ChangedSettingsFlags,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11006)]
+pub struct UpdatePreferencesPacket {
+    pub preferences: UserPreferences,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 11007)]
+pub struct ReportPlayerPacket {
+    pub account_id: i32,
+    pub reason: InlineString<MAX_REPORT_REASON_SIZE>,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11008)]
+pub struct SubscribePresencePacket {
+    /// replaces the previous subscription, an empty list unsubscribes
+    pub accounts: FastVec<i32, MAX_PRESENCE_SUBSCRIPTIONS>,
+}
+
+// the geometry dash friend list of the player, sent after logging in and whenever it changes
+#[derive(Packet, Decodable)]
+#[packet(id = 11009)]
+pub struct UpdateFriendListPacket {
+    pub friends: FastVec<i32, MAX_FRIEND_LIST_SIZE>,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11010)]
+pub struct FriendListQueryPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11011)]
+pub struct RequestSessionListPacket;
+
+// logs out another session of the same account, on this server or any other one in the cluster
+#[derive(Packet, Decodable)]
+#[packet(id = 11012)]
+pub struct LogoutSessionPacket {
+    pub session_id: u64,
+}
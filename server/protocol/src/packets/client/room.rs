@@ -0,0 +1,82 @@
+use crate::*;
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 13000)]
+pub struct CreateRoomPacket {
+    pub room_name: InlineString<32>,
+    pub password: InlineString<16>,
+    pub settings: RoomSettings,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 13001)]
+pub struct JoinRoomPacket {
+    pub room_id: u32,
+    pub password: InlineString<16>,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13002)]
+pub struct LeaveRoomPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13003)]
+pub struct RequestRoomPlayerListPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13004)]
+pub struct UpdateRoomSettingsPacket {
+    pub settings: RoomSettings,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13005)]
+pub struct RoomSendInvitePacket {
+    pub player: i32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13006)]
+pub struct RequestRoomListPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13007)]
+pub struct StartRoomCountdownPacket {
+    pub seconds: u8,
+}
+
+#[derive(Packet, Encodable, Decodable)]
+#[packet(id = 13008)]
+pub struct SetRoomWebhookPacket {
+    /// empty to remove the webhook
+    pub url: FastString,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13009)]
+pub struct SetRoomModeratorPacket {
+    pub player: i32,
+    /// `false` to take the role away
+    pub moderator: bool,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13010)]
+pub struct RoomKickPlayerPacket {
+    pub player: i32,
+}
+
+// only the owner can define tags, tags of players whose tag was removed are taken away
+#[derive(Packet, Decodable)]
+#[packet(id = 13011)]
+pub struct SetRoomTagsPacket {
+    pub tags: FastVec<RoomTag, MAX_ROOM_TAGS>,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13012)]
+pub struct SetRoomMemberTagPacket {
+    pub player: i32,
+    /// index of the tag, `None` takes the tag away
+    pub tag: Option<u8>,
+}
@@ -4,7 +4,7 @@ pub mod server;
 pub use client::*;
 pub use server::*;
 
-use crate::data::*;
+use crate::*;
 
 pub trait Packet: PacketMetadata {}
 
@@ -20,6 +20,8 @@ pub trait PacketMetadata {
 pub struct PacketHeader {
     pub packet_id: u16,
     pub encrypted: bool,
+    /// whether the payload is compressed with the algorithm negotiated during the handshake, only used by the server
+    pub compressed: bool,
 }
 
 impl PacketHeader {
@@ -28,6 +30,7 @@ impl PacketHeader {
         Self {
             packet_id: P::PACKET_ID,
             encrypted: P::ENCRYPTED,
+            compressed: false,
         }
     }
 
@@ -0,0 +1,93 @@
+use crate::*;
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29000, tcp = true)]
+pub struct AdminAuthSuccessPacket {
+    pub role: ComputedRole,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29001, tcp = true, encrypted = true)]
+pub struct AdminErrorPacket<'a> {
+    pub code: ErrorCode,
+    pub message: &'a str,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29002, tcp = true, encrypted = true)]
+pub struct AdminUserDataPacket {
+    pub entry: UserEntry,
+    pub account_data: Option<PlayerRoomPreviewAccountData>,
+    pub connection: Option<ConnectionInfo>,
+    /// the last levels the player was on, oldest first. empty if they are not online and didn't leave recently
+    pub level_history: Vec<LevelVisit>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29003, tcp = true)]
+pub struct AdminSuccessMessagePacket<'a> {
+    pub message: &'a str,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 29004, tcp = true)]
+pub struct AdminAuthFailedPacket;
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29005, tcp = true)]
+pub struct AdminPlayerHistoryPacket {
+    /// seconds between two samples
+    pub interval: u32,
+    pub points: Vec<PlayerCountPoint>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29006, tcp = true)]
+pub struct AdminScheduledNoticesPacket {
+    pub notices: Vec<ScheduledNotice>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29007, tcp = true)]
+pub struct AdminTrafficStatsPacket {
+    /// seconds since unix epoch, when counting started
+    pub since: i64,
+    pub rooms: Vec<TrafficEntry>,
+    pub levels: Vec<TrafficEntry>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29008, tcp = true)]
+pub struct AdminBotsPacket {
+    pub bots: Vec<BotInfo>,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 29009, tcp = true)]
+pub struct AdminInviteCodePacket {
+    pub code: InlineString<MAX_INVITE_CODE_SIZE>,
+    pub uses: u32,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29010, tcp = true)]
+pub struct AdminReportsPacket {
+    pub reports: Vec<AdminReport>,
+}
+
+#[derive(Packet, Encodable, StaticSize, Clone)]
+#[packet(id = 29011, tcp = true)]
+pub struct AdminChatTailPacket {
+    pub room_id: u32,
+    pub level_id: LevelId,
+    pub player_id: i32,
+    pub player_name: InlineString<MAX_NAME_SIZE>,
+    pub message_id: u32,
+    pub message: InlineString<MAX_MESSAGE_SIZE>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29012, tcp = true)]
+pub struct AdminConfigOverridesPacket {
+    pub overrides: Vec<ConfigOverride>,
+}
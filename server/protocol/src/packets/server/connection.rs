@@ -0,0 +1,197 @@
+use crate::*;
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 20000, tcp = false)]
+pub struct PingResponsePacket {
+    pub id: u32,
+    pub player_count: u32,
+    pub distance_class: DistanceClass,
+}
+
+#[derive(Packet, Encodable, Decodable, StaticSize)]
+#[packet(id = 20001, tcp = true)]
+pub struct CryptoHandshakeResponsePacket {
+    pub key: CryptoPublicKey,
+    pub compression: CompressionAlgorithm,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 20002, tcp = false)]
+pub struct KeepaliveResponsePacket {
+    pub player_count: u32,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 20003, tcp = true)]
+pub struct ServerDisconnectPacket<'a> {
+    pub reason: DisconnectReason,
+    /// optional detail text, empty if there is nothing to add to the reason
+    pub message: &'a str,
+}
+
+#[derive(Packet, Encodable, Decodable, DynamicSize)]
+#[packet(id = 20004, encrypted = true, tcp = true)]
+pub struct LoggedInPacket {
+    pub tps: u32,
+    pub special_user_data: SpecialUserData,
+    pub all_roles: Vec<GameServerRole>,
+    pub secret_key: u32,
+    /// udp packets bigger than this must be split into `UdpFragmentPacket`s
+    pub max_datagram_size: u32,
+    /// features the server refuses for the platform of the client
+    pub disabled_features: DisabledFeatures,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 20005, tcp = true)]
+pub struct LoginFailedPacket<'a> {
+    pub code: ErrorCode,
+    /// optional detail text, empty if there is nothing to add to the code
+    pub message: &'a str,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 20006, tcp = true)]
+pub struct ProtocolMismatchPacket<'a> {
+    pub protocol: u16,
+    pub min_client_version: &'a str,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 20007, tcp = true)]
+pub struct KeepaliveTCPResponsePacket;
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 20008, tcp = false)]
+pub struct ClaimThreadFailedPacket;
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 20009, tcp = true)]
+pub struct LoginRecoveryFailedPacket;
+
+#[derive(Packet, Encodable, Decodable, StaticSize)]
+#[packet(id = 20010, encrypted = true, tcp = true)]
+pub struct RekeyRequestPacket {
+    pub key: CryptoPublicKey,
+}
+
+// tells the client to reconnect to another game server, and claim the transfer there with the token
+#[derive(Packet, Encodable, DynamicSize, Clone)]
+#[packet(id = 20011, encrypted = true, tcp = true)]
+pub struct ServerTransferPacket {
+    pub address: FastString,
+    pub token: FastString,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 20012, tcp = true)]
+pub struct TransferClaimedPacket {
+    pub info: RoomInfo,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 20013, tcp = true)]
+pub struct DuplicateLoginPacket {
+    pub notice: DuplicateLoginNotice,
+}
+
+// the client must echo the ID back right away, used for measuring the round trip time
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 20014, tcp = false)]
+pub struct LatencyProbePacket {
+    pub id: u32,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 20015, tcp = true)]
+pub struct SettingsHintsPacket {
+    pub hints: SettingsHints,
+}
+
+// sent instead of LoginFailedPacket when the name of the player is not allowed by the name policy
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 20016, tcp = false)]
+pub struct LoginNameRejectedPacket {
+    pub message: ErrorMessage,
+}
+
+// response to a ServerQueryPacket without a valid token, never larger than the query itself
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 20017, tcp = false)]
+pub struct ServerQueryTokenPacket {
+    pub id: u32,
+    pub token: u32,
+}
+
+// response to a ServerQueryPacket that echoed a valid token
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 20018, tcp = false)]
+pub struct ServerQueryResponsePacket {
+    pub id: u32,
+    pub info: ServerQueryInfo,
+}
+
+// sent instead of LoginFailedPacket when the server has whitelist enabled and the account is not whitelisted,
+// the player can log in again with an invite code
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 20019, tcp = false)]
+pub struct LoginWhitelistRequiredPacket {
+    /// whether the login had an invite code that was invalid or used up
+    pub invalid_code: bool,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 20020, tcp = true)]
+pub struct ThrottleHintPacket {
+    /// whether the client should send less, if `false` it can go back to the settings from `SettingsHintsPacket`
+    pub throttled: bool,
+    /// how many times per second to send player data while throttled
+    pub send_rate: u16,
+    /// bits per second for encoding voice while throttled
+    pub voice_bitrate: u32,
+}
+
+// sent after logging into a standalone server, the client sends this token on the next login to keep its name
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 20021, encrypted = true, tcp = true)]
+pub struct StandaloneTokenPacket {
+    pub token: FastString,
+}
+
+// the server is going down, the client can offer to reconnect once it's back or to move to the alternate server
+#[derive(Packet, Encodable, DynamicSize, Clone)]
+#[packet(id = 20022, tcp = true)]
+pub struct ServerRestartingPacket {
+    /// seconds until the server shuts down
+    pub seconds: u32,
+    /// another game server the player can move to, empty if there is none
+    pub alternate_address: FastString,
+}
+
+// used to communicate a simple message to the user
+#[derive(Packet, Encodable, DynamicSize, Clone)]
+#[packet(id = 20100, tcp = false)]
+pub struct ServerNoticePacket {
+    pub message: FastString,
+}
+
+#[derive(Packet, Encodable, DynamicSize, Clone)]
+#[packet(id = 20101, tcp = true)]
+pub struct ServerBannedPacket {
+    pub message: FastString,
+    pub timestamp: i64,
+}
+
+#[derive(Packet, Encodable, DynamicSize, Clone)]
+#[packet(id = 20102)]
+pub struct ServerMutedPacket {
+    pub reason: FastString,
+    pub timestamp: i64,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 20200, tcp = false)]
+pub struct ConnectionTestResponsePacket {
+    pub uid: u32,
+    pub data: Vec<u8>,
+}
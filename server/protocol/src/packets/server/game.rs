@@ -0,0 +1,125 @@
+use crate::*;
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 22000, tcp = true)]
+pub struct PlayerProfilesPacket {
+    pub players: Vec<PlayerAccountData>,
+}
+
+#[derive(Packet, Encodable)]
+#[packet(id = 22001, tcp = false)]
+pub struct LevelDataPacket {
+    pub players: Vec<AssociatedPlayerData>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 22002, tcp = true)]
+pub struct LevelPlayerMetadataPacket {
+    pub players: Vec<AssociatedPlayerMetadata>,
+}
+
+// the player tried to join a level that is blocked on the server, or was removed from one that just got blocked
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 22003, tcp = true)]
+pub struct LevelBlockedPacket {
+    pub level_id: LevelId,
+}
+
+// another player on the level just completed it, the name is included so the client doesn't need their profile
+#[derive(Clone, Packet, Encodable, StaticSize)]
+#[packet(id = 22004, tcp = true)]
+pub struct LevelCompletedBroadcastPacket {
+    pub player_id: i32,
+    pub player_name: InlineString<MAX_NAME_SIZE>,
+    pub attempts: u32,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 22005, tcp = true)]
+pub struct SpectateStartedPacket {
+    pub player: i32,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 22006, tcp = true)]
+pub struct SpectateFailedPacket<'a> {
+    pub code: ErrorCode,
+    /// optional detail text, empty if there is nothing to add to the code
+    pub message: &'a str,
+}
+
+// how many players are spectating the client, while this is above 0 it should send `SpectatorDataPacket` every frame
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 22007, tcp = true)]
+pub struct SpectatorCountPacket {
+    pub count: u32,
+}
+
+// full rate data of the spectated player, only sent to their spectators
+#[derive(Clone, Packet, Encodable, StaticSize)]
+#[packet(id = 22008, tcp = false)]
+pub struct SpectatedPlayerDataPacket {
+    pub data: AssociatedPlayerData,
+}
+
+// a level event sent by another player on the level
+#[derive(Clone, Packet, Encodable, DynamicSize)]
+#[packet(id = 22009, tcp = false)]
+pub struct LevelEventBroadcastPacket {
+    pub player_id: i32,
+    pub event_type: u32,
+    pub payload: FastVec<u8, MAX_LEVEL_EVENT_SIZE>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 22010, encrypted = true, tcp = false)]
+pub struct VoiceBroadcastPacket {
+    pub player_id: i32,
+    pub data: FastEncodedAudioFrame,
+}
+
+#[derive(Clone, Packet, Encodable, StaticSize)]
+#[packet(id = 22011, encrypted = true, tcp = false)]
+pub struct ChatMessageBroadcastPacket {
+    pub player_id: i32,
+    pub message_id: u32,
+    pub message: InlineString<MAX_MESSAGE_SIZE>,
+}
+
+#[derive(Clone, Packet, Encodable, StaticSize)]
+#[packet(id = 22012, tcp = true)]
+pub struct ChatMessageDeletedPacket {
+    pub message_id: u32,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 22013, tcp = true)]
+pub struct ChatMessageSentPacket {
+    /// `client_id` of the `ChatMessagePacket` this is a response to
+    pub client_id: u32,
+    pub message_id: u32,
+}
+
+// the player was not allowed to join a level and is not on any level now, the client must stop sending level data
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 22014, tcp = true)]
+pub struct LevelJoinRejectedPacket {
+    pub level_id: LevelId,
+    pub reason: LevelJoinRejection,
+}
+
+// the player was mentioned in a chat message, sent to them in addition to the message itself
+#[derive(Clone, Packet, Encodable, StaticSize)]
+#[packet(id = 22015, tcp = true)]
+pub struct ChatMentionPacket {
+    pub player_id: i32,
+    pub message_id: u32,
+}
+
+// the chat channel the player is in on a level with many players, channel_count is 0 if chat on the level isn't split
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 22016, tcp = true)]
+pub struct ChatChannelPacket {
+    pub channel: u32,
+    pub channel_count: u32,
+}
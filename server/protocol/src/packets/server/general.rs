@@ -0,0 +1,72 @@
+use crate::*;
+
+/*
+* For optimization reasons, most of those packets are encoded inline, and their structure is not present here.
+*/
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 21000, tcp = true)]
+pub struct GlobalPlayerListPacket {
+    pub players: Vec<PlayerPreviewAccountData>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 21001, tcp = true)]
+pub struct LevelListPacket {
+    pub levels: Vec<GlobedLevel>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 21002)]
+pub struct LevelPlayerCountPacket {
+    pub levels: Vec<(LevelId, u16)>,
+}
+
+#[derive(Packet, Encodable, DynamicSize, Clone)]
+#[packet(id = 21003)]
+pub struct RolesUpdatedPacket {
+    pub special_user_data: SpecialUserData,
+    /// new definitions of all roles, only sent when they were changed on the central server
+    pub all_roles: Option<Vec<GameServerRole>>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 21004, tcp = true)]
+pub struct PreferencesPacket {
+    pub preferences: UserPreferences,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 21005, tcp = true)]
+pub struct PlayerReportedPacket {
+    /// false if the report could not be sent, for example when reporting too often
+    pub success: bool,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 21006, tcp = true)]
+pub struct PresenceUpdatePacket {
+    pub players: Vec<PlayerPresence>,
+}
+
+#[derive(Packet, Encodable, StaticSize, Clone)]
+#[packet(id = 21007, tcp = true)]
+pub struct PlayerProfileChangedPacket {
+    pub account_id: i32,
+    pub icons: PlayerIconData,
+}
+
+// friends from the uploaded friend list that are online right now, offline and invisible ones are left out
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 21008, tcp = true)]
+pub struct FriendListPacket {
+    pub players: Vec<PlayerPresence>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 21009, tcp = true)]
+pub struct SessionListPacket {
+    /// the session of the player that requested the list
+    pub current: u64,
+    pub sessions: Vec<AccountSession>,
+}
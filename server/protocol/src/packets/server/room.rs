@@ -0,0 +1,94 @@
+use crate::*;
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 23000, tcp = false)]
+pub struct RoomCreatedPacket {
+    pub info: RoomInfo,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 23001, tcp = false)]
+pub struct RoomJoinedPacket;
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 23002, tcp = false)]
+pub struct RoomJoinFailedPacket<'a> {
+    pub code: ErrorCode,
+    /// optional detail text, empty if there is nothing to add to the code
+    pub message: &'a str,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 23003, tcp = true)]
+pub struct RoomPlayerListPacket {
+    pub room_info: RoomInfo,
+    pub players: Vec<PlayerRoomPreviewAccountData>,
+    pub moderators: Vec<i32>,
+    pub tags: RoomTags,
+}
+
+#[derive(Packet, Encodable, DynamicSize, Clone)]
+#[packet(id = 23004)]
+pub struct RoomInfoPacket {
+    pub info: RoomInfo,
+    pub moderators: Vec<i32>,
+    pub tags: RoomTags,
+}
+
+#[derive(Packet, Encodable, StaticSize, DynamicSize, Clone)]
+#[packet(id = 23005)]
+pub struct RoomInvitePacket {
+    pub player_data: PlayerPreviewAccountData,
+    pub room_id: u32,
+    pub room_password: InlineString<16>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 23006)]
+pub struct RoomListPacket {
+    pub rooms: Vec<RoomListingInfo>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 23007)]
+pub struct RoomCreateFailedPacket<'a> {
+    pub code: ErrorCode,
+    /// optional detail text, empty if there is nothing to add to the code
+    pub message: &'a str,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 23008, tcp = true)]
+pub struct RoomCountdownPacket {
+    pub level_id: LevelId,
+    /// milliseconds until the countdown ends, already shortened by the time this packet takes to reach the client
+    pub starts_in: u32,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 23009, tcp = true)]
+pub struct RoomAfkRemovedPacket;
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 23010, tcp = true)]
+pub struct RoomInviteFailedPacket<'a> {
+    pub code: ErrorCode,
+    /// optional detail text, empty if there is nothing to add to the code
+    pub message: &'a str,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 23011, tcp = true)]
+pub struct RoomWebhookSetPacket;
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 23012, tcp = true)]
+pub struct RoomWebhookFailedPacket<'a> {
+    pub code: ErrorCode,
+    /// optional detail text, empty if there is nothing to add to the code
+    pub message: &'a str,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 23013, tcp = true)]
+pub struct RoomKickedPacket;
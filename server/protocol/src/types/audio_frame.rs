@@ -1,4 +1,4 @@
-use crate::data::*;
+use crate::*;
 
 const VOICE_MAX_FRAMES_IN_AUDIO_FRAME: usize = 10;
 
@@ -1,7 +1,7 @@
 use std::io::Read;
 
+use crypto_box::{PublicKey, KEY_SIZE};
 use esp::*;
-use globed_shared::crypto_box::{PublicKey, KEY_SIZE};
 
 pub struct CryptoPublicKey(pub PublicKey);
 
@@ -1,4 +1,4 @@
-use crate::data::*;
+use crate::*;
 
 /* PlayerIconType */
 
@@ -64,3 +64,12 @@ pub struct PlayerData {
 
     pub flags: Bits<1>, // also a bit-field
 }
+
+impl PlayerData {
+    /// Bit in `flags` that is set while the player is talking in voice chat (push-to-talk held or voice activity detected).
+    pub const SPEAKING_FLAG: usize = 6;
+
+    pub fn is_speaking(&self) -> bool {
+        self.flags.get_bit(Self::SPEAKING_FLAG)
+    }
+}
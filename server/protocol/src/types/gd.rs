@@ -1,10 +1,11 @@
-use globed_shared::UserEntry;
-
-use crate::{data::*, managers::RoleManager};
+use crate::*;
 
 pub const NO_GLOW: u8 = u8::MAX;
 
-#[derive(Clone, Encodable, Decodable, StaticSize, DynamicSize)]
+/// SHA-256 hash of the contents of a custom cosmetic, clients use it to fetch the cosmetic from wherever they host them.
+pub type CosmeticHash = [u8; 32];
+
+#[derive(Clone, PartialEq, Eq, Encodable, Decodable, StaticSize, DynamicSize)]
 #[dynamic_size(as_static = true)]
 pub struct PlayerIconData {
     pub cube: i16,
@@ -22,6 +23,8 @@ pub struct PlayerIconData {
     pub glow_color: u8,
     pub streak: u8,
     pub ship_streak: u8,
+    /// custom cosmetic the player wants others to see, only relayed if it was approved on the central server
+    pub custom_cosmetic: Option<CosmeticHash>,
 }
 
 impl Default for PlayerIconData {
@@ -42,6 +45,7 @@ impl Default for PlayerIconData {
             glow_color: NO_GLOW, // glow disabled
             streak: 1,
             ship_streak: 1,
+            custom_cosmetic: None,
         }
     }
 }
@@ -57,6 +61,14 @@ impl PlayerIconData {
             color1: self.color1,
             color2: self.color2,
             glow_color: self.glow_color,
+            custom_cosmetic: self.custom_cosmetic,
+        }
+    }
+
+    /// Removes the custom cosmetic if it's not one of the approved ones, so that it's never relayed to other players.
+    pub fn strip_unapproved_cosmetic(&mut self, approved: &[CosmeticHash]) {
+        if self.custom_cosmetic.is_some_and(|hash| !approved.contains(&hash)) {
+            self.custom_cosmetic = None;
         }
     }
 }
@@ -68,6 +80,7 @@ pub struct PlayerIconDataSimple {
     pub color1: u8,
     pub color2: u8,
     pub glow_color: u8,
+    pub custom_cosmetic: Option<CosmeticHash>,
 }
 
 impl Default for PlayerIconDataSimple {
@@ -77,6 +90,7 @@ impl Default for PlayerIconDataSimple {
             color1: 1,
             color2: 3,
             glow_color: NO_GLOW,
+            custom_cosmetic: None,
         }
     }
 }
@@ -88,18 +102,6 @@ pub struct SpecialUserData {
     pub roles: Option<FastVec<u8, 16>>,
 }
 
-impl SpecialUserData {
-    pub fn from_user_entry(user_entry: &UserEntry, role_manager: &RoleManager) -> Self {
-        if user_entry.user_roles.is_empty() {
-            Self { roles: None }
-        } else {
-            let roles = role_manager.role_ids_to_int_ids(&user_entry.user_roles);
-
-            Self { roles: Some(roles) }
-        }
-    }
-}
-
 /* PlayerAccountData */
 
 #[derive(Clone, Default, Encodable, Decodable, StaticSize, DynamicSize)]
@@ -112,7 +114,7 @@ pub struct PlayerAccountData {
 }
 
 impl PlayerAccountData {
-    pub fn make_room_preview(&self, level_id: LevelId) -> PlayerRoomPreviewAccountData {
+    pub fn make_room_preview(&self, level_id: LevelId, is_afk: bool) -> PlayerRoomPreviewAccountData {
         PlayerRoomPreviewAccountData {
             account_id: self.account_id,
             user_id: self.user_id,
@@ -120,6 +122,8 @@ impl PlayerAccountData {
             icons: self.icons.to_simple(),
             level_id,
             special_user_data: self.special_user_data.clone(),
+            is_afk,
+            spectators: 0,
         }
     }
 
@@ -155,6 +159,23 @@ pub struct PlayerRoomPreviewAccountData {
     pub icons: PlayerIconDataSimple,
     pub level_id: LevelId,
     pub special_user_data: SpecialUserData,
+    pub is_afk: bool,
+    /// how many players are spectating this one, always 0 if they hide it
+    pub spectators: u16,
+}
+
+/* PlayerPresence - where a watched player is, for presence subscriptions and friend lists */
+
+#[derive(Clone, Default, Encodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct PlayerPresence {
+    pub account_id: i32,
+    /// false if the player is offline or invisible, the rest is then zero
+    pub online: bool,
+    /// zero if the player is in the global room, or in a hidden room
+    pub room_id: u32,
+    /// zero unless the subscriber is in the same room or a mutual friend, and the player doesn't hide their level
+    pub level_id: LevelId,
 }
 
 /* AssociatedPlayerData */
@@ -0,0 +1,485 @@
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+#[derive(Encodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct GlobedLevel {
+    pub level_id: LevelId,
+    pub player_count: u16,
+}
+
+#[derive(Encodable, Decodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct ErrorMessage {
+    pub hash: u32,
+}
+
+impl ErrorMessage {
+    pub const fn new(string: &'static str) -> Self {
+        Self {
+            hash: esp::hash::adler32_const(string),
+        }
+    }
+
+    pub const fn new_with_hash(hash: u32) -> Self {
+        Self { hash }
+    }
+}
+
+#[derive(Encodable, Decodable, DynamicSize)]
+// Can be either a builtin error message hash or a custom message string
+pub struct CustomErrorMessage {
+    pub message: Either<ErrorMessage, String>,
+}
+
+impl CustomErrorMessage {
+    pub const fn builtin(string: &'static str) -> Self {
+        Self {
+            message: Either::new_first(ErrorMessage::new(string)),
+        }
+    }
+
+    pub const fn builtin_with_hash(hash: u32) -> Self {
+        Self {
+            message: Either::new_first(ErrorMessage::new_with_hash(hash)),
+        }
+    }
+
+    pub const fn custom(string: String) -> Self {
+        Self {
+            message: Either::new_second(string),
+        }
+    }
+}
+
+/// Connection quality of an online player, shown to moderators.
+#[derive(Clone, Copy, Encodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct ConnectionInfo {
+    /// estimated packet loss in percent
+    pub packet_loss: u8,
+    /// whether the server is sending extra level snapshots to the player because of the packet loss
+    pub lossy: bool,
+}
+
+/// A level a player was on, for the level history shown to moderators.
+#[derive(Clone, Copy, Encodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct LevelVisit {
+    pub level_id: LevelId,
+    /// seconds since unix epoch
+    pub joined_at: i64,
+    /// seconds since unix epoch, 0 if the player is still on the level
+    pub left_at: i64,
+}
+
+/// Player count at a point in time, for the player count history in the admin panel.
+#[derive(Clone, Copy, Encodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct PlayerCountPoint {
+    /// seconds since unix epoch
+    pub timestamp: i64,
+    pub count: u32,
+}
+
+/// Outgoing traffic of a room or a level, for the admin panel. For rooms `level_id` is 0, for levels `room_id` is 0.
+#[derive(Clone, Copy, Encodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct TrafficEntry {
+    pub room_id: u32,
+    pub level_id: LevelId,
+    pub bytes: u64,
+    pub packets: u64,
+}
+
+/// A bot spawned by an admin, for the admin panel.
+#[derive(Clone, Encodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct BotInfo {
+    pub account_id: i32,
+    pub name: InlineString<MAX_NAME_SIZE>,
+    pub room_id: u32,
+    pub level_id: LevelId,
+    pub position: Point,
+    pub spawned_by: InlineString<MAX_NAME_SIZE>,
+}
+
+/// A pending player report, for the admin panel. Names are empty if the player isn't online and didn't leave recently.
+#[derive(Clone, Encodable, DynamicSize)]
+pub struct AdminReport {
+    pub report: PlayerReport,
+    pub reporter_name: InlineString<MAX_NAME_SIZE>,
+    pub reported_name: InlineString<MAX_NAME_SIZE>,
+    pub handled_by_name: InlineString<MAX_NAME_SIZE>,
+}
+
+/// A notice that an admin scheduled to be sent at a later time.
+#[derive(Clone, Encodable, DynamicSize)]
+pub struct ScheduledNotice {
+    pub id: u32,
+    /// seconds since unix epoch
+    pub send_at: i64,
+    pub author: String,
+    pub author_id: i32,
+    /// if true, the notice is sent to everyone on the server (and other instances), and the room and level are ignored
+    pub everyone: bool,
+    pub room_id: u32,
+    /// 0 to send the notice to everyone in the room
+    pub level_id: LevelId,
+    pub message: String,
+}
+
+/// Tells a session what happened when its account was logged into from multiple places.
+#[derive(Clone, Copy, Debug, Encodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum DuplicateLoginNotice {
+    /// (old session) you are about to be disconnected, as your account was logged into elsewhere
+    Replaced = 0,
+    /// (new session) another session of your account was disconnected to let you in
+    ReplacedOther = 1,
+    /// (new session) your login was refused, as your account is already logged in elsewhere
+    Rejected = 2,
+    /// (old session) someone tried to log into your account elsewhere, and was refused
+    RejectedOther = 3,
+    /// (old session) your account was logged into elsewhere, and both sessions stay connected
+    Concurrent = 4,
+}
+
+/// Why the server disconnected a client, so it can decide whether to reconnect. Sent together with optional detail text.
+#[derive(Clone, Copy, Debug, Encodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum DisconnectReason {
+    Other = 0,
+    /// no packets were received from the client for too long
+    Idle = 1,
+    /// kicked by an admin or an external service
+    Kicked = 2,
+    /// banned while connected (a banned user trying to log in gets a `ServerBannedPacket` instead)
+    Banned = 3,
+    /// the server is under maintenance
+    Maintenance = 4,
+    /// the account was logged into from a different place
+    DuplicateLogin = 5,
+    /// the client misbehaved or failed a required step of the protocol
+    ProtocolError = 6,
+    /// the client sent way too many packets for too long
+    Flooding = 7,
+    /// the server is shutting down
+    ServerShutdown = 8,
+}
+
+/// Machine readable reason why a request was refused, sent in failure packets together with optional detail text,
+/// so that clients can show their own (translated) message, and tell which failures are worth retrying.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encodable, Decodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum ErrorCode {
+    /// none of the other codes fit, the detail text says what happened
+    Other = 0,
+    /// something went wrong on the server, trying again later might work
+    Internal = 1,
+    /// the central server could not be reached, trying again later might work
+    CentralUnavailable = 2,
+    /// too many attempts in a short time, the detail text says when to try again
+    RateLimited = 3,
+    /// something that has to finish first is still in progress, trying again in a moment should work
+    Busy = 4,
+    /// the login token is invalid or expired, the client has to get a new one
+    InvalidToken = 5,
+    /// the account is already logged in from a different place
+    AlreadyLoggedIn = 6,
+    /// the request contained invalid data
+    InvalidRequest = 7,
+    /// the player lacks the permission for this
+    NoPermission = 8,
+    /// the account is too new, or the player doesn't have one of the roles this is limited to
+    Restricted = 9,
+    /// the player, room, notice or whatever else the request refers to does not exist
+    NotFound = 10,
+    /// the room password is wrong
+    WrongPassword = 11,
+    /// the room is full
+    Full = 12,
+    /// the level is blocked on this server
+    LevelBlocked = 13,
+    /// the server already has as many of these (bots, notices, invite codes) as it allows
+    LimitReached = 14,
+    /// this is not available on this server, for example because it runs without a database
+    Unavailable = 15,
+    /// the server, a plugin or a script refused the request, the detail text may say why
+    Rejected = 16,
+    /// a two-factor authentication code is required
+    TwoFactorRequired = 17,
+    /// someone else changed the same thing first, the client should refresh what it shows
+    Conflict = 18,
+    /// someone else is using this name or account on a standalone server
+    NameTaken = 19,
+}
+
+impl ErrorCode {
+    /// Whether the same request might succeed if it's sent again later, without the player changing anything.
+    pub const fn is_transient(self) -> bool {
+        matches!(self, Self::Internal | Self::CentralUnavailable | Self::RateLimited | Self::Busy)
+    }
+}
+
+/// Value from the central server config that admins can temporarily override on a single game server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encodable, Decodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum ConfigOverrideKey {
+    Tps = 0,
+    /// 1 to enable maintenance, 0 to disable it
+    Maintenance = 1,
+    ChatBurstLimit = 2,
+    /// in milliseconds
+    ChatBurstInterval = 3,
+}
+
+impl ConfigOverrideKey {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Tps => "tps",
+            Self::Maintenance => "maintenance",
+            Self::ChatBurstLimit => "chat burst limit",
+            Self::ChatBurstInterval => "chat burst interval",
+        }
+    }
+}
+
+/// A config value that is overridden until `expires_at`, after which it goes back to `original`.
+#[derive(Clone, Encodable, DynamicSize)]
+pub struct ConfigOverride {
+    pub key: ConfigOverrideKey,
+    pub value: u32,
+    /// the value from the central server, updated every time the config is refreshed
+    pub original: u32,
+    /// seconds since unix epoch
+    pub expires_at: i64,
+    pub author: String,
+}
+
+/// Public information about the server, for server browsers.
+#[derive(Clone, Encodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct ServerQueryInfo {
+    pub protocol: u16,
+    pub version: InlineString<16>,
+    pub player_count: u32,
+    /// amount of rooms that are listed publicly
+    pub room_count: u32,
+    pub tps: u32,
+    pub maintenance: bool,
+    pub whitelist: bool,
+}
+
+/// Why the server refused to let a player join a level.
+#[derive(Clone, Copy, Debug, Encodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum LevelJoinRejection {
+    /// the player is switching between levels too often
+    TooFast = 0,
+    /// the room is pinned to a different level
+    PinnedLevel = 1,
+}
+
+/// Platform the client runs on, as reported in feature usage and at login.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Encodable, Decodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum ClientPlatform {
+    #[default]
+    Unknown = 0,
+    Windows = 1,
+    Mac = 2,
+    Android = 3,
+    Ios = 4,
+}
+
+impl ClientPlatform {
+    pub const COUNT: usize = 5;
+    pub const ALL: [Self; Self::COUNT] = [Self::Unknown, Self::Windows, Self::Mac, Self::Android, Self::Ios];
+
+    /// Finds the platform in the `platform` string of a `LoginPacket`, such as "Android arm64-v8a (v1.6.0, Geode v3.0.0)".
+    pub fn from_login_string(platform: &str) -> Self {
+        platform
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .find_map(|word| match word.to_ascii_lowercase().as_str() {
+                "windows" => Some(Self::Windows),
+                "mac" | "macos" => Some(Self::Mac),
+                "android" => Some(Self::Android),
+                "ios" => Some(Self::Ios),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::Windows => "windows",
+            Self::Mac => "mac",
+            Self::Android => "android",
+            Self::Ios => "ios",
+        }
+    }
+}
+
+/// Features that are turned off for the platform of the client, for example because they are known to crash there.
+/// The client is told about them at login, and the server refuses them either way.
+#[derive(Serialize, Deserialize, Encodable, Decodable, StaticSize, DynamicSize, Clone, Copy, Debug, Default)]
+#[dynamic_size(as_static = true)]
+pub struct DisabledFeatures {
+    #[serde(default)]
+    pub voice: bool,
+    #[serde(default)]
+    pub chat: bool,
+}
+
+/// Which categories of settings the client has changed from their defaults.
+#[derive(Clone, Copy, Default, Decodable, StaticSize, DynamicSize, Debug)]
+#[bitfield(on = true, size = 1)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ChangedSettingsFlags {
+    pub globed: bool,
+    pub overlay: bool,
+    pub communication: bool,
+    pub level_ui: bool,
+    pub players: bool,
+}
+
+impl ChangedSettingsFlags {
+    pub const CATEGORIES: [&'static str; 5] = ["globed", "overlay", "communication", "level_ui", "players"];
+
+    /// Returns whether each category was changed, in the same order as `CATEGORIES`.
+    pub const fn as_array(&self) -> [bool; 5] {
+        [self.globed, self.overlay, self.communication, self.level_ui, self.players]
+    }
+}
+
+/// Client settings recommended by the server for the connection of a specific client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct SettingsHints {
+    /// how long to keep moving a player past their last received position before stopping them, in milliseconds
+    pub interpolation_window: u16,
+    /// bits per second for encoding voice
+    pub voice_bitrate: u32,
+    /// how many times per second to send player data
+    pub send_rate: u16,
+}
+
+/// Triage state of a player report. Open reports can be claimed by a moderator, so that others know someone is handling them,
+/// and then resolved or rejected by the moderator who claimed them.
+#[derive(Encodable, Decodable, DynamicSize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ReportState {
+    #[default]
+    Open = 0,
+    Claimed = 1,
+    Resolved = 2,
+    Rejected = 3,
+}
+
+impl ReportState {
+    pub const fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Open),
+            1 => Some(Self::Claimed),
+            2 => Some(Self::Resolved),
+            3 => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+
+    /// whether the report was resolved or rejected, and can't change anymore
+    pub const fn is_closed(self) -> bool {
+        matches!(self, Self::Resolved | Self::Rejected)
+    }
+
+    /// Whether the moderator `moderator_id` can move a report from this state to `new_state`, given who is handling it right now.
+    /// Only the moderator who claimed a report can close it, and closed reports can't be changed.
+    pub fn can_change_to(self, new_state: Self, handled_by: i32, moderator_id: i32) -> bool {
+        match (self, new_state) {
+            (Self::Open, Self::Claimed | Self::Resolved | Self::Rejected) => true,
+            (Self::Claimed, Self::Open | Self::Resolved | Self::Rejected) => handled_by == moderator_id,
+            _ => false,
+        }
+    }
+}
+
+/// A player report, kept by the central server (or the local database on standalone servers) so that every game server sees the same state.
+#[derive(Encodable, Decodable, DynamicSize, Clone)]
+pub struct PlayerReport {
+    pub id: i64,
+    pub reporter_id: i32,
+    pub reported_id: i32,
+    pub reason: String,
+    pub state: ReportState,
+    /// account ID of the moderator who claimed or closed the report, 0 if it's open
+    pub handled_by: i32,
+    /// seconds since unix epoch
+    pub created_at: i64,
+}
+
+/// Rough estimate of how far away a client is from a game server, from the closest to the furthest.
+#[derive(Encodable, Decodable, StaticSize, DynamicSize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum DistanceClass {
+    /// no database is configured, or either of the locations is not known
+    #[default]
+    Unknown = 0,
+    /// same city or a neighboring one, up to 500 km
+    Local = 1,
+    /// same country or region, up to 2000 km
+    Regional = 2,
+    /// same continent, up to 6000 km
+    Continental = 3,
+    Intercontinental = 4,
+}
+
+impl DistanceClass {
+    pub fn from_distance(kilometers: f64) -> Self {
+        if kilometers <= 500.0 {
+            Self::Local
+        } else if kilometers <= 2000.0 {
+            Self::Regional
+        } else if kilometers <= 6000.0 {
+            Self::Continental
+        } else {
+            Self::Intercontinental
+        }
+    }
+}
+
+/// Compression algorithms the client is able to decompress, sent during the handshake.
+#[derive(Encodable, Decodable, StaticSize, DynamicSize, Clone, Copy, Debug, Default)]
+#[dynamic_size(as_static = true)]
+pub struct CompressionSupport {
+    pub lz4: bool,
+}
+
+impl CompressionSupport {
+    pub const fn supports(self, algorithm: CompressionAlgorithm) -> bool {
+        match algorithm {
+            CompressionAlgorithm::None => true,
+            CompressionAlgorithm::Lz4 => self.lz4,
+        }
+    }
+}
+
+/// Compression algorithm picked by the server for large packets it sends to the client.
+#[derive(Encodable, Decodable, StaticSize, DynamicSize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum CompressionAlgorithm {
+    /// nothing gets compressed
+    #[default]
+    None = 0,
+    Lz4 = 1,
+}
@@ -4,6 +4,7 @@ pub mod crypto;
 pub mod game;
 pub mod gd;
 pub mod misc;
+pub mod role;
 pub mod room;
 pub mod user;
 
@@ -16,6 +17,7 @@ pub use esp::types::*;
 pub use game::*;
 pub use gd::*;
 pub use misc::*;
+pub use role::*;
 pub use room::*;
 pub use user::*;
 pub type LevelId = i64;
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Default, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ServerRole {
+    pub id: String,
+    pub priority: i32,
+    #[serde(default)]
+    pub badge_icon: String,
+    #[serde(default)]
+    pub name_color: String,
+    #[serde(default)]
+    pub chat_color: String,
+
+    // permissions
+    #[serde(default)]
+    pub notices: bool,
+    #[serde(default)]
+    pub notices_to_everyone: bool,
+    #[serde(default)]
+    pub kick: bool,
+    #[serde(default)]
+    pub kick_everyone: bool,
+    #[serde(default)]
+    pub mute: bool,
+    #[serde(default)]
+    pub ban: bool,
+    #[serde(default)]
+    pub edit_role: bool,
+    #[serde(default)]
+    pub admin: bool,
+    #[serde(default)]
+    pub slow_mode_exempt: bool,
+    #[serde(default)]
+    pub spawn_bots: bool,
+    #[serde(default)]
+    pub room_webhooks: bool,
+}
+
+#[derive(Encodable, Decodable, DynamicSize, Default, Clone)]
+pub struct GameServerRole {
+    pub int_id: u8,
+    pub role: ServerRole,
+}
+
+#[derive(Encodable, Decodable, DynamicSize, Default, Clone)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ComputedRole {
+    pub priority: i32,
+    pub badge_icon: FastString,
+    pub name_color: Option<RichColor>,
+    pub chat_color: Option<Color3B>,
+
+    pub notices: bool,
+    pub notices_to_everyone: bool,
+    pub kick: bool,
+    pub kick_everyone: bool,
+    pub mute: bool,
+    pub ban: bool,
+    pub edit_role: bool,
+    pub admin: bool,
+    pub slow_mode_exempt: bool,
+    pub spawn_bots: bool,
+    pub room_webhooks: bool,
+
+    /// bit `n` is set if the user has the role of int ID `n`, roles past the 64th are not included
+    pub role_mask: u64,
+}
+
+impl ComputedRole {
+    pub fn can_moderate(&self) -> bool {
+        self.notices || self.notices_to_everyone || self.kick || self.kick_everyone || self.mute || self.ban || self.edit_role || self.admin
+    }
+
+    /// Whether the role can send chat messages without waiting for the slow mode of a room or level
+    pub fn bypasses_slow_mode(&self) -> bool {
+        self.slow_mode_exempt || self.can_moderate()
+    }
+
+    /// Whether the role has permissions that would be catastrophic in the wrong hands, requiring two-factor authentication
+    pub fn has_dangerous_perms(&self) -> bool {
+        self.ban || self.edit_role || self.admin
+    }
+}
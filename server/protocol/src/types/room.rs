@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+#[derive(Clone, Copy, Default, Encodable, Decodable, StaticSize, DynamicSize, Debug)]
+#[bitfield(on = true, size = 2)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct RoomSettingsFlags {
+    pub is_hidden: bool,
+    pub public_invites: bool,
+    pub collision: bool,
+    pub two_player: bool,
+    /// don't show anyone in the room as being on an editor level, even if they chose to share it
+    pub hide_editor_levels: bool,
+    /// don't show anyone in the room as being on an unlisted level
+    pub hide_unlisted_levels: bool,
+    /// only the owner and the moderators of the room can send chat messages
+    pub chat_muted: bool,
+}
+
+#[derive(Clone, Copy, Default, Encodable, Decodable, StaticSize, DynamicSize, Debug)]
+#[dynamic_size(as_static = true)]
+pub struct RoomSettings {
+    pub flags: RoomSettingsFlags,
+    pub player_limit: u32,
+    pub chat_filter: RoomChatFilter,
+    /// how many seconds players have to wait between chat messages, 0 if disabled
+    pub slow_mode: u32,
+    /// the only level players in the room are allowed to join, 0 if they can join any level
+    pub pinned_level: LevelId,
+    /// bit `n` is set if players with the role of int ID `n` can join the room, 0 if anyone can join
+    pub allowed_roles: u64,
+    /// who can use voice chat in the room and who hears them
+    pub voice_policy: RoomVoicePolicy,
+}
+
+impl RoomSettings {
+    /// Keeps the chat settings within the bounds allowed by the server.
+    pub fn clamp_chat(&mut self, policy: &ChatFilterPolicy) {
+        if let Some(level) = self.chat_filter.level() {
+            self.chat_filter = RoomChatFilter::from(policy.room_level(Some(level)));
+        }
+
+        self.slow_mode = self.slow_mode.min(policy.max_room_slow_mode);
+    }
+
+    /// Whether players in the room are allowed to join the given level.
+    pub const fn allows_level(&self, level_id: LevelId) -> bool {
+        self.pinned_level == 0 || self.pinned_level == level_id
+    }
+
+    /// Whether a player with the given roles (see [`crate::ComputedRole::role_mask`]) is allowed to join the room.
+    pub const fn allows_roles(&self, role_mask: u64) -> bool {
+        self.allowed_roles == 0 || self.allowed_roles & role_mask != 0
+    }
+
+    /// Whether a player with the given preferences should be shown to others as not being on any level while on this level.
+    /// Editor levels are hidden unless the player chose to share them, unlisted levels are shown unless the player
+    /// chose to hide them, and the room can hide either kind regardless of what the player picked.
+    pub fn hides_level(&self, level_id: LevelId, unlisted: bool, preferences: &UserPreferences) -> bool {
+        let hide_editor = !preferences.share_editor_levels || self.flags.hide_editor_levels;
+        let hide_unlisted = preferences.hide_unlisted_levels || self.flags.hide_unlisted_levels;
+
+        (is_editorcollab_level(level_id) && hide_editor) || (unlisted && hide_unlisted)
+    }
+}
+
+/// The chat filter picked by the room owner.
+#[derive(Clone, Copy, Default, Encodable, Decodable, StaticSize, DynamicSize, Debug, PartialEq, Eq)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum RoomChatFilter {
+    /// the default level of the server is used
+    #[default]
+    ServerDefault = 0,
+    Off = 1,
+    Standard = 2,
+    Strict = 3,
+}
+
+impl RoomChatFilter {
+    /// Returns the picked filter level, or none if the room uses the server default.
+    pub const fn level(self) -> Option<ChatFilterLevel> {
+        match self {
+            Self::ServerDefault => None,
+            Self::Off => Some(ChatFilterLevel::Off),
+            Self::Standard => Some(ChatFilterLevel::Standard),
+            Self::Strict => Some(ChatFilterLevel::Strict),
+        }
+    }
+
+    pub const fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Off,
+            2 => Self::Standard,
+            3 => Self::Strict,
+            _ => Self::ServerDefault,
+        }
+    }
+}
+
+impl From<ChatFilterLevel> for RoomChatFilter {
+    fn from(value: ChatFilterLevel) -> Self {
+        match value {
+            ChatFilterLevel::Off => Self::Off,
+            ChatFilterLevel::Standard => Self::Standard,
+            ChatFilterLevel::Strict => Self::Strict,
+        }
+    }
+}
+
+/// How far away (in units) a player can be from someone talking and still hear them, in rooms with proximity voice chat.
+pub const PROXIMITY_VOICE_RANGE: f32 = 600.0;
+
+/// Who can use voice chat in a room, picked by the room owner.
+#[derive(Clone, Copy, Default, Encodable, Decodable, StaticSize, DynamicSize, Debug, PartialEq, Eq)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum RoomVoicePolicy {
+    /// anyone can talk to everyone on the same level
+    #[default]
+    Everyone = 0,
+    /// only the owner and the moderators of the room can talk
+    ManagersOnly = 1,
+    /// nobody can talk
+    Disabled = 2,
+    /// anyone can talk, but only players within `PROXIMITY_VOICE_RANGE` of them hear it
+    Proximity = 3,
+}
+
+impl RoomVoicePolicy {
+    /// Whether a player can talk at all, `manages_room` is whether they are the owner or a moderator of the room.
+    pub const fn allows_speaker(self, manages_room: bool) -> bool {
+        match self {
+            Self::Everyone | Self::Proximity => true,
+            Self::ManagersOnly => manages_room,
+            Self::Disabled => false,
+        }
+    }
+
+    pub const fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::ManagersOnly,
+            2 => Self::Disabled,
+            3 => Self::Proximity,
+            _ => Self::Everyone,
+        }
+    }
+}
+
+#[derive(Clone, Encodable, Decodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct RoomInfo {
+    pub id: u32,
+    pub owner: PlayerPreviewAccountData,
+    pub name: InlineString<32>,
+    pub password: InlineString<16>,
+    pub settings: RoomSettings,
+}
+
+/// A tag the owner of a room defined for organizing events, such as "host" or "racer". Unrelated to server roles.
+#[derive(Clone, Encodable, Decodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct RoomTag {
+    pub name: InlineString<MAX_ROOM_TAG_NAME_SIZE>,
+    pub color: Color3B,
+}
+
+#[derive(Clone, Encodable, Decodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct RoomMemberTag {
+    pub account_id: i32,
+    /// index of the tag in `RoomTags::tags`
+    pub tag: u8,
+}
+
+/// The tags of a room and the players that have them, every player has at most one tag.
+#[derive(Clone, Default, Encodable, Decodable, DynamicSize)]
+pub struct RoomTags {
+    pub tags: Vec<RoomTag>,
+    pub members: Vec<RoomMemberTag>,
+}
+
+/// The room a player was in before being moved here from another game server.
+#[derive(Clone, Encodable, Decodable, DynamicSize)]
+pub struct TransferredRoom {
+    /// random ID shared by all players that were moved from the same room, so that they end up in the same room again
+    pub group: u64,
+    pub owner: i32,
+    pub moderators: Vec<i32>,
+    pub tags: RoomTags,
+    pub name: InlineString<32>,
+    pub password: InlineString<16>,
+    pub settings: RoomSettings,
+}
+
+#[derive(Clone, Encodable, Decodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct RoomListingInfo {
+    pub id: u32,
+    pub owner: PlayerPreviewAccountData,
+    pub name: InlineString<32>,
+    pub has_password: bool,
+    pub settings: RoomSettings,
+}
+
+/// How strictly chat messages are checked against the word filter.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum ChatFilterLevel {
+    /// messages aren't checked
+    #[default]
+    #[serde(rename = "off")]
+    Off = 0,
+    /// filtered words are replaced with asterisks
+    #[serde(rename = "standard")]
+    Standard = 1,
+    /// messages with filtered words are rejected, also when the words are split up with spaces or symbols
+    #[serde(rename = "strict")]
+    Strict = 2,
+}
+
+/// The chat filter used in the global room and in rooms that don't change it, and the bounds that room owners
+/// have to stay within when changing the chat settings of their room.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct ChatFilterPolicy {
+    pub default_level: ChatFilterLevel,
+    pub min_room_level: ChatFilterLevel,
+    /// the longest slow mode a room can have, in seconds
+    pub max_room_slow_mode: u32,
+}
+
+impl Default for ChatFilterPolicy {
+    fn default() -> Self {
+        Self {
+            default_level: ChatFilterLevel::Standard,
+            min_room_level: ChatFilterLevel::Off,
+            max_room_slow_mode: 60,
+        }
+    }
+}
+
+impl ChatFilterPolicy {
+    /// Returns the filter level a room ends up with, `requested` being none if the room uses the server default.
+    pub fn room_level(&self, requested: Option<ChatFilterLevel>) -> ChatFilterLevel {
+        requested.map_or(self.default_level, |level| level.max(self.min_room_level))
+    }
+}
@@ -0,0 +1,173 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// Structure that can represent either a single RGB color, or a combination of colors
+#[derive(Clone, Encodable, Decodable, StaticSize, DynamicSize)]
+pub struct RichColor {
+    pub color: Either<Color3B, FastVec<Color3B, 8>>,
+}
+
+impl RichColor {
+    pub const fn new_with_one(color: Color3B) -> Self {
+        Self {
+            color: Either::new_first(color),
+        }
+    }
+
+    pub fn new_with_multiple(colors: Vec<Color3B>) -> Self {
+        Self {
+            color: Either::new_second(colors.try_into().expect("failed to convert Vec into FastVec for RichColor")),
+        }
+    }
+}
+
+impl FromStr for RichColor {
+    type Err = ColorParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // if it's a single color, try to just do that
+        if !s.contains('>') {
+            let col = s.parse::<Color3B>()?;
+            return Ok(Self::new_with_one(col));
+        }
+
+        let s = s.strip_prefix('#').unwrap_or(s);
+
+        let mut pieces = s.split('>').map(|x| x.trim().parse::<Color3B>());
+
+        match pieces.find(|x| x.is_err()) {
+            Some(err) => Err(err.unwrap_err()),
+            None => Ok(Self::new_with_multiple(pieces.map(|x| x.unwrap()).collect())),
+        }
+    }
+}
+
+#[derive(Encodable, Decodable, Serialize, Deserialize, DynamicSize, Clone, Default)]
+pub struct UserEntry {
+    pub account_id: i32,
+    pub user_name: Option<String>,
+    pub name_color: Option<String>,
+    pub user_roles: Vec<String>,
+    pub is_banned: bool,
+    pub is_muted: bool,
+    pub is_whitelisted: bool,
+    pub admin_password: Option<String>,
+    pub totp_secret: Option<String>, // base32, only used for the admin panel login
+    pub violation_reason: Option<String>,
+    pub violation_expiry: Option<i64>, // seconds since unix epoch
+    pub first_seen: Option<i64>,       // seconds since unix epoch, when the account first authenticated with the central server
+    #[serde(default)]
+    pub violation_count: u32, // how many times the user has been banned or muted
+    #[serde(default)]
+    pub timed_roles: Vec<TimedRole>,
+    #[serde(default)]
+    pub preferences: UserPreferences,
+}
+
+impl UserEntry {
+    pub fn new(account_id: i32) -> Self {
+        Self {
+            account_id,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the IDs of all roles the user has at the given time (seconds since unix epoch), both permanent and timed ones.
+    pub fn active_roles(&self, now: i64) -> Vec<String> {
+        let mut roles = self.user_roles.clone();
+
+        for timed in self.timed_roles.iter().filter(|x| x.is_active(now)) {
+            if !roles.contains(&timed.role_id) {
+                roles.push(timed.role_id.clone());
+            }
+        }
+
+        roles
+    }
+
+    /// Returns the IDs of all roles assigned to the user, including timed roles that aren't active right now.
+    pub fn assigned_roles(&self) -> Vec<String> {
+        self.user_roles
+            .iter()
+            .chain(self.timed_roles.iter().map(|x| &x.role_id))
+            .cloned()
+            .collect()
+    }
+}
+
+/// A role that a user only has for a limited time (for example a one-month supporter badge), or only starting from a specific time.
+#[derive(Encodable, Decodable, Serialize, Deserialize, DynamicSize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimedRole {
+    pub role_id: String,
+    pub starts_at: Option<i64>,  // seconds since unix epoch, none if the role is active right away
+    pub expires_at: Option<i64>, // seconds since unix epoch, none if the role never expires
+}
+
+impl TimedRole {
+    pub fn is_active(&self, now: i64) -> bool {
+        self.starts_at.is_none_or(|x| now >= x) && !self.has_expired(now)
+    }
+
+    pub fn has_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|x| now >= x)
+    }
+}
+
+/// Preferences the player sets for their own account. They are saved with the account, so they follow the player to every device they log in from.
+#[derive(Encodable, Decodable, Serialize, Deserialize, DynamicSize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct UserPreferences {
+    /// hide the player from player lists, same as logging in as invisible
+    pub hide_in_lists: bool,
+    /// don't deliver room invites to the player
+    pub block_invites: bool,
+    /// don't accept direct messages
+    pub disable_dms: bool,
+    /// show the level to others while in the editor, editor levels are hidden by default so WIP level IDs don't leak
+    pub share_editor_levels: bool,
+    /// don't show the level to others while on an unlisted level
+    pub hide_unlisted_levels: bool,
+    /// don't show others how many players are spectating you
+    pub hide_spectator_count: bool,
+    /// language code, for example `en` or `pt-BR`
+    pub language: Option<String>,
+}
+
+impl UserPreferences {
+    pub const MAX_LANGUAGE_LEN: usize = 8;
+
+    /// Drops the language if it doesn't look like a language code.
+    pub fn sanitize(&mut self) {
+        let valid = self.language.as_deref().is_none_or(|lang| {
+            !lang.is_empty() && lang.len() <= Self::MAX_LANGUAGE_LEN && lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        });
+
+        if !valid {
+            self.language = None;
+        }
+    }
+}
+
+/// A login of an account on a game server. Game servers report them to the central server, which keeps the active ones
+/// along with the last few that ended, so that players can see where they are logged in.
+#[derive(Encodable, Decodable, DynamicSize, Clone, Debug)]
+pub struct AccountSession {
+    pub account_id: i32,
+    /// random, picked by the game server when the player logs in
+    pub session_id: u64,
+    pub platform: ClientPlatform,
+    /// the network the player connected from (like `1.2.3.0/24`), never the full address
+    pub ip_prefix: String,
+    /// unix timestamps, `logout_time` is 0 while the session is still active
+    pub login_time: i64,
+    pub logout_time: i64,
+}
+
+impl AccountSession {
+    pub fn is_active(&self) -> bool {
+        self.logout_time == 0
+    }
+}
@@ -2,20 +2,117 @@ use super::*;
 use esp::FastString;
 use serde::{Deserialize, Serialize};
 
-#[derive(Encodable, Decodable, Clone)]
+// types that are also sent to clients live in the protocol crate
+pub use globed_protocol::{
+    AccountSession, ChatFilterLevel, ChatFilterPolicy, ClientPlatform, CosmeticHash, DisabledFeatures, PlayerReport, ReportState, ServerRole,
+    TimedRole, UserEntry, UserPreferences,
+};
+
+/// Seconds without any activity before a player is considered AFK, unless the central server is configured otherwise.
+pub const DEFAULT_AFK_TIMEOUT: u32 = 300;
+
+/// Sent to game servers in the binary format, or as JSON when they ask for it (see `BOOT_DATA_FORMATS`).
+/// Missing fields in JSON get their default values, so other central server implementations only need to send what they use.
+#[derive(Encodable, Decodable, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct GameServerBootData {
     pub protocol: u16,
     pub tps: u32,
     pub maintenance: bool,
-    pub secret_key2: String,
+    #[serde(with = "plain_secret")]
+    pub secret_key2: Secret,
+    #[serde(with = "plain_secret_list")]
+    pub previous_secret_keys2: Vec<Secret>,
     pub token_expiry: u64,
     pub status_print_interval: u64,
+    pub player_history_interval: u64,
+    pub player_history_size: u32,
+    #[serde(with = "fast_string")]
     pub admin_key: FastString,
+    pub require_totp: bool,
     pub whitelist: bool,
-    pub admin_webhook_url: String,
+    #[serde(with = "plain_secret")]
+    pub admin_webhook_url: Secret,
     pub chat_burst_limit: u32,
     pub chat_burst_interval: u32,
     pub roles: Vec<ServerRole>,
+    /// bumped by the central server every time the role definitions change
+    pub roles_revision: u64,
+    pub trust_account_age: u32,
+    pub min_trust_create_room: TrustTier,
+    pub min_trust_invite: TrustTier,
+    pub min_trust_voice: TrustTier,
+    pub chat_link_policy: ChatLinkPolicy,
+    pub duplicate_login_policy: DuplicateLoginPolicy,
+    pub blocked_levels: Vec<i64>,
+    /// addresses (`1.2.3.4`) and ranges (`1.2.3.0/24`) that can't connect to game servers
+    pub banned_ips: Vec<String>,
+    pub chat_filter_policy: ChatFilterPolicy,
+    pub chat_word_filter: ChatWordFilter,
+    pub name_policy: NamePolicy,
+    /// content hashes of custom cosmetics that players are allowed to show to others
+    pub approved_cosmetics: Vec<CosmeticHash>,
+    /// seconds without any activity before a player is considered AFK, 0 disables AFK detection
+    pub afk_timeout: u32,
+    /// seconds after the AFK warning before the player is moved out of a full room, 0 to never do that
+    pub afk_room_grace: u32,
+    pub platform_features: PlatformFeaturePolicy,
+    pub player_state_policy: PlayerStatePolicy,
+    pub room_quota: RoomQuotaPolicy,
+    /// seconds of voice chat kept for every player, so it can be attached to reports against them. 0 disables it
+    pub voice_capture_seconds: u32,
+    /// public key that voice captures are encrypted for, nothing is captured without it
+    pub voice_capture_key: Option<[u8; 32]>,
+}
+
+/// Media types of the boot data and user entries, sent by game servers in the `Accept` header.
+/// The binary format is preferred, JSON is there so other central server implementations don't have to implement it.
+pub const BOOT_DATA_FORMATS: &str = "application/octet-stream, application/json;q=0.5";
+
+/* the secrets in the boot data are sent as their values, and never resolved as `file:`/`env:` references when received */
+
+mod plain_secret {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(secret: &Secret, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(secret.expose())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Secret, D::Error> {
+        Ok(Secret::new(String::deserialize(deserializer)?))
+    }
+}
+
+mod plain_secret_list {
+    use super::*;
+    use serde::{ser::SerializeSeq, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(secrets: &[Secret], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(secrets.len()))?;
+        for secret in secrets {
+            seq.serialize_element(secret.expose())?;
+        }
+
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Secret>, D::Error> {
+        Ok(Vec::<String>::deserialize(deserializer)?.into_iter().map(Secret::new).collect())
+    }
+}
+
+mod fast_string {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(string: &FastString, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(string.try_to_str())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<FastString, D::Error> {
+        Ok(FastString::new(&String::deserialize(deserializer)?))
+    }
 }
 
 impl Default for GameServerBootData {
@@ -29,69 +126,415 @@ impl Default for GameServerBootData {
             protocol: PROTOCOL_VERSION,
             tps: 30,
             maintenance: false,
-            secret_key2: String::new(),
+            secret_key2: Secret::default(),
+            previous_secret_keys2: Vec::new(),
             token_expiry: 0,
             status_print_interval,
+            player_history_interval: 60,
+            player_history_size: 1440,
             admin_key: generate_alphanum_string(ADMIN_KEY_LENGTH).into(),
+            require_totp: false,
             whitelist: false,
-            admin_webhook_url: String::new(),
+            admin_webhook_url: Secret::default(),
             chat_burst_limit: 0,
             chat_burst_interval: 0,
             roles: Vec::new(),
+            roles_revision: 0,
+            trust_account_age: 0,
+            min_trust_create_room: TrustTier::Flagged,
+            min_trust_invite: TrustTier::Flagged,
+            min_trust_voice: TrustTier::Flagged,
+            chat_link_policy: ChatLinkPolicy::default(),
+            duplicate_login_policy: DuplicateLoginPolicy::default(),
+            blocked_levels: Vec::new(),
+            banned_ips: Vec::new(),
+            chat_filter_policy: ChatFilterPolicy::default(),
+            chat_word_filter: ChatWordFilter::default(),
+            name_policy: NamePolicy::default(),
+            approved_cosmetics: Vec::new(),
+            afk_timeout: DEFAULT_AFK_TIMEOUT,
+            afk_room_grace: 0,
+            platform_features: PlatformFeaturePolicy::default(),
+            player_state_policy: PlayerStatePolicy::default(),
+            room_quota: RoomQuotaPolicy::default(),
+            voice_capture_seconds: 0,
+            voice_capture_key: None,
         }
     }
 }
 
-#[derive(Encodable, Decodable, Serialize, Deserialize, DynamicSize, Clone, Default)]
-pub struct UserEntry {
+/// Handed from one game server to another when a player is moved between them. The central server keeps it until the new server redeems it.
+#[derive(Encodable, Decodable, DynamicSize, Clone)]
+pub struct TransferTicket {
     pub account_id: i32,
-    pub user_name: Option<String>,
-    pub name_color: Option<String>,
-    pub user_roles: Vec<String>,
-    pub is_banned: bool,
-    pub is_muted: bool,
-    pub is_whitelisted: bool,
-    pub admin_password: Option<String>,
-    pub violation_reason: Option<String>,
-    pub violation_expiry: Option<i64>, // seconds since unix epoch
-}
-
-impl UserEntry {
-    pub fn new(account_id: i32) -> Self {
-        Self {
-            account_id,
-            ..Default::default()
+    /// encoded by the game server, the central server doesn't look into it
+    pub payload: Vec<u8>,
+}
+
+/// Sent by a game server when a moderator changes the state of a report.
+#[derive(Encodable, Decodable, DynamicSize, Clone)]
+pub struct ReportStateChange {
+    pub id: i64,
+    pub moderator_id: i32,
+    pub state: ReportState,
+}
+
+/// How much a user is trusted, computed on login. Servers can require a minimum tier for features that are commonly abused by throwaway accounts.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum TrustTier {
+    /// a new account that has already been banned or muted before
+    #[serde(rename = "flagged")]
+    Flagged = 0,
+    /// an account younger than `trust_account_age`, or an older account with prior violations
+    #[serde(rename = "new")]
+    New = 1,
+    /// an account older than `trust_account_age` without any prior violations
+    #[serde(rename = "established")]
+    Established = 2,
+    /// a whitelisted account or an account with a role
+    #[default]
+    #[serde(rename = "trusted")]
+    Trusted = 3,
+}
+
+impl TrustTier {
+    /// Computes the trust tier of the user. `account_age` is the amount of days an account must have existed for to be considered established.
+    pub fn compute(user: &UserEntry, account_age: u32, now: i64) -> Self {
+        if user.is_whitelisted || !user.active_roles(now).is_empty() {
+            return Self::Trusted;
+        }
+
+        let established = user
+            .first_seen
+            .is_some_and(|first_seen| now.saturating_sub(first_seen) >= i64::from(account_age) * 86400);
+
+        match (established, user.violation_count > 0) {
+            (true, false) => Self::Established,
+            (true, true) | (false, false) => Self::New,
+            (false, true) => Self::Flagged,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Default)]
-#[allow(clippy::struct_excessive_bools)]
-pub struct ServerRole {
-    pub id: String,
-    pub priority: i32,
+/// What happens to chat messages that contain links (URLs or Discord invites).
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum LinkAction {
+    /// the message is sent unchanged
+    #[default]
+    #[serde(rename = "allow")]
+    Allow = 0,
+    /// links are removed from the message, and the rest of it is sent
+    #[serde(rename = "strip")]
+    Strip = 1,
+    /// the whole message is rejected
+    #[serde(rename = "block")]
+    Block = 2,
+    /// the message is sent unchanged, but reported to the admin webhook
+    #[serde(rename = "flag")]
+    Flag = 3,
+}
+
+/// The `LinkAction` used for every trust tier, so that for example new accounts can be prevented from posting scam links.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Copy, Debug, Default)]
+pub struct ChatLinkPolicy {
     #[serde(default)]
-    pub badge_icon: String,
+    pub flagged: LinkAction,
     #[serde(default)]
-    pub name_color: String,
+    pub new: LinkAction,
     #[serde(default)]
-    pub chat_color: String,
+    pub established: LinkAction,
+    #[serde(default)]
+    pub trusted: LinkAction,
+}
+
+impl ChatLinkPolicy {
+    pub fn for_tier(&self, tier: TrustTier) -> LinkAction {
+        match tier {
+            TrustTier::Flagged => self.flagged,
+            TrustTier::New => self.new,
+            TrustTier::Established => self.established,
+            TrustTier::Trusted => self.trusted,
+        }
+    }
+}
+
+/// What happens to chat messages that match the `ChatWordFilter`.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum WordFilterAction {
+    /// matches are replaced with asterisks, and the rest of the message is sent
+    #[default]
+    #[serde(rename = "censor")]
+    Censor = 0,
+    /// the whole message is rejected
+    #[serde(rename = "drop")]
+    Drop = 1,
+    /// the message is rejected and the player is muted for `mute_duration`
+    #[serde(rename = "mute")]
+    Mute = 2,
+}
+
+/// Words and regular expressions that are filtered in chat on every game server. Unlike the word list of a game server,
+/// they apply in every room regardless of its chat filter level.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Debug)]
+#[serde(default)]
+pub struct ChatWordFilter {
+    /// matched anywhere in the message, ignoring case
+    pub words: Vec<String>,
+    /// regular expressions, ignoring case
+    pub patterns: Vec<String>,
+    pub action: WordFilterAction,
+    /// how long players are muted for with the `mute` action, in seconds
+    pub mute_duration: u32,
+}
+
+impl Default for ChatWordFilter {
+    fn default() -> Self {
+        Self {
+            words: Vec::new(),
+            patterns: Vec::new(),
+            action: WordFilterAction::Censor,
+            mute_duration: 600,
+        }
+    }
+}
+
+/// What happens once a player fails the player state checks too often on a level.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum StateViolationAction {
+    /// moderators are told through the admin webhook, the player data is still sent to others
+    #[default]
+    #[serde(rename = "flag")]
+    Flag = 0,
+    /// moderators are told, and player data that fails the checks is not sent to others anymore
+    #[serde(rename = "suppress")]
+    Suppress = 1,
+    /// moderators are told, and the player is disconnected
+    #[serde(rename = "kick")]
+    Kick = 2,
+}
+
+/// Plausibility checks of the player data sent by clients, all of them are off by default.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct PlayerStatePolicy {
+    /// how many units per second a player can move at most, by the timestamps in their player data. 0 disables the check
+    pub max_speed: f32,
+    /// whether the percentage has to be between 0 and 100, and during races (after a room countdown) can only go down after dying or restarting the level
+    pub check_percentage: bool,
+    /// how many failed checks on a single level are tolerated before `action` is taken
+    pub max_violations: u32,
+    pub action: StateViolationAction,
+}
+
+impl PlayerStatePolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.max_speed > 0.0 || self.check_percentage
+    }
+}
 
-    // permissions
+impl Default for PlayerStatePolicy {
+    fn default() -> Self {
+        Self {
+            max_speed: 0.0,
+            check_percentage: false,
+            max_violations: 5,
+            action: StateViolationAction::Flag,
+        }
+    }
+}
+
+/// How many rooms a single account can have and create, and how many invites it can have waiting. 0 means no limit.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct RoomQuotaLimits {
+    /// rooms the account can own at the same time, which matters for players logged in from multiple devices
+    pub max_owned_rooms: u32,
+    /// rooms the account can create in an hour
+    pub creations_per_hour: u32,
+    /// invites the account can have sent that weren't accepted and didn't expire yet
+    pub max_outstanding_invites: u32,
+}
+
+/// Limits for the players with a specific role, instead of the default ones.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Debug, Default)]
+pub struct RoomQuotaOverride {
+    pub role: String,
     #[serde(default)]
-    pub notices: bool,
+    pub limits: RoomQuotaLimits,
+}
+
+/// Limits on room creation, so a single account can't fill the room list with junk rooms. Everything is unlimited by default.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct RoomQuotaPolicy {
+    pub default: RoomQuotaLimits,
+    pub role_overrides: Vec<RoomQuotaOverride>,
+}
+
+/// The features that are turned off on every platform. Clients that don't say which platform they are on get everything.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Copy, Debug, Default)]
+pub struct PlatformFeaturePolicy {
     #[serde(default)]
-    pub notices_to_everyone: bool,
+    pub windows: DisabledFeatures,
     #[serde(default)]
-    pub kick: bool,
+    pub mac: DisabledFeatures,
     #[serde(default)]
-    pub kick_everyone: bool,
+    pub android: DisabledFeatures,
     #[serde(default)]
-    pub mute: bool,
+    pub ios: DisabledFeatures,
+}
+
+impl PlatformFeaturePolicy {
+    pub fn for_platform(&self, platform: ClientPlatform) -> DisabledFeatures {
+        match platform {
+            ClientPlatform::Unknown => DisabledFeatures::default(),
+            ClientPlatform::Windows => self.windows,
+            ClientPlatform::Mac => self.mac,
+            ClientPlatform::Android => self.android,
+            ClientPlatform::Ios => self.ios,
+        }
+    }
+}
+
+/// What happens when someone logs into an account that is already logged in on the server.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DuplicateLoginAction {
+    /// the older session is disconnected
+    #[default]
+    #[serde(rename = "kick_old")]
+    KickOld = 0,
+    /// the new login is refused
+    #[serde(rename = "reject_new")]
+    RejectNew = 1,
+}
+
+/// Decides what to do when an account logs in from multiple places. Users with any of `concurrent_roles`
+/// can have up to `max_concurrent_sessions` sessions at once before `action` is applied.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Debug, Default)]
+pub struct DuplicateLoginPolicy {
     #[serde(default)]
-    pub ban: bool,
+    pub action: DuplicateLoginAction,
     #[serde(default)]
-    pub edit_role: bool,
+    pub concurrent_roles: Vec<String>,
     #[serde(default)]
-    pub admin: bool,
+    pub max_concurrent_sessions: u32,
+}
+
+impl DuplicateLoginPolicy {
+    /// Returns how many sessions a user with the given roles can have at once, always at least 1.
+    pub fn session_limit(&self, roles: &[String]) -> usize {
+        if roles.iter().any(|role| self.concurrent_roles.contains(role)) {
+            self.max_concurrent_sessions.max(1) as usize
+        } else {
+            1
+        }
+    }
+}
+
+/// Why a player name was rejected by the `NamePolicy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameRejection {
+    /// the name contains a blocked substring
+    Blocked,
+    /// the name contains invisible characters or characters that change the text direction
+    InvisibleChars,
+    /// the name is empty or only consists of whitespace
+    Blank,
+}
+
+/// What the `NamePolicy` decided about a player name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NameCheck {
+    Allowed,
+    /// the name is allowed, but should be reported to the moderators. Contains the substring that matched
+    Flagged(String),
+    Rejected(NameRejection),
+}
+
+/// Rules that player names are checked against when logging in. Substrings are matched case insensitively,
+/// after removing invisible characters, so they can't be used to sneak past the blocklist.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Debug)]
+#[serde(default)]
+pub struct NamePolicy {
+    /// players with any of these in their name can't log in
+    pub blocked_substrings: Vec<String>,
+    /// players with any of these in their name can log in, but are reported to the admin webhook
+    pub flagged_substrings: Vec<String>,
+    /// reject names with zero-width characters or text direction overrides
+    pub reject_invisible_chars: bool,
+    /// reject names that are empty or only made of whitespace
+    pub reject_blank: bool,
+}
+
+impl Default for NamePolicy {
+    fn default() -> Self {
+        Self {
+            blocked_substrings: Vec::new(),
+            flagged_substrings: Vec::new(),
+            reject_invisible_chars: true,
+            reject_blank: true,
+        }
+    }
+}
+
+impl NamePolicy {
+    pub fn check(&self, name: &str) -> NameCheck {
+        if self.reject_blank && name.chars().all(|c| c.is_whitespace() || is_invisible_char(c)) {
+            return NameCheck::Rejected(NameRejection::Blank);
+        }
+
+        if self.reject_invisible_chars && name.chars().any(is_invisible_char) {
+            return NameCheck::Rejected(NameRejection::InvisibleChars);
+        }
+
+        let normalized = name.chars().filter(|c| !is_invisible_char(*c)).collect::<String>().to_lowercase();
+        let find = |list: &[String]| list.iter().find(|x| !x.is_empty() && normalized.contains(&x.to_lowercase())).cloned();
+
+        if find(&self.blocked_substrings).is_some() {
+            NameCheck::Rejected(NameRejection::Blocked)
+        } else if let Some(matched) = find(&self.flagged_substrings) {
+            NameCheck::Flagged(matched)
+        } else {
+            NameCheck::Allowed
+        }
+    }
+}
+
+/// Zero-width characters, soft hyphens and characters that change the direction of the text.
+const fn is_invisible_char(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00ad}' | '\u{061c}' | '\u{180e}' | '\u{200b}'..='\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2060}'..='\u{2069}' | '\u{feff}'
+    )
+}
+
+/// Role definitions sent to game servers when they change, without the rest of the boot data.
+#[derive(Encodable, Decodable, DynamicSize, Clone, Default)]
+pub struct RoleDefinitions {
+    pub revision: u64,
+    pub roles: Vec<ServerRole>,
+}
+
+/// Parses 32 bytes written as 64 hex characters, like hashes and keys are in the central server config.
+pub fn bytes32_from_hex(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let mut bytes = [0u8; 32];
+    for (byte, chunk) in bytes.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?;
+    }
+
+    Some(bytes)
+}
+
+/// Parses a cosmetic hash written as 64 hex characters, like they are in the central server config.
+pub fn cosmetic_hash_from_hex(hex: &str) -> Option<CosmeticHash> {
+    bytes32_from_hex(hex)
 }
@@ -0,0 +1,84 @@
+//! Approximate locations of IP addresses, used to recommend the closest game server to players.
+//! Lookups only work when built with the `geoip` feature, otherwise the database can't be opened.
+
+use std::net::IpAddr;
+
+use crate::*;
+pub use globed_protocol::DistanceClass;
+
+/// Earth's mean radius, in kilometers.
+const EARTH_RADIUS: f64 = 6371.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Coordinates {
+    /// Parses coordinates in the `latitude,longitude` format, for example `52.52,13.40`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (latitude, longitude) = value.split_once(',')?;
+        let latitude: f64 = latitude.trim().parse().ok()?;
+        let longitude: f64 = longitude.trim().parse().ok()?;
+
+        ((-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude)).then_some(Self { latitude, longitude })
+    }
+
+    /// Great-circle distance between the two points, in kilometers.
+    pub fn distance_to(&self, other: &Coordinates) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+
+        2.0 * EARTH_RADIUS * a.sqrt().asin()
+    }
+}
+
+/// A MaxMind (`.mmdb`) city database, such as GeoLite2 City.
+pub struct GeoIpDatabase {
+    #[cfg(feature = "geoip")]
+    reader: maxminddb::Reader<Vec<u8>>,
+}
+
+impl GeoIpDatabase {
+    #[cfg(feature = "geoip")]
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            reader: maxminddb::Reader::open_readfile(path)?,
+        })
+    }
+
+    #[cfg(not(feature = "geoip"))]
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        let _ = path;
+        anyhow::bail!("the server was built without the `geoip` feature")
+    }
+
+    /// Returns the approximate location of the address, if it's in the database.
+    #[cfg(feature = "geoip")]
+    pub fn lookup(&self, address: IpAddr) -> Option<Coordinates> {
+        let city: maxminddb::geoip2::City = self.reader.lookup(address).ok()?;
+        let location = city.location?;
+
+        Some(Coordinates {
+            latitude: location.latitude?,
+            longitude: location.longitude?,
+        })
+    }
+
+    #[cfg(not(feature = "geoip"))]
+    pub fn lookup(&self, address: IpAddr) -> Option<Coordinates> {
+        let _ = address;
+        None
+    }
+
+    /// Estimates how far away the address is from the given location.
+    pub fn distance_class(&self, from: &Coordinates, address: IpAddr) -> DistanceClass {
+        self.lookup(address).map_or(DistanceClass::Unknown, |location| {
+            DistanceClass::from_distance(from.distance_to(&location))
+        })
+    }
+}
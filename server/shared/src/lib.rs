@@ -15,11 +15,13 @@ pub use nohash_hasher::{IntMap, IntSet};
 pub use parking_lot::{Mutex as SyncMutex, MutexGuard as SyncMutexGuard};
 // module reexports
 pub use anyhow;
+pub use argon2;
 pub use base64;
 pub use colored;
 pub use crypto_box;
 pub use crypto_secretbox;
 pub use esp;
+pub use globed_protocol;
 pub use hmac;
 pub use parking_lot;
 pub use rand;
@@ -29,20 +31,25 @@ pub use time;
 // our reexports
 pub use data::*;
 pub use logger::*;
+pub use password::{constant_time_eq, hash_password, is_password_hash, verify_password};
+pub use secret::Secret;
 pub use token_issuer::TokenIssuer;
+pub use totp::{generate_totp_secret, verify_totp, verify_totp_once};
 pub mod data;
+pub mod geoip;
 pub mod logger;
+pub mod password;
+pub mod secret;
 pub mod token_issuer;
+pub mod totp;
 
-pub const PROTOCOL_VERSION: u16 = 6;
+pub use globed_protocol::{MAX_NAME_SIZE, PROTOCOL_VERSION};
 // used for communicating to the user the minimum required mod version for this protocol
-pub const MIN_CLIENT_VERSION: &str = "v1.4.0";
+pub const MIN_CLIENT_VERSION: &str = "v1.5.0";
 pub const SERVER_MAGIC: &[u8] = b"\xdd\xeeglobed\xda\xee";
 pub const SERVER_MAGIC_LEN: usize = SERVER_MAGIC.len();
 /// amount of chars in an admin key (32)
 pub const ADMIN_KEY_LENGTH: usize = 32;
-/// maximum characters in a user's name (24). they can only be 15 chars max but we give headroom just in case
-pub const MAX_NAME_SIZE: usize = 24;
 pub const VIOLATION_REASON_LENGTH: usize = 128;
 
 pub const DEFAULT_CENTRAL_SERVER_PORT: u16 = 4201;
@@ -52,6 +59,13 @@ pub fn generate_alphanum_string(n: usize) -> String {
     rand::thread_rng().sample_iter(&Alphanumeric).take(n).map(char::from).collect()
 }
 
+/// Returns the current time in seconds since the unix epoch, the format used for all timestamps in user entries.
+pub fn unix_timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |x| i64::try_from(x.as_secs()).unwrap_or(i64::MAX))
+}
+
 pub fn get_log_level(env_var: &str) -> Option<LogLevelFilter> {
     std::env::var(env_var).map_or_else(
         |_| {
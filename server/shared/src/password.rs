@@ -0,0 +1,88 @@
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use rand::rngs::OsRng;
+
+/// Hashes the password with Argon2id and a random salt, returns the hash in the PHC string format (`$argon2id$v=19$...`).
+pub fn hash_password(password: &str) -> String {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("failed to hash the password")
+        .to_string()
+}
+
+/// Returns whether the given string looks like an Argon2 hash rather than a plaintext password.
+pub fn is_password_hash(stored: &str) -> bool {
+    stored.starts_with("$argon2")
+}
+
+/// Verifies the password against the stored value, which should be a hash created by `hash_password`.
+/// For backwards compatibility, plaintext stored values are still accepted and compared in constant time.
+pub fn verify_password(password: &str, stored: &str) -> bool {
+    if is_password_hash(stored) {
+        return PasswordHash::new(stored).is_ok_and(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok());
+    }
+
+    constant_time_eq(password.as_bytes(), stored.as_bytes())
+}
+
+/// Compares two byte strings in constant time, so the time it takes doesn't reveal how much of a secret was guessed right.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut result = 0u8;
+    for (b1, b2) in a.iter().zip(b.iter()) {
+        result |= b1 ^ b2;
+    }
+
+    result == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_roundtrip() {
+        let hash = hash_password("correct horse battery staple");
+
+        assert!(is_password_hash(&hash), "hash is not in the argon2 format: {hash}");
+        assert!(verify_password("correct horse battery staple", &hash));
+    }
+
+    #[test]
+    fn wrong_password_rejected() {
+        let hash = hash_password("correct horse battery staple");
+
+        assert!(!verify_password("correct horse battery stapler", &hash));
+        assert!(!verify_password("", &hash));
+    }
+
+    #[test]
+    fn salts_differ() {
+        let first = hash_password("password");
+        let second = hash_password("password");
+
+        assert_ne!(first, second, "two hashes of the same password should use different salts");
+        assert!(verify_password("password", &first));
+        assert!(verify_password("password", &second));
+    }
+
+    #[test]
+    fn malformed_hash_rejected() {
+        assert!(!verify_password("password", "$argon2id$not-a-real-hash"));
+    }
+
+    #[test]
+    fn plaintext_fallback() {
+        assert!(!is_password_hash("hunter2"));
+        assert!(verify_password("hunter2", "hunter2"));
+        assert!(!verify_password("hunter3", "hunter2"));
+        assert!(!verify_password("hunter", "hunter2"));
+    }
+}
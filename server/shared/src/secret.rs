@@ -0,0 +1,110 @@
+use std::fmt::{Debug, Display};
+
+use esp::{ByteBuffer, ByteReader, Decodable, DecodeResult, DynamicSize, Encodable, FastByteBuffer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroizing;
+
+/// A sensitive string (password, key, webhook URL) that is wiped from memory when dropped and never shows up in logs.
+///
+/// When loaded from a config, the value can also reference a file (`file:/run/secrets/globed`) or an environment variable
+/// (`env:GLOBED_SECRET`) instead of containing the secret inline. The reference is kept, so saving the config
+/// does not write the secret back in plaintext.
+#[derive(Clone, Default)]
+pub struct Secret {
+    value: Zeroizing<String>,
+    source: Option<String>,
+}
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            value: Zeroizing::new(value.into()),
+            source: None,
+        }
+    }
+
+    /// Creates a secret from an inline value or a `file:`/`env:` reference, reading the referenced value.
+    pub fn resolve(spec: &str) -> Result<Self, String> {
+        let value = if let Some(path) = spec.strip_prefix("file:") {
+            let contents = Zeroizing::new(std::fs::read_to_string(path).map_err(|e| format!("failed to read secret from file '{path}': {e}"))?);
+            // editors love adding a trailing newline
+            contents.trim_end_matches(['\r', '\n']).to_owned()
+        } else if let Some(var) = spec.strip_prefix("env:") {
+            std::env::var(var).map_err(|_| format!("failed to read secret from environment variable '{var}'"))?
+        } else {
+            return Ok(Self::new(spec));
+        };
+
+        Ok(Self {
+            value: Zeroizing::new(value),
+            source: Some(spec.to_owned()),
+        })
+    }
+
+    /// Returns the actual secret value. Be careful to never log it.
+    pub fn expose(&self) -> &str {
+        &self.value
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+}
+
+impl AsRef<str> for Secret {
+    fn as_ref(&self) -> &str {
+        self.expose()
+    }
+}
+
+impl Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl Display for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.source.as_deref().unwrap_or(self.expose()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let spec = Zeroizing::new(String::deserialize(deserializer)?);
+        Self::resolve(&spec).map_err(serde::de::Error::custom)
+    }
+}
+
+/* sent as a regular string, only between the central and game servers */
+
+impl Encodable for Secret {
+    fn encode(&self, buf: &mut ByteBuffer) {
+        String::encode(&self.value, buf);
+    }
+
+    fn encode_fast(&self, buf: &mut FastByteBuffer) {
+        String::encode_fast(&self.value, buf);
+    }
+}
+
+impl Decodable for Secret {
+    fn decode_from_reader(buf: &mut ByteReader) -> DecodeResult<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self::new(String::decode_from_reader(buf)?))
+    }
+}
+
+impl DynamicSize for Secret {
+    fn encoded_size(&self) -> usize {
+        String::encoded_size(&self.value)
+    }
+}
@@ -9,6 +9,8 @@ use sha2::Sha256;
 
 pub struct TokenIssuer {
     hmac: Hmac<Sha256>,
+    /// keys that are no longer used for issuing tokens, but tokens signed with them are still accepted
+    previous_hmacs: Vec<Hmac<Sha256>>,
     expiration_period: Duration,
 }
 
@@ -43,15 +45,26 @@ impl TokenIssuer {
         let skey_bytes = secret_key.as_bytes();
         let hmac = Hmac::<Sha256>::new_from_slice(skey_bytes).unwrap();
 
-        Self { hmac, expiration_period }
+        Self {
+            hmac,
+            previous_hmacs: Vec::new(),
+            expiration_period,
+        }
     }
 
-    /// Change the secret key of this token issuer.
+    /// Change the secret key of this token issuer. Tokens signed with any of the previous keys will no longer be valid.
     pub fn set_secret_key(&mut self, secret_key: &str) {
-        let skey_bytes = secret_key.as_bytes();
-        let hmac = Hmac::<Sha256>::new_from_slice(skey_bytes).unwrap();
+        self.set_secret_keys::<&str>(secret_key, &[]);
+    }
 
-        self.hmac = hmac;
+    /// Change the secret key used for issuing new tokens, while still accepting tokens signed with any of the `previous_keys`.
+    /// This allows rotating the secret key without invalidating all the tokens at once.
+    pub fn set_secret_keys<S: AsRef<str>>(&mut self, secret_key: &str, previous_keys: &[S]) {
+        self.hmac = Hmac::<Sha256>::new_from_slice(secret_key.as_bytes()).unwrap();
+        self.previous_hmacs = previous_keys
+            .iter()
+            .map(|key| Hmac::<Sha256>::new_from_slice(key.as_ref().as_bytes()).unwrap())
+            .collect();
     }
 
     pub fn set_expiration_period(&mut self, period: Duration) {
@@ -132,16 +145,127 @@ impl TokenIssuer {
             return Err(TokenValidationFailure::Expired);
         }
 
-        // verify the signature
-        let mut hmac = self.hmac.clone();
-        hmac.update(data_str.as_bytes());
-
+        // verify the signature, first with the current key and then with the previous ones
         let signature = b64e::URL_SAFE_NO_PAD
             .decode(signature)
             .map_err(|_| TokenValidationFailure::MalformedStructure)?;
 
-        hmac.verify_slice(&signature).map_err(|_| TokenValidationFailure::InvalidSignature)?;
+        let valid = std::iter::once(&self.hmac).chain(self.previous_hmacs.iter()).any(|hmac| {
+            let mut hmac = hmac.clone();
+            hmac.update(data_str.as_bytes());
+            hmac.verify_slice(&signature).is_ok()
+        });
+
+        if !valid {
+            return Err(TokenValidationFailure::InvalidSignature);
+        }
 
         Ok(orig_name.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(86400);
+
+    fn sign(issuer: &TokenIssuer, data: &str) -> String {
+        let mut hmac = issuer.hmac.clone();
+        hmac.update(data.as_bytes());
+
+        format!(
+            "{}.{}",
+            b64e::URL_SAFE_NO_PAD.encode(data),
+            b64e::URL_SAFE_NO_PAD.encode(hmac.finalize().into_bytes())
+        )
+    }
+
+    fn timestamp(ago: Duration) -> u64 {
+        (SystemTime::now() - ago).duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    #[test]
+    fn issues_valid_tokens() {
+        let issuer = TokenIssuer::new("secret", DAY);
+        let token = issuer.generate(1, 2, "player");
+
+        assert_eq!(issuer.validate(1, 2, &token).ok().as_deref(), Some("player"));
+    }
+
+    #[test]
+    fn rejects_other_accounts() {
+        let issuer = TokenIssuer::new("secret", DAY);
+        let token = issuer.generate(1, 2, "player");
+
+        assert!(matches!(issuer.validate(3, 2, &token), Err(TokenValidationFailure::Impersonation)));
+        assert!(matches!(issuer.validate(1, 3, &token), Err(TokenValidationFailure::Impersonation)));
+    }
+
+    #[test]
+    fn rejects_expired_tokens() {
+        let issuer = TokenIssuer::new("secret", DAY);
+
+        let fresh = sign(&issuer, &format!("1.2.player.{}", timestamp(DAY - Duration::from_secs(60))));
+        assert!(issuer.validate(1, 2, &fresh).is_ok());
+
+        let expired = sign(&issuer, &format!("1.2.player.{}", timestamp(DAY + Duration::from_secs(60))));
+        assert!(matches!(issuer.validate(1, 2, &expired), Err(TokenValidationFailure::Expired)));
+    }
+
+    #[test]
+    fn rejects_tampered_tokens() {
+        let issuer = TokenIssuer::new("secret", DAY);
+        let token = issuer.generate(1, 2, "player");
+        let (claims, signature) = token.split_once('.').unwrap();
+
+        // same signature, different name
+        let renamed = format!(
+            "{}.{signature}",
+            b64e::URL_SAFE_NO_PAD.encode(format!("1.2.admin.{}", timestamp(Duration::ZERO)))
+        );
+        assert!(matches!(issuer.validate(1, 2, &renamed), Err(TokenValidationFailure::InvalidSignature)));
+
+        // same claims, different signature
+        let mut signature = b64e::URL_SAFE_NO_PAD.decode(signature).unwrap();
+        signature[0] ^= 1;
+        let resigned = format!("{claims}.{}", b64e::URL_SAFE_NO_PAD.encode(signature));
+        assert!(matches!(issuer.validate(1, 2, &resigned), Err(TokenValidationFailure::InvalidSignature)));
+
+        // signed with another key
+        let other = TokenIssuer::new("other secret", DAY);
+        assert!(matches!(
+            issuer.validate(1, 2, &other.generate(1, 2, "player")),
+            Err(TokenValidationFailure::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_tokens() {
+        let issuer = TokenIssuer::new("secret", DAY);
+
+        assert!(matches!(issuer.validate(1, 2, ""), Err(TokenValidationFailure::Missing)));
+        assert!(matches!(issuer.validate(1, 2, "abc"), Err(TokenValidationFailure::MalformedStructure)));
+        assert!(matches!(
+            issuer.validate(1, 2, "!!!.abc"),
+            Err(TokenValidationFailure::MalformedStructure)
+        ));
+        assert!(matches!(
+            issuer.validate(1, 2, &sign(&issuer, "1.2.player")),
+            Err(TokenValidationFailure::MalformedStructure)
+        ));
+    }
+
+    #[test]
+    fn accepts_previous_keys_until_dropped() {
+        let mut issuer = TokenIssuer::new("old", DAY);
+        let token = issuer.generate(1, 2, "player");
+
+        issuer.set_secret_keys("new", &["old"]);
+        assert!(issuer.validate(1, 2, &token).is_ok());
+        assert!(issuer.validate(1, 2, &issuer.generate(1, 2, "player")).is_ok());
+
+        issuer.set_secret_key("new");
+        assert!(matches!(issuer.validate(1, 2, &token), Err(TokenValidationFailure::InvalidSignature)));
+    }
+}
@@ -0,0 +1,196 @@
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha1::Sha1;
+
+/// Length of a single time step, in seconds
+const TIME_STEP: u64 = 30;
+/// How many steps before and after the current one are also accepted, to allow for clock drift
+const ALLOWED_DRIFT: u64 = 1;
+const DIGITS: u32 = 6;
+const SECRET_LENGTH: usize = 20;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Generates a random TOTP secret, encoded in base32 (the format accepted by authenticator apps)
+pub fn generate_totp_secret() -> String {
+    let mut secret = [0u8; SECRET_LENGTH];
+    rand::thread_rng().fill_bytes(&mut secret);
+
+    base32_encode(&secret)
+}
+
+/// Validates a 6-digit RFC 6238 code (HMAC-SHA1, 30 second steps) against the base32 encoded secret.
+/// On success returns the time step that the code belongs to, so that callers can reject reused codes.
+pub fn verify_totp(secret: &str, code: &str, unix_time: u64) -> Option<u64> {
+    let secret = base32_decode(secret)?;
+
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let code = code.parse::<u32>().ok()?;
+    let current_step = unix_time / TIME_STEP;
+
+    (current_step.saturating_sub(ALLOWED_DRIFT)..=current_step + ALLOWED_DRIFT).find(|step| {
+        // not exactly constant time, but the position of a mismatch in a 6 digit number leaks nothing useful
+        generate_code(&secret, *step) == code
+    })
+}
+
+/// Like `verify_totp`, but also rejects codes from time steps at or before `last_step`, so that every code can only be used once.
+/// On success `last_step` is set to the time step of the accepted code.
+pub fn verify_totp_once(secret: &str, code: &str, unix_time: u64, last_step: &mut u64) -> bool {
+    match verify_totp(secret, code, unix_time) {
+        Some(step) if step > *last_step => {
+            *last_step = step;
+            true
+        }
+        _ => false,
+    }
+}
+
+fn generate_code(secret: &[u8], step: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("hmac accepts keys of any size");
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // dynamic truncation, see RFC 4226 section 5.3
+    let offset = (hash[hash.len() - 1] & 0xf) as usize;
+    let binary = u32::from_be_bytes([hash[offset] & 0x7f, hash[offset + 1], hash[offset + 2], hash[offset + 3]]);
+
+    binary % 10u32.pow(DIGITS)
+}
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity((data.len() * 8).div_ceil(5));
+
+    let mut buffer = 0u32;
+    let mut bits = 0u32;
+
+    for byte in data {
+        buffer = (buffer << 8) | u32::from(*byte);
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    out
+}
+
+/// Decodes base32 without padding, ignoring case, spaces and trailing `=`. Returns `None` if the string is invalid or empty.
+fn base32_decode(data: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len() * 5 / 8);
+
+    let mut buffer = 0u32;
+    let mut bits = 0u32;
+
+    for c in data.bytes().filter(|c| *c != b' ' && *c != b'=') {
+        let value = BASE32_ALPHABET.iter().position(|x| *x == c.to_ascii_uppercase())? as u32;
+
+        buffer = (buffer << 5) | value;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(out).filter(|x| !x.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the SHA1 secret from RFC 6238 appendix B ("12345678901234567890"), base32 encoded
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn rfc6238_vectors() {
+        // the RFC lists 8 digit codes, these are their last 6 digits
+        let vectors = [
+            (59, "287082"),
+            (1_111_111_109, "081804"),
+            (1_111_111_111, "050471"),
+            (1_234_567_890, "005924"),
+            (2_000_000_000, "279037"),
+            (20_000_000_000, "353130"),
+        ];
+
+        for (time, code) in vectors {
+            assert_eq!(verify_totp(RFC_SECRET, code, time), Some(time / TIME_STEP), "wrong code at {time}");
+        }
+    }
+
+    #[test]
+    fn drift_window() {
+        let time = 1_234_567_890;
+        let step = time / TIME_STEP;
+
+        // one step before and after are accepted, and report the step the code was made for
+        assert_eq!(verify_totp(RFC_SECRET, "005924", time - TIME_STEP), Some(step));
+        assert_eq!(verify_totp(RFC_SECRET, "005924", time + TIME_STEP), Some(step));
+
+        // anything further away is not
+        assert_eq!(verify_totp(RFC_SECRET, "005924", time - 2 * TIME_STEP), None);
+        assert_eq!(verify_totp(RFC_SECRET, "005924", time + 2 * TIME_STEP), None);
+    }
+
+    #[test]
+    fn replay_rejected() {
+        let time = 1_234_567_890;
+        let mut last_step = 0;
+
+        assert!(verify_totp_once(RFC_SECRET, "005924", time, &mut last_step));
+        assert_eq!(last_step, time / TIME_STEP);
+
+        // the same code again, even a few seconds later
+        assert!(!verify_totp_once(RFC_SECRET, "005924", time + 5, &mut last_step));
+
+        // a code from an earlier step that is still within the drift window
+        let earlier = format!("{:06}", generate_code(&base32_decode(RFC_SECRET).unwrap(), time / TIME_STEP - 1));
+        assert!(!verify_totp_once(RFC_SECRET, &earlier, time, &mut last_step));
+
+        // the code for the next step is fine
+        let next = format!("{:06}", generate_code(&base32_decode(RFC_SECRET).unwrap(), time / TIME_STEP + 1));
+        assert!(verify_totp_once(RFC_SECRET, &next, time + TIME_STEP, &mut last_step));
+    }
+
+    #[test]
+    fn malformed_input_rejected() {
+        let time = 1_234_567_890;
+
+        assert_eq!(verify_totp(RFC_SECRET, "05924", time), None);
+        assert_eq!(verify_totp(RFC_SECRET, "0005924", time), None);
+        assert_eq!(verify_totp(RFC_SECRET, "00592a", time), None);
+        assert_eq!(verify_totp(RFC_SECRET, "", time), None);
+        assert_eq!(verify_totp("not base32!", "005924", time), None);
+        assert_eq!(verify_totp("", "005924", time), None);
+
+        // surrounding whitespace is fine
+        assert_eq!(verify_totp(RFC_SECRET, " 005924 ", time), Some(time / TIME_STEP));
+    }
+
+    #[test]
+    fn secret_roundtrip() {
+        let secret = generate_totp_secret();
+        let decoded = base32_decode(&secret).expect("generated secret is not valid base32");
+
+        assert_eq!(decoded.len(), SECRET_LENGTH);
+        assert_eq!(base32_encode(&decoded), secret);
+
+        // lowercase and spaced out, the way some apps show it
+        assert_eq!(base32_decode(&RFC_SECRET.to_lowercase()), base32_decode(RFC_SECRET));
+        assert_eq!(base32_decode("GEZD GNBV GY3T QOJQ GEZD GNBV GY3T QOJQ"), base32_decode(RFC_SECRET));
+        assert_eq!(base32_decode(RFC_SECRET).unwrap(), b"12345678901234567890");
+    }
+}
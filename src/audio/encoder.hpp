@@ -7,6 +7,8 @@
 #include <data/bytebuffer.hpp>
 
 constexpr size_t VOICE_MAX_BYTES_IN_FRAME = 1000;
+// lets opus pick the bitrate on its own, same as `OPUS_AUTO`
+constexpr int VOICE_AUTO_BITRATE = -1000;
 
 struct OpusEncoder;
 
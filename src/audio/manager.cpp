@@ -220,6 +220,10 @@ void GlobedAudioManager::setRecordBufferCapacity(size_t frames) {
     recordFrame.setCapacity(frames);
 }
 
+void GlobedAudioManager::setEncoderBitrate(int bitrate) {
+    pendingBitrate = bitrate;
+}
+
 Result<> GlobedAudioManager::startRecordingInternal(bool passive) {
     if (!permission::getPermissionStatus(Permission::RecordAudio)) {
         return Err("Recording failed, please grant microphone permission in Globed settings");
@@ -552,6 +556,13 @@ Result<> GlobedAudioManager::audioThreadWork() {
             float pcmbuf[VOICE_TARGET_FRAMESIZE];
             recordQueue.copyTo(pcmbuf, VOICE_TARGET_FRAMESIZE);
 
+            // the encoder is only ever used on this thread, so the bitrate is changed here
+            int bitrate = pendingBitrate.load();
+            if (bitrate != 0) {
+                pendingBitrate.store(0);
+                GLOBED_UNWRAP(encoder.setBitrate(bitrate));
+            }
+
             GLOBED_UNWRAP_INTO(encoder.encode(pcmbuf), auto opusFrame);
             GLOBED_UNWRAP(recordFrame.pushOpusFrame(opusFrame));
         }
@@ -74,6 +74,9 @@ public:
     // set the amount of record frames in a buffer (used by the lowerAudioLatency setting)
     void setRecordBufferCapacity(size_t frames);
 
+    // set the bitrate that voice gets encoded with, applied on the audio thread before the next frame is encoded
+    void setEncoderBitrate(int bitrate);
+
     // start recording the voice and call the callback once a full frame is ready.
     // if `stopRecording()` is called at any point, the callback will be called with the remaining data.
     // in that case it may have less than the full 10 frames.
@@ -149,6 +152,7 @@ private:
     void internalStopRecording();
 
     AudioEncoder encoder;
+    asp::AtomicI32 pendingBitrate = 0; // 0 if unchanged
 
     /* misc */
     FMOD::System* cachedSystem = nullptr;
@@ -16,6 +16,17 @@ std::shared_ptr<Packet> matchPacket(packetid_t packetId) {
         PACKET(KeepaliveTCPResponsePacket);
         PACKET(ClaimThreadFailedPacket);
         PACKET(LoginRecoveryFailecPacket);
+        PACKET(RekeyRequestPacket);
+        PACKET(ServerTransferPacket);
+        PACKET(TransferClaimedPacket);
+        PACKET(DuplicateLoginPacket);
+        PACKET(LatencyProbePacket);
+        PACKET(SettingsHintsPacket);
+        PACKET(LoginNameRejectedPacket);
+        PACKET(LoginWhitelistRequiredPacket);
+        PACKET(ThrottleHintPacket);
+        PACKET(StandaloneTokenPacket);
+        PACKET(ServerRestartingPacket);
 
         PACKET(ServerNoticePacket);
         PACKET(ServerBannedPacket);
@@ -28,14 +39,32 @@ std::shared_ptr<Packet> matchPacket(packetid_t packetId) {
         PACKET(LevelListPacket);
         PACKET(LevelPlayerCountPacket);
         PACKET(RolesUpdatedPacket);
+        PACKET(PreferencesPacket);
+        PACKET(PlayerReportedPacket);
+        PACKET(PresenceUpdatePacket);
+        PACKET(PlayerProfileChangedPacket);
+        PACKET(FriendListPacket);
+        PACKET(SessionListPacket);
 
         // game related
 
         PACKET(PlayerProfilesPacket);
         PACKET(LevelDataPacket);
         PACKET(LevelPlayerMetadataPacket);
+        PACKET(LevelBlockedPacket);
+        PACKET(LevelCompletedBroadcastPacket);
+        PACKET(SpectateStartedPacket);
+        PACKET(SpectateFailedPacket);
+        PACKET(SpectatorCountPacket);
+        PACKET(SpectatedPlayerDataPacket);
+        PACKET(LevelEventBroadcastPacket);
         PACKET(VoiceBroadcastPacket);
         PACKET(ChatMessageBroadcastPacket);
+        PACKET(ChatMessageDeletedPacket);
+        PACKET(ChatMessageSentPacket);
+        PACKET(LevelJoinRejectedPacket);
+        PACKET(ChatMentionPacket);
+        PACKET(ChatChannelPacket);
 
         // room related
 
@@ -47,6 +76,12 @@ std::shared_ptr<Packet> matchPacket(packetid_t packetId) {
         PACKET(RoomInvitePacket);
         PACKET(RoomListPacket);
         PACKET(RoomCreateFailedPacket);
+        PACKET(RoomCountdownPacket);
+        PACKET(RoomAfkRemovedPacket);
+        PACKET(RoomInviteFailedPacket);
+        PACKET(RoomWebhookSetPacket);
+        PACKET(RoomWebhookFailedPacket);
+        PACKET(RoomKickedPacket);
 
         // admin related
 
@@ -55,6 +90,14 @@ std::shared_ptr<Packet> matchPacket(packetid_t packetId) {
         PACKET(AdminUserDataPacket);
         PACKET(AdminSuccessMessagePacket);
         PACKET(AdminAuthFailedPacket);
+        PACKET(AdminPlayerHistoryPacket);
+        PACKET(AdminScheduledNoticesPacket);
+        PACKET(AdminTrafficStatsPacket);
+        PACKET(AdminBotsPacket);
+        PACKET(AdminInviteCodePacket);
+        PACKET(AdminReportsPacket);
+        PACKET(AdminChatTailPacket);
+        PACKET(AdminConfigOverridesPacket);
 
         default:
             return std::shared_ptr<Packet>(nullptr);
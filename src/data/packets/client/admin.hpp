@@ -8,12 +8,13 @@ class AdminAuthPacket : public Packet {
     GLOBED_PACKET(19000, AdminAuthPacket, true, true)
 
     AdminAuthPacket() {}
-    AdminAuthPacket(const std::string_view key) : key(key) {}
+    AdminAuthPacket(const std::string_view key, const std::string_view totpCode) : key(key), totpCode(totpCode) {}
 
     std::string key;
+    std::string totpCode;
 };
 
-GLOBED_SERIALIZABLE_STRUCT(AdminAuthPacket, (key));
+GLOBED_SERIALIZABLE_STRUCT(AdminAuthPacket, (key, totpCode));
 
 enum class AdminSendNoticeType : uint8_t {
     Everyone = 0,
@@ -84,3 +85,283 @@ class AdminUpdateUserPacket : public Packet {
 GLOBED_SERIALIZABLE_STRUCT(AdminUpdateUserPacket, (
     userEntry
 ));
+
+// 19005 - AdminDebugDumpPacket
+class AdminDebugDumpPacket : public Packet {
+    GLOBED_PACKET(19005, AdminDebugDumpPacket, false, true)
+
+    AdminDebugDumpPacket() {}
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminDebugDumpPacket, ());
+
+// 19006 - AdminSetChaosPacket
+class AdminSetChaosPacket : public Packet {
+    GLOBED_PACKET(19006, AdminSetChaosPacket, false, true)
+
+    AdminSetChaosPacket() {}
+    AdminSetChaosPacket(uint32_t bridgeDelay, uint8_t broadcastDropChance, uint8_t disconnectChance)
+        : bridgeDelay(bridgeDelay), broadcastDropChance(broadcastDropChance), disconnectChance(disconnectChance) {}
+
+    uint32_t bridgeDelay; // maximum random delay before requests to the central server, in milliseconds
+    uint8_t broadcastDropChance; // percent
+    uint8_t disconnectChance; // percent
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminSetChaosPacket, (
+    bridgeDelay, broadcastDropChance, disconnectChance
+));
+
+// 19007 - AdminSetLevelBlockedPacket
+class AdminSetLevelBlockedPacket : public Packet {
+    GLOBED_PACKET(19007, AdminSetLevelBlockedPacket, false, true)
+
+    AdminSetLevelBlockedPacket() {}
+    AdminSetLevelBlockedPacket(LevelId levelId, bool blocked) : levelId(levelId), blocked(blocked) {}
+
+    LevelId levelId;
+    bool blocked;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminSetLevelBlockedPacket, (
+    levelId, blocked
+));
+
+// 19008 - AdminSetSlowModePacket
+class AdminSetSlowModePacket : public Packet {
+    GLOBED_PACKET(19008, AdminSetSlowModePacket, false, true)
+
+    AdminSetSlowModePacket() {}
+    AdminSetSlowModePacket(uint32_t roomId, LevelId levelId, uint32_t seconds) : roomId(roomId), levelId(levelId), seconds(seconds) {}
+
+    uint32_t roomId;
+    LevelId levelId;
+    uint32_t seconds;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminSetSlowModePacket, (
+    roomId, levelId, seconds
+));
+
+// 19009 - AdminTakeOverRoomPacket
+class AdminTakeOverRoomPacket : public Packet {
+    GLOBED_PACKET(19009, AdminTakeOverRoomPacket, false, true)
+
+    AdminTakeOverRoomPacket() {}
+    AdminTakeOverRoomPacket(uint32_t roomId) : roomId(roomId) {}
+
+    uint32_t roomId;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminTakeOverRoomPacket, (roomId));
+
+// 19010 - AdminGetPlayerHistoryPacket
+class AdminGetPlayerHistoryPacket : public Packet {
+    GLOBED_PACKET(19010, AdminGetPlayerHistoryPacket, false, true)
+
+    AdminGetPlayerHistoryPacket() {}
+    AdminGetPlayerHistoryPacket(uint32_t roomId, LevelId levelId, uint32_t period) : roomId(roomId), levelId(levelId), period(period) {}
+
+    uint32_t roomId;
+    LevelId levelId;
+    uint32_t period; // in seconds
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminGetPlayerHistoryPacket, (
+    roomId, levelId, period
+));
+
+// 19011 - AdminScheduleNoticePacket
+class AdminScheduleNoticePacket : public Packet {
+    GLOBED_PACKET(19011, AdminScheduleNoticePacket, false, true)
+
+    AdminScheduleNoticePacket() {}
+    AdminScheduleNoticePacket(AdminSendNoticeType ptype, uint32_t roomId, LevelId levelId, const std::string_view message, int64_t sendAt)
+        : ptype(ptype), roomId(roomId), levelId(levelId), message(message), sendAt(sendAt) {}
+
+    AdminSendNoticeType ptype; // can't be Person
+    uint32_t roomId;
+    LevelId levelId;
+    std::string message;
+    int64_t sendAt; // seconds since unix epoch
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminScheduleNoticePacket, (
+    ptype, roomId, levelId, message, sendAt
+));
+
+// 19012 - AdminGetScheduledNoticesPacket
+class AdminGetScheduledNoticesPacket : public Packet {
+    GLOBED_PACKET(19012, AdminGetScheduledNoticesPacket, false, true)
+
+    AdminGetScheduledNoticesPacket() {}
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminGetScheduledNoticesPacket, ());
+
+// 19013 - AdminCancelScheduledNoticePacket
+class AdminCancelScheduledNoticePacket : public Packet {
+    GLOBED_PACKET(19013, AdminCancelScheduledNoticePacket, false, true)
+
+    AdminCancelScheduledNoticePacket() {}
+    AdminCancelScheduledNoticePacket(uint32_t id) : id(id) {}
+
+    uint32_t id;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminCancelScheduledNoticePacket, (id));
+
+// 19014 - AdminGetTrafficStatsPacket
+class AdminGetTrafficStatsPacket : public Packet {
+    GLOBED_PACKET(19014, AdminGetTrafficStatsPacket, false, true)
+
+    AdminGetTrafficStatsPacket() {}
+    AdminGetTrafficStatsPacket(uint8_t count, bool reset) : count(count), reset(reset) {}
+
+    uint8_t count;
+    bool reset; // start counting from zero after the stats are sent
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminGetTrafficStatsPacket, (count, reset));
+
+// 19015 - AdminSpawnBotPacket
+class AdminSpawnBotPacket : public Packet {
+    GLOBED_PACKET(19015, AdminSpawnBotPacket, false, true)
+
+    AdminSpawnBotPacket() {}
+    AdminSpawnBotPacket(uint32_t roomId, LevelId levelId, const std::string_view name) : roomId(roomId), levelId(levelId), name(name) {}
+
+    uint32_t roomId;
+    LevelId levelId; // 0 to only put the bot in the room
+    std::string name; // [BOT] is prepended by the server
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminSpawnBotPacket, (roomId, levelId, name));
+
+// 19016 - AdminMoveBotPacket
+class AdminMoveBotPacket : public Packet {
+    GLOBED_PACKET(19016, AdminMoveBotPacket, false, true)
+
+    AdminMoveBotPacket() {}
+    AdminMoveBotPacket(int accountId, LevelId levelId, cocos2d::CCPoint position) : accountId(accountId), levelId(levelId), position(position) {}
+
+    int accountId;
+    LevelId levelId;
+    cocos2d::CCPoint position;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminMoveBotPacket, (accountId, levelId, position));
+
+// 19017 - AdminBotChatPacket
+class AdminBotChatPacket : public Packet {
+    GLOBED_PACKET(19017, AdminBotChatPacket, false, true)
+
+    AdminBotChatPacket() {}
+    AdminBotChatPacket(int accountId, const std::string_view message) : accountId(accountId), message(message) {}
+
+    int accountId;
+    std::string message;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminBotChatPacket, (accountId, message));
+
+// 19018 - AdminRemoveBotPacket
+class AdminRemoveBotPacket : public Packet {
+    GLOBED_PACKET(19018, AdminRemoveBotPacket, false, true)
+
+    AdminRemoveBotPacket() {}
+    AdminRemoveBotPacket(int accountId) : accountId(accountId) {}
+
+    int accountId;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminRemoveBotPacket, (accountId));
+
+// 19019 - AdminGetBotsPacket
+class AdminGetBotsPacket : public Packet {
+    GLOBED_PACKET(19019, AdminGetBotsPacket, false, true)
+
+    AdminGetBotsPacket() {}
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminGetBotsPacket, ());
+
+// 19020 - AdminCreateInviteCodePacket
+class AdminCreateInviteCodePacket : public Packet {
+    GLOBED_PACKET(19020, AdminCreateInviteCodePacket, false, true)
+
+    AdminCreateInviteCodePacket() {}
+    AdminCreateInviteCodePacket(uint32_t uses) : uses(uses) {}
+
+    uint32_t uses;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminCreateInviteCodePacket, (uses));
+
+// 19021 - AdminGetReportsPacket
+class AdminGetReportsPacket : public Packet {
+    GLOBED_PACKET(19021, AdminGetReportsPacket, false, true)
+
+    AdminGetReportsPacket() {}
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminGetReportsPacket, ());
+
+// 19022 - AdminUpdateReportPacket
+class AdminUpdateReportPacket : public Packet {
+    GLOBED_PACKET(19022, AdminUpdateReportPacket, false, true)
+
+    AdminUpdateReportPacket() {}
+    AdminUpdateReportPacket(int64_t id, ReportState state) : id(id), state(state) {}
+
+    int64_t id;
+    ReportState state;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminUpdateReportPacket, (id, state));
+
+// 19023 - AdminTailChatPacket
+class AdminTailChatPacket : public Packet {
+    GLOBED_PACKET(19023, AdminTailChatPacket, false, true)
+
+    AdminTailChatPacket() {}
+    AdminTailChatPacket(uint32_t roomId, LevelId levelId, bool enabled) : roomId(roomId), levelId(levelId), enabled(enabled) {}
+
+    uint32_t roomId;
+    LevelId levelId; // 0 to follow every level in the room
+    bool enabled; // false to stop following chat
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminTailChatPacket, (roomId, levelId, enabled));
+
+// 19024 - AdminSetConfigOverridePacket
+class AdminSetConfigOverridePacket : public Packet {
+    GLOBED_PACKET(19024, AdminSetConfigOverridePacket, false, true)
+
+    AdminSetConfigOverridePacket() {}
+    AdminSetConfigOverridePacket(ConfigOverrideKey key, uint32_t value, uint32_t duration) : key(key), value(value), duration(duration) {}
+
+    ConfigOverrideKey key;
+    uint32_t value;
+    uint32_t duration; // in seconds, 0 to remove the override
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminSetConfigOverridePacket, (key, value, duration));
+
+// 19025 - AdminGetConfigOverridesPacket
+class AdminGetConfigOverridesPacket : public Packet {
+    GLOBED_PACKET(19025, AdminGetConfigOverridesPacket, false, true)
+
+    AdminGetConfigOverridesPacket() {}
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminGetConfigOverridesPacket, ());
+
+// 19026 - AdminReloadConfigPacket
+class AdminReloadConfigPacket : public Packet {
+    GLOBED_PACKET(19026, AdminReloadConfigPacket, false, true)
+
+    AdminReloadConfigPacket() {}
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminReloadConfigPacket, ());
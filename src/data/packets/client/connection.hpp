@@ -15,18 +15,26 @@ class PingPacket : public Packet {
 
 GLOBED_SERIALIZABLE_STRUCT(PingPacket, (id));
 
+struct CompressionSupport {
+    bool lz4;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(CompressionSupport, (lz4));
+
 // 10001 - CryptoHandshakeStartPacket
 class CryptoHandshakeStartPacket : public Packet {
     GLOBED_PACKET(10001, CryptoHandshakeStartPacket, false, true)
 
     CryptoHandshakeStartPacket() {}
-    CryptoHandshakeStartPacket(uint16_t _protocol, CryptoPublicKey _key) : protocol(_protocol), key(_key) {}
+    CryptoHandshakeStartPacket(uint16_t _protocol, CryptoPublicKey _key, CompressionSupport _compression)
+        : protocol(_protocol), key(_key), compression(_compression) {}
 
     uint16_t protocol;
     CryptoPublicKey key;
+    CompressionSupport compression;
 };
 
-GLOBED_SERIALIZABLE_STRUCT(CryptoHandshakeStartPacket, (protocol, key));
+GLOBED_SERIALIZABLE_STRUCT(CryptoHandshakeStartPacket, (protocol, key, compression));
 
 // 10002 - KeepalivePacket
 class KeepalivePacket : public Packet {
@@ -50,7 +58,8 @@ class LoginPacket : public Packet {
             const PlayerIconData& icons,
             uint16_t fragmentationLimit,
             const std::string_view platform,
-            bool isInvisible
+            bool isInvisible,
+            const std::string_view inviteCode
     ) :
             accountId(accid),
             userId(userId),
@@ -59,7 +68,8 @@ class LoginPacket : public Packet {
             icons(icons),
             fragmentationLimit(fragmentationLimit),
             platform(platform),
-            isInvisible(isInvisible) {}
+            isInvisible(isInvisible),
+            inviteCode(inviteCode) {}
 
     int32_t accountId;
     int32_t userId;
@@ -69,6 +79,7 @@ class LoginPacket : public Packet {
     uint16_t fragmentationLimit;
     std::string platform;
     bool isInvisible;
+    std::string inviteCode;
 };
 
 GLOBED_SERIALIZABLE_STRUCT(LoginPacket, (
@@ -79,7 +90,8 @@ GLOBED_SERIALIZABLE_STRUCT(LoginPacket, (
     icons,
     fragmentationLimit,
     platform,
-    isInvisible
+    isInvisible,
+    inviteCode
 ));
 
 // 10005 - ClaimThreadPacket
@@ -112,6 +124,57 @@ class KeepaliveTCPPacket : public Packet {
 
 GLOBED_SERIALIZABLE_STRUCT(KeepaliveTCPPacket, ());
 
+// 10008 - RekeyResponsePacket
+class RekeyResponsePacket : public Packet {
+    GLOBED_PACKET(10008, RekeyResponsePacket, true, true)
+
+    RekeyResponsePacket() {}
+    RekeyResponsePacket(CryptoPublicKey _key) : key(_key) {}
+
+    CryptoPublicKey key;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(RekeyResponsePacket, (key));
+
+// 10009 - ClaimTransferPacket
+class ClaimTransferPacket : public Packet {
+    GLOBED_PACKET(10009, ClaimTransferPacket, true, true)
+
+    ClaimTransferPacket() {}
+    ClaimTransferPacket(const std::string_view token) : token(token) {}
+
+    std::string token;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(ClaimTransferPacket, (token));
+
+// 10010 - LatencyProbeResponsePacket
+class LatencyProbeResponsePacket : public Packet {
+    GLOBED_PACKET(10010, LatencyProbeResponsePacket, false, false)
+
+    LatencyProbeResponsePacket() {}
+    LatencyProbeResponsePacket(uint32_t id) : id(id) {}
+
+    uint32_t id;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(LatencyProbeResponsePacket, (id));
+
+// 10012 - UdpFragmentPacket
+// One piece of a udp packet that is too big for a single datagram, the piece itself is written right after it by the GameSocket.
+class UdpFragmentPacket : public Packet {
+    GLOBED_PACKET(10012, UdpFragmentPacket, false, false)
+
+    UdpFragmentPacket() {}
+    UdpFragmentPacket(uint16_t messageId, uint16_t index, uint16_t count) : messageId(messageId), index(index), count(count) {}
+
+    uint16_t messageId;
+    uint16_t index;
+    uint16_t count;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(UdpFragmentPacket, (messageId, index, count));
+
 // 10200 - ConnectionTestPacket
 class ConnectionTestPacket : public Packet {
     GLOBED_PACKET(10200, ConnectionTestPacket, false, false)
@@ -19,12 +19,13 @@ class LevelJoinPacket : public Packet {
     GLOBED_PACKET(12001, LevelJoinPacket, false, false)
 
     LevelJoinPacket() {}
-    LevelJoinPacket(LevelId levelId) : levelId(levelId) {}
+    LevelJoinPacket(LevelId levelId, bool unlisted) : levelId(levelId), unlisted(unlisted) {}
 
     LevelId levelId;
+    bool unlisted;
 };
 
-GLOBED_SERIALIZABLE_STRUCT(LevelJoinPacket, (levelId));
+GLOBED_SERIALIZABLE_STRUCT(LevelJoinPacket, (levelId, unlisted));
 
 // 12002 - LevelLeavePacket
 class LevelLeavePacket : public Packet {
@@ -40,12 +41,13 @@ class PlayerDataPacket : public Packet {
     GLOBED_PACKET(12003, PlayerDataPacket, false, false)
 
     PlayerDataPacket() {}
-    PlayerDataPacket(const PlayerData& data) : data(data) {}
+    PlayerDataPacket(uint32_t sequence, const PlayerData& data) : sequence(sequence), data(data) {}
 
+    uint32_t sequence;
     PlayerData data;
 };
 
-GLOBED_SERIALIZABLE_STRUCT(PlayerDataPacket, (data));
+GLOBED_SERIALIZABLE_STRUCT(PlayerDataPacket, (sequence, data));
 
 // 12004 - PlayerMetadataPacket
 class PlayerMetadataPacket : public Packet {
@@ -59,6 +61,55 @@ class PlayerMetadataPacket : public Packet {
 
 GLOBED_SERIALIZABLE_STRUCT(PlayerMetadataPacket, (data));
 
+// 12005 - LevelCompletedPacket
+class LevelCompletedPacket : public Packet {
+    GLOBED_PACKET(12005, LevelCompletedPacket, false, true)
+
+    LevelCompletedPacket() {}
+    LevelCompletedPacket(uint32_t attempts) : attempts(attempts) {}
+
+    uint32_t attempts;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(LevelCompletedPacket, (attempts));
+
+// 12006 - SpectatePlayerPacket
+class SpectatePlayerPacket : public Packet {
+    GLOBED_PACKET(12006, SpectatePlayerPacket, false, true)
+
+    SpectatePlayerPacket() {}
+    SpectatePlayerPacket(int player) : player(player) {}
+
+    int player; // 0 to stop spectating
+};
+
+GLOBED_SERIALIZABLE_STRUCT(SpectatePlayerPacket, (player));
+
+// 12007 - SpectatorDataPacket
+class SpectatorDataPacket : public Packet {
+    GLOBED_PACKET(12007, SpectatorDataPacket, false, false)
+
+    SpectatorDataPacket() {}
+    SpectatorDataPacket(const PlayerData& data) : data(data) {}
+
+    PlayerData data;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(SpectatorDataPacket, (data));
+
+// 12008 - LevelEventPacket
+class LevelEventPacket : public Packet {
+    GLOBED_PACKET(12008, LevelEventPacket, false, false)
+
+    LevelEventPacket() {}
+    LevelEventPacket(uint32_t eventType, std::vector<uint8_t>&& payload) : eventType(eventType), payload(std::move(payload)) {}
+
+    uint32_t eventType;
+    std::vector<uint8_t> payload; // at most 64 bytes
+};
+
+GLOBED_SERIALIZABLE_STRUCT(LevelEventPacket, (eventType, payload));
+
 #ifdef GLOBED_VOICE_SUPPORT
 
 #include <audio/frame.hpp>
@@ -82,9 +133,34 @@ class ChatMessagePacket : public Packet {
     GLOBED_PACKET(12011, ChatMessagePacket, true, false)
 
     ChatMessagePacket() {}
-    ChatMessagePacket(const std::string_view message) : message(message) {}
+    ChatMessagePacket(uint32_t clientId, const std::string_view message) : clientId(clientId), message(message) {}
 
+    uint32_t clientId; // echoed back in ChatMessageSentPacket
     std::string message;
 };
 
-GLOBED_SERIALIZABLE_STRUCT(ChatMessagePacket, (message));
+GLOBED_SERIALIZABLE_STRUCT(ChatMessagePacket, (clientId, message));
+
+// 12012 - DeleteChatMessagePacket
+class DeleteChatMessagePacket : public Packet {
+    GLOBED_PACKET(12012, DeleteChatMessagePacket, true, false)
+
+    DeleteChatMessagePacket() {}
+    DeleteChatMessagePacket(uint32_t messageId) : messageId(messageId) {}
+
+    uint32_t messageId;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(DeleteChatMessagePacket, (messageId));
+
+// 12013 - SwitchChatChannelPacket
+class SwitchChatChannelPacket : public Packet {
+    GLOBED_PACKET(12013, SwitchChatChannelPacket, false, true)
+
+    SwitchChatChannelPacket() {}
+    SwitchChatChannelPacket(uint32_t channel) : channel(channel) {}
+
+    uint32_t channel;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(SwitchChatChannelPacket, (channel));
@@ -2,6 +2,9 @@
 #include <data/packets/packet.hpp>
 #include <data/types/gd.hpp>
 #include <data/types/room.hpp>
+#include <data/types/admin.hpp>
+#include <data/types/misc.hpp>
+#include <data/bitfield.hpp>
 
 // 11000 - SyncIconsPacket
 class SyncIconsPacket : public Packet {
@@ -56,3 +59,116 @@ class UpdatePlayerStatusPacket : public Packet {
 };
 
 GLOBED_SERIALIZABLE_STRUCT(UpdatePlayerStatusPacket, (isInvisible));
+
+struct ChangedSettingsFlags : BitfieldBase {
+    bool globed;
+    bool overlay;
+    bool communication;
+    bool levelUi;
+    bool players;
+};
+
+static_assert((sizeof(ChangedSettingsFlags) + 7) / 8 == 1);
+
+GLOBED_SERIALIZABLE_BITFIELD(ChangedSettingsFlags, (
+    globed, overlay, communication, levelUi, players
+))
+
+// 11005 - FeatureUsagePacket
+class FeatureUsagePacket : public Packet {
+    GLOBED_PACKET(11005, FeatureUsagePacket, false, true);
+
+    FeatureUsagePacket() {}
+    FeatureUsagePacket(ClientPlatform platform, bool voiceEnabled, ChangedSettingsFlags changedSettings)
+        : platform(platform), voiceEnabled(voiceEnabled), changedSettings(changedSettings) {}
+
+    ClientPlatform platform;
+    bool voiceEnabled;
+    ChangedSettingsFlags changedSettings;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(FeatureUsagePacket, (platform, voiceEnabled, changedSettings));
+
+// 11006 - UpdatePreferencesPacket
+class UpdatePreferencesPacket : public Packet {
+    GLOBED_PACKET(11006, UpdatePreferencesPacket, false, true);
+
+    UpdatePreferencesPacket() {}
+    UpdatePreferencesPacket(const UserPreferences& preferences) : preferences(preferences) {}
+
+    UserPreferences preferences;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(UpdatePreferencesPacket, (preferences));
+
+// 11007 - ReportPlayerPacket
+class ReportPlayerPacket : public Packet {
+    GLOBED_PACKET(11007, ReportPlayerPacket, false, true);
+
+    ReportPlayerPacket() {}
+    ReportPlayerPacket(int accountId, const std::string_view reason) : accountId(accountId), reason(reason) {}
+
+    int accountId;
+    std::string reason;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(ReportPlayerPacket, (accountId, reason));
+
+// 11008 - SubscribePresencePacket
+class SubscribePresencePacket : public Packet {
+    GLOBED_PACKET(11008, SubscribePresencePacket, false, true);
+
+    SubscribePresencePacket() {}
+    SubscribePresencePacket(std::vector<int>&& accounts) : accounts(std::move(accounts)) {}
+
+    static constexpr size_t MAX_ACCOUNTS = 256;
+
+    // replaces the previous subscription, an empty list unsubscribes
+    std::vector<int> accounts;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(SubscribePresencePacket, (accounts));
+
+// 11009 - UpdateFriendListPacket
+class UpdateFriendListPacket : public Packet {
+    GLOBED_PACKET(11009, UpdateFriendListPacket, false, true);
+
+    UpdateFriendListPacket() {}
+    UpdateFriendListPacket(std::vector<int>&& friends) : friends(std::move(friends)) {}
+
+    static constexpr size_t MAX_FRIENDS = 512;
+
+    std::vector<int> friends;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(UpdateFriendListPacket, (friends));
+
+// 11010 - FriendListQueryPacket
+class FriendListQueryPacket : public Packet {
+    GLOBED_PACKET(11010, FriendListQueryPacket, false, true);
+
+    FriendListQueryPacket() {}
+};
+
+GLOBED_SERIALIZABLE_STRUCT(FriendListQueryPacket, ());
+
+// 11011 - RequestSessionListPacket
+class RequestSessionListPacket : public Packet {
+    GLOBED_PACKET(11011, RequestSessionListPacket, false, true);
+
+    RequestSessionListPacket() {}
+};
+
+GLOBED_SERIALIZABLE_STRUCT(RequestSessionListPacket, ());
+
+// 11012 - LogoutSessionPacket
+class LogoutSessionPacket : public Packet {
+    GLOBED_PACKET(11012, LogoutSessionPacket, false, true);
+
+    LogoutSessionPacket() {}
+    LogoutSessionPacket(uint64_t sessionId) : sessionId(sessionId) {}
+
+    uint64_t sessionId;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(LogoutSessionPacket, (sessionId));
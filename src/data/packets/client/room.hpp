@@ -80,3 +80,80 @@ class RequestRoomListPacket : public Packet {
 };
 
 GLOBED_SERIALIZABLE_STRUCT(RequestRoomListPacket, ());
+
+// 13007 - StartRoomCountdownPacket
+class StartRoomCountdownPacket : public Packet {
+    GLOBED_PACKET(13007, StartRoomCountdownPacket, false, false)
+
+    StartRoomCountdownPacket() {}
+    StartRoomCountdownPacket(uint8_t seconds) : seconds(seconds) {}
+
+    uint8_t seconds;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(StartRoomCountdownPacket, (seconds));
+
+// 13008 - SetRoomWebhookPacket
+class SetRoomWebhookPacket : public Packet {
+    GLOBED_PACKET(13008, SetRoomWebhookPacket, false, false)
+
+    SetRoomWebhookPacket() {}
+    SetRoomWebhookPacket(const std::string_view url) : url(url) {}
+
+    std::string url; // empty to remove the webhook
+};
+
+GLOBED_SERIALIZABLE_STRUCT(SetRoomWebhookPacket, (url));
+
+// 13009 - SetRoomModeratorPacket
+class SetRoomModeratorPacket : public Packet {
+    GLOBED_PACKET(13009, SetRoomModeratorPacket, false, false)
+
+    SetRoomModeratorPacket() {}
+    SetRoomModeratorPacket(int player, bool moderator) : player(player), moderator(moderator) {}
+
+    int player;
+    bool moderator; // false to take the role away
+};
+
+GLOBED_SERIALIZABLE_STRUCT(SetRoomModeratorPacket, (player, moderator));
+
+// 13010 - RoomKickPlayerPacket
+class RoomKickPlayerPacket : public Packet {
+    GLOBED_PACKET(13010, RoomKickPlayerPacket, false, false)
+
+    RoomKickPlayerPacket() {}
+    RoomKickPlayerPacket(int player) : player(player) {}
+
+    int player;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(RoomKickPlayerPacket, (player));
+
+// 13011 - SetRoomTagsPacket
+class SetRoomTagsPacket : public Packet {
+    GLOBED_PACKET(13011, SetRoomTagsPacket, false, false)
+
+    SetRoomTagsPacket() {}
+    SetRoomTagsPacket(std::vector<RoomTag>&& tags) : tags(std::move(tags)) {}
+
+    static constexpr size_t MAX_TAGS = 8;
+    static constexpr size_t MAX_NAME_LENGTH = 16;
+
+    std::vector<RoomTag> tags;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(SetRoomTagsPacket, (tags));
+
+// 13012 - SetRoomMemberTagPacket
+class SetRoomMemberTagPacket : public Packet {
+    GLOBED_PACKET(13012, SetRoomMemberTagPacket, false, false)
+
+    SetRoomMemberTagPacket() {}
+    SetRoomMemberTagPacket(int player, std::optional<uint8_t> tag) : player(player), tag(tag) {}
+
+    int player;
+    std::optional<uint8_t> tag; // empty to take the tag away
+};
+
+GLOBED_SERIALIZABLE_STRUCT(SetRoomMemberTagPacket, (player, tag));
@@ -62,10 +62,11 @@ public:
 };
 
 struct PacketHeader {
-    static constexpr size_t SIZE = sizeof(packetid_t) + sizeof(bool);
+    static constexpr size_t SIZE = sizeof(packetid_t) + sizeof(bool) + sizeof(bool);
 
     packetid_t id;
     bool encrypted;
+    bool compressed;
 };
 
-GLOBED_SERIALIZABLE_STRUCT(PacketHeader, (id, encrypted));
+GLOBED_SERIALIZABLE_STRUCT(PacketHeader, (id, encrypted, compressed));
@@ -2,6 +2,7 @@
 #include <data/packets/packet.hpp>
 #include <data/types/admin.hpp>
 #include <data/types/gd.hpp>
+#include <data/types/misc.hpp>
 #include <data/types/user.hpp>
 
 // 29000 - AdminAuthSuccessPacket
@@ -21,10 +22,11 @@ class AdminErrorPacket : public Packet {
 
     AdminErrorPacket() {}
 
+    ErrorCode code;
     std::string message;
 };
 
-GLOBED_SERIALIZABLE_STRUCT(AdminErrorPacket, (message));
+GLOBED_SERIALIZABLE_STRUCT(AdminErrorPacket, (code, message));
 
 // 29002 - AdminUserDataPacket
 class AdminUserDataPacket : public Packet {
@@ -34,9 +36,11 @@ class AdminUserDataPacket : public Packet {
 
     UserEntry userEntry;
     std::optional<PlayerRoomPreviewAccountData> accountData;
+    std::optional<ConnectionInfo> connection;
+    std::vector<LevelVisit> levelHistory; // oldest first
 };
 
-GLOBED_SERIALIZABLE_STRUCT(AdminUserDataPacket, (userEntry, accountData));
+GLOBED_SERIALIZABLE_STRUCT(AdminUserDataPacket, (userEntry, accountData, connection, levelHistory));
 
 // 29003 - AdminSuccessMessagePacket
 class AdminSuccessMessagePacket : public Packet {
@@ -57,3 +61,100 @@ class AdminAuthFailedPacket : public Packet {
 };
 
 GLOBED_SERIALIZABLE_STRUCT(AdminAuthFailedPacket, ());
+
+// 29005 - AdminPlayerHistoryPacket
+class AdminPlayerHistoryPacket : public Packet {
+    GLOBED_PACKET(29005, AdminPlayerHistoryPacket, false, false)
+
+    AdminPlayerHistoryPacket() {}
+
+    uint32_t interval; // seconds between two points
+    std::vector<PlayerCountPoint> points;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminPlayerHistoryPacket, (interval, points));
+
+// 29006 - AdminScheduledNoticesPacket
+class AdminScheduledNoticesPacket : public Packet {
+    GLOBED_PACKET(29006, AdminScheduledNoticesPacket, false, false)
+
+    AdminScheduledNoticesPacket() {}
+
+    std::vector<ScheduledNotice> notices; // the earliest first
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminScheduledNoticesPacket, (notices));
+
+// 29007 - AdminTrafficStatsPacket
+class AdminTrafficStatsPacket : public Packet {
+    GLOBED_PACKET(29007, AdminTrafficStatsPacket, false, false)
+
+    AdminTrafficStatsPacket() {}
+
+    int64_t since; // seconds since unix epoch
+    std::vector<TrafficEntry> rooms;
+    std::vector<TrafficEntry> levels;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminTrafficStatsPacket, (since, rooms, levels));
+
+// 29008 - AdminBotsPacket
+class AdminBotsPacket : public Packet {
+    GLOBED_PACKET(29008, AdminBotsPacket, false, true)
+
+    AdminBotsPacket() {}
+
+    std::vector<BotInfo> bots; // the oldest first
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminBotsPacket, (bots));
+
+// 29009 - AdminInviteCodePacket
+class AdminInviteCodePacket : public Packet {
+    GLOBED_PACKET(29009, AdminInviteCodePacket, false, true)
+
+    AdminInviteCodePacket() {}
+
+    std::string code;
+    uint32_t uses;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminInviteCodePacket, (code, uses));
+
+// 29010 - AdminReportsPacket
+class AdminReportsPacket : public Packet {
+    GLOBED_PACKET(29010, AdminReportsPacket, false, true)
+
+    AdminReportsPacket() {}
+
+    std::vector<AdminReport> reports; // open and claimed ones, the newest first
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminReportsPacket, (reports));
+
+// 29011 - AdminChatTailPacket
+class AdminChatTailPacket : public Packet {
+    GLOBED_PACKET(29011, AdminChatTailPacket, false, true)
+
+    AdminChatTailPacket() {}
+
+    uint32_t roomId;
+    LevelId levelId;
+    int32_t playerId;
+    std::string playerName;
+    uint32_t messageId;
+    std::string message;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminChatTailPacket, (roomId, levelId, playerId, playerName, messageId, message));
+
+// 29012 - AdminConfigOverridesPacket
+class AdminConfigOverridesPacket : public Packet {
+    GLOBED_PACKET(29012, AdminConfigOverridesPacket, false, true)
+
+    AdminConfigOverridesPacket() {}
+
+    std::vector<ConfigOverride> overrides;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminConfigOverridesPacket, (overrides));
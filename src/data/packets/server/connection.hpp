@@ -2,6 +2,8 @@
 #include <data/packets/packet.hpp>
 #include <data/types/crypto.hpp>
 #include <data/types/gd.hpp>
+#include <data/types/misc.hpp>
+#include <data/types/room.hpp>
 #include <data/types/user.hpp>
 
 // 20000 - PingResponsePacket
@@ -11,8 +13,9 @@ class PingResponsePacket : public Packet {
     PingResponsePacket() {}
 
     uint32_t id, playerCount;
+    uint8_t distanceClass;
 };
-GLOBED_SERIALIZABLE_STRUCT(PingResponsePacket, (id, playerCount));
+GLOBED_SERIALIZABLE_STRUCT(PingResponsePacket, (id, playerCount, distanceClass));
 
 // 20001 - CryptoHandshakeResponsePacket
 class CryptoHandshakeResponsePacket : public Packet {
@@ -21,8 +24,10 @@ class CryptoHandshakeResponsePacket : public Packet {
     CryptoHandshakeResponsePacket() {}
 
     CryptoPublicKey data;
+    // algorithm of compressed packets from now on, 0 - none, 1 - lz4
+    uint8_t compression;
 };
-GLOBED_SERIALIZABLE_STRUCT(CryptoHandshakeResponsePacket, (data));
+GLOBED_SERIALIZABLE_STRUCT(CryptoHandshakeResponsePacket, (data, compression));
 
 // 20002 - KeepaliveResponsePacket
 class KeepaliveResponsePacket : public Packet {
@@ -34,15 +39,37 @@ class KeepaliveResponsePacket : public Packet {
 };
 GLOBED_SERIALIZABLE_STRUCT(KeepaliveResponsePacket, (playerCount));
 
+enum class DisconnectReason : uint8_t {
+    Other = 0,
+    Idle = 1,
+    Kicked = 2,
+    Banned = 3,
+    Maintenance = 4,
+    DuplicateLogin = 5,
+    ProtocolError = 6,
+    Flooding = 7,
+    ServerShutdown = 8,
+};
+
+GLOBED_SERIALIZABLE_ENUM(DisconnectReason, Other, Idle, Kicked, Banned, Maintenance, DuplicateLogin, ProtocolError, Flooding, ServerShutdown);
+
 // 20003 - ServerDisconnectPacket
 class ServerDisconnectPacket : public Packet {
     GLOBED_PACKET(20003, ServerDisconnectPacket, false, false)
 
     ServerDisconnectPacket() {}
 
-    std::string message;
+    DisconnectReason reason;
+    std::string message; // optional detail text, may be empty
 };
-GLOBED_SERIALIZABLE_STRUCT(ServerDisconnectPacket, (message));
+GLOBED_SERIALIZABLE_STRUCT(ServerDisconnectPacket, (reason, message));
+
+struct DisabledFeatures {
+    bool voice;
+    bool chat;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(DisabledFeatures, (voice, chat));
 
 // 20004 - LoggedInPacket
 class LoggedInPacket : public Packet {
@@ -54,8 +81,10 @@ class LoggedInPacket : public Packet {
     SpecialUserData specialUserData;
     std::vector<GameServerRole> allRoles;
     uint32_t secretKey;
+    uint32_t maxDatagramSize;
+    DisabledFeatures disabledFeatures; // features the server turned off for our platform
 };
-GLOBED_SERIALIZABLE_STRUCT(LoggedInPacket, (tps, specialUserData, allRoles, secretKey));
+GLOBED_SERIALIZABLE_STRUCT(LoggedInPacket, (tps, specialUserData, allRoles, secretKey, maxDatagramSize, disabledFeatures));
 
 // 20005 - LoginFailedPacket
 class LoginFailedPacket : public Packet {
@@ -63,9 +92,10 @@ class LoginFailedPacket : public Packet {
 
     LoginFailedPacket() {}
 
-    std::string message;
+    ErrorCode code;
+    std::string message; // optional detail text, may be empty
 };
-GLOBED_SERIALIZABLE_STRUCT(LoginFailedPacket, (message));
+GLOBED_SERIALIZABLE_STRUCT(LoginFailedPacket, (code, message));
 
 // 20006 - ProtocolMismatchPacket
 class ProtocolMismatchPacket : public Packet {
@@ -102,6 +132,130 @@ class LoginRecoveryFailecPacket : public Packet {
 };
 GLOBED_SERIALIZABLE_STRUCT(LoginRecoveryFailecPacket, ());
 
+// 20010 - RekeyRequestPacket
+class RekeyRequestPacket : public Packet {
+    GLOBED_PACKET(20010, RekeyRequestPacket, true, true)
+
+    RekeyRequestPacket() {}
+
+    CryptoPublicKey key;
+};
+GLOBED_SERIALIZABLE_STRUCT(RekeyRequestPacket, (key));
+
+// 20011 - ServerTransferPacket
+class ServerTransferPacket : public Packet {
+    GLOBED_PACKET(20011, ServerTransferPacket, true, true)
+
+    ServerTransferPacket() {}
+
+    std::string address;
+    std::string token;
+};
+GLOBED_SERIALIZABLE_STRUCT(ServerTransferPacket, (address, token));
+
+// 20012 - TransferClaimedPacket
+class TransferClaimedPacket : public Packet {
+    GLOBED_PACKET(20012, TransferClaimedPacket, false, true)
+
+    TransferClaimedPacket() {}
+
+    RoomInfo info;
+};
+GLOBED_SERIALIZABLE_STRUCT(TransferClaimedPacket, (info));
+
+enum class DuplicateLoginNotice : uint8_t {
+    Replaced = 0,
+    ReplacedOther = 1,
+    Rejected = 2,
+    RejectedOther = 3,
+    Concurrent = 4,
+};
+
+GLOBED_SERIALIZABLE_ENUM(DuplicateLoginNotice, Replaced, ReplacedOther, Rejected, RejectedOther, Concurrent);
+
+// 20013 - DuplicateLoginPacket
+class DuplicateLoginPacket : public Packet {
+    GLOBED_PACKET(20013, DuplicateLoginPacket, false, true)
+
+    DuplicateLoginPacket() {}
+
+    DuplicateLoginNotice notice;
+};
+GLOBED_SERIALIZABLE_STRUCT(DuplicateLoginPacket, (notice));
+
+// 20014 - LatencyProbePacket
+class LatencyProbePacket : public Packet {
+    GLOBED_PACKET(20014, LatencyProbePacket, false, false)
+
+    LatencyProbePacket() {}
+
+    uint32_t id;
+};
+GLOBED_SERIALIZABLE_STRUCT(LatencyProbePacket, (id));
+
+// 20015 - SettingsHintsPacket
+class SettingsHintsPacket : public Packet {
+    GLOBED_PACKET(20015, SettingsHintsPacket, false, true)
+
+    SettingsHintsPacket() {}
+
+    SettingsHints hints;
+};
+GLOBED_SERIALIZABLE_STRUCT(SettingsHintsPacket, (hints));
+
+// 20016 - LoginNameRejectedPacket
+class LoginNameRejectedPacket : public Packet {
+    GLOBED_PACKET(20016, LoginNameRejectedPacket, false, false)
+
+    LoginNameRejectedPacket() {}
+
+    ErrorMessage message;
+};
+GLOBED_SERIALIZABLE_STRUCT(LoginNameRejectedPacket, (message));
+
+// 20019 - LoginWhitelistRequiredPacket
+class LoginWhitelistRequiredPacket : public Packet {
+    GLOBED_PACKET(20019, LoginWhitelistRequiredPacket, false, false)
+
+    LoginWhitelistRequiredPacket() {}
+
+    bool invalidCode;
+};
+GLOBED_SERIALIZABLE_STRUCT(LoginWhitelistRequiredPacket, (invalidCode));
+
+// 20020 - ThrottleHintPacket
+class ThrottleHintPacket : public Packet {
+    GLOBED_PACKET(20020, ThrottleHintPacket, false, true)
+
+    ThrottleHintPacket() {}
+
+    bool throttled;
+    uint16_t sendRate;
+    uint32_t voiceBitrate;
+};
+GLOBED_SERIALIZABLE_STRUCT(ThrottleHintPacket, (throttled, sendRate, voiceBitrate));
+
+// 20021 - StandaloneTokenPacket
+class StandaloneTokenPacket : public Packet {
+    GLOBED_PACKET(20021, StandaloneTokenPacket, true, true)
+
+    StandaloneTokenPacket() {}
+
+    std::string token;
+};
+GLOBED_SERIALIZABLE_STRUCT(StandaloneTokenPacket, (token));
+
+// 20022 - ServerRestartingPacket
+class ServerRestartingPacket : public Packet {
+    GLOBED_PACKET(20022, ServerRestartingPacket, false, true)
+
+    ServerRestartingPacket() {}
+
+    uint32_t seconds;
+    std::string alternateAddress; // empty if there is no server to move to
+};
+GLOBED_SERIALIZABLE_STRUCT(ServerRestartingPacket, (seconds, alternateAddress));
+
 // 20100 - ServerNoticePacket
 class ServerNoticePacket : public Packet {
     GLOBED_PACKET(20100, ServerNoticePacket, false, false)
@@ -1,6 +1,7 @@
 #pragma once
 #include <data/packets/packet.hpp>
 #include <data/types/gd.hpp>
+#include <data/types/misc.hpp>
 
 // 22000 - PlayerProfilesPacket
 class PlayerProfilesPacket : public Packet {
@@ -35,6 +36,88 @@ class LevelPlayerMetadataPacket : public Packet {
 
 GLOBED_SERIALIZABLE_STRUCT(LevelPlayerMetadataPacket, (players));
 
+// 22003 - LevelBlockedPacket
+class LevelBlockedPacket : public Packet {
+    GLOBED_PACKET(22003, LevelBlockedPacket, false, true)
+
+    LevelBlockedPacket() {}
+
+    LevelId levelId;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(LevelBlockedPacket, (levelId));
+
+// 22004 - LevelCompletedBroadcastPacket
+class LevelCompletedBroadcastPacket : public Packet {
+    GLOBED_PACKET(22004, LevelCompletedBroadcastPacket, false, true)
+
+    LevelCompletedBroadcastPacket() {}
+
+    int playerId;
+    std::string playerName;
+    uint32_t attempts;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(LevelCompletedBroadcastPacket, (playerId, playerName, attempts));
+
+// 22005 - SpectateStartedPacket
+class SpectateStartedPacket : public Packet {
+    GLOBED_PACKET(22005, SpectateStartedPacket, false, true)
+
+    SpectateStartedPacket() {}
+
+    int player;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(SpectateStartedPacket, (player));
+
+// 22006 - SpectateFailedPacket
+class SpectateFailedPacket : public Packet {
+    GLOBED_PACKET(22006, SpectateFailedPacket, false, true)
+
+    SpectateFailedPacket() {}
+
+    ErrorCode code;
+    std::string message; // optional detail text, may be empty
+};
+
+GLOBED_SERIALIZABLE_STRUCT(SpectateFailedPacket, (code, message));
+
+// 22007 - SpectatorCountPacket
+class SpectatorCountPacket : public Packet {
+    GLOBED_PACKET(22007, SpectatorCountPacket, false, true)
+
+    SpectatorCountPacket() {}
+
+    uint32_t count;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(SpectatorCountPacket, (count));
+
+// 22008 - SpectatedPlayerDataPacket
+class SpectatedPlayerDataPacket : public Packet {
+    GLOBED_PACKET(22008, SpectatedPlayerDataPacket, false, false)
+
+    SpectatedPlayerDataPacket() {}
+
+    AssociatedPlayerData data;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(SpectatedPlayerDataPacket, (data));
+
+// 22009 - LevelEventBroadcastPacket
+class LevelEventBroadcastPacket : public Packet {
+    GLOBED_PACKET(22009, LevelEventBroadcastPacket, false, false)
+
+    LevelEventBroadcastPacket() {}
+
+    int playerId;
+    uint32_t eventType;
+    std::vector<uint8_t> payload;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(LevelEventBroadcastPacket, (playerId, eventType, payload));
+
 #ifdef GLOBED_VOICE_SUPPORT
 # include <audio/frame.hpp>
 #endif
@@ -64,7 +147,74 @@ class ChatMessageBroadcastPacket : public Packet {
     ChatMessageBroadcastPacket() {}
 
     int sender;
+    uint32_t messageId;
     std::string message;
 };
 
-GLOBED_SERIALIZABLE_STRUCT(ChatMessageBroadcastPacket, (sender, message));
+GLOBED_SERIALIZABLE_STRUCT(ChatMessageBroadcastPacket, (sender, messageId, message));
+
+// 22012 - ChatMessageDeletedPacket
+class ChatMessageDeletedPacket : public Packet {
+    GLOBED_PACKET(22012, ChatMessageDeletedPacket, false, true)
+
+    ChatMessageDeletedPacket() {}
+
+    uint32_t messageId;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(ChatMessageDeletedPacket, (messageId));
+
+// 22013 - ChatMessageSentPacket
+class ChatMessageSentPacket : public Packet {
+    GLOBED_PACKET(22013, ChatMessageSentPacket, false, true)
+
+    ChatMessageSentPacket() {}
+
+    uint32_t clientId;
+    uint32_t messageId;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(ChatMessageSentPacket, (clientId, messageId));
+
+enum class LevelJoinRejection : uint8_t {
+    TooFast = 0,
+    PinnedLevel = 1,
+};
+
+GLOBED_SERIALIZABLE_ENUM(LevelJoinRejection, TooFast, PinnedLevel);
+
+// 22014 - LevelJoinRejectedPacket
+class LevelJoinRejectedPacket : public Packet {
+    GLOBED_PACKET(22014, LevelJoinRejectedPacket, false, true)
+
+    LevelJoinRejectedPacket() {}
+
+    LevelId levelId;
+    LevelJoinRejection reason;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(LevelJoinRejectedPacket, (levelId, reason));
+
+// 22015 - ChatMentionPacket
+class ChatMentionPacket : public Packet {
+    GLOBED_PACKET(22015, ChatMentionPacket, false, true)
+
+    ChatMentionPacket() {}
+
+    int sender;
+    uint32_t messageId;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(ChatMentionPacket, (sender, messageId));
+
+// 22016 - ChatChannelPacket
+class ChatChannelPacket : public Packet {
+    GLOBED_PACKET(22016, ChatChannelPacket, false, true)
+
+    ChatChannelPacket() {}
+
+    uint32_t channel;
+    uint32_t channelCount;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(ChatChannelPacket, (channel, channelCount));
@@ -2,6 +2,7 @@
 #include <data/packets/packet.hpp>
 #include <data/types/gd.hpp>
 #include <data/types/misc.hpp>
+#include <data/types/admin.hpp>
 
 // 21000 - GlobalPlayerListPacket
 class GlobalPlayerListPacket : public Packet {
@@ -43,6 +44,76 @@ class RolesUpdatedPacket : public Packet {
     RolesUpdatedPacket() {}
 
     SpecialUserData specialUserData;
+    // only sent when the role definitions were changed on the central server
+    std::optional<std::vector<GameServerRole>> allRoles;
 };
 
-GLOBED_SERIALIZABLE_STRUCT(RolesUpdatedPacket, (specialUserData));
+GLOBED_SERIALIZABLE_STRUCT(RolesUpdatedPacket, (specialUserData, allRoles));
+
+// 21004 - PreferencesPacket
+class PreferencesPacket : public Packet {
+    GLOBED_PACKET(21004, PreferencesPacket, false, true)
+
+    PreferencesPacket() {}
+
+    UserPreferences preferences;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(PreferencesPacket, (preferences));
+
+// 21005 - PlayerReportedPacket
+class PlayerReportedPacket : public Packet {
+    GLOBED_PACKET(21005, PlayerReportedPacket, false, true)
+
+    PlayerReportedPacket() {}
+
+    bool success; // false if the report could not be sent, for example when reporting too often
+};
+
+GLOBED_SERIALIZABLE_STRUCT(PlayerReportedPacket, (success));
+
+// 21006 - PresenceUpdatePacket
+class PresenceUpdatePacket : public Packet {
+    GLOBED_PACKET(21006, PresenceUpdatePacket, false, true)
+
+    PresenceUpdatePacket() {}
+
+    std::vector<PlayerPresence> players;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(PresenceUpdatePacket, (players));
+
+// 21007 - PlayerProfileChangedPacket
+class PlayerProfileChangedPacket : public Packet {
+    GLOBED_PACKET(21007, PlayerProfileChangedPacket, false, true)
+
+    PlayerProfileChangedPacket() {}
+
+    int32_t accountId;
+    PlayerIconData icons;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(PlayerProfileChangedPacket, (accountId, icons));
+
+// 21008 - FriendListPacket
+class FriendListPacket : public Packet {
+    GLOBED_PACKET(21008, FriendListPacket, false, true)
+
+    FriendListPacket() {}
+
+    std::vector<PlayerPresence> players; // only friends that are online
+};
+
+GLOBED_SERIALIZABLE_STRUCT(FriendListPacket, (players));
+
+// 21009 - SessionListPacket
+class SessionListPacket : public Packet {
+    GLOBED_PACKET(21009, SessionListPacket, false, true)
+
+    SessionListPacket() {}
+
+    uint64_t current; // our own session
+    std::vector<AccountSession> sessions; // active ones and ones that ended recently, newest first
+};
+
+GLOBED_SERIALIZABLE_STRUCT(SessionListPacket, (current, sessions));
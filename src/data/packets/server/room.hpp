@@ -1,6 +1,7 @@
 #pragma once
 #include <data/packets/packet.hpp>
 #include <data/types/gd.hpp>
+#include <data/types/misc.hpp>
 #include <data/types/room.hpp>
 
 // 23000 - RoomCreatedPacket
@@ -29,10 +30,11 @@ class RoomJoinFailedPacket : public Packet {
 
     RoomJoinFailedPacket() {}
 
-    bool wasInvalid, wasProtected, wasFull;
+    ErrorCode code;
+    std::string message; // optional detail text, may be empty
 };
 
-GLOBED_SERIALIZABLE_STRUCT(RoomJoinFailedPacket, (wasInvalid, wasProtected, wasFull));
+GLOBED_SERIALIZABLE_STRUCT(RoomJoinFailedPacket, (code, message));
 
 // 23003 - RoomPlayerListPacket
 class RoomPlayerListPacket : public Packet {
@@ -42,9 +44,11 @@ class RoomPlayerListPacket : public Packet {
 
     RoomInfo info;
     std::vector<PlayerRoomPreviewAccountData> players;
+    std::vector<int> moderators;
+    RoomTags tags;
 };
 
-GLOBED_SERIALIZABLE_STRUCT(RoomPlayerListPacket, (info, players));
+GLOBED_SERIALIZABLE_STRUCT(RoomPlayerListPacket, (info, players, moderators, tags));
 
 // 23004 - RoomInfoPacket
 class RoomInfoPacket : public Packet {
@@ -53,9 +57,11 @@ class RoomInfoPacket : public Packet {
     RoomInfoPacket() {}
 
     RoomInfo info;
+    std::vector<int> moderators;
+    RoomTags tags;
 };
 
-GLOBED_SERIALIZABLE_STRUCT(RoomInfoPacket, (info));
+GLOBED_SERIALIZABLE_STRUCT(RoomInfoPacket, (info, moderators, tags));
 
 // 23005 - RoomInvitePacket
 class RoomInvitePacket : public Packet {
@@ -86,7 +92,71 @@ class RoomCreateFailedPacket : public Packet {
 
     RoomCreateFailedPacket() {}
 
-    std::string reason;
+    ErrorCode code;
+    std::string message; // optional detail text, may be empty
 };
 
-GLOBED_SERIALIZABLE_STRUCT(RoomCreateFailedPacket, (reason));
+GLOBED_SERIALIZABLE_STRUCT(RoomCreateFailedPacket, (code, message));
+
+// 23008 - RoomCountdownPacket
+class RoomCountdownPacket : public Packet {
+    GLOBED_PACKET(23008, RoomCountdownPacket, false, true)
+
+    RoomCountdownPacket() {}
+
+    LevelId levelId;
+    uint32_t startsIn; // in milliseconds
+};
+
+GLOBED_SERIALIZABLE_STRUCT(RoomCountdownPacket, (levelId, startsIn));
+
+// 23009 - RoomAfkRemovedPacket
+class RoomAfkRemovedPacket : public Packet {
+    GLOBED_PACKET(23009, RoomAfkRemovedPacket, false, true)
+
+    RoomAfkRemovedPacket() {}
+};
+
+GLOBED_SERIALIZABLE_STRUCT(RoomAfkRemovedPacket, ());
+
+// 23010 - RoomInviteFailedPacket
+class RoomInviteFailedPacket : public Packet {
+    GLOBED_PACKET(23010, RoomInviteFailedPacket, false, true)
+
+    RoomInviteFailedPacket() {}
+
+    ErrorCode code;
+    std::string message; // optional detail text, may be empty
+};
+
+GLOBED_SERIALIZABLE_STRUCT(RoomInviteFailedPacket, (code, message));
+
+// 23011 - RoomWebhookSetPacket
+class RoomWebhookSetPacket : public Packet {
+    GLOBED_PACKET(23011, RoomWebhookSetPacket, false, true)
+
+    RoomWebhookSetPacket() {}
+};
+
+GLOBED_SERIALIZABLE_STRUCT(RoomWebhookSetPacket, ());
+
+// 23012 - RoomWebhookFailedPacket
+class RoomWebhookFailedPacket : public Packet {
+    GLOBED_PACKET(23012, RoomWebhookFailedPacket, false, true)
+
+    RoomWebhookFailedPacket() {}
+
+    ErrorCode code;
+    std::string message; // optional detail text, may be empty
+};
+
+GLOBED_SERIALIZABLE_STRUCT(RoomWebhookFailedPacket, (code, message));
+
+// 23013 - RoomKickedPacket
+class RoomKickedPacket : public Packet {
+    GLOBED_PACKET(23013, RoomKickedPacket, false, true)
+
+    RoomKickedPacket() {}
+};
+
+GLOBED_SERIALIZABLE_STRUCT(RoomKickedPacket, ());
@@ -2,6 +2,31 @@
 #include <data/bytebuffer.hpp>
 #include <util/data.hpp>
 
+class TimedRole {
+public:
+    std::string roleId;
+    std::optional<int64_t> startsAt;
+    std::optional<int64_t> expiresAt;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(TimedRole, (roleId, startsAt, expiresAt));
+
+// preferences saved with the account on the server, so they are the same on every device
+class UserPreferences {
+public:
+    bool hideInLists = false;
+    bool blockInvites = false;
+    bool disableDms = false;
+    bool shareEditorLevels = false;
+    bool hideUnlistedLevels = false;
+    bool hideSpectatorCount = false;
+    std::optional<std::string> language;
+
+    bool operator==(const UserPreferences&) const = default;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(UserPreferences, (hideInLists, blockInvites, disableDms, shareEditorLevels, hideUnlistedLevels, hideSpectatorCount, language));
+
 class UserEntry {
 public:
     UserEntry() {}
@@ -14,9 +39,14 @@ public:
         bool isMuted,
         bool isWhitelisted,
         std::optional<std::string> adminPassword,
+        std::optional<std::string> totpSecret,
         std::optional<std::string> violationReason,
-        std::optional<int64_t> violationExpiry
-    ) : accountId(accountId), userName(userName), nameColor(nameColor), userRoles(userRoles), isBanned(isBanned), isMuted(isMuted), isWhitelisted(isWhitelisted), adminPassword(adminPassword), violationReason(violationReason), violationExpiry(violationExpiry) {}
+        std::optional<int64_t> violationExpiry,
+        std::optional<int64_t> firstSeen,
+        uint32_t violationCount,
+        const std::vector<TimedRole>& timedRoles,
+        const UserPreferences& preferences
+    ) : accountId(accountId), userName(userName), nameColor(nameColor), userRoles(userRoles), isBanned(isBanned), isMuted(isMuted), isWhitelisted(isWhitelisted), adminPassword(adminPassword), totpSecret(totpSecret), violationReason(violationReason), violationExpiry(violationExpiry), firstSeen(firstSeen), violationCount(violationCount), timedRoles(timedRoles), preferences(preferences) {}
 
     int accountId;
     std::optional<std::string> userName;
@@ -26,8 +56,13 @@ public:
     bool isMuted;
     bool isWhitelisted;
     std::optional<std::string> adminPassword;
+    std::optional<std::string> totpSecret; // never sent by the server, and ignored on updates
     std::optional<std::string> violationReason;
     std::optional<int64_t> violationExpiry;
+    std::optional<int64_t> firstSeen; // read-only, ignored on updates
+    uint32_t violationCount; // read-only, ignored on updates
+    std::vector<TimedRole> timedRoles; // roles that start or expire at a specific time
+    UserPreferences preferences; // read-only, ignored on updates
 };
 
 GLOBED_SERIALIZABLE_STRUCT(
@@ -41,7 +76,100 @@ GLOBED_SERIALIZABLE_STRUCT(
         isMuted,
         isWhitelisted,
         adminPassword,
+        totpSecret,
         violationReason,
-        violationExpiry
+        violationExpiry,
+        firstSeen,
+        violationCount,
+        timedRoles,
+        preferences
     )
 );
+
+class ConnectionInfo {
+public:
+    uint8_t packetLoss; // in percent
+    bool lossy; // whether the server sends extra level snapshots to the user
+};
+
+GLOBED_SERIALIZABLE_STRUCT(ConnectionInfo, (packetLoss, lossy));
+
+// a level a player was on, shown to moderators
+class LevelVisit {
+public:
+    LevelId levelId;
+    int64_t joinedAt; // unix seconds
+    int64_t leftAt; // unix seconds, 0 if still on the level
+};
+
+GLOBED_SERIALIZABLE_STRUCT(LevelVisit, (levelId, joinedAt, leftAt));
+
+// a notice that an admin scheduled to be sent at a later time
+class ScheduledNotice {
+public:
+    uint32_t id;
+    int64_t sendAt; // seconds since unix epoch
+    std::string author;
+    int authorId;
+    bool everyone; // if true, room and level are ignored
+    uint32_t roomId;
+    LevelId levelId;
+    std::string message;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(ScheduledNotice, (id, sendAt, author, authorId, everyone, roomId, levelId, message));
+
+// triage state of a player report
+enum class ReportState : uint8_t {
+    Open = 0,
+    Claimed = 1,
+    Resolved = 2,
+    Rejected = 3,
+};
+
+GLOBED_SERIALIZABLE_ENUM(ReportState, Open, Claimed, Resolved, Rejected);
+
+class PlayerReport {
+public:
+    int64_t id;
+    int reporterId;
+    int reportedId;
+    std::string reason;
+    ReportState state;
+    int handledBy; // the moderator who claimed the report, 0 if it's open
+    int64_t createdAt; // seconds since unix epoch
+};
+
+GLOBED_SERIALIZABLE_STRUCT(PlayerReport, (id, reporterId, reportedId, reason, state, handledBy, createdAt));
+
+// names are empty if the server doesn't know the player right now
+class AdminReport {
+public:
+    PlayerReport report;
+    std::string reporterName;
+    std::string reportedName;
+    std::string handledByName;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminReport, (report, reporterName, reportedName, handledByName));
+
+// central server config values that admins can temporarily override on a game server
+enum class ConfigOverrideKey : uint8_t {
+    Tps = 0,
+    Maintenance = 1, // 1 to enable, 0 to disable
+    ChatBurstLimit = 2,
+    ChatBurstInterval = 3, // in milliseconds
+};
+
+GLOBED_SERIALIZABLE_ENUM(ConfigOverrideKey, Tps, Maintenance, ChatBurstLimit, ChatBurstInterval);
+
+class ConfigOverride {
+public:
+    ConfigOverrideKey key;
+    uint32_t value;
+    uint32_t original; // the value from the central server, restored once the override expires
+    int64_t expiresAt; // seconds since unix epoch
+    std::string author;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(ConfigOverride, (key, value, original, expiresAt, author));
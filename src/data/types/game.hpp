@@ -60,6 +60,7 @@ struct PlayerData {
     bool isDualMode;
     bool isInEditor;
     bool isEditorBuilding; // in the editor && not playtesting (incl. not paused)
+    bool isSpeaking; // talking in voice chat, cleared by the server if the player can't use voice chat
 };
 
 struct PlayerMetadata {
@@ -7,12 +7,16 @@
 #include <Geode/utils/cocos.hpp>
 
 #include <data/bytebuffer.hpp>
+#include <util/data.hpp>
 #include "user.hpp"
 #include "game.hpp"
 
 static constexpr uint8_t NO_GLOW = -1;
 static constexpr uint8_t NO_TRAIL = 1;
 
+// SHA-256 hash of the contents of a custom cosmetic
+using CosmeticHash = util::data::bytearray<32>;
+
 class PlayerIconData {
 public:
     static const PlayerIconData DEFAULT_ICONS;
@@ -29,10 +33,12 @@ public:
 
     int16_t cube, ship, ball, ufo, wave, robot, spider, swing, jetpack;
     uint8_t deathEffect, color1, color2, glowColor, streak, shipStreak;
+    // only relayed to others if the server approved it
+    std::optional<CosmeticHash> customCosmetic;
 };
 
 GLOBED_SERIALIZABLE_STRUCT(PlayerIconData, (
-    cube, ship, ball, ufo, wave, robot, spider, swing, jetpack, deathEffect, color1, color2, glowColor, streak, shipStreak
+    cube, ship, ball, ufo, wave, robot, spider, swing, jetpack, deathEffect, color1, color2, glowColor, streak, shipStreak, customCosmetic
 ));
 
 inline const PlayerIconData PlayerIconData::DEFAULT_ICONS = PlayerIconData(
@@ -54,14 +60,17 @@ public:
             score->m_glowEnabled ? (uint8_t)score->m_color3 : NO_GLOW
         ) {}
 
-    PlayerIconDataSimple(const PlayerIconData& data) : PlayerIconDataSimple(data.cube, data.color1, data.color2, data.glowColor) {}
+    PlayerIconDataSimple(const PlayerIconData& data) : PlayerIconDataSimple(data.cube, data.color1, data.color2, data.glowColor) {
+        customCosmetic = data.customCosmetic;
+    }
 
     int16_t cube;
     uint8_t color1, color2, glowColor;
+    std::optional<CosmeticHash> customCosmetic;
 };
 
 GLOBED_SERIALIZABLE_STRUCT(PlayerIconDataSimple, (
-    cube, color1, color2, glowColor
+    cube, color1, color2, glowColor, customCosmetic
 ));
 
 struct SpecialUserData {
@@ -87,10 +96,12 @@ public:
     PlayerIconDataSimple icons;
     LevelId levelId;
     SpecialUserData specialUserData;
+    bool isAfk = false;
+    uint16_t spectators = 0;
 };
 
 GLOBED_SERIALIZABLE_STRUCT(PlayerRoomPreviewAccountData, (
-    accountId, userId, name, icons, levelId, specialUserData
+    accountId, userId, name, icons, levelId, specialUserData, isAfk, spectators
 ));
 
 class PlayerPreviewAccountData {
@@ -118,6 +129,19 @@ GLOBED_SERIALIZABLE_STRUCT(PlayerPreviewAccountData, (
     accountId, userId, name, icons, specialUserData
 ));
 
+// where a player we subscribed to or a friend is, see SubscribePresencePacket and UpdateFriendListPacket
+class PlayerPresence {
+public:
+    int32_t accountId;
+    bool online;     // false if offline or invisible
+    uint32_t roomId; // 0 if in the global room or a hidden room
+    LevelId levelId; // 0 unless they are in the same room as us or a mutual friend, and don't hide their level
+};
+
+GLOBED_SERIALIZABLE_STRUCT(PlayerPresence, (
+    accountId, online, roomId, levelId
+));
+
 class PlayerAccountData {
 public:
     static const PlayerAccountData DEFAULT_DATA;
@@ -23,8 +23,124 @@ GLOBED_SERIALIZABLE_STRUCT(GlobedLevel, (
     levelId, playerCount
 ));
 
+// player count at a point in time, for the player count history in the admin panel
+class PlayerCountPoint {
+public:
+    int64_t timestamp;
+    uint32_t count;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(PlayerCountPoint, (
+    timestamp, count
+));
+
+// outgoing traffic of a room or a level, levelId is 0 for rooms and roomId is 0 for levels
+class TrafficEntry {
+public:
+    uint32_t roomId;
+    LevelId levelId;
+    uint64_t bytes;
+    uint64_t packets;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(TrafficEntry, (
+    roomId, levelId, bytes, packets
+));
+
+// a bot spawned by an admin, its name already includes the [BOT] prefix
+class BotInfo {
+public:
+    int accountId;
+    std::string name;
+    uint32_t roomId;
+    LevelId levelId;
+    cocos2d::CCPoint position;
+    std::string spawnedBy;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(BotInfo, (
+    accountId, name, roomId, levelId, position, spawnedBy
+));
+
+// client settings recommended by the server for our connection
+class SettingsHints {
+public:
+    uint16_t interpolationWindow; // in milliseconds
+    uint32_t voiceBitrate;
+    uint16_t sendRate;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(SettingsHints, (
+    interpolationWindow, voiceBitrate, sendRate
+));
+
+// why the server refused a request, sent in failure packets together with optional detail text
+enum class ErrorCode : uint8_t {
+    Other = 0,
+    Internal = 1,
+    CentralUnavailable = 2,
+    RateLimited = 3,
+    Busy = 4,
+    InvalidToken = 5,
+    AlreadyLoggedIn = 6,
+    InvalidRequest = 7,
+    NoPermission = 8,
+    Restricted = 9,
+    NotFound = 10,
+    WrongPassword = 11,
+    Full = 12,
+    LevelBlocked = 13,
+    LimitReached = 14,
+    Unavailable = 15,
+    Rejected = 16,
+    TwoFactorRequired = 17,
+    Conflict = 18,
+    NameTaken = 19,
+};
+
+GLOBED_SERIALIZABLE_ENUM(ErrorCode,
+    Other, Internal, CentralUnavailable, RateLimited, Busy, InvalidToken, AlreadyLoggedIn, InvalidRequest, NoPermission,
+    Restricted, NotFound, WrongPassword, Full, LevelBlocked, LimitReached, Unavailable, Rejected, TwoFactorRequired, Conflict,
+    NameTaken
+);
+
+// platform the client runs on, sent in FeatureUsagePacket and shown in the session list
+enum class ClientPlatform : uint8_t {
+    Unknown = 0,
+    Windows = 1,
+    Mac = 2,
+    Android = 3,
+    Ios = 4,
+};
+
+GLOBED_SERIALIZABLE_ENUM(ClientPlatform, Unknown, Windows, Mac, Android, Ios);
+
+// a login of the account on any server, see SessionListPacket
+struct AccountSession {
+    int32_t accountId;
+    uint64_t sessionId;
+    ClientPlatform platform;
+    std::string ipPrefix; // the network, like 1.2.3.0/24, never the full address
+    int64_t loginTime;
+    int64_t logoutTime; // 0 if the session is still active
+
+    bool isActive() const {
+        return logoutTime == 0;
+    }
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AccountSession, (
+    accountId, sessionId, platform, ipPrefix, loginTime, logoutTime
+));
+
+// whether the same request might succeed if it's sent again later, without the player changing anything
+constexpr bool isTransientError(ErrorCode code) {
+    return code == ErrorCode::Internal || code == ErrorCode::CentralUnavailable || code == ErrorCode::RateLimited || code == ErrorCode::Busy;
+}
+
 class ErrorMessage {
 public:
+    constexpr ErrorMessage() : hash(0) {}
     constexpr ErrorMessage(uint32_t h) : hash(h) {}
 
     uint32_t hash;
@@ -8,24 +8,50 @@ struct RoomSettingsFlags : BitfieldBase {
     bool publicInvites;
     bool collision;
     bool twoPlayerMode;
+    bool hideEditorLevels;
+    bool hideUnlistedLevels;
+    bool chatMuted; // only the owner and the moderators can send chat messages
 
     // we need the struct to be 2 bytes
-    bool _pad1, _pad2, _pad3, _pad4, _pad5;
+    bool _pad1, _pad2;
 };
 
 static_assert((sizeof(RoomSettingsFlags) + 7) / 8 == 2);
 
 GLOBED_SERIALIZABLE_BITFIELD(RoomSettingsFlags, (
-    isHidden, publicInvites, collision, twoPlayerMode
+    isHidden, publicInvites, collision, twoPlayerMode, hideEditorLevels, hideUnlistedLevels, chatMuted
 ))
 
+enum class RoomChatFilter : uint8_t {
+    ServerDefault = 0,
+    Off = 1,
+    Standard = 2,
+    Strict = 3,
+};
+
+GLOBED_SERIALIZABLE_ENUM(RoomChatFilter, ServerDefault, Off, Standard, Strict);
+
+enum class RoomVoicePolicy : uint8_t {
+    Everyone = 0,
+    ManagersOnly = 1, // only the owner and the moderators can talk
+    Disabled = 2,
+    Proximity = 3, // only players close to the speaker hear them
+};
+
+GLOBED_SERIALIZABLE_ENUM(RoomVoicePolicy, Everyone, ManagersOnly, Disabled, Proximity);
+
 struct RoomSettings {
     RoomSettingsFlags flags;
     uint32_t playerLimit;
+    RoomChatFilter chatFilter;
+    uint32_t slowMode; // seconds
+    LevelId pinnedLevel; // 0 if players can join any level
+    uint64_t allowedRoles; // bit `n` is set if players with the role of int ID `n` can join, 0 if anyone can join
+    RoomVoicePolicy voicePolicy;
 };
 
 GLOBED_SERIALIZABLE_STRUCT(RoomSettings, (
-    flags, playerLimit
+    flags, playerLimit, chatFilter, slowMode, pinnedLevel, allowedRoles, voicePolicy
 ))
 
 struct RoomInfo {
@@ -51,3 +77,31 @@ struct RoomListingInfo {
 GLOBED_SERIALIZABLE_STRUCT(RoomListingInfo, (
     id, owner, name, hasPassword, settings
 ));
+
+// a tag the room owner defined, independent of server roles
+struct RoomTag {
+    std::string name;
+    cocos2d::ccColor3B color;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(RoomTag, (
+    name, color
+));
+
+struct RoomMemberTag {
+    int accountId;
+    uint8_t tag; // index into RoomTags::tags
+};
+
+GLOBED_SERIALIZABLE_STRUCT(RoomMemberTag, (
+    accountId, tag
+));
+
+struct RoomTags {
+    std::vector<RoomTag> tags;
+    std::vector<RoomMemberTag> members;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(RoomTags, (
+    tags, members
+));
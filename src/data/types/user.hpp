@@ -16,10 +16,13 @@ struct ServerRole {
     bool ban;
     bool editRole;
     bool admin;
+    bool slowModeExempt;
+    bool spawnBots;
+    bool roomWebhooks;
 };
 
 GLOBED_SERIALIZABLE_STRUCT(ServerRole, (
-    id, priority, badgeIcon, nameColor, chatColor, notices, noticesToEveryone, kick, kickEveryone, mute, ban, editRole, admin
+    id, priority, badgeIcon, nameColor, chatColor, notices, noticesToEveryone, kick, kickEveryone, mute, ban, editRole, admin, slowModeExempt, spawnBots, roomWebhooks
 ));
 
 struct GameServerRole {
@@ -43,8 +46,13 @@ struct ComputedRole {
     bool ban;
     bool editRole;
     bool admin;
+    bool slowModeExempt;
+    bool spawnBots;
+    bool roomWebhooks;
+
+    uint64_t roleMask; // bit `n` is set if the user has the role of int ID `n`
 };
 
 GLOBED_SERIALIZABLE_STRUCT(ComputedRole, (
-    priority, badgeIcon, nameColor, chatColor, notices, noticesToEveryone, kick, kickEveryone, mute, ban, editRole, admin
+    priority, badgeIcon, nameColor, chatColor, notices, noticesToEveryone, kick, kickEveryone, mute, ban, editRole, admin, slowModeExempt, spawnBots, roomWebhooks, roleMask
 ));
@@ -92,6 +92,7 @@ static inline void lerpPlayer(
     out.isDualMode = older.isDualMode;
     out.isInEditor = older.isInEditor;
     out.isEditorBuilding = older.isEditorBuilding;
+    out.isSpeaking = older.isSpeaking;
 }
 
 void PlayerInterpolator::tick(float dt) {
@@ -106,7 +107,13 @@ void PlayerInterpolator::tick(float dt) {
             continue;
         }
 
-        float lerpRatio = (player.timeCounter - player.olderFrame.timestamp) / frameDelta;
+        // if the next frame is late, don't keep moving the player forever
+        float lerpTime = player.timeCounter;
+        if (settings.extrapolationWindow > 0.f) {
+            lerpTime = std::min(lerpTime, player.newerFrame.timestamp + settings.extrapolationWindow);
+        }
+
+        float lerpRatio = (lerpTime - player.olderFrame.timestamp) / frameDelta;
         lerpPlayer(player.olderFrame.visual, player.newerFrame.visual, player.interpolatedState, lerpRatio);
 
         LerpLogger::get().logLerpOperation(playerId, this->getLocalTs(), player.timeCounter, player.interpolatedState.player1);
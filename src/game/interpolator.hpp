@@ -7,6 +7,7 @@ struct InterpolatorSettings {
     bool realtime;      // no interpolation at all
     bool isPlatformer;  // platformer duh
     float expectedDelta;
+    float extrapolationWindow; // how long players keep moving past their newest frame, in seconds. 0 means no limit
 };
 
 class PlayerInterpolator {
@@ -11,6 +11,7 @@ struct VisualPlayerState {
     bool isDualMode = false;
     bool isInEditor = false;
     bool isEditorBuilding = false;
+    bool isSpeaking = false;
 
     VisualPlayerState() {}
 
@@ -24,6 +25,7 @@ struct VisualPlayerState {
         isDualMode = pd.isDualMode;
         isInEditor = pd.isInEditor;
         isEditorBuilding = pd.isEditorBuilding;
+        isSpeaking = pd.isSpeaking;
     }
 };
 
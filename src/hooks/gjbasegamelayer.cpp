@@ -183,7 +183,8 @@ void GlobedGJBGL::setupAudio() {
     GlobedSettings& settings = GlobedSettings::get();
     auto winSize = CCDirector::get()->getWinSize();
 
-    if (settings.communication.voiceEnabled) {
+    // the server can turn off voice chat for our platform, in which case don't record or show anything
+    if (settings.communication.voiceEnabled && !NetworkManager::get().isVoiceDisabled()) {
         auto& vm = GlobedAudioManager::get();
 # ifdef GLOBED_VOICE_CAN_TALK
         // set the audio device
@@ -256,12 +257,180 @@ void GlobedGJBGL::setupPacketListeners() {
         }
     });
 
+    nm.addListener<LevelBlockedPacket>(this, [this](std::shared_ptr<LevelBlockedPacket> packet) {
+        // stop sending anything about this level, the server is going to ignore it anyway
+        this->m_fields->globedReady = false;
+        ErrorQueues::get().error("This level has been blocked on this server, other players will not be visible.");
+    });
+
+    nm.addListener<LevelCompletedBroadcastPacket>(this, [this](std::shared_ptr<LevelCompletedBroadcastPacket> packet) {
+        if (!GlobedSettings::get().players.showCompletions) return;
+        if (BlockListManager::get().isHidden(packet->playerId)) return;
+
+        Notification::create(fmt::format("{} completed the level in {} attempts!", packet->playerName, packet->attempts), NotificationIcon::Success)->show();
+    });
+
+    nm.addListener<LevelEventBroadcastPacket>(this, [this](std::shared_ptr<LevelEventBroadcastPacket> packet) {
+        if (BlockListManager::get().isHidden(packet->playerId)) return;
+
+        // the event types are up to the mods using them, so just pass it on
+        using LevelEvent = DispatchEvent<int, uint32_t, std::vector<uint8_t>*>;
+        LevelEvent("level-event"_spr, packet->playerId, packet->eventType, &packet->payload).post();
+    });
+
+    nm.addListener<SpectateStartedPacket>(this, [this](std::shared_ptr<SpectateStartedPacket> packet) {
+        this->m_fields->spectating = packet->player;
+    });
+
+    nm.addListener<SpectateFailedPacket>(this, [this](std::shared_ptr<SpectateFailedPacket> packet) {
+        this->m_fields->spectating = 0;
+
+        switch (packet->code) {
+            case ErrorCode::Full:
+                ErrorQueues::get().warn("This player already has too many spectators.");
+                break;
+            default:
+                ErrorQueues::get().warn("This player can't be spectated right now.");
+                break;
+        }
+    });
+
+    nm.addListener<SpectatorCountPacket>(this, [this](std::shared_ptr<SpectatorCountPacket> packet) {
+        uint32_t previous = std::exchange(this->m_fields->spectatorCount, packet->count);
+
+        if (packet->count != previous && packet->count != 0 && GlobedSettings::get().globed.showSpectatorCount) {
+            Notification::create(
+                packet->count == 1 ? std::string("1 player is spectating you") : fmt::format("{} players are spectating you", packet->count),
+                NotificationIcon::Info
+            )->show();
+        }
+    });
+
+    nm.addListener<SpectatedPlayerDataPacket>(this, [this](std::shared_ptr<SpectatedPlayerDataPacket> packet) {
+        // the player might have left the level, the regular level data will tell us if they come back
+        if (packet->data.accountId != this->m_fields->spectating || !this->m_fields->players.contains(packet->data.accountId)) return;
+
+        this->m_fields->interpolator->updatePlayer(packet->data.accountId, packet->data.data, this->m_fields->timeCounter);
+    });
+
+    nm.addListener<LevelJoinRejectedPacket>(this, [this](std::shared_ptr<LevelJoinRejectedPacket> packet) {
+        // we are not on the level as far as the server is concerned, so stop sending data about it
+        this->m_fields->globedReady = false;
+
+        switch (packet->reason) {
+            case LevelJoinRejection::TooFast:
+                ErrorQueues::get().warn("You are switching levels too quickly, other players will not be visible. Try reopening the level in a few seconds.");
+                break;
+            case LevelJoinRejection::PinnedLevel:
+                ErrorQueues::get().warn("This room only allows playing a specific level, other players will not be visible.");
+                break;
+        }
+    });
+
     nm.addListener<ChatMessageBroadcastPacket>(this, [this](std::shared_ptr<ChatMessageBroadcastPacket> packet) {
-        this->m_fields->chatMessages.push_back({packet->sender, packet->message});
+        bool mentioned = this->m_fields->pendingMentions.erase(packet->messageId) > 0;
+        this->m_fields->chatMessages.push_back({packet->messageId, packet->sender, packet->message, mentioned});
 
         //m_fields->chatOverlay->addMessage(packet->sender, packet->message);
     });
 
+    nm.addListener<ChatMessageSentPacket>(this, [this](std::shared_ptr<ChatMessageSentPacket> packet) {
+        // chat messages go over udp and some get rejected, so they can't be matched by the order they were sent in
+        int ownId = GJAccountManager::sharedState()->m_accountID;
+        for (auto& message : this->m_fields->chatMessages) {
+            if (message.id == 0 && message.accountId == ownId && message.clientId == packet->clientId) {
+                message.id = packet->messageId;
+                this->m_fields->chatRevision++;
+                break;
+            }
+        }
+    });
+
+    nm.addListener<ChatMessageDeletedPacket>(this, [this](std::shared_ptr<ChatMessageDeletedPacket> packet) {
+        auto& messages = this->m_fields->chatMessages;
+        auto it = std::find_if(messages.begin(), messages.end(), [&](const auto& message) {
+            return message.id == packet->messageId;
+        });
+
+        if (it != messages.end()) {
+            messages.erase(it);
+            this->m_fields->chatRevision++;
+        }
+    });
+
+    nm.addListener<ChatMentionPacket>(this, [this](std::shared_ptr<ChatMentionPacket> packet) {
+        if (!this->shouldLetMessageThrough(packet->sender)) return;
+
+        auto& messages = this->m_fields->chatMessages;
+        auto it = std::find_if(messages.begin(), messages.end(), [&](const auto& message) {
+            return message.id == packet->messageId;
+        });
+
+        if (it != messages.end()) {
+            it->mentioned = true;
+            this->m_fields->chatRevision++;
+        } else {
+            this->m_fields->pendingMentions.insert(packet->messageId);
+        }
+
+        auto data = ProfileCacheManager::get().getData(packet->sender);
+        std::string name = data ? data->name : "Someone";
+        Notification::create(fmt::format("{} mentioned you in chat", name), NotificationIcon::Info)->show();
+    });
+
+    nm.addListener<ChatChannelPacket>(this, [this](std::shared_ptr<ChatChannelPacket> packet) {
+        this->m_fields->chatChannel = packet->channel;
+        this->m_fields->chatChannelCount = packet->channelCount;
+    });
+
+    nm.addListener<ThrottleHintPacket>(this, [this](std::shared_ptr<ThrottleHintPacket> packet) {
+        // the manager has already stored the new rate, just start sending at it
+        this->updateConfiguredTps();
+        this->unscheduleSelectors();
+        this->rescheduleSelectors();
+    });
+
+    nm.addListener<RoomAfkRemovedPacket>(this, [this](std::shared_ptr<RoomAfkRemovedPacket> packet) {
+        // we were taken off the level together with the room, join it again in the global room
+        auto levelId = HookedGJGameLevel::getLevelIDFrom(m_level);
+        NetworkManager::get().send(LevelJoinPacket::create(levelId, m_level->m_unlisted));
+    });
+
+    nm.addListener<RoomKickedPacket>(this, [this](std::shared_ptr<RoomKickedPacket> packet) {
+        auto levelId = HookedGJGameLevel::getLevelIDFrom(m_level);
+        NetworkManager::get().send(LevelJoinPacket::create(levelId, m_level->m_unlisted));
+    });
+
+    nm.addListener<RoomCountdownPacket>(this, [this](std::shared_ptr<RoomCountdownPacket> packet) {
+        if (packet->levelId != HookedGJGameLevel::getLevelIDFrom(m_level)) return;
+
+        m_fields->countdownEnd = util::time::now() + util::time::millis(packet->startsIn);
+
+        if (!m_fields->countdownLabel) {
+            auto winSize = CCDirector::get()->getWinSize();
+
+            Build<CCLabelBMFont>::create("", "bigFont.fnt")
+                .pos(winSize.width / 2.f, winSize.height / 2.f + 60.f)
+                .zOrder(11)
+                .id("room-countdown"_spr)
+                .parent(m_uiLayer)
+                .store(m_fields->countdownLabel);
+        }
+
+        m_fields->countdownLabel->stopAllActions();
+        m_fields->countdownLabel->setOpacity(255);
+        m_fields->countdownLabel->setVisible(true);
+        this->updateCountdown();
+    });
+
+    nm.addListener<PlayerReportedPacket>(this, [](std::shared_ptr<PlayerReportedPacket> packet) {
+        if (packet->success) {
+            Notification::create("Report sent", NotificationIcon::Success)->show();
+        } else {
+            ErrorQueues::get().warn("Failed to send the report, please try again later.");
+        }
+    });
+
     nm.addListener<VoiceBroadcastPacket>(this, [this](std::shared_ptr<VoiceBroadcastPacket> packet) {
 #ifdef GLOBED_VOICE_SUPPORT
         // if deafened or voice is disabled, do nothing
@@ -293,7 +462,7 @@ void GlobedGJBGL::setupCustomKeybinds() {
     // the only keybinds used are for voice chat, so if voice is disabled, do nothing
 
     auto& settings = GlobedSettings::get();
-    if (!settings.communication.voiceEnabled) {
+    if (!settings.communication.voiceEnabled || NetworkManager::get().isVoiceDisabled()) {
         return;
     }
 
@@ -349,7 +518,7 @@ void GlobedGJBGL::setupUpdate() {
         // send LevelJoinPacket and RequestPlayerProfilesPacket
 
         auto levelId = HookedGJGameLevel::getLevelIDFrom(self->m_level);
-        nm.send(LevelJoinPacket::create(levelId));
+        nm.send(LevelJoinPacket::create(levelId, self->m_level->m_unlisted));
 
         self->rescheduleSelectors();
         self->getParent()->schedule(schedule_selector(GlobedGJBGL::selUpdate), 0.f);
@@ -364,19 +533,17 @@ void GlobedGJBGL::setupMisc() {
 
     m_fields->isVoiceProximity = m_level->isPlatformer() ? settings.communication.voiceProximity : settings.communication.classicProximity;
 
-    // set the configured tps
-    auto tpsCap = settings.globed.tpsCap;
-    if (tpsCap != 0) {
-        m_fields->configuredTps = std::min(nm.getServerTps(), (uint32_t)tpsCap);
-    } else {
-        m_fields->configuredTps = nm.getServerTps();
-    }
+    this->updateConfiguredTps();
+
+    // the server can also recommend a shorter interpolation window, depending on our connection
+    auto hints = nm.getSettingsHints();
 
     // interpolator
     m_fields->interpolator = std::make_unique<PlayerInterpolator>(InterpolatorSettings {
         .realtime = false,
         .isPlatformer = m_level->isPlatformer(),
-        .expectedDelta = (1.0f / m_fields->configuredTps)
+        .expectedDelta = (1.0f / m_fields->configuredTps),
+        .extrapolationWindow = hints ? hints->interpolationWindow / 1000.f : 0.f
     });
 
     // player store
@@ -400,6 +567,13 @@ void GlobedGJBGL::setupMisc() {
         this->toggleSafeMode(true);
     }
 
+    // let other mods send level events through us
+    using SendLevelEventFilter = DispatchFilter<uint32_t, std::vector<uint8_t>*>;
+    this->addEventListener<SendLevelEventFilter>([this](uint32_t eventType, std::vector<uint8_t>* payload) {
+        this->sendLevelEvent(eventType, *payload);
+        return ListenerResult::Stop;
+    }, "send-level-event"_spr);
+
     // vmt hook
 #ifdef GEODE_IS_WINDOWS
     static auto patch = [&]() -> Patch* {
@@ -497,7 +671,7 @@ void GlobedGJBGL::selSendPlayerData(float) {
     if ((self->m_fields->players.empty() && self->m_fields->totalSentPackets % 30 != 15) || self->m_fields->quitting) return;
 
     auto data = self->gatherPlayerData();
-    NetworkManager::get().send(PlayerDataPacket::create(data));
+    NetworkManager::get().send(PlayerDataPacket::create(++self->m_fields->playerDataSequence, data));
 }
 
 // selSendPlayerMetadata - runs every 10 seconds
@@ -616,6 +790,11 @@ void GlobedGJBGL::selUpdate(float timescaledDt) {
 
     self->m_fields->timeCounter += dt;
 
+    // spectators get our data every frame, the server keeps telling us how many there are until it's zero
+    if (self->m_fields->spectatorCount != 0 && self->established() && !self->m_fields->quitting) {
+        NetworkManager::get().send(SpectatorDataPacket::create(self->gatherPlayerData()));
+    }
+
     self->m_fields->interpolator->tick(dt);
 
     if (auto pl = PlayLayer::get()) {
@@ -639,12 +818,13 @@ void GlobedGJBGL::selUpdate(float timescaledDt) {
 
         auto frameFlags = self->m_fields->interpolator->swapFrameFlags(playerId);
 
-        bool isSpeaking = vpm.isSpeaking(playerId);
+        // the speaking flag comes with the player state, so it also works for players whose audio we don't play
+        bool isPlaying = vpm.isSpeaking(playerId);
         remotePlayer->updateData(
             vstate,
             frameFlags,
-            isSpeaking,
-            isSpeaking ? vpm.getLoudness(playerId) : 0.f
+            isPlaying || vstate.isSpeaking,
+            isPlaying ? vpm.getLoudness(playerId) : 0.f
         );
 
         // update progress icons
@@ -670,6 +850,8 @@ void GlobedGJBGL::selUpdate(float timescaledDt) {
         self->m_fields->voiceOverlay->updateOverlaySoft();
     }
 
+    self->updateCountdown();
+
     // update self names
     if (self->m_fields->ownNameLabel) {
         if (self->m_player1->m_isHidden) {
@@ -848,6 +1030,7 @@ PlayerData GlobedGJBGL::gatherPlayerData() {
         .isDualMode = m_gameState.m_isDualMode,
         .isInEditor = isInEditor,
         .isEditorBuilding = isEditorBuilding,
+        .isSpeaking = VoiceRecordingManager::get().isRecording(),
     };
 }
 
@@ -1027,6 +1210,10 @@ void GlobedGJBGL::onQuitActions() {
 
     if (m_fields->globedReady) {
         if (nm.established()) {
+            if (m_fields->spectating != 0) {
+                nm.send(SpectatePlayerPacket::create(0));
+            }
+
             // send LevelLeavePacket
             nm.send(LevelLeavePacket::create());
         }
@@ -1087,6 +1274,55 @@ bool GlobedGJBGL::accountForSpeedhack(int uniqueKey, float cap, float allowance)
     return true;
 }
 
+void GlobedGJBGL::updateConfiguredTps() {
+    auto& settings = GlobedSettings::get();
+    auto& nm = NetworkManager::get();
+
+    auto tpsCap = settings.globed.tpsCap;
+    if (tpsCap != 0) {
+        m_fields->configuredTps = std::min(nm.getServerTps(), (uint32_t)tpsCap);
+    } else {
+        m_fields->configuredTps = nm.getServerTps();
+    }
+
+    // the server can recommend a lower send rate depending on our connection, or ask us to send less if our packets pile up
+    auto hints = nm.getSettingsHints();
+    if (hints && hints->sendRate != 0) {
+        m_fields->configuredTps = std::min(m_fields->configuredTps, (uint32_t)hints->sendRate);
+    }
+
+    auto throttledRate = nm.getThrottledSendRate();
+    if (throttledRate != 0) {
+        m_fields->configuredTps = std::min(m_fields->configuredTps, throttledRate);
+    }
+}
+
+void GlobedGJBGL::updateCountdown() {
+    if (!m_fields->countdownEnd) return;
+
+    auto remaining = util::time::asMillis(*m_fields->countdownEnd - util::time::now());
+
+    if (remaining > 0) {
+        // round up, so that it shows 3, 2, 1 and not 2, 1, 0
+        m_fields->countdownLabel->setString(std::to_string((remaining + 999) / 1000).c_str());
+        return;
+    }
+
+    m_fields->countdownEnd = std::nullopt;
+
+    m_fields->countdownLabel->setString("Go!");
+    m_fields->countdownLabel->runAction(CCSequence::create(
+        CCDelayTime::create(0.75f),
+        CCFadeOut::create(0.5f),
+        nullptr
+    ));
+
+    // everyone starts the level from the beginning at the same moment
+    if (auto pl = PlayLayer::get()) {
+        pl->resetLevel();
+    }
+}
+
 void GlobedGJBGL::unscheduleSelectors() {
     auto* sched = CCScheduler::get();
     sched->unscheduleSelector(schedule_selector(GlobedGJBGL::selSendPlayerData), this->getParent());
@@ -1309,6 +1545,22 @@ void GlobedGJBGL::updateCamera(float dt) {
     m_player1->setPosition(lastPos);
 }
 
+void GlobedGJBGL::spectatePlayer(int accountId) {
+    if (!this->established()) return;
+
+    m_fields->spectating = 0;
+    NetworkManager::get().send(SpectatePlayerPacket::create(accountId));
+}
+
+void GlobedGJBGL::sendLevelEvent(uint32_t eventType, std::vector<uint8_t> payload) {
+    if (!this->established()) return;
+
+    // the server drops bigger events
+    if (payload.size() > 64) return;
+
+    NetworkManager::get().send(LevelEventPacket::create(eventType, std::move(payload)));
+}
+
 void GlobedGJBGL::linkPlayerTo(int accountId) {
     if (!m_fields->players.contains(accountId)) return;
     if (!m_fields->roomSettings.flags.twoPlayerMode) return;
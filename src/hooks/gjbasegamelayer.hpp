@@ -28,8 +28,11 @@ class $modify(GlobedGJBGL, GJBaseGameLayer) {
         bool deafened = false;
         bool isVoiceProximity = false;
         uint32_t totalSentPackets = 0;
+        uint32_t playerDataSequence = 0; // only counts packets that were actually sent, the server uses it to estimate packet loss
         float timeCounter = 0.f;
         float lastServerUpdate = 0.f;
+        uint32_t spectatorCount = 0; // while anyone is spectating us, player data is also sent every frame
+        int spectating = 0; // account ID of the player we are spectating, 0 if nobody
         std::unique_ptr<PlayerInterpolator> interpolator;
         std::unique_ptr<PlayerStore> playerStore;
         RoomSettings roomSettings;
@@ -65,7 +68,26 @@ class $modify(GlobedGJBGL, GJBaseGameLayer) {
         std::unordered_map<int, util::time::time_point> lastSentPacket;
 
         // chat messages (duh)
-        std::vector<std::pair<int, std::string>> chatMessages;
+        struct ChatMessage {
+            uint32_t id; // 0 until the server tells us the ID of our own message
+            int accountId;
+            std::string message;
+            bool mentioned = false;
+            uint32_t clientId = 0; // for our own messages, matched with the ID the server echoes back
+        };
+        std::vector<ChatMessage> chatMessages;
+        uint32_t lastChatClientId = 0;
+        // mentions can arrive before the message itself, as they are sent over tcp
+        std::unordered_set<uint32_t> pendingMentions;
+        // chat on levels with lots of players is split into channels, 0 if it isn't
+        uint32_t chatChannel = 0;
+        uint32_t chatChannelCount = 0;
+        // bumped whenever already displayed messages change (deleted or got their ID)
+        uint32_t chatRevision = 0;
+
+        // countdown started by the room owner, the level is restarted when it ends
+        std::optional<util::time::time_point> countdownEnd;
+        Ref<CCLabelBMFont> countdownLabel = nullptr;
     };
 
     $override
@@ -148,6 +170,13 @@ class $modify(GlobedGJBGL, GJBaseGameLayer) {
 
     void linkPlayerTo(int accountId);
 
+    // start spectating a player on the level, 0 stops spectating
+    void spectatePlayer(int accountId);
+
+    // send an event to everyone else on the level, the payload can be at most 64 bytes.
+    // other mods can also post a "send-level-event"_spr event, and get events from others with the "level-event"_spr event
+    void sendLevelEvent(uint32_t eventType, std::vector<uint8_t> payload);
+
     // runs every frame while paused
     void pausedUpdate(float dt);
 
@@ -160,6 +189,8 @@ class $modify(GlobedGJBGL, GJBaseGameLayer) {
     // This does result in less smooth experience with non-naive speedhacks however.
     bool accountForSpeedhack(int uniqueKey, float cap, float allowance = 0.9f);
 
+    void updateConfiguredTps();
+    void updateCountdown();
     void unscheduleSelectors();
     void rescheduleSelectors();
 
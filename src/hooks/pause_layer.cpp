@@ -1,6 +1,9 @@
 #include "pause_layer.hpp"
 
+#include <data/packets/all.hpp>
 #include <hooks/gjbasegamelayer.hpp>
+#include <managers/room.hpp>
+#include <net/manager.hpp>
 #include <ui/game/userlist/userlist.hpp>
 #include <ui/game/chat/chatlist.hpp>
 #include <ui/game/chat/unread_badge.hpp>
@@ -8,6 +11,8 @@
 
 using namespace geode::prelude;
 
+constexpr uint8_t ROOM_COUNTDOWN_SECONDS = 5;
+
 void GlobedPauseLayer::customSetup() {
     PauseLayer::customSetup();
 
@@ -32,6 +37,19 @@ void GlobedPauseLayer::customSetup() {
         .id("btn-open-playerlist"_spr)
         .parent(menu);
 
+    // room owners can start a countdown for everyone on the level, so that races start at the same time
+    if (RoomManager::get().isOwner()) {
+        Build<CCSprite>::createSpriteName("GJ_timeIcon_001.png")
+            .scale(1.1f)
+            .intoMenuItem([this](auto) {
+                NetworkManager::get().send(StartRoomCountdownPacket::create(ROOM_COUNTDOWN_SECONDS));
+                this->onResume(nullptr);
+            })
+            .pos(winSize.width - 50.f, 90.f)
+            .id("btn-start-countdown"_spr)
+            .parent(menu);
+    }
+
     // TODO: bring back when it works properly
     // auto* chatIcon = Build<CCSprite>::createSpriteName("icon-chat.png"_spr)
     //     .scale(0.9f)
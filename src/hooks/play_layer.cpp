@@ -2,6 +2,8 @@
 
 #include "gjbasegamelayer.hpp"
 #include "level_editor_layer.hpp"
+#include <data/packets/client/game.hpp>
+#include <managers/settings.hpp>
 
 using namespace geode::prelude;
 
@@ -77,8 +79,18 @@ void GlobedPlayLayer::showNewBest(bool p0, int p1, int p2, bool p3, bool p4, boo
 }
 
 void GlobedPlayLayer::levelComplete() {
-    if (!GlobedGJBGL::get()->isSafeMode()) PlayLayer::levelComplete();
-    else GlobedPlayLayer::onQuit();
+    auto gjbgl = GlobedGJBGL::get();
+    if (gjbgl->isSafeMode()) {
+        GlobedPlayLayer::onQuit();
+        return;
+    }
+
+    PlayLayer::levelComplete();
+
+    // practice and startpos runs aren't much to celebrate
+    if (gjbgl->m_fields->globedReady && !m_isPracticeMode && !m_isTestMode && GlobedSettings::get().players.shareCompletions) {
+        NetworkManager::get().send(LevelCompletedPacket::create(m_level->m_attempts));
+    }
 }
 
 void GlobedPlayLayer::destroyPlayer(PlayerObject* player, GameObject* object) {
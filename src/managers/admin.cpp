@@ -32,8 +32,8 @@ void AdminManager::openUserPopup(const PlayerRoomPreviewAccountData& rpdata) {
         nm.send(AdminGetUserStatePacket::create(std::to_string(rpdata.accountId)));
         nm.addListener<AdminUserDataPacket>(popup, [popup, rpdata = std::move(rpdata)](auto packet) {
             // delay the cration to avoid deadlock
-            Loader::get()->queueInMainThread([popup, userEntry = std::move(packet->userEntry), accountData = std::move(rpdata)] {
-                AdminUserPopup::create(userEntry, accountData)->show();
+            Loader::get()->queueInMainThread([popup, userEntry = std::move(packet->userEntry), accountData = std::move(rpdata), connection = packet->connection, levelHistory = std::move(packet->levelHistory)] {
+                AdminUserPopup::create(userEntry, accountData, connection, levelHistory)->show();
                 popup->onClose(popup);
             });
         });
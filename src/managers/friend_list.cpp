@@ -1,6 +1,8 @@
 #include "friend_list.hpp"
 
+#include <data/packets/client/general.hpp>
 #include <managers/error_queues.hpp>
+#include <net/manager.hpp>
 
 void FriendListManager::load() {
     auto* glm = GameLevelManager::sharedState();
@@ -24,6 +26,7 @@ void FriendListManager::maybeLoad() {
 
 void FriendListManager::invalidate() {
     friends.clear();
+    presence.clear();
     loaded = false;
 }
 
@@ -31,12 +34,50 @@ bool FriendListManager::isFriend(int playerId) {
     return friends.contains(playerId);
 }
 
+void FriendListManager::uploadFriends() {
+    auto& nm = NetworkManager::get();
+    if (!loaded || !nm.established()) return;
+
+    std::vector<int> accounts;
+    for (int id : friends) {
+        if (accounts.size() >= UpdateFriendListPacket::MAX_FRIENDS) break;
+        accounts.push_back(id);
+    }
+
+    nm.send(UpdateFriendListPacket::create(std::move(accounts)));
+    // the server only pushes changes from now on, so ask where everyone is right now
+    nm.send(FriendListQueryPacket::create());
+}
+
+void FriendListManager::setOnlineFriends(const std::vector<PlayerPresence>& players) {
+    presence.clear();
+
+    // friends missing from the list are offline or invisible
+    for (int id : friends) {
+        presence[id] = PlayerPresence{id, false, 0, 0};
+    }
+
+    this->updatePresence(players);
+}
+
+void FriendListManager::updatePresence(const std::vector<PlayerPresence>& players) {
+    for (const auto& player : players) {
+        presence[player.accountId] = player;
+    }
+}
+
+const PlayerPresence* FriendListManager::getPresence(int playerId) {
+    auto it = presence.find(playerId);
+    return it == presence.end() ? nullptr : &it->second;
+}
+
 void FriendListManager::insertPlayers(cocos2d::CCArray* players) {
     for (auto* elem : CCArrayExt<GJUserScore*>(players)) {
         friends.insert(elem->m_accountID);
     }
 
     loaded = true;
+    this->uploadFriends();
 }
 
 void FriendListManager::DummyNode::cleanup() {
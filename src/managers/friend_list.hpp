@@ -1,6 +1,7 @@
 #pragma once
 #include <defs/geode.hpp>
 
+#include <data/types/gd.hpp>
 #include <util/singleton.hpp>
 
 class FriendListManager : public SingletonBase<FriendListManager> {
@@ -30,10 +31,19 @@ public:
 
     bool isFriend(int playerId);
 
+    // sends the friend list to the server, which then tells us whenever a friend comes online, goes offline or moves to a different room or level
+    void uploadFriends();
+    // replaces what we know about friends with the response to FriendListQueryPacket
+    void setOnlineFriends(const std::vector<PlayerPresence>& players);
+    void updatePresence(const std::vector<PlayerPresence>& players);
+    // returns nullptr if we don't know anything about the player yet
+    const PlayerPresence* getPresence(int playerId);
+
 private:
     void insertPlayers(cocos2d::CCArray* players);
 
     Ref<DummyNode> dummyNode;
     std::set<int> friends;
+    std::unordered_map<int, PlayerPresence> presence;
     bool loaded = false;
 };
\ No newline at end of file
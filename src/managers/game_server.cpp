@@ -22,11 +22,13 @@ Result<> GameServerManager::addOrUpdateServer(const std::string_view serverId_,
 
     int ping = -1;
     uint16_t playerCount = 0;
+    uint8_t distanceClass = 0;
 
     auto data = _data.lock();
     if (data->servers.contains(serverId)) {
         ping = data->servers.at(serverId).server.ping;
         playerCount = data->servers.at(serverId).server.playerCount;
+        distanceClass = data->servers.at(serverId).server.distanceClass;
     }
 
     GameServer server = {
@@ -36,6 +38,7 @@ Result<> GameServerManager::addOrUpdateServer(const std::string_view serverId_,
         .address = std::string(address),
         .ping = ping,
         .playerCount = playerCount,
+        .distanceClass = distanceClass,
     };
 
     GameServerManager::GameServerData gsdata = {
@@ -225,7 +228,7 @@ uint32_t GameServerManager::startPing(const std::string_view serverId) {
     return pingId;
 }
 
-void GameServerManager::finishPing(uint32_t pingId, uint32_t playerCount) {
+void GameServerManager::finishPing(uint32_t pingId, uint32_t playerCount, std::optional<uint8_t> distanceClass) {
     auto now = util::time::now();
 
     auto data = _data.lock();
@@ -237,6 +240,9 @@ void GameServerManager::finishPing(uint32_t pingId, uint32_t playerCount) {
 
             server.server.ping = timeTook;
             server.server.playerCount = playerCount;
+            if (distanceClass) {
+                server.server.distanceClass = distanceClass.value();
+            }
             server.pendingPings.erase(pingId);
             return;
         }
@@ -16,6 +16,8 @@ struct GameServer {
 
     int ping;
     uint32_t playerCount;
+    // rough distance from the player to the server, 0 if unknown, otherwise the lower the closer
+    uint8_t distanceClass;
 };
 
 // This class is fully thread safe to use.
@@ -65,7 +67,7 @@ public:
     /* pings */
 
     uint32_t startPing(const std::string_view serverId);
-    void finishPing(uint32_t pingId, uint32_t playerCount);
+    void finishPing(uint32_t pingId, uint32_t playerCount, std::optional<uint8_t> distanceClass = std::nullopt);
 
     void startKeepalive();
     void finishKeepalive(uint32_t playerCount);
@@ -12,6 +12,12 @@ std::optional<PlayerAccountData> ProfileCacheManager::getData(int32_t accountId)
     return std::nullopt;
 }
 
+void ProfileCacheManager::updateIcons(int32_t accountId, const PlayerIconData& icons) {
+    if (cache.contains(accountId)) {
+        cache.at(accountId).icons = icons;
+    }
+}
+
 void ProfileCacheManager::clear() {
     cache.clear();
 }
@@ -9,6 +9,8 @@ class ProfileCacheManager : public SingletonBase<ProfileCacheManager> {
 public:
     void insert(const PlayerAccountData& data);
     std::optional<PlayerAccountData> getData(int32_t accountId);
+    // does nothing if the player is not cached, their whole profile is requested once it's needed
+    void updateIcons(int32_t accountId, const PlayerIconData& icons);
     void clear();
 
     // gather player's icons and call `setOwnData`;
@@ -68,3 +68,28 @@ ComputedRole RoleManager::compute(const std::vector<std::string>& roles) {
 
     return this->compute(out);
 }
+
+uint64_t RoleManager::getRoleMask(const std::vector<std::string>& roles) {
+    uint64_t mask = 0;
+
+    for (const auto& key : roles) {
+        auto it = std::find_if(allRoles.begin(), allRoles.end(), [&](auto& role) { return role.role.id == key; });
+        if (it == allRoles.end() || it->intId >= 64) continue;
+
+        mask |= 1ULL << it->intId;
+    }
+
+    return mask;
+}
+
+std::vector<std::string> RoleManager::getRoleIds(uint64_t mask) {
+    std::vector<std::string> out;
+
+    for (const auto& role : allRoles) {
+        if (role.intId < 64 && (mask & (1ULL << role.intId))) {
+            out.push_back(role.role.id);
+        }
+    }
+
+    return out;
+}
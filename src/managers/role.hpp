@@ -15,6 +15,10 @@ public:
     ComputedRole compute(const std::vector<uint8_t>& roles);
     ComputedRole compute(const std::vector<std::string>& roles);
 
+    // bit `n` of the mask is set for the role of int ID `n`, unknown roles and roles past the 64th are skipped
+    uint64_t getRoleMask(const std::vector<std::string>& roles);
+    std::vector<std::string> getRoleIds(uint64_t mask);
+
 private:
     std::vector<GameServerRole> allRoles;
 };
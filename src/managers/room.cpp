@@ -16,6 +16,14 @@ bool RoomManager::isOwner() {
     return this->isInRoom() && roomInfo.owner.accountId == GJAccountManager::get()->m_accountID;
 }
 
+bool RoomManager::canManage() {
+    return this->isOwner() || (this->isInRoom() && this->isModerator(GJAccountManager::get()->m_accountID));
+}
+
+bool RoomManager::isModerator(int accountId) {
+    return std::find(moderators.begin(), moderators.end(), accountId) != moderators.end();
+}
+
 bool RoomManager::isInGlobal() {
     return roomInfo.id == 0;
 }
@@ -28,7 +36,31 @@ void RoomManager::setInfo(const RoomInfo& info) {
     roomInfo = info;
 }
 
+void RoomManager::setModerators(const std::vector<int>& moderators) {
+    this->moderators = moderators;
+}
+
+void RoomManager::setTags(const RoomTags& tags) {
+    this->tags = tags;
+}
+
+const std::vector<RoomTag>& RoomManager::getTags() {
+    return tags.tags;
+}
+
+const RoomTag* RoomManager::getMemberTag(int accountId) {
+    for (const auto& member : tags.members) {
+        if (member.accountId == accountId && member.tag < tags.tags.size()) {
+            return &tags.tags[member.tag];
+        }
+    }
+
+    return nullptr;
+}
+
 void RoomManager::setGlobal() {
+    moderators.clear();
+    tags = {};
     this->setInfo(RoomInfo {
         .id = 0,
         .owner = {},
@@ -15,13 +15,23 @@ public:
 
     // Returns 'true' if in a room and if the user is the owner of the room.
     bool isOwner();
+    // Returns 'true' if in a room and if the user is the owner or one of the moderators of the room.
+    bool canManage();
+    bool isModerator(int accountId);
 
     bool isInGlobal();
     bool isInRoom();
 
     void setInfo(const RoomInfo& info);
+    void setModerators(const std::vector<int>& moderators);
+    void setTags(const RoomTags& tags);
+    const std::vector<RoomTag>& getTags();
+    // Returns nullptr if the player has no tag in this room.
+    const RoomTag* getMemberTag(int accountId);
     void setGlobal();
 
 private:
     RoomInfo roomInfo;
+    std::vector<int> moderators;
+    RoomTags tags;
 };
\ No newline at end of file
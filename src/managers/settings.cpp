@@ -76,6 +76,30 @@ void GlobedSettings::save() {
     this->reflect(TaskType::SaveSettings);
 }
 
+bool GlobedSettings::isCategoryChanged(std::string_view category) {
+    using SetMd = boost::describe::describe_members<GlobedSettings, boost::describe::mod_public>;
+
+    bool changed = false;
+
+    boost::mp11::mp_for_each<SetMd>([&, this](auto cd) -> void {
+        using CatType = typename util::misc::MemberPtrToUnderlying<decltype(cd.pointer)>::type;
+        if (std::string_view(cd.name) != category) return;
+
+        auto& cat = this->*cd.pointer;
+
+        using CatMd = boost::describe::describe_members<CatType, boost::describe::mod_public>;
+        boost::mp11::mp_for_each<CatMd>([&](auto setd) -> void {
+            using SetTy = typename util::misc::MemberPtrToUnderlying<decltype(setd.pointer)>::type;
+
+            if ((cat.*setd.pointer).get() != SetTy::Default) {
+                changed = true;
+            }
+        });
+    });
+
+    return changed;
+}
+
 bool GlobedSettings::has(std::string_view key) {
     return Mod::get()->hasSavedValue(key);
 }
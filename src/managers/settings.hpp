@@ -149,6 +149,10 @@ public:
         Setting<bool, false> compressedPlayerCount;
         Setting<bool, true> useDiscordRPC;
         Setting<bool, false> isInvisible;
+        Setting<bool, false> shareEditorLevels;
+        Setting<bool, false> hideUnlistedLevels;
+        Setting<bool, false> hideSpectatorCount;
+        Setting<bool, true> showSpectatorCount;
     };
 
     struct Overlay {
@@ -189,9 +193,14 @@ public:
         Setting<bool, false> forceVisibility;
         Setting<bool, false> ownName;
         Setting<bool, false> hidePracticePlayers;
+        Setting<bool, false> shareCompletions;
+        Setting<bool, true> showCompletions;
     };
 
-    struct Advanced {};
+    // not included in the feature usage reports
+    struct Advanced {
+        Setting<bool, false> shareUsage;
+    };
 
     struct Admin {
         Setting<bool, false> rememberPassword;
@@ -224,6 +233,9 @@ public:
     // Reload all settings from the geode save container
     void reload();
 
+    // Whether any setting in the given category (for example "overlay") is not set to its default value
+    bool isCategoryChanged(std::string_view category);
+
     enum class TaskType {
         SaveSettings, LoadSettings, ResetSettings, HardResetSettings
     };
@@ -265,7 +277,7 @@ public:
 /* Enable reflection */
 
 GLOBED_SERIALIZABLE_STRUCT(GlobedSettings::Globed, (
-    autoconnect, tpsCap, preloadAssets, deferPreloadAssets, increaseLevelList, fragmentationLimit, compressedPlayerCount, useDiscordRPC, isInvisible
+    autoconnect, tpsCap, preloadAssets, deferPreloadAssets, increaseLevelList, fragmentationLimit, compressedPlayerCount, useDiscordRPC, isInvisible, shareEditorLevels, hideUnlistedLevels, hideSpectatorCount, showSpectatorCount
 ));
 
 GLOBED_SERIALIZABLE_STRUCT(GlobedSettings::Overlay, (
@@ -281,10 +293,12 @@ GLOBED_SERIALIZABLE_STRUCT(GlobedSettings::LevelUI, (
 ));
 
 GLOBED_SERIALIZABLE_STRUCT(GlobedSettings::Players, (
-    playerOpacity, showNames, dualName, nameOpacity, statusIcons, deathEffects, defaultDeathEffect, hideNearby, forceVisibility, ownName, hidePracticePlayers
+    playerOpacity, showNames, dualName, nameOpacity, statusIcons, deathEffects, defaultDeathEffect, hideNearby, forceVisibility, ownName, hidePracticePlayers, shareCompletions, showCompletions
 ));
 
-GLOBED_SERIALIZABLE_STRUCT(GlobedSettings::Advanced, ());
+GLOBED_SERIALIZABLE_STRUCT(GlobedSettings::Advanced, (
+    shareUsage
+));
 
 GLOBED_SERIALIZABLE_STRUCT(GlobedSettings::Admin, (
     rememberPassword
@@ -7,6 +7,8 @@
 #include <util/format.hpp>
 #include <util/crypto.hpp>
 
+#include <lz4.h>
+
 #ifdef GEODE_IS_WINDOWS
 # include <WinSock2.h>
 #else
@@ -15,6 +17,8 @@
 #endif
 
 constexpr size_t DATA_BUF_SIZE = 2 << 18;
+// the server sends bigger packets uncompressed (`MAX_UNCOMPRESSED_SIZE` in compression.rs)
+constexpr size_t MAX_DECOMPRESSED_SIZE = DATA_BUF_SIZE;
 
 using namespace util::data;
 using namespace util::debug;
@@ -133,7 +137,40 @@ Result<> GameSocket::sendPacket(std::shared_ptr<Packet> packet) {
     if (packet->getUseTcp()) {
         GLOBED_UNWRAP(tcpSocket.sendAll(reinterpret_cast<const char*>(buf.data().data()), buf.size()));
     } else {
-        GLOBED_UNWRAP(udpSocket.send(reinterpret_cast<const char*>(buf.data().data()), buf.size()));
+        size_t maxSize = maxDatagramSize.load();
+
+        if (maxSize != 0 && buf.size() > maxSize) {
+            GLOBED_UNWRAP(this->sendFragmented(buf, maxSize));
+        } else {
+            GLOBED_UNWRAP(udpSocket.send(reinterpret_cast<const char*>(buf.data().data()), buf.size()));
+        }
+    }
+
+    return Ok();
+}
+
+Result<> GameSocket::sendFragmented(const ByteBuffer& buffer, size_t maxSize) {
+    constexpr size_t headerSize = PacketHeader::SIZE + sizeof(uint16_t) * 3;
+    GLOBED_REQUIRE_SAFE(maxSize > headerSize, "datagram size limit is too small to send fragments")
+
+    size_t chunkSize = maxSize - headerSize;
+    size_t count = (buffer.size() + chunkSize - 1) / chunkSize;
+    GLOBED_REQUIRE_SAFE(count <= MAX_FRAGMENTS, "packet is too big to be sent over udp")
+
+    uint16_t messageId = nextFragmentMessageId.fetch_add(1);
+    const auto& data = buffer.data();
+
+    for (size_t i = 0; i < count; i++) {
+        UdpFragmentPacket header(messageId, static_cast<uint16_t>(i), static_cast<uint16_t>(count));
+
+        ByteBuffer fragment;
+        GLOBED_UNWRAP(this->encodePacket(header, fragment));
+
+        size_t start = i * chunkSize;
+        size_t end = std::min(start + chunkSize, buffer.size());
+        fragment.data().insert(fragment.data().end(), data.begin() + start, data.begin() + end);
+
+        GLOBED_UNWRAP(udpSocket.send(reinterpret_cast<const char*>(fragment.data().data()), fragment.size()));
     }
 
     return Ok();
@@ -168,17 +205,31 @@ Result<> GameSocket::sendRecoveryData(int accountId, uint32_t secretKey) {
 }
 
 void GameSocket::cleanupBox() {
+    std::lock_guard lock(boxMutex);
     cryptoBox = std::unique_ptr<CryptoBox>(nullptr);
+    previousCryptoBox = std::unique_ptr<CryptoBox>(nullptr);
 }
 
 void GameSocket::createBox() {
+    std::lock_guard lock(boxMutex);
     cryptoBox = std::make_unique<CryptoBox>();
+    previousCryptoBox = std::unique_ptr<CryptoBox>(nullptr);
+}
+
+void GameSocket::swapBox(std::unique_ptr<CryptoBox> newBox) {
+    std::lock_guard lock(boxMutex);
+    previousCryptoBox = std::move(cryptoBox);
+    cryptoBox = std::move(newBox);
 }
 
 void GameSocket::togglePacketLogging(bool state) {
     dumpPackets = state;
 }
 
+void GameSocket::setMaxDatagramSize(size_t size) {
+    maxDatagramSize = size;
+}
+
 Result<PollResult> GameSocket::poll(int timeoutMs) {
     if (!tcpSocket.connected) {
         GLOBED_UNWRAP_INTO(udpSocket.poll(timeoutMs), auto res);
@@ -216,6 +267,7 @@ Result<> GameSocket::encodePacket(Packet& packet, ByteBuffer& buffer) {
     PacketHeader header = {
         .id = packet.getPacketId(),
         .encrypted = packet.getEncrypted(),
+        .compressed = false,
     };
 
     bool tcp = packet.getUseTcp();
@@ -231,6 +283,7 @@ Result<> GameSocket::encodePacket(Packet& packet, ByteBuffer& buffer) {
     packet.encode(buffer);
 
     if (packet.getEncrypted()) {
+        std::lock_guard lock(boxMutex);
         GLOBED_REQUIRE_SAFE(cryptoBox.get() != nullptr, "attempted to encrypt a packet when no cryptobox is initialized")
 
         // grow the vector by CryptoBox::PREFIX_LEN extra bytes to do in-place encryption
@@ -273,13 +326,26 @@ Result<std::shared_ptr<Packet>> GameSocket::decodePacket(ByteBuffer& buffer) {
     }
 
     if (header.encrypted) {
+        std::lock_guard lock(boxMutex);
         GLOBED_REQUIRE_SAFE(cryptoBox.get() != nullptr, "attempted to decrypt a packet when no cryptobox is initialized")
         bytevector& bufvec = buffer.data();
 
-        messageLength = cryptoBox->decryptInPlace(bufvec.data() + PacketHeader::SIZE, messageLength);
+        try {
+            messageLength = cryptoBox->decryptInPlace(bufvec.data() + PacketHeader::SIZE, messageLength);
+        } catch (const std::exception&) {
+            // the packet may have been sent right before the server switched to the new key.
+            // failed decryption does not touch the buffer, so it's safe to try again.
+            if (!previousCryptoBox) throw;
+
+            messageLength = previousCryptoBox->decryptInPlace(bufvec.data() + PacketHeader::SIZE, messageLength);
+        }
         buffer.resize(messageLength + PacketHeader::SIZE);
     }
 
+    if (header.compressed) {
+        GLOBED_UNWRAP(this->decompressPayload(buffer, messageLength));
+    }
+
     if (dumpPackets) {
         this->dumpPacket(header.id, buffer, false);
     }
@@ -292,6 +358,32 @@ Result<std::shared_ptr<Packet>> GameSocket::decodePacket(ByteBuffer& buffer) {
     return Ok(std::move(packet));
 }
 
+Result<> GameSocket::decompressPayload(ByteBuffer& buffer, size_t messageLength) {
+    // the payload starts with the uncompressed size, as a big endian u32
+    GLOBED_REQUIRE_SAFE(messageLength >= sizeof(uint32_t), "compressed packet is too short")
+
+    bytevector& bufvec = buffer.data();
+    const uint8_t* src = bufvec.data() + PacketHeader::SIZE;
+
+    uint32_t rawSize = (uint32_t(src[0]) << 24) | (uint32_t(src[1]) << 16) | (uint32_t(src[2]) << 8) | uint32_t(src[3]);
+    GLOBED_REQUIRE_SAFE(rawSize <= MAX_DECOMPRESSED_SIZE, "compressed packet is too large")
+
+    bytevector raw(PacketHeader::SIZE + rawSize);
+    std::copy(bufvec.begin(), bufvec.begin() + PacketHeader::SIZE, raw.begin());
+
+    int written = LZ4_decompress_safe(
+        reinterpret_cast<const char*>(src + sizeof(uint32_t)),
+        reinterpret_cast<char*>(raw.data() + PacketHeader::SIZE),
+        messageLength - sizeof(uint32_t),
+        rawSize
+    );
+
+    GLOBED_REQUIRE_SAFE(written >= 0 && static_cast<uint32_t>(written) == rawSize, "failed to decompress a packet")
+
+    bufvec = std::move(raw);
+    return Ok();
+}
+
 void GameSocket::dumpPacket(packetid_t id, ByteBuffer& buffer, bool sending) {
     log::debug("{} packet {}", sending ? "Sending" : "Receiving", id);
 
@@ -7,9 +7,14 @@
 #include <data/packets/packet.hpp>
 #include <crypto/box.hpp>
 
+#include <atomic>
+#include <mutex>
+
 class GameSocket {
     static constexpr uint8_t MARKER_CONN_INITIAL = 0xe0;
     static constexpr uint8_t MARKER_CONN_RECOVERY = 0xe1;
+    // the server drops packets that are split into more pieces than this
+    static constexpr size_t MAX_FRAGMENTS = 512;
 
 public:
     GameSocket();
@@ -47,8 +52,14 @@ public:
     void cleanupBox();
     void createBox();
 
+    // Replace the crypto box after a rekey. The old box is kept to decrypt packets that the server sent before switching keys.
+    void swapBox(std::unique_ptr<CryptoBox> newBox);
+
     void togglePacketLogging(bool enabled);
 
+    // Set the biggest UDP datagram that can be sent, bigger packets are split into fragments. 0 means no limit.
+    void setMaxDatagramSize(size_t size);
+
     enum class PollResult {
         None, Tcp, Udp, Both
     };
@@ -62,15 +73,25 @@ private:
     UdpSocket udpSocket;
 
     std::unique_ptr<CryptoBox> cryptoBox;
+    std::unique_ptr<CryptoBox> previousCryptoBox;
+    std::mutex boxMutex;
     util::data::byte* dataBuffer;
 
     bool dumpPackets = false;
+    asp::AtomicSizeT maxDatagramSize = 0;
+    std::atomic<uint16_t> nextFragmentMessageId = 0;
 
     // Write a packet, packet header, and optionally length if the packet is TCP to the given buffer.
     Result<> encodePacket(Packet& packet, ByteBuffer& buffer);
 
+    // Send an already encoded UDP packet in several datagrams, none of them bigger than `maxDatagramSize`
+    Result<> sendFragmented(const ByteBuffer& buffer, size_t maxSize);
+
     // Decode a packet from a buffer
     Result<std::shared_ptr<Packet>> decodePacket(ByteBuffer& buffer);
 
+    // Replace the compressed payload of a (decrypted) packet with the decompressed one, keeping the header
+    Result<> decompressPayload(ByteBuffer& buffer, size_t messageLength);
+
     void dumpPacket(packetid_t id, ByteBuffer& buffer, bool sending);
 };
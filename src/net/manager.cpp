@@ -8,6 +8,7 @@
 #include <asp/sync.hpp>
 #include <asp/thread.hpp>
 
+#include <audio/manager.hpp>
 #include <data/packets/all.hpp>
 #include <defs/minimal_geode.hpp>
 #include <managers/account.hpp>
@@ -21,15 +22,17 @@
 #include <managers/role.hpp>
 #include <util/cocos.hpp>
 #include <util/format.hpp>
+#include <util/misc.hpp>
 #include <util/time.hpp>
 #include <util/net.hpp>
+#include <ui/general/ask_input_popup.hpp>
 #include <ui/notification/panel.hpp>
 
 using namespace asp::sync;
 using namespace geode::prelude;
 using ConnectionState = NetworkManager::ConnectionState;
 
-static constexpr uint16_t PROTOCOL_VERSION = 6;
+static constexpr uint16_t PROTOCOL_VERSION = 9;
 
 // yes, really
 struct AtomicConnectionState {
@@ -170,6 +173,9 @@ protected:
         std::shared_ptr<Packet> packet;
     };
     struct TaskPingActive {};
+    struct TaskRekey {
+        CryptoPublicKey serverKey;
+    };
 
     struct GlobalListener {
         packetid_t packetId;
@@ -177,7 +183,7 @@ protected:
         PacketListener::CallbackFn callback;
     };
 
-    using Task = std::variant<TaskPingServers, TaskSendPacket, TaskPingActive>;
+    using Task = std::variant<TaskPingServers, TaskSendPacket, TaskPingActive, TaskRekey>;
 
     AtomicConnectionState state;
     GameSocket socket;
@@ -192,6 +198,9 @@ protected:
     // these fields are only used by us and in a safe manner, so they don't need a mutex
     NetworkAddress connectedAddress;
     std::string connectedServerId;
+    std::string pendingTransferToken; // claimed right after logging into the server we were moved to
+    std::string inviteCode; // sent with the next login, to get whitelisted on the server
+    asp::Mutex<std::string> standaloneToken; // proves that our name is ours on a standalone server
     util::time::time_point lastReceivedPacket;
     util::time::time_point lastSentKeepalive;
     util::time::time_point lastTcpExchange;
@@ -203,8 +212,13 @@ protected:
     AtomicBool ignoreProtocolMismatch;
     AtomicBool wasFromRecovery;
     AtomicBool cancellingRecovery;
+    AtomicBool retriedLogin; // whether the last login was already retried after the server was busy
     AtomicU32 secretKey;
     AtomicU32 serverTps;
+    AtomicBool voiceDisabled, chatDisabled; // features the server turned off for our platform
+    asp::Mutex<std::optional<SettingsHints>> settingsHints;
+    AtomicU32 throttledSendRate;
+    asp::Mutex<std::optional<UserPreferences>> preferences;
 
     Impl() {
         // initialize winsock
@@ -270,6 +284,11 @@ protected:
 
         connectedAddress = address;
         connectedServerId = std::string(serverId);
+
+        // when recovering, we are not on the main thread, but the token is already loaded
+        if (standalone && !fromRecovery) {
+            *standaloneToken.lock() = Mod::get()->getSavedValue<std::string>(this->standaloneTokenKey());
+        }
         recovering = false;
         recoverAttempt = 0;
 
@@ -288,6 +307,12 @@ protected:
         recovering = false;
         cancellingRecovery = false;
         recoverAttempt = 0;
+        pendingTransferToken.clear();
+        *settingsHints.lock() = std::nullopt;
+        voiceDisabled = false;
+        chatDisabled = false;
+        throttledSendRate = 0;
+        *preferences.lock() = std::nullopt;
 
         if (!quiet && prevState == ConnectionState::Established) {
             // send it directly instead of pushing to the queue
@@ -435,8 +460,43 @@ protected:
 
         addInternalListener<KeepaliveTCPResponsePacket>([](auto) {});
 
+        addInternalListener<RekeyRequestPacket>([this](auto packet) {
+            // handled on the sending thread, so no packets can be sent in between the response and the key switch
+            taskQueue.push(TaskRekey {
+                .serverKey = packet->key
+            });
+        });
+
+        addInternalListener<LatencyProbePacket>([this](auto packet) {
+            this->send(LatencyProbeResponsePacket::create(packet->id));
+        });
+
+        addInternalListener<SettingsHintsPacket>([this](auto packet) {
+            this->onSettingsHints(std::move(packet));
+        });
+
+        addInternalListener<ThrottleHintPacket>([this](auto packet) {
+            this->onThrottleHint(std::move(packet));
+        });
+
+        addInternalListener<StandaloneTokenPacket>([this](auto packet) {
+            this->onStandaloneToken(std::move(packet));
+        });
+
+        addInternalListener<ServerRestartingPacket>([this](auto packet) {
+            this->onServerRestarting(std::move(packet));
+        });
+
+        addInternalListener<PreferencesPacket>([this](auto packet) {
+            this->onPreferences(std::move(packet));
+        });
+
         addInternalListener<ServerDisconnectPacket>([this](auto packet) {
-            this->disconnectWithMessage(packet->message);
+            this->onServerDisconnect(std::move(packet));
+        });
+
+        addInternalListener<ServerTransferPacket>([this](auto packet) {
+            this->onServerTransfer(std::move(packet));
         });
 
         addInternalListener<LoggedInPacket>([this](auto packet) {
@@ -444,11 +504,19 @@ protected:
         });
 
         addInternalListener<LoginFailedPacket>([this](auto packet) {
-            ErrorQueues::get().error(fmt::format("<cr>Authentication failed!</c> The server rejected the login attempt.\n\nReason: <cy>{}</c>", packet->message));
-            GlobedAccountManager::get().authToken.lock()->clear();
+            this->onLoginFailed(std::move(packet));
+        });
+
+        addInternalListener<LoginNameRejectedPacket>([this](auto packet) {
+            const char* message = packet->message.message();
+            ErrorQueues::get().error(fmt::format("<cr>Authentication failed!</c> {}", message ? message : "Your name is not allowed on this server."));
             this->disconnect(true);
         });
 
+        addInternalListener<LoginWhitelistRequiredPacket>([this](auto packet) {
+            this->onLoginWhitelistRequired(std::move(packet));
+        });
+
         addInternalListener<ProtocolMismatchPacket>([this](auto packet) {
             this->onProtocolMismatch(std::move(packet));
         });
@@ -511,6 +579,10 @@ protected:
         // General packets
 
         addGlobalListener<RolesUpdatedPacket>([](auto packet) {
+            if (packet->allRoles) {
+                RoleManager::get().setAllRoles(std::move(packet->allRoles.value()));
+            }
+
             auto& pcm = ProfileCacheManager::get();
             pcm.setOwnSpecialData(packet->specialUserData);
         });
@@ -537,17 +609,78 @@ protected:
             ErrorQueues::get().success("Room configuration updated");
 
             RoomManager::get().setInfo(packet->info);
+            RoomManager::get().setModerators(packet->moderators);
+            RoomManager::get().setTags(packet->tags);
         });
 
         addGlobalListener<RoomJoinedPacket>([](auto packet) {});
 
+        addGlobalListener<TransferClaimedPacket>([](auto packet) {
+            RoomManager::get().setInfo(packet->info);
+        });
+
+        addGlobalListener<PresenceUpdatePacket>([](auto packet) {
+            FriendListManager::get().updatePresence(packet->players);
+        });
+
+        addGlobalListener<FriendListPacket>([](auto packet) {
+            FriendListManager::get().setOnlineFriends(packet->players);
+        });
+
+        addGlobalListener<PlayerProfileChangedPacket>([](auto packet) {
+            // players on the level pick up the new icons from the cache
+            ProfileCacheManager::get().updateIcons(packet->accountId, packet->icons);
+        });
+
+        addGlobalListener<RoomAfkRemovedPacket>([](auto packet) {
+            RoomManager::get().setGlobal();
+            ErrorQueues::get().notice("You were moved out of the room for being AFK, to make space for other players.");
+        });
+
+        addGlobalListener<RoomKickedPacket>([](auto packet) {
+            RoomManager::get().setGlobal();
+            ErrorQueues::get().notice("You were kicked out of the room.");
+        });
+
+        addGlobalListener<DuplicateLoginPacket>([](auto packet) {
+            // the fatal ones are followed by a disconnect or a failed login, which already show a message
+            switch (packet->notice) {
+                case DuplicateLoginNotice::ReplacedOther:
+                    ErrorQueues::get().notice("Your account was logged in from a different place, that session has been disconnected.");
+                    break;
+                case DuplicateLoginNotice::RejectedOther:
+                    ErrorQueues::get().notice("Someone tried to log into your account from a different place, and was refused.");
+                    break;
+                case DuplicateLoginNotice::Concurrent:
+                    ErrorQueues::get().notice("Your account was logged in from a different place, both sessions will stay connected.");
+                    break;
+                default: break;
+            }
+        });
+
         addGlobalListener<RoomJoinFailedPacket>([](auto packet) {
-            // TODO: handle reason
-            std::string reason = "N/A";
-            if (packet->wasInvalid) reason = "Room doesn't exist";
-            if (packet->wasProtected) reason = "Room password is wrong";
-            if (packet->wasFull) reason = "Room is full";
-            if (!packet->wasProtected) ErrorQueues::get().error(fmt::format("Failed to join room: {}", reason)); //TEMPORARY disable wrong password alerts
+            std::string reason;
+            switch (packet->code) {
+                case ErrorCode::NotFound: reason = "Room doesn't exist"; break;
+                case ErrorCode::WrongPassword: return; // the join popup asks for the password instead
+                case ErrorCode::Full: reason = "Room is full"; break;
+                case ErrorCode::Restricted: reason = "Room is only open to certain roles"; break;
+                default: reason = util::format::serverError(packet->code, packet->message); break;
+            }
+
+            ErrorQueues::get().error(fmt::format("Failed to join room: {}", reason));
+        });
+
+        addGlobalListener<RoomInviteFailedPacket>([](auto packet) {
+            ErrorQueues::get().warn(fmt::format("Failed to send the invite: {}", util::format::serverError(packet->code, packet->message)));
+        });
+
+        addGlobalListener<RoomWebhookSetPacket>([](auto) {
+            ErrorQueues::get().success("Room webhook updated");
+        });
+
+        addGlobalListener<RoomWebhookFailedPacket>([](auto packet) {
+            ErrorQueues::get().warn(fmt::format("Failed to set the room webhook: {}", util::format::serverError(packet->code, packet->message)));
         });
 
         // Admin packets
@@ -569,12 +702,17 @@ protected:
         });
 
         addGlobalListener<AdminErrorPacket>([](auto packet) {
-            ErrorQueues::get().warn(packet->message);
+            ErrorQueues::get().warn(util::format::serverError(packet->code, packet->message));
+        });
+
+        addGlobalListener<AdminInviteCodePacket>([](auto packet) {
+            geode::utils::clipboard::write(packet->code);
+            ErrorQueues::get().success(fmt::format("Invite code <cy>{}</c> ({} uses) copied to clipboard", packet->code, packet->uses));
         });
     }
 
     void onCryptoHandshakeResponse(std::shared_ptr<CryptoHandshakeResponsePacket> packet) {
-        log::debug("handshake successful, logging in");
+        log::debug("handshake successful, logging in (compression: {})", packet->compression);
         auto key = packet->data.key;
 
         socket.cryptoBox->setPeerKey(key.data());
@@ -583,6 +721,8 @@ protected:
 
         if (!standalone) {
             authtoken = *am.authToken.lock();
+        } else {
+            authtoken = *standaloneToken.lock();
         }
 
         auto& pcm = ProfileCacheManager::get();
@@ -604,17 +744,98 @@ protected:
             pcm.getOwnData(),
             settings.globed.fragmentationLimit,
             util::net::loginPlatformString(),
-            settings.globed.isInvisible
+            settings.globed.isInvisible,
+            inviteCode
         );
 
         this->send(pkt);
     }
 
+    void onSettingsHints(std::shared_ptr<SettingsHintsPacket> packet) {
+        log::debug(
+            "received settings hints: interpolation window {}ms, voice bitrate {}, send rate {}",
+            packet->hints.interpolationWindow, packet->hints.voiceBitrate, packet->hints.sendRate
+        );
+
+        *settingsHints.lock() = packet->hints;
+
+#ifdef GLOBED_VOICE_SUPPORT
+        // while throttled, the bitrate from the throttle hint is used until the server tells us we can go back
+        if (throttledSendRate == 0) {
+            GlobedAudioManager::get().setEncoderBitrate(packet->hints.voiceBitrate);
+        }
+#endif
+    }
+
+    void onThrottleHint(std::shared_ptr<ThrottleHintPacket> packet) {
+        log::debug("received throttle hint: throttled {}, send rate {}, voice bitrate {}", packet->throttled, packet->sendRate, packet->voiceBitrate);
+
+        throttledSendRate = packet->throttled ? packet->sendRate : 0;
+
+#ifdef GLOBED_VOICE_SUPPORT
+        if (packet->throttled) {
+            GlobedAudioManager::get().setEncoderBitrate(packet->voiceBitrate);
+        } else {
+            // go back to whatever the server recommended before
+            auto hints = *settingsHints.lock();
+            GlobedAudioManager::get().setEncoderBitrate(hints ? (int)hints->voiceBitrate : VOICE_AUTO_BITRATE);
+        }
+#endif
+    }
+
+    void onStandaloneToken(std::shared_ptr<StandaloneTokenPacket> packet) {
+        *standaloneToken.lock() = packet->token;
+
+        // saved values are not thread-safe, so delay it
+        Loader::get()->queueInMainThread([key = this->standaloneTokenKey(), token = packet->token] {
+            Mod::get()->setSavedValue(key, token);
+        });
+    }
+
+    // tokens are kept per server, so that other servers never get to see them
+    std::string standaloneTokenKey() {
+        return fmt::format("standalone-token-{}", connectedAddress.toString());
+    }
+
+    void onPreferences(std::shared_ptr<PreferencesPacket> packet) {
+        *preferences.lock() = packet->preferences;
+
+        // settings are not thread-safe, so delay it
+        Loader::get()->queueInMainThread([preferences = packet->preferences] {
+            using InvitesFrom = GlobedSettings::InvitesFrom;
+
+            auto& settings = GlobedSettings::get();
+            settings.globed.isInvisible = preferences.hideInLists;
+            settings.globed.shareEditorLevels = preferences.shareEditorLevels;
+            settings.globed.hideUnlistedLevels = preferences.hideUnlistedLevels;
+            settings.globed.hideSpectatorCount = preferences.hideSpectatorCount;
+
+            bool blocksInvites = static_cast<InvitesFrom>((int)settings.globed.invitesFrom) == InvitesFrom::Nobody;
+            if (preferences.blockInvites != blocksInvites) {
+                settings.globed.invitesFrom = (int)(preferences.blockInvites ? InvitesFrom::Nobody : InvitesFrom::Friends);
+            }
+
+            settings.save();
+        });
+    }
+
     void onLoggedIn(std::shared_ptr<LoggedInPacket> packet) {
         log::info("Successfully logged into the server!");
         serverTps = packet->tps;
         secretKey = packet->secretKey;
         state = ConnectionState::Established;
+        inviteCode.clear();
+        retriedLogin = false;
+
+        // the packet limit from the settings also applies to what we send, if it's lower than what the server accepts
+        size_t fragLimit = GlobedSettings::get().globed.fragmentationLimit;
+        socket.setMaxDatagramSize(fragLimit == 0 ? packet->maxDatagramSize : std::min<size_t>(fragLimit, packet->maxDatagramSize));
+
+        voiceDisabled = packet->disabledFeatures.voice;
+        chatDisabled = packet->disabledFeatures.chat;
+        if (packet->disabledFeatures.voice || packet->disabledFeatures.chat) {
+            log::info("server disabled features for this platform (voice: {}, chat: {})", packet->disabledFeatures.voice, packet->disabledFeatures.chat);
+        }
 
         if (recovering || wasFromRecovery) {
             recovering = false;
@@ -633,19 +854,210 @@ protected:
 
             RoomManager::get().setGlobal();
             RoleManager::get().setAllRoles(allRoles);
+
+            // the server forgets friend lists when the player disconnects
+            FriendListManager::get().uploadFriends();
         });
 
         // claim the tcp thread to allow udp packets through
         this->send(ClaimThreadPacket::create(this->secretKey));
 
+        if (GlobedSettings::get().advanced.shareUsage) {
+            this->sendFeatureUsage();
+        }
+
+        // if we were moved here from another server, ask to be put back into our room
+        if (!pendingTransferToken.empty()) {
+            this->send(ClaimTransferPacket::create(pendingTransferToken));
+            pendingTransferToken.clear();
+        }
+
         // try to login as an admin if we can
         auto& am = GlobedAccountManager::get();
         if (am.hasAdminPassword()) {
             auto password = am.getAdminPassword();
             if (password.has_value()) {
-                this->send(AdminAuthPacket::create(password.value()));
+                this->send(AdminAuthPacket::create(password.value(), ""));
+            }
+        }
+    }
+
+    void sendFeatureUsage() {
+        auto& settings = GlobedSettings::get();
+
+#if defined(GEODE_IS_WINDOWS)
+        auto platform = ClientPlatform::Windows;
+#elif defined(GEODE_IS_MACOS)
+        auto platform = ClientPlatform::Mac;
+#elif defined(GEODE_IS_ANDROID)
+        auto platform = ClientPlatform::Android;
+#elif defined(GEODE_IS_IOS)
+        auto platform = ClientPlatform::Ios;
+#else
+        auto platform = ClientPlatform::Unknown;
+#endif
+
+        ChangedSettingsFlags changed = {};
+        changed.globed = settings.isCategoryChanged("globed");
+        changed.overlay = settings.isCategoryChanged("overlay");
+        changed.communication = settings.isCategoryChanged("communication");
+        changed.levelUi = settings.isCategoryChanged("levelUi");
+        changed.players = settings.isCategoryChanged("players");
+
+        this->send(FeatureUsagePacket::create(platform, settings.communication.voiceEnabled, changed));
+    }
+
+    void onServerDisconnect(std::shared_ptr<ServerDisconnectPacket> packet) {
+        log::info("Server disconnected us (reason {}): {}", (int) packet->reason, packet->message);
+
+        // we were likely cut off by a network hiccup, so only drop the socket and let the connection recovery reconnect us
+        if (packet->reason == DisconnectReason::Idle && state == ConnectionState::Established) {
+            socket.disconnect();
+            return;
+        }
+
+        const char* title;
+        switch (packet->reason) {
+            case DisconnectReason::Kicked: title = "You have been kicked from the server."; break;
+            case DisconnectReason::Banned: title = "You have been banned from the server."; break;
+            case DisconnectReason::Maintenance: title = "The server is under maintenance, please try connecting again later."; break;
+            case DisconnectReason::DuplicateLogin: title = "Your account was logged in from a different place."; break;
+            case DisconnectReason::ProtocolError: title = "A connection error occurred, please try connecting again."; break;
+            case DisconnectReason::Flooding: title = "You were disconnected for sending too many packets, please wait a minute before connecting again."; break;
+            case DisconnectReason::ServerShutdown: title = "The server is shutting down, please try connecting again later."; break;
+            default: title = "You have been disconnected from the active server."; break;
+        }
+
+        if (packet->message.empty()) {
+            ErrorQueues::get().error(title);
+        } else {
+            ErrorQueues::get().error(fmt::format("{}\n\nReason: <cy>{}</c>", title, packet->message));
+        }
+
+        this->disconnect(true);
+    }
+
+    void onLoginFailed(std::shared_ptr<LoginFailedPacket> packet) {
+        NetworkAddress address = connectedAddress;
+        std::string serverId = connectedServerId;
+
+        // other failures can be retried with the same token, so only a bad one has to be thrown away
+        if (packet->code == ErrorCode::InvalidToken) {
+            GlobedAccountManager::get().authToken.lock()->clear();
+        }
+
+        this->disconnect(true);
+
+        // the server is usually busy logging out our previous session, which takes a moment, so try once more before bothering the player
+        if (packet->code == ErrorCode::Busy && !retriedLogin) {
+            retriedLogin = true;
+
+            std::thread([this, address = std::move(address), serverId = std::move(serverId)] {
+                std::this_thread::sleep_for(util::time::seconds(2));
+
+                Loader::get()->queueInMainThread([this, address, serverId] {
+                    auto result = this->connect(address, serverId, false);
+                    if (!result) {
+                        ErrorQueues::get().error(fmt::format("Failed to connect to the server.\n\nReason: <cy>{}</c>", result.unwrapErr()));
+                    }
+                });
+            }).detach();
+
+            return;
+        }
+
+        retriedLogin = false;
+
+        auto reason = util::format::serverError(packet->code, packet->message);
+        if (isTransientError(packet->code)) {
+            ErrorQueues::get().warn(fmt::format("Failed to log in: {}", reason));
+        } else {
+            ErrorQueues::get().error(fmt::format("<cr>Authentication failed!</c> The server rejected the login attempt.\n\nReason: <cy>{}</c>", reason));
+        }
+    }
+
+    void onLoginWhitelistRequired(std::shared_ptr<LoginWhitelistRequiredPacket> packet) {
+        NetworkAddress address = connectedAddress;
+        std::string serverId = connectedServerId;
+
+        inviteCode.clear();
+        this->disconnect(true);
+
+        if (packet->invalidCode) {
+            ErrorQueues::get().warn("The invite code is invalid or has already been used up.");
+        }
+
+        // popups are not thread-safe, so delay it
+        Loader::get()->queueInMainThread([this, address = std::move(address), serverId = std::move(serverId)] {
+            AskInputPopup::create("Invite code", [this, address, serverId](auto input) {
+                if (input.empty()) return;
+
+                inviteCode = std::string(input);
+
+                auto result = this->connect(address, serverId, false);
+                if (!result) {
+                    inviteCode.clear();
+                    ErrorQueues::get().error(fmt::format("Failed to connect to the server.\n\nReason: <cy>{}</c>", result.unwrapErr()));
+                }
+            }, 16, "This server is whitelisted", util::misc::STRING_ALPHANUMERIC, 1.f)->show();
+        });
+    }
+
+    void onServerTransfer(std::shared_ptr<ServerTransferPacket> packet) {
+        log::info("Server is moving us to {}", packet->address);
+
+        std::string serverId = this->serverIdFor(packet->address);
+
+        // the old server has already dropped us, so don't bother saying goodbye
+        bool wasStandalone = standalone;
+        this->disconnect(true);
+
+        pendingTransferToken = packet->token;
+
+        auto result = this->connect(NetworkAddress(packet->address), serverId, wasStandalone);
+        if (!result) {
+            pendingTransferToken.clear();
+            log::warn("failed to connect to the new server: {}", result.unwrapErr());
+            ErrorQueues::get().error(fmt::format("Failed to connect to the new server.\n\nReason: <cy>{}</c>", result.unwrapErr()));
+        }
+    }
+
+    void onServerRestarting(std::shared_ptr<ServerRestartingPacket> packet) {
+        log::info("Server is shutting down in {} seconds, alternate server: '{}'", packet->seconds, packet->alternateAddress);
+
+        if (packet->alternateAddress.empty()) {
+            ErrorQueues::get().notice(fmt::format("This server is restarting in {} seconds. You can reconnect once it is back up.", packet->seconds));
+            return;
+        }
+
+        Loader::get()->queueInMainThread([this, packet = std::move(packet)] {
+            geode::createQuickPopup("Server restarting",
+                fmt::format("This server is restarting in <cy>{}</c> seconds. Do you want to move to another server now?", packet->seconds),
+                "Stay", "Move", [this, address = packet->alternateAddress](FLAlertLayer*, bool move) {
+                    if (!move) return;
+
+                    bool wasStandalone = standalone;
+                    this->disconnect(false);
+
+                    auto result = this->connect(NetworkAddress(address), this->serverIdFor(address), wasStandalone);
+                    if (!result) {
+                        log::warn("failed to connect to the alternate server: {}", result.unwrapErr());
+                        ErrorQueues::get().error(fmt::format("Failed to connect to the new server.\n\nReason: <cy>{}</c>", result.unwrapErr()));
+                    }
+                }
+            );
+        });
+    }
+
+    // if the server is in the server list, use its ID so it shows up as the active server
+    std::string serverIdFor(const std::string& address) {
+        for (const auto& [id, server] : GameServerManager::get().getAllServers()) {
+            if (server.address == address) {
+                return id;
             }
         }
+
+        return address;
     }
 
     void onProtocolMismatch(std::shared_ptr<ProtocolMismatchPacket> packet) {
@@ -708,6 +1120,47 @@ protected:
         return established() ? serverTps.load() : 0;
     }
 
+    bool isVoiceDisabled() {
+        return established() && voiceDisabled.load();
+    }
+
+    bool isChatDisabled() {
+        return established() && chatDisabled.load();
+    }
+
+    std::optional<SettingsHints> getSettingsHints() {
+        return established() ? *settingsHints.lock() : std::nullopt;
+    }
+
+    uint32_t getThrottledSendRate() {
+        return established() ? throttledSendRate.load() : 0;
+    }
+
+    std::optional<UserPreferences> getPreferences() {
+        return established() ? *preferences.lock() : std::nullopt;
+    }
+
+    void syncPreferences() {
+        using InvitesFrom = GlobedSettings::InvitesFrom;
+
+        auto current = this->getPreferences();
+        if (!current) return;
+
+        auto& settings = GlobedSettings::get();
+
+        UserPreferences updated = *current;
+        updated.hideInLists = settings.globed.isInvisible;
+        updated.shareEditorLevels = settings.globed.shareEditorLevels;
+        updated.hideUnlistedLevels = settings.globed.hideUnlistedLevels;
+        updated.hideSpectatorCount = settings.globed.hideSpectatorCount;
+        updated.blockInvites = static_cast<InvitesFrom>((int)settings.globed.invitesFrom) == InvitesFrom::Nobody;
+
+        if (updated == *current) return;
+
+        *preferences.lock() = updated;
+        this->send(UpdatePreferencesPacket::create(updated));
+    }
+
     bool isStandalone() {
         return standalone;
     }
@@ -785,7 +1238,7 @@ protected:
 
     void handlePingResponse(std::shared_ptr<Packet>&& packet) {
         if (auto* pingr = packet->tryDowncast<PingResponsePacket>()) {
-            GameServerManager::get().finishPing(pingr->id, pingr->playerCount);
+            GameServerManager::get().finishPing(pingr->id, pingr->playerCount, pingr->distanceClass);
         }
     }
 
@@ -818,7 +1271,8 @@ protected:
 
                 this->send(CryptoHandshakeStartPacket::create(
                     proto,
-                    CryptoPublicKey(socket.cryptoBox->extractPublicKey())
+                    CryptoPublicKey(socket.cryptoBox->extractPublicKey()),
+                    CompressionSupport { .lz4 = true }
                 ));
             }
         }
@@ -921,6 +1375,8 @@ protected:
                 this->handleSendPacketTask(std::move(std::get<TaskSendPacket>(task)));
             } else if (std::holds_alternative<TaskPingActive>(task)) {
                 this->handlePingActive();
+            } else if (std::holds_alternative<TaskRekey>(task)) {
+                this->handleRekeyTask(std::move(std::get<TaskRekey>(task)));
             }
         }
 
@@ -1007,6 +1463,24 @@ protected:
         }
     }
 
+    void handleRekeyTask(TaskRekey task) {
+        auto newBox = std::make_unique<CryptoBox>(task.serverKey.key.data());
+
+        try {
+            // the response itself is still encrypted with the old key
+            auto result = socket.sendPacket(RekeyResponsePacket::create(CryptoPublicKey(newBox->extractPublicKey())));
+            if (!result) {
+                this->onConnectionError(result.unwrapErr());
+                return;
+            }
+        } catch (const std::exception& e) {
+            this->onConnectionError(e.what());
+            return;
+        }
+
+        socket.swapBox(std::move(newBox));
+    }
+
     void handlePingActive() {
         if (!this->established()) return;
 
@@ -1100,6 +1574,30 @@ uint32_t NetworkManager::getServerTps() {
     return impl->getServerTps();
 }
 
+bool NetworkManager::isVoiceDisabled() {
+    return impl->isVoiceDisabled();
+}
+
+bool NetworkManager::isChatDisabled() {
+    return impl->isChatDisabled();
+}
+
+std::optional<SettingsHints> NetworkManager::getSettingsHints() {
+    return impl->getSettingsHints();
+}
+
+uint32_t NetworkManager::getThrottledSendRate() {
+    return impl->getThrottledSendRate();
+}
+
+std::optional<UserPreferences> NetworkManager::getPreferences() {
+    return impl->getPreferences();
+}
+
+void NetworkManager::syncPreferences() {
+    impl->syncPreferences();
+}
+
 bool NetworkManager::standalone() {
     return impl->isStandalone();
 }
@@ -1,6 +1,7 @@
 #pragma once
 
 #include <Geode/utils/Result.hpp>
+#include <optional>
 
 #include <util/singleton.hpp>
 
@@ -10,6 +11,8 @@ class PacketListener;
 class NetworkAddress;
 struct GameServer;
 class Packet;
+class SettingsHints;
+class UserPreferences;
 
 template <typename T>
 concept HasPacketID = requires { T::PACKET_ID; };
@@ -99,6 +102,22 @@ public:
     // Get the TPS of the currently connected server, or 0
     uint32_t getServerTps();
 
+    // Whether the currently connected server turned off voice chat or text chat for our platform
+    bool isVoiceDisabled();
+    bool isChatDisabled();
+
+    // Get the client settings recommended by the server for our connection, if it has sent any
+    std::optional<SettingsHints> getSettingsHints();
+
+    // Get the send rate the server asked us not to exceed because our packets are piling up, or 0
+    uint32_t getThrottledSendRate();
+
+    // Get the preferences saved with our account on the server, if it has sent any
+    std::optional<UserPreferences> getPreferences();
+
+    // Sends our invisibility and invite settings to the server, if they differ from the saved preferences
+    void syncPreferences();
+
     // Returns true if we are connected to a standalone game server, not tied to any central server.
     bool standalone();
 
@@ -2,7 +2,9 @@
 
 #include <audio/voice_playback_manager.hpp>
 #include <data/packets/client/admin.hpp>
+#include <data/packets/client/game.hpp>
 #include <data/packets/server/admin.hpp>
+#include <managers/admin.hpp>
 #include <managers/block_list.hpp>
 #include <managers/settings.hpp>
 #include <managers/profile_cache.hpp>
@@ -23,23 +25,25 @@ PlayerAccountData getAccountData(int id) {
     return PlayerAccountData::DEFAULT_DATA;
 }
 
-bool GlobedChatCell::init(const std::string& username, int accid, const std::string& messageText) {
+bool GlobedChatCell::init(const std::string& username, int accid, uint32_t messageId, const std::string& messageText, bool mentioned) {
     if (!CCLayerColor::init())
         return false;
 
     user = username;
     accountId = accid;
+    this->messageId = messageId;
 
     auto GAM = GJAccountManager::sharedState();
 
     this->setContentSize(ccp(290, CELL_HEIGHT));
     this->setAnchorPoint(ccp(0, 0));
 
-    // background
+    // background, highlighted if the message mentions us
     Build<CCScale9Sprite>::create("square02_001.png")
         .contentSize(this->getContentSize() * 3.f)
         .scale(1.f / 3.f)
-        .opacity(67)
+        .color(mentioned ? ccColor3B{255, 200, 60} : ccColor3B{0, 0, 0})
+        .opacity(mentioned ? 110 : 67)
         .zOrder(-1)
         .anchorPoint(0.f, 0.f)
         .parent(this);
@@ -106,6 +110,23 @@ bool GlobedChatCell::init(const std::string& username, int accid, const std::str
 
     this->addChild(messageTextLabel);
 
+    // the server decides if we are actually allowed to delete it, e.g. authors can only do so shortly after sending
+    bool canDelete = messageId != 0 && (accid == GAM->m_accountID || AdminManager::get().authorized());
+    if (canDelete) {
+        auto* deleteMenu = Build<CCMenu>::create()
+            .pos(this->getContentSize().width - 14.f, CELL_HEIGHT / 2.f)
+            .parent(this)
+            .collect();
+
+        Build<CCSprite>::createSpriteName("GJ_deleteIcon_001.png")
+            .scale(0.6f)
+            .intoMenuItem([this](auto) {
+                this->onDelete(nullptr);
+            })
+            .id("delete-btn")
+            .parent(deleteMenu);
+    }
+
     return true;
 }
 
@@ -113,9 +134,16 @@ void GlobedChatCell::onUser(CCObject* sender) {
     ProfilePage::create(accountId, GJAccountManager::sharedState()->m_accountID == accountId)->show();
 }
 
-GlobedChatCell* GlobedChatCell::create(const std::string& username, int aid, const std::string& messageText) {
+void GlobedChatCell::onDelete(CCObject* sender) {
+    auto& nm = NetworkManager::get();
+    if (!nm.established()) return;
+
+    nm.send(DeleteChatMessagePacket::create(messageId));
+}
+
+GlobedChatCell* GlobedChatCell::create(const std::string& username, int aid, uint32_t messageId, const std::string& messageText, bool mentioned) {
     auto* ret = new GlobedChatCell;
-    if (ret->init(username, aid, messageText)) {
+    if (ret->init(username, aid, messageId, messageText, mentioned)) {
         ret->autorelease();
         return ret;
     }
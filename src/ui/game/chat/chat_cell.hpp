@@ -10,8 +10,10 @@ public:
 
     std::string user;
     int accountId;
+    uint32_t messageId;
 
     void onUser(cocos2d::CCObject* sender);
-    bool init(const std::string& username, int accid, const std::string& messageText);
-    static GlobedChatCell* create(const std::string& username, int aid, const std::string& messageText);
+    void onDelete(cocos2d::CCObject* sender);
+    bool init(const std::string& username, int accid, uint32_t messageId, const std::string& messageText, bool mentioned);
+    static GlobedChatCell* create(const std::string& username, int aid, uint32_t messageId, const std::string& messageText, bool mentioned);
 };
\ No newline at end of file
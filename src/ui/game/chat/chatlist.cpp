@@ -1,6 +1,7 @@
 #include "chatlist.hpp"
 #include <audio/voice_playback_manager.hpp>
 #include <hooks/gjbasegamelayer.hpp>
+#include <managers/error_queues.hpp>
 #include <managers/profile_cache.hpp>
 #include <managers/friend_list.hpp>
 #include <managers/settings.hpp>
@@ -58,13 +59,44 @@ bool GlobedChatListPopup::setup() {
 
     scroll->m_contentLayer->removeAllChildren();
 
+    // only shown on levels where chat is split into channels
+    Build<CCMenu>::create()
+        .layout(RowLayout::create()->setGap(4.f)->setAutoScale(false))
+        .contentSize(120.f, 20.f)
+        .pos(POPUP_WIDTH - 70.f, POPUP_HEIGHT - 20.f)
+        .store(channelMenu)
+        .parent(m_mainLayer);
+
+    Build<CCSprite>::createSpriteName("GJ_arrow_03_001.png")
+        .scale(0.35f)
+        .intoMenuItem([this](auto) {
+            this->switchChannel(-1);
+        })
+        .parent(channelMenu);
+
+    Build<CCLabelBMFont>::create("", "bigFont.fnt")
+        .scale(0.35f)
+        .store(channelLabel)
+        .parent(channelMenu);
+
+    CCSprite* nextSprite;
+    Build<CCSprite>::createSpriteName("GJ_arrow_03_001.png")
+        .scale(0.35f)
+        .store(nextSprite)
+        .intoMenuItem([this](auto) {
+            this->switchChannel(1);
+        })
+        .parent(channelMenu);
+
+    nextSprite->setFlipX(true);
+
+    this->updateChannel();
+
     this->setTouchEnabled(true);
     CCTouchDispatcher::get()->addTargetedDelegate(this, -129, true);
     CCTouchDispatcher::get()->addTargetedDelegate(scroll, -130, true);
 
-    for (auto message : GlobedGJBGL::get()->m_fields->chatMessages) {
-        this->createMessage(message.first, message.second);
-    }
+    this->rebuildMessages();
 
     this->schedule(schedule_selector(GlobedChatListPopup::updateChat));
 
@@ -84,14 +116,22 @@ void GlobedChatListPopup::onClose(CCObject*) {
 }
 
 void GlobedChatListPopup::onChat(CCObject* sender) {
+    auto& nm = NetworkManager::get();
+    if (nm.isChatDisabled()) {
+        ErrorQueues::get().warn("Text chat is disabled by the server on this platform");
+        return;
+    }
+
     if (inp->getString().size() != 0) {
-        auto& nm = NetworkManager::get();
-        nm.send(ChatMessagePacket::create(std::string_view(inp->getString())));
+        auto& fields = GlobedGJBGL::get()->m_fields;
+        uint32_t clientId = ++fields->lastChatClientId;
+
+        nm.send(ChatMessagePacket::create(clientId, std::string_view(inp->getString())));
 
         auto GAM = GJAccountManager::sharedState();
 
-        GlobedGJBGL::get()->m_fields->chatMessages.push_back({GAM->m_accountID, inp->getString()});
-        createMessage(GAM->m_accountID, inp->getString());
+        fields->chatMessages.push_back({0, GAM->m_accountID, inp->getString(), false, clientId});
+        createMessage(0, GAM->m_accountID, inp->getString(), false);
 
         //GlobedGJBGL::get()->m_fields->chatOverlay->addMessage(GAM->m_accountID, inp->getString());
 
@@ -99,7 +139,41 @@ void GlobedChatListPopup::onChat(CCObject* sender) {
     }
 }
 
-void GlobedChatListPopup::createMessage(int accountID, const std::string& message) {
+void GlobedChatListPopup::switchChannel(int offset) {
+    auto& fields = GlobedGJBGL::get()->m_fields;
+    if (fields->chatChannelCount == 0) return;
+
+    // channels are numbered from 1, wrap around at both ends
+    uint32_t count = fields->chatChannelCount;
+    uint32_t channel = (fields->chatChannel - 1 + count + offset) % count + 1;
+
+    NetworkManager::get().send(SwitchChatChannelPacket::create(channel));
+}
+
+void GlobedChatListPopup::updateChannel() {
+    auto& fields = GlobedGJBGL::get()->m_fields;
+
+    shownChannel = fields->chatChannel;
+    shownChannelCount = fields->chatChannelCount;
+
+    channelMenu->setVisible(shownChannelCount != 0);
+    channelLabel->setString(fmt::format("Channel {}/{}", shownChannel, shownChannelCount).c_str());
+    channelMenu->updateLayout();
+}
+
+void GlobedChatListPopup::rebuildMessages() {
+    scroll->m_contentLayer->removeAllChildren();
+    messageCells.clear();
+
+    auto& fields = GlobedGJBGL::get()->m_fields;
+    for (const auto& message : fields->chatMessages) {
+        this->createMessage(message.id, message.accountId, message.message, message.mentioned);
+    }
+
+    chatRevision = fields->chatRevision;
+}
+
+void GlobedChatListPopup::createMessage(uint32_t messageId, int accountID, const std::string& message, bool mentioned) {
     auto& pcm = ProfileCacheManager::get();
     auto& playerStore = GlobedGJBGL::get()->m_fields->playerStore->getAll();
 
@@ -111,7 +185,7 @@ void GlobedChatListPopup::createMessage(int accountID, const std::string& messag
     // if account ID is in the player cache, get the username from there
     if (pcm.getData(accountID)) username = pcm.getData(accountID).value().name;
 
-    auto cell = GlobedChatCell::create(username, accountID, message);
+    auto cell = GlobedChatCell::create(username, accountID, messageId, message, mentioned);
     cell->setPositionY(5.f);
     cell->setPositionX(5.f);
     scroll->m_contentLayer->addChild(cell);
@@ -132,9 +206,22 @@ void GlobedChatListPopup::createMessage(int accountID, const std::string& messag
 
 void GlobedChatListPopup::updateChat(float dt) {
     // if we have more messages stored then the amount we have displaying, display the rest of them
-    auto& messages = GlobedGJBGL::get()->m_fields->chatMessages;
+    auto& fields = GlobedGJBGL::get()->m_fields;
+
+    if (fields->chatChannel != shownChannel || fields->chatChannelCount != shownChannelCount) {
+        this->updateChannel();
+    }
+
+    // a message was deleted or got its ID, simplest to just start over
+    if (fields->chatRevision != chatRevision) {
+        this->rebuildMessages();
+        return;
+    }
+
+    auto& messages = fields->chatMessages;
     if (messages.size() > messageCells.size()) {
-        this->createMessage(messages.back().first, messages.back().second);
+        auto& message = messages.back();
+        this->createMessage(message.id, message.accountId, message.message, message.mentioned);
     }
 }
 
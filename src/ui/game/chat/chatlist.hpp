@@ -16,20 +16,28 @@ protected:
     cocos2d::CCLayer* layer2;
     geode::TextInput* inp;
     cocos2d::CCMenu* menu;
+    cocos2d::CCMenu* channelMenu;
+    cocos2d::CCLabelBMFont* channelLabel;
     std::vector<GlobedChatCell*> messageCells;
 
     float nextY = 0.f;
     int messages = 0;
+    uint32_t chatRevision = 0;
+    uint32_t shownChannel = 0;
+    uint32_t shownChannelCount = 0;
 
     void onChat(cocos2d::CCObject* sender);
+    void switchChannel(int offset);
+    void updateChannel();
     void onClose(cocos2d::CCObject* sender) override;
 
     void updateChat(float dt);
+    void rebuildMessages();
 
     virtual void keyBackClicked() override;
     virtual void keyDown(cocos2d::enumKeyCodes) override;
 
 public:
 	static GlobedChatListPopup* create();
-    void createMessage(int accountID, const std::string& message);
+    void createMessage(uint32_t messageId, int accountID, const std::string& message, bool mentioned);
 };
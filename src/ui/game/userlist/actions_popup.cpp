@@ -89,6 +89,22 @@ void GlobedUserActionsPopup::remakeButtons() {
                 });
             })
             .parent(buttonLayout);
+
+        // spectate button
+        bool isSpectating = pl->m_fields->spectating == accountId;
+
+        auto spectateSprite = Build<CCSprite>::createSpriteName(isSpectating ? "gj_findBtnOff_001.png" : "gj_findBtn_001.png").collect();
+        util::ui::rescaleToMatch(spectateSprite, muteButton);
+
+        Build<CCSprite>(spectateSprite)
+            .intoMenuItem([this, isSpectating, pl](auto) {
+                pl->spectatePlayer(isSpectating ? 0 : accountId);
+
+                Loader::get()->queueInMainThread([this] {
+                    this->remakeButtons();
+                });
+            })
+            .parent(buttonLayout);
     }
 
     buttonLayout->updateLayout();
@@ -3,6 +3,7 @@
 #include "userlist.hpp"
 #include "actions_popup.hpp"
 #include <audio/voice_playback_manager.hpp>
+#include <data/packets/client/general.hpp>
 #include <managers/admin.hpp>
 #include <managers/block_list.hpp>
 #include <managers/settings.hpp>
@@ -12,6 +13,7 @@
 #include <hooks/gjgamelevel.hpp>
 #include <ui/general/ask_input_popup.hpp>
 #include <util/format.hpp>
+#include <util/misc.hpp>
 #include <util/ui.hpp>
 #include <util/cocos.hpp>
 
@@ -178,6 +180,8 @@ void GlobedUserCell::makeButtons() {
     bool createBtnAdmin = AdminManager::get().authorized();
     bool createBtn2plink = pl->m_fields->roomSettings.flags.twoPlayerMode && notSelf;
     bool createBtnTp = createBtnAdmin && notSelf && !createBtn2plink;
+    // moderators have the admin menu instead
+    bool createBtnReport = notSelf && !createBtnAdmin;
     bool createVisualizer = settings.communication.voiceEnabled && notSelf;
     bool createSettingsAlts = false;
 
@@ -274,6 +278,21 @@ void GlobedUserCell::makeButtons() {
         maxWidth += kickButton->getScaledContentSize().width + gap;
     }
 
+    if (createBtnReport) {
+        Build<CCSprite>::createSpriteName("GJ_reportBtn_001.png")
+            .scale(0.4f)
+            .intoMenuItem([accountId = accountData.accountId](auto) {
+                AskInputPopup::create("Report player", [accountId](auto reason) {
+                    NetworkManager::get().send(ReportPlayerPacket::create(accountId, reason));
+                }, 128, "Reason", util::misc::STRING_PRINTABLE_INPUT, 1.f)->show();
+            })
+            .parent(buttonsWrapper)
+            .id("report-button"_spr)
+            .store(reportButton);
+
+        maxWidth += reportButton->getScaledContentSize().width + gap;
+    }
+
     if (createBtnTp) {
         Build<CCSprite>::createSpriteName("icon-teleport.png"_spr)
             .scale(0.35f)
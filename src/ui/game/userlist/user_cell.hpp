@@ -26,7 +26,8 @@ private:
         *hideButton = nullptr,
         *kickButton = nullptr,
         *teleportButton = nullptr,
-        *linkButton = nullptr;
+        *linkButton = nullptr,
+        *reportButton = nullptr;
 
     cocos2d::CCMenu* buttonsWrapper = nullptr;
     GlobedAudioVisualizer* audioVisualizer = nullptr;
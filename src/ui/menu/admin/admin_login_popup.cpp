@@ -14,12 +14,17 @@ bool AdminLoginPopup::setup() {
     setTitle("Admin Login");
 
     Build<InputNode>::create(POPUP_WIDTH * 0.75f, "Password", "chatFont.fnt", std::string(util::misc::STRING_PRINTABLE_INPUT), 32)
-        .pos(sizes.center.width, sizes.center.height + 20.f)
+        .pos(sizes.center.width, sizes.center.height + 35.f)
         .parent(m_mainLayer)
         .store(passwordInput);
 
+    Build<InputNode>::create(POPUP_WIDTH * 0.75f, "2FA code (if enabled)", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 6)
+        .pos(sizes.center.width, sizes.center.height)
+        .parent(m_mainLayer)
+        .store(totpInput);
+
     auto* btnLayout = Build<CCMenu>::create()
-        .pos(sizes.center.width, sizes.center.height - 20.f)
+        .pos(sizes.center.width, sizes.center.height - 40.f)
         .layout(RowLayout::create()->setGap(5.f)->setAutoScale(false))
         .parent(m_mainLayer)
         .collect();
@@ -40,7 +45,7 @@ bool AdminLoginPopup::setup() {
                     GlobedAccountManager::get().storeAdminPassword(password);
                 }
 
-                nm.send(AdminAuthPacket::create(password));
+                nm.send(AdminAuthPacket::create(password, this->totpInput->getString()));
                 this->onClose(this);
             }
         })
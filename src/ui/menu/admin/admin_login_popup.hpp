@@ -6,12 +6,13 @@
 class AdminLoginPopup : public geode::Popup<> {
 public:
     static constexpr float POPUP_WIDTH = 300.f;
-    static constexpr float POPUP_HEIGHT = 140.f;
+    static constexpr float POPUP_HEIGHT = 180.f;
 
     static AdminLoginPopup* create();
 
 private:
     geode::InputNode* passwordInput = nullptr;
+    geode::InputNode* totpInput = nullptr;
 
     void onRememberPassword(cocos2d::CCObject* sender);
 
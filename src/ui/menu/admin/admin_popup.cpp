@@ -1,6 +1,12 @@
 #include "admin_popup.hpp"
 
+#include "bots_popup.hpp"
+#include "chat_tail_popup.hpp"
 #include "send_notice_popup.hpp"
+#include "slow_mode_popup.hpp"
+#include "player_history_popup.hpp"
+#include "reports_popup.hpp"
+#include "traffic_stats_popup.hpp"
 #include "user_popup.hpp"
 #include <data/packets/client/admin.hpp>
 #include <data/packets/server/admin.hpp>
@@ -62,13 +68,12 @@ bool AdminPopup::setup() {
     if (!authorized) return false;
 
     nm.addListener<AdminUserDataPacket>(this, [](auto packet) {
-        AdminUserPopup::create(packet->userEntry, packet->accountData)->show();
+        AdminUserPopup::create(packet->userEntry, packet->accountData, packet->connection, packet->levelHistory)->show();
     });
 
     nm.addListener<AdminErrorPacket>(this, [this](auto packet) {
-        // incredibly scary code
-
-        if (packet->message.find("failed to find the user by name") != std::string::npos) {
+        // the user might exist in gd without having logged into globed yet
+        if (packet->code == ErrorCode::NotFound) {
             // try to search the user in gd
             auto username = this->userInput->getString();
             IntermediaryLoadingPopup::create([this, username = std::move(username)](auto popup) {
@@ -97,14 +102,14 @@ bool AdminPopup::setup() {
                 glm->m_levelManagerDelegate = nullptr;
             })->show();
         } else {
-            ErrorQueues::get().warn(packet->message);
+            ErrorQueues::get().warn(util::format::serverError(packet->code, packet->message));
         }
     }, true);
 
     auto* topRightCorner = Build<CCMenu>::create()
         .layout(RowLayout::create()->setGap(5.f)->setAxisReverse(true))
         .pos(sizes.topRight - CCPoint{10.f, 30.f})
-        .contentSize(65.f, 0.f)
+        .contentSize(250.f, 0.f)
         .anchorPoint(1.f, 0.5f)
         .parent(m_mainLayer)
         .collect();
@@ -133,6 +138,97 @@ bool AdminPopup::setup() {
         })
         .parent(topRightCorner);
 
+    // block and unblock level buttons
+    auto askLevelId = [](const char* title, bool blocked) {
+        AskInputPopup::create(title, [blocked](auto input) {
+            auto levelId = util::format::parse<LevelId>(input);
+            if (!levelId || levelId.value() <= 0) {
+                ErrorQueues::get().warn("Invalid level ID");
+                return;
+            }
+
+            NetworkManager::get().send(AdminSetLevelBlockedPacket::create(levelId.value(), blocked));
+        }, 16, "Level ID", util::misc::STRING_DIGITS, 1.f)->show();
+    };
+
+    Build<CCSprite>::createSpriteName("GJ_reportBtn_001.png")
+        .scale(0.75f)
+        .intoMenuItem([askLevelId](auto) {
+            askLevelId("Block level", true);
+        })
+        .parent(topRightCorner);
+
+    Build<CCSprite>::createSpriteName("GJ_undoBtn_001.png")
+        .scale(0.75f)
+        .intoMenuItem([askLevelId](auto) {
+            askLevelId("Unblock level", false);
+        })
+        .parent(topRightCorner);
+
+    // slow mode button
+    Build<CCSprite>::createSpriteName("GJ_timeIcon_001.png")
+        .scale(0.9f)
+        .intoMenuItem([](auto) {
+            AdminSlowModePopup::create()->show();
+        })
+        .parent(topRightCorner);
+
+    // player count history button
+    Build<CCSprite>::createSpriteName("GJ_statsBtn_001.png")
+        .scale(0.6f)
+        .intoMenuItem([](auto) {
+            AdminPlayerHistoryPopup::create()->show();
+        })
+        .parent(topRightCorner);
+
+    // traffic stats button
+    Build<CCSprite>::createSpriteName("GJ_infoIcon_001.png")
+        .scale(0.75f)
+        .intoMenuItem([](auto) {
+            AdminTrafficStatsPopup::create()->show();
+        })
+        .parent(topRightCorner);
+
+    // bots button
+    Build<CCSprite>::createSpriteName("GJ_playBtn2_001.png")
+        .scale(0.35f)
+        .intoMenuItem([](auto) {
+            AdminBotsPopup::create()->show();
+        })
+        .parent(topRightCorner);
+
+    // whitelist invite code button
+    Build<CCSprite>::createSpriteName("GJ_plusBtn_001.png")
+        .scale(0.6f)
+        .intoMenuItem([](auto) {
+            AskInputPopup::create("Create invite code", [](auto input) {
+                auto uses = util::format::parse<uint32_t>(input);
+                if (!uses || uses.value() == 0) {
+                    ErrorQueues::get().warn("Invalid amount of uses");
+                    return;
+                }
+
+                NetworkManager::get().send(AdminCreateInviteCodePacket::create(uses.value()));
+            }, 3, "Uses", util::misc::STRING_DIGITS, 1.f)->show();
+        })
+        .parent(topRightCorner);
+
+    // player reports button
+    Build<CCSprite>::createSpriteName("accountBtn_messages_001.png")
+        .scale(0.45f)
+        .intoMenuItem([](auto) {
+            AdminReportsPopup::create()->show();
+        })
+        .parent(topRightCorner);
+
+    // follow chat button
+    Build<CCSprite>::createSpriteName("GJ_chatBtn_001.png")
+        .scale(0.45f)
+        .intoMenuItem([](auto) {
+            AdminChatTailPopup::create()->show();
+        })
+        .parent(topRightCorner);
+
     topRightCorner->updateLayout();
 
     // send notice menu
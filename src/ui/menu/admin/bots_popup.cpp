@@ -0,0 +1,185 @@
+#include "bots_popup.hpp"
+
+#include <data/packets/client/admin.hpp>
+#include <net/manager.hpp>
+#include <managers/error_queues.hpp>
+#include <ui/general/ask_input_popup.hpp>
+#include <util/ui.hpp>
+#include <util/misc.hpp>
+#include <util/format.hpp>
+
+using namespace geode::prelude;
+
+bool AdminBotsPopup::setup() {
+    this->setTitle("Bots");
+
+    auto* inputLayout = Build<CCMenu>::create()
+        .layout(RowLayout::create()->setGap(5.f))
+        .pos(m_size.width / 2.f, m_size.height - 45.f)
+        .contentSize(LIST_WIDTH, 30.f)
+        .parent(m_mainLayer)
+        .collect();
+
+    Build<InputNode>::create(LIST_WIDTH * 0.3f, "name", "chatFont.fnt", std::string(util::misc::STRING_ALPHANUMERIC), 18)
+        .parent(inputLayout)
+        .store(nameInput);
+
+    // room ID 0 is the global room, level ID 0 only puts the bot in the room
+    Build<InputNode>::create(LIST_WIDTH * 0.22f, "room ID", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 9)
+        .parent(inputLayout)
+        .store(roomInput);
+
+    Build<InputNode>::create(LIST_WIDTH * 0.22f, "level ID", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 11)
+        .parent(inputLayout)
+        .store(levelInput);
+
+    Build<ButtonSprite>::create("Spawn", "bigFont.fnt", "GJ_button_01.png", 0.5f)
+        .intoMenuItem([this](auto) {
+            this->spawn();
+        })
+        .parent(inputLayout);
+
+    inputLayout->updateLayout();
+
+    auto* listLayer = Build<GJCommentListLayer>::create(nullptr, "", util::ui::BG_COLOR_BROWN, LIST_WIDTH, LIST_HEIGHT, false)
+        .pos((m_mainLayer->getScaledContentSize().width - LIST_WIDTH) / 2, 25.f)
+        .parent(m_mainLayer)
+        .collect();
+
+    Build(ScrollLayer::create({LIST_WIDTH, LIST_HEIGHT}))
+        .parent(listLayer)
+        .store(scrollLayer);
+
+    scrollLayer->m_contentLayer->setLayout(
+        ColumnLayout::create()
+            ->setGap(0.f)
+            ->setAxisReverse(true)
+            ->setAxisAlignment(AxisAlignment::End)
+            ->setAutoScale(false)
+    );
+
+    Build<CCSprite>::createSpriteName("GJ_updateBtn_001.png")
+        .scale(0.9f)
+        .intoMenuItem([this](auto) {
+            this->refresh();
+        })
+        .pos(m_size.width / 2.f - 3.f, -m_size.height / 2.f + 3.f)
+        .intoNewParent(CCMenu::create())
+        .parent(m_mainLayer);
+
+    NetworkManager::get().addListener<AdminBotsPacket>(this, [this](auto packet) {
+        this->showBots(packet->bots);
+    });
+
+    this->refresh();
+
+    return true;
+}
+
+void AdminBotsPopup::refresh() {
+    NetworkManager::get().send(AdminGetBotsPacket::create());
+}
+
+void AdminBotsPopup::spawn() {
+    std::string name = nameInput->getString();
+    if (name.empty()) {
+        ErrorQueues::get().warn("Bot name cannot be empty");
+        return;
+    }
+
+    uint32_t roomId = util::format::parse<uint32_t>(roomInput->getString()).value_or(0);
+    LevelId levelId = util::format::parse<LevelId>(levelInput->getString()).value_or(0);
+
+    NetworkManager::get().send(AdminSpawnBotPacket::create(roomId, levelId, name));
+}
+
+void AdminBotsPopup::showBots(const std::vector<BotInfo>& bots) {
+    scrollLayer->m_contentLayer->removeAllChildren();
+
+    for (const auto& bot : bots) {
+        auto* row = Build<CCMenu>::create()
+            .contentSize(LIST_WIDTH, ROW_HEIGHT)
+            .parent(scrollLayer->m_contentLayer)
+            .collect();
+
+        std::string location = bot.levelId != 0
+            ? fmt::format("level {} (room {}) at {:.0f}, {:.0f}", bot.levelId, bot.roomId, bot.position.x, bot.position.y)
+            : fmt::format("room {}", bot.roomId);
+
+        Build<CCLabelBMFont>::create(bot.name.c_str(), "bigFont.fnt")
+            .limitLabelWidth(LIST_WIDTH - 110.f, 0.4f, 0.1f)
+            .anchorPoint(0.f, 0.5f)
+            .pos(8.f, ROW_HEIGHT * 0.7f)
+            .parent(row);
+
+        Build<CCLabelBMFont>::create(fmt::format("{}, spawned by {}", location, bot.spawnedBy).c_str(), "chatFont.fnt")
+            .limitLabelWidth(LIST_WIDTH - 110.f, 0.5f, 0.1f)
+            .anchorPoint(0.f, 0.5f)
+            .pos(8.f, ROW_HEIGHT * 0.3f)
+            .parent(row);
+
+        // chat button
+        Build<CCSprite>::createSpriteName("GJ_chatBtn_001.png")
+            .scale(0.4f)
+            .intoMenuItem([accountId = bot.accountId](auto) {
+                AskInputPopup::create("Send message", [accountId](auto message) {
+                    NetworkManager::get().send(AdminBotChatPacket::create(accountId, message));
+                }, 156, "message", util::misc::STRING_PRINTABLE_INPUT, 1.f)->show();
+            })
+            .pos(LIST_WIDTH - 80.f, ROW_HEIGHT / 2.f)
+            .parent(row);
+
+        // move button, the bot stays in its room but can be moved to another level
+        Build<CCSprite>::createSpriteName("GJ_arrow_03_001.png")
+            .scale(0.45f)
+            .intoMenuItem([this, accountId = bot.accountId, levelId = bot.levelId](auto) {
+                AskInputPopup::create("Move bot (x, y)", [this, accountId, levelId](auto input) {
+                    auto comma = input.find(',');
+                    if (comma == std::string_view::npos) {
+                        ErrorQueues::get().warn("Position must be in the format x, y");
+                        return;
+                    }
+
+                    // from_chars for floats is not available everywhere
+                    auto x = std::strtof(util::format::trim(input.substr(0, comma)).c_str(), nullptr);
+                    auto y = std::strtof(util::format::trim(input.substr(comma + 1)).c_str(), nullptr);
+
+                    NetworkManager::get().send(AdminMoveBotPacket::create(accountId, levelId, CCPoint{x, y}));
+                    this->refresh();
+                }, 24, "x, y", "0123456789.,- ", 1.f)->show();
+            })
+            .pos(LIST_WIDTH - 50.f, ROW_HEIGHT / 2.f)
+            .parent(row);
+
+        Build<CCSprite>::createSpriteName("GJ_deleteIcon_001.png")
+            .scale(0.7f)
+            .intoMenuItem([this, accountId = bot.accountId](auto) {
+                NetworkManager::get().send(AdminRemoveBotPacket::create(accountId));
+                this->refresh();
+            })
+            .pos(LIST_WIDTH - 20.f, ROW_HEIGHT / 2.f)
+            .parent(row);
+    }
+
+    if (bots.empty()) {
+        Build<CCLabelBMFont>::create("No bots", "bigFont.fnt")
+            .scale(0.4f)
+            .parent(scrollLayer->m_contentLayer);
+    }
+
+    scrollLayer->m_contentLayer->setContentHeight(std::max(LIST_HEIGHT, ROW_HEIGHT * bots.size()));
+    scrollLayer->m_contentLayer->updateLayout();
+
+    util::ui::scrollToTop(scrollLayer);
+}
+
+AdminBotsPopup* AdminBotsPopup::create() {
+    auto* ret = new AdminBotsPopup;
+    if (ret->init(POPUP_WIDTH, POPUP_HEIGHT)) {
+        ret->autorelease();
+        return ret;
+    }
+
+    delete ret;
+    return nullptr;
+}
@@ -0,0 +1,24 @@
+#pragma once
+#include <defs/all.hpp>
+
+#include <data/packets/server/admin.hpp>
+
+class AdminBotsPopup : public geode::Popup<> {
+public:
+    static constexpr float POPUP_WIDTH = 380.f;
+    static constexpr float POPUP_HEIGHT = 260.f;
+    static constexpr float LIST_WIDTH = 340.f;
+    static constexpr float LIST_HEIGHT = 150.f;
+    static constexpr float ROW_HEIGHT = 30.f;
+
+    static AdminBotsPopup* create();
+
+private:
+    geode::ScrollLayer* scrollLayer;
+    geode::InputNode *nameInput, *roomInput, *levelInput;
+
+    bool setup() override;
+    void refresh();
+    void spawn();
+    void showBots(const std::vector<BotInfo>& bots);
+};
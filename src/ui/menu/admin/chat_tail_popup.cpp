@@ -0,0 +1,127 @@
+#include "chat_tail_popup.hpp"
+
+#include <data/packets/client/admin.hpp>
+#include <managers/error_queues.hpp>
+#include <net/manager.hpp>
+#include <util/ui.hpp>
+#include <util/misc.hpp>
+#include <util/format.hpp>
+
+using namespace geode::prelude;
+
+bool AdminChatTailPopup::setup() {
+    this->setTitle("Follow chat");
+
+    auto sizes = util::ui::getPopupLayout(m_size);
+
+    // level ID 0 follows every level in the room, room ID 0 is the global room
+    auto* inputLayout = Build<CCMenu>::create()
+        .layout(RowLayout::create()->setGap(5.f))
+        .pos(sizes.centerTop + CCPoint{0.f, -45.f})
+        .contentSize(m_size.width * 0.9f, 30.f)
+        .parent(m_mainLayer)
+        .collect();
+
+    Build<InputNode>::create(m_size.width * 0.3f, "room ID", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 9)
+        .parent(inputLayout)
+        .store(roomInput);
+
+    Build<InputNode>::create(m_size.width * 0.3f, "level ID", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 11)
+        .parent(inputLayout)
+        .store(levelInput);
+
+    inputLayout->updateLayout();
+
+    Build<CCLabelBMFont>::create("", "chatFont.fnt")
+        .scale(0.55f)
+        .anchorPoint(0.5f, 1.f)
+        .pos(sizes.centerTop + CCPoint{0.f, -70.f})
+        .parent(m_mainLayer)
+        .store(messagesLabel);
+
+    auto* buttonLayout = Build<CCMenu>::create()
+        .layout(RowLayout::create()->setGap(5.f))
+        .pos(sizes.centerBottom + CCPoint{0.f, 20.f})
+        .contentSize(m_size.width * 0.9f, 30.f)
+        .parent(m_mainLayer)
+        .collect();
+
+    Build<ButtonSprite>::create("Start", "bigFont.fnt", "GJ_button_01.png", 0.6f)
+        .scale(0.8f)
+        .intoMenuItem([this](auto) {
+            this->start();
+        })
+        .parent(buttonLayout);
+
+    Build<ButtonSprite>::create("Stop", "bigFont.fnt", "GJ_button_06.png", 0.6f)
+        .scale(0.8f)
+        .intoMenuItem([this](auto) {
+            this->stop();
+        })
+        .parent(buttonLayout);
+
+    buttonLayout->updateLayout();
+
+    NetworkManager::get().addListener<AdminChatTailPacket>(this, [this](auto packet) {
+        this->addMessage(*packet);
+    });
+
+    return true;
+}
+
+void AdminChatTailPopup::onClose(CCObject* sender) {
+    // the chat is only followed while the popup is open
+    this->stop();
+    Popup::onClose(sender);
+}
+
+void AdminChatTailPopup::start() {
+    uint32_t roomId = util::format::parse<uint32_t>(roomInput->getString()).value_or(0);
+    LevelId levelId = util::format::parse<LevelId>(levelInput->getString()).value_or(0);
+
+    if (roomId == 0 && levelId == 0) {
+        ErrorQueues::get().warn("Pick a level to follow chat in the global room");
+        return;
+    }
+
+    messages.clear();
+    messagesLabel->setString("");
+    following = true;
+
+    NetworkManager::get().send(AdminTailChatPacket::create(roomId, levelId, true));
+}
+
+void AdminChatTailPopup::stop() {
+    if (!following) return;
+
+    following = false;
+    NetworkManager::get().send(AdminTailChatPacket::create(0, 0, false));
+}
+
+void AdminChatTailPopup::addMessage(const AdminChatTailPacket& packet) {
+    auto name = packet.playerName.empty() ? std::to_string(packet.playerId) : packet.playerName;
+    messages.push_back(fmt::format("[{}] {}: {}", packet.levelId, name, packet.message));
+
+    while (messages.size() > MAX_MESSAGES) {
+        messages.pop_front();
+    }
+
+    std::string text;
+    for (const auto& message : messages) {
+        if (!text.empty()) text += '\n';
+        text += message;
+    }
+
+    messagesLabel->setString(text.c_str());
+}
+
+AdminChatTailPopup* AdminChatTailPopup::create() {
+    auto* ret = new AdminChatTailPopup;
+    if (ret->init(POPUP_WIDTH, POPUP_HEIGHT)) {
+        ret->autorelease();
+        return ret;
+    }
+
+    delete ret;
+    return nullptr;
+}
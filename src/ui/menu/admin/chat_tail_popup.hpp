@@ -0,0 +1,27 @@
+#pragma once
+#include <defs/all.hpp>
+
+#include <deque>
+
+#include <data/packets/server/admin.hpp>
+
+class AdminChatTailPopup : public geode::Popup<> {
+public:
+    static constexpr float POPUP_WIDTH = 380.f;
+    static constexpr float POPUP_HEIGHT = 260.f;
+    static constexpr size_t MAX_MESSAGES = 12;
+
+    static AdminChatTailPopup* create();
+
+private:
+    geode::InputNode *roomInput, *levelInput;
+    cocos2d::CCLabelBMFont* messagesLabel;
+    std::deque<std::string> messages;
+    bool following = false;
+
+    bool setup() override;
+    void onClose(cocos2d::CCObject* sender) override;
+    void start();
+    void stop();
+    void addMessage(const AdminChatTailPacket& packet);
+};
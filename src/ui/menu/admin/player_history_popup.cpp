@@ -0,0 +1,125 @@
+#include "player_history_popup.hpp"
+
+#include <data/packets/client/admin.hpp>
+#include <net/manager.hpp>
+#include <util/ui.hpp>
+#include <util/misc.hpp>
+#include <util/format.hpp>
+
+using namespace geode::prelude;
+
+bool AdminPlayerHistoryPopup::setup() {
+    this->setTitle("Player count history");
+
+    auto sizes = util::ui::getPopupLayout(m_size);
+
+    // level ID takes priority, leave both empty for the whole server
+    auto* inputLayout = Build<CCMenu>::create()
+        .layout(RowLayout::create()->setGap(5.f))
+        .pos(sizes.centerTop - CCPoint{0.f, 45.f})
+        .contentSize(m_size.width * 0.9f, 30.f)
+        .parent(m_mainLayer)
+        .collect();
+
+    Build<InputNode>::create(m_size.width * 0.35f, "room ID", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 9)
+        .parent(inputLayout)
+        .store(roomInput);
+
+    Build<InputNode>::create(m_size.width * 0.35f, "level ID", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 11)
+        .parent(inputLayout)
+        .store(levelInput);
+
+    inputLayout->updateLayout();
+
+    // graph background
+    Build<CCLayerColor>::create(ccc4(0, 0, 0, 80), GRAPH_WIDTH, GRAPH_HEIGHT)
+        .ignoreAnchorPointForPos(false)
+        .pos(sizes.center - CCPoint{0.f, 10.f})
+        .parent(m_mainLayer)
+        .store(graph);
+
+    Build<CCLabelBMFont>::create("", "bigFont.fnt")
+        .scale(0.35f)
+        .pos(sizes.center - CCPoint{0.f, 10.f + GRAPH_HEIGHT / 2.f + 10.f})
+        .parent(m_mainLayer)
+        .store(summaryLabel);
+
+    auto* periodLayout = Build<CCMenu>::create()
+        .layout(RowLayout::create()->setGap(5.f))
+        .pos(sizes.centerBottom + CCPoint{0.f, 20.f})
+        .contentSize(m_size.width * 0.9f, 30.f)
+        .parent(m_mainLayer)
+        .collect();
+
+    for (auto [name, period] : {std::pair{"1h", 3600u}, std::pair{"12h", 43200u}, std::pair{"24h", 86400u}, std::pair{"7d", 604800u}}) {
+        Build<ButtonSprite>::create(name, "bigFont.fnt", "GJ_button_01.png", 0.6f)
+            .scale(0.8f)
+            .intoMenuItem([this, period](auto) {
+                this->request(period);
+            })
+            .parent(periodLayout);
+    }
+
+    periodLayout->updateLayout();
+
+    NetworkManager::get().addListener<AdminPlayerHistoryPacket>(this, [this](auto packet) {
+        this->showHistory(*packet);
+    });
+
+    this->request(3600);
+
+    return true;
+}
+
+void AdminPlayerHistoryPopup::request(uint32_t period) {
+    uint32_t roomId = util::format::parse<uint32_t>(roomInput->getString()).value_or(0);
+    LevelId levelId = util::format::parse<LevelId>(levelInput->getString()).value_or(0);
+
+    NetworkManager::get().send(AdminGetPlayerHistoryPacket::create(roomId, levelId, period));
+}
+
+void AdminPlayerHistoryPopup::showHistory(const AdminPlayerHistoryPacket& packet) {
+    graph->removeAllChildren();
+
+    if (packet.points.empty()) {
+        summaryLabel->setString("No samples yet");
+        return;
+    }
+
+    uint32_t peak = 0;
+    uint64_t sum = 0;
+    for (const auto& point : packet.points) {
+        peak = std::max(peak, point.count);
+        sum += point.count;
+    }
+
+    // one bar per point, scaled so that the peak fills the whole graph
+    float barWidth = GRAPH_WIDTH / packet.points.size();
+    for (size_t i = 0; i < packet.points.size(); i++) {
+        float height = peak == 0 ? 0.f : GRAPH_HEIGHT * packet.points[i].count / peak;
+        if (height <= 0.f) continue;
+
+        Build<CCLayerColor>::create(ccc4(80, 200, 120, 220), std::max(barWidth - 0.5f, 0.5f), height)
+            .pos(barWidth * i, 0.f)
+            .parent(graph);
+    }
+
+    summaryLabel->setString(fmt::format(
+        "Peak: {}, average: {}, now: {} (every {})",
+        peak,
+        sum / packet.points.size(),
+        packet.points.back().count,
+        util::format::duration(std::chrono::seconds(packet.interval))
+    ).c_str());
+}
+
+AdminPlayerHistoryPopup* AdminPlayerHistoryPopup::create() {
+    auto* ret = new AdminPlayerHistoryPopup;
+    if (ret->init(POPUP_WIDTH, POPUP_HEIGHT)) {
+        ret->autorelease();
+        return ret;
+    }
+
+    delete ret;
+    return nullptr;
+}
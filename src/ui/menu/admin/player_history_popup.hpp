@@ -0,0 +1,23 @@
+#pragma once
+#include <defs/all.hpp>
+
+#include <data/packets/server/admin.hpp>
+
+class AdminPlayerHistoryPopup : public geode::Popup<> {
+public:
+    static constexpr float POPUP_WIDTH = 360.f;
+    static constexpr float POPUP_HEIGHT = 240.f;
+    static constexpr float GRAPH_WIDTH = 300.f;
+    static constexpr float GRAPH_HEIGHT = 100.f;
+
+    static AdminPlayerHistoryPopup* create();
+
+private:
+    geode::InputNode *roomInput, *levelInput;
+    cocos2d::CCLayerColor* graph;
+    cocos2d::CCLabelBMFont* summaryLabel;
+
+    bool setup() override;
+    void request(uint32_t period);
+    void showHistory(const AdminPlayerHistoryPacket& packet);
+};
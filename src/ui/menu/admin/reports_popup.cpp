@@ -0,0 +1,155 @@
+#include "reports_popup.hpp"
+
+#include <data/packets/client/admin.hpp>
+#include <net/manager.hpp>
+#include <util/ui.hpp>
+
+using namespace geode::prelude;
+
+// the name if the server knew it, else the account ID
+static std::string displayName(const std::string& name, int accountId) {
+    return name.empty() ? fmt::format("account {}", accountId) : name;
+}
+
+bool AdminReportsPopup::setup() {
+    this->setTitle("Reports");
+
+    auto* listLayer = Build<GJCommentListLayer>::create(nullptr, "", util::ui::BG_COLOR_BROWN, LIST_WIDTH, LIST_HEIGHT, false)
+        .pos((m_mainLayer->getScaledContentSize().width - LIST_WIDTH) / 2, 25.f)
+        .parent(m_mainLayer)
+        .collect();
+
+    Build(ScrollLayer::create({LIST_WIDTH, LIST_HEIGHT}))
+        .parent(listLayer)
+        .store(scrollLayer);
+
+    scrollLayer->m_contentLayer->setLayout(
+        ColumnLayout::create()
+            ->setGap(0.f)
+            ->setAxisReverse(true)
+            ->setAxisAlignment(AxisAlignment::End)
+            ->setAutoScale(false)
+    );
+
+    Build<CCSprite>::createSpriteName("GJ_updateBtn_001.png")
+        .scale(0.9f)
+        .intoMenuItem([this](auto) {
+            this->refresh();
+        })
+        .pos(m_size.width / 2.f - 3.f, -m_size.height / 2.f + 3.f)
+        .intoNewParent(CCMenu::create())
+        .parent(m_mainLayer);
+
+    NetworkManager::get().addListener<AdminReportsPacket>(this, [this](auto packet) {
+        this->showReports(packet->reports);
+    });
+
+    this->refresh();
+
+    return true;
+}
+
+void AdminReportsPopup::refresh() {
+    NetworkManager::get().send(AdminGetReportsPacket::create());
+}
+
+void AdminReportsPopup::update(int64_t id, ReportState state) {
+    // the server responds with the updated list, or an error if someone else got to the report first
+    NetworkManager::get().send(AdminUpdateReportPacket::create(id, state));
+}
+
+void AdminReportsPopup::showReports(const std::vector<AdminReport>& reports) {
+    scrollLayer->m_contentLayer->removeAllChildren();
+
+    int ownId = GJAccountManager::get()->m_accountID;
+
+    for (const auto& entry : reports) {
+        const auto& report = entry.report;
+
+        auto* row = Build<CCMenu>::create()
+            .contentSize(LIST_WIDTH, ROW_HEIGHT)
+            .parent(scrollLayer->m_contentLayer)
+            .collect();
+
+        Build<CCLabelBMFont>::create(fmt::format("#{} - {}", report.id, displayName(entry.reportedName, report.reportedId)).c_str(), "bigFont.fnt")
+            .limitLabelWidth(LIST_WIDTH - 110.f, 0.4f, 0.1f)
+            .anchorPoint(0.f, 0.5f)
+            .pos(8.f, ROW_HEIGHT * 0.78f)
+            .parent(row);
+
+        Build<CCLabelBMFont>::create(report.reason.c_str(), "chatFont.fnt")
+            .limitLabelWidth(LIST_WIDTH - 110.f, 0.5f, 0.1f)
+            .anchorPoint(0.f, 0.5f)
+            .pos(8.f, ROW_HEIGHT * 0.48f)
+            .parent(row);
+
+        std::string status = fmt::format("reported by {}", displayName(entry.reporterName, report.reporterId));
+        if (report.state == ReportState::Claimed) {
+            status += fmt::format(", claimed by {}", report.handledBy == ownId ? "you" : displayName(entry.handledByName, report.handledBy));
+        }
+
+        Build<CCLabelBMFont>::create(status.c_str(), "chatFont.fnt")
+            .limitLabelWidth(LIST_WIDTH - 110.f, 0.4f, 0.1f)
+            .color(200, 200, 200)
+            .anchorPoint(0.f, 0.5f)
+            .pos(8.f, ROW_HEIGHT * 0.2f)
+            .parent(row);
+
+        if (report.state == ReportState::Open) {
+            Build<ButtonSprite>::create("Claim", "bigFont.fnt", "GJ_button_01.png", 0.5f)
+                .scale(0.7f)
+                .intoMenuItem([this, id = report.id](auto) {
+                    this->update(id, ReportState::Claimed);
+                })
+                .pos(LIST_WIDTH - 40.f, ROW_HEIGHT / 2.f)
+                .parent(row);
+        } else if (report.handledBy == ownId) {
+            // only the moderator who claimed a report can close it or give it back
+            Build<CCSprite>::createSpriteName("GJ_completesIcon_001.png")
+                .scale(0.7f)
+                .intoMenuItem([this, id = report.id](auto) {
+                    this->update(id, ReportState::Resolved);
+                })
+                .pos(LIST_WIDTH - 80.f, ROW_HEIGHT / 2.f)
+                .parent(row);
+
+            Build<CCSprite>::createSpriteName("GJ_deleteIcon_001.png")
+                .scale(0.7f)
+                .intoMenuItem([this, id = report.id](auto) {
+                    this->update(id, ReportState::Rejected);
+                })
+                .pos(LIST_WIDTH - 50.f, ROW_HEIGHT / 2.f)
+                .parent(row);
+
+            Build<CCSprite>::createSpriteName("GJ_undoBtn_001.png")
+                .scale(0.4f)
+                .intoMenuItem([this, id = report.id](auto) {
+                    this->update(id, ReportState::Open);
+                })
+                .pos(LIST_WIDTH - 20.f, ROW_HEIGHT / 2.f)
+                .parent(row);
+        }
+    }
+
+    if (reports.empty()) {
+        Build<CCLabelBMFont>::create("No open reports", "bigFont.fnt")
+            .scale(0.4f)
+            .parent(scrollLayer->m_contentLayer);
+    }
+
+    scrollLayer->m_contentLayer->setContentHeight(std::max(LIST_HEIGHT, ROW_HEIGHT * reports.size()));
+    scrollLayer->m_contentLayer->updateLayout();
+
+    util::ui::scrollToTop(scrollLayer);
+}
+
+AdminReportsPopup* AdminReportsPopup::create() {
+    auto* ret = new AdminReportsPopup;
+    if (ret->init(POPUP_WIDTH, POPUP_HEIGHT)) {
+        ret->autorelease();
+        return ret;
+    }
+
+    delete ret;
+    return nullptr;
+}
@@ -0,0 +1,23 @@
+#pragma once
+#include <defs/all.hpp>
+
+#include <data/packets/server/admin.hpp>
+
+class AdminReportsPopup : public geode::Popup<> {
+public:
+    static constexpr float POPUP_WIDTH = 380.f;
+    static constexpr float POPUP_HEIGHT = 240.f;
+    static constexpr float LIST_WIDTH = 340.f;
+    static constexpr float LIST_HEIGHT = 170.f;
+    static constexpr float ROW_HEIGHT = 40.f;
+
+    static AdminReportsPopup* create();
+
+private:
+    geode::ScrollLayer* scrollLayer;
+
+    bool setup() override;
+    void refresh();
+    void update(int64_t id, ReportState state);
+    void showReports(const std::vector<AdminReport>& reports);
+};
@@ -0,0 +1,119 @@
+#include "scheduled_notices_popup.hpp"
+
+#include <data/packets/client/admin.hpp>
+#include <net/manager.hpp>
+#include <util/ui.hpp>
+#include <util/format.hpp>
+
+using namespace geode::prelude;
+
+bool AdminScheduledNoticesPopup::setup() {
+    this->setTitle("Scheduled notices");
+
+    auto* listLayer = Build<GJCommentListLayer>::create(nullptr, "", util::ui::BG_COLOR_BROWN, LIST_WIDTH, LIST_HEIGHT, false)
+        .pos((m_mainLayer->getScaledContentSize().width - LIST_WIDTH) / 2, 25.f)
+        .parent(m_mainLayer)
+        .collect();
+
+    Build(ScrollLayer::create({LIST_WIDTH, LIST_HEIGHT}))
+        .parent(listLayer)
+        .store(scrollLayer);
+
+    scrollLayer->m_contentLayer->setLayout(
+        ColumnLayout::create()
+            ->setGap(0.f)
+            ->setAxisReverse(true)
+            ->setAxisAlignment(AxisAlignment::End)
+            ->setAutoScale(false)
+    );
+
+    Build<CCSprite>::createSpriteName("GJ_updateBtn_001.png")
+        .scale(0.9f)
+        .intoMenuItem([this](auto) {
+            this->refresh();
+        })
+        .pos(m_size.width / 2.f - 3.f, -m_size.height / 2.f + 3.f)
+        .intoNewParent(CCMenu::create())
+        .parent(m_mainLayer);
+
+    NetworkManager::get().addListener<AdminScheduledNoticesPacket>(this, [this](auto packet) {
+        this->showNotices(packet->notices);
+    });
+
+    this->refresh();
+
+    return true;
+}
+
+void AdminScheduledNoticesPopup::refresh() {
+    NetworkManager::get().send(AdminGetScheduledNoticesPacket::create());
+}
+
+void AdminScheduledNoticesPopup::showNotices(const std::vector<ScheduledNotice>& notices) {
+    scrollLayer->m_contentLayer->removeAllChildren();
+
+    auto now = util::time::asSeconds(util::time::systemNow().time_since_epoch());
+
+    for (const auto& notice : notices) {
+        auto* row = Build<CCMenu>::create()
+            .contentSize(LIST_WIDTH, ROW_HEIGHT)
+            .parent(scrollLayer->m_contentLayer)
+            .collect();
+
+        std::string target = notice.everyone ? "everyone"
+            : notice.levelId != 0 ? fmt::format("level {} (room {})", notice.levelId, notice.roomId)
+            : fmt::format("room {}", notice.roomId);
+
+        Build<CCLabelBMFont>::create(
+            fmt::format(
+                "#{} to {} in {} by {}",
+                notice.id,
+                target,
+                util::format::duration(util::time::seconds(std::max<int64_t>(notice.sendAt - now, 0))),
+                notice.author
+            ).c_str(),
+            "bigFont.fnt"
+        )
+            .limitLabelWidth(LIST_WIDTH - 50.f, 0.35f, 0.1f)
+            .anchorPoint(0.f, 0.5f)
+            .pos(8.f, ROW_HEIGHT * 0.7f)
+            .parent(row);
+
+        Build<CCLabelBMFont>::create(notice.message.c_str(), "chatFont.fnt")
+            .limitLabelWidth(LIST_WIDTH - 50.f, 0.6f, 0.1f)
+            .anchorPoint(0.f, 0.5f)
+            .pos(8.f, ROW_HEIGHT * 0.3f)
+            .parent(row);
+
+        Build<CCSprite>::createSpriteName("GJ_deleteIcon_001.png")
+            .scale(0.7f)
+            .intoMenuItem([this, id = notice.id](auto) {
+                NetworkManager::get().send(AdminCancelScheduledNoticePacket::create(id));
+                this->refresh();
+            })
+            .pos(LIST_WIDTH - 20.f, ROW_HEIGHT / 2.f)
+            .parent(row);
+    }
+
+    if (notices.empty()) {
+        Build<CCLabelBMFont>::create("No scheduled notices", "bigFont.fnt")
+            .scale(0.4f)
+            .parent(scrollLayer->m_contentLayer);
+    }
+
+    scrollLayer->m_contentLayer->setContentHeight(std::max(LIST_HEIGHT, ROW_HEIGHT * notices.size()));
+    scrollLayer->m_contentLayer->updateLayout();
+
+    util::ui::scrollToTop(scrollLayer);
+}
+
+AdminScheduledNoticesPopup* AdminScheduledNoticesPopup::create() {
+    auto* ret = new AdminScheduledNoticesPopup;
+    if (ret->init(POPUP_WIDTH, POPUP_HEIGHT)) {
+        ret->autorelease();
+        return ret;
+    }
+
+    delete ret;
+    return nullptr;
+}
@@ -0,0 +1,22 @@
+#pragma once
+#include <defs/all.hpp>
+
+#include <data/packets/server/admin.hpp>
+
+class AdminScheduledNoticesPopup : public geode::Popup<> {
+public:
+    static constexpr float POPUP_WIDTH = 380.f;
+    static constexpr float POPUP_HEIGHT = 240.f;
+    static constexpr float LIST_WIDTH = 340.f;
+    static constexpr float LIST_HEIGHT = 170.f;
+    static constexpr float ROW_HEIGHT = 30.f;
+
+    static AdminScheduledNoticesPopup* create();
+
+private:
+    geode::ScrollLayer* scrollLayer;
+
+    bool setup() override;
+    void refresh();
+    void showNotices(const std::vector<ScheduledNotice>& notices);
+};
@@ -1,5 +1,7 @@
 #include "send_notice_popup.hpp"
 
+#include "scheduled_notices_popup.hpp"
+#include <managers/error_queues.hpp>
 #include <net/manager.hpp>
 #include <util/ui.hpp>
 #include <util/misc.hpp>
@@ -46,7 +48,7 @@ bool AdminSendNoticePopup::setup(const std::string_view message) {
         .parent(rootLayout)
         .collect();
 
-    Build<InputNode>::create(m_size.width * 0.35, "room ID", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 6)
+    Build<InputNode>::create(m_size.width * 0.35, "room ID", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 9)
         .parent(rlLayout)
         .store(roomInput);
 
@@ -74,9 +76,29 @@ bool AdminSendNoticePopup::setup(const std::string_view message) {
         .layout(RowLayout::create())
         .parent(rootLayout);
 
+    // send later instead of right away, does not apply to notices for a single person
+    auto* delayLayout = Build<CCMenu>::create()
+        .layout(RowLayout::create()->setGap(5.f))
+        .pos(0.f, 0.f)
+        .parent(rootLayout)
+        .collect();
+
+    Build<InputNode>::create(m_size.width * 0.45f, "delay (minutes)", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 5)
+        .parent(delayLayout)
+        .store(delayInput);
+
+    Build<ButtonSprite>::create("Scheduled", "bigFont.fnt", "GJ_button_04.png", 0.5f)
+        .intoMenuItem([](auto) {
+            AdminScheduledNoticesPopup::create()->show();
+        })
+        .parent(delayLayout);
+
+    delayLayout->updateLayout();
+
     rootLayout->setContentSize(CCPoint{
         m_size.width * 0.8f,
-        10.f + userLayout->getScaledContentSize().height + rlLayout->getScaledContentSize().height + everyoneBtn->getScaledContentSize().height
+        15.f + userLayout->getScaledContentSize().height + rlLayout->getScaledContentSize().height + everyoneBtn->getScaledContentSize().height
+            + delayLayout->getScaledContentSize().height
     });
 
     rootLayout->updateLayout();
@@ -93,8 +115,20 @@ void AdminSendNoticePopup::commonSend(AdminSendNoticeType type) {
         roomId = util::format::parse<uint32_t>(roomInput->getString()).value_or(0);
     }
 
-    auto packet = AdminSendNoticePacket::create(type, roomId, levelId, userInput->getString(), message);
-    NetworkManager::get().send(packet);
+    uint32_t delay = util::format::parse<uint32_t>(delayInput->getString()).value_or(0);
+    if (delay == 0) {
+        auto packet = AdminSendNoticePacket::create(type, roomId, levelId, userInput->getString(), message);
+        NetworkManager::get().send(packet);
+        return;
+    }
+
+    if (type == AdminSendNoticeType::Person) {
+        ErrorQueues::get().warn("Notices to a single person can't be scheduled");
+        return;
+    }
+
+    auto sendAt = util::time::asSeconds(util::time::systemNow().time_since_epoch()) + delay * 60;
+    NetworkManager::get().send(AdminScheduleNoticePacket::create(type, roomId, levelId, message, sendAt));
 }
 
 AdminSendNoticePopup* AdminSendNoticePopup::create(const std::string_view message) {
@@ -0,0 +1,66 @@
+#include "slow_mode_popup.hpp"
+
+#include <data/packets/client/admin.hpp>
+#include <net/manager.hpp>
+#include <util/ui.hpp>
+#include <util/misc.hpp>
+#include <util/format.hpp>
+
+using namespace geode::prelude;
+
+bool AdminSlowModePopup::setup() {
+    this->setTitle("Slow mode");
+
+    auto sizes = util::ui::getPopupLayout(m_size);
+
+    // level ID takes priority, room ID 0 is the global room
+    auto* inputLayout = Build<CCMenu>::create()
+        .layout(RowLayout::create()->setGap(5.f))
+        .pos(sizes.center)
+        .contentSize(m_size.width * 0.9f, 30.f)
+        .parent(m_mainLayer)
+        .collect();
+
+    Build<InputNode>::create(m_size.width * 0.3f, "room ID", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 9)
+        .parent(inputLayout)
+        .store(roomInput);
+
+    Build<InputNode>::create(m_size.width * 0.3f, "level ID", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 11)
+        .parent(inputLayout)
+        .store(levelInput);
+
+    Build<InputNode>::create(m_size.width * 0.2f, "seconds", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 4)
+        .parent(inputLayout)
+        .store(secondsInput);
+
+    inputLayout->updateLayout();
+
+    Build<ButtonSprite>::create("Set", "bigFont.fnt", "GJ_button_01.png", 0.6f)
+        .intoMenuItem([this](auto) {
+            this->send();
+        })
+        .intoNewParent(CCMenu::create())
+        .pos(sizes.centerBottom + CCPoint{0.f, 25.f})
+        .parent(m_mainLayer);
+
+    return true;
+}
+
+void AdminSlowModePopup::send() {
+    uint32_t roomId = util::format::parse<uint32_t>(roomInput->getString()).value_or(0);
+    LevelId levelId = util::format::parse<LevelId>(levelInput->getString()).value_or(0);
+    uint32_t seconds = util::format::parse<uint32_t>(secondsInput->getString()).value_or(0);
+
+    NetworkManager::get().send(AdminSetSlowModePacket::create(roomId, levelId, seconds));
+}
+
+AdminSlowModePopup* AdminSlowModePopup::create() {
+    auto* ret = new AdminSlowModePopup;
+    if (ret->init(POPUP_WIDTH, POPUP_HEIGHT)) {
+        ret->autorelease();
+        return ret;
+    }
+
+    delete ret;
+    return nullptr;
+}
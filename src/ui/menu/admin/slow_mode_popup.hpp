@@ -0,0 +1,16 @@
+#pragma once
+#include <defs/all.hpp>
+
+class AdminSlowModePopup : public geode::Popup<> {
+public:
+    static constexpr float POPUP_WIDTH = 320.f;
+    static constexpr float POPUP_HEIGHT = 130.f;
+
+    static AdminSlowModePopup* create();
+
+private:
+    geode::InputNode *roomInput, *levelInput, *secondsInput;
+
+    bool setup() override;
+    void send();
+};
@@ -0,0 +1,103 @@
+#include "traffic_stats_popup.hpp"
+
+#include <data/packets/client/admin.hpp>
+#include <net/manager.hpp>
+#include <util/ui.hpp>
+#include <util/format.hpp>
+#include <util/time.hpp>
+
+using namespace geode::prelude;
+
+bool AdminTrafficStatsPopup::setup() {
+    this->setTitle("Traffic stats");
+
+    auto sizes = util::ui::getPopupLayout(m_size);
+
+    Build<CCLabelBMFont>::create("Loading..", "chatFont.fnt")
+        .scale(0.6f)
+        .anchorPoint(0.5f, 1.f)
+        .pos(sizes.centerTop + CCPoint{-m_size.width / 4.f, -35.f})
+        .parent(m_mainLayer)
+        .store(roomsLabel);
+
+    Build<CCLabelBMFont>::create("", "chatFont.fnt")
+        .scale(0.6f)
+        .anchorPoint(0.5f, 1.f)
+        .pos(sizes.centerTop + CCPoint{m_size.width / 4.f, -35.f})
+        .parent(m_mainLayer)
+        .store(levelsLabel);
+
+    Build<CCLabelBMFont>::create("", "bigFont.fnt")
+        .scale(0.3f)
+        .pos(sizes.centerBottom + CCPoint{0.f, 45.f})
+        .parent(m_mainLayer)
+        .store(sinceLabel);
+
+    auto* buttonLayout = Build<CCMenu>::create()
+        .layout(RowLayout::create()->setGap(5.f))
+        .pos(sizes.centerBottom + CCPoint{0.f, 20.f})
+        .contentSize(m_size.width * 0.9f, 30.f)
+        .parent(m_mainLayer)
+        .collect();
+
+    Build<ButtonSprite>::create("Refresh", "bigFont.fnt", "GJ_button_01.png", 0.6f)
+        .scale(0.8f)
+        .intoMenuItem([this](auto) {
+            this->request(false);
+        })
+        .parent(buttonLayout);
+
+    Build<ButtonSprite>::create("Reset", "bigFont.fnt", "GJ_button_06.png", 0.6f)
+        .scale(0.8f)
+        .intoMenuItem([this](auto) {
+            this->request(true);
+        })
+        .parent(buttonLayout);
+
+    buttonLayout->updateLayout();
+
+    NetworkManager::get().addListener<AdminTrafficStatsPacket>(this, [this](auto packet) {
+        this->showStats(*packet);
+    });
+
+    this->request(false);
+
+    return true;
+}
+
+void AdminTrafficStatsPopup::request(bool reset) {
+    NetworkManager::get().send(AdminGetTrafficStatsPacket::create(ENTRY_COUNT, reset));
+}
+
+void AdminTrafficStatsPopup::showStats(const AdminTrafficStatsPacket& packet) {
+    std::string rooms = "Top rooms";
+    for (const auto& entry : packet.rooms) {
+        auto name = entry.roomId == 0 ? std::string("Global room") : fmt::format("Room {}", entry.roomId);
+        rooms += fmt::format("\n{}: {}", name, util::format::formatBytes(entry.bytes));
+    }
+
+    std::string levels = "Top levels";
+    for (const auto& entry : packet.levels) {
+        levels += fmt::format("\n{}: {}", entry.levelId, util::format::formatBytes(entry.bytes));
+    }
+
+    roomsLabel->setString(rooms.c_str());
+    levelsLabel->setString(levels.c_str());
+
+    auto now = util::time::asSeconds(util::time::systemNow().time_since_epoch());
+    sinceLabel->setString(fmt::format(
+        "Counted over the last {}",
+        util::format::duration(std::chrono::seconds(std::max<int64_t>(now - packet.since, 0)))
+    ).c_str());
+}
+
+AdminTrafficStatsPopup* AdminTrafficStatsPopup::create() {
+    auto* ret = new AdminTrafficStatsPopup;
+    if (ret->init(POPUP_WIDTH, POPUP_HEIGHT)) {
+        ret->autorelease();
+        return ret;
+    }
+
+    delete ret;
+    return nullptr;
+}
@@ -0,0 +1,20 @@
+#pragma once
+#include <defs/all.hpp>
+
+#include <data/packets/server/admin.hpp>
+
+class AdminTrafficStatsPopup : public geode::Popup<> {
+public:
+    static constexpr float POPUP_WIDTH = 360.f;
+    static constexpr float POPUP_HEIGHT = 240.f;
+    static constexpr uint8_t ENTRY_COUNT = 8;
+
+    static AdminTrafficStatsPopup* create();
+
+private:
+    cocos2d::CCLabelBMFont *roomsLabel, *levelsLabel, *sinceLabel;
+
+    bool setup() override;
+    void request(bool reset);
+    void showStats(const AdminTrafficStatsPacket& packet);
+};
@@ -17,9 +17,11 @@
 
 using namespace geode::prelude;
 
-bool AdminUserPopup::setup(const UserEntry& userEntry, const std::optional<PlayerRoomPreviewAccountData>& accountData) {
+bool AdminUserPopup::setup(const UserEntry& userEntry, const std::optional<PlayerRoomPreviewAccountData>& accountData, const std::optional<ConnectionInfo>& connection, const std::vector<LevelVisit>& levelHistory) {
     this->userEntry = userEntry;
     this->accountData = accountData;
+    this->connection = connection;
+    this->levelHistory = levelHistory;
 
     // if we don't have their account data, request from gd servers
     if (!accountData.has_value() || accountData->name.empty()) {
@@ -112,8 +114,29 @@ void AdminUserPopup::onProfileLoaded() {
     // role modify button
     this->recreateRoleModifyButton();
 
+    // level history button, only known if they are online or left recently
+    if (!levelHistory.empty()) {
+        Build<CCSprite>::createSpriteName("GJ_infoIcon_001.png")
+            .scale(0.6f)
+            .intoMenuItem([this](auto) {
+                this->showLevelHistory();
+            })
+            .parent(nameLayout);
+    }
+
     nameLayout->updateLayout();
 
+    // connection quality, only known if they are online
+    if (connection) {
+        auto text = fmt::format("Packet loss: {}%{}", connection->packetLoss, connection->lossy ? " (unstable)" : "");
+
+        Build<CCLabelBMFont>::create(text.c_str(), "bigFont.fnt")
+            .scale(0.3f)
+            .color(connection->lossy ? ccColor3B{255, 100, 100} : ccColor3B{255, 255, 255})
+            .pos(sizes.centerTop - CCPoint{0.f, 38.f})
+            .parent(m_mainLayer);
+    }
+
     // root column layout for all the next stuff
     auto* rootLayout = Build<CCNode>::create()
         .pos(sizes.center)
@@ -419,9 +442,26 @@ void AdminUserPopup::onClose(CCObject* sender) {
     Popup::onClose(sender);
 }
 
-AdminUserPopup* AdminUserPopup::create(const UserEntry& userEntry, const std::optional<PlayerRoomPreviewAccountData>& accountData) {
+void AdminUserPopup::showLevelHistory() {
+    using namespace std::chrono;
+
+    // newest first, as that's usually what you are looking for
+    std::string text;
+    for (auto it = levelHistory.rbegin(); it != levelHistory.rend(); it++) {
+        text += fmt::format(
+            "<cy>{}</c>: {} - {}\n",
+            it->levelId,
+            util::format::formatDateTime(sys_seconds(seconds(it->joinedAt)), false),
+            it->leftAt == 0 ? "now" : util::format::formatDateTime(sys_seconds(seconds(it->leftAt)), false)
+        );
+    }
+
+    FLAlertLayer::create(nullptr, "Level history", text, "Ok", nullptr, 380.f)->show();
+}
+
+AdminUserPopup* AdminUserPopup::create(const UserEntry& userEntry, const std::optional<PlayerRoomPreviewAccountData>& accountData, const std::optional<ConnectionInfo>& connection, const std::vector<LevelVisit>& levelHistory) {
     auto ret = new AdminUserPopup;
-    if (ret->init(POPUP_WIDTH, POPUP_HEIGHT, userEntry, accountData)) {
+    if (ret->init(POPUP_WIDTH, POPUP_HEIGHT, userEntry, accountData, connection, levelHistory)) {
         ret->autorelease();
         return ret;
     }
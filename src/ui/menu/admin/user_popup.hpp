@@ -6,7 +6,7 @@
 #include <data/types/admin.hpp>
 #include <data/types/gd.hpp>
 
-class AdminUserPopup : public geode::Popup<const UserEntry&, const std::optional<PlayerRoomPreviewAccountData>&>, public UserInfoDelegate {
+class AdminUserPopup : public geode::Popup<const UserEntry&, const std::optional<PlayerRoomPreviewAccountData>&, const std::optional<ConnectionInfo>&, const std::vector<LevelVisit>&>, public UserInfoDelegate {
 public:
     static constexpr float POPUP_WIDTH = 300.f;
     static constexpr float POPUP_HEIGHT = 260.f;
@@ -14,11 +14,13 @@ public:
     static constexpr int TAG_MUTE = 42;
     static constexpr int TAG_WHITELIST = 43;
 
-    static AdminUserPopup* create(const UserEntry& userEntry, const std::optional<PlayerRoomPreviewAccountData>& accountData);
+    static AdminUserPopup* create(const UserEntry& userEntry, const std::optional<PlayerRoomPreviewAccountData>& accountData, const std::optional<ConnectionInfo>& connection, const std::vector<LevelVisit>& levelHistory);
 
 private:
     UserEntry userEntry;
     std::optional<PlayerRoomPreviewAccountData> accountData;
+    std::optional<ConnectionInfo> connection;
+    std::vector<LevelVisit> levelHistory;
     GJUserScore* userScore = nullptr;
     Ref<LoadingCircle> loadingCircle = nullptr;
     ColorChannelSprite* nameColorSprite = nullptr;
@@ -28,12 +30,13 @@ private:
     cocos2d::CCMenu* nameLayout;
     Ref<CCMenuItemSpriteExtra> roleModifyButton;
 
-    bool setup(const UserEntry& userEntry, const std::optional<PlayerRoomPreviewAccountData>& accountData) override;
+    bool setup(const UserEntry& userEntry, const std::optional<PlayerRoomPreviewAccountData>& accountData, const std::optional<ConnectionInfo>& connection, const std::vector<LevelVisit>& levelHistory) override;
     void onProfileLoaded();
     void onColorSelected(cocos2d::ccColor3B);
     void onViolationChanged(cocos2d::CCObject* sender);
     void onViolationDurationChanged(cocos2d::CCObject* sender);
     void recreateRoleModifyButton();
+    void showLevelHistory();
 
     cocos2d::ccColor3B getCurrentNameColor();
 
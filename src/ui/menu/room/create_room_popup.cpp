@@ -113,7 +113,7 @@ bool CreateRoomPopup::setup(RoomLayer* parent) {
                     uint32_t playerCount = util::format::parse<uint32_t>(playerLimitInput->getString()).value_or(0);
 
                     NetworkManager::get().send(CreateRoomPacket::create(roomName, passwordInput->getString(), RoomSettings {
-                        settingFlags, playerCount
+                        settingFlags, playerCount, RoomChatFilter::ServerDefault, 0, 0, 0
                     }));
 
                     parent->reloadPlayerList(false);
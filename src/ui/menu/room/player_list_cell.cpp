@@ -7,6 +7,7 @@
 #include <hooks/gjgamelevel.hpp>
 #include <managers/admin.hpp>
 #include <managers/friend_list.hpp>
+#include <managers/room.hpp>
 #include <net/manager.hpp>
 #include <util/ui.hpp>
 #include <util/cocos.hpp>
@@ -84,6 +85,30 @@ bool PlayerListCell::init(const PlayerRoomPreviewAccountData& data, bool forInvi
             .parent(badgeWrapper);
     }
 
+    if (data.isAfk) {
+        Build<CCLabelBMFont>::create("AFK", "goldFont.fnt")
+            .scale(0.45f)
+            .opacity(180)
+            .id("afk-label"_spr)
+            .parent(badgeWrapper);
+    }
+
+    if (data.spectators > 0) {
+        Build<CCLabelBMFont>::create(fmt::format("{} watching", data.spectators).c_str(), "goldFont.fnt")
+            .scale(0.45f)
+            .opacity(180)
+            .id("spectators-label"_spr)
+            .parent(badgeWrapper);
+    }
+
+    if (auto* tag = RoomManager::get().getMemberTag(data.accountId)) {
+        Build<CCLabelBMFont>::create(tag->name.c_str(), "bigFont.fnt")
+            .scale(0.35f)
+            .color(tag->color)
+            .id("room-tag-label"_spr)
+            .parent(badgeWrapper);
+    }
+
     badgeWrapper->updateLayout();
 
     label->setPositionY(CELL_HEIGHT / 2 - 5.15f);
@@ -101,6 +126,7 @@ bool PlayerListCell::init(const PlayerRoomPreviewAccountData& data, bool forInvi
         this->createInviteButton();
     } else {
         this->createJoinButton();
+        this->createManageButtons();
     }
 
     if (AdminManager::get().authorized()) {
@@ -194,6 +220,79 @@ void PlayerListCell::createAdminButton() {
     menu->updateLayout();
 }
 
+void PlayerListCell::createManageButtons() {
+    auto& rm = RoomManager::get();
+    if (!rm.canManage()) return;
+
+    // nobody can do anything to the owner, and moderators can't do anything to other moderators
+    int accountId = data.accountId;
+    if (accountId == GJAccountManager::get()->m_accountID || accountId == rm.getInfo().owner.accountId) return;
+
+    bool isModerator = rm.isModerator(accountId);
+    if (!rm.isOwner() && isModerator) return;
+
+    if (rm.isOwner()) {
+        Build<CCSprite>::createSpriteName("GJ_sModIcon_001.png")
+            .opacity(isModerator ? 255 : 90)
+            .intoMenuItem([accountId, isModerator, name = data.name](auto) {
+                geode::createQuickPopup(
+                    isModerator ? "Remove moderator" : "Add moderator",
+                    isModerator
+                        ? fmt::format("Are you sure you want to take away the moderator role from <cy>{}</c>?", name)
+                        : fmt::format("Are you sure you want to make <cy>{}</c> a moderator of the room? They will be able to kick players and change their tags.", name),
+                    "Cancel", "Confirm",
+                    [accountId, isModerator](auto, bool confirmed) {
+                        if (!confirmed) return;
+
+                        NetworkManager::get().send(SetRoomModeratorPacket::create(accountId, !isModerator));
+                    }
+                );
+            })
+            .scaleMult(1.25f)
+            .id("moderator-button"_spr)
+            .parent(menu);
+    }
+
+    // each click gives the player the next tag, after the last one the tag is taken away
+    const auto& tags = rm.getTags();
+    if (!tags.empty()) {
+        auto* tag = rm.getMemberTag(accountId);
+        size_t next = tag ? (tag - tags.data()) + 1 : 0;
+
+        Build<ButtonSprite>::create("Tag", "bigFont.fnt", "GJ_button_04.png", 0.5f)
+            .scale(0.6f)
+            .intoMenuItem([accountId, next, count = tags.size()](auto) {
+                std::optional<uint8_t> nextTag;
+                if (next < count) nextTag = static_cast<uint8_t>(next);
+
+                NetworkManager::get().send(SetRoomMemberTagPacket::create(accountId, nextTag));
+            })
+            .scaleMult(1.1f)
+            .id("tag-button"_spr)
+            .parent(menu);
+    }
+
+    Build<CCSprite>::createSpriteName("accountBtn_removeFriend_001.png")
+        .scale(0.5f)
+        .intoMenuItem([accountId, name = data.name](auto) {
+            geode::createQuickPopup(
+                "Kick player",
+                fmt::format("Are you sure you want to kick <cy>{}</c> out of the room?", name),
+                "Cancel", "Kick",
+                [accountId](auto, bool confirmed) {
+                    if (!confirmed) return;
+
+                    NetworkManager::get().send(RoomKickPlayerPacket::create(accountId));
+                }
+            );
+        })
+        .scaleMult(1.1f)
+        .id("kick-button"_spr)
+        .parent(menu);
+
+    menu->updateLayout();
+}
+
 void PlayerListCell::onOpenProfile(cocos2d::CCObject*) {
     GameLevelManager::sharedState()->storeUserName(data.userId, data.accountId, data.name);
     ProfilePage::create(data.accountId, false)->show();
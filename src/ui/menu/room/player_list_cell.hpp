@@ -17,6 +17,7 @@ protected:
     void createInviteButton();
     void createJoinButton();
     void createAdminButton();
+    void createManageButtons();
 
     void sendInvite();
     void enableInvites();
@@ -22,7 +22,7 @@ bool RoomJoinPopup::setup() {
         .id("join-room-id-hint"_spr);
 
     // room id input node
-    Build<InputNode>::create(POPUP_WIDTH * 0.75f, "", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 7)
+    Build<InputNode>::create(POPUP_WIDTH * 0.75f, "", "chatFont.fnt", std::string(util::misc::STRING_DIGITS), 9)
         .pos(popupCenter, POPUP_HEIGHT + 25.f)
         .parent(m_mainLayer)
         .id("join-room-id"_spr)
@@ -33,7 +33,8 @@ bool RoomJoinPopup::setup() {
             std::string codestr = this->roomIdInput->getString();
             uint32_t code = util::format::parse<uint32_t>(codestr).value_or(0);
 
-            if (code < 100000 || code > 999999) {
+            // room IDs are 6 to 9 digits long, depending on the server configuration
+            if (code < 100000 || code > 999999999) {
                 Notification::create("Invalid room ID", NotificationIcon::Warning)->show();
                 return;
             }
@@ -47,7 +48,7 @@ bool RoomJoinPopup::setup() {
             NetworkManager::get().send(JoinRoomPacket::create(code, ""));
 
             nm.addListener<RoomJoinFailedPacket>(this, [this, code](std::shared_ptr<RoomJoinFailedPacket> packet) {
-                if (packet->wasProtected) {
+                if (packet->code == ErrorCode::WrongPassword) {
                     Loader::get()->queueInMainThread([this, code] {
                         RoomPasswordPopup::create(code)->show();
                         this->onClose(nullptr);
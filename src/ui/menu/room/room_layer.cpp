@@ -49,6 +49,8 @@ bool RoomLayer::init() {
         auto& rm = RoomManager::get();
         bool changed = rm.getId() != packet->info.id;
         rm.setInfo(packet->info);
+        rm.setModerators(packet->moderators);
+        rm.setTags(packet->tags);
         this->onLoaded(changed || !roomBtnMenu);
     });
 
@@ -77,7 +79,7 @@ bool RoomLayer::init() {
     });
 
     nm.addListener<RoomCreateFailedPacket>(this, [this](std::shared_ptr<RoomCreateFailedPacket> packet) {
-        ErrorQueues::get().error(fmt::format("Failed to create room: <cy>{}</c>", packet->reason));
+        ErrorQueues::get().error(fmt::format("Failed to create room: <cy>{}</c>", util::format::serverError(packet->code, packet->message)));
         this->onLoaded(true);
     });
 
@@ -86,7 +88,12 @@ bool RoomLayer::init() {
         ErrorQueues::get().success("Room configuration updated");
 
         RoomManager::get().setInfo(packet->info);
-        // idk maybe refresh or something something idk
+        RoomManager::get().setModerators(packet->moderators);
+        RoomManager::get().setTags(packet->tags);
+        // rebuild the cells, the buttons and tags in them depend on the moderators and tags
+        if (!this->isWaiting) {
+            this->onLoaded(false);
+        }
 
         this->recreateInviteButton();
     });
@@ -211,6 +218,7 @@ void RoomLayer::onChangeStatus(CCObject*) {
     bool invisible = statusButton->isOn();
     NetworkManager::get().send(UpdatePlayerStatusPacket::create(invisible));
     settings.globed.isInvisible = invisible;
+    NetworkManager::get().syncPreferences();
 }
 
 void RoomLayer::update(float) {
@@ -2,6 +2,9 @@
 
 #include "room_password_popup.hpp"
 #include "room_listing_popup.hpp"
+#include <data/packets/client/admin.hpp>
+#include <managers/admin.hpp>
+#include <managers/role.hpp>
 #include <ui/general/simple_player.hpp>
 #include <net/manager.hpp>
 #include <util/ui.hpp>
@@ -84,7 +87,25 @@ bool RoomListingCell::init(const RoomListingInfo& rli, RoomListingPopup* parent)
 
     this->addChild(roomNameLabel);
 
-    Build<ButtonSprite>::create("Join", "bigFont.fnt", "GJ_button_01.png", 0.8f)
+    // rooms that only let certain roles in say which ones
+    if (rli.settings.allowedRoles != 0) {
+        auto roles = RoleManager::get().getRoleIds(rli.settings.allowedRoles);
+
+        Build<CCLabelBMFont>::create(fmt::format("({} only)", utils::string::join(roles, ", ")).c_str(), "bigFont.fnt")
+            .pos(roomNameLabel->getPositionX() + roomNameLabel->getScaledContentSize().width + 5.f, CELL_HEIGHT - 10.f)
+            .limitLabelWidth(100.f, 0.3f, 0.1f)
+            .anchorPoint(0.f, 0.5f)
+            .color(ccColor3B{255, 200, 80})
+            .id("roles-label")
+            .parent(this);
+    }
+
+    auto* buttonMenu = Build<CCMenu>::create()
+        .pos(0.f, 0.f)
+        .parent(this)
+        .collect();
+
+    auto* joinButton = Build<ButtonSprite>::create("Join", "bigFont.fnt", "GJ_button_01.png", 0.8f)
         .scale(0.7f)
         .intoMenuItem([this, rli](auto) {
             if (rli.hasPassword) {
@@ -99,9 +120,36 @@ bool RoomListingCell::init(const RoomListingInfo& rli, RoomListingPopup* parent)
             btn->setPosition(RoomListingPopup::LIST_WIDTH - btn->getScaledContentSize().width / 2.f - 3.f, CELL_HEIGHT / 2.f);
         })
         .scaleMult(1.15f)
-        .intoNewParent(CCMenu::create())
-        .pos(0.f, 0.f)
-        .parent(this);
+        .parent(buttonMenu)
+        .collect();
+
+    // moderators can take over rooms whose owner is gone or abusing them
+    auto& adminRole = AdminManager::get().getRole();
+    if (AdminManager::get().authorized() && (adminRole.kick || adminRole.admin)) {
+        Build<ButtonSprite>::create("Take over", "bigFont.fnt", "GJ_button_02.png", 0.8f)
+            .scale(0.5f)
+            .intoMenuItem([this, rli](auto) {
+                geode::createQuickPopup(
+                    "Take over room",
+                    fmt::format("Are you sure you want to take over <cy>{}</c>? You will join the room and become its owner.", rli.name),
+                    "Cancel", "Confirm",
+                    [this, roomId = rli.id](auto, bool confirmed) {
+                        if (!confirmed) return;
+
+                        NetworkManager::get().send(AdminTakeOverRoomPacket::create(roomId));
+                        this->parent->close();
+                    }
+                );
+            })
+            .with([&](auto* btn) {
+                btn->setPosition(
+                    joinButton->getPositionX() - joinButton->getScaledContentSize().width / 2.f - btn->getScaledContentSize().width / 2.f - 5.f,
+                    CELL_HEIGHT / 2.f
+                );
+            })
+            .scaleMult(1.15f)
+            .parent(buttonMenu);
+    }
 
     return true;
 }
@@ -1,9 +1,13 @@
 #include "room_settings_popup.hpp"
 
 #include <managers/error_queues.hpp>
+#include <managers/role.hpp>
 #include <data/packets/server/room.hpp>
 #include <data/packets/client/room.hpp>
 #include <net/manager.hpp>
+#include <ui/general/ask_input_popup.hpp>
+#include <util/format.hpp>
+#include <util/misc.hpp>
 #include <util/ui.hpp>
 
 using namespace geode::prelude;
@@ -12,7 +16,10 @@ enum {
     TAG_COLLISION = 454,
     TAG_TWO_PLAYER,
     TAG_PUBLIC_INVITES,
-    TAG_INVITE_ONLY
+    TAG_INVITE_ONLY,
+    TAG_HIDE_EDITOR_LEVELS,
+    TAG_HIDE_UNLISTED_LEVELS,
+    TAG_CHAT_MUTED
 };
 
 #define MAKE_SETTING(name, desc, tag, storage) \
@@ -29,6 +36,9 @@ bool RoomSettingsPopup::setup() {
     MAKE_SETTING("Private Room", "While enabled, the room can not be found on the public room listing and can only be joined by entering the room ID", TAG_INVITE_ONLY, cellInviteOnly);
     MAKE_SETTING("Open Invites", "While enabled, all players in the room can invite players instead of just the room owner", TAG_PUBLIC_INVITES, cellPublicInvites);
     MAKE_SETTING("Collision", "While enabled, players can collide with each other", TAG_COLLISION, cellCollision);
+    MAKE_SETTING("Hide Editor Levels", "While enabled, nobody in the room is shown as being on an editor level, even if they chose to share it", TAG_HIDE_EDITOR_LEVELS, cellHideEditorLevels);
+    MAKE_SETTING("Hide Unlisted Levels", "While enabled, nobody in the room is shown as being on an unlisted level", TAG_HIDE_UNLISTED_LEVELS, cellHideUnlistedLevels);
+    MAKE_SETTING("Mute Chat", "While enabled, only the room owner and the room moderators can send chat messages", TAG_CHAT_MUTED, cellChatMuted);
 
 #ifdef GLOBED_DEBUG
     MAKE_SETTING("2-Player Mode", "While enabled, players can link with another player to play a 2-player enabled level together", TAG_TWO_PLAYER, cellTwoPlayer);
@@ -40,12 +50,69 @@ bool RoomSettingsPopup::setup() {
         .parent(m_mainLayer)
         .collect();
 
-    listlayer->setPosition(popupLayout.center - listlayer->getContentSize() / 2);
+    listlayer->setPosition(popupLayout.center - listlayer->getContentSize() / 2 + CCPoint{0.f, 12.f});
+
+    // chat settings
+    Build<CCMenu>::create()
+        .layout(RowLayout::create()->setGap(5.f))
+        .contentSize(m_size.width - 20.f, 30.f)
+        .pos(popupLayout.centerBottom + CCPoint{0.f, 18.f})
+        .parent(m_mainLayer)
+        .child(
+            Build<ButtonSprite>::create("Chat filter", "bigFont.fnt", "GJ_button_04.png", 0.5f)
+                .store(chatFilterSprite)
+                .intoMenuItem([this](auto) {
+                    this->onChatFilterClicked();
+                })
+                .collect()
+        )
+        .child(
+            Build<ButtonSprite>::create("Slow mode", "bigFont.fnt", "GJ_button_04.png", 0.5f)
+                .store(slowModeSprite)
+                .intoMenuItem([this](auto) {
+                    this->onSlowModeClicked();
+                })
+                .collect()
+        )
+        .child(
+            Build<ButtonSprite>::create("Level", "bigFont.fnt", "GJ_button_04.png", 0.5f)
+                .store(pinnedLevelSprite)
+                .intoMenuItem([this](auto) {
+                    this->onPinnedLevelClicked();
+                })
+                .collect()
+        )
+        .child(
+            Build<ButtonSprite>::create("Roles", "bigFont.fnt", "GJ_button_04.png", 0.5f)
+                .store(allowedRolesSprite)
+                .intoMenuItem([this](auto) {
+                    this->onAllowedRolesClicked();
+                })
+                .collect()
+        )
+        .child(
+            Build<ButtonSprite>::create("Voice", "bigFont.fnt", "GJ_button_04.png", 0.5f)
+                .store(voicePolicySprite)
+                .intoMenuItem([this](auto) {
+                    this->onVoicePolicyClicked();
+                })
+                .collect()
+        )
+        .child(
+            Build<ButtonSprite>::create("Tags", "bigFont.fnt", "GJ_button_04.png", 0.5f)
+                .intoMenuItem([this](auto) {
+                    this->onTagsClicked();
+                })
+                .collect()
+        )
+        .updateLayout();
 
     NetworkManager::get().addListener<RoomInfoPacket>(this, [this](auto packet) {
         log::debug("room configuration updated");
 
         RoomManager::get().setInfo(packet->info);
+        RoomManager::get().setModerators(packet->moderators);
+        RoomManager::get().setTags(packet->tags);
         this->currentSettings = packet->info.settings;
         this->updateCheckboxes();
     });
@@ -65,36 +132,223 @@ void RoomSettingsPopup::onSettingClicked(cocos2d::CCObject* sender) {
         case TAG_PUBLIC_INVITES: currentSettings.flags.publicInvites = enabled; break;
         case TAG_COLLISION: currentSettings.flags.collision = enabled; break;
         case TAG_TWO_PLAYER: currentSettings.flags.twoPlayerMode = enabled; break;
+        case TAG_HIDE_EDITOR_LEVELS: currentSettings.flags.hideEditorLevels = enabled; break;
+        case TAG_HIDE_UNLISTED_LEVELS: currentSettings.flags.hideUnlistedLevels = enabled; break;
+        case TAG_CHAT_MUTED: currentSettings.flags.chatMuted = enabled; break;
     }
 
     // if we are not the room owner, just revert the changes next frame
-    if (!RoomManager::get().isOwner()) {
-        ErrorQueues::get().warn("Not the room creator");
+    if (!RoomManager::get().canManage()) {
+        ErrorQueues::get().warn("Only the room owner and moderators can change this");
         Loader::get()->queueInMainThread([self = Ref(this)] {
             self->updateCheckboxes();
         });
     } else {
         // otherwise, actually update the settings
         log::debug("settings: {}", currentSettings.flags.isHidden, currentSettings.flags.publicInvites, currentSettings.flags.collision, currentSettings.flags.twoPlayerMode);
-        NetworkManager::get().send(UpdateRoomSettingsPacket::create(currentSettings));
+        this->sendSettings();
+    }
+}
+
+void RoomSettingsPopup::onChatFilterClicked() {
+    if (!RoomManager::get().canManage()) {
+        ErrorQueues::get().warn("Only the room owner and moderators can change this");
+        return;
+    }
+
+    // cycle through the options, the server will pick a stricter one if it doesn't allow this one
+    switch (currentSettings.chatFilter) {
+        case RoomChatFilter::ServerDefault: currentSettings.chatFilter = RoomChatFilter::Off; break;
+        case RoomChatFilter::Off: currentSettings.chatFilter = RoomChatFilter::Standard; break;
+        case RoomChatFilter::Standard: currentSettings.chatFilter = RoomChatFilter::Strict; break;
+        case RoomChatFilter::Strict: currentSettings.chatFilter = RoomChatFilter::ServerDefault; break;
+    }
+
+    this->updateChatButtons();
+    this->sendSettings();
+}
+
+void RoomSettingsPopup::onVoicePolicyClicked() {
+    if (!RoomManager::get().canManage()) {
+        ErrorQueues::get().warn("Only the room owner and moderators can change this");
+        return;
+    }
+
+    switch (currentSettings.voicePolicy) {
+        case RoomVoicePolicy::Everyone: currentSettings.voicePolicy = RoomVoicePolicy::Proximity; break;
+        case RoomVoicePolicy::Proximity: currentSettings.voicePolicy = RoomVoicePolicy::ManagersOnly; break;
+        case RoomVoicePolicy::ManagersOnly: currentSettings.voicePolicy = RoomVoicePolicy::Disabled; break;
+        case RoomVoicePolicy::Disabled: currentSettings.voicePolicy = RoomVoicePolicy::Everyone; break;
+    }
+
+    this->updateChatButtons();
+    this->sendSettings();
+}
+
+void RoomSettingsPopup::onSlowModeClicked() {
+    if (!RoomManager::get().canManage()) {
+        ErrorQueues::get().warn("Only the room owner and moderators can change this");
+        return;
+    }
+
+    AskInputPopup::create("Slow mode", [self = Ref(this)](auto input) {
+        // the server caps this at its own limit
+        self->currentSettings.slowMode = util::format::parse<uint32_t>(input).value_or(0);
+        self->updateChatButtons();
+        self->sendSettings();
+    }, 4, "Seconds (0 to disable)", util::misc::STRING_DIGITS, 1.f)->show();
+}
+
+void RoomSettingsPopup::onPinnedLevelClicked() {
+    if (!RoomManager::get().canManage()) {
+        ErrorQueues::get().warn("Only the room owner and moderators can change this");
+        return;
+    }
+
+    AskInputPopup::create("Pinned level", [self = Ref(this)](auto input) {
+        // players in the room can only join this level, 0 lets them join any level
+        self->currentSettings.pinnedLevel = util::format::parse<LevelId>(input).value_or(0);
+        self->updateChatButtons();
+        self->sendSettings();
+    }, 10, "Level ID (0 for any level)", util::misc::STRING_DIGITS, 1.f)->show();
+}
+
+void RoomSettingsPopup::onAllowedRolesClicked() {
+    if (!RoomManager::get().canManage()) {
+        ErrorQueues::get().warn("Only the room owner and moderators can change this");
+        return;
+    }
+
+    AskInputPopup::create("Allowed roles", [self = Ref(this)](auto input) {
+        std::vector<std::string> roles;
+        for (auto part : util::format::split(input, ",")) {
+            auto role = util::format::trim(part);
+            if (!role.empty()) roles.push_back(std::move(role));
+        }
+
+        // only players with one of these roles can join, an empty list lets anyone join
+        auto mask = RoleManager::get().getRoleMask(roles);
+        if (mask == 0 && !roles.empty()) {
+            ErrorQueues::get().warn("None of these roles exist on this server");
+            return;
+        }
+
+        self->currentSettings.allowedRoles = mask;
+        self->updateChatButtons();
+        self->sendSettings();
+    }, 64, "Role IDs, separated by commas (empty for anyone)", util::misc::STRING_PRINTABLE_INPUT, 1.f)->show();
+}
+
+void RoomSettingsPopup::onTagsClicked() {
+    if (!RoomManager::get().isOwner()) {
+        ErrorQueues::get().warn("Only the room owner can change this");
+        return;
+    }
+
+    AskInputPopup::create("Room tags", [](auto input) {
+        std::vector<RoomTag> tags;
+        for (auto part : util::format::split(input, ",")) {
+            auto entry = util::format::trim(part);
+            if (entry.empty()) continue;
+
+            // an optional hex color can follow the name, e.g. "host #ff0000"
+            RoomTag tag { .name = entry, .color = {255, 255, 255} };
+            size_t colorPos = entry.rfind(" #");
+            if (colorPos != std::string::npos) {
+                auto color = util::format::parseColor(std::string_view(entry).substr(colorPos + 1));
+                if (color.isErr()) {
+                    ErrorQueues::get().warn(fmt::format("Invalid color for the tag \"{}\"", entry));
+                    return;
+                }
+
+                tag.color = color.unwrap();
+                tag.name = util::format::trim(std::string_view(entry).substr(0, colorPos));
+            }
+
+            if (tag.name.empty() || tag.name.size() > SetRoomTagsPacket::MAX_NAME_LENGTH) {
+                ErrorQueues::get().warn(fmt::format("Tag names can be at most {} characters long", SetRoomTagsPacket::MAX_NAME_LENGTH));
+                return;
+            }
+
+            tags.push_back(std::move(tag));
+        }
+
+        if (tags.size() > SetRoomTagsPacket::MAX_TAGS) {
+            ErrorQueues::get().warn(fmt::format("A room can have at most {} tags", SetRoomTagsPacket::MAX_TAGS));
+            return;
+        }
+
+        // replaces all the tags, players whose tag is gone lose it
+        NetworkManager::get().send(SetRoomTagsPacket::create(std::move(tags)));
+    }, 200, "Tags, separated by commas, e.g. host #ff0000, racer #00ff00 (empty for none)", util::misc::STRING_PRINTABLE_INPUT, 0.4f)->show();
+}
+
+void RoomSettingsPopup::updateChatButtons() {
+    const char* filter = "Default";
+    switch (currentSettings.chatFilter) {
+        case RoomChatFilter::ServerDefault: filter = "Default"; break;
+        case RoomChatFilter::Off: filter = "Off"; break;
+        case RoomChatFilter::Standard: filter = "Standard"; break;
+        case RoomChatFilter::Strict: filter = "Strict"; break;
+    }
+
+    chatFilterSprite->setString(fmt::format("Filter: {}", filter).c_str());
+
+    const char* voice = "Everyone";
+    switch (currentSettings.voicePolicy) {
+        case RoomVoicePolicy::Everyone: voice = "Everyone"; break;
+        case RoomVoicePolicy::ManagersOnly: voice = "Mods"; break;
+        case RoomVoicePolicy::Disabled: voice = "Off"; break;
+        case RoomVoicePolicy::Proximity: voice = "Proximity"; break;
     }
+
+    voicePolicySprite->setString(fmt::format("Voice: {}", voice).c_str());
+
+    if (currentSettings.slowMode == 0) {
+        slowModeSprite->setString("Slow mode: off");
+    } else {
+        slowModeSprite->setString(fmt::format("Slow mode: {}s", currentSettings.slowMode).c_str());
+    }
+
+    if (currentSettings.pinnedLevel == 0) {
+        pinnedLevelSprite->setString("Level: any");
+    } else {
+        pinnedLevelSprite->setString(fmt::format("Level: {}", currentSettings.pinnedLevel).c_str());
+    }
+
+    if (currentSettings.allowedRoles == 0) {
+        allowedRolesSprite->setString("Roles: any");
+    } else {
+        allowedRolesSprite->setString(fmt::format("Roles: {}", RoleManager::get().getRoleIds(currentSettings.allowedRoles).size()).c_str());
+    }
+}
+
+void RoomSettingsPopup::sendSettings() {
+    NetworkManager::get().send(UpdateRoomSettingsPacket::create(currentSettings));
 }
 
 void RoomSettingsPopup::updateCheckboxes() {
     cellInviteOnly->setToggled(currentSettings.flags.isHidden);
     cellPublicInvites->setToggled(currentSettings.flags.publicInvites);
     cellCollision->setToggled(currentSettings.flags.collision);
+    cellHideEditorLevels->setToggled(currentSettings.flags.hideEditorLevels);
+    cellHideUnlistedLevels->setToggled(currentSettings.flags.hideUnlistedLevels);
+    cellChatMuted->setToggled(currentSettings.flags.chatMuted);
 #ifdef GLOBED_DEBUG
     cellTwoPlayer->setToggled(currentSettings.flags.twoPlayerMode);
 #endif
 
-    this->enableCheckboxes(RoomManager::get().isOwner());
+    this->updateChatButtons();
+    this->enableCheckboxes(RoomManager::get().canManage());
 }
 
 void RoomSettingsPopup::enableCheckboxes(bool enabled) {
     cellInviteOnly->setEnabled(enabled);
     cellPublicInvites->setEnabled(enabled);
     cellCollision->setEnabled(enabled);
+    cellHideEditorLevels->setEnabled(enabled);
+    cellHideUnlistedLevels->setEnabled(enabled);
+    cellChatMuted->setEnabled(enabled);
 
 #ifdef GLOBED_DEBUG
     cellTwoPlayer->setEnabled(enabled);
@@ -8,7 +8,7 @@ class RoomSettingCell;
 class RoomSettingsPopup : public geode::Popup<> {
 public:
     static constexpr float POPUP_WIDTH = 250.f;
-    static constexpr float POPUP_HEIGHT = 170.f;
+    static constexpr float POPUP_HEIGHT = 200.f;
     static constexpr float LIST_WIDTH = 340.f;
     static constexpr float LIST_HEIGHT = 220.f;
 
@@ -19,6 +19,15 @@ public:
 
     void enableCheckboxes(bool enabled);
 
+    void onChatFilterClicked();
+    void onSlowModeClicked();
+    void onPinnedLevelClicked();
+    void onAllowedRolesClicked();
+    void onVoicePolicyClicked();
+    void onTagsClicked();
+    void updateChatButtons();
+    void sendSettings();
+
 private:
     friend class RoomSettingCell;
 
@@ -27,9 +36,14 @@ private:
         *cellInviteOnly,
         *cellCollision,
         *cellTwoPlayer,
-        *cellPublicInvites
+        *cellPublicInvites,
+        *cellHideEditorLevels,
+        *cellHideUnlistedLevels,
+        *cellChatMuted
         ;
 
+    ButtonSprite *chatFilterSprite, *slowModeSprite, *pinnedLevelSprite, *allowedRolesSprite, *voicePolicySprite;
+
     bool setup() override;
 };
 
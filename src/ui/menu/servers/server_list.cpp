@@ -1,6 +1,7 @@
 #include "server_list.hpp"
 
 #include "server_list_cell.hpp"
+#include <algorithm>
 #include <net/manager.hpp>
 #include <managers/game_server.hpp>
 #include <util/ui.hpp>
@@ -68,8 +69,19 @@ CCArray* GlobedServerList::createServerList() {
 
     auto activeServer = gsm.getActiveId();
 
-    for (const auto& [serverId, server] : gsm.getAllServers()) {
-        bool active = authenticated && serverId == activeServer;
+    std::vector<GameServer> servers;
+    for (auto& [_, server] : gsm.getAllServers()) {
+        servers.push_back(std::move(server));
+    }
+
+    // show the closest servers first, servers with an unknown distance go last
+    std::stable_sort(servers.begin(), servers.end(), [](const GameServer& a, const GameServer& b) {
+        auto key = [](const GameServer& gs) { return gs.distanceClass == 0 ? UINT8_MAX : gs.distanceClass; };
+        return key(a) < key(b);
+    });
+
+    for (const auto& server : servers) {
+        bool active = authenticated && server.id == activeServer;
         auto cell = ServerListCell::create(server, active);
         ret->addObject(cell);
     }
@@ -1,5 +1,7 @@
 #include "advanced_settings_popup.hpp"
 
+#include "sessions_popup.hpp"
+
 #include <managers/account.hpp>
 #include <managers/settings.hpp>
 #include <net/manager.hpp>
@@ -68,6 +70,20 @@ bool AdvancedSettingsPopup::setup() {
         .pos(rlayout.center - CCPoint{0.f, 60.f})
         .parent(menu);
 
+    // see where else the account is logged in, and log out other devices
+    Build<ButtonSprite>::create("Sessions", "bigFont.fnt", "GJ_button_01.png", 0.75f)
+        .scale(0.8f)
+        .intoMenuItem([this](auto) {
+            if (!NetworkManager::get().established()) {
+                Notification::create("Connect to a server first", NotificationIcon::Error)->show();
+                return;
+            }
+
+            SessionsPopup::create()->show();
+        })
+        .pos(rlayout.center - CCPoint{0.f, 90.f})
+        .parent(menu);
+
     auto* thing = Build(CCMenuItemToggler::createWithStandardSprites(this, menu_selector(AdvancedSettingsPopup::onPacketLog), 0.7f))
         .parent(menu)
         .collect();
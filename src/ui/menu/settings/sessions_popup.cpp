@@ -0,0 +1,128 @@
+#include "sessions_popup.hpp"
+
+#include <data/packets/client/general.hpp>
+#include <net/manager.hpp>
+#include <util/ui.hpp>
+#include <util/format.hpp>
+
+using namespace geode::prelude;
+
+static const char* platformName(ClientPlatform platform) {
+    switch (platform) {
+        case ClientPlatform::Windows: return "Windows";
+        case ClientPlatform::Mac: return "Mac";
+        case ClientPlatform::Android: return "Android";
+        case ClientPlatform::Ios: return "iOS";
+        default: return "Unknown";
+    }
+}
+
+bool SessionsPopup::setup() {
+    this->setTitle("Sessions");
+
+    auto* listLayer = Build<GJCommentListLayer>::create(nullptr, "", util::ui::BG_COLOR_BROWN, LIST_WIDTH, LIST_HEIGHT, false)
+        .pos((m_mainLayer->getScaledContentSize().width - LIST_WIDTH) / 2, 25.f)
+        .parent(m_mainLayer)
+        .collect();
+
+    Build(ScrollLayer::create({LIST_WIDTH, LIST_HEIGHT}))
+        .parent(listLayer)
+        .store(scrollLayer);
+
+    scrollLayer->m_contentLayer->setLayout(
+        ColumnLayout::create()
+            ->setGap(0.f)
+            ->setAxisReverse(true)
+            ->setAxisAlignment(AxisAlignment::End)
+            ->setAutoScale(false)
+    );
+
+    Build<CCSprite>::createSpriteName("GJ_updateBtn_001.png")
+        .scale(0.9f)
+        .intoMenuItem([this](auto) {
+            this->refresh();
+        })
+        .pos(m_size.width / 2.f - 3.f, -m_size.height / 2.f + 3.f)
+        .intoNewParent(CCMenu::create())
+        .parent(m_mainLayer);
+
+    NetworkManager::get().addListener<SessionListPacket>(this, [this](auto packet) {
+        this->showSessions(*packet);
+    });
+
+    this->refresh();
+
+    return true;
+}
+
+void SessionsPopup::refresh() {
+    // the server answers at most once every few seconds
+    NetworkManager::get().send(RequestSessionListPacket::create());
+}
+
+void SessionsPopup::showSessions(const SessionListPacket& packet) {
+    scrollLayer->m_contentLayer->removeAllChildren();
+
+    auto now = util::time::asSeconds(util::time::systemNow().time_since_epoch());
+
+    for (const auto& session : packet.sessions) {
+        auto* row = Build<CCMenu>::create()
+            .contentSize(LIST_WIDTH, ROW_HEIGHT)
+            .parent(scrollLayer->m_contentLayer)
+            .collect();
+
+        bool isCurrent = session.sessionId == packet.current;
+
+        std::string state = isCurrent ? std::string("this device")
+            : session.isActive() ? std::string("online")
+            : fmt::format("logged out {} ago", util::format::duration(util::time::seconds(std::max<int64_t>(now - session.logoutTime, 0))));
+
+        Build<CCLabelBMFont>::create(fmt::format("{} ({})", platformName(session.platform), state).c_str(), "bigFont.fnt")
+            .limitLabelWidth(LIST_WIDTH - 50.f, 0.35f, 0.1f)
+            .anchorPoint(0.f, 0.5f)
+            .pos(8.f, ROW_HEIGHT * 0.7f)
+            .parent(row);
+
+        Build<CCLabelBMFont>::create(
+            fmt::format(
+                "from {}, logged in {} ago",
+                session.ipPrefix,
+                util::format::duration(util::time::seconds(std::max<int64_t>(now - session.loginTime, 0)))
+            ).c_str(),
+            "chatFont.fnt"
+        )
+            .limitLabelWidth(LIST_WIDTH - 50.f, 0.6f, 0.1f)
+            .anchorPoint(0.f, 0.5f)
+            .pos(8.f, ROW_HEIGHT * 0.3f)
+            .parent(row);
+
+        // to log out of this device, the player can just disconnect
+        if (!isCurrent && session.isActive()) {
+            Build<CCSprite>::createSpriteName("GJ_deleteIcon_001.png")
+                .scale(0.7f)
+                .intoMenuItem([id = session.sessionId](auto btn) {
+                    NetworkManager::get().send(LogoutSessionPacket::create(id));
+                    btn->setEnabled(false);
+                    btn->setOpacity(90);
+                })
+                .pos(LIST_WIDTH - 20.f, ROW_HEIGHT / 2.f)
+                .parent(row);
+        }
+    }
+
+    scrollLayer->m_contentLayer->setContentHeight(std::max(LIST_HEIGHT, ROW_HEIGHT * packet.sessions.size()));
+    scrollLayer->m_contentLayer->updateLayout();
+
+    util::ui::scrollToTop(scrollLayer);
+}
+
+SessionsPopup* SessionsPopup::create() {
+    auto* ret = new SessionsPopup;
+    if (ret->init(POPUP_WIDTH, POPUP_HEIGHT)) {
+        ret->autorelease();
+        return ret;
+    }
+
+    delete ret;
+    return nullptr;
+}
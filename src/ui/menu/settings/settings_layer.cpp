@@ -3,6 +3,7 @@
 #include "setting_header_cell.hpp"
 #include "setting_cell.hpp"
 #include <managers/settings.hpp>
+#include <net/manager.hpp>
 #include <util/ui.hpp>
 #include <util/cocos.hpp>
 
@@ -142,6 +143,7 @@ void GlobedSettingsLayer::onTab(CCObject* sender) {
 }
 
 void GlobedSettingsLayer::keyBackClicked() {
+    NetworkManager::get().syncPreferences();
     util::ui::navigateBack();
 }
 
@@ -216,8 +218,13 @@ void GlobedSettingsLayer::createSettingsCells(int category) {
             registerSetting(cat, settings.globed.preloadAssets, "Preload assets", "Increases the loading times but prevents most lagspikes in a level.");
             registerSetting(cat, settings.globed.deferPreloadAssets, "Defer preloading", "Instead of making the loading screen longer, load assets only when you join a level while connected.");
             registerSetting(cat, settings.globed.invitesFrom, "Receive invites from", "Controls who can invite you into a room.", Type::InvitesFrom);
+            registerSetting(cat, settings.globed.shareEditorLevels, "Share editor levels", "Lets other players see which level you are on while you are in the editor. Room owners can hide editor levels regardless of this setting.");
+            registerSetting(cat, settings.globed.hideUnlistedLevels, "Hide unlisted levels", "Other players will not see which level you are on while you are playing an unlisted level.");
+            registerSetting(cat, settings.globed.showSpectatorCount, "Show spectators", "Shows a notification when the amount of players spectating you changes.");
+            registerSetting(cat, settings.globed.hideSpectatorCount, "Hide spectator count", "Other players will not see how many players are spectating you in the room player list.");
             registerSetting(cat, settings.globed.fragmentationLimit, "Packet limit", "Press the \"Test\" button to calibrate the maximum packet size. Should fix some of the issues with players not appearing in a level.", Type::PacketFragmentation);
             registerSetting(cat, settings.globed.tpsCap, "TPS cap", "Maximum amount of packets per second sent between the client and the server. Useful only for very silly things.");
+            registerSetting(cat, settings.advanced.shareUsage, "Share usage", "Sends the server anonymous info about which features you use (your platform, whether voice chat is enabled and which settings categories you changed), to help decide what to work on next.");
 #ifndef GEODE_IS_ANDROID
             registerSetting(cat, settings.globed.useDiscordRPC, "Discord RPC", "If you have the Discord Rich Presence standalone mod, this option will toggle a Globed-specific RPC on your profile.", Type::DiscordRPC);
 #endif
@@ -269,6 +276,8 @@ void GlobedSettingsLayer::createSettingsCells(int category) {
             registerSetting(cat, settings.players.hideNearby, "Hide nearby players", "Increases the transparency of players as they get closer to you, so that they don't obstruct your view.");
             registerSetting(cat, settings.players.statusIcons, "Status icons", "Show an icon above a player if they are paused, in practice mode, or currently speaking.");
             registerSetting(cat, settings.players.hidePracticePlayers, "Hide players in practice", "Hide players that are in practice mode.");
+            registerSetting(cat, settings.players.shareCompletions, "Share completions", "When you complete a level, the other players on it are told how many attempts it took you.");
+            registerSetting(cat, settings.players.showCompletions, "Show completions", "Show a notification when another player on the level completes it.");
         } break;
     }
 }
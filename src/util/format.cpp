@@ -5,6 +5,7 @@
 #include <iomanip>
 
 #include <managers/web.hpp>
+#include <data/types/misc.hpp>
 
 namespace util::format {
     std::string formatDateTime(const time::system_time_point& tp, bool ms) {
@@ -72,6 +73,39 @@ namespace util::format {
         }
     }
 
+    std::string serverError(ErrorCode code, std::string_view detail) {
+        if (!detail.empty()) {
+            return std::string(detail);
+        }
+
+        const char* message = "Unknown error";
+
+        switch (code) {
+            case ErrorCode::Internal: message = "Something went wrong on the server, please try again later"; break;
+            case ErrorCode::CentralUnavailable: message = "The central server could not be reached, please try again later"; break;
+            case ErrorCode::RateLimited: message = "Too many attempts, please wait a moment"; break;
+            case ErrorCode::Busy: message = "The server is busy, please try again in a moment"; break;
+            case ErrorCode::InvalidToken: message = "Your login token is invalid or has expired"; break;
+            case ErrorCode::AlreadyLoggedIn: message = "You are already logged in from a different place"; break;
+            case ErrorCode::InvalidRequest: message = "Invalid request"; break;
+            case ErrorCode::NoPermission: message = "You don't have permission to do this"; break;
+            case ErrorCode::Restricted: message = "This is not available to your account"; break;
+            case ErrorCode::NotFound: message = "Not found"; break;
+            case ErrorCode::WrongPassword: message = "The password is wrong"; break;
+            case ErrorCode::Full: message = "The room is full"; break;
+            case ErrorCode::LevelBlocked: message = "This level is blocked on the server"; break;
+            case ErrorCode::LimitReached: message = "The limit on the server has been reached"; break;
+            case ErrorCode::Unavailable: message = "This is not available on this server"; break;
+            case ErrorCode::Rejected: message = "The server rejected the request"; break;
+            case ErrorCode::TwoFactorRequired: message = "A two-factor authentication code is required"; break;
+            case ErrorCode::Conflict: message = "Someone else changed this first, please refresh"; break;
+            case ErrorCode::NameTaken: message = "Someone else is using this name on the server"; break;
+            default: break;
+        }
+
+        return message;
+    }
+
     std::string formatPlatformerTime(uint32_t ms) {
         auto dur = time::millis(ms);
         auto hours = time::as<time::hours>(dur).count() % 24;
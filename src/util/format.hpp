@@ -8,6 +8,7 @@
 #include <util/misc.hpp>
 
 struct WebRequestError;
+enum class ErrorCode : uint8_t;
 
 namespace util::format {
     // example: 2.123s, 69.123ms
@@ -47,6 +48,9 @@ namespace util::format {
     // format a web request error into a string in format like "code 404: Not found" or "code -1: empty response"
     std::string webError(const WebRequestError& error);
 
+    // describe an error code sent by the game server. the detail text sent with it is more specific, so it's used instead if there is one
+    std::string serverError(ErrorCode code, std::string_view detail);
+
     // format milliseconds into a string like 45.200, 1:20.300, 3:01:40.500
     std::string formatPlatformerTime(uint32_t ms);
 
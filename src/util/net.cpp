@@ -48,8 +48,8 @@ namespace util::net {
             Loader::get()->getVersion().toString()
         );
 #else
-        // no telemetry in release :(
-        return fmt::format("Globed {}", Mod::get()->getVersion().toString());
+        // no telemetry in release, only the platform so the server can tell how many players are on each one
+        return fmt::format("Globed {} ({})", Mod::get()->getVersion().toString(), GLOBED_PLATFORM_STRING_PLATFORM);
 #endif
     }
 